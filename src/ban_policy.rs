@@ -0,0 +1,79 @@
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::package_match::match_str;
+use crate::util::name_to_key;
+use crate::util::ResultDynError;
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Ban {
+    package: String,
+    reason: Option<String>,
+}
+
+/// A policy of forbidden packages, read from a TOML file of `[[ban]]` tables, each with a `package` name, which can use glob-like wildcards, and an optional `reason`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BanPolicy {
+    #[serde(default)]
+    ban: Vec<Ban>,
+}
+
+impl BanPolicy {
+    pub(crate) fn from_str(content: &str) -> ResultDynError<Self> {
+        let bp: BanPolicy = toml::from_str(content)?;
+        Ok(bp)
+    }
+
+    pub(crate) fn from_file(file_path: &Path) -> ResultDynError<Self> {
+        let content = fs::read_to_string(file_path)?;
+        Self::from_str(&content)
+    }
+
+    /// If the package key is banned, return the reason of the first matching ban, or a default reason if not provided.
+    pub(crate) fn get_reason(&self, key: &str) -> Option<String> {
+        self.ban
+            .iter()
+            .find(|b| match_str(&name_to_key(&b.package), key, true))
+            .map(|b| {
+                b.reason
+                    .clone()
+                    .unwrap_or_else(|| "banned by policy".to_string())
+            })
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ban_policy_a() {
+        let bp = BanPolicy::from_str(
+            "[[ban]]\npackage = \"pycrypto\"\nreason = \"Unmaintained; use pycryptodome\"\n\n[[ban]]\npackage = \"Acme_Legacy*\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            bp.get_reason("pycrypto"),
+            Some("Unmaintained; use pycryptodome".to_string())
+        );
+        assert_eq!(
+            bp.get_reason("acme_legacy_auth"),
+            Some("banned by policy".to_string())
+        );
+        assert_eq!(bp.get_reason("pycryptodome"), None);
+        assert_eq!(bp.get_reason("acme"), None);
+    }
+
+    #[test]
+    fn test_ban_policy_b() {
+        assert!(BanPolicy::from_str("[[ban]]\nreason = \"no package\"\n").is_err());
+        assert!(BanPolicy::from_str("deny = [\"pycrypto\"]\n").is_err());
+        assert_eq!(BanPolicy::from_str("").unwrap().get_reason("numpy"), None);
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt;
+
+use crate::dep_graph::DepGraph;
+use crate::dep_spec::DepSpec;
+use crate::package::Package;
+use crate::table::ColumnFormat;
+use crate::table::Rowable;
+use crate::table::RowableContext;
+use crate::table::Tableable;
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CheckExplain {
+    Missing,
+    Conflict,
+}
+
+impl fmt::Display for CheckExplain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            CheckExplain::Missing => "Missing", // required, not found
+            CheckExplain::Conflict => "Conflict", // required, found, not matched version
+        };
+        write!(f, "{}", value)
+    }
+}
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone)]
+pub(crate) struct CheckRecord {
+    package: Package,
+    dep_spec: DepSpec,
+    installed: Option<Package>,
+    explain: CheckExplain,
+}
+
+impl Rowable for CheckRecord {
+    fn to_rows(&self, _context: &RowableContext) -> Vec<Vec<String>> {
+        vec![vec![
+            self.package.to_string(),
+            self.dep_spec.to_string(),
+            self.installed
+                .as_ref()
+                .map(|p| p.to_string())
+                .unwrap_or_default(),
+            self.explain.to_string(),
+        ]]
+    }
+}
+
+//------------------------------------------------------------------------------
+/// A report of the Requires-Dist requirements of installed packages that are not satisfied by the environment, independent of any bound requirements. Requirements are either missing or in conflict with the version of the installed package.
+pub(crate) struct CheckReport {
+    records: Vec<CheckRecord>,
+}
+
+impl CheckReport {
+    pub(crate) fn from_dep_graph(dg: &DepGraph) -> Self {
+        let mut records = Vec::new();
+        for package in dg.get_packages() {
+            for edge in dg.get_edges(package) {
+                if edge.packages.is_empty() {
+                    records.push(CheckRecord {
+                        package: package.clone(),
+                        dep_spec: edge.dep_spec.clone(),
+                        installed: None,
+                        explain: CheckExplain::Missing,
+                    });
+                }
+                for installed in edge.packages.iter() {
+                    // as with installers, a local label (e.g. `+cu118`) does not conflict with a requirement without one
+                    if !edge.dep_spec.validate_version(&installed.version)
+                        && !edge
+                            .dep_spec
+                            .validate_version(&installed.version.to_public())
+                    {
+                        records.push(CheckRecord {
+                            package: package.clone(),
+                            dep_spec: edge.dep_spec.clone(),
+                            installed: Some(installed.clone()),
+                            explain: CheckExplain::Conflict,
+                        });
+                    }
+                }
+            }
+        }
+        CheckReport { records }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.records.len()
+    }
+}
+
+impl Tableable<CheckRecord> for CheckReport {
+    fn get_header(&self) -> Vec<ColumnFormat> {
+        vec![
+            ColumnFormat::new("Package".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Requirement".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Installed".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Explain".to_string(), false, "#666666".to_string()),
+        ]
+    }
+    fn get_records(&self) -> &Vec<CheckRecord> {
+        &self.records
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path_shared::PathShared;
+    use std::collections::HashMap;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_check_report_a() {
+        let dir = tempdir().unwrap();
+        let site = PathShared::from_path_buf(dir.path().to_path_buf());
+        let metadata = [
+            ("pandas", "2.2.3", "Requires-Dist: numpy>=1.26.0\nRequires-Dist: python-dateutil>=2.8.2\nRequires-Dist: pytz>=2020.1\nRequires-Dist: pyarrow>=10.0.1; extra == \"parquet\"\n"),
+            ("numpy", "2.1.2", ""),
+            ("pytz", "2024.2", ""),
+            ("requests", "2.31.0", "Requires-Dist: urllib3<2,>=1.21.1\n"),
+            ("urllib3", "2.2.3", ""),
+        ];
+        let mut package_to_sites = HashMap::new();
+        for (name, version, requires) in metadata {
+            let fp_di = dir.path().join(format!("{}-{}.dist-info", name, version));
+            fs::create_dir(&fp_di).unwrap();
+            fs::write(
+                fp_di.join("METADATA"),
+                format!("Name: {}\nVersion: {}\n{}", name, version, requires),
+            )
+            .unwrap();
+            let package = Package::from_name_version_durl(name, version, None).unwrap();
+            package_to_sites.insert(package, vec![site.clone()]);
+        }
+        let dg = DepGraph::from_package_to_sites(&package_to_sites, &HashMap::new());
+        let cr = CheckReport::from_dep_graph(&dg);
+
+        let fp = dir.path().join("report.txt");
+        cr.to_file(&fp, ',').unwrap();
+        let lines: Vec<String> = fs::read_to_string(&fp)
+            .unwrap()
+            .lines()
+            .map(|l| l.to_string())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Package,Requirement,Installed,Explain",
+                "pandas-2.2.3,python-dateutil>=2.8.2,,Missing",
+                "requests-2.31.0,\"urllib3<2,>=1.21.1\",urllib3-2.2.3,Conflict",
+            ]
+        );
+    }
+}
@@ -1,10 +1,20 @@
 use std::process;
 // use std::str::FromStr;
 
+use crate::validation_report::validation_digest_diff;
+use crate::validation_report::ValidationDigest;
+use crate::validation_report::ValidationExplain;
 use crate::validation_report::ValidationFlags;
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use std::env;
 use std::ffi::OsString;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
@@ -12,15 +22,39 @@ use std::sync::{
 };
 use std::thread;
 use std::time::Duration;
+use tracing::warn;
 
+use crate::ban_policy::BanPolicy;
+use crate::config::Config;
 use crate::dep_manifest::DepManifest;
+use crate::error::FetterError;
+use crate::license_policy::LicensePolicy;
+use crate::logging::init_logging;
+use crate::logging::LogFormat;
+use crate::package_match::PackageFilter;
+use crate::sbom::SbomFormat;
 use crate::scan_fs::Anchor;
 use crate::scan_fs::ScanFS;
+use crate::scan_fs::ScanFlags;
 use crate::spin::spin;
+use crate::stray_report::StrayReport;
+use crate::table::set_color_choice;
+use crate::table::to_table_format;
+use crate::table::ColorChoice;
+use crate::table::Rowable;
+use crate::table::TableFormat;
 use crate::table::Tableable;
+use crate::table_view::TableView;
+use crate::unpack_report::UnpackReport;
 use crate::ureq_client::UreqClientLive;
+use crate::util::path_cache_clear;
 use crate::util::path_normalize;
+use crate::util::path_wait_for_change;
+use crate::util::set_exe_timeout;
 use crate::util::DURATION_0;
+use crate::util::EXE_TIMEOUT_DEFAULT;
+use crate::waiver::days_today;
+use crate::waiver::Waivers;
 
 //------------------------------------------------------------------------------
 // utility enums
@@ -30,6 +64,8 @@ enum CliAnchor {
     Lower,
     Upper,
     Both,
+    Exact,
+    Compatible,
 }
 impl From<CliAnchor> for Anchor {
     fn from(cli_anchor: CliAnchor) -> Self {
@@ -37,6 +73,71 @@ impl From<CliAnchor> for Anchor {
             CliAnchor::Lower => Anchor::Lower,
             CliAnchor::Upper => Anchor::Upper,
             CliAnchor::Both => Anchor::Both,
+            CliAnchor::Exact => Anchor::Exact,
+            CliAnchor::Compatible => Anchor::Compatible,
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum CliSbomFormat {
+    Cyclonedx,
+    Spdx,
+}
+impl From<CliSbomFormat> for SbomFormat {
+    fn from(cli_sbom_format: CliSbomFormat) -> Self {
+        match cli_sbom_format {
+            CliSbomFormat::Cyclonedx => SbomFormat::CycloneDX,
+            CliSbomFormat::Spdx => SbomFormat::Spdx,
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum CliColor {
+    Auto,
+    Always,
+    Never,
+}
+impl From<CliColor> for ColorChoice {
+    fn from(cli_color: CliColor) -> Self {
+        match cli_color {
+            CliColor::Auto => ColorChoice::Auto,
+            CliColor::Always => ColorChoice::Always,
+            CliColor::Never => ColorChoice::Never,
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum CliLogFormat {
+    Text,
+    Json,
+}
+impl From<CliLogFormat> for LogFormat {
+    fn from(cli_log_format: CliLogFormat) -> Self {
+        match cli_log_format {
+            CliLogFormat::Text => LogFormat::Text,
+            CliLogFormat::Json => LogFormat::Json,
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum CliFormat {
+    Delimited,
+    Json,
+    Markdown,
+    Html,
+}
+
+impl CliFormat {
+    fn to_table_format(self, delimiter: char) -> TableFormat {
+        match self {
+            CliFormat::Delimited => TableFormat::Delimited(delimiter),
+            CliFormat::Json => TableFormat::Json,
+            CliFormat::Markdown => TableFormat::Markdown,
+            CliFormat::Html => TableFormat::Html,
         }
     }
 }
@@ -50,32 +151,123 @@ const AFTER_HELP: &str = "\
 Examples:
   fetter scan
   fetter scan write -o /tmp/pkgscan.txt --delimiter '|'
+  fetter -o scan.md scan
+  fetter scan --columns name,version,site --sort version:desc
+  fetter scan --include 'numpy*' --exclude 'internal-*'
+  fetter validate --bound requirements.txt --site /opt/venv/lib/python3.12/site-packages
+  fetter -o report.json validate --bound requirements.txt
+  fetter validate --bound requirements.txt write -o report.html --format html
+  fetter validate --bound requirements.txt junit -o report.xml
+  fetter validate --bound requirements.txt sarif -o fetter.sarif
+  fetter validate --bound requirements.lock --github-annotations
+  fetter validate --bound requirements.txt --ignore V002,inventory
+  fetter validate --bound requirements.txt --summary
+  fetter audit sarif --bound requirements.txt -o fetter-audit.sarif
 
   fetter search --pattern pip*
 
   fetter count
+  fetter count --detail
+
+  fetter interpreters
+  fetter --root /mnt/image interpreters json
+  fetter check
+  fetter tree
+  fetter tree requests --depth 1
+  fetter map cv2
+  fetter map --package numpy*
+  fetter map --bound requirements.txt
+  fetter diff --exe python3 --exe /opt/venv/bin/python
+  fetter diff --snapshot env.json --exe python3
+
+  fetter sbom write -o /tmp/sbom.json
+  fetter sbom --format spdx write -o /tmp/sbom.spdx.json
 
   fetter --exe python3 derive -a lower write -o /tmp/bound_requirements.txt
+  fetter derive --out requirements.txt
+  fetter derive -a compatible --out requirements.txt
+  fetter derive --hashes --out requirements.lock
+  fetter --exe pyenv scan
+  fetter --exe python3.11 --exe python3.12 scan
+  fetter --no-user-site validate --bound requirements.txt
+  fetter --exe '/opt/venvs/*/bin/python' validate --bound requirements.txt
+  fetter --exe dist/app.pyz validate --bound requirements.txt
+  fetter --scan-root /opt/venvs validate --bound /tmp/bound_requirements.txt
+  fetter -vv --log-format json scan
+  fetter --exe-timeout 30 --scan-root /opt/venvs scan
+  fetter --root /mnt/image audit
+  fetter --static --scan-root /mnt/share/venvs scan
+  fetter --no-cache validate --bound /tmp/bound_requirements.txt
+  fetter snapshot --out env.json
+  fetter validate --bound requirements.txt --from-snapshot env.json
+  fetter --color never scan
+  fetter cache clear
+  fetter completions zsh > ~/.zfunc/_fetter
+  fetter man -o ~/.local/share/man/man1
+  fetter watch --bound requirements.lock
 
   fetter validate --bound /tmp/bound_requirements.txt
+  fetter validate --bound pyproject.toml --group test
+  fetter validate --bound poetry.lock --group dev
+  fetter validate --bound Pipfile.lock --group develop
+  fetter validate --bound uv.lock
+  fetter validate --superset --bound environment.yml
   fetter -e python3 validate --bound /tmp/bound_requirements.txt
   fetter -e python3 validate --superset --bound git@github.com:fetter-io/bound-test.git
+  cat requirements.txt | fetter validate --bound -
+  ssh build-host pip freeze | fetter validate --bound -
+  fetter validate --bound base.txt --bound prod-extra.txt
+  fetter validate --bound requirements.lock --exact
+  fetter --no-config validate --bound requirements.txt
+  fetter validate --bound https://example.com/requirements.lock
+  fetter --bound-header \"Authorization: Bearer $TOKEN\" validate --bound https://example.com/uv.lock
+  fetter validate --bound requirements.txt --resolve-revisions
+  fetter validate --bound requirements.txt --no-editable
+  fetter validate --superset --bound requirements.txt --orphans
+  fetter validate --bound requirements.txt --ignore-local
+  fetter validate --bound requirements.txt --pre
+  fetter validate --bound requirements.txt --license-policy license_policy.toml
+  fetter validate --bound requirements.txt --waivers .fetter-ignore
+  fetter validate --superset --bound requirements.txt --ban-policy ban_policy.toml
+  fetter validate --bound requirements.txt --requires-python
+  fetter validate --bound requirements.txt --requires-python --wheel-tags
+  fetter --user-site validate --superset --bound requirements.txt --shadowed
+  fetter validate --bound requirements.txt --require-installer pip,uv
 
   fetter audit
   fetter -e python3 -e /usr/bin/python audit write -o /tmp/audit.txt  -d '|'
+  fetter audit --yanked
+
+  fetter outdated
+  fetter outdated -p numpy* --index-cache-duration 86400
+
+  fetter verify
+  fetter verify -p requests json
+  fetter scripts
+  fetter scripts -p black display
 
   fetter -e python3 unpack-count
   fetter unpack-count -p pip*
+  fetter files requests
+  fetter files numpy --site ~/.venv/lib/python3.12/site-packages json
+  fetter size
+  fetter size --by-site
+  fetter size -p torch* write -o /tmp/size.txt
+  fetter strays
+  fetter strays --site ~/.venv/lib/python3.12/site-packages purge
+  fetter doctor
+  fetter --scan-root ~/projects doctor json
 
   fetter -e /usr/bin/python purge-pattern -p numpy*
 
-  fetter purge-invalid --bound /tmp/bound_requirements.txt
+  fetter purge-invalid --bound /tmp/bound_requirements.txt --dry-run
+  fetter purge-invalid --bound /tmp/bound_requirements.txt --yes
 ";
 
 #[derive(clap::Parser)]
 #[command(version, about, long_about = TITLE, after_help = AFTER_HELP)]
 struct Cli {
-    /// Zero or more executable paths to derive site package locations. If not provided, all discoverable executables will be used.
+    /// Zero or more executable paths to derive site package locations. If not provided, all discoverable executables will be used. Provide `pyenv` to use all installed pyenv versions. Paths can have `*` or `?` wildcards (e.g. `'/opt/venvs/*/bin/python'`) to use all matching executables. If more than one executable is used, the executables that use each site are shown in an additional column of scan, search, and validate reports. A zipapp (`.pyz`) or zipped egg can be provided in place of an executable; it is not run, and the packages in its archive are scanned.
     #[arg(
         short,
         long,
@@ -85,26 +277,96 @@ struct Cli {
     )]
     exe: Vec<PathBuf>,
 
+    /// Zero or more directories to recursively search for virtual environments; all executables found will be used to derive site package locations.
+    #[arg(long, value_name = "DIR", required = false, conflicts_with = "exe")]
+    scan_root: Vec<PathBuf>,
+
+    /// Path to the root directory of a mounted or extracted filesystem, such as a container image, in which to discover site packages by layout, without running any executables. Scans of a root are not cached.
+    #[arg(long, value_name = "DIR", required = false, conflicts_with_all = ["exe", "scan_root"])]
+    root: Option<PathBuf>,
+
+    /// Derive site packages from `pyvenv.cfg` files and the layout conventions of `sysconfig`, without running any executables, as needed for untrusted or non-executable environments. Executables are found without running them; pyenv shims are excluded. Reports that must run executables are not available, and static scans are not cached.
+    #[arg(long = "static", required = false, conflicts_with = "root")]
+    static_sites: bool,
+
     /// Create or use a cache that expires after the provided number of seconds. A duration of zero will disable caching.
     #[arg(long, short, required = false, default_value = "40")]
     cache_duration: u64,
 
+    /// Disable reading and writing the scan cache and the site cache; equivalent to a cache duration of zero.
+    #[arg(long)]
+    no_cache: bool,
+
     /// Disable logging and terminal animation.
     #[arg(long, short)]
     quiet: bool,
 
+    /// Increase logging verbosity: `-v` logs timed spans around executable search, site scanning, and validation; `-vv` logs everything. The FETTER_LOG environment variable, if set to a filter directive (e.g. `fetter=debug`), takes precedence.
+    #[arg(short, long, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+
+    /// Select the format of log records written to stderr.
+    #[arg(long, value_enum, default_value = "text")]
+    log_format: CliLogFormat,
+
+    /// The number of seconds each Python executable is given to report its sites and other metadata; an executable that takes longer is stopped and reported as failed. Zero waits indefinitely.
+    #[arg(long, value_name = "SECONDS", default_value_t = EXE_TIMEOUT_DEFAULT)]
+    exe_timeout: u64,
+
+    /// Select when to use color in terminal output. With `auto`, color is not used if the NO_COLOR environment variable is set, is used if the CLICOLOR_FORCE environment variable is set, and is otherwise used only when writing to a terminal.
+    #[arg(long, value_enum, default_value = "auto")]
+    color: CliColor,
+
     /// Force inclusion of the user site-packages, even if it is not activated. If not set, user site packages will only be included if the interpreter has been configured to use it.
     #[arg(long, required = false)]
     user_site: bool,
 
+    /// Exclude the user site-packages (e.g. `~/.local/lib/python3.12/site-packages`), and packages only installed there, even if the interpreter is configured to use it.
+    #[arg(long, required = false, conflicts_with = "user_site")]
+    no_user_site: bool,
+
+    /// Do not read default options from `fetter.toml`, or `[tool.fetter]` of pyproject.toml, in the current directory.
+    #[arg(long, required = false)]
+    no_config: bool,
+
+    /// Write the report of the command to a file instead of displaying it, in a format inferred from its extension: `.csv`, `.tsv`, `.json`, `.md`, or `.html`. The SBOM and derive commands write their own formats.
+    #[arg(short, long, value_name = "FILE", required = false)]
+    output: Option<PathBuf>,
+
+    /// Zero or more headers, as `NAME: VALUE`, to send when reading a bound from an HTTPS URL, such as an `Authorization` header for a private host. If not provided, a header is read from the FETTER_BOUND_HEADER environment variable, if set.
+    #[arg(long, value_name = "HEADER", required = false)]
+    bound_header: Vec<String>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
+// the enum is parsed once per run, so the size of its largest variant is not a concern
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Commands {
     /// Scan environment to report on installed packages.
     Scan {
+        /// Only report packages whose names, or names and versions, match these comma-separated glob-like patterns (e.g. `numpy*`).
+        #[arg(long, value_name = "PATTERNS", value_delimiter = ',')]
+        include: Vec<String>,
+
+        /// Do not report packages whose names, or names and versions, match these comma-separated glob-like patterns (e.g. `internal-*`).
+        #[arg(long, value_name = "PATTERNS", value_delimiter = ',')]
+        exclude: Vec<String>,
+
+        /// Only report packages installed in this site.
+        #[arg(long, value_name = "DIR")]
+        site: Option<PathBuf>,
+
+        /// Display or write only these comma-separated columns, in order, selected by header (e.g. `package,site` or `user-site`); `name` and `version` select the name and version of each package.
+        #[arg(long, value_name = "COLUMNS", value_delimiter = ',')]
+        columns: Option<Vec<String>>,
+
+        /// Sort records by a column, as `COLUMN`, `COLUMN:asc`, or `COLUMN:desc` (e.g. `version:desc`); versions are compared as versions.
+        #[arg(long, value_name = "COLUMN")]
+        sort: Option<String>,
+
         #[command(subcommand)]
         subcommands: Option<ScanSubcommand>,
     },
@@ -120,30 +382,138 @@ enum Commands {
         #[command(subcommand)]
         subcommands: Option<SearchSubcommand>,
     },
+    /// Report the version, implementation, build, and prefix of each discovered executable.
+    Interpreters {
+        #[command(subcommand)]
+        subcommands: Option<InterpretersSubcommand>,
+    },
+    /// Check that the Requires-Dist requirements of all installed packages are satisfied.
+    Check {
+        #[command(subcommand)]
+        subcommands: Option<CheckSubcommand>,
+    },
+    /// Compare the packages of two executables, or of a snapshot and an executable, reporting packages added, removed, and changed in version.
+    Diff {
+        /// Executable paths; the first is the environment compared from, the second the environment compared to.
+        #[arg(short, long, value_name = "EXECUTABLE")]
+        exe: Vec<PathBuf>,
+
+        /// File path to a snapshot written with the `snapshot` command, used as the environment compared from; only one executable is then provided.
+        #[arg(long, value_name = "FILE")]
+        snapshot: Option<PathBuf>,
+
+        #[command(subcommand)]
+        subcommands: Option<DiffSubcommand>,
+    },
+    /// Display the installed dependency tree of packages, from their Requires-Dist requirements.
+    Tree {
+        /// Provide the name, or a glob-like pattern, of packages to display; if not provided, all packages not required by other packages are displayed.
+        package: Option<String>,
+
+        /// Enable case-sensitive pattern matching.
+        #[arg(long)]
+        case: bool,
+
+        /// Do not display requirements deeper than this depth.
+        #[arg(long, value_name = "DEPTH")]
+        depth: Option<usize>,
+
+        #[command(subcommand)]
+        subcommands: Option<TreeSubcommand>,
+    },
+    /// Map top-level module import names to the packages that provide them, as read from `top_level.txt` or RECORD files.
+    Map {
+        /// Provide the name, or a glob-like pattern, of modules to display; if not provided, all modules are displayed.
+        #[arg(default_value = "*")]
+        module: String,
+
+        /// Provide the name, or a glob-like pattern, of packages whose modules are displayed.
+        #[arg(short, long, default_value = "*")]
+        package: String,
+
+        /// Enable case-sensitive pattern matching.
+        #[arg(long)]
+        case: bool,
+
+        /// File path or URL from which to read bound requirements, or `-` to read from stdin; the requirement of each package, if any, is displayed. Can be repeated to merge layered requirements.
+        #[arg(short, long, value_name = "FILE")]
+        bound: Vec<PathBuf>,
+
+        #[command(subcommand)]
+        subcommands: Option<MapSubcommand>,
+    },
     /// Count discovered executables, sites, and packages.
     Count {
+        /// Also count the packages available to each executable and the packages in each site.
+        #[arg(long)]
+        detail: bool,
+
         #[command(subcommand)]
         subcommands: Option<CountSubcommand>,
     },
+    /// Export a software bill of materials of discovered packages.
+    Sbom {
+        /// Select the SBOM format.
+        #[arg(short, long, value_enum, default_value = "cyclonedx")]
+        format: CliSbomFormat,
+
+        #[command(subcommand)]
+        subcommands: Option<SbomSubcommand>,
+    },
+    /// Write a snapshot of discovered executables, sites, and packages, with the environment marker values and interpreter metadata of each executable, to a JSON file that can be validated later or on another machine.
+    Snapshot {
+        /// File path to which to write the snapshot.
+        #[arg(long, value_name = "FILE")]
+        out: PathBuf,
+    },
     /// Derive new requirements from discovered packages.
     Derive {
-        // Select the nature of the bound in the derived requirements.
-        #[arg(short, long, value_enum)]
+        /// Select the nature of the bound in the derived requirements: `exact` pins with `==`, `lower` uses `>=`, `upper` uses `<=`, and `compatible` uses `~=`. Packages installed from a VCS are always pinned to their installed commit.
+        #[arg(short, long, value_enum, default_value = "exact")]
         anchor: CliAnchor,
 
+        /// File path to which to write the derived requirements; equivalent to the `write` subcommand.
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+
+        /// Pin the exact version of each package with the hashes of its artifacts from PyPI, for use with `pip install --require-hashes`; requires the `exact` anchor.
+        #[arg(long)]
+        hashes: bool,
+
+        /// Cache PyPI responses for the provided number of seconds. A duration of zero, or the global `--no-cache` option, disables this cache.
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            default_value = "3600",
+            requires = "hashes"
+        )]
+        index_cache_duration: u64,
+
         #[command(subcommand)]
         subcommands: Option<DeriveSubcommand>,
     },
     /// Validate if packages conform to a validation target.
     Validate {
-        /// File path or URL from which to read bound requirements.
-        #[arg(short, long, value_name = "FILE")]
-        bound: PathBuf,
+        /// File path or URL from which to read bound requirements, or `-` to read from stdin. Can be repeated to merge layered requirements (e.g. `--bound base.txt --bound prod.txt`): packages defined in more than one must satisfy all of their requirements, and conflicting requirements are an error.
+        #[arg(short, long, value_name = "FILE", required = true)]
+        bound: Vec<PathBuf>,
 
-        /// Names of additional optional dependency groups.
-        #[arg(long, value_name = "OPTIONS")]
+        /// File path to a snapshot written with the `snapshot` command; packages are validated from the snapshot without scanning or running executables.
+        #[arg(long, value_name = "FILE")]
+        from_snapshot: Option<PathBuf>,
+
+        /// Names of additional optional dependency groups, from `[project.optional-dependencies]`, `[dependency-groups]`, or `[tool.poetry.group]`, from the groups recorded in poetry.lock, or `develop` for Pipfile.lock.
+        #[arg(long, visible_alias = "group", value_name = "OPTIONS")]
         bound_options: Option<Vec<String>>,
 
+        /// Do not replace `${VAR}` references in requirements files with the values of environment variables.
+        #[arg(long)]
+        no_env_expand: bool,
+
+        /// Require the artifact hashes recorded in poetry.lock, Pipfile.lock, and uv.lock bounds, validating installed packages against them as if given with `--hash` options.
+        #[arg(long)]
+        lock_hashes: bool,
+
         /// If the subset flag is set, the observed packages can be a subset of the bound requirements.
         #[arg(long)]
         subset: bool,
@@ -152,6 +522,110 @@ enum Commands {
         #[arg(long)]
         superset: bool,
 
+        /// If the exact flag is set, the observed packages must match the bound requirements one-to-one: nothing missing, nothing extra, and packages installed for requirements whose markers do not apply are not required.
+        #[arg(long, conflicts_with_all = ["subset", "superset"])]
+        exact: bool,
+
+        /// Resolve requested VCS branch or tag revisions with `git ls-remote`, reporting packages whose installed commit has drifted; this requires network access.
+        #[arg(long)]
+        resolve_revisions: bool,
+
+        /// Report packages installed in editable mode (with `pip install -e` or `setup.py develop`) as invalid.
+        #[arg(long)]
+        no_editable: bool,
+
+        /// Report installed packages that are neither required by the bound requirements nor required, directly or transitively, by a required package as "Orphan" records.
+        #[arg(long)]
+        orphans: bool,
+
+        /// Ignore local version labels of installed packages (e.g. the `+cu118` of `2.1.0+cu118`) when matching requirements; by default, a requirement of `==2.1.0` is not satisfied by `2.1.0+cu118`.
+        #[arg(long)]
+        ignore_local: bool,
+
+        /// Permit installed pre-releases to satisfy requirements such as `>=1.0`; by default, as with pip's resolution, installed pre-releases (e.g. `2.0rc1`) only satisfy requirements that specify a pre-release.
+        #[arg(long)]
+        pre: bool,
+
+        /// Report packages of requirements that define hashes (with `--hash` options or in the fragment of an archive URL) as invalid if no archive hash was recorded for the installed package, as for packages installed from an index.
+        #[arg(long)]
+        require_hashes: bool,
+
+        /// File path to a TOML license policy with `allow` and `deny` arrays of SPDX license identifiers; packages with licenses not permitted are invalid.
+        #[arg(long, value_name = "FILE")]
+        license_policy: Option<PathBuf>,
+
+        /// File path to a TOML ban policy of `[[ban]]` tables, each with a `package` name or glob-like pattern and an optional `reason`; installed packages that match are invalid.
+        #[arg(long, value_name = "FILE")]
+        ban_policy: Option<PathBuf>,
+
+        /// Report installed packages whose `Requires-Python`, as read from METADATA, is not satisfied by the version of the Python that owns their site as "PythonIncompatible" records.
+        #[arg(long)]
+        requires_python: bool,
+
+        /// Report installed packages whose wheel tags, as read from the WHEEL file, are not compatible with the Python version, ABI, and platform of the Python that owns their site as "IncompatibleBinary" records.
+        #[arg(long)]
+        wheel_tags: bool,
+
+        /// Report installed packages that are shadowed by a package of the same name in a site that precedes it on `sys.path` as "Shadowed" records.
+        #[arg(long)]
+        shadowed: bool,
+
+        /// Report installed packages not installed by one of these comma-separated tools (e.g. `pip,uv`), as read from the INSTALLER file, as "InstallerDenied" records.
+        #[arg(long, value_name = "INSTALLERS", value_delimiter = ',')]
+        require_installer: Option<Vec<String>>,
+
+        /// Only report records with these comma-separated explanation codes (e.g. `V003`), names (e.g. `Misdefined`), or categories (e.g. `integrity`).
+        #[arg(long, value_name = "CODES", value_delimiter = ',')]
+        select: Option<Vec<String>>,
+
+        /// Do not report records with these comma-separated explanation codes, names, or categories.
+        #[arg(long, value_name = "CODES", value_delimiter = ',')]
+        ignore: Option<Vec<String>>,
+
+        /// File path to a TOML file of waivers, as `[[ignore]]` tables with `package`, and optional `code`, `expires`, and `reason` keys; waived records are reported but do not fail validation. If not provided, waivers are read from `.fetter-ignore` or the `[[tool.fetter.ignore]]` tables of pyproject.toml in the current directory, if found.
+        #[arg(long, value_name = "FILE")]
+        waivers: Option<PathBuf>,
+
+        /// When running in GitHub Actions, as indicated by the `GITHUB_ACTIONS` environment variable, print an error annotation for each failure at the line of the bound requirements that declares its package.
+        #[arg(long)]
+        github_annotations: bool,
+
+        /// Always exit with 0, even if validation fails.
+        #[arg(long, conflicts_with_all = ["fail_on", "warn_only"])]
+        exit_zero: bool,
+
+        /// Only exit with an error if at least this many records fail validation.
+        #[arg(long, value_name = "COUNT", default_value = "1")]
+        fail_on: usize,
+
+        /// Report validation failures as a warning on stderr and exit with 0.
+        #[arg(long)]
+        warn_only: bool,
+
+        /// Display, print, or write the number of records of each explanation, the total number of records and of failures, and the number of records of each site, instead of each record.
+        #[arg(long, conflicts_with_all = ["columns", "sort"])]
+        summary: bool,
+
+        /// Only report packages whose names, or names and versions, match these comma-separated glob-like patterns (e.g. `numpy*`).
+        #[arg(long, value_name = "PATTERNS", value_delimiter = ',')]
+        include: Vec<String>,
+
+        /// Do not report packages whose names, or names and versions, match these comma-separated glob-like patterns (e.g. `internal-*`).
+        #[arg(long, value_name = "PATTERNS", value_delimiter = ',')]
+        exclude: Vec<String>,
+
+        /// Only report packages installed in this site.
+        #[arg(long, value_name = "DIR")]
+        site: Option<PathBuf>,
+
+        /// Display or write only these comma-separated columns, in order, selected by header (e.g. `package,site` or `user-site`); `name` and `version` select the name and version of each package.
+        #[arg(long, value_name = "COLUMNS", value_delimiter = ',')]
+        columns: Option<Vec<String>>,
+
+        /// Sort records by a column, as `COLUMN`, `COLUMN:asc`, or `COLUMN:desc` (e.g. `version:desc`); versions are compared as versions.
+        #[arg(long, value_name = "COLUMN")]
+        sort: Option<String>,
+
         #[command(subcommand)]
         subcommands: Option<ValidateSubcommand>,
     },
@@ -165,9 +639,65 @@ enum Commands {
         #[arg(long)]
         case: bool,
 
+        /// Also query PyPI to report packages whose installed version has been yanked, with the reason given.
+        #[arg(long)]
+        yanked: bool,
+
+        /// Cache PyPI responses for the provided number of seconds. A duration of zero, or the global `--no-cache` option, disables this cache.
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            default_value = "3600",
+            requires = "yanked"
+        )]
+        index_cache_duration: u64,
+
         #[command(subcommand)]
         subcommands: Option<AuditSubcommand>,
     },
+    /// Search for installed packages that are older than the latest release on PyPI.
+    Outdated {
+        /// Provide a glob-like pattern to select packages.
+        #[arg(short, long, default_value = "*")]
+        pattern: String,
+
+        /// Enable case-sensitive pattern matching.
+        #[arg(long)]
+        case: bool,
+
+        /// Cache PyPI responses for the provided number of seconds. A duration of zero, or the global `--no-cache` option, disables this cache.
+        #[arg(long, value_name = "SECONDS", default_value = "3600")]
+        index_cache_duration: u64,
+
+        #[command(subcommand)]
+        subcommands: Option<OutdatedSubcommand>,
+    },
+    /// Verify installed files against the digests in RECORD files, reporting modified, missing, and extra files.
+    Verify {
+        /// Provide a glob-like pattern to select packages.
+        #[arg(short, long, default_value = "*")]
+        pattern: String,
+
+        /// Enable case-sensitive pattern matching.
+        #[arg(long)]
+        case: bool,
+
+        #[command(subcommand)]
+        subcommands: Option<VerifySubcommand>,
+    },
+    /// Check that the console and GUI scripts declared by packages exist, with shebangs that name an executable of their environment.
+    Scripts {
+        /// Provide a glob-like pattern to select packages.
+        #[arg(short, long, default_value = "*")]
+        pattern: String,
+
+        /// Enable case-sensitive pattern matching.
+        #[arg(long)]
+        case: bool,
+
+        #[command(subcommand)]
+        subcommands: Option<ScriptsSubcommand>,
+    },
     /// Discover counts of all installed packages artifacts.
     UnpackCount {
         /// Provide a glob-like pattern to select packages.
@@ -194,6 +724,53 @@ enum Commands {
         #[command(subcommand)]
         subcommands: Option<UnpackFilesSubcommand>,
     },
+    /// Report the disk usage of packages, as the sum of the sizes of the files in their RECORD files, sorted by descending size.
+    Size {
+        /// Provide a glob-like pattern to select packages.
+        #[arg(short, long, default_value = "*")]
+        pattern: String,
+
+        /// Enable case-sensitive pattern matching.
+        #[arg(long)]
+        case: bool,
+
+        /// Sum the sizes of all selected packages in each site.
+        #[arg(long)]
+        by_site: bool,
+
+        #[command(subcommand)]
+        subcommands: Option<SizeSubcommand>,
+    },
+    /// List every file installed by a package, with its size and hash, from its RECORD file.
+    Files {
+        /// Provide the name, or a glob-like pattern, of the package.
+        package: String,
+
+        /// Enable case-sensitive pattern matching.
+        #[arg(long)]
+        case: bool,
+
+        /// Only list files of the package installed in this site.
+        #[arg(long, value_name = "SITE")]
+        site: Option<PathBuf>,
+
+        #[command(subcommand)]
+        subcommands: Option<UnpackFilesSubcommand>,
+    },
+    /// Report files in sites that are not owned by any installed package, as recorded in RECORD files, such as those left over from failed uninstalls or manual edits.
+    Strays {
+        /// Only examine this site.
+        #[arg(long, value_name = "SITE")]
+        site: Option<PathBuf>,
+
+        #[command(subcommand)]
+        subcommands: Option<StraysSubcommand>,
+    },
+    /// Check the virtual environment of each executable for a missing or broken interpreter symlink, a `pyvenv.cfg` home that does not exist or does not match the interpreter, and version drift between the environment and its base Python.
+    Doctor {
+        #[command(subcommand)]
+        subcommands: Option<DoctorSubcommand>,
+    },
     /// Purge packages that match a search pattern.
     PurgePattern {
         /// Provide a glob-like pattern to select packages.
@@ -203,17 +780,59 @@ enum Commands {
         /// Enable case-sensitive pattern matching.
         #[arg(long)]
         case: bool,
+
+        /// Display the artifacts that would be removed without removing them.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Remove artifacts without a confirmation prompt.
+        #[arg(long, short)]
+        yes: bool,
     },
     /// Purge packages that are invalid based on dependency specification.
     PurgeInvalid {
-        /// File path or URL from which to read bound requirements.
-        #[arg(short, long, value_name = "FILE")]
-        bound: PathBuf,
+        /// File path or URL from which to read bound requirements. Can be repeated to merge layered requirements.
+        #[arg(short, long, value_name = "FILE", required = true)]
+        bound: Vec<PathBuf>,
+
+        /// Names of additional optional dependency groups, from `[project.optional-dependencies]`, `[dependency-groups]`, or `[tool.poetry.group]`, from the groups recorded in poetry.lock, or `develop` for Pipfile.lock.
+        #[arg(long, visible_alias = "group", value_name = "OPTIONS")]
+        bound_options: Option<Vec<String>>,
+
+        /// Do not replace `${VAR}` references in requirements files with the values of environment variables.
+        #[arg(long)]
+        no_env_expand: bool,
+
+        /// If the subset flag is set, the observed packages can be a subset of the bound requirements.
+        #[arg(long)]
+        subset: bool,
+
+        /// If the superset flag is set, the observed packages can be a superset of the bound requirements.
+        #[arg(long)]
+        superset: bool,
+
+        /// Display the artifacts that would be removed without removing them.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Remove artifacts without a confirmation prompt.
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Validate packages, then revalidate whenever site packages or the bound requirements change, printing new ("+") and resolved ("-") records.
+    Watch {
+        /// File path or URL from which to read bound requirements. Can be repeated to merge layered requirements.
+        #[arg(short, long, value_name = "FILE", required = true)]
+        bound: Vec<PathBuf>,
 
-        /// Names of additional optional dependency groups.
-        #[arg(long, value_name = "OPTIONS")]
+        /// Names of additional optional dependency groups, from `[project.optional-dependencies]`, `[dependency-groups]`, or `[tool.poetry.group]`, from the groups recorded in poetry.lock, or `develop` for Pipfile.lock.
+        #[arg(long, visible_alias = "group", value_name = "OPTIONS")]
         bound_options: Option<Vec<String>>,
 
+        /// Do not replace `${VAR}` references in requirements files with the values of environment variables.
+        #[arg(long)]
+        no_env_expand: bool,
+
         /// If the subset flag is set, the observed packages can be a subset of the bound requirements.
         #[arg(long)]
         subset: bool,
@@ -221,20 +840,51 @@ enum Commands {
         /// If the superset flag is set, the observed packages can be a superset of the bound requirements.
         #[arg(long)]
         superset: bool,
+
+        /// Number of seconds to wait after a change is observed, such that changes from one install are revalidated together.
+        #[arg(long, default_value = "2")]
+        interval: u64,
+    },
+    /// Manage cached scans and sites.
+    Cache {
+        #[command(subcommand)]
+        subcommands: CacheSubcommand,
+    },
+    /// Print a shell completion script.
+    Completions {
+        /// The shell for which to generate completions.
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Print a man page, or write man pages for all commands to a directory.
+    Man {
+        /// Directory in which to write a man page for `fetter` and for each command (e.g. `fetter-validate.1`).
+        #[arg(short, long, value_name = "DIR")]
+        output: Option<PathBuf>,
     },
 }
 
 //------------------------------------------------------------------------------
+#[derive(Subcommand)]
+enum CacheSubcommand {
+    /// Remove all cached scans and sites.
+    Clear,
+}
+
 #[derive(Subcommand)]
 enum ScanSubcommand {
     /// Display scan in the terminal.
     Display,
+    /// Print a JSON representation of scan.
+    Json,
     /// Write a scan report to a file.
     Write {
         #[arg(short, long, value_name = "FILE")]
         output: PathBuf,
         #[arg(short, long, default_value = ",")]
         delimiter: char,
+        #[arg(short, long, value_enum, default_value = "delimited")]
+        format: CliFormat,
     },
 }
 
@@ -242,12 +892,16 @@ enum ScanSubcommand {
 enum SearchSubcommand {
     /// Display search int the terminal.
     Display,
+    /// Print a JSON representation of search.
+    Json,
     /// Write a search report to a file.
     Write {
         #[arg(short, long, value_name = "FILE")]
         output: PathBuf,
         #[arg(short, long, default_value = ",")]
         delimiter: char,
+        #[arg(short, long, value_enum, default_value = "delimited")]
+        format: CliFormat,
     },
 }
 
@@ -255,12 +909,27 @@ enum SearchSubcommand {
 enum CountSubcommand {
     /// Display scan in the terminal.
     Display,
+    /// Print a JSON representation of counts.
+    Json,
     /// Write a report to a delimited file.
     Write {
         #[arg(short, long, value_name = "FILE")]
         output: PathBuf,
         #[arg(short, long, default_value = ",")]
         delimiter: char,
+        #[arg(short, long, value_enum, default_value = "delimited")]
+        format: CliFormat,
+    },
+}
+
+#[derive(Subcommand)]
+enum SbomSubcommand {
+    /// Print the SBOM JSON in the terminal.
+    Display,
+    /// Write the SBOM JSON to a file.
+    Write {
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
     },
 }
 
@@ -287,6 +956,18 @@ enum ValidateSubcommand {
         output: PathBuf,
         #[arg(short, long, default_value = ",")]
         delimiter: char,
+        #[arg(short, long, value_enum, default_value = "delimited")]
+        format: CliFormat,
+    },
+    /// Write validation results as JUnit XML, with a test case for each bound requirement, for display by CI systems.
+    Junit {
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+    },
+    /// Write validation results as SARIF 2.1, located in the bound requirements file, for GitHub code scanning.
+    Sarif {
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
     },
     /// Return an exit code, 0 on success, 3 (by default) on error.
     Exit {
@@ -299,62 +980,341 @@ enum ValidateSubcommand {
 enum AuditSubcommand {
     /// Display audit results in the terminal.
     Display,
+    /// Print a JSON representation of audit results.
+    Json,
     /// Write audit results to a delimited file.
     Write {
         #[arg(short, long, value_name = "FILE")]
         output: PathBuf,
         #[arg(short, long, default_value = ",")]
         delimiter: char,
+        #[arg(short, long, value_enum, default_value = "delimited")]
+        format: CliFormat,
+    },
+    /// Write audit results as SARIF 2.1, located in a bound requirements file, for GitHub code scanning.
+    Sarif {
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+        /// File path to the bound requirements in which vulnerable packages are located.
+        #[arg(short, long, value_name = "FILE")]
+        bound: PathBuf,
     },
 }
 
 #[derive(Subcommand)]
-enum UnpackCountSubcommand {
-    /// Display installed artifacts in the terminal.
+enum OutdatedSubcommand {
+    /// Display outdated packages in the terminal.
     Display,
-    /// Write installed artifacts to a delimited file.
+    /// Print a JSON representation of outdated packages.
+    Json,
+    /// Write outdated packages to a delimited file.
     Write {
         #[arg(short, long, value_name = "FILE")]
         output: PathBuf,
         #[arg(short, long, default_value = ",")]
         delimiter: char,
+        #[arg(short, long, value_enum, default_value = "delimited")]
+        format: CliFormat,
     },
 }
 
 #[derive(Subcommand)]
-enum UnpackFilesSubcommand {
-    /// Display installed artifacts in the terminal.
+enum InterpretersSubcommand {
+    /// Display interpreter metadata in the terminal.
     Display,
-    /// Write installed artifacts to a delimited file.
+    /// Print a JSON representation of interpreter metadata.
+    Json,
+    /// Write interpreter metadata to a delimited file.
     Write {
         #[arg(short, long, value_name = "FILE")]
         output: PathBuf,
         #[arg(short, long, default_value = ",")]
         delimiter: char,
+        #[arg(short, long, value_enum, default_value = "delimited")]
+        format: CliFormat,
     },
 }
 
-//------------------------------------------------------------------------------
-// Utility constructors specialized fro CLI contexts
-
-// Provided `exe_paths` are not normalize.
-fn get_scan(
-    exe_paths: &Vec<PathBuf>, // could be a ref
+#[derive(Subcommand)]
+enum CheckSubcommand {
+    /// Display unsatisfied requirements in the terminal.
+    Display,
+    /// Print a JSON representation of unsatisfied requirements.
+    Json,
+    /// Write unsatisfied requirements to a delimited file.
+    Write {
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+        #[arg(short, long, default_value = ",")]
+        delimiter: char,
+        #[arg(short, long, value_enum, default_value = "delimited")]
+        format: CliFormat,
+    },
+}
+
+#[derive(Subcommand)]
+enum MapSubcommand {
+    /// Display modules and packages in the terminal.
+    Display,
+    /// Print a JSON representation of modules and packages.
+    Json,
+    /// Write modules and packages to a delimited file.
+    Write {
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+        #[arg(short, long, default_value = ",")]
+        delimiter: char,
+        #[arg(short, long, value_enum, default_value = "delimited")]
+        format: CliFormat,
+    },
+}
+
+#[derive(Subcommand)]
+enum DiffSubcommand {
+    /// Display differences in the terminal.
+    Display,
+    /// Print a JSON representation of differences.
+    Json,
+    /// Write differences to a delimited file.
+    Write {
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+        #[arg(short, long, default_value = ",")]
+        delimiter: char,
+        #[arg(short, long, value_enum, default_value = "delimited")]
+        format: CliFormat,
+    },
+}
+
+#[derive(Subcommand)]
+enum TreeSubcommand {
+    /// Display the dependency tree in the terminal.
+    Display,
+    /// Print a JSON representation of the dependency tree.
+    Json,
+    /// Write the dependency tree to a delimited file.
+    Write {
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+        #[arg(short, long, default_value = ",")]
+        delimiter: char,
+        #[arg(short, long, value_enum, default_value = "delimited")]
+        format: CliFormat,
+    },
+}
+
+#[derive(Subcommand)]
+enum VerifySubcommand {
+    /// Display verification results in the terminal.
+    Display,
+    /// Print a JSON representation of verification results.
+    Json,
+    /// Write verification results to a delimited file.
+    Write {
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+        #[arg(short, long, default_value = ",")]
+        delimiter: char,
+        #[arg(short, long, value_enum, default_value = "delimited")]
+        format: CliFormat,
+    },
+}
+
+#[derive(Subcommand)]
+enum ScriptsSubcommand {
+    /// Display script problems in the terminal.
+    Display,
+    /// Print a JSON representation of script problems.
+    Json,
+    /// Write script problems to a delimited file.
+    Write {
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+        #[arg(short, long, default_value = ",")]
+        delimiter: char,
+        #[arg(short, long, value_enum, default_value = "delimited")]
+        format: CliFormat,
+    },
+}
+
+#[derive(Subcommand)]
+enum UnpackCountSubcommand {
+    /// Display installed artifacts in the terminal.
+    Display,
+    /// Print a JSON representation of installed artifacts.
+    Json,
+    /// Write installed artifacts to a delimited file.
+    Write {
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+        #[arg(short, long, default_value = ",")]
+        delimiter: char,
+        #[arg(short, long, value_enum, default_value = "delimited")]
+        format: CliFormat,
+    },
+}
+
+#[derive(Subcommand)]
+enum SizeSubcommand {
+    /// Display disk usage in the terminal.
+    Display,
+    /// Print a JSON representation of disk usage.
+    Json,
+    /// Write disk usage to a delimited file.
+    Write {
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+        #[arg(short, long, default_value = ",")]
+        delimiter: char,
+        #[arg(short, long, value_enum, default_value = "delimited")]
+        format: CliFormat,
+    },
+}
+
+#[derive(Subcommand)]
+enum StraysSubcommand {
+    /// Display stray files in the terminal.
+    Display,
+    /// Print a JSON representation of stray files.
+    Json,
+    /// Write stray files to a delimited file.
+    Write {
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+        #[arg(short, long, default_value = ",")]
+        delimiter: char,
+        #[arg(short, long, value_enum, default_value = "delimited")]
+        format: CliFormat,
+    },
+    /// Remove stray files.
+    Purge {
+        /// Remove files without a confirmation prompt.
+        #[arg(long, short)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum DoctorSubcommand {
+    /// Display environment problems in the terminal.
+    Display,
+    /// Print a JSON representation of environment problems.
+    Json,
+    /// Write environment problems to a delimited file.
+    Write {
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+        #[arg(short, long, default_value = ",")]
+        delimiter: char,
+        #[arg(short, long, value_enum, default_value = "delimited")]
+        format: CliFormat,
+    },
+}
+
+#[derive(Subcommand)]
+enum UnpackFilesSubcommand {
+    /// Display installed artifacts in the terminal.
+    Display,
+    /// Print a JSON representation of installed artifacts.
+    Json,
+    /// Write installed artifacts to a delimited file.
+    Write {
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+        #[arg(short, long, default_value = ",")]
+        delimiter: char,
+        #[arg(short, long, value_enum, default_value = "delimited")]
+        format: CliFormat,
+    },
+}
+
+//------------------------------------------------------------------------------
+// Utility constructors specialized fro CLI contexts
+
+// Provided `exe_paths` and `scan_roots` are not normalize. If `scan_roots` are provided, they are used instead of `exe_paths`. If `root` is provided, it is scanned offline without a cache. If `static_sites` is set, sites are derived without running executables and the scan cache is not used. If `exclude_usite` is set, user sites are removed after scanning or loading from the cache.
+#[allow(clippy::too_many_arguments)]
+fn get_scan(
+    exe_paths: &[PathBuf],
+    scan_roots: &[PathBuf],
+    root: Option<&PathBuf>,
+    static_sites: bool,
     force_usite: bool,
+    exclude_usite: bool,
     log: bool,
     cache_dur: Duration,
 ) -> Result<ScanFS, Box<dyn std::error::Error>> {
-    ScanFS::from_cache(exe_paths, force_usite, cache_dur).or_else(|_err| {
+    let sfs = get_scan_inner(
+        exe_paths,
+        scan_roots,
+        root,
+        static_sites,
+        force_usite,
+        log,
+        cache_dur,
+    )?;
+    if exclude_usite {
+        Ok(sfs.without_user_sites())
+    } else {
+        Ok(sfs)
+    }
+}
+
+fn get_scan_inner(
+    exe_paths: &[PathBuf],
+    scan_roots: &[PathBuf],
+    root: Option<&PathBuf>,
+    static_sites: bool,
+    force_usite: bool,
+    log: bool,
+    cache_dur: Duration,
+) -> Result<ScanFS, Box<dyn std::error::Error>> {
+    if root.is_some() || static_sites {
+        let active = Arc::new(AtomicBool::new(true));
+        if log {
+            spin(active.clone(), "scanning".to_string());
+        }
+        let flags = ScanFlags::default()
+            .with_force_usite(force_usite)
+            .with_site_cache(cache_dur > DURATION_0)
+            .with_static_sites(true);
+        let sfsl = match root {
+            Some(root) => ScanFS::from_root(root)?,
+            None if scan_roots.is_empty() => ScanFS::from_exes(exe_paths, flags)?,
+            None => ScanFS::from_scan_roots(scan_roots, flags)?,
+        };
+        if log {
+            active.store(false, Ordering::Relaxed);
+            thread::sleep(Duration::from_millis(100));
+        }
+        return Ok(sfsl);
+    }
+    let cache_paths: &[PathBuf] = if scan_roots.is_empty() {
+        exe_paths
+    } else {
+        scan_roots
+    };
+    ScanFS::from_cache(cache_paths, force_usite, cache_dur).or_else(|_err| {
         // eprintln!("Could not load from cache: {:?}", err);
         // full load
         let active = Arc::new(AtomicBool::new(true));
         if log {
             spin(active.clone(), "scanning".to_string());
         }
-        let sfsl = ScanFS::from_exes(exe_paths, force_usite)?;
+        // sites are cached by modification time when caching is enabled
+        let flags = ScanFlags::default()
+            .with_force_usite(force_usite)
+            .with_site_cache(cache_dur > DURATION_0);
+        let sfsl = if scan_roots.is_empty() {
+            ScanFS::from_exes(exe_paths, flags)?
+        } else {
+            ScanFS::from_scan_roots(scan_roots, flags)?
+        };
 
-        if cache_dur > DURATION_0 {
-            sfsl.to_cache(cache_dur)?;
+        // a scan with warnings is incomplete and is not cached
+        if cache_dur > DURATION_0 && !sfsl.has_warnings() {
+            if let Err(e) = sfsl.to_cache(cache_dur) {
+                warn!("Failed to write scan cache: {}", e);
+            }
         }
         if log {
             active.store(false, Ordering::Relaxed);
@@ -364,26 +1324,244 @@ fn get_scan(
     })
 }
 
-// Given a Path, load a DepManifest. This might branch by extension to handle pyproject.toml and other formats.
+// Write a man page for `fetter`, and for each command as `fetter-<command>.1`, to `dir`, creating it if necessary.
+fn write_man_pages(dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(dir)?;
+    let command = Cli::command();
+    let mut pages = vec![("fetter".to_string(), command.clone())];
+    for sub in command.get_subcommands() {
+        let name = format!("fetter-{}", sub.get_name());
+        pages.push((name.clone(), sub.clone().name(name)));
+    }
+    for (name, page) in pages {
+        let mut file = File::create(dir.join(format!("{}.1", name)))?;
+        clap_mangen::Man::new(page).render(&mut file)?;
+    }
+    Ok(())
+}
+
+// Given `NAME: VALUE` headers, or if none are provided the FETTER_BOUND_HEADER environment variable, return name and value pairs.
+fn get_bound_headers(
+    bound_header: &[String],
+) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let from_env: Vec<String> = if bound_header.is_empty() {
+        env::var("FETTER_BOUND_HEADER").into_iter().collect()
+    } else {
+        Vec::new()
+    };
+    bound_header
+        .iter()
+        .chain(from_env.iter())
+        .map(|h| match h.split_once(':') {
+            Some((name, value)) if !name.trim().is_empty() => {
+                Ok((name.trim().to_string(), value.trim().to_string()))
+            }
+            _ => Err(format!("Invalid header, expected NAME: VALUE: {}", h).into()),
+        })
+        .collect()
+}
+
+// Given one or more Paths, load a DepManifest from each and merge them in order. This might branch by extension to handle pyproject.toml and other formats. Conflicts between bounds, and requirements that no version can satisfy, are an error.
 fn get_dep_manifest(
+    bounds: &[PathBuf],
+    headers: &[(String, String)],
+    bound_options: Option<&Vec<String>>,
+    env_expand: bool,
+    lock_hashes: bool,
+) -> Result<DepManifest, Box<dyn std::error::Error>> {
+    let mut dm: Option<DepManifest> = None;
+    for bound in bounds {
+        let dm_bound = get_dep_manifest_unchecked(
+            bound,
+            headers,
+            bound_options,
+            env_expand,
+            lock_hashes,
+        )?;
+        dm = Some(match dm {
+            Some(dm) => dm
+                .merge(&dm_bound)
+                .map_err(|e| format!("Cannot merge bound {}: {}", bound.display(), e))?,
+            None => dm_bound,
+        });
+    }
+    let dm = dm.ok_or("No bound provided")?;
+    dm.validate_satisfiable()?;
+    Ok(dm)
+}
+
+fn get_dep_manifest_unchecked(
     bound: &PathBuf,
+    headers: &[(String, String)],
     bound_options: Option<&Vec<String>>,
+    env_expand: bool,
+    lock_hashes: bool,
 ) -> Result<DepManifest, Box<dyn std::error::Error>> {
     if bound.to_str().is_some_and(|s| s.ends_with(".git")) {
         // if bound.to_str().map_or(false, |s| s.ends_with(".git")) {
-        DepManifest::from_git_repo(bound, bound_options)
+        DepManifest::from_git_repo(bound, bound_options, env_expand)
+    } else if bound.as_os_str() == "-" {
+        // the format is detected from the content
+        let mut content = String::new();
+        io::stdin().read_to_string(&mut content)?;
+        DepManifest::from_content(&content, None, bound_options, env_expand, lock_hashes)
+    } else if bound
+        .to_str()
+        .is_some_and(|s| s.starts_with("https://") || s.starts_with("http://"))
+    {
+        // might have URL based requirements or pyproject
+        DepManifest::from_url(
+            &UreqClientLive,
+            bound,
+            headers,
+            bound_options,
+            env_expand,
+            lock_hashes,
+        )
     } else if bound
         .to_str()
         .is_some_and(|s| s.ends_with("pyproject.toml"))
     {
         DepManifest::from_pyproject_file(bound, bound_options)
-    } else if bound.to_str().is_some_and(|s| s.starts_with("http")) {
-        // might have URL based requirements or pyproject
-        DepManifest::from_url(&UreqClientLive, bound, bound_options)
+    } else if bound.to_str().is_some_and(|s| s.ends_with("poetry.lock")) {
+        DepManifest::from_poetry_lock_file(bound, bound_options, lock_hashes)
+    } else if bound.to_str().is_some_and(|s| s.ends_with("Pipfile.lock")) {
+        DepManifest::from_pipfile_lock_file(bound, bound_options, lock_hashes)
+    } else if bound.to_str().is_some_and(|s| s.ends_with("uv.lock")) {
+        DepManifest::from_uv_lock_file(bound, lock_hashes)
+    } else if bound
+        .extension()
+        .is_some_and(|ext| ext == "yml" || ext == "yaml")
+    {
+        DepManifest::from_environment_yml_file(bound)
     } else {
         // assume all text files are requirements-style
         let fp = path_normalize(bound).unwrap_or_else(|_| bound.clone());
-        DepManifest::from_requirements_file(&fp)
+        DepManifest::from_requirements_file_with_env_expand(&fp, env_expand)
+    }
+}
+
+// Given codes, names, or categories of validation explanations, return all selected explanations; a selector that matches no explanation is an error.
+fn get_explains(
+    selectors: &[String],
+) -> Result<Vec<ValidationExplain>, Box<dyn std::error::Error>> {
+    let mut explains = Vec::new();
+    for selector in selectors {
+        let selected = ValidationExplain::from_selector(selector);
+        if selected.is_empty() {
+            return Err(format!("Unknown validation code: {}", selector).into());
+        }
+        explains.extend(selected);
+    }
+    Ok(explains)
+}
+
+// Prompt on stderr and read a response from stdin, returning true if the response is "y" or "yes".
+fn confirm(prompt: &str) -> io::Result<bool> {
+    eprint!("{} [y/N] ", prompt);
+    io::stderr().flush()?;
+    let mut response = String::new();
+    io::stdin().read_line(&mut response)?;
+    Ok(is_confirmed(&response))
+}
+
+fn is_confirmed(response: &str) -> bool {
+    matches!(response.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+// Remove the artifacts in an UnpackReport. If `dry_run`, only display the artifacts; unless `yes`, display the artifacts and prompt for confirmation before removing.
+fn purge(ur: &UnpackReport, dry_run: bool, yes: bool, log: bool) -> io::Result<()> {
+    if dry_run {
+        return ur.to_stdout();
+    }
+    if ur.len() == 0 {
+        return Ok(());
+    }
+    if !yes {
+        ur.to_stdout()?;
+        if !confirm(&format!("Remove {} installed packages?", ur.len()))? {
+            return Ok(());
+        }
+    }
+    ur.remove(log)
+}
+
+// Remove the files in a StrayReport. Unless `yes`, display the files and prompt for confirmation before removing.
+fn purge_strays(sr: &StrayReport, yes: bool, log: bool) -> io::Result<()> {
+    if sr.len() == 0 {
+        return Ok(());
+    }
+    if !yes {
+        sr.to_stdout()?;
+        if !confirm(&format!("Remove {} stray files?", sr.len()))? {
+            return Ok(());
+        }
+    }
+    sr.remove(log)
+}
+
+// Return the exit code of a validation with `count` failed records, where `code` is returned on failure.
+fn validation_exit_code(
+    count: usize,
+    code: i32,
+    fail_on: usize,
+    exit_zero: bool,
+    warn_only: bool,
+) -> i32 {
+    if exit_zero || warn_only || count == 0 || count < fail_on {
+        0
+    } else {
+        code
+    }
+}
+
+// Write a report to a file in the requested format.
+fn write_report<R: Rowable, T: Tableable<R>>(
+    report: &T,
+    output: &PathBuf,
+    delimiter: char,
+    format: CliFormat,
+) -> Result<(), FetterError> {
+    report
+        .to_file_format(output, format.to_table_format(delimiter))
+        .map_err(|source| FetterError::ReportWrite {
+            path: output.clone(),
+            source,
+        })
+}
+
+// Display a report in the terminal or, if the global output is provided, write it to that file in the format inferred from its extension.
+fn display_report<R: Rowable, T: Tableable<R>>(
+    report: &T,
+    output: Option<&PathBuf>,
+) -> Result<(), FetterError> {
+    match output {
+        Some(output) => {
+            report
+                .to_output(output)
+                .map_err(|source| FetterError::ReportWrite {
+                    path: output.clone(),
+                    source,
+                })
+        }
+        None => Ok(report.to_stdout()?),
+    }
+}
+
+// Display the executables and sites that could not be scanned as a section following a report displayed in the terminal; otherwise, log them.
+fn display_warnings(sfs: &ScanFS, section: bool) -> io::Result<()> {
+    let wr = sfs.to_warning_report();
+    if wr.is_empty() {
+        return Ok(());
+    }
+    if section {
+        println!();
+        wr.to_stdout()
+    } else {
+        for warning in wr.get_records() {
+            warn!("{}", warning);
+        }
+        Ok(())
     }
 }
 
@@ -396,132 +1574,670 @@ where
     if env::consts::OS != "macos" && env::consts::OS != "linux" {
         return Err("No support for this platform. To request support, visit https://github.com/fetter-io/fetter-rs/issues/66".into());
     }
+    let args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+    let config = if args.iter().any(|a| a == "--no-config") {
+        None
+    } else {
+        Config::from_dir(&env::current_dir()?)?
+    };
+    let args = match config {
+        Some(config) => config.to_args(&Cli::command(), args)?,
+        None => args,
+    };
     let cli = Cli::parse_from(args);
     if cli.command.is_none() {
         return Err("No command provided. For more information, try '--help'.".into());
     }
+    set_color_choice(cli.color.into());
+    init_logging(cli.verbose, cli.quiet, cli.log_format.into());
+    set_exe_timeout(cli.exe_timeout);
+    // the format of the global output is inferred before scanning
+    if let Some(output) = &cli.output {
+        if !matches!(
+            cli.command,
+            Some(Commands::Sbom { .. }) | Some(Commands::Derive { .. })
+        ) {
+            to_table_format(output)?;
+        }
+    }
+    // cache management does not require a scan
+    if let Some(Commands::Cache { subcommands }) = &cli.command {
+        match subcommands {
+            CacheSubcommand::Clear => path_cache_clear()?,
+        }
+        return Ok(());
+    }
+    // completions and man pages do not require a scan
+    if let Some(Commands::Completions { shell }) = &cli.command {
+        clap_complete::generate(*shell, &mut Cli::command(), "fetter", &mut io::stdout());
+        return Ok(());
+    }
+    if let Some(Commands::Man { output }) = &cli.command {
+        match output {
+            Some(dir) => write_man_pages(dir)?,
+            None => clap_mangen::Man::new(Cli::command()).render(&mut io::stdout())?,
+        }
+        return Ok(());
+    }
+    let headers = get_bound_headers(&cli.bound_header)?;
     // we always do a scan; we might cache this
     let quiet = cli.quiet;
-    let sfs = get_scan(
-        &cli.exe,
-        cli.user_site,
-        !quiet,
-        Duration::from_secs(cli.cache_duration),
-    )?;
+    let cache_dur = if cli.no_cache {
+        DURATION_0
+    } else {
+        Duration::from_secs(cli.cache_duration)
+    };
+    // a diff scans each of its executables independently
+    if let Some(Commands::Diff {
+        exe,
+        snapshot,
+        subcommands,
+    }) = &cli.command
+    {
+        if exe.len() + usize::from(snapshot.is_some()) != 2 {
+            return Err(
+                "Provide exactly two executables with --exe, or one executable with --snapshot"
+                    .into(),
+            );
+        }
+        let sfs_from = match snapshot {
+            Some(fp) => ScanFS::from_snapshot_file(fp)?,
+            None => get_scan(
+                &exe[..1],
+                &[],
+                None,
+                cli.static_sites,
+                cli.user_site,
+                cli.no_user_site,
+                !quiet,
+                cache_dur,
+            )?,
+        };
+        let sfs_to = get_scan(
+            &exe[exe.len() - 1..],
+            &[],
+            None,
+            cli.static_sites,
+            cli.user_site,
+            cli.no_user_site,
+            !quiet,
+            cache_dur,
+        )?;
+        let dr = sfs_from.to_diff_report(&sfs_to);
+        match subcommands {
+            Some(DiffSubcommand::Write {
+                output,
+                delimiter,
+                format,
+            }) => {
+                write_report(&dr, output, *delimiter, *format)?;
+            }
+            Some(DiffSubcommand::Json) => {
+                dr.to_json_stdout()?;
+            }
+            Some(DiffSubcommand::Display) | None => {
+                display_report(&dr, cli.output.as_ref())?;
+            }
+        }
+        return Ok(());
+    }
+    // validating a snapshot does not require a scan
+    let mut sfs = match &cli.command {
+        Some(Commands::Validate {
+            from_snapshot: Some(fp),
+            ..
+        }) => ScanFS::from_snapshot_file(fp)?,
+        _ => get_scan(
+            &cli.exe,
+            &cli.scan_root,
+            cli.root.as_ref(),
+            cli.static_sites,
+            cli.user_site,
+            cli.no_user_site,
+            !quiet,
+            cache_dur,
+        )?,
+    };
+
+    // filters are applied before validation and display
+    if let Some(Commands::Scan {
+        include,
+        exclude,
+        site,
+        ..
+    })
+    | Some(Commands::Validate {
+        include,
+        exclude,
+        site,
+        ..
+    }) = &cli.command
+    {
+        let filter = PackageFilter::new(include, exclude);
+        if !filter.is_empty() || site.is_some() {
+            sfs.retain_packages(&filter, site.as_deref())?;
+        }
+    }
+    // scan and validate reports displayed in the terminal are followed by a section of warnings
+    if !matches!(
+        cli.command,
+        Some(Commands::Scan { .. }) | Some(Commands::Validate { .. })
+    ) {
+        display_warnings(&sfs, false)?;
+    }
 
     match &cli.command {
-        Some(Commands::Scan { subcommands }) => match subcommands {
-            Some(ScanSubcommand::Write { output, delimiter }) => {
-                let sr = sfs.to_scan_report();
-                let _ = sr.to_file(output, *delimiter);
-            }
-            Some(ScanSubcommand::Display) | None => {
-                let sr = sfs.to_scan_report();
-                let _ = sr.to_stdout();
+        Some(Commands::Scan {
+            columns,
+            sort,
+            subcommands,
+            ..
+        }) => {
+            let sr = TableView::from_report(
+                &sfs.to_scan_report(),
+                columns.as_deref(),
+                sort.as_deref(),
+            )?;
+            match subcommands {
+                Some(ScanSubcommand::Write {
+                    output,
+                    delimiter,
+                    format,
+                }) => {
+                    write_report(&sr, output, *delimiter, *format)?;
+                }
+                Some(ScanSubcommand::Json) => {
+                    sr.to_json_stdout()?;
+                }
+                Some(ScanSubcommand::Display) | None => {
+                    display_report(&sr, cli.output.as_ref())?;
+                }
             }
-        },
+            let section = matches!(subcommands, Some(ScanSubcommand::Display) | None)
+                && cli.output.is_none();
+            display_warnings(&sfs, section)?;
+        }
         Some(Commands::Search {
             subcommands,
             pattern,
             case,
         }) => match subcommands {
-            Some(SearchSubcommand::Write { output, delimiter }) => {
+            Some(SearchSubcommand::Write {
+                output,
+                delimiter,
+                format,
+            }) => {
+                let sr = sfs.to_search_report(pattern, !case);
+                write_report(&sr, output, *delimiter, *format)?;
+            }
+            Some(SearchSubcommand::Json) => {
                 let sr = sfs.to_search_report(pattern, !case);
-                let _ = sr.to_file(output, *delimiter);
+                sr.to_json_stdout()?;
             }
             Some(SearchSubcommand::Display) | None => {
                 // default
                 let sr = sfs.to_search_report(pattern, !case);
-                let _ = sr.to_stdout();
+                display_report(&sr, cli.output.as_ref())?;
             }
         },
-        Some(Commands::Count { subcommands }) => match subcommands {
-            Some(CountSubcommand::Write { output, delimiter }) => {
-                let cr = sfs.to_count_report();
-                let _ = cr.to_file(output, *delimiter);
+        Some(Commands::Count {
+            subcommands,
+            detail,
+        }) => match subcommands {
+            Some(CountSubcommand::Write {
+                output,
+                delimiter,
+                format,
+            }) => {
+                let cr = sfs.to_count_report(*detail);
+                write_report(&cr, output, *delimiter, *format)?;
+            }
+            Some(CountSubcommand::Json) => {
+                let cr = sfs.to_count_report(*detail);
+                cr.to_json_stdout()?;
             }
             Some(CountSubcommand::Display) | None => {
                 // default
-                let cr = sfs.to_count_report();
-                let _ = cr.to_stdout();
+                let cr = sfs.to_count_report(*detail);
+                display_report(&cr, cli.output.as_ref())?;
             }
         },
+        Some(Commands::Sbom {
+            format,
+            subcommands,
+        }) => {
+            let sbom = sfs.to_sbom((*format).into());
+            match subcommands {
+                Some(SbomSubcommand::Write { output }) => {
+                    sbom.to_file(output)?;
+                }
+                Some(SbomSubcommand::Display) | None => {
+                    match cli.output.as_ref() {
+                        Some(output) => sbom.to_file(output),
+                        None => sbom.to_stdout(),
+                    }?;
+                }
+            }
+        }
         Some(Commands::Derive {
             subcommands,
             anchor,
-        }) => match subcommands {
-            Some(DeriveSubcommand::Write { output }) => {
-                let dm = sfs.to_dep_manifest((*anchor).into())?;
-                let dmr = dm.to_dep_manifest_report();
-                let _ = dmr.to_file(output, ' ');
-            }
-            Some(DeriveSubcommand::Display) | None => {
-                // default
-                let dm = sfs.to_dep_manifest((*anchor).into())?;
-                let dmr = dm.to_dep_manifest_report();
-                let _ = dmr.to_stdout();
+            out,
+            hashes,
+            index_cache_duration,
+        }) => {
+            let dm = if *hashes {
+                if !matches!(anchor, CliAnchor::Exact) {
+                    return Err("Hashes require the exact anchor".into());
+                }
+                let index_cache_dur = if cli.no_cache {
+                    DURATION_0
+                } else {
+                    Duration::from_secs(*index_cache_duration)
+                };
+                sfs.to_dep_manifest_hashed(index_cache_dur)?
+            } else {
+                sfs.to_dep_manifest((*anchor).into())?
+            };
+            let dmr = dm.to_dep_manifest_report();
+            match (out.as_ref().or(cli.output.as_ref()), subcommands) {
+                (Some(output), _) | (None, Some(DeriveSubcommand::Write { output })) => {
+                    dmr.to_file(output, ' ')?;
+                }
+                (None, Some(DeriveSubcommand::Display)) | (None, None) => {
+                    // default
+                    dmr.to_stdout()?;
+                }
             }
-        },
+        }
+        Some(Commands::Snapshot { out }) => {
+            sfs.to_snapshot_file(out)?;
+        }
         Some(Commands::Validate {
             bound,
+            from_snapshot: _,
             bound_options,
+            no_env_expand,
+            lock_hashes,
             subset,
             superset,
+            exact,
+            resolve_revisions,
+            no_editable,
+            orphans,
+            ignore_local,
+            pre,
+            require_hashes,
+            license_policy,
+            ban_policy,
+            requires_python,
+            wheel_tags,
+            shadowed,
+            require_installer,
+            select,
+            ignore,
+            waivers,
+            github_annotations,
+            exit_zero,
+            fail_on,
+            warn_only,
+            summary,
+            include,
+            exclude,
+            site: _,
+            columns,
+            sort,
             subcommands,
         }) => {
-            let dm = get_dep_manifest(bound, bound_options.as_ref())?;
+            let dm = get_dep_manifest(
+                bound,
+                &headers,
+                bound_options.as_ref(),
+                !*no_env_expand,
+                *lock_hashes,
+            )?
+            .filter_packages(&PackageFilter::new(include, exclude));
             let permit_superset = *superset;
             let permit_subset = *subset;
-            let vr = sfs.to_validation_report(
-                dm,
-                ValidationFlags {
-                    permit_superset,
-                    permit_subset,
-                },
+            // resolving revisions makes this potentially slow
+            let active = Arc::new(AtomicBool::new(true));
+            if !quiet {
+                spin(active.clone(), "validating".to_string());
+            }
+            let mut vr = sfs.to_validation_report(
+                dm.clone(),
+                ValidationFlags::default()
+                    .with_permit_superset(permit_superset)
+                    .with_permit_subset(permit_subset)
+                    .with_resolve_revisions(*resolve_revisions)
+                    .with_reject_editable(*no_editable)
+                    .with_report_orphans(*orphans)
+                    .with_ignore_local(*ignore_local)
+                    .with_reject_prerelease(!*pre)
+                    .with_require_hashes(*require_hashes)
+                    .with_exact(*exact),
             );
+            if !quiet {
+                active.store(false, Ordering::Relaxed);
+                thread::sleep(Duration::from_millis(100));
+            }
+            if let Some(fp) = license_policy {
+                let lp = LicensePolicy::from_file(fp)?;
+                vr.records.extend(sfs.to_license_records(&lp));
+            }
+            if let Some(fp) = ban_policy {
+                let bp = BanPolicy::from_file(fp)?;
+                vr.records.extend(sfs.to_ban_records(&bp));
+            }
+            if *requires_python {
+                vr.records.extend(sfs.to_requires_python_records());
+            }
+            if *wheel_tags {
+                vr.records.extend(sfs.to_wheel_tag_records());
+            }
+            if *shadowed {
+                vr.records.extend(sfs.to_shadow_records());
+            }
+            if let Some(installers) = require_installer {
+                vr.records.extend(sfs.to_installer_records(installers));
+            }
+            if select.is_some() || ignore.is_some() {
+                let select = select.as_deref().map(get_explains).transpose()?;
+                let ignore = get_explains(ignore.as_deref().unwrap_or_default())?;
+                vr.filter_explains(select.as_deref(), &ignore);
+            }
+            let waivers = match waivers {
+                Some(fp) => Waivers::from_file(fp)?,
+                None => Waivers::from_dir(&env::current_dir()?)?,
+            };
+            vr.apply_waivers(&waivers, days_today());
+            vr.sort_records();
+            if let Some(site_to_exes) = sfs.get_site_to_exes() {
+                vr.set_site_to_exes(&site_to_exes);
+            }
+            // the JSON digest is not changed by columns or sort
+            let vt = TableView::from_report(&vr, columns.as_deref(), sort.as_deref())?;
+            let mut code = ERROR_EXIT_CODE;
             match subcommands {
+                Some(ValidateSubcommand::Json) if *summary => {
+                    vr.to_summary_report().to_json_stdout()?;
+                }
                 Some(ValidateSubcommand::Json) => {
-                    println!("{}", serde_json::to_string(&vr.to_validation_digest())?);
+                    vr.to_json_stdout()?;
+                }
+                Some(ValidateSubcommand::Write {
+                    output,
+                    delimiter,
+                    format,
+                }) if *summary => {
+                    let sr = vr.to_summary_report();
+                    write_report(&sr, output, *delimiter, *format)?;
+                }
+                Some(ValidateSubcommand::Write {
+                    output,
+                    delimiter,
+                    format,
+                }) => {
+                    write_report(&vt, output, *delimiter, *format)?;
+                }
+                Some(ValidateSubcommand::Junit { output }) => {
+                    vr.to_junit_file(output, &dm.get_dep_specs())?;
                 }
-                Some(ValidateSubcommand::Write { output, delimiter }) => {
-                    let _ = vr.to_file(output, *delimiter);
+                Some(ValidateSubcommand::Sarif { output }) => {
+                    vr.to_sarif(bound).to_file(output)?;
                 }
-                Some(ValidateSubcommand::Exit { code }) => {
-                    process::exit(if vr.len() > 0 { *code } else { 0 });
+                Some(ValidateSubcommand::Exit { code: code_exit }) => {
+                    code = *code_exit;
+                }
+                Some(ValidateSubcommand::Display) | None if *summary => {
+                    display_report(&vr.to_summary_report(), cli.output.as_ref())?;
                 }
                 Some(ValidateSubcommand::Display) | None => {
                     // default
-                    let _ = vr.to_stdout();
-                    process::exit(if vr.len() > 0 { ERROR_EXIT_CODE } else { 0 });
+                    display_report(&vt, cli.output.as_ref())?;
                 }
             }
+            let section = matches!(subcommands, Some(ValidateSubcommand::Display) | None)
+                && cli.output.is_none();
+            display_warnings(&sfs, section)?;
+            if *github_annotations
+                && env::var("GITHUB_ACTIONS").is_ok_and(|v| v == "true")
+            {
+                for annotation in vr.to_github_annotations(bound) {
+                    println!("{}", annotation);
+                }
+            }
+            if *warn_only && vr.failure_count() > 0 {
+                eprintln!("warning: {} records failed validation", vr.failure_count());
+            }
+            process::exit(validation_exit_code(
+                vr.failure_count(),
+                code,
+                *fail_on,
+                *exit_zero,
+                *warn_only,
+            ));
         }
         Some(Commands::Audit {
             subcommands,
             pattern,
             case,
+            yanked,
+            index_cache_duration,
         }) => {
+            let index_cache_dur = if cli.no_cache {
+                DURATION_0
+            } else {
+                Duration::from_secs(*index_cache_duration)
+            };
             // network look makes this potentially slow
             let active = Arc::new(AtomicBool::new(true));
             if !quiet {
                 spin(active.clone(), "vulnerability searching".to_string());
             }
-            let ar = sfs.to_audit_report(pattern, !case);
+            let ar = sfs.to_audit_report(pattern, !case, *yanked, index_cache_dur);
             if !quiet {
                 active.store(false, Ordering::Relaxed);
                 thread::sleep(Duration::from_millis(100));
             }
             match subcommands {
-                Some(AuditSubcommand::Write { output, delimiter }) => {
-                    let _ = ar.to_file(output, *delimiter);
-                } // NOTE: might add Json and Exit
+                Some(AuditSubcommand::Write {
+                    output,
+                    delimiter,
+                    format,
+                }) => {
+                    write_report(&ar, output, *delimiter, *format)?;
+                }
+                Some(AuditSubcommand::Json) => {
+                    ar.to_json_stdout()?;
+                } // NOTE: might add Exit
+                Some(AuditSubcommand::Sarif { output, bound }) => {
+                    ar.to_sarif(bound).to_file(output)?;
+                }
                 Some(AuditSubcommand::Display) | None => {
                     // default
-                    let _ = ar.to_stdout();
+                    display_report(&ar, cli.output.as_ref())?;
                     process::exit(if ar.len() > 0 { ERROR_EXIT_CODE } else { 0 });
                 }
             }
         }
+        Some(Commands::Outdated {
+            subcommands,
+            pattern,
+            case,
+            index_cache_duration,
+        }) => {
+            let index_cache_dur = if cli.no_cache {
+                DURATION_0
+            } else {
+                Duration::from_secs(*index_cache_duration)
+            };
+            // network look makes this potentially slow
+            let active = Arc::new(AtomicBool::new(true));
+            if !quiet {
+                spin(active.clone(), "index searching".to_string());
+            }
+            let or = sfs.to_outdated_report(pattern, !case, index_cache_dur);
+            if !quiet {
+                active.store(false, Ordering::Relaxed);
+                thread::sleep(Duration::from_millis(100));
+            }
+            match subcommands {
+                Some(OutdatedSubcommand::Write {
+                    output,
+                    delimiter,
+                    format,
+                }) => {
+                    write_report(&or, output, *delimiter, *format)?;
+                }
+                Some(OutdatedSubcommand::Json) => {
+                    or.to_json_stdout()?;
+                }
+                Some(OutdatedSubcommand::Display) | None => {
+                    display_report(&or, cli.output.as_ref())?;
+                }
+            }
+        }
+        Some(Commands::Interpreters { subcommands }) => {
+            let ir = sfs.to_interpreter_report();
+            match subcommands {
+                Some(InterpretersSubcommand::Write {
+                    output,
+                    delimiter,
+                    format,
+                }) => {
+                    write_report(&ir, output, *delimiter, *format)?;
+                }
+                Some(InterpretersSubcommand::Json) => {
+                    ir.to_json_stdout()?;
+                }
+                Some(InterpretersSubcommand::Display) | None => {
+                    display_report(&ir, cli.output.as_ref())?;
+                }
+            }
+        }
+        Some(Commands::Check { subcommands }) => {
+            let cr = sfs.to_check_report();
+            match subcommands {
+                Some(CheckSubcommand::Write {
+                    output,
+                    delimiter,
+                    format,
+                }) => {
+                    write_report(&cr, output, *delimiter, *format)?;
+                }
+                Some(CheckSubcommand::Json) => {
+                    cr.to_json_stdout()?;
+                }
+                Some(CheckSubcommand::Display) | None => {
+                    // default
+                    display_report(&cr, cli.output.as_ref())?;
+                    process::exit(if cr.len() > 0 { ERROR_EXIT_CODE } else { 0 });
+                }
+            }
+        }
+        Some(Commands::Map {
+            subcommands,
+            module,
+            package,
+            case,
+            bound,
+        }) => {
+            let dm = if bound.is_empty() {
+                None
+            } else {
+                Some(get_dep_manifest(bound, &headers, None, true, false)?)
+            };
+            let mr = sfs.to_map_report(module, package, !case, dm.as_ref());
+            match subcommands {
+                Some(MapSubcommand::Write {
+                    output,
+                    delimiter,
+                    format,
+                }) => {
+                    write_report(&mr, output, *delimiter, *format)?;
+                }
+                Some(MapSubcommand::Json) => {
+                    mr.to_json_stdout()?;
+                }
+                Some(MapSubcommand::Display) | None => {
+                    // default
+                    display_report(&mr, cli.output.as_ref())?;
+                }
+            }
+        }
+        Some(Commands::Tree {
+            subcommands,
+            package,
+            case,
+            depth,
+        }) => {
+            let tr = sfs.to_tree_report(package.as_deref(), !case, *depth);
+            match subcommands {
+                Some(TreeSubcommand::Write {
+                    output,
+                    delimiter,
+                    format,
+                }) => {
+                    write_report(&tr, output, *delimiter, *format)?;
+                }
+                Some(TreeSubcommand::Json) => {
+                    tr.to_json_stdout()?;
+                }
+                Some(TreeSubcommand::Display) | None => {
+                    // default
+                    display_report(&tr, cli.output.as_ref())?;
+                }
+            }
+        }
+        Some(Commands::Verify {
+            subcommands,
+            pattern,
+            case,
+        }) => {
+            let vr = sfs.to_verify_report(pattern, !case);
+            match subcommands {
+                Some(VerifySubcommand::Write {
+                    output,
+                    delimiter,
+                    format,
+                }) => {
+                    write_report(&vr, output, *delimiter, *format)?;
+                }
+                Some(VerifySubcommand::Json) => {
+                    vr.to_json_stdout()?;
+                }
+                Some(VerifySubcommand::Display) | None => {
+                    // default
+                    display_report(&vr, cli.output.as_ref())?;
+                    process::exit(if vr.len() > 0 { ERROR_EXIT_CODE } else { 0 });
+                }
+            }
+        }
+        Some(Commands::Scripts {
+            subcommands,
+            pattern,
+            case,
+        }) => {
+            let sr = sfs.to_scripts_report(pattern, !case)?;
+            match subcommands {
+                Some(ScriptsSubcommand::Write {
+                    output,
+                    delimiter,
+                    format,
+                }) => {
+                    write_report(&sr, output, *delimiter, *format)?;
+                }
+                Some(ScriptsSubcommand::Json) => {
+                    sr.to_json_stdout()?;
+                }
+                Some(ScriptsSubcommand::Display) | None => {
+                    // default
+                    display_report(&sr, cli.output.as_ref())?;
+                    process::exit(if sr.len() > 0 { ERROR_EXIT_CODE } else { 0 });
+                }
+            }
+        }
         Some(Commands::UnpackCount {
             subcommands,
             pattern,
@@ -530,12 +2246,22 @@ where
             let count = true;
             let ir = sfs.to_unpack_report(pattern, !case, count);
             match subcommands {
-                Some(UnpackCountSubcommand::Write { output, delimiter }) => {
-                    let _ = ir.to_file(output, *delimiter);
+                Some(UnpackCountSubcommand::Write {
+                    output,
+                    delimiter,
+                    format,
+                }) => {
+                    ir.to_file_format(output, format.to_table_format(*delimiter))?;
+                }
+                Some(UnpackCountSubcommand::Json) => {
+                    ir.to_json_stdout()?;
                 }
                 Some(UnpackCountSubcommand::Display) | None => {
                     // default
-                    let _ = ir.to_stdout();
+                    match cli.output.as_ref() {
+                        Some(output) => ir.to_output(output),
+                        None => ir.to_stdout(),
+                    }?;
                 }
             }
         }
@@ -547,45 +2273,226 @@ where
             let count = false;
             let ir = sfs.to_unpack_report(pattern, !case, count);
             match subcommands {
-                Some(UnpackFilesSubcommand::Write { output, delimiter }) => {
-                    let _ = ir.to_file(output, *delimiter);
+                Some(UnpackFilesSubcommand::Write {
+                    output,
+                    delimiter,
+                    format,
+                }) => {
+                    ir.to_file_format(output, format.to_table_format(*delimiter))?;
+                }
+                Some(UnpackFilesSubcommand::Json) => {
+                    ir.to_json_stdout()?;
+                }
+                Some(UnpackFilesSubcommand::Display) | None => {
+                    // default
+                    match cli.output.as_ref() {
+                        Some(output) => ir.to_output(output),
+                        None => ir.to_stdout(),
+                    }?;
+                }
+            }
+        }
+        Some(Commands::Size {
+            subcommands,
+            pattern,
+            case,
+            by_site,
+        }) => {
+            let sr = sfs.to_size_report(pattern, !case, *by_site);
+            match subcommands {
+                Some(SizeSubcommand::Write {
+                    output,
+                    delimiter,
+                    format,
+                }) => {
+                    write_report(&sr, output, *delimiter, *format)?;
+                }
+                Some(SizeSubcommand::Json) => {
+                    sr.to_json_stdout()?;
+                }
+                Some(SizeSubcommand::Display) | None => {
+                    // default
+                    display_report(&sr, cli.output.as_ref())?;
+                }
+            }
+        }
+        Some(Commands::Files {
+            subcommands,
+            package,
+            case,
+            site,
+        }) => {
+            let fr = sfs.to_files_report(package, !case, site.as_deref());
+            match subcommands {
+                Some(UnpackFilesSubcommand::Write {
+                    output,
+                    delimiter,
+                    format,
+                }) => {
+                    write_report(&fr, output, *delimiter, *format)?;
+                }
+                Some(UnpackFilesSubcommand::Json) => {
+                    fr.to_json_stdout()?;
                 }
                 Some(UnpackFilesSubcommand::Display) | None => {
                     // default
-                    let _ = ir.to_stdout();
+                    display_report(&fr, cli.output.as_ref())?;
+                }
+            }
+        }
+        Some(Commands::Strays { subcommands, site }) => {
+            let sr = sfs.to_stray_report(site.as_deref());
+            match subcommands {
+                Some(StraysSubcommand::Write {
+                    output,
+                    delimiter,
+                    format,
+                }) => {
+                    write_report(&sr, output, *delimiter, *format)?;
+                }
+                Some(StraysSubcommand::Json) => {
+                    sr.to_json_stdout()?;
+                }
+                Some(StraysSubcommand::Purge { yes }) => {
+                    purge_strays(&sr, *yes, !quiet)?;
+                }
+                Some(StraysSubcommand::Display) | None => {
+                    // default
+                    display_report(&sr, cli.output.as_ref())?;
+                }
+            }
+        }
+        Some(Commands::Doctor { subcommands }) => {
+            let dr = sfs.to_doctor_report()?;
+            match subcommands {
+                Some(DoctorSubcommand::Write {
+                    output,
+                    delimiter,
+                    format,
+                }) => {
+                    write_report(&dr, output, *delimiter, *format)?;
+                }
+                Some(DoctorSubcommand::Json) => {
+                    dr.to_json_stdout()?;
+                }
+                Some(DoctorSubcommand::Display) | None => {
+                    // default
+                    display_report(&dr, cli.output.as_ref())?;
+                    process::exit(if dr.len() > 0 { ERROR_EXIT_CODE } else { 0 });
                 }
             }
         }
-        Some(Commands::PurgePattern { pattern, case }) => {
-            let _ = sfs.to_purge_pattern(pattern, !case, !quiet);
+        Some(Commands::PurgePattern {
+            pattern,
+            case,
+            dry_run,
+            yes,
+        }) => {
+            let ur = sfs.to_purge_pattern_report(pattern, !case);
+            purge(&ur, *dry_run, *yes, !quiet)?;
         }
         Some(Commands::PurgeInvalid {
             bound,
             bound_options,
+            no_env_expand,
             subset,
             superset,
+            dry_run,
+            yes,
         }) => {
-            let dm = get_dep_manifest(bound, bound_options.as_ref())?;
+            let dm = get_dep_manifest(
+                bound,
+                &headers,
+                bound_options.as_ref(),
+                !*no_env_expand,
+                false,
+            )?;
             let permit_superset = *superset;
             let permit_subset = *subset;
-            let _ = sfs.to_purge_invalid(
+            let ur = sfs.to_purge_invalid_report(
                 dm,
-                ValidationFlags {
-                    permit_superset,
-                    permit_subset,
-                },
-                !quiet,
+                ValidationFlags::default()
+                    .with_permit_superset(permit_superset)
+                    .with_permit_subset(permit_subset),
             );
+            purge(&ur, *dry_run, *yes, !quiet)?;
+        }
+        Some(Commands::Watch {
+            bound,
+            bound_options,
+            no_env_expand,
+            subset,
+            superset,
+            interval,
+        }) => {
+            let vf = ValidationFlags::default()
+                .with_permit_superset(*superset)
+                .with_permit_subset(*subset);
+            if bound.iter().any(|b| b.as_os_str() == "-") {
+                return Err("Cannot watch a bound read from stdin".into());
+            }
+            let mut sfs = sfs;
+            let mut vd_prior: Option<ValidationDigest> = None;
+            loop {
+                match get_dep_manifest(
+                    bound,
+                    &headers,
+                    bound_options.as_ref(),
+                    !*no_env_expand,
+                    false,
+                ) {
+                    Ok(dm) => {
+                        let vr = sfs.to_validation_report(dm, vf);
+                        let vd = vr.to_validation_digest();
+                        match &vd_prior {
+                            Some(prior) => {
+                                for line in validation_digest_diff(prior, &vd) {
+                                    println!("{}", line);
+                                }
+                            }
+                            None => {
+                                vr.to_stdout()?;
+                            }
+                        }
+                        vd_prior = Some(vd);
+                    }
+                    Err(e) => warn!("Failed to read bound: {}", e),
+                }
+                // wait for notification of changes to sites or local bound files
+                let mut watched: Vec<PathBuf> = sfs
+                    .get_sites()
+                    .iter()
+                    .map(|s| s.as_path().to_path_buf())
+                    .collect();
+                watched.extend(bound.iter().cloned());
+                path_wait_for_change(&watched, Duration::from_secs(*interval))?;
+                // scans for revalidation must not use the cache
+                sfs = get_scan(
+                    &cli.exe,
+                    &cli.scan_root,
+                    cli.root.as_ref(),
+                    cli.static_sites,
+                    cli.user_site,
+                    cli.no_user_site,
+                    false,
+                    DURATION_0,
+                )?;
+            }
         }
-        None => {}
+        Some(Commands::Cache { .. })
+        | Some(Commands::Completions { .. })
+        | Some(Commands::Man { .. })
+        | Some(Commands::Diff { .. })
+        | None => {}
     }
     Ok(())
 }
 
 //-----------------------------------------------------------------------------
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison, clippy::useless_vec)]
 mod tests {
-    // use super::*;
+    use super::*;
     use std::ffi::OsString;
 
     #[test]
@@ -593,4 +2500,241 @@ mod tests {
         let _args = vec![OsString::from("fetter"), OsString::from("-h")];
         // run_cli(args); // print to stdout
     }
+
+    #[test]
+    fn test_run_cli_b() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("missing").join("out.csv");
+        let args = vec![
+            OsString::from("fetter"),
+            OsString::from("--no-config"),
+            OsString::from("--no-cache"),
+            OsString::from("--quiet"),
+            OsString::from("-e"),
+            OsString::from("python3"),
+            OsString::from("-o"),
+            output.into_os_string(),
+            OsString::from("scan"),
+        ];
+        // a report that cannot be written is an error naming the file
+        let e = run_cli(args).unwrap_err();
+        assert!(e.to_string().starts_with("Failed to write"));
+        assert!(e.to_string().contains("out.csv"));
+    }
+
+    #[test]
+    fn test_is_confirmed_a() {
+        assert_eq!(is_confirmed("y\n"), true);
+        assert_eq!(is_confirmed(" YES \n"), true);
+        assert_eq!(is_confirmed("\n"), false);
+        assert_eq!(is_confirmed("no\n"), false);
+    }
+
+    #[test]
+    fn test_validation_exit_code_a() {
+        assert_eq!(validation_exit_code(0, 3, 1, false, false), 0);
+        assert_eq!(validation_exit_code(2, 3, 1, false, false), 3);
+        assert_eq!(validation_exit_code(2, 5, 1, false, false), 5);
+        assert_eq!(validation_exit_code(2, 3, 3, false, false), 0);
+        assert_eq!(validation_exit_code(3, 3, 3, false, false), 3);
+        assert_eq!(validation_exit_code(2, 3, 1, true, false), 0);
+        assert_eq!(validation_exit_code(2, 3, 1, false, true), 0);
+    }
+
+    #[test]
+    fn test_get_explains_a() {
+        let explains =
+            get_explains(&["v003".to_string(), "integrity".to_string()]).unwrap();
+        assert_eq!(
+            explains,
+            vec![
+                ValidationExplain::Misdefined,
+                ValidationExplain::HashMismatch,
+                ValidationExplain::HashUnverifiable,
+                ValidationExplain::Drifted,
+            ]
+        );
+        assert!(get_explains(&["V999".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_config_to_args_a() {
+        let config = Config::from_str(
+            "no-cache = true\n\n[validate]\nbound = [\"requirements.txt\"]\nexact = true\nignore = [\"V002\"]\nsubcommand = \"json\"\n",
+            Path::new("/repo"),
+        )
+        .unwrap();
+        let args = config
+            .to_args(
+                &Cli::command(),
+                vec![OsString::from("fetter"), OsString::from("validate")],
+            )
+            .unwrap();
+        let cli = Cli::try_parse_from(args).unwrap();
+        assert!(cli.no_cache);
+        match cli.command {
+            Some(Commands::Validate {
+                bound,
+                exact,
+                ignore,
+                subcommands,
+                ..
+            }) => {
+                assert_eq!(bound, vec![PathBuf::from("/repo/requirements.txt")]);
+                assert!(exact);
+                assert_eq!(ignore, Some(vec!["V002".to_string()]));
+                assert!(matches!(subcommands, Some(ValidateSubcommand::Json)));
+            }
+            _ => panic!("expected validate"),
+        }
+
+        // a conflicting option on the command line takes precedence
+        let args = config
+            .to_args(
+                &Cli::command(),
+                vec![
+                    OsString::from("fetter"),
+                    OsString::from("validate"),
+                    OsString::from("--superset"),
+                ],
+            )
+            .unwrap();
+        assert!(Cli::try_parse_from(args).is_ok());
+    }
+
+    #[test]
+    fn test_cli_output_a() {
+        let cli = Cli::try_parse_from(["fetter", "-o", "report.md", "scan"]).unwrap();
+        assert_eq!(cli.output, Some(PathBuf::from("report.md")));
+        // the output of a write subcommand is distinct from the global output
+        let cli =
+            Cli::try_parse_from(["fetter", "scan", "write", "-o", "report.csv"]).unwrap();
+        assert_eq!(cli.output, None);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Scan {
+                subcommands: Some(ScanSubcommand::Write {
+                    format: CliFormat::Delimited,
+                    ..
+                }),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_cli_pre_a() {
+        let cli =
+            Cli::try_parse_from(["fetter", "validate", "--bound", "requirements.txt"])
+                .unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Validate { pre: false, .. })
+        ));
+        let cli = Cli::try_parse_from([
+            "fetter",
+            "validate",
+            "--bound",
+            "requirements.txt",
+            "--pre",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Validate { pre: true, .. })
+        ));
+        assert!(Cli::try_parse_from([
+            "fetter",
+            "validate",
+            "--bound",
+            "requirements.txt",
+            "--no-pre",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_cli_columns_a() {
+        let cli = Cli::try_parse_from([
+            "fetter",
+            "scan",
+            "--columns",
+            "name,version,site",
+            "--sort",
+            "version:desc",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Commands::Scan { columns, sort, .. }) => {
+                assert_eq!(
+                    columns,
+                    Some(vec![
+                        "name".to_string(),
+                        "version".to_string(),
+                        "site".to_string()
+                    ])
+                );
+                assert_eq!(sort, Some("version:desc".to_string()));
+            }
+            _ => panic!("expected scan"),
+        }
+    }
+
+    #[test]
+    fn test_completions_a() {
+        let mut buffer = Vec::new();
+        clap_complete::generate(Shell::Bash, &mut Cli::command(), "fetter", &mut buffer);
+        let script = String::from_utf8(buffer).unwrap();
+        assert!(script.contains("validate"));
+        assert!(script.contains("--bound"));
+    }
+
+    #[test]
+    fn test_write_man_pages_a() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("man1");
+        write_man_pages(&out).unwrap();
+        assert!(out.join("fetter.1").is_file());
+        let page = fs::read_to_string(out.join("fetter-validate.1")).unwrap();
+        assert!(page.contains(".TH"));
+        assert!(page.contains("bound"));
+    }
+
+    #[test]
+    fn test_get_bound_headers_a() {
+        let headers =
+            get_bound_headers(&["Authorization: Bearer a:b".to_string()]).unwrap();
+        assert_eq!(
+            headers,
+            vec![("Authorization".to_string(), "Bearer a:b".to_string())]
+        );
+        assert!(get_bound_headers(&["Authorization".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_get_dep_manifest_a() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("base.txt");
+        let prod = dir.path().join("prod.txt");
+        let other = dir.path().join("other.txt");
+        fs::write(&base, "numpy>=1.26\nsix\n").unwrap();
+        fs::write(&prod, "numpy<2\ngunicorn\n").unwrap();
+        fs::write(&other, "numpy>=2\n").unwrap();
+
+        let dm = get_dep_manifest(&[base.clone(), prod], &[], None, true, false).unwrap();
+        assert_eq!(dm.len(), 3);
+        assert_eq!(
+            dm.get_dep_spec("numpy").unwrap().to_string(),
+            "numpy>=1.26,<2"
+        );
+
+        let dm = get_dep_manifest(
+            &[base, dir.path().join("prod.txt"), other],
+            &[],
+            None,
+            true,
+            false,
+        );
+        assert!(dm.is_err());
+    }
 }
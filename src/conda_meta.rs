@@ -0,0 +1,182 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use crate::package::Package;
+use crate::util::ResultDynError;
+
+// see https://docs.conda.io/projects/conda-build/en/latest/resources/package-spec.html
+
+const CONDA_META: &str = "conda-meta";
+
+/// Return true if this path is a conda-meta directory, which is used as the site of conda-installed packages.
+pub(crate) fn is_conda_meta(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == CONDA_META)
+}
+
+/// Given a path to a Python executable, return the conda-meta directory of its environment, if it exists. On Windows the executable is in the environment root; elsewhere it is in `bin`.
+pub(crate) fn exe_to_conda_meta(executable: &Path) -> Option<PathBuf> {
+    let dir = executable.parent()?;
+    let candidates = [Some(dir), dir.parent()];
+    candidates
+        .into_iter()
+        .flatten()
+        .map(|prefix| prefix.join(CONDA_META))
+        .find(|path| path.is_dir())
+}
+
+// Given a file path relative to the environment prefix, return the portion within a site-packages directory.
+fn to_site_relative(file: &str) -> Option<&str> {
+    file.split_once("site-packages/").map(|(_, rel)| rel)
+}
+
+//------------------------------------------------------------------------------
+/// A record of a package installed by conda, as stored in a JSON file in conda-meta.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct CondaRecord {
+    pub(crate) name: String,
+    pub(crate) version: String,
+    #[serde(default)]
+    files: Vec<String>,
+}
+
+impl CondaRecord {
+    pub(crate) fn from_str(content: &str) -> ResultDynError<Self> {
+        let record: CondaRecord = serde_json::from_str(content)?;
+        Ok(record)
+    }
+
+    /// Read all records from a conda-meta directory, sorted by name. Files that cannot be read as records are ignored.
+    pub(crate) fn from_conda_meta(conda_meta: &Path) -> Vec<Self> {
+        let mut records = Vec::new();
+        if let Ok(entries) = fs::read_dir(conda_meta) {
+            for entry in entries.flatten() {
+                let file_path = entry.path();
+                if file_path.extension().is_some_and(|ext| ext == "json") {
+                    if let Ok(content) = fs::read_to_string(&file_path) {
+                        if let Ok(record) = Self::from_str(&content) {
+                            records.push(record);
+                        }
+                    }
+                }
+            }
+        }
+        records.sort_by(|a, b| a.name.cmp(&b.name));
+        records
+    }
+
+    //--------------------------------------------------------------------------
+
+    /// Return true if this record installs files into site-packages. Non-Python dependencies (such as compiled libraries) and the interpreter itself are excluded.
+    pub(crate) fn is_python_package(&self) -> bool {
+        self.files
+            .iter()
+            .filter_map(|f| to_site_relative(f))
+            .any(|rel| {
+                rel.contains('/')
+                    || rel.ends_with(".py")
+                    || rel.ends_with(".pth")
+                    || rel.ends_with(".so")
+                    || rel.ends_with(".pyd")
+            })
+    }
+
+    /// If this record installed a dist-info directory, return the Package it describes. As conda and PyPI names can differ, this is the reliable way to relate a record to a Package found in site-packages.
+    pub(crate) fn to_dist_info_package(&self) -> Option<Package> {
+        let mut names: HashSet<&str> = HashSet::new();
+        for rel in self.files.iter().filter_map(|f| to_site_relative(f)) {
+            if let Some((dir, _)) = rel.split_once('/') {
+                if dir.ends_with(".dist-info") {
+                    names.insert(dir);
+                }
+            }
+        }
+        // a record should not install more than one dist-info; if so, the relation is ambiguous
+        if names.len() == 1 {
+            let name = names.into_iter().next()?;
+            Package::from_dist_info(name, None, None)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn to_package(&self) -> Option<Package> {
+        Package::from_name_version_durl(&self.name, &self.version, None)
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const RECORD_PYYAML: &str = r#"{
+  "build": "py311h2dc5d0c_1",
+  "channel": "https://conda.anaconda.org/conda-forge/linux-64",
+  "depends": ["python >=3.11,<3.12.0a0", "yaml >=0.2.5,<0.3.0a0"],
+  "files": [
+    "lib/python3.11/site-packages/PyYAML-6.0.2.dist-info/INSTALLER",
+    "lib/python3.11/site-packages/PyYAML-6.0.2.dist-info/METADATA",
+    "lib/python3.11/site-packages/yaml/__init__.py"
+  ],
+  "name": "pyyaml",
+  "version": "6.0.2"
+}"#;
+
+    #[test]
+    fn test_from_str_a() {
+        let record = CondaRecord::from_str(RECORD_PYYAML).unwrap();
+        assert_eq!(record.name, "pyyaml");
+        assert_eq!(record.version, "6.0.2");
+        assert!(record.is_python_package());
+        assert_eq!(
+            record.to_dist_info_package().unwrap().to_string(),
+            "PyYAML-6.0.2"
+        );
+        assert_eq!(record.to_package().unwrap().to_string(), "pyyaml-6.0.2");
+    }
+
+    #[test]
+    fn test_is_python_package_a() {
+        let record = CondaRecord::from_str(
+            r#"{"name": "openssl", "version": "3.3.2", "files": ["lib/libssl.so.3", "include/openssl/ssl.h"]}"#,
+        )
+        .unwrap();
+        assert!(!record.is_python_package());
+        assert!(record.to_dist_info_package().is_none());
+
+        let record = CondaRecord::from_str(
+            r#"{"name": "python", "version": "3.11.10", "files": ["bin/python3.11", "lib/python3.11/site-packages/README.txt"]}"#,
+        )
+        .unwrap();
+        assert!(!record.is_python_package());
+    }
+
+    #[test]
+    fn test_from_conda_meta_a() {
+        let dir = tempdir().unwrap();
+        let fp_bin = dir.path().join("bin");
+        fs::create_dir(&fp_bin).unwrap();
+        let fp_cm = dir.path().join("conda-meta");
+        fs::create_dir(&fp_cm).unwrap();
+        fs::write(
+            fp_cm.join("pyyaml-6.0.2-py311h2dc5d0c_1.json"),
+            RECORD_PYYAML,
+        )
+        .unwrap();
+        fs::write(fp_cm.join("history"), "==> 2024-10-14 <==").unwrap();
+        fs::write(fp_cm.join("broken-1.0-0.json"), "{").unwrap();
+
+        let cm = exe_to_conda_meta(&fp_bin.join("python")).unwrap();
+        assert!(is_conda_meta(&cm));
+        let records = CondaRecord::from_conda_meta(&cm);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].name, "pyyaml");
+
+        assert!(exe_to_conda_meta(Path::new("/python")).is_none());
+    }
+}
@@ -0,0 +1,444 @@
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use clap::Arg;
+use clap::Command;
+
+use crate::util::ResultDynError;
+
+// The name of the file, in the current directory, from which defaults are read; if not found, defaults are read from `[tool.fetter]` of pyproject.toml.
+const CONFIG_FILE: &str = "fetter.toml";
+
+// Keys of `[tool.fetter]` that are not options, such as the `[[tool.fetter.ignore]]` tables of waivers.
+const RESERVED_KEYS: [&str; 1] = ["ignore"];
+
+// The key of a command table that provides the subcommand, and its options, used if none is provided.
+const SUBCOMMAND_KEY: &str = "subcommand";
+
+// Return true if `arg` was provided in `tokens`, by its long name, with or without an `=` value, or by its short name.
+fn is_provided(arg: &Arg, tokens: &[&str]) -> bool {
+    tokens.iter().any(|t| {
+        let long = arg.get_long().is_some_and(|long| {
+            t.strip_prefix("--").is_some_and(|post| {
+                post == long
+                    || post.strip_prefix(long).is_some_and(|v| v.starts_with('='))
+            })
+        });
+        let short = arg.get_short().is_some_and(|short| {
+            !t.starts_with("--")
+                && t.strip_prefix('-')
+                    .is_some_and(|post| post.starts_with(short))
+        });
+        long || short
+    })
+}
+
+// Return true if an argument that conflicts with `arg`, in either direction, was provided in `tokens`.
+fn is_conflicted(command: &Command, arg: &Arg, tokens: &[&str]) -> bool {
+    command.get_arguments().any(|other| {
+        other.get_id() != arg.get_id()
+            && is_provided(other, tokens)
+            && (command
+                .get_arg_conflicts_with(arg)
+                .iter()
+                .any(|a| a.get_id() == other.get_id())
+                || command
+                    .get_arg_conflicts_with(other)
+                    .iter()
+                    .any(|a| a.get_id() == arg.get_id()))
+    })
+}
+
+// Return true if `arg` takes a value, such that the token after its name is its value.
+fn takes_value(arg: &Arg) -> bool {
+    arg.get_num_args().is_some_and(|n| n.takes_values())
+        || arg.get_action().takes_values()
+}
+
+// Given the tokens after the program name, return the index of the first token that names a subcommand of `command`, skipping options and their values.
+fn find_subcommand(command: &Command, tokens: &[&str]) -> Option<usize> {
+    let mut i = 0;
+    while i < tokens.len() {
+        let t = tokens[i];
+        let arg = if let Some(long) = t.strip_prefix("--") {
+            command
+                .get_arguments()
+                .find(|a| !long.contains('=') && a.get_long() == Some(long))
+        } else if let Some(short) = t.strip_prefix('-') {
+            let mut chars = short.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => {
+                    command.get_arguments().find(|a| a.get_short() == Some(c))
+                }
+                _ => None,
+            }
+        } else {
+            return command.find_subcommand(t).map(|_| i);
+        };
+        if arg.is_some_and(takes_value) {
+            i += 1;
+        }
+        i += 1;
+    }
+    None
+}
+
+//------------------------------------------------------------------------------
+/// Default options read from a `fetter.toml`, or the `[tool.fetter]` table of a pyproject.toml. Top-level keys are global options, and tables, named by command (e.g. `[tool.fetter.validate]`), provide the options of that command. Keys are the long names of options; a value of `true` provides a flag, and an array provides an option for each value. Options provided on the command line take precedence.
+#[derive(Debug, Clone)]
+pub(crate) struct Config {
+    table: toml::Table,
+    /// The directory of the config file, to which relative file and directory paths are joined.
+    dir: PathBuf,
+}
+
+impl Config {
+    pub(crate) fn from_str(content: &str, dir: &Path) -> ResultDynError<Self> {
+        Ok(Config {
+            table: toml::from_str(content)?,
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    /// Read the `[tool.fetter]` table of a pyproject.toml, if defined.
+    pub(crate) fn from_pyproject(
+        content: &str,
+        dir: &Path,
+    ) -> ResultDynError<Option<Self>> {
+        let mut table: toml::Table = toml::from_str(content)?;
+        let fetter = table.remove("tool").and_then(|tool| match tool {
+            toml::Value::Table(mut tool) => tool.remove("fetter"),
+            _ => None,
+        });
+        match fetter {
+            Some(toml::Value::Table(table)) => Ok(Some(Config {
+                table,
+                dir: dir.to_path_buf(),
+            })),
+            Some(_) => Err("Invalid config: [tool.fetter] must be a table".into()),
+            None => Ok(None),
+        }
+    }
+
+    /// Read a `fetter.toml` in `dir`, or, if not found, the `[tool.fetter]` table of a pyproject.toml in `dir`. If neither is found, None is returned.
+    pub(crate) fn from_dir(dir: &Path) -> ResultDynError<Option<Self>> {
+        let fp = dir.join(CONFIG_FILE);
+        if fp.is_file() {
+            let content = fs::read_to_string(&fp)?;
+            return Self::from_str(&content, dir)
+                .map(Some)
+                .map_err(|e| format!("Invalid config {}: {}", fp.display(), e).into());
+        }
+        let fp = dir.join("pyproject.toml");
+        if fp.is_file() {
+            let content = fs::read_to_string(&fp)?;
+            return Self::from_pyproject(&content, dir)
+                .map_err(|e| format!("Invalid config {}: {}", fp.display(), e).into());
+        }
+        Ok(None)
+    }
+
+    // Given an option of `command` and its configured value, return arguments; paths of options with `FILE` or `DIR` values are joined to the directory of the config file.
+    fn value_to_args(
+        &self,
+        command: &Command,
+        key: &str,
+        value: &toml::Value,
+    ) -> ResultDynError<Vec<OsString>> {
+        let arg = command
+            .get_arguments()
+            .find(|a| a.get_long() == Some(key))
+            .ok_or_else(|| {
+                format!(
+                    "Unknown option in config for {}: {}",
+                    command.get_name(),
+                    key
+                )
+            })?;
+        let is_path = arg.get_value_names().is_some_and(|names| {
+            names
+                .iter()
+                .any(|n| n.as_str() == "FILE" || n.as_str() == "DIR")
+        });
+        let flag = OsString::from(format!("--{}", key));
+        let values: Vec<&toml::Value> = match value {
+            toml::Value::Array(values) => values.iter().collect(),
+            toml::Value::Boolean(true) => return Ok(vec![flag]),
+            toml::Value::Boolean(false) => return Ok(Vec::new()),
+            value => vec![value],
+        };
+        let mut args = Vec::new();
+        for value in values {
+            let value = match value {
+                toml::Value::String(s) => s.clone(),
+                toml::Value::Integer(i) => i.to_string(),
+                toml::Value::Float(f) => f.to_string(),
+                _ => {
+                    return Err(format!(
+                        "Invalid value in config for {} --{}: {}",
+                        command.get_name(),
+                        key,
+                        value
+                    )
+                    .into())
+                }
+            };
+            // URLs, git repositories, and stdin are not paths
+            let value = if is_path && value != "-" && !value.contains(':') {
+                self.dir.join(&value).into_os_string()
+            } else {
+                OsString::from(value)
+            };
+            args.push(flag.clone());
+            args.push(value);
+        }
+        Ok(args)
+    }
+
+    // Given a table of options of `command`, return arguments for those options not provided, or conflicting with options provided, in `tokens`. Tables, used for commands, and `reserved` keys are skipped.
+    fn table_to_args(
+        &self,
+        command: &Command,
+        table: &toml::Table,
+        tokens: &[&str],
+        reserved: &[&str],
+    ) -> ResultDynError<Vec<OsString>> {
+        let mut args = Vec::new();
+        for (key, value) in table {
+            if value.is_table() || reserved.contains(&key.as_str()) {
+                continue;
+            }
+            let arg = command.get_arguments().find(|a| a.get_long() == Some(key));
+            if arg.is_some_and(|a| {
+                is_provided(a, tokens) || is_conflicted(command, a, tokens)
+            }) {
+                continue;
+            }
+            args.extend(self.value_to_args(command, key, value)?);
+        }
+        Ok(args)
+    }
+
+    /// Given command-line arguments, including the program name, return arguments with configured options inserted: global options after the program name, and options of the command after the command name. If a command table defines a `subcommand`, it is appended if no subcommand is provided.
+    pub(crate) fn to_args(
+        &self,
+        command: &Command,
+        args: Vec<OsString>,
+    ) -> ResultDynError<Vec<OsString>> {
+        // arguments that are not UTF-8 cannot be matched
+        let tokens: Vec<&str> = match args.iter().skip(1).map(|a| a.to_str()).collect() {
+            Some(tokens) => tokens,
+            None => return Ok(args),
+        };
+        let pos = match find_subcommand(command, &tokens) {
+            Some(pos) => pos,
+            // without a command, such as with `--help`, nothing is inserted
+            None => return Ok(args),
+        };
+        for (key, value) in &self.table {
+            if value.is_table() && command.find_subcommand(key).is_none() {
+                return Err(format!("Unknown command in config: {}", key).into());
+            }
+        }
+        let sub = command
+            .find_subcommand(tokens[pos])
+            .ok_or("Unknown command")?;
+        let (tokens_global, tokens_sub) = (&tokens[..pos], &tokens[pos + 1..]);
+
+        let mut out: Vec<OsString> = args[..1].to_vec();
+        out.extend(self.table_to_args(
+            command,
+            &self.table,
+            tokens_global,
+            &RESERVED_KEYS,
+        )?);
+        out.extend(args[1..pos + 2].iter().cloned());
+        let table_sub = self.table.get(sub.get_name()).and_then(|v| v.as_table());
+        if let Some(table_sub) = table_sub {
+            out.extend(self.table_to_args(
+                sub,
+                table_sub,
+                tokens_sub,
+                &[SUBCOMMAND_KEY],
+            )?);
+        }
+        out.extend(args[pos + 2..].iter().cloned());
+        if let Some(subcommand) = table_sub.and_then(|t| t.get(SUBCOMMAND_KEY)) {
+            if find_subcommand(sub, tokens_sub).is_none() {
+                let parts: Vec<&toml::Value> = match subcommand {
+                    toml::Value::Array(parts) => parts.iter().collect(),
+                    part => vec![part],
+                };
+                for part in parts {
+                    let part = part.as_str().ok_or_else(|| {
+                        format!("Invalid subcommand in config for {}", sub.get_name())
+                    })?;
+                    out.push(OsString::from(part));
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn to_command() -> Command {
+        Command::new("fetter")
+            .arg(Arg::new("exe").long("exe").short('e'))
+            .arg(
+                Arg::new("quiet")
+                    .long("quiet")
+                    .short('q')
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .subcommand(
+                Command::new("validate")
+                    .arg(
+                        Arg::new("bound")
+                            .long("bound")
+                            .short('b')
+                            .value_name("FILE"),
+                    )
+                    .arg(
+                        Arg::new("superset")
+                            .long("superset")
+                            .action(clap::ArgAction::SetTrue),
+                    )
+                    .arg(
+                        Arg::new("exact")
+                            .long("exact")
+                            .action(clap::ArgAction::SetTrue)
+                            .conflicts_with("superset"),
+                    )
+                    .subcommand(Command::new("json"))
+                    .subcommand(Command::new("display")),
+            )
+    }
+
+    fn to_strings(args: Vec<OsString>) -> Vec<String> {
+        args.into_iter()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect()
+    }
+
+    fn to_os_strings(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_find_subcommand_a() {
+        let command = to_command();
+        assert_eq!(
+            find_subcommand(&command, &["-e", "python3", "validate"]),
+            Some(2)
+        );
+        assert_eq!(
+            find_subcommand(&command, &["--exe=python3", "-q", "validate"]),
+            Some(2)
+        );
+        assert_eq!(find_subcommand(&command, &["--help"]), None);
+    }
+
+    #[test]
+    fn test_config_from_pyproject_a() {
+        let dir = Path::new("/repo");
+        let content = "[project]\nname = \"foo\"\n\n[tool.fetter]\nexe = \"python3\"\n\n[[tool.fetter.ignore]]\npackage = \"numpy\"\n";
+        let config = Config::from_pyproject(content, dir).unwrap().unwrap();
+        assert_eq!(config.table.get("exe").unwrap().as_str(), Some("python3"));
+        assert!(Config::from_pyproject("[project]\nname = \"foo\"\n", dir)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_config_to_args_a() {
+        let command = to_command();
+        let content = "exe = \"python3\"\nquiet = true\n\n[[ignore]]\npackage = \"numpy\"\n\n[validate]\nbound = [\"base.txt\", \"https://example.com/prod.txt\"]\nsuperset = true\nsubcommand = \"json\"\n";
+        let config = Config::from_str(content, Path::new("/repo")).unwrap();
+
+        let args = config
+            .to_args(&command, to_os_strings(&["fetter", "validate"]))
+            .unwrap();
+        assert_eq!(
+            to_strings(args),
+            vec![
+                "fetter",
+                "--exe",
+                "python3",
+                "--quiet",
+                "validate",
+                "--bound",
+                "/repo/base.txt",
+                "--bound",
+                "https://example.com/prod.txt",
+                "--superset",
+                "json"
+            ]
+        );
+
+        // options and subcommands on the command line take precedence, as do conflicting options
+        let args = config
+            .to_args(
+                &command,
+                to_os_strings(&[
+                    "fetter", "-e", "python", "validate", "-b", "r.txt", "--exact",
+                    "display",
+                ]),
+            )
+            .unwrap();
+        assert_eq!(
+            to_strings(args),
+            vec![
+                "fetter", "--quiet", "-e", "python", "validate", "-b", "r.txt",
+                "--exact", "display"
+            ]
+        );
+
+        // without a command, nothing is inserted
+        let args = config
+            .to_args(&command, to_os_strings(&["fetter", "--help"]))
+            .unwrap();
+        assert_eq!(to_strings(args), vec!["fetter", "--help"]);
+    }
+
+    #[test]
+    fn test_config_to_args_b() {
+        let command = to_command();
+        let config =
+            Config::from_str("[validate]\nsubset = true\n", Path::new("/repo")).unwrap();
+        assert!(config
+            .to_args(&command, to_os_strings(&["fetter", "validate"]))
+            .is_err());
+        let config =
+            Config::from_str("[valid]\nexact = true\n", Path::new("/repo")).unwrap();
+        assert!(config
+            .to_args(&command, to_os_strings(&["fetter", "validate"]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_config_from_dir_a() {
+        let dir = tempdir().unwrap();
+        assert!(Config::from_dir(dir.path()).unwrap().is_none());
+        fs::write(
+            dir.path().join("pyproject.toml"),
+            "[tool.fetter]\nexe = \"python3\"\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join(CONFIG_FILE), "exe = \"python3.12\"\n").unwrap();
+        // a fetter.toml takes precedence
+        let config = Config::from_dir(dir.path()).unwrap().unwrap();
+        assert_eq!(
+            config.table.get("exe").unwrap().as_str(),
+            Some("python3.12")
+        );
+        assert_eq!(config.dir, dir.path());
+    }
+}
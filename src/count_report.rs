@@ -1,5 +1,10 @@
+use std::collections::HashMap;
 use std::collections::HashSet;
+use std::io;
+use std::io::Write;
+use std::path::PathBuf;
 
+use crate::package::Package;
 use crate::path_shared::PathShared;
 use crate::scan_fs::ScanFS;
 use crate::table::ColumnFormat;
@@ -33,21 +38,47 @@ pub(crate) struct CountReport {
 }
 
 impl CountReport {
-    pub(crate) fn from_scan_fs(scan_fs: &ScanFS) -> CountReport {
-        // discover unique packages
-        let mut site_packages: HashSet<&PathShared> = HashSet::new();
-        for package in scan_fs.package_to_sites.keys() {
-            if let Some(site_paths) = scan_fs.package_to_sites.get(package) {
-                for path in site_paths {
-                    site_packages.insert(path);
-                }
+    pub(crate) fn from_records(records: Vec<CountRecord>) -> CountReport {
+        CountReport { records }
+    }
+
+    /// Count executables, sites, and packages. If `detail` is true, the number of packages available to each executable, and the number of packages in each site, are also counted.
+    pub(crate) fn from_scan_fs(scan_fs: &ScanFS, detail: bool) -> CountReport {
+        // discover unique packages per site
+        let mut site_to_packages: HashMap<&PathShared, HashSet<&Package>> =
+            HashMap::new();
+        for (package, site_paths) in scan_fs.package_to_sites.iter() {
+            for path in site_paths {
+                site_to_packages.entry(path).or_default().insert(package);
             }
         }
-        let records = vec![
+        let mut records = vec![
             CountRecord::new("Executables".to_string(), scan_fs.exe_to_sites.len()),
-            CountRecord::new("Sites".to_string(), site_packages.len()),
+            CountRecord::new("Sites".to_string(), site_to_packages.len()),
             CountRecord::new("Packages".to_string(), scan_fs.package_to_sites.len()),
         ];
+        if detail {
+            let mut exes: Vec<&PathBuf> = scan_fs.exe_to_sites.keys().collect();
+            exes.sort();
+            for exe in exes {
+                let packages: HashSet<&Package> = scan_fs.exe_to_sites[exe]
+                    .iter()
+                    .filter_map(|site| site_to_packages.get(site))
+                    .flatten()
+                    .copied()
+                    .collect();
+                let key = PathShared::from_path_buf(exe.clone()).to_string();
+                records.push(CountRecord::new(key, packages.len()));
+            }
+            let mut sites: Vec<(&PathShared, usize)> = site_to_packages
+                .iter()
+                .map(|(site, packages)| (*site, packages.len()))
+                .collect();
+            sites.sort_by(|a, b| a.0.as_path().cmp(b.0.as_path()));
+            for (site, count) in sites {
+                records.push(CountRecord::new(site.to_string(), count));
+            }
+        }
         CountReport { records }
     }
 }
@@ -62,17 +93,22 @@ impl Tableable<CountRecord> for CountReport {
     fn get_records(&self) -> &Vec<CountRecord> {
         &self.records
     }
+    fn to_json_writer<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut object = serde_json::Map::new();
+        for record in &self.records {
+            object.insert(record.key.clone(), record.value.into());
+        }
+        serde_json::to_writer(&mut *writer, &object)?;
+        writeln!(writer)
+    }
 }
 
 //------------------------------------------------------------------------------
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::package::Package;
     use std::fs::File;
-    use std::io;
     use std::io::BufRead;
-    use std::path::PathBuf;
     use tempfile::tempdir;
 
     #[test]
@@ -85,7 +121,7 @@ mod tests {
             Package::from_name_version_durl("flask", "1.1.3", None).unwrap(),
         ];
         let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
-        let cr = CountReport::from_scan_fs(&sfs);
+        let cr = CountReport::from_scan_fs(&sfs, false);
 
         let dir = tempdir().unwrap();
         let fp = dir.path().join("report.txt");
@@ -98,4 +134,55 @@ mod tests {
         assert_eq!(lines.next().unwrap().unwrap(), "Sites,1");
         assert_eq!(lines.next().unwrap().unwrap(), "Packages,3");
     }
+
+    #[test]
+    fn test_to_json_writer_a() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "1.19.3", None).unwrap(),
+            Package::from_name_version_durl("flask", "1.1.3", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let cr = CountReport::from_scan_fs(&sfs, false);
+        let mut buffer = Vec::new();
+        cr.to_json_writer(&mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "{\"Executables\":1,\"Packages\":2,\"Sites\":1}\n"
+        );
+    }
+
+    #[test]
+    fn test_from_scan_fs_detail_a() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "1.19.3", None).unwrap(),
+            Package::from_name_version_durl("flask", "1.1.3", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let cr = CountReport::from_scan_fs(&sfs, true);
+
+        let dir = tempdir().unwrap();
+        let fp = dir.path().join("report.txt");
+        let _ = cr.to_file(&fp, ',');
+
+        let file = File::open(&fp).unwrap();
+        let lines: Vec<String> = io::BufReader::new(file)
+            .lines()
+            .map(|l| l.unwrap())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                ",Count",
+                "Executables,1",
+                "Sites,1",
+                "Packages,2",
+                "/usr/bin/python3,2",
+                "/usr/lib/python3/site-packages,2",
+            ]
+        );
+    }
 }
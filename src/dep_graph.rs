@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use rayon::prelude::*;
+
+use crate::dep_marker::MarkerEnv;
+use crate::dep_spec::DepSpec;
+use crate::package::Package;
+use crate::package_metadata::PackageMetadata;
+use crate::path_shared::PathShared;
+
+//------------------------------------------------------------------------------
+/// A requirement of a package, as declared in Requires-Dist, and the installed packages with the same key. If no packages are installed, the requirement is missing.
+#[derive(Debug, Clone)]
+pub(crate) struct DepEdge {
+    pub(crate) dep_spec: DepSpec,
+    pub(crate) packages: Vec<Package>,
+}
+
+/// A graph of installed packages, where each package is connected to the installed packages it requires. Requirements conditioned on extras are excluded, as are requirements with markers that do not apply to any of the environments of the package's sites.
+#[derive(Debug)]
+pub(crate) struct DepGraph {
+    edges: HashMap<Package, Vec<DepEdge>>,
+}
+
+impl DepGraph {
+    /// Given installed packages and the sites in which they were found, read the Requires-Dist entries of each package's METADATA and build a DepGraph. Packages without METADATA, such as those found only in conda-meta, have no edges.
+    pub(crate) fn from_package_to_sites(
+        package_to_sites: &HashMap<Package, Vec<PathShared>>,
+        site_to_envs: &HashMap<PathShared, Vec<MarkerEnv>>,
+    ) -> Self {
+        let mut key_to_packages: HashMap<&str, Vec<Package>> = HashMap::new();
+        for package in package_to_sites.keys() {
+            key_to_packages
+                .entry(package.key.as_str())
+                .or_default()
+                .push(package.clone());
+        }
+        for packages in key_to_packages.values_mut() {
+            packages.sort();
+        }
+        let edges = package_to_sites
+            .par_iter()
+            .map(|(package, sites)| {
+                let requires_dist = PackageMetadata::from_package_sites(package, sites)
+                    .map(|md| md.requires_dist)
+                    .unwrap_or_default();
+                let envs: Vec<MarkerEnv> = sites
+                    .iter()
+                    .filter_map(|site| site_to_envs.get(site))
+                    .flatten()
+                    .cloned()
+                    .collect();
+                let mut edges: Vec<DepEdge> = requires_dist
+                    .iter()
+                    .filter_map(|r| DepSpec::from_string(r).ok())
+                    .filter(|ds| {
+                        !ds.marker.as_ref().is_some_and(|m| m.has_extra())
+                            && ds.is_applicable(&envs)
+                    })
+                    .map(|dep_spec| {
+                        let packages = key_to_packages
+                            .get(dep_spec.key.as_str())
+                            .cloned()
+                            .unwrap_or_default();
+                        DepEdge { dep_spec, packages }
+                    })
+                    .collect();
+                edges.sort_by(|a, b| a.dep_spec.key.cmp(&b.dep_spec.key));
+                (package.clone(), edges)
+            })
+            .collect();
+        DepGraph { edges }
+    }
+
+    /// Return all packages in the graph, sorted.
+    pub(crate) fn get_packages(&self) -> Vec<&Package> {
+        let mut packages: Vec<&Package> = self.edges.keys().collect();
+        packages.sort();
+        packages
+    }
+
+    /// Return the requirements of a package, sorted by key.
+    pub(crate) fn get_edges(&self, package: &Package) -> &[DepEdge] {
+        self.edges.get(package).map(|e| e.as_slice()).unwrap_or(&[])
+    }
+
+    /// Return the installed packages required by a package, sorted.
+    pub(crate) fn get_dependencies(&self, package: &Package) -> Vec<&Package> {
+        let mut packages: Vec<&Package> = self
+            .get_edges(package)
+            .iter()
+            .flat_map(|e| e.packages.iter())
+            .collect();
+        packages.sort();
+        packages.dedup();
+        packages
+    }
+
+    /// Return the installed packages that require a package, sorted.
+    pub(crate) fn get_dependents(&self, package: &Package) -> Vec<&Package> {
+        let mut packages: Vec<&Package> = self
+            .edges
+            .iter()
+            .filter(|(_, edges)| edges.iter().any(|e| e.packages.contains(package)))
+            .map(|(p, _)| p)
+            .collect();
+        packages.sort();
+        packages
+    }
+
+    /// Return all packages reachable from the provided packages, including those packages. Cycles are visited once.
+    pub(crate) fn get_reachable<'a>(
+        &'a self,
+        roots: &[&'a Package],
+    ) -> HashSet<&'a Package> {
+        let mut reachable: HashSet<&Package> = HashSet::new();
+        let mut stack: Vec<&Package> = roots.to_vec();
+        while let Some(package) = stack.pop() {
+            if reachable.insert(package) {
+                stack.extend(self.get_dependencies(package));
+            }
+        }
+        reachable
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    // Write dist-info METADATA files to a temporary site, returning the package to sites mapping.
+    fn write_site(
+        site: &std::path::Path,
+        packages: &[(&str, &str, &[&str])],
+    ) -> HashMap<Package, Vec<PathShared>> {
+        let mut package_to_sites = HashMap::new();
+        let site_shared = PathShared::from_path_buf(site.to_path_buf());
+        for (name, version, requires) in packages {
+            let fp_di = site.join(format!("{}-{}.dist-info", name, version));
+            fs::create_dir(&fp_di).unwrap();
+            let mut content = format!("Name: {}\nVersion: {}\n", name, version);
+            for r in requires.iter() {
+                content.push_str(&format!("Requires-Dist: {}\n", r));
+            }
+            fs::write(fp_di.join("METADATA"), content).unwrap();
+            let package = Package::from_name_version_durl(name, version, None).unwrap();
+            package_to_sites.insert(package, vec![site_shared.clone()]);
+        }
+        package_to_sites
+    }
+
+    #[test]
+    fn test_dep_graph_a() {
+        let dir = tempdir().unwrap();
+        let package_to_sites = write_site(
+            dir.path(),
+            &[
+                (
+                    "requests",
+                    "2.32.3",
+                    &[
+                        "idna<4,>=2.5",
+                        "urllib3<3,>=1.21.1",
+                        "PySocks!=1.5.7,>=1.5.6; extra == \"socks\"",
+                    ],
+                ),
+                ("idna", "3.10", &[]),
+                ("urllib3", "2.2.3", &[]),
+                ("pysocks", "1.7.1", &[]),
+            ],
+        );
+        let dg = DepGraph::from_package_to_sites(&package_to_sites, &HashMap::new());
+        let requests =
+            Package::from_name_version_durl("requests", "2.32.3", None).unwrap();
+        let idna = Package::from_name_version_durl("idna", "3.10", None).unwrap();
+        let urllib3 = Package::from_name_version_durl("urllib3", "2.2.3", None).unwrap();
+        let pysocks = Package::from_name_version_durl("pysocks", "1.7.1", None).unwrap();
+
+        assert_eq!(dg.get_packages().len(), 4);
+        // requirements of extras are excluded
+        assert_eq!(dg.get_edges(&requests).len(), 2);
+        assert_eq!(dg.get_dependencies(&requests), vec![&idna, &urllib3]);
+        assert_eq!(dg.get_dependents(&idna), vec![&requests]);
+        assert_eq!(dg.get_dependents(&pysocks).len(), 0);
+
+        let reachable = dg.get_reachable(&[&requests]);
+        assert_eq!(reachable.len(), 3);
+        assert!(!reachable.contains(&pysocks));
+    }
+
+    #[test]
+    fn test_dep_graph_b() {
+        let dir = tempdir().unwrap();
+        let package_to_sites = write_site(
+            dir.path(),
+            &[
+                ("a", "1.0", &["b>=1", "c"]),
+                ("b", "1.0", &["a"]),
+                ("d", "1.0", &["pywin32; sys_platform == \"win32\""]),
+            ],
+        );
+        let site_to_envs = HashMap::from([(
+            PathShared::from_path_buf(dir.path().to_path_buf()),
+            vec![MarkerEnv::from_pairs(&[("sys_platform", "linux")])],
+        )]);
+        let dg = DepGraph::from_package_to_sites(&package_to_sites, &site_to_envs);
+        let a = Package::from_name_version_durl("a", "1.0", None).unwrap();
+        let b = Package::from_name_version_durl("b", "1.0", None).unwrap();
+        let d = Package::from_name_version_durl("d", "1.0", None).unwrap();
+
+        // a missing requirement has no packages
+        let edges = dg.get_edges(&a);
+        assert_eq!(edges.len(), 2);
+        assert_eq!(edges[1].dep_spec.key, "c");
+        assert!(edges[1].packages.is_empty());
+        // cycles are visited once
+        assert_eq!(dg.get_reachable(&[&b]).len(), 2);
+        // requirements with markers that do not apply are excluded
+        assert_eq!(dg.get_edges(&d).len(), 0);
+    }
+}
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::env;
 use std::fs;
 use std::fs::File;
 use std::io;
@@ -19,6 +20,8 @@ use crate::ureq_client::UreqClient;
 
 use crate::dep_spec::DepSpec;
 use crate::package::Package;
+use crate::package_match::PackageFilter;
+use crate::util::extra_normalize;
 use crate::util::ResultDynError;
 
 //------------------------------------------------------------------------------
@@ -28,7 +31,11 @@ pub(crate) struct DepManifestRecord {
 
 impl Rowable for DepManifestRecord {
     fn to_rows(&self, _context: &RowableContext) -> Vec<Vec<String>> {
-        vec![vec![self.dep_spec.to_string()]]
+        let mut line = self.dep_spec.to_string();
+        for hash in self.dep_spec.hashes.iter() {
+            line.push_str(&format!(" --hash={}", hash));
+        }
+        vec![vec![line]]
     }
 }
 
@@ -62,13 +69,269 @@ fn poetry_toml_value_to_string((name, value): (&String, &toml::Value)) -> String
             .to_string(),
         _ => String::new(),
     };
-    format!("{}{}", name, version)
+    match value
+        .as_table()
+        .and_then(|t| t.get("markers"))
+        .and_then(|m| m.as_str())
+    {
+        Some(markers) => format!("{}{}; {}", name, version, markers),
+        None => format!("{}{}", name, version),
+    }
+}
+
+// Dependency group names are compared in normalized form.
+fn group_normalize(name: &str) -> String {
+    extra_normalize(name)
+}
+
+const DEPENDENCY_GROUP_DEPTH_MAX: usize = 20;
+
+// Given the `[dependency-groups]` table of a pyproject.toml, return the requirement strings of the named group, resolving `include-group` entries. Returns None if the group is not defined.
+fn dependency_group_to_strings(
+    groups: &toml::value::Table,
+    name: &str,
+    depth: usize,
+) -> ResultDynError<Option<Vec<String>>> {
+    if depth > DEPENDENCY_GROUP_DEPTH_MAX {
+        return Err(format!("Cyclic dependency group include: {}", name).into());
+    }
+    let key = group_normalize(name);
+    let entries = match groups
+        .iter()
+        .find(|(k, _)| group_normalize(k) == key)
+        .and_then(|(_, v)| v.as_array())
+    {
+        Some(entries) => entries,
+        None => return Ok(None),
+    };
+    let mut deps = Vec::new();
+    for entry in entries {
+        if let Some(dep) = entry.as_str() {
+            deps.push(dep.to_string());
+        } else if let Some(include) = entry.get("include-group").and_then(|g| g.as_str())
+        {
+            match dependency_group_to_strings(groups, include, depth + 1)? {
+                Some(included) => deps.extend(included),
+                None => {
+                    return Err(format!(
+                        "Included dependency group not found: {}",
+                        include
+                    )
+                    .into())
+                }
+            }
+        }
+    }
+    Ok(Some(deps))
+}
+
+/// Convert a conda match specification, such as `numpy=1.26`, `conda-forge::numpy>=1.26`, or `numpy 1.26.*`, to a requirement string. Conda's fuzzy `=` is a prefix match, and builds strings are discarded. Returns None for `python` and for the `pip` list.
+fn conda_spec_to_requirement(spec: &str) -> Option<String> {
+    // remove the channel, if defined
+    let spec = match spec.rsplit_once("::") {
+        Some((_, spec)) => spec,
+        None => spec,
+    };
+    let (name, constraint) = match spec.find(|c: char| "=<>!~ ".contains(c)) {
+        Some(i) => (&spec[..i], spec[i..].trim()),
+        None => (spec, ""),
+    };
+    if name.is_empty() || name == "python" || name.ends_with(':') {
+        return None;
+    }
+    // an alternative of constraints cannot be represented as a requirement
+    if constraint.is_empty() || constraint.contains('|') {
+        return Some(name.to_string());
+    }
+    // a conda glob without a separator (e.g. `1.26*`) is given as a wildcard release segment
+    let glob = |v: &str| match v.strip_suffix('*') {
+        Some(prefix) if !prefix.ends_with('.') => format!("{}.*", prefix),
+        _ => v.to_string(),
+    };
+    let version = if let Some(v) = constraint.strip_prefix("==") {
+        format!("=={}", v.split(['=', ' ']).next().unwrap_or(v))
+    } else if let Some(v) = constraint.strip_prefix('=') {
+        let v = v.split(['=', ' ']).next().unwrap_or(v);
+        if v.ends_with('*') {
+            format!("=={}", glob(v))
+        } else {
+            format!("=={}.*", v)
+        }
+    } else if constraint.starts_with(|c: char| "<>!~".contains(c)) {
+        constraint
+            .split(' ')
+            .next()
+            .unwrap_or(constraint)
+            .to_string()
+    } else {
+        // space-separated version and build, where the version is an exact or glob match
+        let v = constraint.split(' ').next().unwrap_or(constraint);
+        format!("=={}", glob(v))
+    };
+    Some(format!("{}{}", name, version))
+}
+
+// Given the content of a document without a name, as read from stdin, return a name for its detected format: Pipfile.lock is JSON with `_meta`; poetry.lock and uv.lock have `[[package]]` tables, with poetry.lock also defining a `lock-version`; pyproject.toml has `[project]` or `[tool.poetry]` tables; environment.yml has a top-level `dependencies:` key. Other content is taken as requirements.
+fn content_to_name(content: &str) -> &'static str {
+    let has_line = |target: &str| content.lines().any(|line| line.trim_end() == target);
+    if content.trim_start().starts_with('{') && content.contains("\"_meta\"") {
+        "Pipfile.lock"
+    } else if has_line("[[package]]") {
+        if content.lines().any(|line| line.starts_with("lock-version")) {
+            "poetry.lock"
+        } else {
+            "uv.lock"
+        }
+    } else if has_line("[project]") || has_line("[tool.poetry]") {
+        "pyproject.toml"
+    } else if has_line("dependencies:") {
+        "environment.yml"
+    } else {
+        "requirements.txt"
+    }
+}
+
+// Given a comment written by `pip freeze` before an editable requirement without a VCS URL, such as `# Editable install with no version control (mypkg==1.0)`, return the requirement in parentheses.
+fn editable_comment_requirement(line: &str) -> Option<&str> {
+    let comment = line.trim_start().strip_prefix('#')?.trim_start();
+    if !comment.starts_with("Editable") {
+        return None;
+    }
+    let start = comment.find('(')? + 1;
+    let end = start + comment[start..].find(')')?;
+    Some(comment[start..end].trim()).filter(|r| !r.is_empty())
+}
+
+// Given an editable requirement (`-e <url>` or `--editable <url>`), as written by `pip freeze`, return a requirement of the package by name, as the source paths of editable installs differ between hosts: the requirement of the preceding comment, if provided, or otherwise the name of the `#egg=` fragment of the URL.
+fn editable_to_requirement(line: &str, comment: Option<&str>) -> Option<String> {
+    let url = line
+        .strip_prefix("--editable")
+        .or_else(|| line.strip_prefix("-e"))?;
+    let url = url.strip_prefix('=').unwrap_or(url).trim();
+    if url.is_empty() {
+        return None;
+    }
+    if let Some(requirement) = comment {
+        return Some(requirement.to_string());
+    }
+    url.split_once('#')?
+        .1
+        .split('&')
+        .find_map(|part| part.strip_prefix("egg="))
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+}
+
+/// Given lines from a requirements file, return logical lines: lines continued with a trailing backslash are joined, and comments and empty lines are removed. Editable requirements, as written by `pip freeze`, are replaced with requirements by name.
+fn requirement_lines<I, S>(lines: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut logical = Vec::new();
+    let mut current = String::new();
+    let mut editable: Option<String> = None;
+    for line in lines {
+        let line = line.as_ref();
+        if let Some(requirement) = editable_comment_requirement(line) {
+            editable = Some(requirement.to_string());
+        }
+        // a comment starts with "#" at the start of a line or after whitespace
+        let line = match line
+            .match_indices('#')
+            .find(|(pos, _)| *pos == 0 || line[..*pos].ends_with(char::is_whitespace))
+        {
+            Some((pos, _)) => &line[..pos],
+            None => line,
+        };
+        match line.trim_end().strip_suffix('\\') {
+            Some(part) => {
+                current.push_str(part);
+                current.push(' ');
+            }
+            None => {
+                current.push_str(line);
+                let t = current.trim();
+                if !t.is_empty() {
+                    logical.push(
+                        editable_to_requirement(t, editable.take().as_deref())
+                            .unwrap_or_else(|| t.to_string()),
+                    );
+                }
+                current.clear();
+            }
+        }
+    }
+    let t = current.trim();
+    if !t.is_empty() {
+        logical.push(t.to_string());
+    }
+    logical
+}
+
+// A function returning the value of a variable referenced in requirements, if defined.
+type EnvLookup<'a> = &'a dyn Fn(&str) -> Option<String>;
+
+// Return the value of an environment variable of this process, if defined and valid unicode.
+fn env_lookup(name: &str) -> Option<String> {
+    env::var(name).ok()
+}
+
+// Given a requirements line, replace each `${VAR}` reference, where VAR is composed of uppercase ASCII letters, digits, and underscores, with the value returned by `lookup`, as pip does with environment variables. References to undefined variables are retained.
+fn expand_env_vars<F>(line: &str, lookup: &F) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut expanded = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let post = &rest[start + 2..];
+        match post.find('}') {
+            Some(end)
+                if end > 0
+                    && post[..end].chars().all(|c| {
+                        c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'
+                    }) =>
+            {
+                match lookup(&post[..end]) {
+                    Some(value) => expanded.push_str(&value),
+                    None => expanded.push_str(&rest[start..start + end + 3]),
+                }
+                rest = &post[end + 1..];
+            }
+            _ => {
+                expanded.push_str("${");
+                rest = post;
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+// Given a requirements line, return the path of a requirements file (`-r`, `--requirement`) or constraints file (`-c`, `--constraint`) it includes, and true if it is a constraints file.
+fn requirement_include(line: &str) -> Option<(&str, bool)> {
+    for (prefix, is_constraint) in [
+        ("--requirement", false),
+        ("--constraint", true),
+        ("-r", false),
+        ("-c", true),
+    ] {
+        if let Some(post) = line.strip_prefix(prefix) {
+            let post = post.strip_prefix('=').unwrap_or(post).trim();
+            if !post.is_empty() {
+                return Some((post, is_constraint));
+            }
+        }
+    }
+    None
 }
 
 //------------------------------------------------------------------------------
-// A DepManifest is a requirements listing, implemented as HashMap for quick lookup by package name.
+/// A DepManifest is a requirements listing, implemented as HashMap for quick lookup by package name.
 #[derive(Debug, Clone)]
-pub(crate) struct DepManifest {
+pub struct DepManifest {
     dep_specs: HashMap<String, DepSpec>,
 }
 
@@ -84,7 +347,7 @@ impl DepManifest {
             if spec.is_empty() {
                 continue;
             }
-            let dep_spec = DepSpec::from_string(spec)?;
+            let dep_spec = DepSpec::from_requirement(spec)?;
             if dep_specs.contains_key(&dep_spec.key) {
                 return Err(
                     format!("Duplicate package key found: {}", dep_spec.key).into()
@@ -94,28 +357,61 @@ impl DepManifest {
         }
         Ok(DepManifest { dep_specs })
     }
-    // Create a DepManifest from a requirements.txt file, which might reference other requirements.txt files.
-    pub(crate) fn from_requirements_file(file_path: &Path) -> ResultDynError<Self> {
-        let mut files: VecDeque<PathBuf> = VecDeque::new();
-        files.push_back(file_path.to_path_buf());
+    // Create a DepManifest from a requirements.txt file, which might reference other requirements files with `-r` and constraints files with `-c`. Included paths are relative to the including file, and each file is read once, such that cyclic includes terminate. Constraints narrow the versions of required packages and do not add requirements.
+    pub fn from_requirements_file(file_path: &Path) -> ResultDynError<Self> {
+        Self::from_requirements_file_with_env_expand(file_path, true)
+    }
+
+    // Create a DepManifest from a requirements.txt file; if `env_expand` is set, `${VAR}` references are replaced with the values of environment variables.
+    pub fn from_requirements_file_with_env_expand(
+        file_path: &Path,
+        env_expand: bool,
+    ) -> ResultDynError<Self> {
+        if env_expand {
+            Self::from_requirements_file_with_env_lookup(file_path, Some(&env_lookup))
+        } else {
+            Self::from_requirements_file_with_env_lookup(file_path, None)
+        }
+    }
+
+    // Create a DepManifest from a requirements.txt file; if `lookup` is provided, `${VAR}` references are replaced with the values it returns.
+    fn from_requirements_file_with_env_lookup(
+        file_path: &Path,
+        lookup: Option<EnvLookup>,
+    ) -> ResultDynError<Self> {
+        let mut files: VecDeque<(PathBuf, bool)> = VecDeque::new();
+        files.push_back((file_path.to_path_buf(), false));
+        let mut visited: HashSet<PathBuf> = HashSet::new();
         let mut dep_specs = HashMap::new();
+        let mut constraints: Vec<DepSpec> = Vec::new();
 
-        while !files.is_empty() {
-            let fp = files.pop_front().unwrap();
+        while let Some((fp, is_constraint)) = files.pop_front() {
             let file = File::open(&fp)
                 .map_err(|e| format!("Failed to open file: {:?} {}", fp, e))?;
+            if !visited.insert(fs::canonicalize(&fp).unwrap_or(fp.clone())) {
+                continue;
+            }
             let lines = io::BufReader::new(file).lines();
-            for line in lines.map_while(Result::ok) {
-                let t = line.trim();
-                if t.is_empty() || t.starts_with('#') {
+            for line in requirement_lines(lines.map_while(Result::ok)) {
+                let line = match lookup {
+                    Some(lookup) => expand_env_vars(&line, &lookup),
+                    None => line,
+                };
+                let t = line.as_str();
+                if let Some((include, include_constraint)) = requirement_include(t) {
+                    let dir = fp.parent().unwrap_or(Path::new(""));
+                    // includes of a constraints file are also constraints
+                    files.push_back((
+                        dir.join(include),
+                        is_constraint || include_constraint,
+                    ));
+                } else if t.starts_with("--") {
+                    // global options, such as `--require-hashes` or `--index-url`, do not define requirements
                     continue;
-                }
-                if let Some(post) = t.strip_prefix("-r ") {
-                    files.push_back(file_path.parent().unwrap().join(post.trim()));
-                } else if let Some(post) = t.strip_prefix("--requirement ") {
-                    files.push_back(file_path.parent().unwrap().join(post.trim()));
+                } else if is_constraint {
+                    constraints.push(DepSpec::from_requirement(t)?);
                 } else {
-                    let ds = DepSpec::from_string(&line)?;
+                    let ds = DepSpec::from_requirement(t)?;
                     if dep_specs.contains_key(&ds.key) {
                         return Err(
                             format!("Duplicate package key found: {}", ds.key).into()
@@ -125,15 +421,24 @@ impl DepManifest {
                 }
             }
         }
+        for constraint in constraints {
+            if let Some(ds) = dep_specs.remove(&constraint.key) {
+                let mut ds_new = ds.intersect(&constraint)?;
+                // only versions and hashes are taken from the constraint
+                ds_new.url = ds.url;
+                ds_new.extras = ds.extras;
+                ds_new.marker = ds.marker;
+                dep_specs.insert(ds_new.key.clone(), ds_new);
+            }
+        }
         Ok(DepManifest { dep_specs })
     }
-    pub(crate) fn from_dep_specs(dep_specs: &Vec<DepSpec>) -> ResultDynError<Self> {
+    pub fn from_dep_specs(dep_specs: &Vec<DepSpec>) -> ResultDynError<Self> {
         let mut ds: HashMap<String, DepSpec> = HashMap::new();
         for dep_spec in dep_specs {
             if let Some(dep_spec_prev) = ds.remove(&dep_spec.key) {
                 // remove and replace with composite
-                let dep_spec_new =
-                    DepSpec::from_dep_specs(vec![&dep_spec_prev, &dep_spec])?;
+                let dep_spec_new = dep_spec_prev.intersect(dep_spec)?;
                 ds.insert(dep_spec_new.key.clone(), dep_spec_new);
             } else {
                 ds.insert(dep_spec.key.clone(), dep_spec.clone());
@@ -179,6 +484,19 @@ impl DepManifest {
                         }
                     }
                 }
+                // [dependency-groups]
+                if let Some(groups) =
+                    value.get("dependency-groups").and_then(|g| g.as_table())
+                {
+                    let names: Vec<&String> = opt_set.iter().cloned().collect();
+                    for name in names {
+                        if let Some(deps) = dependency_group_to_strings(groups, name, 0)?
+                        {
+                            deps_list.extend(deps);
+                            opt_set.remove(name);
+                        }
+                    }
+                }
                 if !opt_set.is_empty() {
                     let msg = format!(
                         "Requested optional dependencies not found: {:?}",
@@ -228,7 +546,7 @@ impl DepManifest {
         Err("Dependencies section not found in pyproject.toml".into())
     }
 
-    pub(crate) fn from_pyproject_file(
+    pub fn from_pyproject_file(
         file_path: &PathBuf,
         bound_options: Option<&Vec<String>>,
     ) -> ResultDynError<Self> {
@@ -237,25 +555,405 @@ impl DepManifest {
         Self::from_pyproject(&content, bound_options)
     }
 
-    // Create a DepManifest from a URL point to a requirements.txt or pyproject.toml file.
+    /// Create a DepManifest from a poetry.lock file, pinning each locked package to its exact version. If `lock_hashes` is set, the file hashes of each package are recorded. If the lock records dependency groups, only the main group and the groups named in `options` are included.
+    pub(crate) fn from_poetry_lock(
+        content: &str,
+        options: Option<&Vec<String>>,
+        lock_hashes: bool,
+    ) -> ResultDynError<Self> {
+        let value: toml::Value = content
+            .parse::<toml::Value>()
+            .map_err(|e| format!("Failed to parse TOML: {}", e))?;
+        let packages = value
+            .get("package")
+            .and_then(|p| p.as_array())
+            .ok_or("Package section not found in poetry.lock")?;
+
+        // lock files before version 2 store hashes in [metadata.files]
+        let metadata_files = value
+            .get("metadata")
+            .and_then(|m| m.get("files"))
+            .and_then(|f| f.as_table());
+
+        let mut groups_selected: HashSet<String> = HashSet::new();
+        groups_selected.insert("main".to_string());
+        if let Some(opt) = options {
+            groups_selected.extend(opt.iter().cloned());
+        }
+        let mut groups_found: HashSet<String> = HashSet::new();
+
+        let mut dep_specs = Vec::new();
+        for package in packages {
+            let name = package
+                .get("name")
+                .and_then(|v| v.as_str())
+                .ok_or("Package without name in poetry.lock")?;
+            let version = package
+                .get("version")
+                .and_then(|v| v.as_str())
+                .ok_or("Package without version in poetry.lock")?;
+
+            // poetry 2 records `groups`; earlier versions record `category`
+            let groups: Vec<String> =
+                match package.get("groups").and_then(|g| g.as_array()) {
+                    Some(groups) => groups
+                        .iter()
+                        .filter_map(|g| g.as_str().map(String::from))
+                        .collect(),
+                    None => package
+                        .get("category")
+                        .and_then(|c| c.as_str())
+                        .map(|c| vec![c.to_string()])
+                        .unwrap_or_default(),
+                };
+            groups_found.extend(groups.iter().cloned());
+            if !groups.is_empty() && !groups.iter().any(|g| groups_selected.contains(g)) {
+                continue;
+            }
+
+            let mut spec = format!("{}=={}", name, version);
+            if let Some(source) = package.get("source") {
+                if source.get("type").and_then(|t| t.as_str()) == Some("git") {
+                    if let (Some(url), Some(reference)) = (
+                        source.get("url").and_then(|u| u.as_str()),
+                        source.get("resolved_reference").and_then(|r| r.as_str()),
+                    ) {
+                        spec = format!("{} @ git+{}@{}", spec, url, reference);
+                    }
+                }
+            }
+            if let Some(markers) = package.get("markers").and_then(|m| m.as_str()) {
+                spec = format!("{} ; {}", spec, markers);
+            }
+            let mut ds = DepSpec::from_string(&spec)?;
+
+            let files = package.get("files").and_then(|f| f.as_array()).or_else(|| {
+                metadata_files
+                    .and_then(|mf| mf.get(name))
+                    .and_then(|f| f.as_array())
+            });
+            if let Some(files) = files.filter(|_| lock_hashes) {
+                ds.hashes = files
+                    .iter()
+                    .filter_map(|f| f.get("hash").and_then(|h| h.as_str()))
+                    .map(String::from)
+                    .collect();
+            }
+            dep_specs.push(ds);
+        }
+        if let Some(opt) = options {
+            for o in opt {
+                if !groups_found.contains(o) {
+                    return Err(
+                        format!("Requested dependency group not found: {}", o).into()
+                    );
+                }
+            }
+        }
+        Self::from_dep_specs(&dep_specs)
+    }
+
+    pub fn from_poetry_lock_file(
+        file_path: &PathBuf,
+        bound_options: Option<&Vec<String>>,
+        lock_hashes: bool,
+    ) -> ResultDynError<Self> {
+        let content = fs::read_to_string(file_path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        Self::from_poetry_lock(&content, bound_options, lock_hashes)
+    }
+
+    /// Create a DepManifest from a Pipfile.lock file, taking the exact version of each locked package and, if `lock_hashes` is set, its hashes. The `default` section is always included; the `develop` section is included if named in `options`.
+    pub(crate) fn from_pipfile_lock(
+        content: &str,
+        options: Option<&Vec<String>>,
+        lock_hashes: bool,
+    ) -> ResultDynError<Self> {
+        let value: serde_json::Value = serde_json::from_str(content)
+            .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+        let mut sections = vec!["default"];
+        if let Some(opt) = options {
+            for o in opt {
+                match o.as_str() {
+                    "default" => {}
+                    "develop" => sections.push("develop"),
+                    _ => {
+                        return Err(format!(
+                            "Requested Pipfile.lock section not found: {}",
+                            o
+                        )
+                        .into())
+                    }
+                }
+            }
+        }
+
+        let mut dep_specs = Vec::new();
+        for section in sections {
+            let packages = match value.get(section).and_then(|s| s.as_object()) {
+                Some(packages) => packages,
+                None => continue,
+            };
+            for (name, package) in packages {
+                let mut spec = name.clone();
+                if let Some(extras) = package.get("extras").and_then(|e| e.as_array()) {
+                    let extras: Vec<&str> =
+                        extras.iter().filter_map(|e| e.as_str()).collect();
+                    if !extras.is_empty() {
+                        spec = format!("{}[{}]", spec, extras.join(","));
+                    }
+                }
+                if let Some(version) = package.get("version").and_then(|v| v.as_str()) {
+                    spec.push_str(version);
+                }
+                if let Some(url) = package.get("git").and_then(|g| g.as_str()) {
+                    let url = if url.starts_with("git+") {
+                        url.to_string()
+                    } else {
+                        format!("git+{}", url)
+                    };
+                    spec = match package.get("ref").and_then(|r| r.as_str()) {
+                        Some(reference) => format!("{} @ {}@{}", spec, url, reference),
+                        None => format!("{} @ {}", spec, url),
+                    };
+                } else if let Some(url) = package.get("file").and_then(|f| f.as_str()) {
+                    spec = format!("{} @ {}", spec, url);
+                }
+                if let Some(markers) = package.get("markers").and_then(|m| m.as_str()) {
+                    spec = format!("{} ; {}", spec, markers);
+                }
+                let mut ds = DepSpec::from_string(&spec)?;
+                if let Some(hashes) = package
+                    .get("hashes")
+                    .and_then(|h| h.as_array())
+                    .filter(|_| lock_hashes)
+                {
+                    ds.hashes = hashes
+                        .iter()
+                        .filter_map(|h| h.as_str())
+                        .map(String::from)
+                        .collect();
+                }
+                dep_specs.push(ds);
+            }
+        }
+        Self::from_dep_specs(&dep_specs)
+    }
+
+    pub fn from_pipfile_lock_file(
+        file_path: &PathBuf,
+        bound_options: Option<&Vec<String>>,
+        lock_hashes: bool,
+    ) -> ResultDynError<Self> {
+        let content = fs::read_to_string(file_path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        Self::from_pipfile_lock(&content, bound_options, lock_hashes)
+    }
+
+    /// Create a DepManifest from a conda environment.yml file. Conda match specifications in `dependencies` are converted to equivalent requirements, and requirements in a nested `pip` list are read as is. The `python` dependency is excluded, as the interpreter is not found in site packages.
+    pub(crate) fn from_environment_yml(content: &str) -> ResultDynError<Self> {
+        let mut dep_specs = Vec::new();
+        let mut in_dependencies = false;
+        // the indent of the pip list, if within it
+        let mut pip_indent: Option<usize> = None;
+        for line in content.lines() {
+            let line_no_comment = match line.find(" #") {
+                Some(i) => &line[..i],
+                None => line,
+            };
+            let trimmed = line_no_comment.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let indent = line_no_comment.len() - line_no_comment.trim_start().len();
+            // list items may have the same indent as their key
+            if indent == 0 && !trimmed.starts_with('-') {
+                in_dependencies = trimmed == "dependencies:";
+                pip_indent = None;
+                continue;
+            }
+            if !in_dependencies {
+                continue;
+            }
+            let item = match trimmed.strip_prefix('-') {
+                Some(item) => item.trim().trim_matches(|c| c == '"' || c == '\''),
+                None => continue,
+            };
+            if let Some(pi) = pip_indent {
+                if indent > pi {
+                    // pip options, such as `-r requirements.txt` or `-e .`, are not requirements
+                    if !item.starts_with('-') {
+                        dep_specs.push(DepSpec::from_string(item)?);
+                    }
+                    continue;
+                }
+                pip_indent = None;
+            }
+            if item == "pip:" {
+                pip_indent = Some(indent);
+            } else if let Some(spec) = conda_spec_to_requirement(item) {
+                dep_specs.push(DepSpec::from_string(&spec)?);
+            }
+        }
+        Self::from_dep_specs(&dep_specs)
+    }
+
+    pub fn from_environment_yml_file(file_path: &PathBuf) -> ResultDynError<Self> {
+        let content = fs::read_to_string(file_path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        Self::from_environment_yml(&content)
+    }
+
+    /// Create a DepManifest from a uv.lock file, pinning each locked package to its exact version. If `lock_hashes` is set, the artifact hashes of each package are recorded. Resolution markers are retained as the marker of each package. A package locked at different versions for different resolutions is bound by name only.
+    pub(crate) fn from_uv_lock(content: &str, lock_hashes: bool) -> ResultDynError<Self> {
+        let value: toml::Value = content
+            .parse::<toml::Value>()
+            .map_err(|e| format!("Failed to parse TOML: {}", e))?;
+        let packages = value
+            .get("package")
+            .and_then(|p| p.as_array())
+            .ok_or("Package section not found in uv.lock")?;
+
+        let mut key_to_dep_specs: HashMap<String, Vec<DepSpec>> = HashMap::new();
+        for package in packages {
+            let name = package
+                .get("name")
+                .and_then(|v| v.as_str())
+                .ok_or("Package without name in uv.lock")?;
+            let source = package.get("source");
+            // a virtual package is the project or a workspace member that is not installed
+            if source.and_then(|s| s.get("virtual")).is_some() {
+                continue;
+            }
+            let mut spec = match package.get("version").and_then(|v| v.as_str()) {
+                Some(version) => format!("{}=={}", name, version),
+                None => name.to_string(),
+            };
+            if let Some(url) = source.and_then(|s| s.get("git")).and_then(|g| g.as_str())
+            {
+                // the locked commit is recorded as the fragment; any query is the requested revision
+                if let Some((repo, commit)) = url.split_once('#') {
+                    let repo = repo.split('?').next().unwrap_or(repo);
+                    spec = format!("{} @ git+{}@{}", spec, repo, commit);
+                }
+            } else if let Some(url) =
+                source.and_then(|s| s.get("url")).and_then(|u| u.as_str())
+            {
+                spec = format!("{} @ {}", spec, url);
+            }
+            if let Some(markers) =
+                package.get("resolution-markers").and_then(|m| m.as_array())
+            {
+                let markers: Vec<String> = markers
+                    .iter()
+                    .filter_map(|m| m.as_str())
+                    .map(|m| format!("({})", m))
+                    .collect();
+                if !markers.is_empty() {
+                    spec = format!("{} ; {}", spec, markers.join(" or "));
+                }
+            }
+            let mut ds = DepSpec::from_string(&spec)?;
+
+            let mut hashes = Vec::new();
+            if let Some(sdist) = package.get("sdist").filter(|_| lock_hashes) {
+                hashes
+                    .extend(sdist.get("hash").and_then(|h| h.as_str()).map(String::from));
+            }
+            if let Some(wheels) = package
+                .get("wheels")
+                .and_then(|w| w.as_array())
+                .filter(|_| lock_hashes)
+            {
+                hashes.extend(
+                    wheels
+                        .iter()
+                        .filter_map(|w| w.get("hash").and_then(|h| h.as_str()))
+                        .map(String::from),
+                );
+            }
+            ds.hashes = hashes;
+            key_to_dep_specs.entry(ds.key.clone()).or_default().push(ds);
+        }
+
+        let mut dep_specs = Vec::new();
+        for (_, mut specs) in key_to_dep_specs {
+            if specs.len() == 1 {
+                dep_specs.push(specs.remove(0));
+            } else {
+                // versions for different resolutions cannot be combined into one specifier
+                let mut ds = DepSpec::from_string(&specs[0].name)?;
+                ds.hashes = specs.iter().flat_map(|s| s.hashes.clone()).collect();
+                dep_specs.push(ds);
+            }
+        }
+        Self::from_dep_specs(&dep_specs)
+    }
+
+    pub fn from_uv_lock_file(
+        file_path: &PathBuf,
+        lock_hashes: bool,
+    ) -> ResultDynError<Self> {
+        let content = fs::read_to_string(file_path)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        Self::from_uv_lock(&content, lock_hashes)
+    }
+
+    // Create a DepManifest from a URL point to a requirements.txt, pyproject.toml, or lock file. If `env_expand` is set, `${VAR}` references in requirements files are replaced with the values of environment variables.
+    /// Create a DepManifest from a URL, sending `headers` (e.g. `Authorization`) with the request. As headers might carry credentials, they are only sent over HTTPS. The format is selected by the name at the end of the URL path.
     pub(crate) fn from_url<U: UreqClient>(
         client: &U,
         url: &Path,
+        headers: &[(String, String)],
         bound_options: Option<&Vec<String>>,
+        env_expand: bool,
+        lock_hashes: bool,
     ) -> ResultDynError<Self> {
         let url_str = url.to_str().ok_or("Invalid URL")?;
-        let content = client.get(url_str)?;
-        if url_str.ends_with(".toml") {
-            Self::from_pyproject(&content, bound_options)
+        if !headers.is_empty() && !url_str.starts_with("https://") {
+            return Err(format!("Headers are only sent over HTTPS: {}", url_str).into());
+        }
+        let content = client.get_with_headers(url_str, headers)?;
+        // a query or fragment does not name the document
+        let name = url_str.split(['?', '#']).next().unwrap_or(url_str);
+        Self::from_content(&content, Some(name), bound_options, env_expand, lock_hashes)
+    }
+
+    /// Create a DepManifest from the content of a document, with its format selected by the ending of `name`, a file name or URL. If `name` is not provided, as when read from stdin, the format is detected from the content. Content in an unknown format is read as requirements.
+    pub(crate) fn from_content(
+        content: &str,
+        name: Option<&str>,
+        bound_options: Option<&Vec<String>>,
+        env_expand: bool,
+        lock_hashes: bool,
+    ) -> ResultDynError<Self> {
+        let name = name.unwrap_or_else(|| content_to_name(content));
+        if name.ends_with(".toml") {
+            Self::from_pyproject(content, bound_options)
+        } else if name.ends_with("poetry.lock") {
+            Self::from_poetry_lock(content, bound_options, lock_hashes)
+        } else if name.ends_with("Pipfile.lock") {
+            Self::from_pipfile_lock(content, bound_options, lock_hashes)
+        } else if name.ends_with("uv.lock") {
+            Self::from_uv_lock(content, lock_hashes)
+        } else if name.ends_with(".yml") || name.ends_with(".yaml") {
+            Self::from_environment_yml(content)
         } else {
             // assume txt
-            Self::from_iter(content.lines())
+            let lines = requirement_lines(content.lines());
+            if env_expand {
+                Self::from_iter(lines.iter().map(|l| expand_env_vars(l, &env_lookup)))
+            } else {
+                Self::from_iter(lines)
+            }
         }
     }
 
     pub(crate) fn from_git_repo(
         url: &Path,
         _bound_options: Option<&Vec<String>>,
+        env_expand: bool,
     ) -> ResultDynError<Self> {
         let tmp_dir = tempdir()
             .map_err(|e| format!("Failed to create temporary directory: {}", e))?;
@@ -277,7 +975,10 @@ impl DepManifest {
         }
         // TODO: look for pyproject first
         let requirements_path = repo_path.join("requirements.txt");
-        let manifest = DepManifest::from_requirements_file(&requirements_path)?;
+        let manifest = DepManifest::from_requirements_file_with_env_expand(
+            &requirements_path,
+            env_expand,
+        )?;
         Ok(manifest)
     }
 
@@ -288,8 +989,16 @@ impl DepManifest {
         keys
     }
 
+    // Return all DepSpec references, sorted by key.
+    pub(crate) fn get_dep_specs(&self) -> Vec<&DepSpec> {
+        self.keys()
+            .iter()
+            .filter_map(|key| self.dep_specs.get(key))
+            .collect()
+    }
+
     // Return an optional DepSpec reference.
-    pub(crate) fn get_dep_spec(&self, key: &str) -> Option<&DepSpec> {
+    pub fn get_dep_spec(&self, key: &str) -> Option<&DepSpec> {
         self.dep_specs.get(key)
     }
 
@@ -308,19 +1017,123 @@ impl DepManifest {
         dep_specs
     }
 
+    /// Return true if any DepSpec requests extras.
+    pub(crate) fn has_extras(&self) -> bool {
+        self.dep_specs.values().any(|ds| !ds.extras.is_empty())
+    }
+
+    /// Return all DepSpec that request extras, sorted by key.
+    pub(crate) fn get_dep_specs_with_extras(&self) -> Vec<&DepSpec> {
+        let mut dep_specs: Vec<&DepSpec> = self
+            .dep_specs
+            .values()
+            .filter(|ds| !ds.extras.is_empty())
+            .collect();
+        dep_specs.sort_by(|a, b| a.key.cmp(&b.key));
+        dep_specs
+    }
+
+    /// Return a new DepManifest that includes the provided DepSpecs; DepSpecs for packages already defined are combined.
+    pub(crate) fn extend(&self, dep_specs: Vec<DepSpec>) -> ResultDynError<Self> {
+        let mut combined: Vec<DepSpec> = self.dep_specs.values().cloned().collect();
+        combined.extend(dep_specs);
+        Self::from_dep_specs(&combined)
+    }
+
+    /// Return a new DepManifest with only the DepSpecs of packages whose names match the filter.
+    pub(crate) fn filter_packages(&self, filter: &PackageFilter) -> Self {
+        let dep_specs = self
+            .dep_specs
+            .iter()
+            .filter(|(_, ds)| filter.is_match(&[&ds.name, &ds.key]))
+            .map(|(k, ds)| (k.clone(), ds.clone()))
+            .collect();
+        DepManifest { dep_specs }
+    }
+
+    /// Return a new DepManifest that merges `other`, as when layering bound requirements. Packages defined in one are taken as is; for packages defined in both, all version specifiers must be satisfied, extras are combined, a URL defined in either is kept, and, if both define hashes, only hashes defined in both are permitted. Different URLs, version specifiers that no version can satisfy, and hashes without any in common are conflicts, and are an error.
+    pub(crate) fn merge(&self, other: &DepManifest) -> ResultDynError<Self> {
+        let mut dep_specs = self.dep_specs.clone();
+        let mut keys: Vec<&String> = other.dep_specs.keys().collect();
+        keys.sort();
+        for key in keys {
+            let ds_other = &other.dep_specs[key];
+            let ds = match dep_specs.remove(key) {
+                Some(ds) => ds,
+                None => {
+                    dep_specs.insert(key.clone(), ds_other.clone());
+                    continue;
+                }
+            };
+            let url = match (&ds.url, &ds_other.url) {
+                (Some(a), Some(b)) if a != b => {
+                    return Err(
+                        format!("Conflicting URLs for {}: {} and {}", key, a, b).into()
+                    );
+                }
+                (a, b) => a.clone().or_else(|| b.clone()),
+            };
+            let mut ds_new = ds.intersect(ds_other)?;
+            ds_new.url = url;
+            if !ds.hashes.is_empty() && !ds_other.hashes.is_empty() {
+                ds_new.hashes = ds
+                    .hashes
+                    .iter()
+                    .filter(|h| ds_other.hashes.contains(h))
+                    .cloned()
+                    .collect();
+                if ds_new.hashes.is_empty() {
+                    return Err(format!("Conflicting hashes for {}", key).into());
+                }
+            }
+            dep_specs.insert(key.clone(), ds_new);
+        }
+        Ok(DepManifest { dep_specs })
+    }
+
+    /// Return true if any DepSpec defines an environment marker.
+    pub(crate) fn has_markers(&self) -> bool {
+        self.dep_specs.values().any(|ds| ds.marker.is_some())
+    }
+
     //--------------------------------------------------------------------------
-    #[allow(dead_code)]
-    pub(crate) fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         self.dep_specs.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.dep_specs.is_empty()
+    }
+
+    /// Return an error describing each DepSpec whose version specifiers, after merging includes, constraints, and groups, cannot be satisfied by any version (e.g. `>=2.0,<1.5`).
+    pub(crate) fn validate_satisfiable(&self) -> ResultDynError<()> {
+        let contradictions: Vec<String> = self
+            .get_dep_specs()
+            .into_iter()
+            .filter_map(|ds| {
+                ds.to_contradiction()
+                    .map(|contradiction| format!("{}: {}", ds, contradiction))
+            })
+            .collect();
+        if contradictions.is_empty() {
+            Ok(())
+        } else {
+            Err(
+                format!("Unsatisfiable requirements:\n{}", contradictions.join("\n"))
+                    .into(),
+            )
+        }
+    }
+
     pub(crate) fn validate(
         &self,
         package: &Package,
         permit_superset: bool,
     ) -> (bool, Option<&DepSpec>) {
         if let Some(ds) = self.dep_specs.get(&package.key) {
-            let valid = ds.validate_version(&package.version) && ds.validate_url(package);
+            let valid = ds.validate_version(&package.version)
+                && ds.validate_url(package)
+                && ds.validate_hash(package);
             (valid, Some(ds))
         } else {
             (permit_superset, None) // cannot get a dep spec
@@ -344,6 +1157,11 @@ impl DepManifest {
 
 //------------------------------------------------------------------------------
 #[cfg(test)]
+#[allow(
+    clippy::bool_assert_comparison,
+    clippy::useless_vec,
+    clippy::writeln_empty_string
+)]
 mod tests {
     use super::*;
     use crate::package_durl::DirectURL;
@@ -407,6 +1225,97 @@ mod tests {
         assert_eq!(dep_manifest.validate(&p4, false).0, false);
     }
 
+    #[test]
+    fn test_editable_to_requirement_a() {
+        assert_eq!(
+            editable_comment_requirement(
+                "# Editable install with no version control (mypkg==1.0)"
+            ),
+            Some("mypkg==1.0")
+        );
+        assert_eq!(editable_comment_requirement("# numpy (pinned)"), None);
+        assert_eq!(
+            editable_to_requirement(
+                "-e git+https://github.com/owner/repo.git@41b95ec#egg=package_two",
+                None
+            ),
+            Some("package_two".to_string())
+        );
+        assert_eq!(
+            editable_to_requirement("--editable=/opt/src/mypkg", Some("mypkg==1.0")),
+            Some("mypkg==1.0".to_string())
+        );
+        assert_eq!(editable_to_requirement("-e /opt/src/mypkg", None), None);
+        assert_eq!(editable_to_requirement("numpy==2.1.0", None), None);
+    }
+
+    #[test]
+    fn test_from_content_freeze_a() {
+        let content = "# Editable install with no version control (mypkg==1.0)\n-e /opt/src/mypkg\n-e git+https://github.com/owner/repo.git@41b95ec#egg=package_two\nnumpy==2.1.0\nsix @ file:///tmp/six-1.16.0-py2.py3-none-any.whl\n";
+        let dm = DepManifest::from_content(content, None, None, true, false).unwrap();
+        assert_eq!(dm.keys(), vec!["mypkg", "numpy", "package_two", "six"]);
+        assert_eq!(dm.get_dep_spec("mypkg").unwrap().to_string(), "mypkg==1.0");
+        assert_eq!(
+            dm.get_dep_spec("package_two").unwrap().to_string(),
+            "package_two"
+        );
+        assert_eq!(
+            dm.get_dep_spec("six").unwrap().url.as_deref(),
+            Some("file:///tmp/six-1.16.0-py2.py3-none-any.whl")
+        );
+    }
+
+    #[test]
+    fn test_requirement_lines_a() {
+        let lines = requirement_lines(vec![
+            "# a comment",
+            "certifi==2024.8.30 \\",
+            "    --hash=sha256:bec941d2 \\",
+            "    --hash=sha256:922820b5",
+            "    # via requests",
+            "",
+            "dill @ git+https://github.com/uqfoundation/dill.git#egg=dill  # pinned",
+        ]);
+        assert_eq!(
+            lines,
+            vec![
+                "certifi==2024.8.30      --hash=sha256:bec941d2      --hash=sha256:922820b5",
+                "dill @ git+https://github.com/uqfoundation/dill.git#egg=dill",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_requirements_hashes_a() {
+        let content = r#"#
+# This file is autogenerated by pip-compile with Python 3.12
+#
+--index-url https://pypi.org/simple
+
+certifi==2024.8.30 \
+    --hash=sha256:922820b53db7a7257ffbda3f597266d435245903d80737e34f8a45ff3e3230d8 \
+    --hash=sha256:bec941d2aa8195e248a60b31ff9f0558284cf01a52591ceda73ea9afffd69fd9
+    # via requests
+idna==3.10 \
+    --hash=sha256:12f65c9b470abda6dc35cf8e63cc574b1c52b11df2c86030af0ac09b01b13ea9
+    # via requests
+"#;
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("requirements.txt");
+        let mut file = File::create(&file_path).unwrap();
+        write!(file, "{}", content).unwrap();
+
+        let dm = DepManifest::from_requirements_file(&file_path).unwrap();
+        assert_eq!(dm.keys(), vec!["certifi", "idna"]);
+        assert_eq!(dm.get_dep_spec("certifi").unwrap().hashes.len(), 2);
+        assert_eq!(
+            dm.get_dep_spec("idna").unwrap().hashes,
+            vec![
+                "sha256:12f65c9b470abda6dc35cf8e63cc574b1c52b11df2c86030af0ac09b01b13ea9"
+            ]
+        );
+    }
+
     #[test]
     fn test_from_requirements_b() {
         let content = r#"
@@ -569,44 +1478,174 @@ regex==2024.4.16
         let mut f2 = File::create(&fp2).unwrap();
         write!(f2, "{}", content2).unwrap();
 
-        let dm1 = DepManifest::from_requirements_file(&fp2).unwrap();
-        assert_eq!(dm1.len(), 9);
+        let dm1 = DepManifest::from_requirements_file(&fp2).unwrap();
+        assert_eq!(dm1.len(), 9);
+    }
+
+    #[test]
+    fn test_from_requirements_f() {
+        let content1 = r#"
+python-slugify==8.0.4
+pytz==2023.3
+pytzdata==2020.1
+pyyaml==6.0
+pyzmq==26.0.0
+"#;
+        let dir = tempdir().unwrap();
+        let fp1 = dir.path().join("requirements-a.txt");
+        let mut f1 = File::create(&fp1).unwrap();
+        write!(f1, "{}", content1).unwrap();
+
+        let content2 = r#"
+readme-renderer==43.0
+redshift-connector==2.1.1
+--requirement  requirements-a.txt
+"#;
+        let fp2 = dir.path().join("requirements-b.txt");
+        let mut f2 = File::create(&fp2).unwrap();
+        write!(f2, "{}", content2).unwrap();
+
+        let content3 = r#"
+referencing==0.34.0
+regex==2024.4.16
+--requirement    requirements-b.txt
+"#;
+        let fp3 = dir.path().join("requirements-c.txt");
+        let mut f3 = File::create(&fp3).unwrap();
+        write!(f3, "{}", content3).unwrap();
+
+        let dm1 = DepManifest::from_requirements_file(&fp3).unwrap();
+        assert_eq!(dm1.len(), 9);
+    }
+
+    #[test]
+    fn test_from_requirements_g() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("requirements")).unwrap();
+        fs::write(
+            dir.path().join("requirements.txt"),
+            "pytz==2023.3\n-r requirements/base.txt\n-r requirements/dev.txt\n",
+        )
+        .unwrap();
+        // includes are relative to the including file; a cycle is only read once
+        fs::write(
+            dir.path().join("requirements/base.txt"),
+            "pyyaml==6.0\n-r ../requirements.txt\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("requirements/dev.txt"),
+            "regex==2024.4.16\n--requirement=base.txt\n",
+        )
+        .unwrap();
+        let dm1 =
+            DepManifest::from_requirements_file(&dir.path().join("requirements.txt"))
+                .unwrap();
+        assert_eq!(dm1.keys(), vec!["pytz", "pyyaml", "regex"]);
+    }
+
+    #[test]
+    fn test_from_requirements_h() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("requirements.txt"),
+            "requests>=2\nnumpy\n-c constraints.txt\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("constraints.txt"),
+            "numpy<2\n-c constraints-extra.txt\n",
+        )
+        .unwrap();
+        // constraints of packages not required are not added
+        fs::write(
+            dir.path().join("constraints-extra.txt"),
+            "requests<3\nurllib3<2\n",
+        )
+        .unwrap();
+        let dm1 =
+            DepManifest::from_requirements_file(&dir.path().join("requirements.txt"))
+                .unwrap();
+        assert_eq!(dm1.keys(), vec!["numpy", "requests"]);
+        assert_eq!(dm1.get_dep_spec("numpy").unwrap().to_string(), "numpy<2");
+        assert_eq!(
+            dm1.get_dep_spec("requests").unwrap().to_string(),
+            "requests>=2,<3"
+        );
+    }
+
+    #[test]
+    fn test_from_requirements_j() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("requirements.txt"),
+            "requests>=2\n-c constraints.txt\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("constraints.txt"), "requests<1.5\n").unwrap();
+        let err =
+            DepManifest::from_requirements_file(&dir.path().join("requirements.txt"))
+                .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unsatisfiable requirement requests>=2,<1.5: >=2 contradicts <1.5"
+        );
+    }
+
+    #[test]
+    fn test_validate_satisfiable_a() {
+        let dm =
+            DepManifest::from_iter(vec!["numpy>=1.26,<2", "requests"].iter()).unwrap();
+        assert!(dm.validate_satisfiable().is_ok());
+        let dm = DepManifest::from_iter(
+            vec!["numpy>=2.0,<1.5", "requests", "six==1.16.0,!=1.16.0"].iter(),
+        )
+        .unwrap();
+        assert_eq!(
+            dm.validate_satisfiable().unwrap_err().to_string(),
+            "Unsatisfiable requirements:\nnumpy>=2.0,<1.5: >=2.0 contradicts <1.5\nsix==1.16.0,!=1.16.0: ==1.16.0 contradicts !=1.16.0"
+        );
+    }
+
+    #[test]
+    fn test_expand_env_vars_a() {
+        let lookup =
+            |name: &str| (name == "FETTER_TEST_TOKEN").then(|| "abc123".to_string());
+        assert_eq!(
+            expand_env_vars(
+                "pkg @ https://${FETTER_TEST_TOKEN}@example.com/pkg.whl",
+                &lookup
+            ),
+            "pkg @ https://abc123@example.com/pkg.whl"
+        );
+        // undefined, lowercase, and unterminated references are retained
+        assert_eq!(
+            expand_env_vars(
+                "${FETTER_TEST_UNDEFINED} ${fetter_test_token} ${FETTER_TEST_TOKEN",
+                &lookup
+            ),
+            "${FETTER_TEST_UNDEFINED} ${fetter_test_token} ${FETTER_TEST_TOKEN"
+        );
+        assert_eq!(
+            expand_env_vars("$FETTER_TEST_TOKEN ${}", &lookup),
+            "$FETTER_TEST_TOKEN ${}"
+        );
     }
 
     #[test]
-    fn test_from_requirements_f() {
-        let content1 = r#"
-python-slugify==8.0.4
-pytz==2023.3
-pytzdata==2020.1
-pyyaml==6.0
-pyzmq==26.0.0
-"#;
+    fn test_from_requirements_i() {
+        let lookup =
+            |name: &str| (name == "FETTER_TEST_VERSION").then(|| "2.31.0".to_string());
         let dir = tempdir().unwrap();
-        let fp1 = dir.path().join("requirements-a.txt");
-        let mut f1 = File::create(&fp1).unwrap();
-        write!(f1, "{}", content1).unwrap();
-
-        let content2 = r#"
-readme-renderer==43.0
-redshift-connector==2.1.1
---requirement  requirements-a.txt
-"#;
-        let fp2 = dir.path().join("requirements-b.txt");
-        let mut f2 = File::create(&fp2).unwrap();
-        write!(f2, "{}", content2).unwrap();
-
-        let content3 = r#"
-referencing==0.34.0
-regex==2024.4.16
---requirement    requirements-b.txt
-"#;
-        let fp3 = dir.path().join("requirements-c.txt");
-        let mut f3 = File::create(&fp3).unwrap();
-        write!(f3, "{}", content3).unwrap();
-
-        let dm1 = DepManifest::from_requirements_file(&fp3).unwrap();
-        assert_eq!(dm1.len(), 9);
+        let fp = dir.path().join("requirements.txt");
+        fs::write(&fp, "requests==${FETTER_TEST_VERSION}\n").unwrap();
+        let dm1 = DepManifest::from_requirements_file_with_env_lookup(&fp, Some(&lookup))
+            .unwrap();
+        assert_eq!(
+            dm1.get_dep_spec("requests").unwrap().to_string(),
+            "requests==2.31.0"
+        );
+        assert!(DepManifest::from_requirements_file_with_env_expand(&fp, false).is_err());
     }
 
     //--------------------------------------------------------------------------
@@ -750,6 +1789,44 @@ cli = [
         assert!(DepManifest::from_pyproject_file(&file_path, Some(&bo3)).is_err());
     }
 
+    #[test]
+    fn test_from_pyproject_b3() {
+        let content = r#"
+[project]
+name = "example"
+version = "0.0.1"
+dependencies = [
+  "httpx",
+]
+[project.optional-dependencies]
+cli = ["rich"]
+
+[dependency-groups]
+test = ["pytest>=8", "coverage"]
+Type_Check = ["mypy"]
+dev = [{include-group = "test"}, {include-group = "type-check"}, "ruff"]
+loop = [{include-group = "loop"}]
+broken = [{include-group = "missing"}]
+"#;
+        let bo1 = vec!["test".to_string()];
+        let dm1 = DepManifest::from_pyproject(content, Some(&bo1)).unwrap();
+        assert_eq!(dm1.keys(), vec!["coverage", "httpx", "pytest"]);
+
+        let bo2 = vec!["cli".to_string(), "dev".to_string()];
+        let dm2 = DepManifest::from_pyproject(content, Some(&bo2)).unwrap();
+        assert_eq!(
+            dm2.keys(),
+            vec!["coverage", "httpx", "mypy", "pytest", "rich", "ruff"]
+        );
+
+        let bo3 = vec!["loop".to_string()];
+        assert!(DepManifest::from_pyproject(content, Some(&bo3)).is_err());
+        let bo4 = vec!["broken".to_string()];
+        assert!(DepManifest::from_pyproject(content, Some(&bo4)).is_err());
+        let bo5 = vec!["docs".to_string()];
+        assert!(DepManifest::from_pyproject(content, Some(&bo5)).is_err());
+    }
+
     #[test]
     fn test_from_pyproject_c1() {
         let content = r#"
@@ -867,6 +1944,11 @@ pytest-github-actions-annotate-failures = "==0.1.7"
             dm.get_dep_spec("importlib_metadata").unwrap().to_string(),
             "importlib-metadata>=4.4"
         );
+        assert_eq!(
+            dm.get_dep_spec("xattr").unwrap().to_string(),
+            "xattr==1.0.0; sys_platform == \"darwin\""
+        );
+        assert_eq!(dm.has_markers(), true);
     }
 
     #[test]
@@ -1129,6 +2211,423 @@ pytest-github-actions-annotate-failures = "==0.1.7"
 
     //--------------------------------------------------------------------------
 
+    #[test]
+    fn test_from_poetry_lock_a() {
+        let content = r#"
+# This file is automatically @generated by Poetry 1.8.3 and should not be changed by hand.
+
+[[package]]
+name = "certifi"
+version = "2024.8.30"
+description = "Python package for providing Mozilla's CA Bundle."
+optional = false
+python-versions = ">=3.6"
+files = [
+    {file = "certifi-2024.8.30-py3-none-any.whl", hash = "sha256:922820b53db7a7257ffbda3f597266d435245903d80737e34f8a45ff3e3230d8"},
+    {file = "certifi-2024.8.30.tar.gz", hash = "sha256:bec941d2aa8195e248a60b31ff9f0558284cf01a52591ceda73ea9afffd69fd9"},
+]
+
+[[package]]
+name = "colorama"
+version = "0.4.6"
+description = "Cross-platform colored terminal text."
+optional = false
+python-versions = "!=3.0.*,!=3.1.*,!=3.2.*,!=3.3.*,!=3.4.*,!=3.5.*,!=3.6.*,>=2.7"
+markers = "sys_platform == \"win32\""
+files = [
+    {file = "colorama-0.4.6-py2.py3-none-any.whl", hash = "sha256:4f1d9991f5acc0ca119f9d443620b77f9d6b33703e51011c16baf57afb285fc6"},
+]
+
+[[package]]
+name = "dill"
+version = "0.3.8"
+description = ""
+optional = false
+python-versions = ">=3.8"
+files = []
+
+[package.source]
+type = "git"
+url = "https://github.com/uqfoundation/dill.git"
+reference = "HEAD"
+resolved_reference = "a0a8e86976708d0436eec5c8f7d25329da727cb5"
+
+[metadata]
+lock-version = "2.0"
+python-versions = "^3.11"
+content-hash = "f8b2a1c5e6d1"
+"#;
+        let dm = DepManifest::from_poetry_lock(content, None, true).unwrap();
+        assert_eq!(dm.keys(), vec!["certifi", "colorama", "dill"]);
+
+        let ds1 = dm.get_dep_spec("certifi").unwrap();
+        assert_eq!(ds1.to_string(), "certifi==2024.8.30");
+        assert_eq!(ds1.hashes.len(), 2);
+        assert!(ds1.hashes[0].starts_with("sha256:9228"));
+
+        // hashes are only recorded if requested
+        let dm = DepManifest::from_poetry_lock(content, None, false).unwrap();
+        assert!(dm.get_dep_spec("certifi").unwrap().hashes.is_empty());
+        let dm = DepManifest::from_poetry_lock(content, None, true).unwrap();
+
+        let ds2 = dm.get_dep_spec("colorama").unwrap();
+        assert_eq!(
+            ds2.to_string(),
+            "colorama==0.4.6; sys_platform == \"win32\""
+        );
+        let ds3 = dm.get_dep_spec("dill").unwrap();
+        assert_eq!(
+            ds3.url.as_deref(),
+            Some("git+https://github.com/uqfoundation/dill.git@a0a8e86976708d0436eec5c8f7d25329da727cb5")
+        );
+        assert!(ds3.hashes.is_empty());
+        assert!(dm.has_markers());
+    }
+
+    #[test]
+    fn test_from_poetry_lock_b() {
+        let content = r#"
+[[package]]
+name = "iniconfig"
+version = "2.0.0"
+category = "dev"
+optional = false
+python-versions = ">=3.7"
+
+[[package]]
+name = "packaging"
+version = "24.1"
+category = "main"
+optional = false
+python-versions = ">=3.8"
+
+[metadata]
+lock-version = "1.1"
+
+[metadata.files]
+iniconfig = [
+    {file = "iniconfig-2.0.0-py3-none-any.whl", hash = "sha256:b6a85871a79d2e3b22d2d1b94ac2824226a63c6b741c88f7ae975f18b6778374"},
+]
+packaging = [
+    {file = "packaging-24.1-py3-none-any.whl", hash = "sha256:5b8f2217dbdbd2f7f384c41c628544e6d52f2d0f53c6d0c3ea61aa5d1d7ff124"},
+]
+"#;
+        let dm1 = DepManifest::from_poetry_lock(content, None, true).unwrap();
+        assert_eq!(dm1.keys(), vec!["packaging"]);
+        assert_eq!(
+            dm1.get_dep_spec("packaging").unwrap().hashes,
+            vec![
+                "sha256:5b8f2217dbdbd2f7f384c41c628544e6d52f2d0f53c6d0c3ea61aa5d1d7ff124"
+            ]
+        );
+
+        let bo2 = vec!["dev".to_string()];
+        let dm2 = DepManifest::from_poetry_lock(content, Some(&bo2), true).unwrap();
+        assert_eq!(dm2.keys(), vec!["iniconfig", "packaging"]);
+
+        let bo3 = vec!["docs".to_string()];
+        assert!(DepManifest::from_poetry_lock(content, Some(&bo3), true).is_err());
+    }
+
+    #[test]
+    fn test_from_poetry_lock_c() {
+        let content = r#"
+[[package]]
+name = "pytest"
+version = "8.3.3"
+groups = ["test"]
+files = []
+
+[[package]]
+name = "requests"
+version = "2.32.3"
+groups = ["main", "test"]
+files = []
+"#;
+        let dm1 = DepManifest::from_poetry_lock(content, None, true).unwrap();
+        assert_eq!(dm1.keys(), vec!["requests"]);
+
+        let bo2 = vec!["test".to_string()];
+        let dm2 = DepManifest::from_poetry_lock(content, Some(&bo2), true).unwrap();
+        assert_eq!(dm2.keys(), vec!["pytest", "requests"]);
+
+        let dir = tempdir().unwrap();
+        let fp = dir.path().join("poetry.lock");
+        fs::write(&fp, content).unwrap();
+        let dm3 = DepManifest::from_poetry_lock_file(&fp, Some(&bo2), true).unwrap();
+        assert_eq!(dm3.len(), 2);
+    }
+
+    #[test]
+    fn test_from_pipfile_lock_a() {
+        let content = r#"{
+    "_meta": {
+        "hash": {"sha256": "7f7606f08e0544d8d012ef4d097dabdd6df6843a28793eb6551245d4b2db4242"},
+        "pipfile-spec": 6,
+        "requires": {"python_version": "3.11"},
+        "sources": [{"name": "pypi", "url": "https://pypi.org/simple", "verify_ssl": true}]
+    },
+    "default": {
+        "certifi": {
+            "hashes": [
+                "sha256:922820b53db7a7257ffbda3f597266d435245903d80737e34f8a45ff3e3230d8",
+                "sha256:bec941d2aa8195e248a60b31ff9f0558284cf01a52591ceda73ea9afffd69fd9"
+            ],
+            "index": "pypi",
+            "markers": "python_version >= '3.6'",
+            "version": "==2024.8.30"
+        },
+        "dill": {
+            "git": "https://github.com/uqfoundation/dill.git",
+            "ref": "a0a8e86976708d0436eec5c8f7d25329da727cb5"
+        },
+        "requests": {
+            "extras": ["socks"],
+            "hashes": [],
+            "version": "==2.32.3"
+        }
+    },
+    "develop": {
+        "pytest": {
+            "hashes": ["sha256:a6853c7375b2663155079443d2e45de913a911a11d669df02a50814944db57b2"],
+            "version": "==8.3.3"
+        }
+    }
+}"#;
+        let dm1 = DepManifest::from_pipfile_lock(content, None, true).unwrap();
+        assert_eq!(dm1.keys(), vec!["certifi", "dill", "requests"]);
+
+        let ds1 = dm1.get_dep_spec("certifi").unwrap();
+        assert_eq!(
+            ds1.to_string(),
+            "certifi==2024.8.30; python_version >= \"3.6\""
+        );
+        assert_eq!(ds1.hashes.len(), 2);
+        let dm3 = DepManifest::from_pipfile_lock(content, None, false).unwrap();
+        assert!(dm3.get_dep_spec("certifi").unwrap().hashes.is_empty());
+        assert_eq!(
+            dm1.get_dep_spec("dill").unwrap().url.as_deref(),
+            Some("git+https://github.com/uqfoundation/dill.git@a0a8e86976708d0436eec5c8f7d25329da727cb5")
+        );
+        assert_eq!(
+            dm1.get_dep_spec("requests").unwrap().to_string(),
+            "requests[socks]==2.32.3"
+        );
+
+        let bo2 = vec!["develop".to_string()];
+        let dm2 = DepManifest::from_pipfile_lock(content, Some(&bo2), true).unwrap();
+        assert_eq!(dm2.keys(), vec!["certifi", "dill", "pytest", "requests"]);
+
+        let bo3 = vec!["test".to_string()];
+        assert!(DepManifest::from_pipfile_lock(content, Some(&bo3), true).is_err());
+    }
+
+    #[test]
+    fn test_from_pipfile_lock_b() {
+        let dir = tempdir().unwrap();
+        let fp = dir.path().join("Pipfile.lock");
+        fs::write(
+            &fp,
+            r#"{"_meta": {}, "default": {"six": {"version": "==1.16.0"}}, "develop": {}}"#,
+        )
+        .unwrap();
+        let dm = DepManifest::from_pipfile_lock_file(&fp, None, true).unwrap();
+        assert_eq!(dm.get_dep_spec("six").unwrap().to_string(), "six==1.16.0");
+        assert!(DepManifest::from_pipfile_lock("{", None, true).is_err());
+    }
+
+    #[test]
+    fn test_from_uv_lock_a() {
+        let content = r#"
+version = 1
+requires-python = ">=3.11"
+resolution-markers = [
+    "python_full_version >= '3.12'",
+    "python_full_version < '3.12'",
+]
+
+[[package]]
+name = "certifi"
+version = "2024.8.30"
+source = { registry = "https://pypi.org/simple" }
+sdist = { url = "https://files.pythonhosted.org/packages/certifi-2024.8.30.tar.gz", hash = "sha256:bec941d2aa8195e248a60b31ff9f0558284cf01a52591ceda73ea9afffd69fd9", size = 168507 }
+wheels = [
+    { url = "https://files.pythonhosted.org/packages/certifi-2024.8.30-py3-none-any.whl", hash = "sha256:922820b53db7a7257ffbda3f597266d435245903d80737e34f8a45ff3e3230d8", size = 167321 },
+]
+
+[[package]]
+name = "dill"
+version = "0.3.8"
+source = { git = "https://github.com/uqfoundation/dill.git?rev=0.3.8#a0a8e86976708d0436eec5c8f7d25329da727cb5" }
+
+[[package]]
+name = "example"
+version = "0.1.0"
+source = { virtual = "." }
+dependencies = [
+    { name = "certifi" },
+    { name = "dill" },
+    { name = "numpy" },
+]
+
+[[package]]
+name = "numpy"
+version = "2.0.2"
+source = { registry = "https://pypi.org/simple" }
+resolution-markers = [
+    "python_full_version < '3.12'",
+]
+wheels = [
+    { url = "https://files.pythonhosted.org/packages/numpy-2.0.2-cp311-cp311-manylinux_2_17_x86_64.whl", hash = "sha256:a0a8e86976708d0436eec5c8f7d25329da727cb5a0a8e86976708d0436eec5c8", size = 1 },
+]
+
+[[package]]
+name = "numpy"
+version = "2.1.2"
+source = { registry = "https://pypi.org/simple" }
+resolution-markers = [
+    "python_full_version >= '3.12'",
+]
+wheels = [
+    { url = "https://files.pythonhosted.org/packages/numpy-2.1.2-cp312-cp312-manylinux_2_17_x86_64.whl", hash = "sha256:b0a8e86976708d0436eec5c8f7d25329da727cb5a0a8e86976708d0436eec5c8", size = 1 },
+]
+
+[[package]]
+name = "tomli"
+version = "2.0.2"
+source = { registry = "https://pypi.org/simple" }
+resolution-markers = [
+    "python_full_version < '3.11'",
+]
+"#;
+        let dm = DepManifest::from_uv_lock(content, true).unwrap();
+        assert_eq!(dm.keys(), vec!["certifi", "dill", "numpy", "tomli"]);
+
+        let ds1 = dm.get_dep_spec("certifi").unwrap();
+        assert_eq!(ds1.to_string(), "certifi==2024.8.30");
+        assert_eq!(ds1.hashes.len(), 2);
+
+        assert_eq!(
+            dm.get_dep_spec("dill").unwrap().url.as_deref(),
+            Some("git+https://github.com/uqfoundation/dill.git@a0a8e86976708d0436eec5c8f7d25329da727cb5")
+        );
+        let ds3 = dm.get_dep_spec("numpy").unwrap();
+        assert_eq!(ds3.to_string(), "numpy");
+        assert_eq!(ds3.hashes.len(), 2);
+
+        let dm2 = DepManifest::from_uv_lock(content, false).unwrap();
+        assert!(dm2.get_dep_spec("certifi").unwrap().hashes.is_empty());
+        assert!(dm2.get_dep_spec("numpy").unwrap().hashes.is_empty());
+
+        assert_eq!(
+            dm.get_dep_spec("tomli").unwrap().to_string(),
+            "tomli==2.0.2; python_full_version < \"3.11\""
+        );
+        assert!(dm.has_markers());
+    }
+
+    #[test]
+    fn test_from_uv_lock_b() {
+        let dir = tempdir().unwrap();
+        let fp = dir.path().join("uv.lock");
+        fs::write(
+            &fp,
+            "version = 1\n[[package]]\nname = \"six\"\nversion = \"1.16.0\"\nsource = { registry = \"https://pypi.org/simple\" }\n",
+        )
+        .unwrap();
+        let dm = DepManifest::from_uv_lock_file(&fp, true).unwrap();
+        assert_eq!(dm.get_dep_spec("six").unwrap().to_string(), "six==1.16.0");
+        assert!(DepManifest::from_uv_lock("version = 1\n", true).is_err());
+    }
+
+    #[test]
+    fn test_conda_spec_to_requirement_a() {
+        assert_eq!(
+            conda_spec_to_requirement("numpy=1.26").as_deref(),
+            Some("numpy==1.26.*")
+        );
+        assert_eq!(
+            conda_spec_to_requirement("numpy==1.26.4=py311h64a7726_0").as_deref(),
+            Some("numpy==1.26.4")
+        );
+        assert_eq!(
+            conda_spec_to_requirement("conda-forge::scipy>=1.11,<2").as_deref(),
+            Some("scipy>=1.11,<2")
+        );
+        assert_eq!(
+            conda_spec_to_requirement("pandas 2.2.*").as_deref(),
+            Some("pandas==2.2.*")
+        );
+        assert_eq!(
+            conda_spec_to_requirement("pyyaml=6.0.*").as_deref(),
+            Some("pyyaml==6.0.*")
+        );
+        assert_eq!(
+            conda_spec_to_requirement("scipy 1.11*").as_deref(),
+            Some("scipy==1.11.*")
+        );
+        assert_eq!(
+            conda_spec_to_requirement("scipy=1.11*").as_deref(),
+            Some("scipy==1.11.*")
+        );
+        assert_eq!(
+            conda_spec_to_requirement("six 1.16|1.17").as_deref(),
+            Some("six")
+        );
+        assert_eq!(conda_spec_to_requirement("python=3.11"), None);
+    }
+
+    #[test]
+    fn test_from_environment_yml_a() {
+        let content = r#"
+name: analysis
+channels:
+  - conda-forge
+  - defaults
+dependencies:
+  - python=3.11
+  - numpy=1.26  # pinned for abi
+  - conda-forge::scipy>=1.11
+  - pip
+  - pip:
+    - requests==2.32.3
+    - "static-frame>=2.0"
+    - -r requirements-extra.txt
+  - pyyaml
+variables:
+  MODE: test
+"#;
+        let dm = DepManifest::from_environment_yml(content).unwrap();
+        assert_eq!(
+            dm.keys(),
+            vec![
+                "numpy",
+                "pip",
+                "pyyaml",
+                "requests",
+                "scipy",
+                "static_frame"
+            ]
+        );
+        assert_eq!(
+            dm.get_dep_spec("numpy").unwrap().to_string(),
+            "numpy==1.26.*"
+        );
+        assert_eq!(
+            dm.get_dep_spec("requests").unwrap().to_string(),
+            "requests==2.32.3"
+        );
+    }
+
+    #[test]
+    fn test_from_environment_yml_b() {
+        let dir = tempdir().unwrap();
+        let fp = dir.path().join("environment.yml");
+        fs::write(&fp, "name: x\ndependencies:\n- six=1.16.0\n").unwrap();
+        let dm = DepManifest::from_environment_yml_file(&fp).unwrap();
+        assert_eq!(dm.get_dep_spec("six").unwrap().to_string(), "six==1.16.0.*");
+    }
+
     #[test]
     fn test_from_url_a() {
         let mock_get = r#"
@@ -1143,10 +2642,140 @@ numpy>= 2.0
         };
 
         let url = PathBuf::from("http://example.com/requirements.txt");
-        let dm = DepManifest::from_url(&client, &url, None).unwrap();
+        let dm = DepManifest::from_url(&client, &url, &[], None, true, false).unwrap();
         assert_eq!(dm.keys(), vec!["dill", "numpy", "six"])
     }
 
+    #[test]
+    fn test_from_url_b() {
+        let client = UreqClientMock {
+            mock_post: None,
+            mock_get: Some(
+                "[[package]]\nname = \"six\"\nversion = \"1.16.0\"\n".to_string(),
+            ),
+        };
+        let headers = vec![("Authorization".to_string(), "Bearer abc".to_string())];
+        // the format is named by the URL path, not the query
+        let url = PathBuf::from("https://example.com/uv.lock?ref=main");
+        let dm =
+            DepManifest::from_url(&client, &url, &headers, None, true, false).unwrap();
+        assert_eq!(dm.keys(), vec!["six"]);
+        // headers are not sent over HTTP
+        let url = PathBuf::from("http://example.com/uv.lock");
+        assert!(
+            DepManifest::from_url(&client, &url, &headers, None, true, false).is_err()
+        );
+    }
+
+    #[test]
+    fn test_filter_packages_a() {
+        let dm = DepManifest::from_iter(
+            vec!["numpy>=1.26,<2", "Internal_Tools==1.0", "requests"].iter(),
+        )
+        .unwrap();
+        let dm1 =
+            dm.filter_packages(&PackageFilter::new(&[], &["internal-*".to_string()]));
+        assert_eq!(dm1.keys(), vec!["numpy", "requests"]);
+        let dm2 = dm.filter_packages(&PackageFilter::new(&["num*".to_string()], &[]));
+        assert_eq!(dm2.keys(), vec!["numpy"]);
+    }
+
+    #[test]
+    fn test_merge_a() {
+        let dm1 =
+            DepManifest::from_iter(vec!["numpy>=1.26", "requests[socks]>=2.31", "six"])
+                .unwrap();
+        let dm2 = DepManifest::from_iter(vec![
+            "numpy<2",
+            "requests[security]",
+            "gunicorn==22.0.0",
+        ])
+        .unwrap();
+        let dm3 = dm1.merge(&dm2).unwrap();
+        assert_eq!(dm3.keys(), vec!["gunicorn", "numpy", "requests", "six"]);
+        assert_eq!(
+            dm3.get_dep_spec("numpy").unwrap().to_string(),
+            "numpy>=1.26,<2"
+        );
+        assert_eq!(
+            dm3.get_dep_spec("requests").unwrap().extras,
+            vec!["security".to_string(), "socks".to_string()]
+        );
+        // the original manifests are not changed
+        assert_eq!(dm1.len(), 3);
+    }
+
+    #[test]
+    fn test_merge_b() {
+        let dm1 = DepManifest::from_iter(vec!["numpy>=2"]).unwrap();
+        let dm2 = DepManifest::from_iter(vec!["numpy<1.26"]).unwrap();
+        assert!(dm1.merge(&dm2).is_err());
+
+        let dm1 =
+            DepManifest::from_iter(vec!["six @ https://example.com/six-1.16.0.tar.gz"])
+                .unwrap();
+        let dm2 =
+            DepManifest::from_iter(vec!["six @ https://example.com/six-1.17.0.tar.gz"])
+                .unwrap();
+        assert!(dm1.merge(&dm2).is_err());
+
+        let dm3 = DepManifest::from_iter(vec!["six>=1.16"]).unwrap();
+        let dm4 = dm1.merge(&dm3).unwrap();
+        assert_eq!(
+            dm4.get_dep_spec("six").unwrap().url,
+            Some("https://example.com/six-1.16.0.tar.gz".to_string())
+        );
+    }
+
+    #[test]
+    fn test_content_to_name_a() {
+        assert_eq!(content_to_name("numpy>=2\nsix\n"), "requirements.txt");
+        assert_eq!(
+            content_to_name("{\n  \"_meta\": {},\n  \"default\": {}\n}"),
+            "Pipfile.lock"
+        );
+        assert_eq!(
+            content_to_name("version = 1\n\n[[package]]\nname = \"six\"\n"),
+            "uv.lock"
+        );
+        assert_eq!(
+            content_to_name(
+                "[[package]]\nname = \"six\"\n\n[metadata]\nlock-version = \"2.0\"\n"
+            ),
+            "poetry.lock"
+        );
+        assert_eq!(
+            content_to_name("[project]\nname = \"foo\"\n"),
+            "pyproject.toml"
+        );
+        assert_eq!(
+            content_to_name("name: env\ndependencies:\n  - numpy\n"),
+            "environment.yml"
+        );
+    }
+
+    #[test]
+    fn test_from_content_a() {
+        let dm = DepManifest::from_content(
+            "numpy>=2\n# comment\nsix\n",
+            None,
+            None,
+            true,
+            false,
+        )
+        .unwrap();
+        assert_eq!(dm.keys(), vec!["numpy", "six"]);
+        let dm = DepManifest::from_content(
+            "version = 1\n\n[[package]]\nname = \"six\"\nversion = \"1.16.0\"\n",
+            None,
+            None,
+            true,
+            false,
+        )
+        .unwrap();
+        assert_eq!(dm.keys(), vec!["six"]);
+    }
+
     //--------------------------------------------------------------------------
 
     #[test]
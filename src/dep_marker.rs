@@ -0,0 +1,471 @@
+use pest::iterators::Pair;
+use pest::Parser;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::dep_spec::DepOperator;
+use crate::dep_spec::DepSpecParser;
+use crate::dep_spec::Rule;
+use crate::util::extra_normalize;
+use crate::util::py_command;
+use crate::util::py_output;
+use crate::util::ResultDynError;
+use crate::version_spec::VersionSpec;
+use crate::zip_index::is_zip_archive;
+
+// see https://packaging.python.org/en/latest/specifications/dependency-specifiers/#environment-markers
+
+// Environment marker variables that are compared with version semantics.
+const VERSION_VARS: [&str; 3] = [
+    "python_version",
+    "python_full_version",
+    "implementation_version",
+];
+
+const PY_MARKER_ENV: &str = "import json,os,sys,platform;i=sys.implementation.version;v='%d.%d.%d'%(i.major,i.minor,i.micro)+('' if i.releaselevel=='final' else i.releaselevel[0]+str(i.serial));print(json.dumps(dict(os_name=os.name,sys_platform=sys.platform,platform_machine=platform.machine(),platform_python_implementation=platform.python_implementation(),platform_release=platform.release(),platform_system=platform.system(),platform_version=platform.version(),python_version='.'.join(platform.python_version_tuple()[:2]),python_full_version=platform.python_version(),implementation_name=sys.implementation.name,implementation_version=v)))";
+
+//------------------------------------------------------------------------------
+/// The values of environment marker variables for a specific interpreter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct MarkerEnv {
+    values: HashMap<String, String>,
+}
+
+impl MarkerEnv {
+    pub(crate) fn from_json_str(json_str: &str) -> ResultDynError<Self> {
+        let values: HashMap<String, String> = serde_json::from_str(json_str)?;
+        Ok(MarkerEnv { values })
+    }
+
+    /// Given a path to a Python binary, call out to Python to get the values of all environment marker variables. A zip archive, used in place of an executable, is not run.
+    pub(crate) fn from_exe(executable: &Path) -> ResultDynError<Self> {
+        if is_zip_archive(executable) {
+            return Err(format!("cannot run a zip archive: {:?}", executable).into());
+        }
+        let output =
+            py_output(py_command(executable, true).arg("-c").arg(PY_MARKER_ENV))?;
+        Self::from_json_str(std::str::from_utf8(&output.stdout)?.trim())
+    }
+
+    /// Given the major and minor version of an interpreter that is not run, such as one in a container filesystem, return the environment marker values that can be inferred. A CPython on Linux is assumed.
+    pub(crate) fn from_python_version(python_version: &str) -> Self {
+        Self::from_pairs(&[
+            ("os_name", "posix"),
+            ("sys_platform", "linux"),
+            ("platform_system", "Linux"),
+            ("platform_python_implementation", "CPython"),
+            ("implementation_name", "cpython"),
+            ("python_version", python_version),
+        ])
+    }
+
+    // Alternate constructor for test.
+    pub(crate) fn from_pairs(pairs: &[(&str, &str)]) -> Self {
+        let values = pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        MarkerEnv { values }
+    }
+
+    /// Return a copy of this environment with the `extra` variable set.
+    pub(crate) fn with_extra(&self, extra: &str) -> Self {
+        let mut values = self.values.clone();
+        values.insert("extra".to_string(), extra.to_string());
+        MarkerEnv { values }
+    }
+
+    // Undefined variables, such as `extra` when no extras are requested, evaluate to an empty string.
+    pub(crate) fn get(&self, name: &str) -> &str {
+        self.values.get(name).map(|v| v.as_str()).unwrap_or("")
+    }
+
+    /// Return the full version of the Python interpreter, or, if not defined, the major and minor version.
+    pub(crate) fn get_python_version(&self) -> Option<&str> {
+        ["python_full_version", "python_version"]
+            .into_iter()
+            .map(|name| self.get(name))
+            .find(|v| !v.is_empty())
+    }
+}
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum MarkerValue {
+    Variable(String),
+    Literal(String),
+}
+
+impl MarkerValue {
+    fn from_pair(pair: Pair<Rule>) -> ResultDynError<Self> {
+        let inner = pair.into_inner().next().ok_or("Expected marker value")?;
+        match inner.as_rule() {
+            Rule::env_var => Ok(MarkerValue::Variable(inner.as_str().to_string())),
+            Rule::python_str => {
+                let value = inner.into_inner().next().map_or("", |p| p.as_str());
+                Ok(MarkerValue::Literal(value.to_string()))
+            }
+            _ => Err("Expected env_var or python_str".into()),
+        }
+    }
+
+    fn is_extra_var(&self) -> bool {
+        matches!(self, MarkerValue::Variable(name) if name == "extra")
+    }
+
+    fn is_version_var(&self) -> bool {
+        matches!(self, MarkerValue::Variable(name) if VERSION_VARS.contains(&name.as_str()))
+    }
+
+    fn resolve<'a>(&'a self, env: &'a MarkerEnv) -> &'a str {
+        match self {
+            MarkerValue::Variable(name) => env.get(name),
+            MarkerValue::Literal(value) => value,
+        }
+    }
+}
+
+impl fmt::Display for MarkerValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarkerValue::Variable(name) => write!(f, "{}", name),
+            MarkerValue::Literal(value) if value.contains('"') => {
+                write!(f, "'{}'", value)
+            }
+            MarkerValue::Literal(value) => write!(f, "\"{}\"", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum MarkerOperator {
+    Version(DepOperator),
+    In,
+    NotIn,
+}
+
+impl MarkerOperator {
+    fn from_str(s: &str) -> ResultDynError<Self> {
+        match s {
+            "in" => Ok(MarkerOperator::In),
+            _ if s.starts_with("not") => Ok(MarkerOperator::NotIn),
+            _ => Ok(MarkerOperator::Version(s.parse::<DepOperator>()?)),
+        }
+    }
+}
+
+impl fmt::Display for MarkerOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarkerOperator::Version(op) => write!(f, "{}", op),
+            MarkerOperator::In => write!(f, "in"),
+            MarkerOperator::NotIn => write!(f, "not in"),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+/// A parsed environment marker, such as `python_version < "3.11" and sys_platform == "linux"`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum DepMarker {
+    Expr {
+        left: MarkerValue,
+        op: MarkerOperator,
+        right: MarkerValue,
+    },
+    And(Vec<DepMarker>),
+    Or(Vec<DepMarker>),
+}
+
+impl DepMarker {
+    /// Create a DepMarker from a `marker`, `marker_or`, `marker_and`, or `marker_expr` parse result.
+    pub(crate) fn from_pair(pair: Pair<Rule>) -> ResultDynError<Self> {
+        match pair.as_rule() {
+            Rule::marker => {
+                let inner = pair.into_inner().next().ok_or("Expected marker_or")?;
+                Self::from_pair(inner)
+            }
+            Rule::marker_or | Rule::marker_and => {
+                let is_or = pair.as_rule() == Rule::marker_or;
+                let mut markers = pair
+                    .into_inner()
+                    .map(Self::from_pair)
+                    .collect::<ResultDynError<Vec<_>>>()?;
+                if markers.len() == 1 {
+                    Ok(markers.remove(0))
+                } else if is_or {
+                    Ok(DepMarker::Or(markers))
+                } else {
+                    Ok(DepMarker::And(markers))
+                }
+            }
+            Rule::marker_expr => {
+                let mut inner = pair.into_inner();
+                let first = inner.next().ok_or("Expected marker_var")?;
+                if first.as_rule() == Rule::marker_or {
+                    // a parenthesized expression
+                    return Self::from_pair(first);
+                }
+                let left = MarkerValue::from_pair(first)?;
+                let op_pair = inner.next().ok_or("Expected marker_op")?;
+                let op = MarkerOperator::from_str(op_pair.as_str().trim())?;
+                let right =
+                    MarkerValue::from_pair(inner.next().ok_or("Expected marker_var")?)?;
+                Ok(DepMarker::Expr { left, op, right })
+            }
+            _ => Err(format!("Unexpected marker rule: {:?}", pair.as_rule()).into()),
+        }
+    }
+
+    pub(crate) fn from_string(input: &str) -> ResultDynError<Self> {
+        let mut parsed = DepSpecParser::parse(Rule::marker, input.trim())
+            .map_err(|e| format!("Parsing error: {}", e))?;
+        let pair = parsed.next().ok_or("Parsing error: No results")?;
+        if pair.as_str() != input.trim() {
+            return Err(format!("Unrecognized marker: {:?}", input).into());
+        }
+        Self::from_pair(pair)
+    }
+
+    //--------------------------------------------------------------------------
+
+    /// Return true if this marker references the `extra` variable.
+    pub(crate) fn has_extra(&self) -> bool {
+        match self {
+            DepMarker::Expr { left, right, .. } => {
+                left.is_extra_var() || right.is_extra_var()
+            }
+            DepMarker::And(markers) | DepMarker::Or(markers) => {
+                markers.iter().any(|m| m.has_extra())
+            }
+        }
+    }
+
+    /// Evaluate this marker against the provided environment.
+    pub(crate) fn evaluate(&self, env: &MarkerEnv) -> bool {
+        match self {
+            DepMarker::And(markers) => markers.iter().all(|m| m.evaluate(env)),
+            DepMarker::Or(markers) => markers.iter().any(|m| m.evaluate(env)),
+            DepMarker::Expr { left, op, right } => {
+                let (lv, rv) = if left.is_extra_var() || right.is_extra_var() {
+                    // extra names are compared in normalized form
+                    (
+                        Cow::Owned(extra_normalize(left.resolve(env))),
+                        Cow::Owned(extra_normalize(right.resolve(env))),
+                    )
+                } else {
+                    (
+                        Cow::Borrowed(left.resolve(env)),
+                        Cow::Borrowed(right.resolve(env)),
+                    )
+                };
+                let (lv, rv) = (lv.as_ref(), rv.as_ref());
+                match op {
+                    MarkerOperator::In => rv.contains(lv),
+                    MarkerOperator::NotIn => !rv.contains(lv),
+                    MarkerOperator::Version(op)
+                        if left.is_version_var() || right.is_version_var() =>
+                    {
+                        let lv = VersionSpec::new(lv);
+                        let rv = VersionSpec::new(rv);
+                        match op {
+                            DepOperator::LessThan => lv < rv,
+                            DepOperator::LessThanOrEq => lv <= rv,
+                            DepOperator::Eq => lv == rv,
+                            DepOperator::NotEq => lv != rv,
+                            DepOperator::GreaterThan => lv > rv,
+                            DepOperator::GreaterThanOrEq => lv >= rv,
+                            DepOperator::Compatible => lv.is_compatible(&rv),
+                            DepOperator::ArbitraryEq => lv.is_arbitrary_equal(&rv),
+                        }
+                    }
+                    MarkerOperator::Version(op) => match op {
+                        DepOperator::LessThan => lv < rv,
+                        DepOperator::LessThanOrEq => lv <= rv,
+                        DepOperator::Eq | DepOperator::ArbitraryEq => lv == rv,
+                        DepOperator::NotEq => lv != rv,
+                        DepOperator::GreaterThan => lv > rv,
+                        DepOperator::GreaterThanOrEq => lv >= rv,
+                        DepOperator::Compatible => false,
+                    },
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for DepMarker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DepMarker::Expr { left, op, right } => write!(f, "{} {} {}", left, op, right),
+            DepMarker::And(markers) => {
+                let parts: Vec<String> = markers
+                    .iter()
+                    .map(|m| match m {
+                        DepMarker::Or(_) => format!("({})", m),
+                        _ => m.to_string(),
+                    })
+                    .collect();
+                write!(f, "{}", parts.join(" and "))
+            }
+            DepMarker::Or(markers) => {
+                let parts: Vec<String> = markers.iter().map(|m| m.to_string()).collect();
+                write!(f, "{}", parts.join(" or "))
+            }
+        }
+    }
+}
+
+impl Serialize for DepMarker {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for DepMarker {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let marker_str = String::deserialize(deserializer)?;
+        DepMarker::from_string(&marker_str).map_err(serde::de::Error::custom)
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    fn env_linux_312() -> MarkerEnv {
+        MarkerEnv::from_pairs(&[
+            ("os_name", "posix"),
+            ("sys_platform", "linux"),
+            ("platform_system", "Linux"),
+            ("platform_machine", "x86_64"),
+            ("python_version", "3.12"),
+            ("python_full_version", "3.12.4"),
+            ("implementation_name", "cpython"),
+        ])
+    }
+
+    #[test]
+    fn test_marker_from_string_a() {
+        let m1 = DepMarker::from_string("python_version < '3.11'").unwrap();
+        assert_eq!(
+            m1,
+            DepMarker::Expr {
+                left: MarkerValue::Variable("python_version".to_string()),
+                op: MarkerOperator::Version(DepOperator::LessThan),
+                right: MarkerValue::Literal("3.11".to_string()),
+            }
+        );
+        assert_eq!(m1.to_string(), "python_version < \"3.11\"");
+    }
+
+    #[test]
+    fn test_marker_from_string_b() {
+        let m1 = DepMarker::from_string(
+            "sys_platform == 'linux' and (python_version<'3.9' or os_name=='nt')",
+        )
+        .unwrap();
+        assert_eq!(
+            m1.to_string(),
+            "sys_platform == \"linux\" and (python_version < \"3.9\" or os_name == \"nt\")"
+        );
+    }
+
+    #[test]
+    fn test_marker_from_string_c() {
+        assert!(DepMarker::from_string("python_version <").is_err());
+        assert!(DepMarker::from_string("foo == 'bar'").is_err());
+    }
+
+    #[test]
+    fn test_marker_evaluate_a() {
+        let env = env_linux_312();
+        let m1 = DepMarker::from_string("python_version < \"3.11\"").unwrap();
+        assert_eq!(m1.evaluate(&env), false);
+        let m2 = DepMarker::from_string("python_version >= \"3.9\"").unwrap();
+        assert_eq!(m2.evaluate(&env), true);
+        let m3 = DepMarker::from_string("'3.13' > python_full_version").unwrap();
+        assert_eq!(m3.evaluate(&env), true);
+    }
+
+    #[test]
+    fn test_marker_evaluate_b() {
+        let env = env_linux_312();
+        let m1 = DepMarker::from_string("sys_platform == 'linux'").unwrap();
+        assert_eq!(m1.evaluate(&env), true);
+        let m2 = DepMarker::from_string("sys_platform == 'darwin'").unwrap();
+        assert_eq!(m2.evaluate(&env), false);
+        let m3 = DepMarker::from_string("os_name != 'nt'").unwrap();
+        assert_eq!(m3.evaluate(&env), true);
+    }
+
+    #[test]
+    fn test_marker_evaluate_c() {
+        let env = env_linux_312();
+        let m1 = DepMarker::from_string("platform_machine in 'x86_64 aarch64'").unwrap();
+        assert_eq!(m1.evaluate(&env), true);
+        let m2 = DepMarker::from_string("platform_machine not in 'arm64'").unwrap();
+        assert_eq!(m2.evaluate(&env), true);
+        let m3 =
+            DepMarker::from_string("sys_platform == 'win32' or python_version > '3.10'")
+                .unwrap();
+        assert_eq!(m3.evaluate(&env), true);
+        let m4 = DepMarker::from_string(
+            "sys_platform == 'linux' and (python_version < '3.9' or os_name == 'nt')",
+        )
+        .unwrap();
+        assert_eq!(m4.evaluate(&env), false);
+    }
+
+    #[test]
+    fn test_marker_evaluate_d() {
+        let env = env_linux_312();
+        let m1 = DepMarker::from_string("extra == 'test'").unwrap();
+        assert_eq!(m1.evaluate(&env), false);
+    }
+
+    #[test]
+    fn test_marker_evaluate_e() {
+        let env = env_linux_312().with_extra("Socks");
+        let m1 = DepMarker::from_string("extra == 'socks'").unwrap();
+        assert_eq!(m1.has_extra(), true);
+        assert_eq!(m1.evaluate(&env), true);
+        let m2 = DepMarker::from_string("python_version < '3.8' and extra == 'socks'")
+            .unwrap();
+        assert_eq!(m2.has_extra(), true);
+        assert_eq!(m2.evaluate(&env), false);
+        let m3 = DepMarker::from_string("python_version < '3.8'").unwrap();
+        assert_eq!(m3.has_extra(), false);
+    }
+
+    #[test]
+    fn test_marker_env_from_json_str_a() {
+        let env = MarkerEnv::from_json_str(
+            r#"{"python_version": "3.11", "sys_platform": "darwin"}"#,
+        )
+        .unwrap();
+        assert_eq!(env.get("python_version"), "3.11");
+        assert_eq!(env.get("os_name"), "");
+    }
+
+    #[test]
+    fn test_marker_json_a() {
+        let m1 = DepMarker::from_string("os_name != 'nt'").unwrap();
+        let json = serde_json::to_string(&m1).unwrap();
+        assert_eq!(json, r#""os_name != \"nt\"""#);
+        let m2: DepMarker = serde_json::from_str(&json).unwrap();
+        assert_eq!(m1, m2);
+    }
+}
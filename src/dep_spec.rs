@@ -1,5 +1,6 @@
 use pest::Parser;
 use pest_derive::Parser;
+use std::cmp::Ordering;
 use std::collections::HashSet;
 use std::error::Error;
 use std::fmt;
@@ -8,7 +9,12 @@ use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
+use crate::dep_marker::DepMarker;
+use crate::dep_marker::MarkerEnv;
 use crate::package::Package;
+use crate::package_durl::url_to_file_path;
+use crate::package_durl::url_to_hashes;
+use crate::util::extra_normalize;
 use crate::util::name_to_key;
 use crate::util::url_strip_user;
 use crate::util::url_trim;
@@ -18,7 +24,7 @@ use crate::version_spec::VersionSpec;
 // This is a grammar for https://packaging.python.org/en/latest/specifications/dependency-specifiers/
 #[derive(Parser)]
 #[grammar = "dep_spec.pest"]
-struct DepSpecParser;
+pub(crate) struct DepSpecParser;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub(crate) enum DepOperator {
@@ -65,26 +71,78 @@ impl fmt::Display for DepOperator {
     }
 }
 
-// Dependency Specfication: A model of a specification of one or more versions, such as "numpy>1.18,<2.0".
+// Return true if `version` satisfies `spec_version` with the operator `op`.
+fn is_operator_match(
+    op: &DepOperator,
+    version: &VersionSpec,
+    spec_version: &VersionSpec,
+) -> bool {
+    match op {
+        DepOperator::LessThan => version.is_less(spec_version),
+        DepOperator::LessThanOrEq => version.is_less_equal(spec_version),
+        DepOperator::Eq => version.is_equal(spec_version),
+        DepOperator::NotEq => !version.is_equal(spec_version),
+        DepOperator::GreaterThan => version.is_greater(spec_version),
+        DepOperator::GreaterThanOrEq => version.is_greater_equal(spec_version),
+        DepOperator::Compatible => version.is_compatible(spec_version),
+        DepOperator::ArbitraryEq => version.is_arbitrary_equal(spec_version),
+    }
+}
+
+// Validate that a version is permitted with an operator: a compatible release (`~=`) requires at least two release segments, and a wildcard is only permitted as the last release segment (e.g. `1.4.*`) with `==` or `!=`.
+fn validate_operator_version(op: &DepOperator, version: &str) -> ResultDynError<()> {
+    if *op == DepOperator::Compatible && !version.contains('.') {
+        return Err(format!(
+            "Invalid version for ~=: {} (a compatible release requires at least two release segments)",
+            version
+        )
+        .into());
+    }
+    if version.contains('*') {
+        let is_prefix = version
+            .strip_suffix(".*")
+            .is_some_and(|prefix| !prefix.is_empty() && !prefix.contains(['*', '+']));
+        if !is_prefix || !matches!(op, DepOperator::Eq | DepOperator::NotEq) {
+            return Err(format!(
+                "Invalid version for {}: {} (a wildcard is only permitted as the last release segment with == or !=)",
+                op, version
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// Dependency Specfication: A model of a specification of one or more versions, such as "numpy>1.18,<2.0".
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub(crate) struct DepSpec {
+pub struct DepSpec {
     pub(crate) name: String,
     pub(crate) key: String,
     pub(crate) url: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub(crate) extras: Vec<String>,
     operators: Vec<DepOperator>,
     versions: Vec<VersionSpec>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) marker: Option<DepMarker>,
+    /// Hashes of permitted artifacts, formatted as "<algorithm>:<digest>", as provided by lock files.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub(crate) hashes: Vec<String>,
 }
 
 impl DepSpec {
     /// Given a URL to a whl file, parse the name and version and return a DepSpec
     fn from_whl(input: &str) -> ResultDynError<Self> {
         let input = input.trim();
-        if input.starts_with("http://")
+        // a fragment, such as `#sha256=<digest>`, is not part of the file name
+        let path = input.split('#').next().unwrap_or(input);
+        if (input.starts_with("http://")
             || input.starts_with("https://")
-            || input.starts_with("file://") && input.ends_with(".whl")
+            || input.starts_with("file://"))
+            && path.ends_with(".whl")
         {
             // extract the last path component
-            let name = Path::new(input)
+            let name = Path::new(path)
                 .file_stem()
                 .ok_or_else(|| "Invalid .whl".to_string())?
                 .to_str()
@@ -99,16 +157,43 @@ impl DepSpec {
                     key: name_to_key(&package_name),
                     name: package_name,
                     url: Some(input.to_string()),
+                    extras: Vec::new(),
                     operators,
                     versions,
+                    marker: None,
+                    hashes: Vec::new(),
                 });
             }
         }
         Err("Invalid .whl".into())
     }
 
+    /// Given a line from a requirements file, create a DepSpec. Any `--hash` options, as provided by hash-pinned requirements, are read as permitted artifact hashes.
+    pub(crate) fn from_requirement(line: &str) -> ResultDynError<Self> {
+        let (spec, options) = match line.find("--hash") {
+            Some(pos) => (line[..pos].trim(), &line[pos..]),
+            None => return Self::from_string(line.trim()),
+        };
+        let mut hashes = Vec::new();
+        let mut tokens = options.split_whitespace();
+        while let Some(token) = tokens.next() {
+            let hash = match token.strip_prefix("--hash") {
+                Some("") => tokens.next(),
+                Some(h) => h.strip_prefix('='),
+                None => None,
+            };
+            match hash {
+                Some(h) if h.contains(':') => hashes.push(h.to_string()),
+                _ => return Err(format!("Invalid hash option: {:?}", token).into()),
+            }
+        }
+        let mut ds = Self::from_string(spec)?;
+        ds.hashes = hashes;
+        Ok(ds)
+    }
+
     /// Given a string as found in a requirements.txt or similar, create a DepSpec.
-    pub(crate) fn from_string(input: &str) -> ResultDynError<Self> {
+    pub fn from_string(input: &str) -> ResultDynError<Self> {
         if let Ok(ds) = DepSpec::from_whl(input) {
             return Ok(ds);
         }
@@ -130,6 +215,8 @@ impl DepSpec {
         let mut url = None;
         let mut operators = Vec::new();
         let mut versions = Vec::new();
+        let mut marker = None;
+        let mut extras = Vec::new();
 
         let inner_pairs: Vec<_> = parse_result.into_inner().collect();
         for pair in inner_pairs {
@@ -141,6 +228,18 @@ impl DepSpec {
                 Rule::url_reference => {
                     url = Some(url_trim(pair.as_str().to_string()));
                 }
+                Rule::extras => {
+                    for extras_list in pair.into_inner() {
+                        for extra in extras_list.into_inner() {
+                            extras.push(extra_normalize(extra.as_str()));
+                        }
+                    }
+                }
+                Rule::quoted_marker => {
+                    let marker_pair =
+                        pair.into_inner().next().ok_or("Expected marker")?;
+                    marker = Some(DepMarker::from_pair(marker_pair)?);
+                }
                 Rule::version_many => {
                     for version_pair in pair.into_inner() {
                         let mut inner_pairs = version_pair.into_inner();
@@ -161,6 +260,7 @@ impl DepSpec {
                             return Err("Expected version".into());
                         }
                         let version = version_pair.as_str().trim().to_string();
+                        validate_operator_version(&op, &version)?;
 
                         operators.push(op);
                         versions.push(VersionSpec::new(&version));
@@ -173,7 +273,7 @@ impl DepSpec {
         let key = name_to_key(&package_name);
         // if url is defined and it is wheel, take definition from the wheel
         if let Some(ref url) = url {
-            if let Ok(ds) = DepSpec::from_whl(url) {
+            if let Ok(mut ds) = DepSpec::from_whl(url) {
                 if ds.key != key {
                     return Err(format!(
                         "Provided name {} does not match whl name {}",
@@ -181,6 +281,8 @@ impl DepSpec {
                     )
                     .into());
                 }
+                ds.extras = extras;
+                ds.marker = marker;
                 return Ok(ds);
             }
         }
@@ -188,8 +290,11 @@ impl DepSpec {
             name: package_name,
             key,
             url,
+            extras,
             operators,
             versions,
+            marker,
+            hashes: Vec::new(),
         })
     }
     /// Create a DepSpec from a Package struct.
@@ -205,23 +310,63 @@ impl DepSpec {
             name: package.name.clone(),
             key: package.key.clone(),
             url: None,
+            extras: Vec::new(),
             operators,
             versions,
+            marker: None,
+            hashes: Vec::new(),
         })
     }
 
+    /// Create a DepSpec that requires a package from a direct URL, such as a pip-style VCS URL pinned to a commit.
+    pub(crate) fn from_package_url(package: &Package, url: String) -> Self {
+        DepSpec {
+            name: package.name.clone(),
+            key: package.key.clone(),
+            url: Some(url),
+            extras: Vec::new(),
+            operators: Vec::new(),
+            versions: Vec::new(),
+            marker: None,
+            hashes: Vec::new(),
+        }
+    }
+
     /// If, in evaluating multiple sources of DepSpec, we find the same package, this derives a new DepSpec based on (an intersection) of the others.
     pub(crate) fn from_dep_specs(dep_specs: Vec<&DepSpec>) -> ResultDynError<Self> {
         let mut names = HashSet::new();
         let mut keys = HashSet::new();
         let mut operators = Vec::new();
         let mut versions = Vec::new();
+        let mut markers = Vec::new();
+        let mut extras = Vec::new();
+        let mut hashes = Vec::new();
         for ds in &dep_specs {
             names.insert(&ds.name);
             keys.insert(&ds.key);
+            extras.extend(ds.extras.iter().cloned());
+            hashes.extend(ds.hashes.iter().cloned());
             operators.extend(ds.operators.iter().cloned());
             versions.extend(ds.versions.iter().cloned());
+            markers.push(ds.marker.clone());
         }
+        // the combined DepSpec applies wherever any component applies; if any component has no marker, it always applies
+        let marker =
+            markers
+                .into_iter()
+                .collect::<Option<Vec<DepMarker>>>()
+                .map(|mut markers| {
+                    markers.dedup();
+                    if markers.len() == 1 {
+                        markers.remove(0)
+                    } else {
+                        DepMarker::Or(markers)
+                    }
+                });
+        extras.sort();
+        extras.dedup();
+        hashes.sort();
+        hashes.dedup();
         if keys.len() == 1 {
             let name = names.iter().next().unwrap();
             let key = keys.iter().next().unwrap();
@@ -229,35 +374,152 @@ impl DepSpec {
                 name: name.to_string(),
                 key: key.to_string(),
                 url: None,
+                extras,
                 operators,
                 versions,
+                marker,
+                hashes,
             });
         }
         Err(format!("Unreconcilable dependency specifiers: {:?}", dep_specs).into())
     }
 
+    /// Return the intersection of this DepSpec and `other`, of the same package, such that a version must satisfy both; if no version can, the contradiction is returned as an error.
+    pub(crate) fn intersect(&self, other: &DepSpec) -> ResultDynError<Self> {
+        let ds = Self::from_dep_specs(vec![self, other])?;
+        match ds.to_contradiction() {
+            Some(contradiction) => {
+                Err(format!("Unsatisfiable requirement {}: {}", ds, contradiction).into())
+            }
+            None => Ok(ds),
+        }
+    }
+
     //--------------------------------------------------------------------------
-    pub(crate) fn validate_version(&self, version: &VersionSpec) -> bool {
+    pub fn validate_version(&self, version: &VersionSpec) -> bool {
         // operators and versions are always the same length
         // println!("validate_version: self {:?} input {:?}", self.versions, version);
         for (op, spec_version) in self.operators.iter().zip(&self.versions) {
-            let valid = match op {
-                DepOperator::LessThan => version < spec_version,
-                DepOperator::LessThanOrEq => version <= spec_version,
-                DepOperator::Eq => version == spec_version,
-                DepOperator::NotEq => version != spec_version,
-                DepOperator::GreaterThan => version > spec_version,
-                DepOperator::GreaterThanOrEq => version >= spec_version,
-                DepOperator::Compatible => version.is_compatible(spec_version),
-                DepOperator::ArbitraryEq => version.is_arbitrary_equal(spec_version),
-            };
-            if !valid {
+            if !is_operator_match(op, version, spec_version) {
                 return false;
             }
         }
         true
     }
 
+    /// Return a description of a contradiction between the version specifiers of this DepSpec, such that no version can satisfy it (e.g. `>=2.0,<1.5`), or None if a version might. Exclusions (`!=`) only contradict exact pins.
+    pub(crate) fn to_contradiction(&self) -> Option<String> {
+        let specs: Vec<(&DepOperator, &VersionSpec)> =
+            self.operators.iter().zip(&self.versions).collect();
+        // an exact pin is only satisfiable by the pinned version
+        let mut pinned = false;
+        for (op, version) in &specs {
+            let is_pin = match op {
+                DepOperator::Eq => version.to_wildcard_bounds().is_none(),
+                DepOperator::ArbitraryEq => true,
+                _ => false,
+            };
+            if !is_pin {
+                continue;
+            }
+            if let Some((op_other, version_other)) = specs
+                .iter()
+                .find(|(o, v)| !is_operator_match(o, version, v))
+            {
+                return Some(format!(
+                    "{}{} contradicts {}{}",
+                    op, version, op_other, version_other
+                ));
+            }
+            pinned = true;
+        }
+        if pinned {
+            return None;
+        }
+        // collect lower and upper bounds, each with if it is inclusive and the specifier that defines it
+        let mut lowers: Vec<(VersionSpec, bool, String)> = Vec::new();
+        let mut uppers: Vec<(VersionSpec, bool, String)> = Vec::new();
+        for (op, version) in &specs {
+            let label = format!("{}{}", op, version);
+            match op {
+                DepOperator::GreaterThanOrEq => {
+                    lowers.push(((*version).clone(), true, label))
+                }
+                DepOperator::GreaterThan => {
+                    lowers.push(((*version).clone(), false, label))
+                }
+                DepOperator::LessThanOrEq => {
+                    uppers.push(((*version).clone(), true, label))
+                }
+                DepOperator::LessThan => uppers.push(((*version).clone(), false, label)),
+                DepOperator::Compatible => {
+                    if let Some(upper) = version.to_compatible_upper() {
+                        uppers.push((upper, false, label.clone()));
+                    }
+                    lowers.push(((*version).clone(), true, label));
+                }
+                DepOperator::Eq => {
+                    if let Some((lower, upper)) = version.to_wildcard_bounds() {
+                        lowers.push((lower, true, label.clone()));
+                        uppers.push((upper, false, label));
+                    }
+                }
+                DepOperator::NotEq | DepOperator::ArbitraryEq => {}
+            }
+        }
+        for (lower, lower_inclusive, lower_label) in &lowers {
+            for (upper, upper_inclusive, upper_label) in &uppers {
+                let is_empty = match lower.cmp(upper) {
+                    Ordering::Greater => true,
+                    Ordering::Equal => !(*lower_inclusive && *upper_inclusive),
+                    Ordering::Less => false,
+                };
+                if is_empty {
+                    return Some(format!("{} contradicts {}", lower_label, upper_label));
+                }
+            }
+        }
+        None
+    }
+
+    // Return the hashes defined with `--hash` options and in the fragment of an archive URL.
+    fn get_hashes(&self) -> Vec<String> {
+        let mut hashes = self.hashes.clone();
+        if let Some(url) = &self.url {
+            hashes.extend(url_to_hashes(url));
+        }
+        hashes
+    }
+
+    /// If this DepSpec defines hashes, either with `--hash` options or in the fragment of an archive URL (e.g. `#sha256=<digest>`), validate that an archive hash recorded in the Package's DirectURL is one of them. As the hash of the installed artifact is only recorded for direct URL installs, a Package without a recorded hash is not invalidated here; its installed files are verified against its RECORD by the caller.
+    pub(crate) fn validate_hash(&self, package: &Package) -> bool {
+        let hashes = self.get_hashes();
+        if hashes.is_empty() {
+            return true;
+        }
+        match package
+            .direct_url
+            .as_ref()
+            .map(|durl| durl.get_archive_hashes())
+        {
+            Some(archive_hashes) if !archive_hashes.is_empty() => {
+                archive_hashes.iter().any(|h| hashes.contains(h))
+            }
+            _ => true,
+        }
+    }
+
+    /// Return true if this DepSpec defines hashes but the Package has no recorded archive hash to compare them to.
+    pub(crate) fn is_hash_unverifiable(&self, package: &Package) -> bool {
+        if self.get_hashes().is_empty() {
+            return false;
+        }
+        match &package.direct_url {
+            Some(durl) => durl.get_archive_hashes().is_empty(),
+            None => true,
+        }
+    }
+
     pub(crate) fn validate_url(&self, package: &Package) -> bool {
         // if the DepSpec has a URL (the requirements specfied a URL) we have to validate that the installed package has a direct url.
         if let Some(url) = &self.url {
@@ -271,34 +533,93 @@ impl DepSpec {
         true
     }
 
-    #[allow(dead_code)]
-    pub(crate) fn validate_package(&self, package: &Package) -> bool {
+    /// If this DepSpec requires a local directory (e.g. `mypkg @ file:///opt/src/mypkg`) that the Package was not installed from, return a description of how the Package was installed, including if it was installed in editable mode.
+    pub(crate) fn to_local_mismatch(&self, package: &Package) -> Option<String> {
+        let url = self.url.as_ref()?;
+        url_to_file_path(url)?;
+        if self.validate_url(package) {
+            return None;
+        }
+        Some(match &package.direct_url {
+            Some(durl) => durl.to_source_description(),
+            None => "not installed from a URL".to_string(),
+        })
+    }
+
+    /// If the version of the Package has a local label (e.g. `2.1.0+cu118`) and this DepSpec is only not satisfied because of it, return a description of the label.
+    pub(crate) fn to_local_label_mismatch(&self, package: &Package) -> Option<String> {
+        let label = package.version.get_local()?;
+        if !self.validate_version(&package.version)
+            && self.validate_version(&package.version.to_public())
+        {
+            Some(format!("installed version has local label +{}", label))
+        } else {
+            None
+        }
+    }
+
+    /// If the version of the Package is a pre-release (e.g. `2.0rc1`) and this DepSpec does not specify a pre-release version, return a description of the pre-release; as with pip's default resolution, such a DepSpec does not select pre-releases. DepSpecs with a URL select whatever version is built from it.
+    pub(crate) fn to_prerelease_mismatch(&self, package: &Package) -> Option<String> {
+        if !package.version.is_prerelease()
+            || self.url.is_some()
+            || self.versions.iter().any(|v| v.is_prerelease())
+        {
+            return None;
+        }
+        Some(format!(
+            "installed version {} is a pre-release",
+            package.version
+        ))
+    }
+
+    /// Return true if this DepSpec has no marker or if its marker evaluates true for any of the provided environments. If no environments are provided, the DepSpec is assumed to apply.
+    pub(crate) fn is_applicable(&self, envs: &[MarkerEnv]) -> bool {
+        match &self.marker {
+            Some(marker) if !envs.is_empty() => {
+                envs.iter().any(|env| marker.evaluate(env))
+            }
+            _ => true,
+        }
+    }
+
+    pub fn validate_package(&self, package: &Package) -> bool {
         self.key == package.key
             && self.validate_version(&package.version)
             && self.validate_url(package)
+            && self.validate_hash(package)
     }
 }
 
 impl fmt::Display for DepSpec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut parts = Vec::new();
+        let name = if self.extras.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}[{}]", self.name, self.extras.join(","))
+        };
         // if we have versions, we do not need URL
         if !self.versions.is_empty() {
             for (op, ver) in self.operators.iter().zip(self.versions.iter()) {
                 parts.push(format!("{}{}", op, ver));
             }
-            write!(f, "{}{}", self.name, parts.join(","))
+            write!(f, "{}{}", name, parts.join(","))?;
         } else if let Some(url) = &self.url {
-            write!(f, "{} @ {}", self.name, url_strip_user(url))
+            write!(f, "{} @ {}", name, url_strip_user(url))?;
         } else {
-            write!(f, "{}", self.name)
+            write!(f, "{}", name)?;
+        }
+        if let Some(marker) = &self.marker {
+            write!(f, "; {}", marker)?;
         }
+        Ok(())
     }
 }
 
 //------------------------------------------------------------------------------
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use crate::package_durl::DirectURL;
 
@@ -474,8 +795,7 @@ mod tests {
         let ds1 = DepSpec::from_string(input).unwrap();
         assert_eq!(ds1.validate_version(&VersionSpec::new("1.1")), false);
         assert_eq!(ds1.validate_version(&VersionSpec::new("1.1a1")), true);
-        // this is supposed to match...
-        // assert_eq!(ds1.validate_version(&VersionSpec::new("1.1.*")), true);
+        assert_eq!(ds1.validate_version(&VersionSpec::new("1.1.*")), true);
     }
     #[test]
     fn test_dep_spec_validate_version_i() {
@@ -489,6 +809,113 @@ mod tests {
         assert_eq!(ds1.validate_version(&VersionSpec::new("1.1.post1")), false);
         assert_eq!(ds1.validate_version(&VersionSpec::new("1.1.*")), true);
     }
+    #[test]
+    fn test_dep_spec_validate_version_i2() {
+        let ds1 = DepSpec::from_string("name>=2.0.0rc1,<3").unwrap();
+        assert_eq!(ds1.validate_version(&VersionSpec::new("2.0.0b2")), false);
+        assert_eq!(ds1.validate_version(&VersionSpec::new("2.0rc1")), true);
+        assert_eq!(ds1.validate_version(&VersionSpec::new("2.0.post1")), true);
+        assert_eq!(ds1.validate_version(&VersionSpec::new("3.0.dev1")), false);
+        assert_eq!(ds1.validate_version(&VersionSpec::new("1!2.0")), false);
+
+        let ds2 = DepSpec::from_string("name==2.0").unwrap();
+        assert_eq!(ds2.validate_version(&VersionSpec::new("2.0+cu121")), false);
+        assert_eq!(ds2.validate_version(&VersionSpec::new("2.0.dev3")), false);
+
+        let ds3 = DepSpec::from_string("name>2.0").unwrap();
+        assert_eq!(ds3.validate_version(&VersionSpec::new("2.0.post1")), false);
+        assert_eq!(ds3.validate_version(&VersionSpec::new("2.0.1")), true);
+    }
+    #[test]
+    fn test_dep_spec_validate_version_i3() {
+        let ds1 = DepSpec::from_string("name~=1.4.2").unwrap();
+        assert_eq!(ds1.to_string(), "name~=1.4.2");
+        assert_eq!(ds1.validate_version(&VersionSpec::new("1.4.2")), true);
+        assert_eq!(ds1.validate_version(&VersionSpec::new("1.4.10")), true);
+        assert_eq!(ds1.validate_version(&VersionSpec::new("1.4.1")), false);
+        assert_eq!(ds1.validate_version(&VersionSpec::new("1.5.0")), false);
+
+        let ds2 = DepSpec::from_string("name~=2.2").unwrap();
+        assert_eq!(ds2.validate_version(&VersionSpec::new("2.9")), true);
+        assert_eq!(ds2.validate_version(&VersionSpec::new("3.0")), false);
+
+        assert!(DepSpec::from_string("name~=1").is_err());
+    }
+    #[test]
+    fn test_dep_spec_validate_version_i4() {
+        let ds1 = DepSpec::from_string("name===1.0+local").unwrap();
+        assert_eq!(ds1.to_string(), "name===1.0+local");
+        assert_eq!(ds1.validate_version(&VersionSpec::new("1.0+local")), true);
+        assert_eq!(ds1.validate_version(&VersionSpec::new("1.0")), false);
+        assert_eq!(
+            ds1.validate_version(&VersionSpec::new("1.0.0+local")),
+            false
+        );
+    }
+    #[test]
+    fn test_dep_spec_validate_version_i5() {
+        let ds1 = DepSpec::from_string("name==1.4.*").unwrap();
+        assert_eq!(ds1.to_string(), "name==1.4.*");
+        for version in [
+            "1.4",
+            "1.4.0",
+            "1.4.5",
+            "1.4.5.1",
+            "1.4rc1",
+            "1.4.post1",
+            "1.4.2+local",
+        ] {
+            assert_eq!(ds1.validate_version(&VersionSpec::new(version)), true);
+        }
+        for version in ["1.3.9", "1.5", "1", "1!1.4.0"] {
+            assert_eq!(ds1.validate_version(&VersionSpec::new(version)), false);
+        }
+        let ds2 = DepSpec::from_string("name!=1.4.*").unwrap();
+        assert_eq!(ds2.validate_version(&VersionSpec::new("1.4.5")), false);
+        assert_eq!(ds2.validate_version(&VersionSpec::new("1.5")), true);
+
+        assert!(DepSpec::from_string("name>=1.4.*").is_err());
+        assert!(DepSpec::from_string("name~=1.4.*").is_err());
+        assert!(DepSpec::from_string("name==1.*.4").is_err());
+        assert!(DepSpec::from_string("name==1.4*").is_err());
+        assert!(DepSpec::from_string("name==*").is_err());
+    }
+    #[test]
+    fn test_dep_spec_local_label_a() {
+        let ds = DepSpec::from_string("torch==2.1.0").unwrap();
+        let p1 = Package::from_name_version_durl("torch", "2.1.0+cu118", None).unwrap();
+        assert!(!ds.validate_package(&p1));
+        assert!(ds.validate_package(&p1.to_public()));
+        assert_eq!(
+            ds.to_local_label_mismatch(&p1).unwrap(),
+            "installed version has local label +cu118"
+        );
+        let p2 = Package::from_name_version_durl("torch", "2.2.0+cu118", None).unwrap();
+        assert_eq!(ds.to_local_label_mismatch(&p2), None);
+        let ds = DepSpec::from_string("torch==2.1.0+cu118").unwrap();
+        assert!(ds.validate_package(&p1));
+    }
+    #[test]
+    fn test_dep_spec_prerelease_a() {
+        let ds = DepSpec::from_string("numpy>=1.0").unwrap();
+        let p1 = Package::from_name_version_durl("numpy", "2.0rc1", None).unwrap();
+        assert!(ds.validate_package(&p1));
+        assert_eq!(
+            ds.to_prerelease_mismatch(&p1).unwrap(),
+            "installed version 2.0rc1 is a pre-release"
+        );
+        let p2 = Package::from_name_version_durl("numpy", "2.0", None).unwrap();
+        assert_eq!(ds.to_prerelease_mismatch(&p2), None);
+        let p3 = Package::from_name_version_durl("numpy", "2.1.dev3", None).unwrap();
+        assert!(ds.to_prerelease_mismatch(&p3).is_some());
+
+        let ds = DepSpec::from_string("numpy>=2.0b1").unwrap();
+        assert_eq!(ds.to_prerelease_mismatch(&p1), None);
+        let ds =
+            DepSpec::from_string("numpy @ git+https://github.com/numpy/numpy").unwrap();
+        assert_eq!(ds.to_prerelease_mismatch(&p3), None);
+    }
+
     #[test]
     fn test_dep_spec_validate_version_j1() {
         let input = "name===12";
@@ -559,7 +986,7 @@ mod tests {
     fn test_dep_spec_to_string_b() {
         let ds1 = DepSpec::from_string("requests [security,tests] >= 2.8.1, == 2.8.* ")
             .unwrap();
-        assert_eq!(ds1.to_string(), "requests>=2.8.1,==2.8.*");
+        assert_eq!(ds1.to_string(), "requests[security,tests]>=2.8.1,==2.8.*");
     }
     //--------------------------------------------------------------------------
     #[test]
@@ -580,6 +1007,46 @@ mod tests {
         let ds = DepSpec::from_package(&p, DepOperator::LessThanOrEq).unwrap();
         assert_eq!(ds.to_string(), "foo<=1.2.3.4");
     }
+    #[test]
+    fn test_dep_spec_from_requirement_a() {
+        let ds = DepSpec::from_requirement(
+            "certifi==2024.8.30 --hash=sha256:bec941d2 --hash sha256:922820b5",
+        )
+        .unwrap();
+        assert_eq!(ds.to_string(), "certifi==2024.8.30");
+        assert_eq!(ds.hashes, vec!["sha256:bec941d2", "sha256:922820b5"]);
+
+        let ds = DepSpec::from_requirement("  certifi==2024.8.30 ").unwrap();
+        assert!(ds.hashes.is_empty());
+
+        assert!(DepSpec::from_requirement("certifi --hash=bec941d2").is_err());
+        assert!(DepSpec::from_requirement("certifi --hash").is_err());
+    }
+
+    #[test]
+    fn test_dep_spec_validate_hash_a() {
+        let ds = DepSpec::from_requirement("six==1.16.0 --hash=sha256:8abb2f1d").unwrap();
+        let durl1: DirectURL = serde_json::from_str(
+            r#"{"url": "https://example.com/six-1.16.0-py2.py3-none-any.whl", "archive_info": {"hash": "sha256=8abb2f1d"}}"#,
+        )
+        .unwrap();
+        let p1 = Package::from_name_version_durl("six", "1.16.0", Some(durl1)).unwrap();
+        assert!(ds.validate_hash(&p1));
+
+        let durl2: DirectURL = serde_json::from_str(
+            r#"{"url": "https://example.com/six-1.16.0-py2.py3-none-any.whl", "archive_info": {"hashes": {"sha256": "1e61c374"}}}"#,
+        )
+        .unwrap();
+        let p2 = Package::from_name_version_durl("six", "1.16.0", Some(durl2)).unwrap();
+        assert!(!ds.validate_hash(&p2));
+        assert!(!ds.validate_package(&p2));
+
+        let p3 = Package::from_name_version_durl("six", "1.16.0", None).unwrap();
+        assert!(ds.validate_hash(&p3));
+        assert!(ds.is_hash_unverifiable(&p3));
+        assert!(!ds.is_hash_unverifiable(&p2));
+    }
+
     //--------------------------------------------------------------------------
     #[test]
     fn test_dep_spec_url_a() {
@@ -593,6 +1060,45 @@ mod tests {
         assert_eq!(ds.url.unwrap(), "git+https://git.repo/some_pkg.git@1.3.1")
     }
     #[test]
+    fn test_dep_spec_url_e() {
+        for url in [
+            "hg+https://hg.example.com/acme@stable",
+            "svn+svn://svn.example.com/acme/trunk@r1234",
+            "bzr+sftp://bzr.example.com/acme@revno:42",
+            "git+git://git.example.com/acme.git@v1.0#egg=acme",
+        ] {
+            let ds = DepSpec::from_string(&format!("acme @ {}", url)).unwrap();
+            assert_eq!(ds.url.unwrap(), url);
+        }
+        assert!(DepSpec::from_string("acme @ cvs+https://cvs.example.com/acme").is_err());
+    }
+    #[test]
+    fn test_dep_spec_url_f() {
+        let ds = DepSpec::from_string("mypkg @ file:///opt/src/mypkg").unwrap();
+        assert_eq!(ds.url.as_deref(), Some("file:///opt/src/mypkg"));
+
+        let durl = DirectURL::from_editable_path(Path::new("/opt/src/mypkg/"));
+        let p1 = Package::from_name_version_durl("mypkg", "1.0", Some(durl)).unwrap();
+        assert!(ds.validate_package(&p1));
+        assert_eq!(ds.to_local_mismatch(&p1), None);
+
+        let durl: DirectURL =
+            serde_json::from_str(r#"{"url": "file:///opt/build/mypkg", "dir_info": {}}"#)
+                .unwrap();
+        let p2 = Package::from_name_version_durl("mypkg", "1.0", Some(durl)).unwrap();
+        assert!(!ds.validate_package(&p2));
+        assert_eq!(
+            ds.to_local_mismatch(&p2).unwrap(),
+            "installed non-editable from /opt/build/mypkg"
+        );
+
+        let p3 = Package::from_name_version_durl("mypkg", "1.0", None).unwrap();
+        assert_eq!(
+            ds.to_local_mismatch(&p3).unwrap(),
+            "not installed from a URL"
+        );
+    }
+    #[test]
     fn test_dep_spec_url_b() {
         let ds = DepSpec::from_string("https://example.com/app-1.0.whl").unwrap();
         assert_eq!(ds.to_string(), "app==1.0");
@@ -722,4 +1228,125 @@ mod tests {
         let json = serde_json::to_string(&ds).unwrap();
         assert_eq!(json, "{\"name\":\"app\",\"key\":\"app\",\"url\":\"https://example.com/app-1.0.whl\",\"operators\":[\"Eq\"],\"versions\":[\"1.0\"]}")
     }
+    //--------------------------------------------------------------------------
+    #[test]
+    fn test_dep_spec_marker_a() {
+        let ds1 =
+            DepSpec::from_string("tomli>=1.1.0; python_version < \"3.11\"").unwrap();
+        assert_eq!(ds1.key, "tomli");
+        assert!(ds1.marker.is_some());
+        assert_eq!(ds1.to_string(), "tomli>=1.1.0; python_version < \"3.11\"");
+
+        let env1 = MarkerEnv::from_pairs(&[("python_version", "3.12")]);
+        let env2 = MarkerEnv::from_pairs(&[("python_version", "3.10")]);
+        assert_eq!(ds1.is_applicable(std::slice::from_ref(&env1)), false);
+        assert_eq!(ds1.is_applicable(std::slice::from_ref(&env2)), true);
+        assert_eq!(ds1.is_applicable(&[env1, env2]), true);
+        assert_eq!(ds1.is_applicable(&[]), true);
+    }
+    #[test]
+    fn test_dep_spec_marker_b() {
+        let ds1 = DepSpec::from_string(
+            "pywin32>=306 ; sys_platform == 'win32' and platform_python_implementation == 'CPython'",
+        )
+        .unwrap();
+        let env1 = MarkerEnv::from_pairs(&[
+            ("sys_platform", "linux"),
+            ("platform_python_implementation", "CPython"),
+        ]);
+        assert_eq!(ds1.is_applicable(&[env1]), false);
+        assert_eq!(
+            ds1.to_string(),
+            "pywin32>=306; sys_platform == \"win32\" and platform_python_implementation == \"CPython\""
+        );
+    }
+    #[test]
+    fn test_dep_spec_marker_c() {
+        let ds1 = DepSpec::from_string("numpy>=2; python_version >= '3.10'").unwrap();
+        let ds2 = DepSpec::from_string("numpy<3; python_version < '3.10'").unwrap();
+        let ds3 = DepSpec::from_dep_specs(vec![&ds1, &ds2]).unwrap();
+        assert_eq!(
+            ds3.to_string(),
+            "numpy>=2,<3; python_version >= \"3.10\" or python_version < \"3.10\""
+        );
+        let ds4 = DepSpec::from_string("numpy").unwrap();
+        let ds5 = DepSpec::from_dep_specs(vec![&ds1, &ds4]).unwrap();
+        assert!(ds5.marker.is_none());
+    }
+    #[test]
+    fn test_dep_spec_contradiction_a() {
+        let contradiction = |s: &str| DepSpec::from_string(s).unwrap().to_contradiction();
+        assert_eq!(
+            contradiction("numpy>=2.0,<1.5").unwrap(),
+            ">=2.0 contradicts <1.5"
+        );
+        assert_eq!(
+            contradiction("numpy>1.5,<=1.5").unwrap(),
+            ">1.5 contradicts <=1.5"
+        );
+        assert_eq!(
+            contradiction("numpy~=1.4.2,>=1.5").unwrap(),
+            ">=1.5 contradicts ~=1.4.2"
+        );
+        assert_eq!(
+            contradiction("numpy==1.*,>=2").unwrap(),
+            ">=2 contradicts ==1.*"
+        );
+        assert_eq!(
+            contradiction("numpy==1.26.4,<1.26").unwrap(),
+            "==1.26.4 contradicts <1.26"
+        );
+        assert_eq!(
+            contradiction("numpy==1.26.4,!=1.26.4").unwrap(),
+            "==1.26.4 contradicts !=1.26.4"
+        );
+        assert_eq!(contradiction("numpy>=1.5,<=1.5"), None);
+        assert_eq!(contradiction("numpy>=1.4,<2,!=1.5"), None);
+        assert_eq!(contradiction("numpy~=1.4.2,<1.4.3"), None);
+        assert_eq!(contradiction("numpy==1.26.4,>=1.26"), None);
+        assert_eq!(contradiction("numpy"), None);
+    }
+    #[test]
+    fn test_dep_spec_intersect_a() {
+        let ds1 = DepSpec::from_string("numpy>=1.26").unwrap();
+        let ds2 = DepSpec::from_string("numpy<2").unwrap();
+        assert_eq!(ds1.intersect(&ds2).unwrap().to_string(), "numpy>=1.26,<2");
+        let ds3 = DepSpec::from_string("numpy<1.5").unwrap();
+        assert_eq!(
+            ds1.intersect(&ds3).unwrap_err().to_string(),
+            "Unsatisfiable requirement numpy>=1.26,<1.5: >=1.26 contradicts <1.5"
+        );
+    }
+    #[test]
+    fn test_dep_spec_marker_json_a() {
+        let ds1 = DepSpec::from_string("tomli; python_version < '3.11'").unwrap();
+        let json = serde_json::to_string(&ds1).unwrap();
+        assert_eq!(
+            json,
+            r#"{"name":"tomli","key":"tomli","url":null,"operators":[],"versions":[],"marker":"python_version < \"3.11\""}"#
+        );
+        let ds2: DepSpec = serde_json::from_str(&json).unwrap();
+        assert_eq!(ds1, ds2);
+    }
+    //--------------------------------------------------------------------------
+    #[test]
+    fn test_dep_spec_extras_a() {
+        let ds1 = DepSpec::from_string("requests[socks]>=2.31").unwrap();
+        assert_eq!(ds1.key, "requests");
+        assert_eq!(ds1.extras, vec!["socks"]);
+        assert_eq!(ds1.to_string(), "requests[socks]>=2.31");
+        let json = serde_json::to_string(&ds1).unwrap();
+        assert_eq!(
+            json,
+            r#"{"name":"requests","key":"requests","url":null,"extras":["socks"],"operators":["GreaterThanOrEq"],"versions":["2.31"]}"#
+        );
+    }
+    #[test]
+    fn test_dep_spec_extras_b() {
+        let ds1 = DepSpec::from_string("Requests[Use_Chardet_On_Py3, socks]").unwrap();
+        assert_eq!(ds1.extras, vec!["use-chardet-on-py3", "socks"]);
+        let ds2 = DepSpec::from_string("requests[socks]>=2").unwrap();
+        let ds3 = DepSpec::from_dep_specs(vec![&ds1, &ds2]).unwrap();
+        assert_eq!(ds3.extras, vec!["socks", "use-chardet-on-py3"]);
+    }
 }
@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::package::Package;
+use crate::table::ColumnFormat;
+use crate::table::Rowable;
+use crate::table::RowableContext;
+use crate::table::Tableable;
+use crate::version_spec::VersionSpec;
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DiffExplain {
+    Added,
+    Removed,
+    Upgraded,
+    Downgraded,
+    Changed,
+}
+
+impl fmt::Display for DiffExplain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            DiffExplain::Added => "Added", // not found before, found after
+            DiffExplain::Removed => "Removed", // found before, not found after
+            DiffExplain::Upgraded => "Upgraded", // found with a higher version after
+            DiffExplain::Downgraded => "Downgraded", // found with a lower version after
+            DiffExplain::Changed => "Changed", // found with different versions, same highest version
+        };
+        write!(f, "{}", value)
+    }
+}
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone)]
+pub(crate) struct DiffRecord {
+    name: String,
+    before: Vec<VersionSpec>,
+    after: Vec<VersionSpec>,
+    explain: DiffExplain,
+}
+
+fn versions_display(versions: &[VersionSpec]) -> String {
+    versions
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+impl Rowable for DiffRecord {
+    fn to_rows(&self, _context: &RowableContext) -> Vec<Vec<String>> {
+        vec![vec![
+            self.name.clone(),
+            versions_display(&self.before),
+            versions_display(&self.after),
+            self.explain.to_string(),
+        ]]
+    }
+}
+
+//------------------------------------------------------------------------------
+/// A report of the packages added, removed, and changed in version between two environments. As an environment might have more than one version of a package installed, all versions are compared.
+pub(crate) struct DiffReport {
+    records: Vec<DiffRecord>,
+}
+
+impl DiffReport {
+    pub(crate) fn from_packages<'a, I, J>(before: I, after: J) -> Self
+    where
+        I: IntoIterator<Item = &'a Package>,
+        J: IntoIterator<Item = &'a Package>,
+    {
+        // map key to name and versions in each environment
+        let mut key_to_versions: BTreeMap<
+            String,
+            (String, Vec<VersionSpec>, Vec<VersionSpec>),
+        > = BTreeMap::new();
+        for package in before {
+            let entry = key_to_versions
+                .entry(package.key.clone())
+                .or_insert_with(|| (package.name.clone(), Vec::new(), Vec::new()));
+            entry.1.push(package.version.clone());
+        }
+        for package in after {
+            let entry = key_to_versions
+                .entry(package.key.clone())
+                .or_insert_with(|| (package.name.clone(), Vec::new(), Vec::new()));
+            entry.2.push(package.version.clone());
+        }
+        let mut records = Vec::new();
+        for (_, (name, mut before, mut after)) in key_to_versions {
+            before.sort();
+            before.dedup();
+            after.sort();
+            after.dedup();
+            let explain = match (before.last(), after.last()) {
+                (None, Some(_)) => DiffExplain::Added,
+                (Some(_), None) => DiffExplain::Removed,
+                (Some(b), Some(a)) if a > b => DiffExplain::Upgraded,
+                (Some(b), Some(a)) if a < b => DiffExplain::Downgraded,
+                _ if before != after => DiffExplain::Changed,
+                _ => continue,
+            };
+            records.push(DiffRecord {
+                name,
+                before,
+                after,
+                explain,
+            });
+        }
+        DiffReport { records }
+    }
+}
+
+impl Tableable<DiffRecord> for DiffReport {
+    fn get_header(&self) -> Vec<ColumnFormat> {
+        vec![
+            ColumnFormat::new("Package".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Before".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("After".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Change".to_string(), false, "#666666".to_string()),
+        ]
+    }
+    fn get_records(&self) -> &Vec<DiffRecord> {
+        &self.records
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn to_packages(pairs: &[(&str, &str)]) -> Vec<Package> {
+        pairs
+            .iter()
+            .map(|(n, v)| Package::from_name_version_durl(n, v, None).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_report_a() {
+        let before = to_packages(&[
+            ("numpy", "1.26.4"),
+            ("requests", "2.32.3"),
+            ("pytz", "2024.2"),
+            ("six", "1.16.0"),
+            ("idna", "3.10"),
+        ]);
+        let after = to_packages(&[
+            ("numpy", "2.1.2"),
+            ("requests", "2.31.0"),
+            ("pytz", "2024.2"),
+            ("idna", "3.10"),
+            ("idna", "3.7"),
+            ("Flask", "3.0.3"),
+        ]);
+        let dr = DiffReport::from_packages(&before, &after);
+
+        let dir = tempdir().unwrap();
+        let fp = dir.path().join("report.txt");
+        dr.to_file(&fp, '|').unwrap();
+        let lines: Vec<String> = fs::read_to_string(&fp)
+            .unwrap()
+            .lines()
+            .map(|l| l.to_string())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Package|Before|After|Change",
+                "Flask||3.0.3|Added",
+                "idna|3.10|3.7,3.10|Changed",
+                "numpy|1.26.4|2.1.2|Upgraded",
+                "requests|2.32.3|2.31.0|Downgraded",
+                "six|1.16.0||Removed",
+            ]
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use rayon::prelude::*;
+
+use crate::root_search::read_pyvenv_cfg;
+use crate::table::ColumnFormat;
+use crate::table::Rowable;
+use crate::table::RowableContext;
+use crate::table::Tableable;
+use crate::util::path_is_same;
+use crate::util::py_command;
+use crate::util::py_output;
+
+const PY_VERSION: &str = "import sys;print(\"%d.%d.%d\" % sys.version_info[:3])";
+
+// The maximum number of symlinks followed within the bin directory of a virtual environment.
+const MAX_LINKS: usize = 8;
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum DoctorProblem {
+    MissingInterpreter,
+    BrokenInterpreter,
+    MissingHome,
+    HomeMismatch,
+    VersionDrift,
+}
+
+impl fmt::Display for DoctorProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            DoctorProblem::MissingInterpreter => "MissingInterpreter",
+            DoctorProblem::BrokenInterpreter => "BrokenInterpreter",
+            DoctorProblem::MissingHome => "MissingHome",
+            DoctorProblem::HomeMismatch => "HomeMismatch",
+            DoctorProblem::VersionDrift => "VersionDrift",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+// Given an executable in the bin directory of a virtual environment, follow symlinks within that directory (e.g. `python` to `python3.12`) and return the first target outside of it, which is the base interpreter. Returns None if the executable is not a symlink out of that directory, as when created with `--copies`.
+fn exe_to_link_target(exe: &Path) -> Option<PathBuf> {
+    let bin = exe.parent()?;
+    let mut path = exe.to_path_buf();
+    for _ in 0..MAX_LINKS {
+        let target = bin.join(fs::read_link(&path).ok()?);
+        if target.parent() != Some(bin) {
+            return Some(target);
+        }
+        path = target;
+    }
+    None
+}
+
+// Given a path to a Python binary, call out to Python to get its full version.
+fn get_exe_version(executable: &Path) -> Option<String> {
+    let output =
+        py_output(py_command(executable, true).arg("-c").arg(PY_VERSION)).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let version = std::str::from_utf8(&output.stdout).ok()?.trim().to_string();
+    if version.is_empty() {
+        None
+    } else {
+        Some(version)
+    }
+}
+
+// Return the major and minor components of a version, e.g. `3.12` of `3.12.4`.
+fn to_minor(version: &str) -> String {
+    version.split('.').take(2).collect::<Vec<_>>().join(".")
+}
+
+// Given a virtual environment executable and its prefix, return the problems found. The interpreter is only run if `run` is set and the interpreter exists.
+fn exe_to_problems(
+    exe: &Path,
+    prefix: &Path,
+    cfg: &[(String, String)],
+    run: bool,
+) -> Vec<(DoctorProblem, String)> {
+    let get = |key: &str| cfg.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+    let mut problems = Vec::new();
+    if fs::symlink_metadata(exe).is_err() {
+        problems.push((
+            DoctorProblem::MissingInterpreter,
+            format!("{} does not exist; recreate the environment", exe.display()),
+        ));
+        return problems;
+    }
+    let target = exe_to_link_target(exe);
+    let is_broken = fs::metadata(exe).is_err();
+    if is_broken {
+        let target_display = target
+            .as_ref()
+            .map(|t| t.display().to_string())
+            .unwrap_or_default();
+        problems.push((
+            DoctorProblem::BrokenInterpreter,
+            format!(
+                "links to {}, which does not exist; the base Python was moved or removed; recreate the environment",
+                target_display
+            ),
+        ));
+    }
+    match get("home").map(PathBuf::from) {
+        None => problems.push((
+            DoctorProblem::MissingHome,
+            "pyvenv.cfg does not define home; recreate the environment".to_string(),
+        )),
+        Some(home) if !home.is_dir() => problems.push((
+            DoctorProblem::MissingHome,
+            format!(
+                "home {} of pyvenv.cfg does not exist; update home or recreate the environment",
+                home.display()
+            ),
+        )),
+        Some(home) => {
+            let target_dir = target.as_ref().and_then(|t| t.parent());
+            if let Some(target_dir) = target_dir.filter(|d| !is_broken && !path_is_same(d, &home))
+            {
+                problems.push((
+                    DoctorProblem::HomeMismatch,
+                    format!(
+                        "interpreter links to {}, not to home {} of pyvenv.cfg; update home to {}",
+                        target.as_ref().map(|t| t.display().to_string()).unwrap_or_default(),
+                        home.display(),
+                        target_dir.display()
+                    ),
+                ));
+            }
+        }
+    }
+    // uv writes `version_info`; venv and virtualenv write `version`
+    let version_cfg = get("version_info").or_else(|| get("version"));
+    let version_exe = if run && !is_broken {
+        get_exe_version(exe)
+    } else {
+        None
+    };
+    if let (Some(version_cfg), Some(version_exe)) = (version_cfg, version_exe) {
+        if to_minor(version_cfg) != to_minor(&version_exe) {
+            problems.push((
+                DoctorProblem::VersionDrift,
+                format!(
+                    "pyvenv.cfg version {} but base Python is {}; installed packages are not importable; recreate the environment",
+                    version_cfg, version_exe
+                ),
+            ));
+        } else if version_cfg != version_exe {
+            problems.push((
+                DoctorProblem::VersionDrift,
+                format!(
+                    "pyvenv.cfg version {} but base Python is {}; run `{} -m venv --upgrade {}`",
+                    version_cfg,
+                    version_exe,
+                    exe.display(),
+                    prefix.display()
+                ),
+            ));
+        }
+    }
+    problems
+}
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone)]
+pub(crate) struct DoctorRecord {
+    exe: PathBuf,
+    problem: DoctorProblem,
+    detail: String,
+}
+
+impl Rowable for DoctorRecord {
+    fn to_rows(&self, _context: &RowableContext) -> Vec<Vec<String>> {
+        vec![vec![
+            self.exe.display().to_string(),
+            self.problem.to_string(),
+            self.detail.clone(),
+        ]]
+    }
+}
+
+//------------------------------------------------------------------------------
+/// A report of the integrity of the virtual environments of scanned executables, as defined by a `pyvenv.cfg` in the parent of their bin directory: interpreter symlinks that are missing or broken, a `home` that does not exist or does not match the interpreter, and a `pyvenv.cfg` version that has drifted from that of the base Python. Executables that are not in virtual environments are not examined.
+pub(crate) struct DoctorReport {
+    records: Vec<DoctorRecord>,
+}
+
+impl DoctorReport {
+    /// Given executables, return a report of the problems of their virtual environments. If `run` is set, interpreters are run to get their versions.
+    pub(crate) fn from_exes(exes: &[PathBuf], run: bool) -> Self {
+        let mut records: Vec<DoctorRecord> = exes
+            .par_iter()
+            .flat_map(|exe| {
+                let prefix = exe.parent().and_then(|p| p.parent());
+                let cfg = prefix.and_then(read_pyvenv_cfg);
+                match (prefix, cfg) {
+                    (Some(prefix), Some(cfg)) => exe_to_problems(exe, prefix, &cfg, run)
+                        .into_iter()
+                        .map(|(problem, detail)| DoctorRecord {
+                            exe: exe.clone(),
+                            problem,
+                            detail,
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                }
+            })
+            .collect();
+        records.sort_by(|a, b| (&a.exe, a.problem).cmp(&(&b.exe, b.problem)));
+        DoctorReport { records }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.records.len()
+    }
+}
+
+impl Tableable<DoctorRecord> for DoctorReport {
+    fn get_header(&self) -> Vec<ColumnFormat> {
+        vec![
+            ColumnFormat::new("Executable".to_string(), true, "#666666".to_string()),
+            ColumnFormat::new("Problem".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Detail".to_string(), false, "#666666".to_string()),
+        ]
+    }
+    fn get_records(&self) -> &Vec<DoctorRecord> {
+        &self.records
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(unix)]
+    use std::os::unix::fs::symlink;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_to_minor_a() {
+        assert_eq!(to_minor("3.12.4"), "3.12");
+        assert_eq!(to_minor("3.12"), "3.12");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_doctor_report_a() {
+        let dir = tempdir().unwrap();
+        let base = dir.path().join("base/bin");
+        let other = dir.path().join("other/bin");
+        fs::create_dir_all(&base).unwrap();
+        fs::create_dir_all(&other).unwrap();
+        fs::write(base.join("python3.12"), "").unwrap();
+        fs::write(other.join("python3.12"), "").unwrap();
+
+        let venvs = [
+            // valid: links within bin then to the base
+            ("ok", base.clone(), base.join("python3.12")),
+            // the base interpreter was removed
+            ("broken", base.clone(), base.join("python3.11")),
+            // home does not exist
+            (
+                "moved",
+                dir.path().join("gone/bin"),
+                base.join("python3.12"),
+            ),
+            // home is not the base of the link
+            ("mismatch", base.clone(), other.join("python3.12")),
+        ];
+        let mut exes = Vec::new();
+        for (name, home, target) in venvs.iter() {
+            let prefix = dir.path().join(name);
+            let bin = prefix.join("bin");
+            fs::create_dir_all(&bin).unwrap();
+            fs::write(
+                prefix.join("pyvenv.cfg"),
+                format!("home = {}\nversion = 3.12.4\n", home.display()),
+            )
+            .unwrap();
+            symlink(target, bin.join("python3.12")).unwrap();
+            symlink("python3.12", bin.join("python")).unwrap();
+            exes.push(bin.join("python"));
+        }
+        // not a virtual environment
+        exes.push(base.join("python3.12"));
+        // a missing interpreter
+        exes.push(dir.path().join("ok/bin/python3"));
+
+        let dr = DoctorReport::from_exes(&exes, false);
+        let problems: Vec<(String, DoctorProblem)> = dr
+            .records
+            .iter()
+            .map(|r| {
+                let prefix = r.exe.parent().unwrap().parent().unwrap();
+                (
+                    prefix.file_name().unwrap().to_string_lossy().to_string(),
+                    r.problem,
+                )
+            })
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                ("broken".to_string(), DoctorProblem::BrokenInterpreter),
+                ("mismatch".to_string(), DoctorProblem::HomeMismatch),
+                ("moved".to_string(), DoctorProblem::MissingHome),
+                ("ok".to_string(), DoctorProblem::MissingInterpreter),
+            ]
+        );
+        assert!(dr.records[1]
+            .detail
+            .ends_with(&format!("update home to {}", other.display())));
+    }
+}
@@ -0,0 +1,74 @@
+use std::io;
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use crate::warning_report::ScanWarning;
+
+//------------------------------------------------------------------------------
+/// Errors raised while discovering executables, reading sites, and writing reports. Failures limited to one executable or site are recorded as warnings of a scan rather than stopping it.
+#[derive(Debug, Error)]
+pub enum FetterError {
+    /// An executable could not be run.
+    #[error("Failed to run {executable:?}: {source}")]
+    ExeRun {
+        executable: PathBuf,
+        source: io::Error,
+    },
+    /// An executable ran but did not report its site packages.
+    #[error("Failed to read site packages from {executable:?}: {message}")]
+    ExeOutput {
+        executable: PathBuf,
+        message: String,
+    },
+    /// A site exists but could not be read.
+    #[error("Failed to read site {site:?}: {source}")]
+    SiteRead { site: PathBuf, source: io::Error },
+    /// A report could not be written to a file.
+    #[error("Failed to write {path:?}: {source}")]
+    ReportWrite { path: PathBuf, source: io::Error },
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+impl FetterError {
+    /// Return a warning, naming the executable or site that failed, to be reported with the results of a scan.
+    pub(crate) fn to_warning(&self) -> ScanWarning {
+        match self {
+            FetterError::ExeRun { executable, source } => {
+                ScanWarning::new(executable.clone(), source.to_string())
+            }
+            FetterError::ExeOutput {
+                executable,
+                message,
+            } => ScanWarning::new(executable.clone(), message.clone()),
+            FetterError::SiteRead { site, source } => {
+                ScanWarning::new(site.clone(), source.to_string())
+            }
+            e => ScanWarning::new(PathBuf::new(), e.to_string()),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_warning_a() {
+        let e = FetterError::SiteRead {
+            site: PathBuf::from("/usr/lib/python3/dist-packages"),
+            source: io::Error::new(io::ErrorKind::PermissionDenied, "permission denied"),
+        };
+        assert_eq!(
+            e.to_string(),
+            "Failed to read site \"/usr/lib/python3/dist-packages\": permission denied"
+        );
+        let w = e.to_warning();
+        assert_eq!(w.path, PathBuf::from("/usr/lib/python3/dist-packages"));
+        assert_eq!(w.message, "permission denied");
+    }
+}
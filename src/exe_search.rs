@@ -3,14 +3,52 @@ use std::env;
 use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
+use std::process::Command;
 
 use rayon::prelude::*;
+use tracing::warn;
 
+use crate::package_match::match_str;
+use crate::util::find_on_path;
 use crate::util::get_absolute_path_from_exe;
 use crate::util::is_python_exe;
 use crate::util::path_home;
 
 //------------------------------------------------------------------------------
+// Given the output of `py -0p`, return the paths of the executables listed. Each line has a version tag, an optional `*` marking the default, and a path that might contain spaces; older launchers mark the default after the path.
+fn py_launcher_to_exes(output: &str) -> Vec<PathBuf> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (tag, rest) = line.trim().split_once(char::is_whitespace)?;
+            if !tag.starts_with('-') {
+                return None;
+            }
+            let path = rest
+                .trim()
+                .trim_start_matches('*')
+                .trim_end_matches('*')
+                .trim();
+            if path.to_lowercase().ends_with(".exe") {
+                Some(PathBuf::from(path))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+// On Windows, use the `py` launcher to list all registered Python executables.
+fn get_py_launcher_exes() -> Vec<PathBuf> {
+    match Command::new("py").arg("-0p").output() {
+        Ok(output) => match std::str::from_utf8(&output.stdout) {
+            Ok(s) => py_launcher_to_exes(s),
+            Err(_) => Vec::new(),
+        },
+        Err(_) => Vec::new(),
+    }
+}
+
 // Provide absolute paths for directories that should be excluded from executable search.
 fn get_search_exclude_paths() -> HashSet<PathBuf> {
     let mut paths: HashSet<PathBuf> = HashSet::new();
@@ -26,10 +64,13 @@ fn get_search_exclude_paths() -> HashSet<PathBuf> {
                 paths.insert(home.clone().join(".Trash"));
             } else if env::consts::OS == "linux" {
                 paths.insert(home.clone().join(".local/share/Trash"));
+            } else if env::consts::OS == "windows" {
+                // per-user installs in AppData are searched directly
+                paths.insert(home.clone().join("AppData"));
             }
         }
         None => {
-            eprintln!("Error getting HOME");
+            warn!("Error getting HOME");
         }
     }
     paths
@@ -40,9 +81,9 @@ fn get_search_origins() -> HashSet<(PathBuf, bool)> {
     let mut paths: HashSet<(PathBuf, bool)> = HashSet::new();
 
     // get all paths on PATH
-    if let Ok(path_var) = env::var("PATH") {
-        for path in path_var.split(':') {
-            paths.insert((PathBuf::from(path), false));
+    if let Some(path_var) = env::var_os("PATH") {
+        for path in env::split_paths(&path_var) {
+            paths.insert((path, false));
         }
     }
     match path_home() {
@@ -51,21 +92,41 @@ fn get_search_origins() -> HashSet<(PathBuf, bool)> {
             // collect all directories in the user's home directory
             match fs::read_dir(home) {
                 Ok(entries) => {
-                    for entry in entries {
-                        let path = entry.unwrap().path();
+                    for entry in entries.flatten() {
+                        let path = entry.path();
                         if path.is_dir() {
                             paths.insert((path, true));
                         }
                     }
                 }
                 Err(e) => {
-                    eprintln!("Error reading home: {}", e);
+                    warn!("Error reading home: {}", e);
                 }
             }
         }
         None => {
-            eprintln!("Error getting HOME");
+            warn!("Error getting HOME");
+        }
+    }
+    if env::consts::OS == "windows" {
+        // per-user installs, as well as system installs
+        if let Some(local) = env::var_os("LOCALAPPDATA") {
+            let path = PathBuf::from(local).join("Programs").join("Python");
+            paths.insert((path, true));
+        }
+        for var in ["ProgramFiles", "ProgramFiles(x86)"] {
+            if let Some(program_files) = env::var_os(var) {
+                let path = PathBuf::from(program_files);
+                if let Ok(entries) = fs::read_dir(&path) {
+                    for entry in entries.flatten() {
+                        if entry.file_name().to_string_lossy().starts_with("Python") {
+                            paths.insert((entry.path(), false));
+                        }
+                    }
+                }
+            }
         }
+        return paths;
     }
     paths.insert((PathBuf::from("/bin"), false));
     paths.insert((PathBuf::from("/sbin"), false));
@@ -79,6 +140,66 @@ fn get_search_origins() -> HashSet<(PathBuf, bool)> {
     paths
 }
 
+// Return the pyenv root directory, as set by PYENV_ROOT or the default `~/.pyenv`.
+fn pyenv_root() -> Option<PathBuf> {
+    match env::var_os("PYENV_ROOT") {
+        Some(root) => Some(PathBuf::from(root)),
+        None => path_home().map(|home| home.join(".pyenv")),
+    }
+}
+
+// Return True if the path is an executable in the shims directory of the pyenv root.
+fn is_pyenv_shim(path: &Path, root: &Path) -> bool {
+    path.parent() == Some(root.join("shims").as_path())
+}
+
+/// If the path is a pyenv shim, follow it to the interpreter in the version directory selected by pyenv; otherwise, return the path unchanged.
+pub(crate) fn pyenv_shim_resolve(path: PathBuf) -> PathBuf {
+    match pyenv_root() {
+        Some(root) if is_pyenv_shim(&path, &root) => {
+            // the shim execs the selected interpreter, which reports its own path
+            match path.to_str().and_then(get_absolute_path_from_exe) {
+                Some(exe) if exe.is_absolute() => exe,
+                _ => path,
+            }
+        }
+        _ => path,
+    }
+}
+
+// Given a pyenv root, return the executable of each installed version, sorted by version directory. pyenv-win places `python.exe` directly in the version directory.
+fn pyenv_versions_to_exes(root: &Path) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Ok(entries) = fs::read_dir(root.join("versions")) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            if let Some(exe) = [
+                path.join("bin").join("python3"),
+                path.join("bin").join("python"),
+                path.join("python.exe"),
+            ]
+            .into_iter()
+            .find(|p| p.exists() && is_python_exe(p))
+            {
+                paths.push(exe);
+            }
+        }
+    }
+    paths.sort();
+    paths
+}
+
+/// Return the executables of all pyenv installed versions.
+pub(crate) fn find_exe_pyenv() -> Vec<PathBuf> {
+    match pyenv_root() {
+        Some(root) => pyenv_versions_to_exes(&root),
+        None => Vec::with_capacity(0),
+    }
+}
+
 fn is_symlink(path: &Path) -> bool {
     match fs::symlink_metadata(path) {
         Ok(metadata) => metadata.file_type().is_symlink(),
@@ -99,18 +220,24 @@ fn find_exe_inner(
     let mut paths = Vec::new();
 
     if path.is_dir() {
-        // if we find "fpdir/pyvenv.cfg", we can always get fpdir/bin/python3
+        // if we find "fpdir/pyvenv.cfg", we can always get fpdir/bin/python3 (or fpdir/bin/python), or fpdir/Scripts/python.exe on Windows
         let path_cfg = path.to_path_buf().join("pyvenv.cfg");
         if path_cfg.exists() {
-            let path_exe = path.to_path_buf().join("bin/python3");
-            if path_exe.exists() && is_python_exe(&path_exe) {
+            if let Some(path_exe) = [
+                path.join("bin").join("python3"),
+                path.join("bin").join("python"),
+                path.join("Scripts").join("python.exe"),
+            ]
+            .into_iter()
+            .find(|p| p.exists() && is_python_exe(p))
+            {
                 paths.push(path_exe)
             }
         } else {
             match fs::read_dir(path) {
                 Ok(entries) => {
-                    for entry in entries {
-                        let path = entry.unwrap().path();
+                    for entry in entries.flatten() {
+                        let path = entry.path();
                         if recurse && path.is_dir() && !is_symlink(&path) {
                             // recurse
                             // println!("recursing: {:?}", path);
@@ -122,7 +249,7 @@ fn find_exe_inner(
                 }
                 Err(e) => {
                     // log this?
-                    eprintln!("Error reading {:?}: {}", path, e);
+                    warn!("Error reading {:?}: {}", path, e);
                 }
             }
         }
@@ -130,28 +257,109 @@ fn find_exe_inner(
     paths
 }
 
-// After collecting origins, find all executables
-pub(crate) fn find_exe() -> HashSet<PathBuf> {
+// After collecting origins, find all executables. If `run` is false, no executables are run: pyenv shims, which cannot be followed without running them, are excluded, the `py` launcher is not used, and the default Python is found on `PATH`.
+pub(crate) fn find_exe(run: bool) -> HashSet<PathBuf> {
     let exclude = get_search_exclude_paths();
     let origins = get_search_origins();
 
-    let mut paths: HashSet<PathBuf> = origins
+    let found = origins
         .par_iter()
-        .flat_map(|(path, recurse)| find_exe_inner(path, &exclude, *recurse))
-        .collect();
-    if let Some(exe_def) = get_absolute_path_from_exe("python3") {
+        .flat_map(|(path, recurse)| find_exe_inner(path, &exclude, *recurse));
+    if !run {
+        let root = pyenv_root();
+        let mut paths: HashSet<PathBuf> = found
+            .filter(|p| !root.as_ref().is_some_and(|r| is_pyenv_shim(p, r)))
+            .collect();
+        let name = if env::consts::OS == "windows" {
+            "python"
+        } else {
+            "python3"
+        };
+        paths.extend(find_on_path(name));
+        return paths;
+    }
+    let mut paths: HashSet<PathBuf> = found.map(pyenv_shim_resolve).collect();
+    if env::consts::OS == "windows" {
+        paths.extend(get_py_launcher_exes());
+        if let Some(exe_def) = get_absolute_path_from_exe("python") {
+            paths.insert(exe_def);
+        }
+    } else if let Some(exe_def) = get_absolute_path_from_exe("python3") {
         paths.insert(exe_def);
     }
     paths
 }
 
+/// Recursively search each root directory for virtual environments and other Python executables. No directories are excluded.
+pub(crate) fn find_exe_roots(roots: &[PathBuf]) -> HashSet<PathBuf> {
+    let exclude = HashSet::with_capacity(0);
+    roots
+        .par_iter()
+        .flat_map(|root| find_exe_inner(root, &exclude, true))
+        .collect()
+}
+
+/// Return true if the path has a component with a `*` or `?` wildcard. A single `*` component, used to discover all executables, is not a glob.
+pub(crate) fn is_exe_glob(path: &Path) -> bool {
+    path.components().count() > 1
+        && path
+            .to_str()
+            .is_some_and(|s| s.contains('*') || s.contains('?'))
+}
+
+/// Expand a path with `*` or `?` wildcards in any component (e.g. `/opt/venvs/*/bin/python`) to the sorted paths of all matching files.
+pub(crate) fn find_exe_glob(pattern: &Path) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::new()];
+    for component in pattern.components() {
+        let part = component.as_os_str();
+        let glob = match part.to_str() {
+            Some(s) if s.contains('*') || s.contains('?') => s,
+            _ => {
+                for path in paths.iter_mut() {
+                    path.push(part);
+                }
+                continue;
+            }
+        };
+        let mut matched = Vec::new();
+        for path in &paths {
+            // a relative pattern starts from the current directory
+            let dir = if path.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                path.as_path()
+            };
+            let entries = match fs::read_dir(dir) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries.flatten() {
+                if entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| match_str(glob, name, false))
+                {
+                    matched.push(path.join(entry.file_name()));
+                }
+            }
+        }
+        paths = matched;
+    }
+    let mut paths: Vec<PathBuf> = paths.into_iter().filter(|p| p.is_file()).collect();
+    paths.sort();
+    paths
+}
+
 //------------------------------------------------------------------------------
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison, clippy::into_iter_on_ref)]
 mod tests {
 
     use super::*;
     use std::fs::File;
+    #[cfg(unix)]
     use std::os::unix::fs::symlink;
+    #[cfg(unix)]
     use std::os::unix::fs::PermissionsExt;
     use tempfile::tempdir;
 
@@ -236,4 +444,165 @@ mod tests {
         let pcp = pcv.iter().rev().collect::<PathBuf>();
         assert_eq!(pcp, PathBuf::from("bin/python3"));
     }
+
+    #[test]
+    fn test_scan_executable_inner_b() {
+        let temp_dir = tempdir().unwrap();
+        let fpd1 = temp_dir.path();
+        let _ = File::create(fpd1.join("pyvenv.cfg")).unwrap();
+
+        let fpd2 = fpd1.join("Scripts");
+        fs::create_dir(fpd2.clone()).unwrap();
+        let fpf2 = fpd2.join("python.exe");
+        let _ = File::create(fpf2.clone()).unwrap();
+        let mut perms = fs::metadata(fpf2.clone()).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(fpf2.clone(), perms).unwrap();
+
+        let exclude_paths = HashSet::with_capacity(0);
+        let result = find_exe_inner(fpd1, &exclude_paths, true);
+        assert_eq!(result, vec![fpf2]);
+    }
+
+    #[test]
+    fn test_scan_executable_inner_c() {
+        let temp_dir = tempdir().unwrap();
+        let fpd1 = temp_dir.path();
+        let _ = File::create(fpd1.join("pyvenv.cfg")).unwrap();
+
+        let fpd2 = fpd1.join("bin");
+        fs::create_dir(fpd2.clone()).unwrap();
+        let fpf2 = fpd2.join("python");
+        let _ = File::create(fpf2.clone()).unwrap();
+        let mut perms = fs::metadata(fpf2.clone()).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(fpf2.clone(), perms).unwrap();
+
+        let exclude_paths = HashSet::with_capacity(0);
+        let result = find_exe_inner(fpd1, &exclude_paths, true);
+        assert_eq!(result, vec![fpf2]);
+    }
+
+    #[test]
+    fn test_find_exe_roots_a() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        let mut expected = HashSet::new();
+        for venv in ["app-a", "team/app-b", "team/nested/app-c"] {
+            let fpd = root.join(venv);
+            fs::create_dir_all(fpd.join("bin")).unwrap();
+            let _ = File::create(fpd.join("pyvenv.cfg")).unwrap();
+            let fpf = fpd.join("bin").join("python3");
+            let _ = File::create(fpf.clone()).unwrap();
+            let mut perms = fs::metadata(fpf.clone()).unwrap().permissions();
+            perms.set_mode(0o755);
+            fs::set_permissions(fpf.clone(), perms).unwrap();
+            expected.insert(fpf);
+        }
+        // directories without environments are walked but contribute nothing
+        fs::create_dir_all(root.join("team/empty")).unwrap();
+        assert_eq!(find_exe_roots(&[root.to_path_buf()]), expected);
+    }
+
+    #[test]
+    fn test_is_pyenv_shim_a() {
+        let root = PathBuf::from("/home/user/.pyenv");
+        assert_eq!(
+            is_pyenv_shim(Path::new("/home/user/.pyenv/shims/python3"), &root),
+            true
+        );
+        assert_eq!(
+            is_pyenv_shim(
+                Path::new("/home/user/.pyenv/versions/3.12.4/bin/python3"),
+                &root
+            ),
+            false
+        );
+        assert_eq!(is_pyenv_shim(Path::new("/usr/bin/python3"), &root), false);
+    }
+
+    #[test]
+    fn test_pyenv_shim_resolve_a() {
+        let fp = PathBuf::from("/usr/bin/python3");
+        assert_eq!(pyenv_shim_resolve(fp.clone()), fp);
+    }
+
+    #[test]
+    fn test_pyenv_versions_to_exes_a() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        let mut expected = Vec::new();
+        for version in ["3.12.4", "3.9.19"] {
+            let fpd = root.join("versions").join(version).join("bin");
+            fs::create_dir_all(fpd.clone()).unwrap();
+            let fpf = fpd.join("python3");
+            let _ = File::create(fpf.clone()).unwrap();
+            let mut perms = fs::metadata(fpf.clone()).unwrap().permissions();
+            perms.set_mode(0o755);
+            fs::set_permissions(fpf.clone(), perms).unwrap();
+            expected.push(fpf);
+        }
+        // a version directory without an interpreter is ignored
+        fs::create_dir_all(root.join("versions").join("broken")).unwrap();
+        assert_eq!(pyenv_versions_to_exes(root), expected);
+    }
+
+    #[test]
+    fn test_pyenv_versions_to_exes_b() {
+        let temp_dir = tempdir().unwrap();
+        assert!(pyenv_versions_to_exes(temp_dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_py_launcher_to_exes_a() {
+        let output = concat!(
+            " -V:3.12 *        C:\\Users\\me\\AppData\\Local\\Programs\\Python\\Python312\\python.exe\r\n",
+            " -V:3.11          C:\\Program Files\\Python311\\python.exe\r\n",
+            " -V:ContinuumAnalytics/Anaconda39-64 C:\\ProgramData\\Anaconda3\\python.exe\r\n",
+        );
+        assert_eq!(
+            py_launcher_to_exes(output),
+            vec![
+                PathBuf::from("C:\\Users\\me\\AppData\\Local\\Programs\\Python\\Python312\\python.exe"),
+                PathBuf::from("C:\\Program Files\\Python311\\python.exe"),
+                PathBuf::from("C:\\ProgramData\\Anaconda3\\python.exe"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_py_launcher_to_exes_b() {
+        let output = "Installed Pythons found by py Launcher for Windows\n -3.9-64        C:\\Python39\\python.exe *\n -2.7-32        C:\\Python27\\python.exe\n";
+        assert_eq!(
+            py_launcher_to_exes(output),
+            vec![
+                PathBuf::from("C:\\Python39\\python.exe"),
+                PathBuf::from("C:\\Python27\\python.exe"),
+            ]
+        );
+        assert!(py_launcher_to_exes("No installed Pythons found!\n").is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_exe_glob_a() {
+        let dir = tempdir().unwrap();
+        for venv in ["env-a", "env-b", "other"] {
+            let dir_bin = dir.path().join(venv).join("bin");
+            fs::create_dir_all(&dir_bin).unwrap();
+            File::create(dir_bin.join("python")).unwrap();
+        }
+        let pattern = dir.path().join("env-*/bin/python");
+        assert!(is_exe_glob(&pattern));
+        assert_eq!(
+            find_exe_glob(&pattern),
+            vec![
+                dir.path().join("env-a/bin/python"),
+                dir.path().join("env-b/bin/python"),
+            ]
+        );
+        assert_eq!(find_exe_glob(&dir.path().join("*/bin/python3")).len(), 0);
+        assert!(!is_exe_glob(Path::new("*")));
+        assert!(!is_exe_glob(Path::new("/usr/bin/python3")));
+    }
 }
@@ -0,0 +1,80 @@
+use std::process::Command;
+
+use crate::util::ResultDynError;
+
+pub trait GitClient {
+    /// Return the output of `git ls-remote` for the given repository URL and reference.
+    fn ls_remote(&self, url: &str, reference: &str) -> ResultDynError<String>;
+}
+
+pub struct GitClientLive;
+
+impl GitClient for GitClientLive {
+    fn ls_remote(&self, url: &str, reference: &str) -> ResultDynError<String> {
+        let output = Command::new("git")
+            .arg("ls-remote")
+            .arg(url)
+            .arg(reference)
+            .output()
+            .map_err(|e| format!("Failed to execute git: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "git ls-remote failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+        Ok(String::from_utf8(output.stdout)?)
+    }
+}
+
+#[allow(dead_code)]
+pub struct GitClientMock {
+    pub mock_ls_remote: Option<String>,
+}
+
+impl GitClient for GitClientMock {
+    fn ls_remote(&self, _url: &str, _reference: &str) -> ResultDynError<String> {
+        match &self.mock_ls_remote {
+            Some(mock_ls_remote) => Ok(mock_ls_remote.clone()),
+            None => Ok("".to_string()),
+        }
+    }
+}
+
+/// Given the output of `git ls-remote`, return the commit of the first reference. If a tag is annotated, the commit of the peeled tag (as listed with a `^{}` suffix) is returned.
+pub(crate) fn ls_remote_to_commit(output: &str) -> Option<String> {
+    let refs: Vec<(&str, &str)> = output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .collect();
+    refs.iter()
+        .find(|(_, name)| name.ends_with("^{}"))
+        .or_else(|| refs.first())
+        .map(|(commit, _)| commit.trim().to_string())
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ls_remote_to_commit_a() {
+        let output = "a0a8e86976708d0436eec5c8f7d25329da727cb5\trefs/heads/main\n";
+        assert_eq!(
+            ls_remote_to_commit(output).unwrap(),
+            "a0a8e86976708d0436eec5c8f7d25329da727cb5"
+        );
+        assert_eq!(ls_remote_to_commit(""), None);
+    }
+
+    #[test]
+    fn test_ls_remote_to_commit_b() {
+        let output = "3c5a2c7c1e0e0cb1dd1a5ab0b3f0a0ac0a4c1d3e\trefs/tags/0.3.8\na0a8e86976708d0436eec5c8f7d25329da727cb5\trefs/tags/0.3.8^{}\n";
+        assert_eq!(
+            ls_remote_to_commit(output).unwrap(),
+            "a0a8e86976708d0436eec5c8f7d25329da727cb5"
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::path_shared::PathShared;
+use crate::root_search::read_pyvenv_cfg;
+use crate::root_search::site_to_python_version;
+use crate::table::ColumnFormat;
+use crate::table::Rowable;
+use crate::table::RowableContext;
+use crate::table::Tableable;
+use crate::util::py_command;
+use crate::util::py_output;
+use crate::util::ResultDynError;
+use crate::zip_index::is_zip_archive;
+
+const PY_INTERPRETER: &str = "import json,sys,platform;print(json.dumps(dict(version=platform.python_version(),implementation=platform.python_implementation(),build=', '.join(platform.python_build()),prefix=sys.prefix)))";
+
+//------------------------------------------------------------------------------
+/// How the metadata of an interpreter was obtained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum InterpreterSource {
+    /// The interpreter was run.
+    Run,
+    /// Read from the `pyvenv.cfg` of a virtual environment.
+    PyvenvCfg,
+    /// Inferred from the names of site directories.
+    Site,
+}
+
+impl fmt::Display for InterpreterSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            InterpreterSource::Run => "run",
+            InterpreterSource::PyvenvCfg => "pyvenv.cfg",
+            InterpreterSource::Site => "site",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+// Return the numeric release components of a `pyvenv.cfg` version: virtualenv writes `version_info = 3.12.4.final.0`.
+fn to_release(version: &str) -> String {
+    version
+        .split('.')
+        .take_while(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+        .take(3)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+//------------------------------------------------------------------------------
+/// The version, implementation, build, and prefix of a Python executable. Values that cannot be determined without running the interpreter are None.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Interpreter {
+    pub(crate) executable: PathBuf,
+    pub(crate) version: Option<String>,
+    pub(crate) implementation: Option<String>,
+    pub(crate) build: Option<String>,
+    pub(crate) prefix: Option<PathBuf>,
+    pub(crate) source: InterpreterSource,
+}
+
+impl Interpreter {
+    /// Given a path to a Python binary, call out to Python to get its metadata. A zip archive, used in place of an executable, is not run.
+    pub(crate) fn from_exe(executable: &Path) -> ResultDynError<Self> {
+        if is_zip_archive(executable) {
+            return Err(format!("cannot run a zip archive: {:?}", executable).into());
+        }
+        let output =
+            py_output(py_command(executable, true).arg("-c").arg(PY_INTERPRETER))?;
+        let mut values: HashMap<String, String> =
+            serde_json::from_str(std::str::from_utf8(&output.stdout)?.trim())?;
+        Ok(Interpreter {
+            executable: executable.to_path_buf(),
+            version: values.remove("version"),
+            implementation: values.remove("implementation"),
+            build: values.remove("build"),
+            prefix: values.remove("prefix").map(PathBuf::from),
+            source: InterpreterSource::Run,
+        })
+    }
+
+    /// Given a path to a Python binary that is not run, such as one in a container filesystem, return the metadata that can be read from the `pyvenv.cfg` of its virtual environment or, if not in a virtual environment, inferred from the names of its sites. The build is never known.
+    pub(crate) fn from_static(executable: &Path, sites: &[PathShared]) -> Self {
+        let dir = executable.parent();
+        let prefix = dir.and_then(|d| d.parent());
+        let cfg = prefix.and_then(read_pyvenv_cfg);
+        let get = |key: &str| {
+            cfg.as_ref()
+                .and_then(|cfg| cfg.iter().find(|(k, _)| k == key))
+                .map(|(_, v)| v.clone())
+        };
+        // uv writes `version_info`; venv and virtualenv write `version`
+        let version = get("version_info")
+            .or_else(|| get("version"))
+            .map(|v| to_release(&v))
+            .filter(|v| !v.is_empty())
+            .or_else(|| {
+                sites
+                    .iter()
+                    .find_map(|site| site_to_python_version(site.as_path()))
+            });
+        let is_pypy = executable
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with("pypy"));
+        let implementation =
+            get("implementation").or_else(|| is_pypy.then(|| "PyPy".to_string()));
+        // without a pyvenv.cfg, only executables in `bin` have a known prefix
+        let prefix = if cfg.is_some()
+            || dir.and_then(|d| d.file_name()).is_some_and(|n| n == "bin")
+        {
+            prefix.map(|p| p.to_path_buf())
+        } else {
+            None
+        };
+        Interpreter {
+            executable: executable.to_path_buf(),
+            version,
+            implementation,
+            build: None,
+            prefix,
+            source: if cfg.is_some() {
+                InterpreterSource::PyvenvCfg
+            } else {
+                InterpreterSource::Site
+            },
+        }
+    }
+}
+
+impl Rowable for Interpreter {
+    fn to_rows(&self, _context: &RowableContext) -> Vec<Vec<String>> {
+        vec![vec![
+            self.executable.display().to_string(),
+            self.version.clone().unwrap_or_default(),
+            self.implementation.clone().unwrap_or_default(),
+            self.build.clone().unwrap_or_default(),
+            self.prefix
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default(),
+            self.source.to_string(),
+        ]]
+    }
+}
+
+//------------------------------------------------------------------------------
+/// A report of the metadata of each scanned executable.
+pub(crate) struct InterpreterReport {
+    records: Vec<Interpreter>,
+}
+
+impl InterpreterReport {
+    pub(crate) fn from_interpreters(mut records: Vec<Interpreter>) -> Self {
+        records.sort_by(|a, b| a.executable.cmp(&b.executable));
+        InterpreterReport { records }
+    }
+}
+
+impl Tableable<Interpreter> for InterpreterReport {
+    fn get_header(&self) -> Vec<ColumnFormat> {
+        vec![
+            ColumnFormat::new("Executable".to_string(), true, "#666666".to_string()),
+            ColumnFormat::new("Version".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Implementation".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Build".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Prefix".to_string(), true, "#666666".to_string()),
+            ColumnFormat::new("Source".to_string(), false, "#666666".to_string()),
+        ]
+    }
+    fn get_records(&self) -> &Vec<Interpreter> {
+        &self.records
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_to_release_a() {
+        assert_eq!(to_release("3.12.4"), "3.12.4");
+        assert_eq!(to_release("3.12.4.final.0"), "3.12.4");
+        assert_eq!(to_release("3.13.0rc1"), "3.13");
+    }
+
+    #[test]
+    fn test_interpreter_from_static_a() {
+        let dir = tempdir().unwrap();
+        let prefix = dir.path().join("venv");
+        fs::create_dir_all(prefix.join("bin")).unwrap();
+        fs::write(
+            prefix.join("pyvenv.cfg"),
+            "home = /usr/bin\nimplementation = CPython\nversion_info = 3.12.4.final.0\n",
+        )
+        .unwrap();
+        let i1 = Interpreter::from_static(&prefix.join("bin/python"), &[]);
+        assert_eq!(i1.version, Some("3.12.4".to_string()));
+        assert_eq!(i1.implementation, Some("CPython".to_string()));
+        assert_eq!(i1.build, None);
+        assert_eq!(i1.prefix, Some(prefix.clone()));
+        assert_eq!(i1.source, InterpreterSource::PyvenvCfg);
+
+        let sites = vec![PathShared::from_str("/opt/pypy/lib/pypy3.10/site-packages")];
+        let i2 = Interpreter::from_static(Path::new("/opt/pypy/bin/pypy3"), &sites);
+        assert_eq!(i2.version, None);
+        assert_eq!(i2.implementation, Some("PyPy".to_string()));
+        assert_eq!(i2.prefix, Some(PathBuf::from("/opt/pypy")));
+        assert_eq!(i2.source, InterpreterSource::Site);
+
+        let sites = vec![PathShared::from_str("/usr/lib/python3.11/site-packages")];
+        let i3 = Interpreter::from_static(Path::new("/usr/bin/python3"), &sites);
+        assert_eq!(i3.version, Some("3.11".to_string()));
+        assert_eq!(i3.implementation, None);
+    }
+}
@@ -1,24 +1,86 @@
+//! Discovery and validation of the Python packages installed in the site packages of one or more Python executables.
+//!
+//! The `fetter` binary is a thin wrapper of `run_cli`; the scanning and validation logic is also available to other tools:
+//!
+//! ```no_run
+//! use std::path::{Path, PathBuf};
+//! use fetter::{DepManifest, ScanFS, ScanFlags, ValidationFlags};
+//!
+//! let sfs = ScanFS::from_exes(&[PathBuf::from("python3")], ScanFlags::default()).unwrap();
+//! let dm = DepManifest::from_requirements_file(Path::new("requirements.txt")).unwrap();
+//! let vf = ValidationFlags::default().with_permit_superset(true);
+//! let vr = sfs.to_validation_report(dm, vf);
+//! for record in vr.to_validation_digest() {
+//!     println!("{}", record);
+//! }
+//! ```
+
 mod audit_report;
+mod ban_policy;
+mod check_report;
 mod cli;
+mod conda_meta;
+mod config;
 mod count_report;
+mod dep_graph;
 mod dep_manifest;
+mod dep_marker;
 mod dep_spec;
+mod diff_report;
+mod doctor_report;
+mod error;
 mod exe_search;
+mod git_client;
+mod interpreter_report;
+mod license_policy;
+mod logging;
+mod map_report;
 mod osv_query;
 mod osv_vulns;
+mod outdated_report;
 mod package;
 mod package_durl;
 mod package_match;
+mod package_metadata;
 mod path_shared;
+mod pypi_json;
+mod root_search;
+mod sarif;
+mod sbom;
+mod sbom_cyclonedx;
+mod sbom_spdx;
 mod scan_fs;
 mod scan_report;
+mod scripts_report;
+mod site_cache;
+mod size_report;
 mod spin;
+mod stray_report;
 mod table;
+mod table_view;
+mod tree_report;
 mod unpack_report;
 mod ureq_client;
 mod util;
 mod validation_report;
+mod verify_report;
 mod version_spec;
+mod waiver;
+mod warning_report;
+mod wheel_tag;
+mod zip_index;
 
 pub use cli::run_cli;
+pub use dep_manifest::DepManifest;
+pub use dep_spec::DepSpec;
+pub use error::FetterError;
+pub use package::Package;
+pub use scan_fs::ScanFS;
+pub use scan_fs::ScanFlags;
 pub use table::write_color;
+pub use validation_report::ValidationDigest;
+pub use validation_report::ValidationDigestRecord;
+pub use validation_report::ValidationExplain;
+pub use validation_report::ValidationFlags;
+pub use validation_report::ValidationReport;
+pub use version_spec::VersionSpec;
@@ -0,0 +1,227 @@
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::package_match::match_str;
+use crate::util::ResultDynError;
+
+//------------------------------------------------------------------------------
+// A parsed SPDX license expression. License exceptions (`WITH`) are retained only as part of the identifier they modify, and are not considered by policies.
+#[derive(Debug, Clone, PartialEq)]
+enum LicenseExpr {
+    Id(String),
+    And(Vec<LicenseExpr>),
+    Or(Vec<LicenseExpr>),
+}
+
+fn tokenize(expression: &str) -> Vec<String> {
+    expression
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .split_whitespace()
+        .map(|t| t.to_string())
+        .collect()
+}
+
+// A recursive descent parser, where AND binds tighter than OR.
+struct LicenseParser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl LicenseParser {
+    fn peek_is(&self, keyword: &str) -> bool {
+        self.tokens
+            .get(self.pos)
+            .is_some_and(|t| t.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse_or(&mut self) -> Option<LicenseExpr> {
+        let mut parts = vec![self.parse_and()?];
+        while self.peek_is("OR") {
+            self.pos += 1;
+            parts.push(self.parse_and()?);
+        }
+        Some(if parts.len() == 1 {
+            parts.remove(0)
+        } else {
+            LicenseExpr::Or(parts)
+        })
+    }
+
+    fn parse_and(&mut self) -> Option<LicenseExpr> {
+        let mut parts = vec![self.parse_atom()?];
+        while self.peek_is("AND") {
+            self.pos += 1;
+            parts.push(self.parse_atom()?);
+        }
+        Some(if parts.len() == 1 {
+            parts.remove(0)
+        } else {
+            LicenseExpr::And(parts)
+        })
+    }
+
+    fn parse_atom(&mut self) -> Option<LicenseExpr> {
+        let token = self.tokens.get(self.pos)?.clone();
+        self.pos += 1;
+        match token.as_str() {
+            "(" => {
+                let expr = self.parse_or()?;
+                if self.tokens.get(self.pos).is_some_and(|t| t == ")") {
+                    self.pos += 1;
+                    Some(expr)
+                } else {
+                    None
+                }
+            }
+            ")" => None,
+            _ if ["AND", "OR", "WITH"]
+                .iter()
+                .any(|k| token.eq_ignore_ascii_case(k)) =>
+            {
+                None
+            }
+            _ => {
+                if self.peek_is("WITH") {
+                    // skip the exception
+                    self.pos += 2;
+                }
+                Some(LicenseExpr::Id(token))
+            }
+        }
+    }
+}
+
+impl LicenseExpr {
+    fn from_str(expression: &str) -> Option<Self> {
+        let mut parser = LicenseParser {
+            tokens: tokenize(expression),
+            pos: 0,
+        };
+        let expr = parser.parse_or()?;
+        if parser.pos == parser.tokens.len() {
+            Some(expr)
+        } else {
+            None
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+/// A policy of permitted and prohibited licenses, read from a TOML file with `allow` and `deny` arrays of SPDX identifiers. Identifiers can use glob-like wildcards, such as `GPL-*`. If `allow` is empty, all licenses not denied are permitted. If `deny-unknown` is set, packages whose license cannot be determined are not permitted.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct LicensePolicy {
+    #[serde(default)]
+    allow: Vec<String>,
+    #[serde(default)]
+    deny: Vec<String>,
+    #[serde(default)]
+    deny_unknown: bool,
+}
+
+impl LicensePolicy {
+    pub(crate) fn from_str(content: &str) -> ResultDynError<Self> {
+        let lp: LicensePolicy = toml::from_str(content)?;
+        Ok(lp)
+    }
+
+    pub(crate) fn from_file(file_path: &Path) -> ResultDynError<Self> {
+        let content = fs::read_to_string(file_path)?;
+        Self::from_str(&content)
+    }
+
+    fn is_id_permitted(&self, id: &str) -> bool {
+        !self.deny.iter().any(|p| match_str(p, id, true))
+            && (self.allow.is_empty()
+                || self.allow.iter().any(|p| match_str(p, id, true)))
+    }
+
+    fn is_expr_permitted(&self, expr: &LicenseExpr) -> bool {
+        match expr {
+            LicenseExpr::Id(id) => self.is_id_permitted(id),
+            LicenseExpr::And(parts) => parts.iter().all(|e| self.is_expr_permitted(e)),
+            // any one alternative might be selected
+            LicenseExpr::Or(parts) => parts.iter().any(|e| self.is_expr_permitted(e)),
+        }
+    }
+
+    /// Return true if the SPDX license expression is permitted. Expressions that cannot be parsed, or are not provided, are unknown.
+    pub(crate) fn is_permitted(&self, expression: Option<&str>) -> bool {
+        match expression.and_then(LicenseExpr::from_str) {
+            Some(expr) => self.is_expr_permitted(&expr),
+            None => !self.deny_unknown,
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_license_expr_a() {
+        assert_eq!(
+            LicenseExpr::from_str("MIT"),
+            Some(LicenseExpr::Id("MIT".to_string()))
+        );
+        assert_eq!(
+            LicenseExpr::from_str("MIT OR Apache-2.0 AND BSD-3-Clause"),
+            Some(LicenseExpr::Or(vec![
+                LicenseExpr::Id("MIT".to_string()),
+                LicenseExpr::And(vec![
+                    LicenseExpr::Id("Apache-2.0".to_string()),
+                    LicenseExpr::Id("BSD-3-Clause".to_string()),
+                ]),
+            ]))
+        );
+        assert_eq!(
+            LicenseExpr::from_str("(GPL-2.0-only WITH Classpath-exception-2.0 OR MIT)"),
+            Some(LicenseExpr::Or(vec![
+                LicenseExpr::Id("GPL-2.0-only".to_string()),
+                LicenseExpr::Id("MIT".to_string()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_license_expr_b() {
+        assert_eq!(LicenseExpr::from_str(""), None);
+        assert_eq!(LicenseExpr::from_str("MIT OR"), None);
+        assert_eq!(LicenseExpr::from_str("(MIT"), None);
+        assert_eq!(LicenseExpr::from_str("MIT Apache-2.0"), None);
+    }
+
+    #[test]
+    fn test_license_policy_a() {
+        let lp = LicensePolicy::from_str("deny = [\"GPL-*\", \"AGPL-*\"]\n").unwrap();
+        assert_eq!(lp.is_permitted(Some("MIT")), true);
+        assert_eq!(lp.is_permitted(Some("GPL-3.0-only")), false);
+        assert_eq!(lp.is_permitted(Some("gpl-3.0-or-later")), false);
+        assert_eq!(lp.is_permitted(Some("LGPL-3.0-only")), true);
+        // an alternative can be selected
+        assert_eq!(lp.is_permitted(Some("GPL-3.0-only OR MIT")), true);
+        assert_eq!(lp.is_permitted(Some("GPL-3.0-only AND MIT")), false);
+        assert_eq!(lp.is_permitted(None), true);
+    }
+
+    #[test]
+    fn test_license_policy_b() {
+        let lp = LicensePolicy::from_str(
+            "allow = [\"MIT\", \"BSD-*\", \"Apache-2.0\"]\ndeny-unknown = true\n",
+        )
+        .unwrap();
+        assert_eq!(lp.is_permitted(Some("BSD-3-Clause")), true);
+        assert_eq!(lp.is_permitted(Some("MPL-2.0")), false);
+        assert_eq!(
+            lp.is_permitted(Some("(MIT OR MPL-2.0) AND Apache-2.0")),
+            true
+        );
+        assert_eq!(lp.is_permitted(None), false);
+        assert_eq!(lp.is_permitted(Some("MIT OR")), false);
+    }
+}
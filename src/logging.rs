@@ -0,0 +1,63 @@
+use std::io;
+
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::EnvFilter;
+
+/// The environment variable that, if set, provides a filter directive (e.g. `fetter=debug`) that overrides the verbosity given on the command line.
+const LOG_ENV: &str = "FETTER_LOG";
+
+//------------------------------------------------------------------------------
+/// The format of log records written to stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LogFormat {
+    Text,
+    Json,
+}
+
+// Return the filter directive for a count of verbose flags; quiet overrides verbosity.
+fn to_directive(verbose: u8, quiet: bool) -> &'static str {
+    if quiet {
+        return "fetter=error";
+    }
+    match verbose {
+        0 => "fetter=info",
+        1 => "fetter=debug",
+        _ => "fetter=trace",
+    }
+}
+
+/// Install the global subscriber that writes log records and closed spans to stderr. If FETTER_LOG is set to a valid directive, it is used in place of the level given by `verbose` and `quiet`. Calling this more than once has no effect.
+pub(crate) fn init_logging(verbose: u8, quiet: bool, format: LogFormat) {
+    let filter = EnvFilter::try_from_env(LOG_ENV)
+        .unwrap_or_else(|_| EnvFilter::new(to_directive(verbose, quiet)));
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(io::stderr)
+        .with_span_events(FmtSpan::CLOSE);
+    // an Err only indicates that a subscriber is already installed
+    let _ = match format {
+        LogFormat::Text => builder.without_time().with_target(false).try_init(),
+        LogFormat::Json => builder.json().try_init(),
+    };
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_directive_a() {
+        assert_eq!(to_directive(0, false), "fetter=info");
+        assert_eq!(to_directive(1, false), "fetter=debug");
+        assert_eq!(to_directive(3, false), "fetter=trace");
+        assert_eq!(to_directive(2, true), "fetter=error");
+    }
+
+    #[test]
+    fn test_init_logging_a() {
+        init_logging(0, true, LogFormat::Text);
+        // a second call is ignored
+        init_logging(2, false, LogFormat::Json);
+    }
+}
@@ -0,0 +1,219 @@
+use std::fs;
+use std::path::Path;
+
+use crate::dep_manifest::DepManifest;
+use crate::dep_spec::DepSpec;
+use crate::package::Package;
+use crate::package_match::match_str;
+use crate::path_shared::PathShared;
+use crate::table::ColumnFormat;
+use crate::table::Rowable;
+use crate::table::RowableContext;
+use crate::table::Tableable;
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+// Given a path from a RECORD file, return the name of the top-level module it provides, if any. Paths outside the site, in metadata directories, or that are not Python modules or packages are excluded.
+fn record_path_to_module(path: &str) -> Option<String> {
+    let mut parts = path.split(['/', '\\']);
+    let first = parts.next()?;
+    let is_package = parts.next().is_some();
+    let name = if is_package {
+        first
+    } else if let Some(stem) = first.strip_suffix(".py") {
+        stem
+    } else if first.ends_with(".so") || first.ends_with(".pyd") {
+        // extension modules are named with tags, e.g. `_cffi_backend.cpython-312-x86_64-linux-gnu.so`
+        first.split('.').next()?
+    } else {
+        return None;
+    };
+    if name == "__pycache__" || !is_identifier(name) {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+/// Return the names of the top-level modules provided by an installed package, as read from the `top_level.txt` of its dist-info directory or, if not defined, derived from the paths of its RECORD.
+pub(crate) fn get_import_names(dist_info: &Path) -> Vec<String> {
+    let mut names: Vec<String> = match fs::read_to_string(dist_info.join("top_level.txt"))
+    {
+        Ok(content) if !content.trim().is_empty() => content
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| is_identifier(line))
+            .collect(),
+        _ => fs::read_to_string(dist_info.join("RECORD"))
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| line.split(',').next())
+            .filter_map(record_path_to_module)
+            .collect(),
+    };
+    names.sort();
+    names.dedup();
+    names
+}
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone)]
+pub(crate) struct MapRecord {
+    module: String,
+    package: Package,
+    dep_spec: Option<DepSpec>,
+    sites: Vec<PathShared>,
+}
+
+impl Rowable for MapRecord {
+    fn to_rows(&self, _context: &RowableContext) -> Vec<Vec<String>> {
+        vec![vec![
+            self.module.clone(),
+            self.package.to_string(),
+            self.dep_spec
+                .as_ref()
+                .map(|ds| ds.to_string())
+                .unwrap_or_default(),
+            self.sites
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        ]]
+    }
+}
+
+//------------------------------------------------------------------------------
+/// A report of the top-level modules provided by installed packages, such that the package that provides an imported module (e.g. `cv2`) can be found. If a DepManifest is provided, the requirement of each package is included.
+pub(crate) struct MapReport {
+    records: Vec<MapRecord>,
+}
+
+impl MapReport {
+    pub(crate) fn from_package_to_sites(
+        package_to_sites: &[(Package, Vec<PathShared>)],
+        module: &str,
+        case_insensitive: bool,
+        dm: Option<&DepManifest>,
+    ) -> Self {
+        let mut records = Vec::new();
+        for (package, sites) in package_to_sites {
+            let mut modules: Vec<String> = sites
+                .iter()
+                .filter_map(|site| package.to_dist_info_dir(site))
+                .flat_map(|dist_info| get_import_names(&dist_info))
+                .filter(|name| match_str(module, name, case_insensitive))
+                .collect();
+            modules.sort();
+            modules.dedup();
+            let dep_spec = dm.and_then(|dm| dm.get_dep_spec(&package.key));
+            for module in modules {
+                records.push(MapRecord {
+                    module,
+                    package: package.clone(),
+                    dep_spec: dep_spec.cloned(),
+                    sites: sites.clone(),
+                });
+            }
+        }
+        records.sort_by(|a, b| (&a.module, &a.package).cmp(&(&b.module, &b.package)));
+        MapReport { records }
+    }
+}
+
+impl Tableable<MapRecord> for MapReport {
+    fn get_header(&self) -> Vec<ColumnFormat> {
+        vec![
+            ColumnFormat::new("Module".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Package".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Requirement".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Sites".to_string(), true, "#666666".to_string()),
+        ]
+    }
+    fn get_records(&self) -> &Vec<MapRecord> {
+        &self.records
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+#[allow(clippy::useless_vec)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_record_path_to_module_a() {
+        assert_eq!(record_path_to_module("six.py"), Some("six".to_string()));
+        assert_eq!(
+            record_path_to_module("yaml/__init__.py"),
+            Some("yaml".to_string())
+        );
+        assert_eq!(
+            record_path_to_module("_cffi_backend.cpython-312-x86_64-linux-gnu.so"),
+            Some("_cffi_backend".to_string())
+        );
+        assert_eq!(record_path_to_module("PyYAML-6.0.2.dist-info/RECORD"), None);
+        assert_eq!(record_path_to_module("numpy.libs/libgfortran.so"), None);
+        assert_eq!(
+            record_path_to_module("__pycache__/six.cpython-312.pyc"),
+            None
+        );
+        assert_eq!(record_path_to_module("../../../bin/f2py"), None);
+        assert_eq!(record_path_to_module("distutils-precedence.pth"), None);
+    }
+
+    #[test]
+    fn test_map_report_a() {
+        let dir = tempdir().unwrap();
+        let site = PathShared::from_path_buf(dir.path().to_path_buf());
+        let dist_infos = [
+            ("opencv_python-4.10.0.84.dist-info", "top_level.txt", "cv2\n"),
+            ("six-1.16.0.dist-info", "RECORD", "six.py,sha256=abc,34549\n__pycache__/six.cpython-312.pyc,,\nsix-1.16.0.dist-info/RECORD,,\n"),
+            ("pyyaml-6.0.2.dist-info", "RECORD", "_yaml/__init__.py,sha256=abc,1402\nyaml/__init__.py,sha256=abc,12311\nyaml/_yaml.cpython-312-x86_64-linux-gnu.so,sha256=abc,2383664\n"),
+        ];
+        for (di, name, content) in dist_infos {
+            let fp_di = dir.path().join(di);
+            fs::create_dir(&fp_di).unwrap();
+            fs::write(fp_di.join(name), content).unwrap();
+        }
+        let package_to_sites: Vec<(Package, Vec<PathShared>)> = [
+            ("opencv-python", "4.10.0.84"),
+            ("six", "1.16.0"),
+            ("PyYAML", "6.0.2"),
+        ]
+        .iter()
+        .map(|(n, v)| {
+            (
+                Package::from_name_version_durl(n, v, None).unwrap(),
+                vec![site.clone()],
+            )
+        })
+        .collect();
+        let dm = DepManifest::from_iter(vec!["opencv-python>=4"].iter()).unwrap();
+
+        let mr =
+            MapReport::from_package_to_sites(&package_to_sites, "*", false, Some(&dm));
+        let fp = dir.path().join("report.txt");
+        mr.to_file(&fp, '|').unwrap();
+        let site = dir.path().display();
+        assert_eq!(
+            fs::read_to_string(&fp).unwrap().lines().collect::<Vec<_>>(),
+            vec![
+                "Module|Package|Requirement|Sites".to_string(),
+                format!("_yaml|PyYAML-6.0.2||{}", site),
+                format!("cv2|opencv-python-4.10.0.84|opencv-python>=4|{}", site),
+                format!("six|six-1.16.0||{}", site),
+                format!("yaml|PyYAML-6.0.2||{}", site),
+            ]
+        );
+
+        let mr = MapReport::from_package_to_sites(&package_to_sites, "CV2", true, None);
+        assert_eq!(mr.records.len(), 1);
+        assert_eq!(mr.records[0].package.to_string(), "opencv-python-4.10.0.84");
+    }
+}
@@ -7,7 +7,10 @@ use std::fmt;
 // use std::ops::Deref;
 // use ureq;
 
+use crate::package::Package;
 use crate::ureq_client::UreqClient;
+use crate::util::name_to_key;
+use crate::version_spec::VersionSpec;
 
 //------------------------------------------------------------------------------
 #[derive(Debug, Deserialize)]
@@ -103,6 +106,31 @@ impl fmt::Display for OSVSeverities {
     }
 }
 
+//------------------------------------------------------------------------------
+#[derive(Debug, Deserialize)]
+struct OSVAffectedPackage {
+    name: String,
+    ecosystem: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OSVRangeEvent {
+    fixed: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OSVRange {
+    r#type: String,
+    events: Vec<OSVRangeEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OSVAffected {
+    package: OSVAffectedPackage,
+    #[serde(default)]
+    ranges: Vec<OSVRange>,
+}
+
 //------------------------------------------------------------------------------
 #[derive(Debug, Deserialize)]
 pub(crate) struct OSVVulnInfo {
@@ -111,13 +139,32 @@ pub(crate) struct OSVVulnInfo {
     pub(crate) references: OSVReferences,
     pub(crate) severity: Option<OSVSeverities>,
     // details: String,
-    // affected: Vec<OSVAffected>,
+    #[serde(default)]
+    affected: Vec<OSVAffected>,
 }
 
 impl OSVVulnInfo {
     pub(crate) fn get_url(&self) -> String {
         format!("https://osv.dev/vulnerability/{}", self.id)
     }
+
+    /// Return the lowest version, greater than that of the provided Package, in which this vulnerability is fixed, if defined.
+    pub(crate) fn get_fixed(&self, package: &Package) -> Option<String> {
+        self.affected
+            .iter()
+            .filter(|a| {
+                a.package.ecosystem == "PyPI"
+                    && name_to_key(&a.package.name) == package.key
+            })
+            .flat_map(|a| a.ranges.iter())
+            .filter(|r| r.r#type == "ECOSYSTEM")
+            .flat_map(|r| r.events.iter())
+            .filter_map(|e| e.fixed.as_ref())
+            .map(|v| VersionSpec::new(v))
+            .filter(|v| *v > package.version)
+            .min()
+            .map(|v| v.to_string())
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -204,5 +251,11 @@ mod tests {
             vuln.severity.as_ref().unwrap().get_prime(),
             "CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:U/C:N/I:N/A:L"
         );
+        let p1 = Package::from_name_version_durl("gradio", "4.0.0", None).unwrap();
+        assert_eq!(vuln.get_fixed(&p1), Some("4.19.2".to_string()));
+        let p2 = Package::from_name_version_durl("gradio", "4.20.0", None).unwrap();
+        assert_eq!(vuln.get_fixed(&p2), None);
+        let p3 = Package::from_name_version_durl("numpy", "1.0.0", None).unwrap();
+        assert_eq!(vuln.get_fixed(&p3), None);
     }
 }
@@ -0,0 +1,156 @@
+use std::path::Path;
+use std::time::Duration;
+
+use crate::package::Package;
+use crate::pypi_json::query_pypi_projects;
+use crate::table::ColumnFormat;
+use crate::table::Rowable;
+use crate::table::RowableContext;
+use crate::table::Tableable;
+use crate::ureq_client::UreqClient;
+use crate::util::iso8601_to_days;
+use crate::version_spec::VersionSpec;
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone)]
+pub(crate) struct OutdatedRecord {
+    package: Package,
+    latest: VersionSpec,
+    behind: usize,
+    released: Option<String>,
+    latest_released: Option<String>,
+}
+
+impl OutdatedRecord {
+    // Return the number of days between the release of the installed version and the latest version.
+    fn get_days(&self) -> Option<i64> {
+        let released = iso8601_to_days(self.released.as_ref()?)?;
+        let latest_released = iso8601_to_days(self.latest_released.as_ref()?)?;
+        Some(latest_released - released)
+    }
+}
+
+fn to_date(value: &Option<String>) -> String {
+    value
+        .as_ref()
+        .and_then(|v| v.get(..10))
+        .unwrap_or_default()
+        .to_string()
+}
+
+impl Rowable for OutdatedRecord {
+    fn to_rows(&self, _context: &RowableContext) -> Vec<Vec<String>> {
+        vec![vec![
+            self.package.to_string(),
+            self.latest.to_string(),
+            self.behind.to_string(),
+            to_date(&self.released),
+            to_date(&self.latest_released),
+            self.get_days().map(|d| d.to_string()).unwrap_or_default(),
+        ]]
+    }
+}
+
+//------------------------------------------------------------------------------
+/// A report of installed packages that are older than the latest release on PyPI, with the number of newer releases and the days between the release of the installed version and the latest version.
+#[derive(Debug)]
+pub(crate) struct OutdatedReport {
+    records: Vec<OutdatedRecord>,
+}
+
+impl OutdatedReport {
+    pub(crate) fn from_packages<U: UreqClient + std::marker::Sync>(
+        client: &U,
+        packages: &[Package],
+        cache_dir: Option<&Path>,
+        cache_dur: Duration,
+    ) -> Self {
+        let mut keys: Vec<String> = packages.iter().map(|p| p.key.clone()).collect();
+        keys.sort();
+        keys.dedup();
+        let projects = query_pypi_projects(client, &keys, cache_dir, cache_dur);
+
+        let mut records = Vec::new();
+        for package in packages.iter() {
+            if let Some(project) = projects.get(&package.key) {
+                let latest = project.get_latest();
+                if latest <= package.version {
+                    continue;
+                }
+                let behind = project
+                    .get_releases()
+                    .iter()
+                    .filter(|v| **v > package.version && **v <= latest)
+                    .count();
+                records.push(OutdatedRecord {
+                    package: package.clone(),
+                    released: project.get_upload_time(&package.version),
+                    latest_released: project.get_upload_time(&latest),
+                    latest,
+                    behind,
+                });
+            }
+        }
+        OutdatedReport { records }
+    }
+}
+
+impl Tableable<OutdatedRecord> for OutdatedReport {
+    fn get_header(&self) -> Vec<ColumnFormat> {
+        vec![
+            ColumnFormat::new("Package".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Latest".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Behind".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Released".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new(
+                "Latest Released".to_string(),
+                false,
+                "#666666".to_string(),
+            ),
+            ColumnFormat::new("Days".to_string(), false, "#666666".to_string()),
+        ]
+    }
+    fn get_records(&self) -> &Vec<OutdatedRecord> {
+        &self.records
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ureq_client::UreqClientMock;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_outdated_report_a() {
+        let content = r#"{"info":{"name":"requests","version":"2.32.3"},"releases":{"2.31.0":[{"upload_time":"2023-05-22T15:12:42","yanked":false}],"2.32.0":[{"upload_time":"2024-05-20T15:59:30","yanked":true}],"2.32.2":[{"upload_time":"2024-05-21T18:51:41","yanked":false}],"2.32.3":[{"upload_time":"2024-05-29T15:37:47","yanked":false}]}}"#;
+        let client = UreqClientMock {
+            mock_get: Some(content.to_string()),
+            mock_post: None,
+        };
+        let packages = vec![
+            Package::from_name_version_durl("requests", "2.31.0", None).unwrap(),
+            Package::from_name_version_durl("requests", "2.32.3", None).unwrap(),
+        ];
+        let or = OutdatedReport::from_packages(&client, &packages, None, Duration::ZERO);
+        assert_eq!(or.records.len(), 1);
+
+        let dir = tempdir().unwrap();
+        let fp = dir.path().join("report.txt");
+        or.to_file(&fp, ',').unwrap();
+        let lines: Vec<String> = fs::read_to_string(&fp)
+            .unwrap()
+            .lines()
+            .map(|l| l.to_string())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Package,Latest,Behind,Released,Latest Released,Days",
+                "requests-2.31.0,2.32.3,2,2023-05-22,2024-05-29,373",
+            ]
+        );
+    }
+}
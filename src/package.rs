@@ -6,10 +6,13 @@ use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
+use crate::conda_meta::is_conda_meta;
 use crate::package_durl::DirectURL;
 use crate::path_shared::PathShared;
 use crate::util::name_to_key;
+use crate::util::percent_encode;
 use crate::version_spec::VersionSpec;
+use crate::zip_index::ZipIndex;
 
 //------------------------------------------------------------------------------
 // Given a name from the dist-info dir, try to find the src dir in the site dir doing a case-insensitive search. Then, return the case-sensitve name of the src dir. Note that some packages might only have source file ending in .py; we will not find it but it will be defined in RECORD.
@@ -41,10 +44,144 @@ fn extract_from_dist_info(file_name: &str) -> Option<(String, String)> {
     }
 }
 
+// Given the name of a legacy egg-info directory or file, or of an egg, get the name and the version. These names might include a Python version and platform after the version, as in `six-1.16.0-py3.12.egg-info`, and might not include a version at all.
+fn extract_from_egg(file_name: &str) -> Option<(String, String)> {
+    let stem = file_name
+        .strip_suffix(".egg-info")
+        .or_else(|| file_name.strip_suffix(".egg"))?;
+    let mut parts = stem.split('-');
+    match (parts.next(), parts.next()) {
+        (Some(name), Some(version)) if !name.is_empty() && !version.is_empty() => {
+            Some((name.to_string(), version.to_string()))
+        }
+        _ => None,
+    }
+}
+
+// Suffixes added by Debian and Ubuntu to the versions of the Python packages they distribute.
+const DEBIAN_TAGS: [&str; 4] = ["ubuntu", "debian", "build", "deb"];
+
+// Debian and Ubuntu patch the versions of packages installed with apt (e.g. `python3-debian`) with suffixes that are not valid PEP 440, as in `0.1.43ubuntu1` or `1.2.3-1build1`. Given such a version, make the suffix a local label (e.g. `0.1.43+ubuntu1`), such that the public version can be compared. Other versions are returned unchanged.
+fn debian_version_to_local(version: &str) -> String {
+    if version.contains('+') {
+        return version.to_string();
+    }
+    let pos = match version.find(|c: char| !(c.is_ascii_digit() || c == '.')) {
+        Some(pos) => pos,
+        None => return version.to_string(),
+    };
+    let (release, suffix) = version.split_at(pos);
+    let suffix = suffix.trim_start_matches(['-', '_', '.', '~']);
+    let suffix_lower = suffix.to_lowercase();
+    if release.is_empty()
+        || release.ends_with('.')
+        || !DEBIAN_TAGS.iter().any(|tag| suffix_lower.contains(tag))
+    {
+        return version.to_string();
+    }
+    let local: String = suffix
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '.' })
+        .collect();
+    format!("{}+{}", release, local)
+}
+
+// Given the contents of a PKG-INFO or METADATA file, parse the name and version headers.
+fn parse_name_version(content: &str) -> Option<(String, String)> {
+    let mut name = None;
+    let mut version = None;
+    for line in content.lines() {
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some(v) = line.strip_prefix("Name:") {
+            name = Some(v.trim().to_string());
+        } else if let Some(v) = line.strip_prefix("Version:") {
+            version = Some(v.trim().to_string());
+        }
+    }
+    Some((name?, version?))
+}
+
+// Given a PKG-INFO or METADATA file, read the name and version headers.
+fn read_name_version(file_path: &Path) -> Option<(String, String)> {
+    parse_name_version(&fs::read_to_string(file_path).ok()?)
+}
+
+// Given the index of a zip archive and the name of an entry that is the metadata file of a dist-info, egg-info, or EGG-INFO directory, return the name and version, taken from the directory name if possible and otherwise read from the entry.
+fn zip_entry_to_name_version(zi: &ZipIndex, entry: &str) -> Option<(String, String)> {
+    let mut parts = entry.rsplit('/');
+    let file_name = parts.next()?;
+    let dir_name = parts.next()?;
+    let nv = if dir_name.ends_with(".dist-info") && file_name == "METADATA" {
+        extract_from_dist_info(dir_name)
+    } else if dir_name.ends_with(".egg-info") && file_name == "PKG-INFO" {
+        extract_from_egg(dir_name)
+    } else if dir_name == "EGG-INFO" && file_name == "PKG-INFO" {
+        None
+    } else {
+        return None;
+    };
+    nv.or_else(|| parse_name_version(&zi.read_to_string(entry).ok()?))
+}
+
+/// Given a zip archive, such as a zipapp (`.pyz`) or a zipped egg, return the Packages defined by dist-info, egg-info, or EGG-INFO directories found anywhere in the archive, in the order of their entries. If the archive cannot be read, no Packages are returned.
+pub(crate) fn zip_to_packages(file_path: &Path) -> Vec<Package> {
+    let zi = match ZipIndex::from_path(file_path) {
+        Ok(zi) => zi,
+        Err(_) => return Vec::with_capacity(0),
+    };
+    let mut packages: Vec<Package> = Vec::new();
+    for entry in zi.get_names() {
+        if let Some((name, version)) = zip_entry_to_name_version(&zi, entry) {
+            if let Some(package) = Package::from_name_version_durl(&name, &version, None)
+            {
+                if !packages.contains(&package) {
+                    packages.push(package);
+                }
+            }
+        }
+    }
+    packages
+}
+
+// Given the contents of a setuptools editable finder module, return the first source path in its MAPPING.
+fn editable_finder_to_src(content: &str) -> Option<PathBuf> {
+    let mapping = &content[content.find("MAPPING")?..];
+    let mapping = &mapping[mapping.find('{')? + 1..mapping.find('}')?];
+    // the values of quoted keys and values alternate
+    let value = mapping
+        .split(['\'', '"'])
+        .enumerate()
+        .filter(|(i, _)| i % 2 == 1)
+        .map(|(_, s)| s)
+        .nth(1)?;
+    // the mapping is to the package directory; the source is its parent
+    Path::new(value).parent().map(|p| p.to_path_buf())
+}
+
+// Given a site and the name and version from a dist-info, find a setuptools `__editable__` pth file written for a PEP 660 editable install and return the source directory it refers to. A pth file either lists the source directory or imports a finder module that maps packages to their source.
+fn find_editable_src(site: &Path, name: &str, version: &str) -> Option<PathBuf> {
+    let fp_pth = [name.to_string(), name_to_key(name)]
+        .iter()
+        .map(|n| site.join(format!("__editable__.{}-{}.pth", n, version)))
+        .find(|fp| fp.is_file())?;
+    let content = fs::read_to_string(fp_pth).ok()?;
+    let line = content.lines().find(|l| !l.trim().is_empty())?.trim();
+    match line.strip_prefix("import ") {
+        Some(imports) => {
+            let module = imports.split([';', ' ']).next()?;
+            let finder = fs::read_to_string(site.join(format!("{}.py", module))).ok()?;
+            editable_finder_to_src(&finder)
+        }
+        None => Some(PathBuf::from(line)),
+    }
+}
+
 //------------------------------------------------------------------------------
-// A Package is package artifact, representing a specific version installed on a file system. This differs from a DepSpec, which might refer to a range of acceptable versions without a specific artifact.
+/// A Package is package artifact, representing a specific version installed on a file system. This differs from a DepSpec, which might refer to a range of acceptable versions without a specific artifact.
 #[derive(PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
-pub(crate) struct Package {
+pub struct Package {
     pub(crate) name: String,
     pub(crate) key: String,
     pub(crate) version: VersionSpec,
@@ -65,7 +202,6 @@ impl Package {
         })
     }
     /// Create a Package from a dist-info string. As the name of the package / source dir may be different than the dist-info representation, optionall provide a `name`
-    #[allow(dead_code)]
     pub(crate) fn from_dist_info(
         file_name: &str,
         name: Option<&str>,
@@ -81,13 +217,66 @@ impl Package {
         }
         None
     }
+
+    /// Create a Package from a legacy egg-info directory or file, or from an egg directory or zipped egg file. The name and version are taken from the file name if possible; otherwise, the PKG-INFO file is read, from within the archive if a zipped egg.
+    pub(crate) fn from_egg(file_path: &Path) -> Option<Self> {
+        let file_name = file_path.file_name().and_then(|name| name.to_str())?;
+        let (name_from_egg, version) = match extract_from_egg(file_name) {
+            Some(nv) => nv,
+            None if file_name.ends_with(".egg") && file_path.is_file() => {
+                let zi = ZipIndex::from_path(file_path).ok()?;
+                parse_name_version(&zi.read_to_string("EGG-INFO/PKG-INFO").ok()?)?
+            }
+            None => {
+                let fp_pkg_info = if file_name.ends_with(".egg") {
+                    file_path.join("EGG-INFO").join("PKG-INFO")
+                } else if file_path.is_dir() {
+                    file_path.join("PKG-INFO")
+                } else {
+                    file_path.to_path_buf() // a distutils egg-info file is the PKG-INFO
+                };
+                read_name_version(&fp_pkg_info)?
+            }
+        };
+        let dir_site = file_path.parent()?.to_path_buf();
+        let name = match find_dir_src(&dir_site, &name_from_egg) {
+            Some(name) => name,
+            None => name_from_egg,
+        };
+        Self::from_name_version_durl(&name, &debian_version_to_local(&version), None)
+    }
+
+    /// Create a Package from a legacy `.egg-link` file, as written by `setup.py develop`. The file lists the source directory, which contains the egg-info directory that defines the name and version.
+    pub(crate) fn from_egg_link(file_path: &Path) -> Option<Self> {
+        let key = name_to_key(file_path.file_stem()?.to_str()?);
+        let content = fs::read_to_string(file_path).ok()?;
+        let dir_src = PathBuf::from(content.lines().next()?.trim());
+        for entry in fs::read_dir(&dir_src).ok()?.flatten() {
+            let path = entry.path();
+            let stem = match path.file_name().and_then(|n| n.to_str()) {
+                Some(name) => match name.strip_suffix(".egg-info") {
+                    Some(stem) => stem,
+                    None => continue,
+                },
+                None => continue,
+            };
+            if name_to_key(stem) != key {
+                continue;
+            }
+            let (name, version) = read_name_version(&path.join("PKG-INFO"))?;
+            let durl = DirectURL::from_editable_path(&dir_src);
+            return Self::from_name_version_durl(&name, &version, Some(durl));
+        }
+        None
+    }
+
     /// Create a Package from a dist_info file path. This is the main constructor for live usage.
     pub(crate) fn from_file_path(file_path: &Path) -> Option<Self> {
         let file_name = file_path.file_name().and_then(|name| name.to_str())?;
 
         if file_name.ends_with(".dist-info") && file_path.is_dir() {
             let fp_durl = file_path.join("direct_url.json");
-            let durl = if fp_durl.is_file() {
+            let mut durl = if fp_durl.is_file() {
                 DirectURL::from_file(&fp_durl).ok()
             } else {
                 None
@@ -96,20 +285,96 @@ impl Package {
             let dir_site = file_path.parent()?.to_path_buf(); // TODO: propagate package errors
 
             if let Some((name_from_di, version)) = extract_from_dist_info(file_name) {
+                // some editable installs do not write a direct_url.json
+                if durl.is_none() {
+                    durl = find_editable_src(&dir_site, &name_from_di, &version)
+                        .map(|src| DirectURL::from_editable_path(&src));
+                }
                 let name = match find_dir_src(&dir_site, &name_from_di) {
                     Some(name) => name,
                     None => name_from_di,
                 };
                 return Self::from_name_version_durl(&name, &version, durl);
             }
+        } else if file_name.ends_with(".egg-link") && file_path.is_file() {
+            return Self::from_egg_link(file_path);
+        } else if file_name.ends_with(".egg-info") || file_name.ends_with(".egg") {
+            return Self::from_egg(file_path);
         }
         None
     }
 
+    /// Return the name of this Package, as declared in its metadata.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return the normalized name of this Package, used for comparison with requirements.
+    pub fn get_key(&self) -> &str {
+        &self.key
+    }
+
+    /// Return the installed version of this Package.
+    pub fn get_version(&self) -> &VersionSpec {
+        &self.version
+    }
+
+    /// Return true if this Package was installed in editable mode.
+    pub fn is_editable(&self) -> bool {
+        self.direct_url
+            .as_ref()
+            .is_some_and(|durl| durl.is_editable())
+    }
+
+    /// If this Package was installed in editable mode, return the source directory.
+    pub(crate) fn to_editable_src(&self) -> Option<PathBuf> {
+        match &self.direct_url {
+            Some(durl) if durl.is_editable() => durl.to_dir_path(),
+            _ => None,
+        }
+    }
+
+    /// Return a copy of this Package without the local label of its version (e.g. `+cu118`), as used to ignore local labels when validating.
+    pub(crate) fn to_public(&self) -> Self {
+        Package {
+            version: self.version.to_public(),
+            ..self.clone()
+        }
+    }
+
+    /// Return a copy of this Package with user info, such as an embedded token, removed from its direct URL, as suitable for output.
+    pub(crate) fn to_redacted(&self) -> Self {
+        Package {
+            direct_url: self.direct_url.as_ref().map(|durl| durl.to_redacted()),
+            ..self.clone()
+        }
+    }
+
+    /// Return a Package URL (PURL) for this Package. Following the PyPI PURL type, the name is lower-cased with underscores replaced by hyphens. If installed from a VCS, the pinned VCS URL is provided as a `vcs_url` qualifier.
+    pub(crate) fn to_purl(&self) -> String {
+        let purl = format!(
+            "pkg:pypi/{}@{}",
+            self.name.to_lowercase().replace('_', "-"),
+            percent_encode(&self.version.to_string())
+        );
+        match self.direct_url.as_ref().and_then(|durl| durl.to_vcs_url()) {
+            Some(vcs_url) => format!("{}?vcs_url={}", purl, percent_encode(&vcs_url)),
+            None => purl,
+        }
+    }
+
     /// Given a site directory, return a `PathBuf` to this Package's dist info directory.
     pub(crate) fn to_dist_info_dir(&self, site: &PathShared) -> Option<PathBuf> {
-        // dist-info files will always be written in normalized key style
+        // dist-info files are generally written in normalized key style, but older installers might preserve case
         let fp = site.join(&format!("{}-{}.dist-info", self.key, self.version));
+        if fp.exists() {
+            return Some(fp);
+        }
+        let fp = site.join(&format!(
+            "{}-{}.dist-info",
+            self.name.replace('-', "_"),
+            self.version
+        ));
         if fp.exists() {
             Some(fp)
         } else {
@@ -117,6 +382,21 @@ impl Package {
         }
     }
 
+    /// Given a site directory, return the name of the tool that installed this Package, as recorded in the INSTALLER file of its dist-info directory. Packages found in conda-meta are installed by conda.
+    pub(crate) fn to_installer(&self, site: &PathShared) -> Option<String> {
+        if is_conda_meta(site.as_path()) {
+            return Some("conda".to_string());
+        }
+        let fp = self.to_dist_info_dir(site)?.join("INSTALLER");
+        let content = fs::read_to_string(fp).ok()?;
+        let installer = content.trim();
+        if installer.is_empty() {
+            None
+        } else {
+            Some(installer.to_string())
+        }
+    }
+
     /// Given a site directory, return a `PathBuf` to this Package's src directory. This assumes that the name, if case sensitive, was observed as with case.
     pub(crate) fn to_src_dir(&self, site: &PathShared) -> Option<PathBuf> {
         let fp = site.join(&self.name);
@@ -128,13 +408,13 @@ impl Package {
     }
 }
 
-// A case insensitive ordering.
+// An ordering by normalized key, such that names that differ only in case or separators are adjacent; the name breaks ties.
 impl Ord for Package {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.name
-            .to_lowercase()
-            .cmp(&other.name.to_lowercase())
+        self.key
+            .cmp(&other.key)
             .then_with(|| self.version.cmp(&other.version))
+            .then_with(|| self.name.cmp(&other.name))
     }
 }
 impl PartialOrd for Package {
@@ -154,9 +434,13 @@ impl fmt::Debug for Package {
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
 
     use super::*;
+    use crate::dep_spec::DepSpec;
+    use crate::zip_index::write_zip_stored;
+    use tempfile::tempdir;
 
     #[test]
     fn test_package_a() {
@@ -187,6 +471,26 @@ mod tests {
         assert_eq!(p1 == p3, false);
         assert_eq!(p2 == p3, true);
     }
+
+    #[test]
+    fn test_package_d() {
+        let p1 =
+            Package::from_name_version_durl("Flask_SQLAlchemy", "3.1.1", None).unwrap();
+        let p2 =
+            Package::from_name_version_durl("flask.sqlalchemy", "3.1.1", None).unwrap();
+        let p3 =
+            Package::from_name_version_durl("flask-sqlalchemy", "3.0.0", None).unwrap();
+        let p4 = Package::from_name_version_durl("flask2", "1.0", None).unwrap();
+        assert_eq!(p1.key, p2.key);
+        assert_eq!(p1.key, p3.key);
+        let mut packages = vec![p4.clone(), p1.clone(), p2.clone(), p3.clone()];
+        packages.sort();
+        assert_eq!(packages, vec![p4, p3, p1, p2]);
+
+        let ds = DepSpec::from_string("flask.SQLAlchemy>=3.1").unwrap();
+        assert!(ds.validate_package(&packages[2]));
+        assert!(ds.validate_package(&packages[3]));
+    }
     #[test]
     fn test_package_to_string_a() {
         let p1 =
@@ -225,4 +529,225 @@ mod tests {
         let p2: Package = serde_json::from_str(&json).unwrap();
         assert_eq!(p2.to_string(), "dill-0.3.8");
     }
+    //--------------------------------------------------------------------------
+    #[test]
+    fn test_package_to_purl_a() {
+        let p1 =
+            Package::from_name_version_durl("Zope_Interface", "7.1.0", None).unwrap();
+        assert_eq!(p1.to_purl(), "pkg:pypi/zope-interface@7.1.0");
+    }
+    #[test]
+    fn test_package_to_purl_b() {
+        let durl = DirectURL::from_url_vcs_cid(
+            "https://github.com/uqfoundation/dill.git".to_string(),
+            Some("git".to_string()),
+            Some("a0a8e86976708d0436eec5c8f7d25329da727cb5".to_string()),
+        )
+        .unwrap();
+        let p1 = Package::from_name_version_durl("dill", "0.3.8", Some(durl)).unwrap();
+        assert_eq!(p1.to_purl(), "pkg:pypi/dill@0.3.8?vcs_url=git%2Bhttps://github.com/uqfoundation/dill.git%40a0a8e86976708d0436eec5c8f7d25329da727cb5");
+    }
+
+    #[test]
+    fn test_package_editable_a() {
+        let dir = tempdir().unwrap();
+        let site = dir.path().join("site-packages");
+        fs::create_dir(&site).unwrap();
+        let fp_di = site.join("my_pkg-0.1.0.dist-info");
+        fs::create_dir(&fp_di).unwrap();
+        fs::write(
+            site.join("__editable__.my_pkg-0.1.0.pth"),
+            "/home/user/src/my-pkg/src\n",
+        )
+        .unwrap();
+
+        let p1 = Package::from_file_path(&fp_di).unwrap();
+        assert!(p1.is_editable());
+        assert_eq!(
+            p1.to_editable_src().unwrap(),
+            PathBuf::from("/home/user/src/my-pkg/src")
+        );
+    }
+
+    #[test]
+    fn test_package_editable_b() {
+        let dir = tempdir().unwrap();
+        let site = dir.path().to_path_buf();
+        let fp_di = site.join("my_pkg-0.1.0.dist-info");
+        fs::create_dir(&fp_di).unwrap();
+        fs::write(
+            site.join("__editable__.my_pkg-0.1.0.pth"),
+            "import __editable___my_pkg_0_1_0_finder; __editable___my_pkg_0_1_0_finder.install()\n",
+        )
+        .unwrap();
+        fs::write(
+            site.join("__editable___my_pkg_0_1_0_finder.py"),
+            "import sys\nMAPPING: dict[str, str] = {'my_pkg': '/home/user/src/my-pkg/src/my_pkg'}\nNAMESPACES = {}\n",
+        )
+        .unwrap();
+
+        let p1 = Package::from_file_path(&fp_di).unwrap();
+        assert_eq!(
+            p1.to_editable_src().unwrap(),
+            PathBuf::from("/home/user/src/my-pkg/src")
+        );
+
+        let fp_di2 = site.join("six-1.16.0.dist-info");
+        fs::create_dir(&fp_di2).unwrap();
+        let p2 = Package::from_file_path(&fp_di2).unwrap();
+        assert!(!p2.is_editable());
+        assert_eq!(p2.to_editable_src(), None);
+    }
+
+    #[test]
+    fn test_package_egg_link_a() {
+        let dir = tempdir().unwrap();
+        let src = dir.path().join("src");
+        let fp_ei = src.join("My_Pkg.egg-info");
+        fs::create_dir_all(&fp_ei).unwrap();
+        fs::write(
+            fp_ei.join("PKG-INFO"),
+            "Metadata-Version: 2.1\nName: My-Pkg\nVersion: 0.3.0\n",
+        )
+        .unwrap();
+        let site = dir.path().join("site-packages");
+        fs::create_dir(&site).unwrap();
+        let fp_el = site.join("My-Pkg.egg-link");
+        fs::write(&fp_el, format!("{}\n.", src.display())).unwrap();
+
+        let p1 = Package::from_file_path(&fp_el).unwrap();
+        assert_eq!(p1.to_string(), "My-Pkg-0.3.0");
+        assert!(p1.is_editable());
+        assert_eq!(p1.to_editable_src().unwrap(), src);
+    }
+
+    #[test]
+    fn test_package_egg_a() {
+        let dir = tempdir().unwrap();
+        let site = dir.path().to_path_buf();
+
+        // a setuptools egg-info directory
+        let fp_ei = site.join("six-1.16.0-py3.12.egg-info");
+        fs::create_dir(&fp_ei).unwrap();
+        let p1 = Package::from_file_path(&fp_ei).unwrap();
+        assert_eq!(p1.to_string(), "six-1.16.0");
+
+        // a distutils egg-info file
+        let fp_ei = site.join("python_apt-2.4.0.egg-info");
+        fs::write(&fp_ei, "Metadata-Version: 1.1\nName: python-apt\n").unwrap();
+        let p2 = Package::from_file_path(&fp_ei).unwrap();
+        assert_eq!(p2.to_string(), "python_apt-2.4.0");
+
+        // a zipped egg
+        let fp_egg = site.join("requests-2.32.3-py3.12.egg");
+        fs::write(&fp_egg, "").unwrap();
+        let p3 = Package::from_file_path(&fp_egg).unwrap();
+        assert_eq!(p3.to_string(), "requests-2.32.3");
+        assert!(!p3.is_editable());
+    }
+
+    #[test]
+    fn test_package_egg_b() {
+        let dir = tempdir().unwrap();
+        let site = dir.path().to_path_buf();
+
+        // without a version, PKG-INFO is read
+        let fp_ei = site.join("Foo.egg-info");
+        fs::create_dir(&fp_ei).unwrap();
+        fs::write(
+            fp_ei.join("PKG-INFO"),
+            "Metadata-Version: 2.1\nName: Foo\nVersion: 1.0\n\nName: ignored\n",
+        )
+        .unwrap();
+        let p1 = Package::from_file_path(&fp_ei).unwrap();
+        assert_eq!(p1.to_string(), "Foo-1.0");
+
+        let fp_egg = site.join("bar.egg");
+        fs::create_dir_all(fp_egg.join("EGG-INFO")).unwrap();
+        fs::write(
+            fp_egg.join("EGG-INFO").join("PKG-INFO"),
+            "Name: bar\nVersion: 0.2\n",
+        )
+        .unwrap();
+        let p2 = Package::from_file_path(&fp_egg).unwrap();
+        assert_eq!(p2.to_string(), "bar-0.2");
+
+        let fp_egg = site.join("baz.egg");
+        fs::write(&fp_egg, "").unwrap();
+        assert!(Package::from_file_path(&fp_egg).is_none());
+
+        // a zipped egg without a version reads PKG-INFO from the archive
+        let fp_egg = site.join("qux.egg");
+        write_zip_stored(
+            &fp_egg,
+            b"",
+            &[("EGG-INFO/PKG-INFO", "Name: qux\nVersion: 3.1\n")],
+        )
+        .unwrap();
+        let p3 = Package::from_file_path(&fp_egg).unwrap();
+        assert_eq!(p3.to_string(), "qux-3.1");
+    }
+
+    #[test]
+    fn test_debian_version_to_local_a() {
+        assert_eq!(debian_version_to_local("0.1.43ubuntu1"), "0.1.43+ubuntu1");
+        assert_eq!(debian_version_to_local("1.2.3-1build1"), "1.2.3+1build1");
+        assert_eq!(debian_version_to_local("2.0.1~deb12u1"), "2.0.1+deb12u1");
+        assert_eq!(debian_version_to_local("2.4.0+ubuntu4"), "2.4.0+ubuntu4");
+        assert_eq!(debian_version_to_local("1.16.0"), "1.16.0");
+        assert_eq!(debian_version_to_local("1.0rc1"), "1.0rc1");
+        assert_eq!(debian_version_to_local("ubuntu1"), "ubuntu1");
+    }
+
+    #[test]
+    fn test_package_egg_debian_a() {
+        let dir = tempdir().unwrap();
+        let site = dir.path().join("usr/lib/python3/dist-packages");
+        fs::create_dir_all(&site).unwrap();
+
+        // distutils egg-info files, as installed by apt
+        let fp_ei = site.join("python_debian-0.1.43ubuntu1.egg-info");
+        fs::write(&fp_ei, "Metadata-Version: 1.1\nName: python-debian\n").unwrap();
+        let p1 = Package::from_file_path(&fp_ei).unwrap();
+        assert_eq!(p1.to_string(), "python_debian-0.1.43+ubuntu1");
+        assert_eq!(p1.to_public().to_string(), "python_debian-0.1.43");
+
+        let fp_ei = site.join("PyGObject-3.42.1.egg-info");
+        fs::create_dir(&fp_ei).unwrap();
+        let p2 = Package::from_file_path(&fp_ei).unwrap();
+        assert_eq!(p2.to_string(), "PyGObject-3.42.1");
+    }
+
+    #[test]
+    fn test_zip_to_packages_a() {
+        let dir = tempdir().unwrap();
+        let fp = dir.path().join("app.pyz");
+        write_zip_stored(
+            &fp,
+            b"#!/usr/bin/env python3\n",
+            &[
+                ("__main__.py", "import six\n"),
+                (
+                    "six-1.16.0.dist-info/METADATA",
+                    "Name: six\nVersion: 1.16.0\n",
+                ),
+                ("six-1.16.0.dist-info/RECORD", ""),
+                (
+                    ".deps/attrs-24.2.0-py3-none-any.whl/attrs-24.2.0.dist-info/METADATA",
+                    "Name: attrs\nVersion: 24.2.0\n",
+                ),
+                ("Foo.egg-info/PKG-INFO", "Name: Foo\nVersion: 1.0\n"),
+                ("bar-0.2.egg-info/PKG-INFO", "Name: bar\nVersion: 0.2\n"),
+                ("six.py", ""),
+            ],
+        )
+        .unwrap();
+        let packages: Vec<String> =
+            zip_to_packages(&fp).iter().map(|p| p.to_string()).collect();
+        assert_eq!(
+            packages,
+            vec!["six-1.16.0", "attrs-24.2.0", "Foo-1.0", "bar-0.2"]
+        );
+        assert!(zip_to_packages(&dir.path().join("missing.pyz")).is_empty());
+    }
 }
@@ -60,6 +60,36 @@ pub(crate) fn match_str(pattern: &str, input: &str, case_insensitive: bool) -> b
     i_chars.next().is_none()
 }
 
+/// Glob-like patterns of packages to include and exclude, matched case-insensitively against package names, or names and versions (e.g. `numpy-2.*`). If no include patterns are provided, all packages not excluded are included.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PackageFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl PackageFilter {
+    pub(crate) fn new(include: &[String], exclude: &[String]) -> Self {
+        PackageFilter {
+            include: include.to_vec(),
+            exclude: exclude.to_vec(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Return true if any of the candidates, such as the name and the name and version of a package, are included and none are excluded.
+    pub(crate) fn is_match(&self, candidates: &[&str]) -> bool {
+        let matched = |patterns: &Vec<String>| {
+            patterns
+                .iter()
+                .any(|p| candidates.iter().any(|c| match_str(p, c, true)))
+        };
+        (self.include.is_empty() || matched(&self.include)) && !matched(&self.exclude)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -139,4 +169,21 @@ mod tests {
         assert!(!match_str("-_-_??*.png", "____o.png", true));
         assert!(!match_str("-_-_??.png", "____ooo.png", true));
     }
+
+    #[test]
+    fn test_package_filter_a() {
+        let pf = PackageFilter::new(&["numpy*".to_string()], &["internal-*".to_string()]);
+        assert!(pf.is_match(&["numpy", "numpy-2.1.2"]));
+        assert!(pf.is_match(&["NumPy"]));
+        assert!(!pf.is_match(&["requests", "requests-2.32.3"]));
+
+        let pf =
+            PackageFilter::new(&[], &["internal-*".to_string(), "pip-24.*".to_string()]);
+        assert!(pf.is_match(&["requests", "requests-2.32.3"]));
+        assert!(!pf.is_match(&["internal_tools", "internal_tools-1.0"]));
+        assert!(!pf.is_match(&["pip", "pip-24.2"]));
+        assert!(pf.is_match(&["pip", "pip-23.3"]));
+        assert!(PackageFilter::default().is_empty());
+        assert!(!pf.is_empty());
+    }
 }
@@ -0,0 +1,368 @@
+use std::fs;
+use std::path::Path;
+
+use crate::dep_spec::DepSpec;
+use crate::package::Package;
+use crate::path_shared::PathShared;
+use crate::util::ResultDynError;
+use crate::version_spec::VersionSpec;
+
+// see https://packaging.python.org/en/latest/specifications/core-metadata/
+
+// Commonly found SPDX license identifiers; used to identify when a free-form License field is already a valid SPDX identifier.
+const SPDX_IDS: [&str; 24] = [
+    "0BSD",
+    "AGPL-3.0-only",
+    "AGPL-3.0-or-later",
+    "Apache-2.0",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "BSL-1.0",
+    "CC0-1.0",
+    "EPL-2.0",
+    "GPL-2.0-only",
+    "GPL-2.0-or-later",
+    "GPL-3.0-only",
+    "GPL-3.0-or-later",
+    "HPND",
+    "ISC",
+    "LGPL-2.1-only",
+    "LGPL-2.1-or-later",
+    "LGPL-3.0-only",
+    "LGPL-3.0-or-later",
+    "MIT",
+    "MPL-2.0",
+    "PSF-2.0",
+    "Unlicense",
+    "Zlib",
+];
+
+// Trove license classifiers that map unambiguously to an SPDX identifier.
+const CLASSIFIER_TO_SPDX: [(&str, &str); 8] = [
+    ("License :: OSI Approved :: MIT License", "MIT"),
+    ("License :: OSI Approved :: ISC License (ISCL)", "ISC"),
+    (
+        "License :: OSI Approved :: Mozilla Public License 2.0 (MPL 2.0)",
+        "MPL-2.0",
+    ),
+    (
+        "License :: OSI Approved :: The Unlicense (Unlicense)",
+        "Unlicense",
+    ),
+    ("License :: OSI Approved :: zlib/libpng License", "Zlib"),
+    (
+        "License :: OSI Approved :: Boost Software License 1.0 (BSL-1.0)",
+        "BSL-1.0",
+    ),
+    (
+        "License :: CC0 1.0 Universal (CC0 1.0) Public Domain Dedication",
+        "CC0-1.0",
+    ),
+    (
+        "License :: OSI Approved :: Python Software Foundation License",
+        "PSF-2.0",
+    ),
+];
+
+// Trove license classifiers of the GPL family. Where a classifier does not distinguish "only" from "or later", the deprecated SPDX identifier is used; where no version is given, any version might be used. These are only used to evaluate license policies.
+const CLASSIFIER_TO_SPDX_GPL: [(&str, &str); 11] = [
+    (
+        "License :: OSI Approved :: GNU General Public License (GPL)",
+        "GPL-1.0-or-later",
+    ),
+    (
+        "License :: OSI Approved :: GNU General Public License v2 (GPLv2)",
+        "GPL-2.0",
+    ),
+    (
+        "License :: OSI Approved :: GNU General Public License v2 or later (GPLv2+)",
+        "GPL-2.0-or-later",
+    ),
+    (
+        "License :: OSI Approved :: GNU General Public License v3 (GPLv3)",
+        "GPL-3.0",
+    ),
+    (
+        "License :: OSI Approved :: GNU General Public License v3 or later (GPLv3+)",
+        "GPL-3.0-or-later",
+    ),
+    (
+        "License :: OSI Approved :: GNU Library or Lesser General Public License (LGPL)",
+        "LGPL-2.0-or-later",
+    ),
+    (
+        "License :: OSI Approved :: GNU Lesser General Public License v2 (LGPLv2)",
+        "LGPL-2.0",
+    ),
+    (
+        "License :: OSI Approved :: GNU Lesser General Public License v2 or later (LGPLv2+)",
+        "LGPL-2.0-or-later",
+    ),
+    (
+        "License :: OSI Approved :: GNU Lesser General Public License v3 (LGPLv3)",
+        "LGPL-3.0",
+    ),
+    (
+        "License :: OSI Approved :: GNU Lesser General Public License v3 or later (LGPLv3+)",
+        "LGPL-3.0-or-later",
+    ),
+    (
+        "License :: OSI Approved :: GNU Affero General Public License v3",
+        "AGPL-3.0",
+    ),
+];
+
+fn license_to_spdx(license: &str) -> Option<String> {
+    SPDX_IDS
+        .iter()
+        .find(|id| id.eq_ignore_ascii_case(license))
+        .map(|id| id.to_string())
+}
+
+//------------------------------------------------------------------------------
+/// Selected fields from the core metadata (the METADATA file) of an installed package.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) struct PackageMetadata {
+    pub(crate) license: Option<String>,
+    pub(crate) license_expression: Option<String>,
+    pub(crate) classifiers: Vec<String>,
+    pub(crate) requires_dist: Vec<String>,
+    pub(crate) requires_python: Option<String>,
+}
+
+impl PackageMetadata {
+    /// Parse the RFC 822 style headers of a METADATA file. Parsing stops at the first blank line, as the remainder is the description body.
+    pub(crate) fn from_str(content: &str) -> Self {
+        let mut headers: Vec<(String, String)> = Vec::new();
+        for line in content.lines() {
+            if line.trim().is_empty() {
+                break;
+            }
+            if line.starts_with(' ') || line.starts_with('\t') {
+                // a continuation of the previous header value
+                if let Some((_, value)) = headers.last_mut() {
+                    value.push('\n');
+                    value.push_str(line.trim());
+                }
+            } else if let Some((key, value)) = line.split_once(':') {
+                headers.push((key.trim().to_string(), value.trim().to_string()));
+            }
+        }
+
+        let mut metadata = PackageMetadata::default();
+        for (key, value) in headers {
+            match key.to_lowercase().as_str() {
+                "license" => metadata.license = Some(value),
+                "license-expression" => metadata.license_expression = Some(value),
+                "classifier" => metadata.classifiers.push(value),
+                "requires-dist" => metadata.requires_dist.push(value),
+                "requires-python" => metadata.requires_python = Some(value),
+                _ => {}
+            }
+        }
+        metadata
+    }
+
+    pub(crate) fn from_file(path: &Path) -> ResultDynError<Self> {
+        let content = fs::read_to_string(path)?;
+        Ok(Self::from_str(&content))
+    }
+
+    /// Given a Package and the sites it was found in, read the METADATA of the first dist-info directory found.
+    pub(crate) fn from_package_sites(
+        package: &Package,
+        sites: &[PathShared],
+    ) -> Option<Self> {
+        sites.iter().find_map(|site| {
+            let dist_info = package.to_dist_info_dir(site)?;
+            Self::from_file(&dist_info.join("METADATA")).ok()
+        })
+    }
+
+    //--------------------------------------------------------------------------
+
+    /// Return false if Requires-Python is defined and is not satisfied by the Python version; a Requires-Python that cannot be parsed is assumed to be satisfied.
+    pub(crate) fn is_python_supported(&self, python: &VersionSpec) -> bool {
+        match self.requires_python.as_deref().map(str::trim) {
+            Some(rp) if !rp.is_empty() => DepSpec::from_string(&format!("python{}", rp))
+                .map(|ds| ds.validate_version(python))
+                .unwrap_or(true),
+            _ => true,
+        }
+    }
+
+    /// Return an SPDX license expression if one can be determined. A License-Expression field is preferred, then a License field that is a known SPDX identifier, and finally unambiguous Trove classifiers.
+    pub(crate) fn to_spdx_license(&self) -> Option<String> {
+        if let Some(expression) = &self.license_expression {
+            return Some(expression.clone());
+        }
+        if let Some(spdx) = self.license.as_deref().and_then(license_to_spdx) {
+            return Some(spdx);
+        }
+        self.classifiers_to_spdx(&CLASSIFIER_TO_SPDX)
+    }
+
+    /// Return an SPDX license expression from the license classifiers found in the provided table.
+    fn classifiers_to_spdx(&self, table: &[(&str, &str)]) -> Option<String> {
+        let mut ids: Vec<&str> = self
+            .classifiers
+            .iter()
+            .filter_map(|c| {
+                table
+                    .iter()
+                    .find(|(classifier, _)| classifier == c)
+                    .map(|(_, id)| *id)
+            })
+            .collect();
+        ids.sort();
+        ids.dedup();
+        match ids.len() {
+            0 => None,
+            1 => Some(ids[0].to_string()),
+            // multiple license classifiers are taken as alternatives
+            _ => Some(format!("({})", ids.join(" OR "))),
+        }
+    }
+
+    /// Return an SPDX license expression for evaluating a license policy. As an SBOM license, the expression of `to_spdx_license()` is preferred; otherwise, GPL-family classifiers are used, as these are commonly declared without a License-Expression.
+    pub(crate) fn to_policy_license(&self) -> Option<String> {
+        self.to_spdx_license()
+            .or_else(|| self.classifiers_to_spdx(&CLASSIFIER_TO_SPDX_GPL))
+    }
+
+    /// Return a human-readable description of the declared license, using the License field or, if not defined, the license classifiers.
+    pub(crate) fn to_license_text(&self) -> Option<String> {
+        if let Some(license) = &self.license {
+            if !license.is_empty() && license != "UNKNOWN" {
+                return Some(license.clone());
+            }
+        }
+        let classifiers: Vec<&str> = self
+            .classifiers
+            .iter()
+            .filter(|c| c.starts_with("License ::"))
+            .map(|c| c.as_str())
+            .collect();
+        if classifiers.is_empty() {
+            None
+        } else {
+            Some(classifiers.join("; "))
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_from_str_a() {
+        let content = "Metadata-Version: 2.1\nName: requests\nVersion: 2.32.3\nLicense: Apache-2.0\nClassifier: Development Status :: 5 - Production/Stable\nClassifier: License :: OSI Approved :: Apache Software License\n\nClassifier: Not a header\n";
+        let md = PackageMetadata::from_str(content);
+        assert_eq!(md.license.as_deref(), Some("Apache-2.0"));
+        assert_eq!(md.license_expression, None);
+        assert_eq!(md.classifiers.len(), 2);
+        assert_eq!(md.to_spdx_license().as_deref(), Some("Apache-2.0"));
+    }
+
+    #[test]
+    fn test_from_str_b() {
+        let content = "Metadata-Version: 2.1\nName: numpy\nLicense: Copyright (c) 2005-2024, NumPy Developers.\n        All rights reserved.\nClassifier: License :: OSI Approved :: BSD License\n";
+        let md = PackageMetadata::from_str(content);
+        assert_eq!(
+            md.license.as_deref(),
+            Some("Copyright (c) 2005-2024, NumPy Developers.\nAll rights reserved.")
+        );
+        assert_eq!(md.to_spdx_license(), None);
+        assert!(md.to_license_text().unwrap().starts_with("Copyright"));
+    }
+
+    #[test]
+    fn test_from_str_c() {
+        let content = "Metadata-Version: 2.4\nName: fetter\nLicense-Expression: MIT OR Apache-2.0\n";
+        let md = PackageMetadata::from_str(content);
+        assert_eq!(md.to_spdx_license().as_deref(), Some("MIT OR Apache-2.0"));
+    }
+
+    #[test]
+    fn test_to_spdx_license_a() {
+        let content = "Name: six\nLicense: UNKNOWN\nClassifier: License :: OSI Approved :: MIT License\n";
+        let md = PackageMetadata::from_str(content);
+        assert_eq!(md.to_spdx_license().as_deref(), Some("MIT"));
+        assert_eq!(
+            md.to_license_text().as_deref(),
+            Some("License :: OSI Approved :: MIT License")
+        );
+    }
+
+    #[test]
+    fn test_to_spdx_license_b() {
+        let content = "Name: x\nClassifier: License :: OSI Approved :: MIT License\nClassifier: License :: OSI Approved :: ISC License (ISCL)\n";
+        let md = PackageMetadata::from_str(content);
+        assert_eq!(md.to_spdx_license().as_deref(), Some("(ISC OR MIT)"));
+    }
+
+    #[test]
+    fn test_to_policy_license_a() {
+        let content = "Name: x\nLicense: GPL\nClassifier: License :: OSI Approved :: GNU General Public License v3 (GPLv3)\n";
+        let md = PackageMetadata::from_str(content);
+        assert_eq!(md.to_spdx_license(), None);
+        assert_eq!(md.to_policy_license().as_deref(), Some("GPL-3.0"));
+
+        let content = "Name: x\nClassifier: License :: OSI Approved :: MIT License\nClassifier: License :: OSI Approved :: GNU General Public License (GPL)\n";
+        let md = PackageMetadata::from_str(content);
+        assert_eq!(md.to_policy_license().as_deref(), Some("MIT"));
+    }
+
+    #[test]
+    fn test_from_str_d() {
+        let content = "Name: requests\nRequires-Dist: charset-normalizer<4,>=2\nRequires-Dist: PySocks!=1.5.7,>=1.5.6; extra == \"socks\"\n";
+        let md = PackageMetadata::from_str(content);
+        assert_eq!(
+            md.requires_dist,
+            vec![
+                "charset-normalizer<4,>=2",
+                "PySocks!=1.5.7,>=1.5.6; extra == \"socks\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_python_supported_a() {
+        let content = "Name: numpy\nVersion: 2.1.2\nRequires-Python: >=3.10\n";
+        let md = PackageMetadata::from_str(content);
+        assert_eq!(md.requires_python.as_deref(), Some(">=3.10"));
+        assert!(md.is_python_supported(&VersionSpec::new("3.12.4")));
+        assert!(md.is_python_supported(&VersionSpec::new("3.10")));
+        assert!(!md.is_python_supported(&VersionSpec::new("3.9.18")));
+
+        let content = "Name: six\nRequires-Python: >=2.7, !=3.0.*, !=3.1.*, !=3.2.*\n";
+        let md = PackageMetadata::from_str(content);
+        assert!(md.is_python_supported(&VersionSpec::new("3.12.4")));
+        assert!(!md.is_python_supported(&VersionSpec::new("3.1.2")));
+
+        // undefined or unparsable values are supported
+        let md = PackageMetadata::from_str("Name: six\n");
+        assert!(md.is_python_supported(&VersionSpec::new("2.6")));
+        let md = PackageMetadata::from_str("Name: six\nRequires-Python: any\n");
+        assert!(md.is_python_supported(&VersionSpec::new("2.6")));
+    }
+
+    #[test]
+    fn test_from_package_sites_a() {
+        let dir = tempdir().unwrap();
+        let fp_di = dir.path().join("flask-3.0.3.dist-info");
+        fs::create_dir(&fp_di).unwrap();
+        let mut file = File::create(fp_di.join("METADATA")).unwrap();
+        write!(file, "Name: Flask\nVersion: 3.0.3\nLicense: BSD-3-Clause\n").unwrap();
+
+        let site = PathShared::from_path_buf(dir.path().to_path_buf());
+        let package = Package::from_name_version_durl("Flask", "3.0.3", None).unwrap();
+        let md = PackageMetadata::from_package_sites(&package, &[site]).unwrap();
+        assert_eq!(md.to_spdx_license().as_deref(), Some("BSD-3-Clause"));
+    }
+}
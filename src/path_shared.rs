@@ -1,3 +1,4 @@
+use std::env;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::path::Path;
@@ -9,6 +10,42 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::util::path_home;
 
+// Return the user base directories in which user sites are found, as defined by `PYTHONUSERBASE` or the platform defaults.
+pub(crate) fn get_user_bases() -> Vec<PathBuf> {
+    if let Some(base) = env::var_os("PYTHONUSERBASE").filter(|b| !b.is_empty()) {
+        return vec![PathBuf::from(base)];
+    }
+    let mut bases = Vec::new();
+    if let Some(home) = path_home() {
+        bases.push(home.join(".local"));
+        bases.push(home.join("Library").join("Python"));
+    }
+    if let Some(appdata) = env::var_os("APPDATA") {
+        bases.push(PathBuf::from(appdata).join("Python"));
+    }
+    bases
+}
+
+// Return true if `path` is the user site of one of `bases`: `lib/pythonX.Y/site-packages` on Linux, `X.Y/lib/python/site-packages` in `~/Library/Python` on macOS, or `PythonXY/site-packages` on Windows.
+fn is_user_site_path(path: &Path, bases: &[PathBuf]) -> bool {
+    bases.iter().any(|base| {
+        let rel = match path.strip_prefix(base) {
+            Ok(rel) => rel,
+            Err(_) => return false,
+        };
+        let parts: Vec<&str> = rel
+            .components()
+            .filter_map(|c| c.as_os_str().to_str())
+            .collect();
+        match parts.as_slice() {
+            ["lib", py, "site-packages"] => py.starts_with("python"),
+            [_, "lib", "python", "site-packages"] => true,
+            [py, "site-packages"] => py.starts_with("Python"),
+            _ => false,
+        }
+    })
+}
+
 /// As a normal Arc-wrapped PathBuf cannot be a key in a mapping or set, we create this wrapped Arc PathBuf that implements hashability. Cloning this type will increment the reference count.
 #[derive(Debug, Clone)]
 pub(crate) struct PathShared(Arc<PathBuf>);
@@ -34,6 +71,11 @@ impl PathShared {
     pub(crate) fn join(&self, part: &str) -> PathBuf {
         self.0.join(part)
     }
+
+    /// Return true if this is a user site (e.g. `~/.local/lib/python3.12/site-packages`), in which packages are installed with `pip install --user`.
+    pub(crate) fn is_user_site(&self) -> bool {
+        is_user_site_path(self.as_path(), &get_user_bases())
+    }
 }
 
 impl Serialize for PathShared {
@@ -87,6 +129,11 @@ impl Hash for PathShared {
 
 //------------------------------------------------------------------------------
 #[cfg(test)]
+#[allow(
+    clippy::single_component_path_imports,
+    clippy::to_string_in_format_args,
+    clippy::useless_vec
+)]
 mod tests {
     use super::*;
     use serde_json;
@@ -123,6 +170,31 @@ mod tests {
         assert_eq!(path1.as_path(), Path::new("/home/user1"));
     }
 
+    #[test]
+    fn test_is_user_site_path_a() {
+        let bases = vec![
+            PathBuf::from("/home/user1/.local"),
+            PathBuf::from("/Users/user1/Library/Python"),
+        ];
+        let is_user_site = |p: &str| is_user_site_path(Path::new(p), &bases);
+        assert!(is_user_site(
+            "/home/user1/.local/lib/python3.12/site-packages"
+        ));
+        assert!(is_user_site(
+            "/Users/user1/Library/Python/3.11/lib/python/site-packages"
+        ));
+        assert!(!is_user_site(
+            "/home/user1/.local/pipx/venvs/black/lib/python3.12/site-packages"
+        ));
+        assert!(!is_user_site("/usr/lib/python3.12/site-packages"));
+        assert!(!is_user_site("/home/user1/.local/lib/python3.12"));
+        let bases = vec![PathBuf::from("C:/Users/user1/AppData/Roaming/Python")];
+        assert!(is_user_site_path(
+            Path::new("C:/Users/user1/AppData/Roaming/Python/Python312/site-packages"),
+            &bases
+        ));
+    }
+
     #[test]
     fn test_serialization_a() {
         let path = PathBuf::from("/some/example/path");
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rayon::prelude::*;
+use serde::Deserialize;
+
+use crate::ureq_client::UreqClient;
+use crate::util::path_cache;
+use crate::util::path_within_duration;
+use crate::util::DURATION_0;
+use crate::version_spec::VersionSpec;
+
+// The name of the directory, within the cache directory, in which PyPI JSON API responses are stored.
+const PYPI_CACHE_DIR: &str = "pypi";
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Deserialize)]
+struct PyPIInfo {
+    version: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PyPIFile {
+    upload_time: Option<String>,
+    #[serde(default)]
+    yanked: bool,
+    yanked_reason: Option<String>,
+    #[serde(default)]
+    digests: HashMap<String, String>,
+}
+
+/// A project as returned by the PyPI JSON API, limited to the latest version and the files of each release.
+#[derive(Debug, Deserialize)]
+pub(crate) struct PyPIProject {
+    info: PyPIInfo,
+    #[serde(default)]
+    releases: HashMap<String, Vec<PyPIFile>>,
+}
+
+impl PyPIProject {
+    pub(crate) fn from_str(content: &str) -> Option<Self> {
+        serde_json::from_str(content).ok()
+    }
+
+    /// Return the latest version, as determined by the index; this excludes pre-releases unless no other releases are available.
+    pub(crate) fn get_latest(&self) -> VersionSpec {
+        VersionSpec::new(&self.info.version)
+    }
+
+    /// Return the sorted versions of all releases that have at least one file that is not yanked.
+    pub(crate) fn get_releases(&self) -> Vec<VersionSpec> {
+        let mut versions: Vec<VersionSpec> = self
+            .releases
+            .iter()
+            .filter(|(_, files)| files.iter().any(|f| !f.yanked))
+            .map(|(v, _)| VersionSpec::new(v))
+            .collect();
+        versions.sort();
+        versions
+    }
+
+    fn get_files(&self, version: &VersionSpec) -> Vec<&PyPIFile> {
+        self.releases
+            .iter()
+            .filter(|(v, _)| VersionSpec::new(v) == *version)
+            .flat_map(|(_, files)| files.iter())
+            .collect()
+    }
+
+    /// Return true if the release has files and all of them are yanked.
+    pub(crate) fn is_yanked(&self, version: &VersionSpec) -> bool {
+        let files = self.get_files(version);
+        !files.is_empty() && files.iter().all(|f| f.yanked)
+    }
+
+    /// Return the reason given for yanking the release, if defined.
+    pub(crate) fn get_yanked_reason(&self, version: &VersionSpec) -> Option<String> {
+        self.get_files(version)
+            .iter()
+            .filter(|f| f.yanked)
+            .filter_map(|f| f.yanked_reason.clone())
+            .find(|r| !r.is_empty())
+    }
+
+    /// Return the sha256 hashes of all files of a release, formatted as "sha256:<digest>" and sorted.
+    pub(crate) fn get_hashes(&self, version: &VersionSpec) -> Vec<String> {
+        let mut hashes: Vec<String> = self
+            .get_files(version)
+            .iter()
+            .filter_map(|f| f.digests.get("sha256"))
+            .map(|d| format!("sha256:{}", d))
+            .collect();
+        hashes.sort();
+        hashes.dedup();
+        hashes
+    }
+
+    /// Return the earliest upload time of the files of a release, if defined.
+    pub(crate) fn get_upload_time(&self, version: &VersionSpec) -> Option<String> {
+        self.get_files(version)
+            .iter()
+            .filter_map(|f| f.upload_time.clone())
+            .min()
+    }
+}
+
+//------------------------------------------------------------------------------
+/// Return the directory in which to cache PyPI responses, creating the cache directory if necessary; if the cache duration is zero, no directory is returned.
+pub(crate) fn path_pypi_cache(cache_dur: Duration) -> Option<PathBuf> {
+    if cache_dur > DURATION_0 {
+        path_cache(true).map(|dir| dir.join(PYPI_CACHE_DIR))
+    } else {
+        None
+    }
+}
+
+fn query_pypi_project<U: UreqClient + std::marker::Sync>(
+    client: &U,
+    key: &str,
+    cache_dir: Option<&Path>,
+    cache_dur: Duration,
+) -> Option<PyPIProject> {
+    let cache_fp = cache_dir.map(|dir| dir.join(format!("{}.json", key)));
+    if let Some(fp) = &cache_fp {
+        if path_within_duration(fp, cache_dur) {
+            if let Some(project) = fs::read_to_string(fp)
+                .ok()
+                .and_then(|c| PyPIProject::from_str(&c))
+            {
+                return Some(project);
+            }
+        }
+    }
+    let url = format!("https://pypi.org/pypi/{}/json", key);
+    let body_str = client.get(&url).ok()?;
+    let project = PyPIProject::from_str(&body_str)?;
+    if let Some(fp) = &cache_fp {
+        let _ = fs::write(fp, &body_str);
+    }
+    Some(project)
+}
+
+/// Query the PyPI JSON API for each package key, returning a mapping of key to PyPIProject. Keys that cannot be found are excluded. If a cache directory is provided, responses are written to it, and responses written within the cache duration are used without querying the index.
+pub(crate) fn query_pypi_projects<U: UreqClient + std::marker::Sync>(
+    client: &U,
+    keys: &[String],
+    cache_dir: Option<&Path>,
+    cache_dur: Duration,
+) -> HashMap<String, PyPIProject> {
+    if let Some(dir) = cache_dir {
+        let _ = fs::create_dir_all(dir);
+    }
+    keys.par_iter()
+        .filter_map(|key| {
+            query_pypi_project(client, key, cache_dir, cache_dur)
+                .map(|project| (key.clone(), project))
+        })
+        .collect()
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use crate::ureq_client::UreqClientMock;
+    use tempfile::tempdir;
+
+    const PROJECT: &str = r#"{"info":{"name":"requests","version":"2.32.3"},"releases":{"2.31.0":[{"upload_time":"2023-05-22T15:12:44","yanked":false,"digests":{"md5":"a9d5c5b8d3bb5e8a1f1b8a1c3d7e1f0b","sha256":"942c5a758f98d790eaed1a29cb6eefc7ffb0d1cf7af05c3d2791656dbd6ad1e1"}},{"upload_time":"2023-05-22T15:12:42","yanked":false,"digests":{"sha256":"58cd2187c01e70e6e26505bca751777aa9f2ee0b7f4300988b709f44e013003f"}}],"2.32.0":[{"upload_time":"2024-05-20T15:59:30","yanked":true,"yanked_reason":"Yanked due to conflicts with CVE-2024-35195 mitigation"}],"2.32.2":[{"upload_time":"2024-05-21T18:51:41","yanked":false}],"2.32.3":[{"upload_time":"2024-05-29T15:37:47","yanked":false}],"3.0.0a1":[]}}"#;
+
+    #[test]
+    fn test_pypi_project_a() {
+        let project = PyPIProject::from_str(PROJECT).unwrap();
+        assert_eq!(project.get_latest().to_string(), "2.32.3");
+        // yanked releases and releases without files are excluded
+        assert_eq!(
+            project
+                .get_releases()
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>(),
+            vec!["2.31.0", "2.32.2", "2.32.3"]
+        );
+        assert_eq!(
+            project.get_upload_time(&VersionSpec::new("2.31.0")),
+            Some("2023-05-22T15:12:42".to_string())
+        );
+        assert_eq!(project.get_upload_time(&VersionSpec::new("1.0")), None);
+        assert_eq!(
+            project.get_hashes(&VersionSpec::new("2.31.0")),
+            vec![
+                "sha256:58cd2187c01e70e6e26505bca751777aa9f2ee0b7f4300988b709f44e013003f",
+                "sha256:942c5a758f98d790eaed1a29cb6eefc7ffb0d1cf7af05c3d2791656dbd6ad1e1",
+            ]
+        );
+        assert!(project.get_hashes(&VersionSpec::new("2.32.3")).is_empty());
+    }
+
+    #[test]
+    fn test_pypi_project_b() {
+        let project = PyPIProject::from_str(PROJECT).unwrap();
+        let v1 = VersionSpec::new("2.32.0");
+        assert_eq!(project.is_yanked(&v1), true);
+        assert_eq!(
+            project.get_yanked_reason(&v1).unwrap(),
+            "Yanked due to conflicts with CVE-2024-35195 mitigation"
+        );
+        let v2 = VersionSpec::new("2.31.0");
+        assert_eq!(project.is_yanked(&v2), false);
+        assert_eq!(project.get_yanked_reason(&v2), None);
+        // releases without files are not yanked
+        assert_eq!(project.is_yanked(&VersionSpec::new("3.0.0a1")), false);
+    }
+
+    #[test]
+    fn test_query_pypi_projects_a() {
+        let dir = tempdir().unwrap();
+        let keys = vec!["requests".to_string()];
+        let client = UreqClientMock {
+            mock_get: Some(PROJECT.to_string()),
+            mock_post: None,
+        };
+        let projects = query_pypi_projects(
+            &client,
+            &keys,
+            Some(dir.path()),
+            Duration::from_secs(60),
+        );
+        assert_eq!(projects["requests"].get_latest().to_string(), "2.32.3");
+        assert!(dir.path().join("requests.json").exists());
+
+        // a cached response is used without a request
+        let client = UreqClientMock {
+            mock_get: None,
+            mock_post: None,
+        };
+        let projects = query_pypi_projects(
+            &client,
+            &keys,
+            Some(dir.path()),
+            Duration::from_secs(60),
+        );
+        assert_eq!(projects.len(), 1);
+        let projects = query_pypi_projects(&client, &keys, None, Duration::from_secs(60));
+        assert_eq!(projects.len(), 0);
+    }
+}
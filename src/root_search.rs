@@ -0,0 +1,597 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::path_shared::get_user_bases;
+use crate::path_shared::PathShared;
+
+//------------------------------------------------------------------------------
+// Discover site packages in a mounted or extracted filesystem, such as an exported container image, without executing any interpreters.
+
+// Top-level directories of a root filesystem that never contain site packages.
+const ROOT_EXCLUDE: [&str; 3] = ["proc", "sys", "dev"];
+
+const PYVENV_CFG: &str = "pyvenv.cfg";
+
+/// The directory of PEP 582 local packages in a project, with sites in `X.Y/lib`.
+pub(crate) const PYPACKAGES: &str = "__pypackages__";
+
+// Return true if this path exists, without following symlinks; symlinks in a root filesystem might point outside of it.
+fn path_exists_no_follow(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
+// Return true if the path is a site-packages or dist-packages directory within a `pythonX.Y` directory.
+fn is_site_dir(path: &Path) -> bool {
+    let name_site = path.file_name().and_then(|f| f.to_str());
+    let name_version = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .and_then(|f| f.to_str());
+    matches!(name_site, Some("site-packages") | Some("dist-packages"))
+        && name_version.is_some_and(|n| n.starts_with("python"))
+}
+
+/// Given a site packages path, return the major and minor Python version from the name of its `pythonX.Y` directory, or of the `X.Y` directory of a `__pypackages__/X.Y/lib` site, if defined.
+pub(crate) fn site_to_python_version(site: &Path) -> Option<String> {
+    let dir = site.parent()?;
+    let name = dir.file_name()?.to_str()?;
+    let is_pypackages = site.file_name().is_some_and(|n| n == "lib")
+        && dir
+            .parent()
+            .and_then(|p| p.file_name())
+            .is_some_and(|n| n == PYPACKAGES);
+    let version = if is_pypackages {
+        name
+    } else {
+        name.strip_prefix("python")?
+    };
+    if is_major_minor(version) {
+        Some(version.to_string())
+    } else {
+        None
+    }
+}
+
+// Return true if the version is a major and minor version, e.g. `3.12`.
+fn is_major_minor(version: &str) -> bool {
+    match version.split_once('.') {
+        Some((major, minor)) => {
+            !major.is_empty()
+                && !minor.is_empty()
+                && major
+                    .chars()
+                    .chain(minor.chars())
+                    .all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+// Return true if the site is a Debian or Ubuntu `dist-packages` directory.
+fn is_dist_packages(site: &Path) -> bool {
+    site.file_name().is_some_and(|n| n == "dist-packages")
+}
+
+// Sort Debian `dist-packages` sites in the order of the `sys.path` of the system Python: `/usr/local/lib/pythonX.Y`, for packages installed with pip, then the version-less `/usr/lib/python3` and `/usr/lib/pythonX.Y`, for packages installed with apt. Other sites keep their order.
+fn sort_dist_packages(sites: &mut [PathShared], usr_local: &Path) {
+    sites.sort_by_key(|site| {
+        let site = site.as_path();
+        if !is_dist_packages(site) {
+            return 0;
+        }
+        match (
+            site.starts_with(usr_local),
+            site_to_python_version(site).is_some(),
+        ) {
+            (true, _) => 1,
+            (false, false) => 2,
+            (false, true) => 3,
+        }
+    });
+}
+
+// Given a site packages path in the form `{prefix}/lib/pythonX.Y/site-packages`, return the prefix.
+fn site_to_prefix(site: &Path) -> Option<&Path> {
+    site.parent()?.parent()?.parent()
+}
+
+// Given a site packages path and its prefix, return the executable of that environment. As executables are not run, the executable named by the `pythonX.Y` directory is returned if it cannot be found.
+fn site_to_exe(site: &Path, prefix: &Path) -> Option<PathBuf> {
+    let name = site.parent()?.file_name()?;
+    let bin = prefix.join("bin");
+    let exe_version = bin.join(name);
+    if path_exists_no_follow(&exe_version) {
+        return Some(exe_version);
+    }
+    let exe = bin.join("python3");
+    if path_exists_no_follow(&exe) {
+        return Some(exe);
+    }
+    Some(exe_version)
+}
+
+// Recursively collect all site packages directories, not following symlinks and not descending into site packages.
+fn find_sites_inner(path: &Path, sites: &mut Vec<PathBuf>) {
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            if !entry.file_type().is_ok_and(|ft| ft.is_dir()) {
+                continue;
+            }
+            let path = entry.path();
+            if is_site_dir(&path) {
+                sites.push(path);
+            } else {
+                find_sites_inner(&path, sites);
+            }
+        }
+    }
+}
+
+/// Given the prefix of a virtual environment, read its `pyvenv.cfg` and return its keys and values, in order. Returns None if the prefix has no `pyvenv.cfg`.
+pub(crate) fn read_pyvenv_cfg(prefix: &Path) -> Option<Vec<(String, String)>> {
+    let content = fs::read_to_string(prefix.join(PYVENV_CFG)).ok()?;
+    Some(
+        content
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .collect(),
+    )
+}
+
+/// Given the prefix of a virtual environment, read its `pyvenv.cfg` and, if system site packages are included, return the `home` directory of the base executable.
+fn venv_to_home(prefix: &Path) -> Option<PathBuf> {
+    let mut home = None;
+    let mut include_system = false;
+    for (key, value) in read_pyvenv_cfg(prefix)? {
+        match key.as_str() {
+            "home" => home = Some(PathBuf::from(value)),
+            "include-system-site-packages" => {
+                include_system = value.eq_ignore_ascii_case("true")
+            }
+            _ => {}
+        }
+    }
+    if include_system {
+        home
+    } else {
+        None
+    }
+}
+
+/// Given a root directory of a filesystem, find all site packages by layout and attribute them to executables within that root. Virtual environments configured in `pyvenv.cfg` to include system site packages also include the sites of their base executable. On Debian and Ubuntu, the `dist-packages` of `/usr/local` and the version-less `/usr/lib/python3/dist-packages` are attributed to the system Python of each version in `/usr/bin`.
+pub(crate) fn find_root_exe_to_sites(root: &Path) -> HashMap<PathBuf, Vec<PathShared>> {
+    let mut sites = Vec::new();
+    if let Ok(entries) = fs::read_dir(root) {
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            if ROOT_EXCLUDE.iter().any(|e| name == *e) {
+                continue;
+            }
+            if entry.file_type().is_ok_and(|ft| ft.is_dir()) {
+                find_sites_inner(&entry.path(), &mut sites);
+            }
+        }
+    }
+    sites.sort();
+
+    let usr = root.join("usr");
+    let usr_local = usr.join("local");
+    let mut prefix_to_sites: HashMap<PathBuf, Vec<PathShared>> = HashMap::new();
+    let mut exe_to_sites: HashMap<PathBuf, Vec<PathShared>> = HashMap::new();
+    for site in sites {
+        let prefix = match site_to_prefix(&site) {
+            // Debian installs packages with pip in `/usr/local` for the system Python in `/usr`
+            Some(prefix) if prefix == usr_local && is_dist_packages(&site) => usr.clone(),
+            Some(prefix) => prefix.to_path_buf(),
+            None => continue,
+        };
+        let exe = match site_to_exe(&site, &prefix) {
+            Some(exe) => exe,
+            None => continue,
+        };
+        let site = PathShared::from_path_buf(site);
+        prefix_to_sites
+            .entry(prefix)
+            .or_default()
+            .push(site.clone());
+        exe_to_sites.entry(exe).or_default().push(site);
+    }
+    // a version-less `python3/dist-packages` is shared by all `python3.Y` executables in the same directory
+    let exes_shared: Vec<PathBuf> = exe_to_sites
+        .iter()
+        .filter(|(_, sites)| {
+            sites.iter().all(|s| {
+                is_dist_packages(s.as_path())
+                    && site_to_python_version(s.as_path()).is_none()
+            })
+        })
+        .map(|(exe, _)| exe.clone())
+        .collect();
+    for exe in exes_shared {
+        let prefix_name = match exe.file_name().and_then(|n| n.to_str()) {
+            Some(name) => format!("{}.", name),
+            None => continue,
+        };
+        let exes_versioned: Vec<PathBuf> = exe_to_sites
+            .keys()
+            .filter(|e| {
+                e.parent() == exe.parent()
+                    && e.file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with(&prefix_name))
+            })
+            .cloned()
+            .collect();
+        if exes_versioned.is_empty() {
+            continue;
+        }
+        if let Some(sites_shared) = exe_to_sites.remove(&exe) {
+            for e in exes_versioned {
+                exe_to_sites
+                    .entry(e)
+                    .or_default()
+                    .extend(sites_shared.iter().cloned());
+            }
+        }
+    }
+    for sites in exe_to_sites
+        .values_mut()
+        .chain(prefix_to_sites.values_mut())
+    {
+        sort_dist_packages(sites, &usr_local);
+    }
+    // the home of a virtual environment is an absolute path within the root
+    for (exe, sites) in exe_to_sites.iter_mut() {
+        let prefix = match exe.parent().and_then(|p| p.parent()) {
+            Some(prefix) => prefix,
+            None => continue,
+        };
+        if let Some(home) = venv_to_home(prefix) {
+            let home = root.join(home.strip_prefix("/").unwrap_or(home.as_path()));
+            if let Some(sites_base) = home.parent().and_then(|p| prefix_to_sites.get(p)) {
+                sites.extend(sites_base.iter().cloned());
+            }
+        }
+    }
+    exe_to_sites
+}
+
+//------------------------------------------------------------------------------
+// Derive the site packages of an executable on this machine from `pyvenv.cfg` and layout, without executing it.
+
+// Given the name of an executable (e.g. `python3.12`) or of a library directory (e.g. `pypy3.10`), return its major and minor version, if defined.
+fn name_to_python_version(name: &str) -> Option<String> {
+    let version = name
+        .strip_prefix("python")
+        .or_else(|| name.strip_prefix("pypy"))?;
+    let version = version.strip_suffix(".exe").unwrap_or(version);
+    if is_major_minor(version) {
+        Some(version.to_string())
+    } else {
+        None
+    }
+}
+
+// Given the directory of an executable, return its prefix: executables are in `bin` or `Scripts`, except in the prefix of a Windows installation.
+fn exe_dir_to_prefix(dir: &Path) -> &Path {
+    match dir.file_name().and_then(|n| n.to_str()) {
+        Some("bin") | Some("Scripts") => dir.parent().unwrap_or(dir),
+        _ => dir,
+    }
+}
+
+// Given a prefix and, if known, the major and minor version of its Python, return the existing site packages of `sysconfig` schemes, in the order of `sys.path`: `lib/pythonX.Y/site-packages` (or `pypyX.Y`) and `lib64` on POSIX; `Lib/site-packages` on Windows; and, for Debian and Ubuntu, `local/lib/pythonX.Y/dist-packages`, `lib/python3/dist-packages`, and `lib/pythonX.Y/dist-packages`. If the version is not known, sites of all versions are returned.
+fn prefix_to_sites_static(prefix: &Path, version: Option<&str>) -> Vec<PathBuf> {
+    let is_version = |name: &str| match (name_to_python_version(name), version) {
+        (Some(v), Some(version)) => v == version,
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+    let mut sites: Vec<PathBuf> = Vec::new();
+    let mut push = |site: PathBuf| {
+        // in virtual environments `lib64` is a symlink to `lib`
+        let canonical = fs::canonicalize(&site).ok();
+        let is_new = !sites.iter().any(|s| fs::canonicalize(s).ok() == canonical);
+        if site.is_dir() && is_new {
+            sites.push(site);
+        }
+    };
+    for (lib, is_debian_local) in [
+        (prefix.join("local").join("lib"), true),
+        (prefix.join("lib"), false),
+        (prefix.join("lib64"), false),
+    ] {
+        let mut dirs: Vec<(PathBuf, bool)> = match fs::read_dir(&lib) {
+            Ok(entries) => entries
+                .flatten()
+                .filter_map(|e| {
+                    let name = e.file_name().to_str()?.to_string();
+                    if is_version(&name) {
+                        Some((e.path(), false))
+                    } else if name == "python3" {
+                        Some((e.path(), true))
+                    } else {
+                        None
+                    }
+                })
+                .collect(),
+            Err(_) => continue,
+        };
+        dirs.sort();
+        for (dir, is_versionless) in dirs {
+            if !is_debian_local && !is_versionless {
+                push(dir.join("site-packages"));
+            }
+            push(dir.join("dist-packages"));
+        }
+    }
+    push(prefix.join("Lib").join("site-packages"));
+    sites
+}
+
+// Given a user base and the major and minor version of a Python, return the user site of that version, if it exists.
+fn user_base_to_site(base: &Path, version: &str) -> Option<PathBuf> {
+    [
+        base.join("lib")
+            .join(format!("python{}", version))
+            .join("site-packages"),
+        base.join(version).join("lib/python/site-packages"),
+        base.join(format!("Python{}", version.replace('.', "")))
+            .join("site-packages"),
+    ]
+    .into_iter()
+    .find(|site| site.is_dir())
+}
+
+/// Given an executable on this machine, return its site packages without running it, as derived from its `pyvenv.cfg` and the layout of `sysconfig` schemes. The version is read from `pyvenv.cfg` or the name of the executable; if neither defines it, sites of all versions in the prefix are returned. Virtual environments configured to include system site packages also include the sites of the prefix of their `home`. As `site.ENABLE_USER_SITE` cannot be read without running the executable, the user site is only included if `force_usite` is set.
+pub(crate) fn exe_to_sites_static(exe: &Path, force_usite: bool) -> Vec<PathShared> {
+    let prefix = match exe.parent() {
+        Some(dir) => exe_dir_to_prefix(dir),
+        None => return Vec::new(),
+    };
+    let cfg = read_pyvenv_cfg(prefix);
+    let version = cfg
+        .iter()
+        .flatten()
+        .find(|(k, _)| k == "version_info" || k == "version")
+        .map(|(_, v)| v.split('.').take(2).collect::<Vec<_>>().join("."))
+        .filter(|v| is_major_minor(v))
+        .or_else(|| {
+            exe.file_name()
+                .and_then(|n| n.to_str())
+                .and_then(name_to_python_version)
+        });
+    let mut sites = prefix_to_sites_static(prefix, version.as_deref());
+    if let Some(home) = cfg.as_ref().and_then(|_| venv_to_home(prefix)) {
+        sites.extend(prefix_to_sites_static(
+            exe_dir_to_prefix(&home),
+            version.as_deref(),
+        ));
+    }
+    let version =
+        version.or_else(|| sites.iter().find_map(|s| site_to_python_version(s)));
+    if let Some(version) = version.filter(|_| force_usite) {
+        sites.extend(
+            get_user_bases()
+                .iter()
+                .find_map(|base| user_base_to_site(base, &version)),
+        );
+    }
+    sites.into_iter().map(PathShared::from_path_buf).collect()
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    fn create_site(root: &Path, site: &str) -> PathBuf {
+        let fp = root.join(site);
+        fs::create_dir_all(&fp).unwrap();
+        fp
+    }
+
+    #[test]
+    fn test_is_site_dir_a() {
+        assert_eq!(
+            is_site_dir(Path::new("/usr/lib/python3.11/site-packages")),
+            true
+        );
+        assert_eq!(
+            is_site_dir(Path::new("/usr/lib/python3/dist-packages")),
+            true
+        );
+        assert_eq!(is_site_dir(Path::new("/usr/lib/site-packages")), false);
+        assert_eq!(is_site_dir(Path::new("/usr/lib/python3.11")), false);
+    }
+
+    #[test]
+    fn test_site_to_python_version_a() {
+        assert_eq!(
+            site_to_python_version(Path::new("/usr/lib/python3.11/site-packages")),
+            Some("3.11".to_string())
+        );
+        assert_eq!(
+            site_to_python_version(Path::new("/usr/lib/python3/dist-packages")),
+            None
+        );
+        assert_eq!(
+            site_to_python_version(Path::new("/home/proj/__pypackages__/3.12/lib")),
+            Some("3.12".to_string())
+        );
+        assert_eq!(
+            site_to_python_version(Path::new("/home/proj/3.12/lib")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_find_root_exe_to_sites_a() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        let site1 = create_site(root, "usr/local/lib/python3.12/site-packages");
+        fs::create_dir_all(root.join("usr/local/bin")).unwrap();
+        let _ = File::create(root.join("usr/local/bin/python3.12")).unwrap();
+        let site2 = create_site(root, "usr/lib/python3/dist-packages");
+        // excluded top-level directories are not searched
+        let _ = create_site(root, "proc/1/root/usr/lib/python3.9/site-packages");
+
+        let post = find_root_exe_to_sites(root);
+        assert_eq!(post.len(), 2);
+        assert_eq!(
+            post[&root.join("usr/local/bin/python3.12")],
+            vec![PathShared::from_path_buf(site1)]
+        );
+        assert_eq!(
+            post[&root.join("usr/bin/python3")],
+            vec![PathShared::from_path_buf(site2)]
+        );
+    }
+
+    #[test]
+    fn test_find_root_exe_to_sites_b() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        let site1 = create_site(root, "usr/local/lib/python3.12/site-packages");
+        let site2 = create_site(root, "opt/venv/lib/python3.12/site-packages");
+        let mut file = File::create(root.join("opt/venv/pyvenv.cfg")).unwrap();
+        writeln!(file, "home = /usr/local/bin").unwrap();
+        writeln!(file, "include-system-site-packages = true").unwrap();
+        writeln!(file, "version = 3.12.4").unwrap();
+        let site3 = create_site(root, "srv/app/.venv/lib/python3.12/site-packages");
+        let mut file = File::create(root.join("srv/app/.venv/pyvenv.cfg")).unwrap();
+        writeln!(file, "home = /usr/local/bin").unwrap();
+        writeln!(file, "include-system-site-packages = false").unwrap();
+
+        let post = find_root_exe_to_sites(root);
+        assert_eq!(post.len(), 3);
+        assert_eq!(
+            post[&root.join("opt/venv/bin/python3.12")],
+            vec![
+                PathShared::from_path_buf(site2),
+                PathShared::from_path_buf(site1.clone())
+            ]
+        );
+        assert_eq!(
+            post[&root.join("srv/app/.venv/bin/python3.12")],
+            vec![PathShared::from_path_buf(site3)]
+        );
+        assert_eq!(
+            post[&root.join("usr/local/bin/python3.12")],
+            vec![PathShared::from_path_buf(site1)]
+        );
+    }
+
+    #[test]
+    fn test_find_root_exe_to_sites_c() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        let site1 = create_site(root, "usr/lib/python3/dist-packages");
+        let site2 = create_site(root, "usr/lib/python3.10/dist-packages");
+        let site3 = create_site(root, "usr/local/lib/python3.10/dist-packages");
+        let site4 = create_site(root, "usr/lib/python3.12/dist-packages");
+        fs::create_dir_all(root.join("usr/bin")).unwrap();
+        let _ = File::create(root.join("usr/bin/python3.10")).unwrap();
+
+        let post = find_root_exe_to_sites(root);
+        assert_eq!(post.len(), 2);
+        assert_eq!(
+            post[&root.join("usr/bin/python3.10")],
+            vec![
+                PathShared::from_path_buf(site3),
+                PathShared::from_path_buf(site1.clone()),
+                PathShared::from_path_buf(site2),
+            ]
+        );
+        assert_eq!(
+            post[&root.join("usr/bin/python3.12")],
+            vec![
+                PathShared::from_path_buf(site1),
+                PathShared::from_path_buf(site4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_name_to_python_version_a() {
+        assert_eq!(
+            name_to_python_version("python3.12"),
+            Some("3.12".to_string())
+        );
+        assert_eq!(name_to_python_version("pypy3.10"), Some("3.10".to_string()));
+        assert_eq!(
+            name_to_python_version("python3.12.exe"),
+            Some("3.12".to_string())
+        );
+        assert_eq!(name_to_python_version("python3"), None);
+    }
+
+    #[test]
+    fn test_exe_to_sites_static_a() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        let site_base = create_site(root, "base/lib/python3.12/site-packages");
+        let _ = create_site(root, "base/lib/python3.11/site-packages");
+        fs::create_dir_all(root.join("base/bin")).unwrap();
+        let site_venv = create_site(root, "venv/lib/python3.12/site-packages");
+        fs::create_dir_all(root.join("venv/bin")).unwrap();
+        let mut file = File::create(root.join("venv/pyvenv.cfg")).unwrap();
+        writeln!(file, "home = {}", root.join("base/bin").display()).unwrap();
+        writeln!(file, "include-system-site-packages = false").unwrap();
+        writeln!(file, "version = 3.12.4").unwrap();
+
+        // the version is read from pyvenv.cfg
+        assert_eq!(
+            exe_to_sites_static(&root.join("venv/bin/python"), false),
+            vec![PathShared::from_path_buf(site_venv.clone())]
+        );
+        // the version is read from the name of the executable
+        assert_eq!(
+            exe_to_sites_static(&root.join("base/bin/python3.12"), false),
+            vec![PathShared::from_path_buf(site_base.clone())]
+        );
+        assert_eq!(
+            exe_to_sites_static(&root.join("base/bin/python3"), false).len(),
+            2
+        );
+
+        let mut file = File::create(root.join("venv/pyvenv.cfg")).unwrap();
+        writeln!(file, "home = {}", root.join("base/bin").display()).unwrap();
+        writeln!(file, "include-system-site-packages = true").unwrap();
+        writeln!(file, "version_info = 3.12.4.final.0").unwrap();
+        assert_eq!(
+            exe_to_sites_static(&root.join("venv/bin/python"), false),
+            vec![
+                PathShared::from_path_buf(site_venv),
+                PathShared::from_path_buf(site_base)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exe_to_sites_static_b() {
+        let temp_dir = tempdir().unwrap();
+        let root = temp_dir.path();
+        let usr = root.join("usr");
+        let site1 = create_site(root, "usr/local/lib/python3.10/dist-packages");
+        let _ = create_site(root, "usr/local/lib/python3.10/site-packages");
+        let site2 = create_site(root, "usr/lib/python3/dist-packages");
+        let site3 = create_site(root, "usr/lib/python3.10/dist-packages");
+        fs::create_dir_all(usr.join("bin")).unwrap();
+        assert_eq!(
+            exe_to_sites_static(&usr.join("bin/python3.10"), false),
+            vec![
+                PathShared::from_path_buf(site1),
+                PathShared::from_path_buf(site2),
+                PathShared::from_path_buf(site3),
+            ]
+        );
+    }
+}
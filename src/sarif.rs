@@ -0,0 +1,303 @@
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::util::name_to_key;
+
+// see https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+const SARIF_VERSION: &str = "2.1.0";
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+// Given the content of a bound requirements file, return the 1-based number of the first line that declares the package `key`. Lines are matched by their leading name, after removing quotes, such that requirements files as well as TOML arrays and tables are supported.
+pub(crate) fn find_requirement_line(content: &str, key: &str) -> Option<usize> {
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim().trim_start_matches(['"', '\'']);
+        if line.starts_with('#') {
+            continue;
+        }
+        let name: String = line
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            .collect();
+        if !name.is_empty() && name_to_key(&name) == key {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+// Given bound requirements files, return the path and content of each; a file that cannot be read has no content.
+pub(crate) fn read_bounds(bounds: &[PathBuf]) -> Vec<(&Path, String)> {
+    bounds
+        .iter()
+        .map(|bound| {
+            (
+                bound.as_path(),
+                fs::read_to_string(bound).unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+// Given bounds with their content, return the first bound that declares the package `key`, with the line of the declaration; if no bound declares it, return the first bound without a line.
+pub(crate) fn locate_requirement<'a>(
+    bounds: &[(&'a Path, String)],
+    key: Option<&str>,
+) -> Option<(&'a Path, Option<usize>)> {
+    key.and_then(|key| {
+        bounds.iter().find_map(|(bound, content)| {
+            find_requirement_line(content, key).map(|line| (*bound, Some(line)))
+        })
+    })
+    .or_else(|| bounds.first().map(|(bound, _)| (*bound, None)))
+}
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Serialize)]
+struct SarifMessage {
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRule {
+    id: String,
+    short_description: SarifMessage,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_uri: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifDriver {
+    name: String,
+    version: String,
+    information_uri: String,
+    rules: Vec<SarifRule>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifTool {
+    driver: SarifDriver,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRegion {
+    start_line: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifArtifactLocation {
+    uri: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation {
+    artifact_location: SarifArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<SarifRegion>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation {
+    physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: String,
+    level: String,
+    message: SarifMessage,
+    locations: Vec<SarifLocation>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifRun {
+    tool: SarifTool,
+    results: Vec<SarifResult>,
+}
+
+//------------------------------------------------------------------------------
+/// A finding to be reported in a SARIF log. The `key` of the package, if defined, is used to find the line of the bound requirements that produced the finding.
+#[derive(Debug)]
+pub(crate) struct SarifFinding {
+    pub(crate) rule_id: String,
+    pub(crate) rule_description: String,
+    pub(crate) help_uri: Option<String>,
+    pub(crate) level: &'static str,
+    pub(crate) message: String,
+    pub(crate) key: Option<String>,
+}
+
+/// A SARIF log of findings, each located in a bound requirements file.
+#[derive(Debug, Serialize)]
+pub(crate) struct SarifLog {
+    #[serde(rename = "$schema")]
+    schema: String,
+    version: String,
+    runs: Vec<SarifRun>,
+}
+
+impl SarifLog {
+    /// Create a log from findings, locating each in the first of the files at `bounds` that declares its package; if no file can be read, or a finding's package is not found, the finding is located at the first file without a line.
+    pub(crate) fn from_findings(findings: Vec<SarifFinding>, bounds: &[PathBuf]) -> Self {
+        let contents = read_bounds(bounds);
+
+        let mut rules: Vec<SarifRule> = Vec::new();
+        let mut results = Vec::new();
+        for finding in findings {
+            if !rules.iter().any(|r| r.id == finding.rule_id) {
+                rules.push(SarifRule {
+                    id: finding.rule_id.clone(),
+                    short_description: SarifMessage {
+                        text: finding.rule_description,
+                    },
+                    help_uri: finding.help_uri,
+                });
+            }
+            let (uri, region) =
+                match locate_requirement(&contents, finding.key.as_deref()) {
+                    Some((bound, line)) => (
+                        bound.to_string_lossy().replace('\\', "/"),
+                        line.map(|start_line| SarifRegion { start_line }),
+                    ),
+                    None => (String::new(), None),
+                };
+            results.push(SarifResult {
+                rule_id: finding.rule_id,
+                level: finding.level.to_string(),
+                message: SarifMessage {
+                    text: finding.message,
+                },
+                locations: vec![SarifLocation {
+                    physical_location: SarifPhysicalLocation {
+                        artifact_location: SarifArtifactLocation { uri },
+                        region,
+                    },
+                }],
+            });
+        }
+        SarifLog {
+            schema: SARIF_SCHEMA.to_string(),
+            version: SARIF_VERSION.to_string(),
+            runs: vec![SarifRun {
+                tool: SarifTool {
+                    driver: SarifDriver {
+                        name: env!("CARGO_PKG_NAME").to_string(),
+                        version: env!("CARGO_PKG_VERSION").to_string(),
+                        information_uri: env!("CARGO_PKG_HOMEPAGE").to_string(),
+                        rules,
+                    },
+                },
+                results,
+            }],
+        }
+    }
+
+    //--------------------------------------------------------------------------
+
+    fn to_writer<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writeln!(writer)
+    }
+
+    pub(crate) fn to_file(&self, file_path: &PathBuf) -> io::Result<()> {
+        let file = File::create(file_path)?;
+        self.to_writer(file)
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_requirement_line_a() {
+        let content =
+            "# numpy\nrequests>=2.31\n\nNumPy==2.1.0 ; python_version >= '3.10'\n";
+        assert_eq!(find_requirement_line(content, "numpy"), Some(4));
+        assert_eq!(find_requirement_line(content, "requests"), Some(2));
+        assert_eq!(find_requirement_line(content, "flask"), None);
+    }
+
+    #[test]
+    fn test_find_requirement_line_b() {
+        let content = "[project]\ndependencies = [\n    \"static_frame>=2\",\n]\n[tool.poetry.dependencies]\nzope-interface = \"^6\"\n";
+        assert_eq!(find_requirement_line(content, "static_frame"), Some(3));
+        assert_eq!(find_requirement_line(content, "zope_interface"), Some(6));
+    }
+
+    #[test]
+    fn test_locate_requirement_a() {
+        let bounds = vec![
+            (Path::new("base.txt"), "numpy>=1.26\nsix\n".to_string()),
+            (Path::new("prod.txt"), "gunicorn\nnumpy<2\n".to_string()),
+        ];
+        assert_eq!(
+            locate_requirement(&bounds, Some("numpy")),
+            Some((Path::new("base.txt"), Some(1)))
+        );
+        assert_eq!(
+            locate_requirement(&bounds, Some("gunicorn")),
+            Some((Path::new("prod.txt"), Some(1)))
+        );
+        assert_eq!(
+            locate_requirement(&bounds, Some("flask")),
+            Some((Path::new("base.txt"), None))
+        );
+        assert_eq!(locate_requirement(&[], Some("flask")), None);
+    }
+
+    #[test]
+    fn test_sarif_log_a() {
+        let dir = tempdir().unwrap();
+        let bound = dir.path().join("requirements.txt");
+        fs::write(&bound, "requests>=2.31\nnumpy==2.1.0\n").unwrap();
+        let findings = vec![
+            SarifFinding {
+                rule_id: "Misdefined".to_string(),
+                rule_description:
+                    "An installed package does not satisfy its requirement.".to_string(),
+                help_uri: None,
+                level: "error",
+                message: "numpy-1.19.3 does not satisfy numpy==2.1.0".to_string(),
+                key: Some("numpy".to_string()),
+            },
+            SarifFinding {
+                rule_id: "Unrequired".to_string(),
+                rule_description: "An installed package is not required.".to_string(),
+                help_uri: None,
+                level: "error",
+                message: "flask-3.0.3 is not required".to_string(),
+                key: Some("flask".to_string()),
+            },
+        ];
+        let log = SarifLog::from_findings(findings, std::slice::from_ref(&bound));
+        let value = serde_json::to_value(&log).unwrap();
+        assert_eq!(value["version"], "2.1.0");
+        let run = &value["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "fetter");
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 2);
+        let location = &run["results"][0]["locations"][0]["physicalLocation"];
+        assert_eq!(
+            location["artifactLocation"]["uri"],
+            bound.to_string_lossy().as_ref()
+        );
+        assert_eq!(location["region"]["startLine"], 2);
+        // packages not found in the bound have no region
+        let location = &run["results"][1]["locations"][0]["physicalLocation"];
+        assert!(location.get("region").is_none());
+    }
+}
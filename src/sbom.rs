@@ -0,0 +1,35 @@
+use std::io;
+use std::path::PathBuf;
+
+use crate::sbom_cyclonedx::CycloneDXBom;
+use crate::sbom_spdx::SPDXDocument;
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum SbomFormat {
+    CycloneDX,
+    Spdx,
+}
+
+/// A software bill of materials in one of the supported formats.
+#[derive(Debug)]
+pub(crate) enum Sbom {
+    CycloneDX(CycloneDXBom),
+    Spdx(SPDXDocument),
+}
+
+impl Sbom {
+    pub(crate) fn to_file(&self, file_path: &PathBuf) -> io::Result<()> {
+        match self {
+            Sbom::CycloneDX(bom) => bom.to_file(file_path),
+            Sbom::Spdx(doc) => doc.to_file(file_path),
+        }
+    }
+
+    pub(crate) fn to_stdout(&self) -> io::Result<()> {
+        match self {
+            Sbom::CycloneDX(bom) => bom.to_stdout(),
+            Sbom::Spdx(doc) => doc.to_stdout(),
+        }
+    }
+}
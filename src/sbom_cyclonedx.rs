@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::package::Package;
+use crate::path_shared::PathShared;
+
+// see https://cyclonedx.org/docs/1.5/json/
+
+const SPEC_VERSION: &str = "1.5";
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Serialize)]
+struct CycloneDXTool {
+    #[serde(rename = "type")]
+    tool_type: String,
+    name: String,
+    version: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CycloneDXTools {
+    components: Vec<CycloneDXTool>,
+}
+
+#[derive(Debug, Serialize)]
+struct CycloneDXMetadata {
+    timestamp: String,
+    tools: CycloneDXTools,
+}
+
+#[derive(Debug, Serialize)]
+struct CycloneDXExternalReference {
+    #[serde(rename = "type")]
+    reference_type: String,
+    url: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CycloneDXProperty {
+    name: String,
+    value: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CycloneDXComponent {
+    #[serde(rename = "type")]
+    component_type: String,
+    #[serde(rename = "bom-ref")]
+    bom_ref: String,
+    name: String,
+    version: String,
+    purl: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    external_references: Vec<CycloneDXExternalReference>,
+    properties: Vec<CycloneDXProperty>,
+}
+
+impl CycloneDXComponent {
+    fn from_package_sites(package: &Package, sites: &[PathShared]) -> Self {
+        let purl = package.to_purl();
+        // a VCS install is recorded with the pinned commit; other direct URLs are distributions or local directories
+        let external_references = match &package.direct_url {
+            Some(durl) => match durl.to_vcs_url() {
+                Some(url) => vec![CycloneDXExternalReference {
+                    reference_type: "vcs".to_string(),
+                    url,
+                }],
+                None => vec![CycloneDXExternalReference {
+                    reference_type: "distribution".to_string(),
+                    url: durl.get_url(),
+                }],
+            },
+            None => Vec::new(),
+        };
+        let properties = sites
+            .iter()
+            .map(|site| CycloneDXProperty {
+                name: "fetter:site".to_string(),
+                value: site.to_string(),
+            })
+            .collect();
+        CycloneDXComponent {
+            component_type: "library".to_string(),
+            bom_ref: purl.clone(),
+            name: package.name.clone(),
+            version: package.version.to_string(),
+            purl,
+            external_references,
+            properties,
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+/// A CycloneDX software bill of materials of all discovered packages.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CycloneDXBom {
+    bom_format: String,
+    spec_version: String,
+    version: u32,
+    metadata: CycloneDXMetadata,
+    components: Vec<CycloneDXComponent>,
+}
+
+impl CycloneDXBom {
+    /// Create a BOM from a mapping of Package to sites. The `timestamp` is expected to be an ISO 8601 string.
+    pub(crate) fn from_package_to_sites(
+        package_to_sites: &HashMap<Package, Vec<PathShared>>,
+        timestamp: String,
+    ) -> Self {
+        let mut packages: Vec<&Package> = package_to_sites.keys().collect();
+        packages.sort();
+
+        let mut bom_refs: HashSet<String> = HashSet::new();
+        let mut components = Vec::new();
+        for package in packages {
+            let mut component = CycloneDXComponent::from_package_sites(
+                package,
+                &package_to_sites[package],
+            );
+            // bom-ref values must be unique; Packages that differ only by a non-VCS direct URL share a PURL
+            let mut i = 1;
+            while bom_refs.contains(&component.bom_ref) {
+                component.bom_ref = format!("{}#{}", component.purl, i);
+                i += 1;
+            }
+            bom_refs.insert(component.bom_ref.clone());
+            components.push(component);
+        }
+        let tool = CycloneDXTool {
+            tool_type: "application".to_string(),
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        };
+        CycloneDXBom {
+            bom_format: "CycloneDX".to_string(),
+            spec_version: SPEC_VERSION.to_string(),
+            version: 1,
+            metadata: CycloneDXMetadata {
+                timestamp,
+                tools: CycloneDXTools {
+                    components: vec![tool],
+                },
+            },
+            components,
+        }
+    }
+
+    //--------------------------------------------------------------------------
+
+    fn to_writer<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writeln!(writer)
+    }
+
+    pub(crate) fn to_file(&self, file_path: &PathBuf) -> io::Result<()> {
+        let file = File::create(file_path)?;
+        self.to_writer(file)
+    }
+
+    pub(crate) fn to_stdout(&self) -> io::Result<()> {
+        let stdout = io::stdout();
+        let handle = stdout.lock();
+        self.to_writer(handle)
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package_durl::DirectURL;
+    use crate::scan_fs::ScanFS;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn to_bom(packages: Vec<Package>) -> CycloneDXBom {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        CycloneDXBom::from_package_to_sites(
+            &sfs.package_to_sites,
+            "2024-10-14T08:30:00Z".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_from_package_to_sites_a() {
+        let bom = to_bom(vec![
+            Package::from_name_version_durl("numpy", "1.19.3", None).unwrap(),
+            Package::from_name_version_durl("Flask", "1.1.3", None).unwrap(),
+        ]);
+        let value = serde_json::to_value(&bom).unwrap();
+        assert_eq!(value["bomFormat"], "CycloneDX");
+        assert_eq!(value["specVersion"], "1.5");
+        assert_eq!(value["metadata"]["timestamp"], "2024-10-14T08:30:00Z");
+        assert_eq!(
+            value["metadata"]["tools"]["components"][0]["name"],
+            "fetter"
+        );
+        assert_eq!(value["components"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            serde_json::to_string(&value["components"][0]).unwrap(),
+            r#"{"bom-ref":"pkg:pypi/flask@1.1.3","name":"Flask","properties":[{"name":"fetter:site","value":"/usr/lib/python3/site-packages"}],"purl":"pkg:pypi/flask@1.1.3","type":"library","version":"1.1.3"}"#
+        );
+    }
+
+    #[test]
+    fn test_from_package_to_sites_b() {
+        let durl = DirectURL::from_url_vcs_cid(
+            "https://github.com/uqfoundation/dill.git".to_string(),
+            Some("git".to_string()),
+            Some("a0a8e86976708d0436eec5c8f7d25329da727cb5".to_string()),
+        )
+        .unwrap();
+        let bom = to_bom(vec![Package::from_name_version_durl(
+            "dill",
+            "0.3.8",
+            Some(durl),
+        )
+        .unwrap()]);
+        let value = serde_json::to_value(&bom).unwrap();
+        assert_eq!(
+            value["components"][0]["externalReferences"].to_string(),
+            r#"[{"type":"vcs","url":"git+https://github.com/uqfoundation/dill.git@a0a8e86976708d0436eec5c8f7d25329da727cb5"}]"#
+        );
+    }
+
+    #[test]
+    fn test_from_package_to_sites_c() {
+        let durl1 = DirectURL::from_url_vcs_cid(
+            "https://example.com/six-1.16.0-py2.py3-none-any.whl".to_string(),
+            None,
+            None,
+        )
+        .unwrap();
+        let bom = to_bom(vec![
+            Package::from_name_version_durl("six", "1.16.0", None).unwrap(),
+            Package::from_name_version_durl("six", "1.16.0", Some(durl1)).unwrap(),
+        ]);
+        let value = serde_json::to_value(&bom).unwrap();
+        let refs: HashSet<String> = value["components"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["bom-ref"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(refs.len(), 2);
+        assert!(refs.contains("pkg:pypi/six@1.16.0"));
+    }
+
+    #[test]
+    fn test_to_file_a() {
+        let bom = to_bom(vec![Package::from_name_version_durl(
+            "numpy", "1.19.3", None,
+        )
+        .unwrap()]);
+        let dir = tempdir().unwrap();
+        let fp = dir.path().join("sbom.json");
+        bom.to_file(&fp).unwrap();
+        let value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(fp).unwrap()).unwrap();
+        assert_eq!(value["components"][0]["purl"], "pkg:pypi/numpy@1.19.3");
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::package::Package;
+use crate::package_metadata::PackageMetadata;
+use crate::path_shared::PathShared;
+use crate::util::hash_str;
+
+// see https://spdx.github.io/spdx-spec/v2.3/
+
+const SPDX_VERSION: &str = "SPDX-2.3";
+const NOASSERTION: &str = "NOASSERTION";
+const DOCUMENT_ID: &str = "SPDXRef-DOCUMENT";
+
+// SPDX identifiers may only contain letters, numbers, periods, and hyphens.
+fn to_spdx_id_part(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Serialize)]
+struct SPDXCreationInfo {
+    created: String,
+    creators: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SPDXExternalRef {
+    reference_category: String,
+    reference_type: String,
+    reference_locator: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SPDXPackage {
+    name: String,
+    #[serde(rename = "SPDXID")]
+    spdx_id: String,
+    version_info: String,
+    download_location: String,
+    files_analyzed: bool,
+    license_concluded: String,
+    license_declared: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    license_comments: Option<String>,
+    copyright_text: String,
+    external_refs: Vec<SPDXExternalRef>,
+}
+
+impl SPDXPackage {
+    fn from_package_sites(package: &Package, sites: &[PathShared], index: usize) -> Self {
+        // a VCS install is recorded with the pinned commit, which SPDX accepts as a download location
+        let download_location = match &package.direct_url {
+            Some(durl) => durl.to_vcs_url().unwrap_or_else(|| durl.get_url()),
+            None => NOASSERTION.to_string(),
+        };
+        let metadata = PackageMetadata::from_package_sites(package, sites);
+        let license_declared = metadata
+            .as_ref()
+            .and_then(|md| md.to_spdx_license())
+            .unwrap_or_else(|| NOASSERTION.to_string());
+        let license_comments = metadata.as_ref().and_then(|md| md.to_license_text());
+
+        SPDXPackage {
+            name: package.name.clone(),
+            spdx_id: format!(
+                "SPDXRef-Package-{}-{}",
+                index,
+                to_spdx_id_part(&package.name)
+            ),
+            version_info: package.version.to_string(),
+            download_location,
+            files_analyzed: false,
+            license_concluded: NOASSERTION.to_string(),
+            license_declared,
+            license_comments,
+            copyright_text: NOASSERTION.to_string(),
+            external_refs: vec![SPDXExternalRef {
+                reference_category: "PACKAGE-MANAGER".to_string(),
+                reference_type: "purl".to_string(),
+                reference_locator: package.to_purl(),
+            }],
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SPDXRelationship {
+    spdx_element_id: String,
+    relationship_type: String,
+    related_spdx_element: String,
+}
+
+//------------------------------------------------------------------------------
+/// An SPDX document of all discovered packages.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SPDXDocument {
+    spdx_version: String,
+    data_license: String,
+    #[serde(rename = "SPDXID")]
+    spdx_id: String,
+    name: String,
+    document_namespace: String,
+    creation_info: SPDXCreationInfo,
+    packages: Vec<SPDXPackage>,
+    relationships: Vec<SPDXRelationship>,
+}
+
+impl SPDXDocument {
+    /// Create a document from a mapping of Package to sites; license information is read from each Package's METADATA file. The `timestamp` is expected to be an ISO 8601 string.
+    pub(crate) fn from_package_to_sites(
+        package_to_sites: &HashMap<Package, Vec<PathShared>>,
+        timestamp: String,
+    ) -> Self {
+        let mut packages: Vec<&Package> = package_to_sites.keys().collect();
+        packages.sort();
+
+        let spdx_packages: Vec<SPDXPackage> = packages
+            .iter()
+            .enumerate()
+            .map(|(i, package)| {
+                SPDXPackage::from_package_sites(package, &package_to_sites[*package], i)
+            })
+            .collect();
+
+        let relationships = spdx_packages
+            .iter()
+            .map(|p| SPDXRelationship {
+                spdx_element_id: DOCUMENT_ID.to_string(),
+                relationship_type: "DESCRIBES".to_string(),
+                related_spdx_element: p.spdx_id.clone(),
+            })
+            .collect();
+
+        // the namespace must be unique per document; derive it from the creation time and contents
+        let mut hash_input = timestamp.clone();
+        for package in packages.iter() {
+            hash_input.push('\n');
+            hash_input.push_str(&package.to_purl());
+        }
+        let name = format!("{}-scan", env!("CARGO_PKG_NAME"));
+        let document_namespace = format!(
+            "https://spdx.org/spdxdocs/{}-{}",
+            name,
+            hash_str(&hash_input)
+        );
+
+        SPDXDocument {
+            spdx_version: SPDX_VERSION.to_string(),
+            data_license: "CC0-1.0".to_string(),
+            spdx_id: DOCUMENT_ID.to_string(),
+            name,
+            document_namespace,
+            creation_info: SPDXCreationInfo {
+                created: timestamp,
+                creators: vec![format!(
+                    "Tool: {}-{}",
+                    env!("CARGO_PKG_NAME"),
+                    env!("CARGO_PKG_VERSION")
+                )],
+            },
+            packages: spdx_packages,
+            relationships,
+        }
+    }
+
+    //--------------------------------------------------------------------------
+
+    fn to_writer<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writeln!(writer)
+    }
+
+    pub(crate) fn to_file(&self, file_path: &PathBuf) -> io::Result<()> {
+        let file = File::create(file_path)?;
+        self.to_writer(file)
+    }
+
+    pub(crate) fn to_stdout(&self) -> io::Result<()> {
+        let stdout = io::stdout();
+        let handle = stdout.lock();
+        self.to_writer(handle)
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package_durl::DirectURL;
+    use crate::scan_fs::ScanFS;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_to_spdx_id_part_a() {
+        assert_eq!(to_spdx_id_part("zope_interface"), "zope-interface");
+        assert_eq!(to_spdx_id_part("numpy"), "numpy");
+    }
+
+    #[test]
+    fn test_from_package_to_sites_a() {
+        let dir = tempdir().unwrap();
+        let fp_di = dir.path().join("flask-3.0.3.dist-info");
+        fs::create_dir(&fp_di).unwrap();
+        fs::write(
+            fp_di.join("METADATA"),
+            "Name: Flask\nVersion: 3.0.3\nClassifier: License :: OSI Approved :: BSD License\nLicense-Expression: BSD-3-Clause\n",
+        )
+        .unwrap();
+
+        let exe = PathBuf::from("/usr/bin/python3");
+        let packages = vec![
+            Package::from_name_version_durl("Flask", "3.0.3", None).unwrap(),
+            Package::from_name_version_durl("numpy", "2.1.2", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, dir.path().to_path_buf(), packages)
+            .unwrap();
+        let doc = SPDXDocument::from_package_to_sites(
+            &sfs.package_to_sites,
+            "2024-10-14T08:30:00Z".to_string(),
+        );
+        let value = serde_json::to_value(&doc).unwrap();
+        assert_eq!(value["spdxVersion"], "SPDX-2.3");
+        assert_eq!(value["SPDXID"], "SPDXRef-DOCUMENT");
+        assert_eq!(value["creationInfo"]["created"], "2024-10-14T08:30:00Z");
+        assert!(value["documentNamespace"]
+            .as_str()
+            .unwrap()
+            .starts_with("https://spdx.org/spdxdocs/fetter-scan-"));
+
+        let p0 = &value["packages"][0];
+        assert_eq!(p0["SPDXID"], "SPDXRef-Package-0-Flask");
+        assert_eq!(p0["licenseDeclared"], "BSD-3-Clause");
+        assert_eq!(
+            p0["licenseComments"],
+            "License :: OSI Approved :: BSD License"
+        );
+        assert_eq!(
+            p0["externalRefs"][0]["referenceLocator"],
+            "pkg:pypi/flask@3.0.3"
+        );
+        let p1 = &value["packages"][1];
+        assert_eq!(p1["licenseDeclared"], "NOASSERTION");
+        assert_eq!(p1["downloadLocation"], "NOASSERTION");
+        assert!(p1.get("licenseComments").is_none());
+
+        assert_eq!(
+            value["relationships"][1].to_string(),
+            r#"{"relatedSpdxElement":"SPDXRef-Package-1-numpy","relationshipType":"DESCRIBES","spdxElementId":"SPDXRef-DOCUMENT"}"#
+        );
+    }
+
+    #[test]
+    fn test_from_package_to_sites_b() {
+        let durl = DirectURL::from_url_vcs_cid(
+            "https://github.com/uqfoundation/dill.git".to_string(),
+            Some("git".to_string()),
+            Some("a0a8e86976708d0436eec5c8f7d25329da727cb5".to_string()),
+        )
+        .unwrap();
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages =
+            vec![Package::from_name_version_durl("dill", "0.3.8", Some(durl)).unwrap()];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let doc = SPDXDocument::from_package_to_sites(
+            &sfs.package_to_sites,
+            "2024-10-14T08:30:00Z".to_string(),
+        );
+        let value = serde_json::to_value(&doc).unwrap();
+        assert_eq!(
+            value["packages"][0]["downloadLocation"],
+            "git+https://github.com/uqfoundation/dill.git@a0a8e86976708d0436eec5c8f7d25329da727cb5"
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::env;
 use std::fs;
 use std::fs::File;
 use std::io;
@@ -7,34 +8,92 @@ use std::io::Read;
 use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
-use std::process::Command;
 use std::time::Duration;
+use std::time::SystemTime;
 
 use rayon::prelude::*;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tracing::debug;
+use tracing::debug_span;
+use tracing::warn;
 
 use crate::audit_report::AuditReport;
+use crate::ban_policy::BanPolicy;
+use crate::check_report::CheckReport;
+use crate::conda_meta::exe_to_conda_meta;
+use crate::conda_meta::CondaRecord;
 use crate::count_report::CountReport;
+use crate::dep_graph::DepGraph;
 use crate::dep_manifest::DepManifest;
+use crate::dep_marker::MarkerEnv;
 use crate::dep_spec::DepOperator;
 use crate::dep_spec::DepSpec;
+use crate::diff_report::DiffReport;
+use crate::doctor_report::DoctorReport;
+use crate::error::FetterError;
 use crate::exe_search::find_exe;
+use crate::exe_search::find_exe_glob;
+use crate::exe_search::find_exe_pyenv;
+use crate::exe_search::find_exe_roots;
+use crate::exe_search::is_exe_glob;
+use crate::exe_search::pyenv_shim_resolve;
+use crate::git_client::GitClient;
+use crate::git_client::GitClientLive;
+use crate::interpreter_report::Interpreter;
+use crate::interpreter_report::InterpreterReport;
+use crate::license_policy::LicensePolicy;
+use crate::map_report::MapReport;
+use crate::outdated_report::OutdatedReport;
+use crate::package::zip_to_packages;
 use crate::package::Package;
 use crate::package_match::match_str;
+use crate::package_match::PackageFilter;
+use crate::package_metadata::PackageMetadata;
 use crate::path_shared::PathShared;
+use crate::pypi_json::path_pypi_cache;
+use crate::pypi_json::query_pypi_projects;
+use crate::pypi_json::PyPIProject;
+use crate::root_search::exe_to_sites_static;
+use crate::root_search::find_root_exe_to_sites;
+use crate::root_search::site_to_python_version;
+use crate::root_search::PYPACKAGES;
+use crate::sbom::Sbom;
+use crate::sbom::SbomFormat;
+use crate::sbom_cyclonedx::CycloneDXBom;
+use crate::sbom_spdx::SPDXDocument;
 use crate::scan_report::ScanReport;
+use crate::scripts_report::ScriptsReport;
+use crate::site_cache::SiteCache;
+use crate::size_report::SizeReport;
+use crate::spin::PROGRESS;
+use crate::stray_report::StrayReport;
+use crate::tree_report::TreeReport;
+use crate::unpack_report::UnpackFilesReport;
 use crate::unpack_report::UnpackReport;
 use crate::ureq_client::UreqClientLive;
 use crate::util::exe_path_normalize;
 use crate::util::hash_paths;
 use crate::util::path_cache;
 use crate::util::path_is_component;
+use crate::util::path_mtime;
+use crate::util::path_normalize;
 use crate::util::path_within_duration;
+use crate::util::py_command;
+use crate::util::py_output;
+use crate::util::time_to_iso8601;
 use crate::util::ResultDynError;
 use crate::util::DURATION_0;
+use crate::validation_report::ValidationExplain;
 use crate::validation_report::ValidationFlags;
 use crate::validation_report::ValidationRecord;
 use crate::validation_report::ValidationReport;
+use crate::verify_report::validate_record;
+use crate::verify_report::VerifyReport;
+use crate::version_spec::VersionSpec;
+use crate::warning_report::ScanWarning;
+use crate::warning_report::WarningReport;
+use crate::wheel_tag::WheelTag;
+use crate::zip_index::is_zip_archive;
 
 //------------------------------------------------------------------------------
 #[derive(Debug, Copy, Clone)]
@@ -42,65 +101,288 @@ pub(crate) enum Anchor {
     Lower,
     Upper,
     Both,
+    Exact,
+    Compatible,
 }
 
 //------------------------------------------------------------------------------
-const PY_SITE_PACKAGES: &str = "import site;print(site.ENABLE_USER_SITE);print(\"\\n\".join(site.getsitepackages()));print(site.getusersitepackages())";
-
-/// Given a path to a Python binary, call out to Python to get all known site packages; some site packages may not exist; we do not filter them here. This will include "dist-packages" on Linux. If `force_usite` is false, we use ENABLE_USER_SITE to determine if we should include the user site packages; if `force_usite` is true, we always include usite.
-fn get_site_package_dirs(executable: &Path, force_usite: bool) -> Vec<PathShared> {
-    match Command::new(executable)
-        .arg("-c")
-        .arg(PY_SITE_PACKAGES)
-        .output()
-    {
-        Ok(output) => {
-            let mut paths = Vec::new();
-            let mut usite_enabled = false;
-
-            let lines = std::str::from_utf8(&output.stdout)
-                .expect("Failed to convert to UTF-8")
-                .trim()
-                .lines();
-            for (i, line) in lines.enumerate() {
-                if i == 0 {
-                    usite_enabled = line.trim() == "True";
-                } else {
-                    paths.push(PathShared::from_str(line.trim()));
-                }
+const PY_SITE_PACKAGES: &str = "import site,sys;print(site.ENABLE_USER_SITE);print(\"%d.%d\" % sys.version_info[:2]);print(\"\\n\".join(site.getsitepackages()));print(site.getusersitepackages())";
+
+/// Given a path to a Python binary, call out to Python to get all known site packages; some site packages may not exist; we do not filter them here. This will include "dist-packages" on Linux. Directories of `sys.path` with installed packages, as added with `PYTHONPATH` or `.pth` files, are also included, as is the PEP 582 `__pypackages__/X.Y/lib` directory of the project of the current directory for the version of that Python. If `force_usite` is false, we use ENABLE_USER_SITE to determine if we should include the user site packages; if `force_usite` is true, we always include usite.
+fn get_site_package_dirs(
+    executable: &Path,
+    force_usite: bool,
+) -> Result<Vec<PathShared>, FetterError> {
+    let output = py_output(
+        py_command(executable, false)
+            .arg("-c")
+            .arg(PY_SITE_PACKAGES),
+    )
+    .map_err(|source| FetterError::ExeRun {
+        executable: executable.to_path_buf(),
+        source,
+    })?;
+    let to_error = |message: String| FetterError::ExeOutput {
+        executable: executable.to_path_buf(),
+        message,
+    };
+    if !output.status.success() {
+        return Err(to_error(format!("exited with {}", output.status)));
+    }
+    let stdout = std::str::from_utf8(&output.stdout)
+        .map_err(|e| to_error(format!("output is not UTF-8: {}", e)))?;
+
+    let mut paths = Vec::new();
+    let mut usite_enabled = false;
+    let mut python_version = None;
+    for (i, line) in stdout.trim().lines().enumerate() {
+        if i == 0 {
+            usite_enabled = line.trim() == "True";
+        } else if i == 1 {
+            python_version = Some(line.trim().to_string());
+        } else {
+            paths.push(PathShared::from_str(line.trim()));
+        }
+    }
+    // if necessary, remove the usite
+    if !force_usite && !usite_enabled {
+        let _p = paths.pop();
+    }
+    // directories added to sys.path by PYTHONPATH or .pth files can also have installed packages
+    if let Some(sys_path) = get_sys_path(executable) {
+        let extra = sys_path_to_sites(&sys_path, &paths);
+        paths.extend(extra);
+    }
+    let pypackages = python_version
+        .zip(env::current_dir().ok())
+        .and_then(|(v, cwd)| find_pypackages_site(&cwd, &v));
+    if let Some(site) = pypackages {
+        if !paths.iter().any(|p| p.as_path() == site.as_path()) {
+            paths.push(PathShared::from_path_buf(site));
+        }
+    }
+    Ok(paths)
+}
+
+// Given the site packages found for each executable, return the sites of each executable and a warning for each executable that could not report its sites; such executables are retained without sites.
+fn partition_exe_to_sites(
+    exe_to_dirs: Vec<(PathBuf, Result<Vec<PathShared>, FetterError>)>,
+) -> (HashMap<PathBuf, Vec<PathShared>>, Vec<ScanWarning>) {
+    let mut exe_to_sites = HashMap::new();
+    let mut warnings = Vec::new();
+    for (exe, dirs) in exe_to_dirs {
+        let dirs = match dirs {
+            Ok(dirs) => {
+                debug!(executable = ?exe, sites = dirs.len(), "found sites");
+                dirs
             }
-            // if necessary, remove the usite
-            if !force_usite && !usite_enabled {
-                let _p = paths.pop();
+            Err(e) => {
+                debug!("{}", e);
+                warnings.push(e.to_warning());
+                Vec::new()
             }
-            paths
+        };
+        exe_to_sites.insert(exe, dirs);
+    }
+    (exe_to_sites, warnings)
+}
+
+/// Given a path to a Python binary, derive its site packages from `pyvenv.cfg` and layout without running it, including the PEP 582 `__pypackages__/X.Y/lib` directory of the project of the current directory for the version of its sites.
+fn get_site_package_dirs_static(executable: &Path, force_usite: bool) -> Vec<PathShared> {
+    let mut paths = exe_to_sites_static(executable, force_usite);
+    let pypackages = paths
+        .iter()
+        .find_map(|p| site_to_python_version(p.as_path()))
+        .zip(env::current_dir().ok())
+        .and_then(|(v, cwd)| find_pypackages_site(&cwd, &v));
+    if let Some(site) = pypackages {
+        paths.push(PathShared::from_path_buf(site));
+    }
+    paths
+}
+
+const PY_SYS_PATH: &str = "import sys;print(\"\\n\".join(sys.path))";
+
+/// Given a path to a Python binary, call out to Python to get the paths of `sys.path`, in order of import precedence. A zip archive, used in place of an executable, is not run.
+fn get_sys_path(executable: &Path) -> Option<Vec<PathBuf>> {
+    if is_zip_archive(executable) {
+        return None;
+    }
+    let output =
+        py_output(py_command(executable, false).arg("-c").arg(PY_SYS_PATH)).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let paths = std::str::from_utf8(&output.stdout)
+        .ok()?
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect();
+    Some(paths)
+}
+
+// Return true if the directory has the dist-info directory of an installed package. As the source directories of editable installs, added to `sys.path` by `.pth` files, often have egg-info directories of the same packages, egg-info directories are not considered. A zip archive has package metadata if it defines any Packages.
+fn has_package_metadata(dir: &Path) -> bool {
+    if is_zip_archive(dir) {
+        return !zip_to_packages(dir).is_empty();
+    }
+    fs::read_dir(dir).is_ok_and(|entries| {
+        entries
+            .flatten()
+            .any(|entry| entry.file_name().to_string_lossy().ends_with(".dist-info"))
+    })
+}
+
+/// Given the paths of `sys.path`, which include directories added by `PYTHONPATH` and by `.pth` files of sites, return the directories and zip archives not in `sites` that have installed packages. Directories without dist-info directories, such as those of the standard library, are excluded; zipped eggs within a site are Packages of that site and are also excluded.
+fn sys_path_to_sites(sys_path: &[PathBuf], sites: &[PathShared]) -> Vec<PathShared> {
+    let mut extra: Vec<PathShared> = Vec::new();
+    for path in sys_path {
+        if path.as_os_str().is_empty() || !path.is_absolute() {
+            continue; // the working directory of the call
         }
-        Err(e) => {
-            eprintln!("Failed to execute command with {:?}: {}", executable, e); // log this
-            Vec::with_capacity(0)
+        let is_known = sites
+            .iter()
+            .chain(extra.iter())
+            .any(|site| site.as_path() == path.as_path());
+        let is_site_egg = path.extension().is_some_and(|ext| ext == "egg")
+            && path
+                .parent()
+                .is_some_and(|p| sites.iter().any(|site| site.as_path() == p));
+        if !is_known
+            && !is_site_egg
+            && (path.is_dir() || is_zip_archive(path))
+            && has_package_metadata(path)
+        {
+            extra.push(PathShared::from_path_buf(path.clone()));
         }
     }
+    extra
+}
+
+// Given a project directory, return the nearest PEP 582 `__pypackages__` directory in it or in its ancestors.
+fn find_pypackages(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|d| d.join(PYPACKAGES))
+        .find(|d| d.is_dir())
+}
+
+/// Given a project directory and the major and minor version of a Python, return the `__pypackages__/X.Y/lib` directory of local packages, as installed by PDM for PEP 582, if it exists.
+fn find_pypackages_site(dir: &Path, python_version: &str) -> Option<PathBuf> {
+    let site = find_pypackages(dir)?.join(python_version).join("lib");
+    if site.is_dir() {
+        Some(site)
+    } else {
+        None
+    }
+}
+
+// Return the hash of the un-normalized exe inputs for cache lookup. As PEP 582 local packages are found relative to the current directory, the `__pypackages__` directory in use, if any, is included.
+fn hash_exes(exes: &[PathBuf], force_usite: bool) -> String {
+    let mut paths = exes.to_vec();
+    paths.extend(
+        env::current_dir()
+            .ok()
+            .and_then(|cwd| find_pypackages(&cwd)),
+    );
+    hash_paths(&paths, force_usite)
+}
+
+// Return the sites in order of their position in `sys.path`; sites not found in `sys.path` follow, in their original order.
+fn order_sites(sites: &[PathShared], sys_path: &[PathBuf]) -> Vec<PathShared> {
+    let mut ordered = sites.to_vec();
+    ordered.sort_by_key(|site| {
+        sys_path
+            .iter()
+            .position(|p| p.as_path() == site.as_path())
+            .unwrap_or(usize::MAX)
+    });
+    ordered
 }
 
-// Given a package directory, collect the name of all packages.
-fn get_packages(site_packages: &Path) -> Vec<Package> {
+// Given a conda-meta directory and the Packages found in the site packages of that environment, return the Packages recorded by conda. Records that installed a dist-info directory are related to the Package found from that dist-info; Python packages without a dist-info are returned as new Packages.
+fn get_conda_packages(
+    conda_meta: &Path,
+    site_packages: &HashSet<&Package>,
+) -> Vec<Package> {
     let mut packages = Vec::new();
-    if let Ok(entries) = fs::read_dir(site_packages) {
-        for entry in entries.flatten() {
-            let file_path = entry.path();
-            if let Some(package) = Package::from_file_path(&file_path) {
-                packages.push(package);
+    for record in CondaRecord::from_conda_meta(conda_meta) {
+        if !record.is_python_package() {
+            continue;
+        }
+        match record.to_dist_info_package() {
+            Some(package_di) => {
+                if let Some(package) = site_packages
+                    .iter()
+                    .find(|p| p.key == package_di.key && p.version == package_di.version)
+                {
+                    packages.push((*package).clone());
+                }
             }
+            None => packages.extend(record.to_package()),
         }
     }
     packages
 }
 
+// Given a package directory, collect the name of all packages. Entries are sorted before being parsed in parallel, such that packages are returned in a deterministic order. If the site is a zip archive, packages are read from its index. Sites reported by an executable need not exist, and have no packages; a site that exists but cannot be read is an error.
+fn get_packages(site_packages: &Path) -> Result<Vec<Package>, FetterError> {
+    if is_zip_archive(site_packages) {
+        return Ok(zip_to_packages(site_packages));
+    }
+    let mut file_paths: Vec<PathBuf> = match fs::read_dir(site_packages) {
+        Ok(entries) => entries.flatten().map(|entry| entry.path()).collect(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(source) => {
+            return Err(FetterError::SiteRead {
+                site: site_packages.to_path_buf(),
+                source,
+            })
+        }
+    };
+    file_paths.sort();
+    Ok(file_paths
+        .par_iter()
+        .filter_map(|file_path| Package::from_file_path(file_path))
+        .collect())
+}
+
 //------------------------------------------------------------------------------
+/// Options of a scan. As options might be added, create with `ScanFlags::default()` and set each with the corresponding `with_` method.
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct ScanFlags {
+    /// If set, the user site is included even if an executable does not report it.
+    pub force_usite: bool,
+    /// If set, the Packages of sites not modified since they were last read are taken from the site cache.
+    pub site_cache: bool,
+    /// If set, no executables are run: sites are derived from `pyvenv.cfg` and layout.
+    pub static_sites: bool,
+}
+
+impl ScanFlags {
+    pub fn with_force_usite(mut self, value: bool) -> Self {
+        self.force_usite = value;
+        self
+    }
+
+    pub fn with_site_cache(mut self, value: bool) -> Self {
+        self.site_cache = value;
+        self
+    }
+
+    pub fn with_static_sites(mut self, value: bool) -> Self {
+        self.static_sites = value;
+        self
+    }
+}
 
-// The result of a file-system scan.
+//------------------------------------------------------------------------------
+
+/// The result of a file-system scan.
 #[derive(Clone, Debug)]
-pub(crate) struct ScanFS {
+pub struct ScanFS {
     // NOTE: these attributes are used by reporters
     /// A mapping of exe path to site packages paths
     pub(crate) exe_to_sites: HashMap<PathBuf, Vec<PathShared>>,
@@ -109,6 +391,14 @@ pub(crate) struct ScanFS {
     force_usite: bool,
     /// Store the hash of the un-normalized exe inputs for cache lookup.
     exes_hash: String,
+    /// If true, sites were discovered by layout in a root filesystem and executables must not be run. These scans are not cached.
+    offline: bool,
+    /// If defined, the environment marker values of each executable recorded in a snapshot, used instead of calling out to executables.
+    marker_envs: Option<Vec<(PathBuf, MarkerEnv)>>,
+    /// If defined, the interpreter metadata recorded in a snapshot, used instead of calling out to executables.
+    interpreters: Option<Vec<Interpreter>>,
+    /// Executables and sites that could not be scanned.
+    warnings: Vec<ScanWarning>,
 }
 
 impl Serialize for ScanFS {
@@ -118,10 +408,10 @@ impl Serialize for ScanFS {
     {
         // Collect and sort by keys for stable ordering
         let mut exe_to_sites: Vec<_> = self.exe_to_sites.iter().collect();
-        exe_to_sites.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        exe_to_sites.sort_by_key(|(k1, _)| *k1);
 
         let mut package_to_sites: Vec<_> = self.package_to_sites.iter().collect();
-        package_to_sites.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        package_to_sites.sort_by_key(|(k1, _)| *k1);
 
         // Serialize as tuple of sorted vectors
         let data = (
@@ -134,6 +424,16 @@ impl Serialize for ScanFS {
     }
 }
 
+/// A portable representation of a scan, including the environment marker values of each executable, such that validation can be done later or on another machine without running executables.
+#[derive(Serialize, Deserialize)]
+struct ScanSnapshot {
+    exe_to_sites: Vec<(PathBuf, Vec<PathShared>)>,
+    package_to_sites: Vec<(Package, Vec<PathShared>)>,
+    marker_envs: Vec<(PathBuf, MarkerEnv)>,
+    #[serde(default)]
+    interpreters: Vec<Interpreter>,
+}
+
 /// Flattened data representation used for serialization.
 type ScanFSData = (
     Vec<(PathBuf, Vec<PathShared>)>,
@@ -158,30 +458,114 @@ impl<'de> Deserialize<'de> for ScanFS {
             package_to_sites,
             force_usite,
             exes_hash,
+            offline: false,
+            marker_envs: None,
+            interpreters: None,
+            warnings: Vec::new(),
         })
     }
 }
 
 impl ScanFS {
-    /// Main entry point for creating a ScanFS. All public creation should go through this interface.
+    /// Main entry point for creating a ScanFS. All public creation should go through this interface. If `site_cache` is true, the Packages of sites that have not been modified since they were last read are taken from the site cache, and the site cache is updated. Sites that cannot be read are added to `warnings`, and the scan continues without their packages.
     fn from_exe_to_sites(
         exe_to_sites: HashMap<PathBuf, Vec<PathShared>>,
         force_usite: bool,
         exes_hash: String,
-    ) -> ResultDynError<Self> {
-        // Some site packages will be repeated; let them be processed more than once here, as it seems easier than filtering them out
-        let site_to_packages = exe_to_sites
+        site_cache: bool,
+        mut warnings: Vec<ScanWarning>,
+    ) -> Result<Self, FetterError> {
+        // Some site packages are shared by exes; process each once, in sorted order such that sites are recorded for each package in a deterministic order
+        let mut sites: Vec<&PathShared> = exe_to_sites.values().flatten().collect();
+        sites.sort_by(|a, b| a.as_path().cmp(b.as_path()));
+        sites.dedup();
+        PROGRESS.add_sites(sites.len());
+        let _span = debug_span!("site_scan", sites = sites.len()).entered();
+
+        let mut sc = if site_cache {
+            Some(SiteCache::from_cache_dir())
+        } else {
+            None
+        };
+        // mtime is observed before reading such that a concurrent modification is read again next time
+        let packages_by_site = sites
+            .into_par_iter()
+            .map(|site_package_path| {
+                let site = site_package_path.as_path();
+                let mtime = sc.as_ref().and_then(|_| path_mtime(site));
+                let cached = sc
+                    .as_ref()
+                    .zip(mtime)
+                    .and_then(|(sc, mtime)| sc.get_packages(site, mtime));
+                let is_cached = cached.is_some();
+                let (packages, mtime_read) = match cached {
+                    Some(packages) => (Ok(packages), None),
+                    None => (get_packages(site), mtime),
+                };
+                if let Ok(packages) = &packages {
+                    debug!(site = ?site, packages = packages.len(), cached = is_cached, "read site");
+                    PROGRESS.add_packages(packages.len());
+                }
+                (site_package_path.clone(), packages, mtime_read)
+            })
+            .collect::<Vec<(PathShared, Result<Vec<Package>, FetterError>, Option<Duration>)>>();
+        if let Some(sc) = sc.as_mut() {
+            let mut updated = false;
+            for (site, packages, mtime_read) in packages_by_site.iter() {
+                if let (Ok(packages), Some(mtime)) = (packages, mtime_read) {
+                    sc.insert(site.as_path(), *mtime, packages);
+                    updated = true;
+                }
+            }
+            if updated {
+                if let Err(e) = sc.to_cache_dir() {
+                    warn!("Failed to write site cache: {}", e);
+                }
+            }
+        }
+        let packages_by_site: Vec<(PathShared, Vec<Package>)> = packages_by_site
+            .into_iter()
+            .map(|(site, packages, _)| match packages {
+                Ok(packages) => (site, packages),
+                Err(e) => {
+                    debug!("{}", e);
+                    warnings.push(e.to_warning());
+                    (site, Vec::new())
+                }
+            })
+            .collect();
+        let site_to_packages: HashMap<&PathShared, &Vec<Package>> =
+            packages_by_site.iter().map(|(s, p)| (s, p)).collect();
+
+        // conda environments record installed packages in conda-meta; multiple exes may share an environment
+        let mut conda_meta_to_sites: HashMap<PathBuf, HashSet<&PathShared>> =
+            HashMap::new();
+        for (exe, site_packages) in exe_to_sites.iter() {
+            if let Some(conda_meta) = exe_to_conda_meta(exe) {
+                conda_meta_to_sites
+                    .entry(conda_meta)
+                    .or_default()
+                    .extend(site_packages.iter());
+            }
+        }
+        let mut conda_meta_to_packages = conda_meta_to_sites
             .par_iter()
-            .flat_map(|(_, site_packages)| {
-                site_packages.par_iter().map(|site_package_path| {
-                    let packages = get_packages(site_package_path.as_path());
-                    (site_package_path.clone(), packages)
-                })
+            .map(|(conda_meta, site_packages)| {
+                let packages_site: HashSet<&Package> = site_packages
+                    .iter()
+                    .filter_map(|site| site_to_packages.get(*site))
+                    .flat_map(|packages| packages.iter())
+                    .collect();
+                let packages = get_conda_packages(conda_meta, &packages_site);
+                (PathShared::from_path_buf(conda_meta.clone()), packages)
             })
-            .collect::<HashMap<PathShared, Vec<Package>>>();
+            .collect::<Vec<(PathShared, Vec<Package>)>>();
+        conda_meta_to_packages.sort_by(|a, b| a.0.as_path().cmp(b.0.as_path()));
 
         let mut package_to_sites: HashMap<Package, Vec<PathShared>> = HashMap::new();
-        for (site_package_path, packages) in site_to_packages.iter() {
+        for (site_package_path, packages) in
+            packages_by_site.iter().chain(conda_meta_to_packages.iter())
+        {
             for package in packages {
                 package_to_sites
                     .entry(package.clone())
@@ -194,6 +578,10 @@ impl ScanFS {
             package_to_sites,
             force_usite,
             exes_hash,
+            offline: false,
+            marker_envs: None,
+            interpreters: None,
+            warnings,
         })
     }
 
@@ -206,7 +594,7 @@ impl ScanFS {
         if cache_dur == DURATION_0 {
             Err("Cache disabled by duration".into())
         } else if let Some(mut cache_dir) = path_cache(true) {
-            let exes_hash = hash_paths(exes, force_usite);
+            let exes_hash = hash_exes(exes, force_usite);
             cache_dir.push(exes_hash);
             let cache_fp = cache_dir.with_extension("json");
 
@@ -227,30 +615,136 @@ impl ScanFS {
         }
     }
 
-    /// Given a Vec of PathBuf to executables, use them to collect site packages. In this function, provided PathBuf are normalized to absolute paths, and if a PathBuf is "*", a system-wide path search will be conducted.
-    pub(crate) fn from_exes(
-        exes: &Vec<PathBuf>,
-        force_usite: bool,
-    ) -> ResultDynError<Self> {
+    /// Given a Vec of PathBuf to executables, use them to collect site packages, optionally using the site cache. In this function, provided PathBuf are normalized to absolute paths, and if a PathBuf is "*", a system-wide path search will be conducted. If a PathBuf is "pyenv", the executables of all installed pyenv versions are used. If a PathBuf has wildcards in any component (e.g. `/opt/venvs/*/bin/python`), all matching executables are used. pyenv shims are followed to the interpreter they select. If a PathBuf is a zip archive, such as a zipapp (`.pyz`) or zipped egg, it is not run but is used as its own site. If `static_sites` is set in `flags`, no executables are run: sites are derived from `pyvenv.cfg` and layout, pyenv shims are not followed, and the scan is offline.
+    pub fn from_exes(exes: &[PathBuf], flags: ScanFlags) -> Result<Self, FetterError> {
+        let ScanFlags {
+            force_usite,
+            site_cache,
+            static_sites,
+        } = flags;
         let path_wild = PathBuf::from("*");
-        let exes_hash = hash_paths(exes, force_usite);
+        let path_pyenv = PathBuf::from("pyenv");
+        let exes_hash = hash_exes(exes, force_usite);
+        let span = debug_span!("exe_search").entered();
         let mut exes_norm = Vec::new();
         for e in exes {
             if path_is_component(e) && *e == path_wild {
-                exes_norm.extend(find_exe());
-            } else if let Ok(normalized) = exe_path_normalize(e) {
-                exes_norm.push(normalized);
+                exes_norm.extend(find_exe(!static_sites));
+            } else if path_is_component(e) && *e == path_pyenv {
+                exes_norm.extend(find_exe_pyenv());
+            } else if is_exe_glob(e) {
+                exes_norm.extend(
+                    find_exe_glob(e)
+                        .into_iter()
+                        .filter_map(|fp| exe_path_normalize(&fp, !static_sites).ok())
+                        .map(|fp| {
+                            if static_sites {
+                                fp
+                            } else {
+                                pyenv_shim_resolve(fp)
+                            }
+                        }),
+                );
+            } else if is_zip_archive(e) {
+                exes_norm.extend(path_normalize(e).ok());
+            } else if let Ok(normalized) = exe_path_normalize(e, !static_sites) {
+                if static_sites {
+                    exes_norm.push(normalized);
+                } else {
+                    exes_norm.push(pyenv_shim_resolve(normalized));
+                }
+            }
+        }
+        debug!(executables = exes_norm.len(), "found executables");
+        drop(span);
+
+        let span = debug_span!("site_search", executables = exes_norm.len()).entered();
+        let exe_to_dirs: Vec<(PathBuf, Result<Vec<PathShared>, FetterError>)> = exes_norm
+            .into_par_iter()
+            .map(|exe| {
+                let dirs = if is_zip_archive(&exe) {
+                    Ok(vec![PathShared::from_path_buf(exe.clone())])
+                } else if static_sites {
+                    Ok(get_site_package_dirs_static(&exe, force_usite))
+                } else {
+                    get_site_package_dirs(&exe, force_usite)
+                };
+                PROGRESS.add_exes(1);
+                (exe, dirs)
+            })
+            .collect();
+        drop(span);
+        let (exe_to_sites, warnings) = partition_exe_to_sites(exe_to_dirs);
+        let mut sfs = Self::from_exe_to_sites(
+            exe_to_sites,
+            force_usite,
+            exes_hash,
+            site_cache,
+            warnings,
+        )?;
+        sfs.offline = static_sites;
+        Ok(sfs)
+    }
+
+    /// Given a Vec of PathBuf to root directories, recursively search each for virtual environments and other Python executables, and use them to collect site packages, optionally using the site cache. The hash of the un-normalized roots is used for cache lookup. If `static_sites` is set in `flags`, sites are derived from `pyvenv.cfg` and layout without running executables, and the scan is offline.
+    pub fn from_scan_roots(roots: &[PathBuf], flags: ScanFlags) -> ResultDynError<Self> {
+        let ScanFlags {
+            force_usite,
+            site_cache,
+            static_sites,
+        } = flags;
+        let exes_hash = hash_exes(roots, force_usite);
+        let mut roots_norm = Vec::new();
+        for r in roots {
+            let normalized = path_normalize(r)?;
+            if !normalized.is_dir() {
+                return Err(format!("scan root is not a directory: {:?}", r).into());
             }
+            roots_norm.push(normalized);
         }
+        let span = debug_span!("exe_search").entered();
+        let exes = find_exe_roots(&roots_norm);
+        debug!(executables = exes.len(), "found executables");
+        drop(span);
 
-        let exe_to_sites: HashMap<PathBuf, Vec<PathShared>> = exes_norm
+        let span = debug_span!("site_search", executables = exes.len()).entered();
+        let exe_to_dirs: Vec<(PathBuf, Result<Vec<PathShared>, FetterError>)> = exes
             .into_par_iter()
             .map(|exe| {
-                let dirs = get_site_package_dirs(&exe, force_usite);
+                let dirs = if static_sites {
+                    Ok(get_site_package_dirs_static(&exe, force_usite))
+                } else {
+                    get_site_package_dirs(&exe, force_usite)
+                };
+                PROGRESS.add_exes(1);
                 (exe, dirs)
             })
             .collect();
-        Self::from_exe_to_sites(exe_to_sites, force_usite, exes_hash)
+        drop(span);
+        let (exe_to_sites, warnings) = partition_exe_to_sites(exe_to_dirs);
+        let mut sfs = Self::from_exe_to_sites(
+            exe_to_sites,
+            force_usite,
+            exes_hash,
+            site_cache,
+            warnings,
+        )?;
+        sfs.offline = static_sites;
+        Ok(sfs)
+    }
+
+    /// Given a path to the root directory of a mounted or extracted filesystem, find site packages by layout and `pyvenv.cfg` files, without running any executables.
+    pub fn from_root(root: &Path) -> ResultDynError<Self> {
+        let root_norm = path_normalize(root)?;
+        if !root_norm.is_dir() {
+            return Err(format!("root is not a directory: {:?}", root).into());
+        }
+        let exes_hash = hash_paths(std::slice::from_ref(&root_norm), false);
+        let exe_to_sites = find_root_exe_to_sites(&root_norm);
+        let mut sfs =
+            Self::from_exe_to_sites(exe_to_sites, false, exes_hash, false, Vec::new())?;
+        sfs.offline = true;
+        Ok(sfs)
     }
 
     /// Alternative constructor from in-memory objects, only for testing. Here we provide notional exe and site paths, and focus just on collecting Packages.
@@ -280,6 +774,10 @@ impl ScanFS {
             package_to_sites,
             force_usite,
             exes_hash,
+            offline: false,
+            marker_envs: None,
+            interpreters: None,
+            warnings: Vec::new(),
         })
     }
 
@@ -302,10 +800,66 @@ impl ScanFS {
         matched
     }
 
+    /// Return packages whose name, or name and version, match the pattern, such that a bare package name selects all installed versions.
+    pub(crate) fn search_by_name(
+        &self,
+        pattern: &str,
+        case_insensitive: bool,
+    ) -> Vec<Package> {
+        self.get_packages()
+            .into_par_iter()
+            .filter(|package| {
+                match_str(pattern, &package.name, case_insensitive)
+                    || match_str(pattern, package.to_string().as_str(), case_insensitive)
+            })
+            .collect()
+    }
+
+    /// Retain only packages that match the filter and, if `site` is provided, only that site of each executable and the packages installed in it. It is an error if `site` is not a scanned site.
+    pub(crate) fn retain_packages(
+        &mut self,
+        filter: &PackageFilter,
+        site: Option<&Path>,
+    ) -> ResultDynError<()> {
+        let site = match site {
+            Some(site) => {
+                let normalized =
+                    path_normalize(site).unwrap_or_else(|_| site.to_path_buf());
+                if !self.get_sites().iter().any(|s| s.as_path() == normalized) {
+                    return Err(format!("Site not found in scan: {:?}", site).into());
+                }
+                Some(normalized)
+            }
+            None => None,
+        };
+        if let Some(site) = &site {
+            for sites in self.exe_to_sites.values_mut() {
+                sites.retain(|s| s.as_path() == site);
+            }
+        }
+        self.package_to_sites.retain(|package, sites| {
+            if let Some(site) = &site {
+                sites.retain(|s| s.as_path() == site);
+            }
+            !sites.is_empty()
+                && filter.is_match(&[&package.name, &package.key, &package.to_string()])
+        });
+        Ok(())
+    }
+
     //--------------------------------------------------------------------------
 
+    /// Return sorted, unique site packages paths of all executables.
+    pub(crate) fn get_sites(&self) -> Vec<PathShared> {
+        let mut sites: Vec<PathShared> =
+            self.exe_to_sites.values().flatten().cloned().collect();
+        sites.sort_by(|a, b| a.as_path().cmp(b.as_path()));
+        sites.dedup();
+        sites
+    }
+
     /// Return sorted packages.
-    pub(crate) fn get_packages(&self) -> Vec<Package> {
+    pub fn get_packages(&self) -> Vec<Package> {
         let mut packages: Vec<Package> = self.package_to_sites.keys().cloned().collect();
         packages.sort();
         packages
@@ -334,88 +888,920 @@ impl ScanFS {
         Err("could not get cache directory".into())
     }
 
+    /// Write a snapshot of this scan, including packages, sites, direct URLs, and the environment marker values and interpreter metadata of each executable, as JSON.
+    pub fn to_snapshot_file(&self, file_path: &Path) -> ResultDynError<()> {
+        let mut exe_to_sites: Vec<_> = self
+            .exe_to_sites
+            .iter()
+            .map(|(e, s)| (e.clone(), s.clone()))
+            .collect();
+        exe_to_sites.sort_by(|a, b| a.0.cmp(&b.0));
+        // credentials embedded in direct URLs are not written
+        let mut package_to_sites: Vec<_> = self
+            .package_to_sites
+            .iter()
+            .map(|(p, s)| (p.to_redacted(), s.clone()))
+            .collect();
+        package_to_sites.sort_by(|a, b| a.0.cmp(&b.0));
+        let snapshot = ScanSnapshot {
+            exe_to_sites,
+            package_to_sites,
+            marker_envs: self.get_exe_marker_envs(),
+            interpreters: self.get_interpreters(),
+        };
+        let json = serde_json::to_string_pretty(&snapshot)?;
+        fs::write(file_path, json)?;
+        Ok(())
+    }
+
+    /// Create a ScanFS from a snapshot written by `to_snapshot_file`. Executables are never run: environment marker values are those recorded in the snapshot.
+    pub fn from_snapshot_file(file_path: &Path) -> ResultDynError<Self> {
+        let content = fs::read_to_string(file_path)
+            .map_err(|e| format!("could not read snapshot {:?}: {}", file_path, e))?;
+        let snapshot: ScanSnapshot = serde_json::from_str(&content)
+            .map_err(|e| format!("could not parse snapshot {:?}: {}", file_path, e))?;
+        Ok(ScanFS {
+            exe_to_sites: snapshot.exe_to_sites.into_iter().collect(),
+            package_to_sites: snapshot.package_to_sites.into_iter().collect(),
+            force_usite: false,
+            exes_hash: hash_paths(&[file_path.to_path_buf()], false),
+            offline: true,
+            marker_envs: Some(snapshot.marker_envs),
+            interpreters: Some(snapshot.interpreters),
+            warnings: Vec::new(),
+        })
+    }
+
     //--------------------------------------------------------------------------
 
-    /// Validate this scan against the provided DepManifest.
-    pub(crate) fn to_validation_report(
+    /// Return the environment marker values of the executables that use any of the sites of `package`.
+    fn get_package_envs(
+        &self,
+        package: &Package,
+        site_to_envs: &HashMap<PathShared, Vec<MarkerEnv>>,
+    ) -> Vec<MarkerEnv> {
+        self.package_to_sites
+            .get(package)
+            .into_iter()
+            .flatten()
+            .filter_map(|site| site_to_envs.get(site))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// For each DepSpec that requests extras, read the Requires-Dist entries of the installed package and return DepSpecs for those entries activated by the requested extras.
+    fn get_extra_dep_specs(
+        &self,
+        dm: &DepManifest,
+        site_to_envs: &HashMap<PathShared, Vec<MarkerEnv>>,
+    ) -> Vec<DepSpec> {
+        let mut dep_specs = Vec::new();
+        for ds in dm.get_dep_specs_with_extras() {
+            for package in self.get_packages().iter().filter(|p| p.key == ds.key) {
+                let envs = self.get_package_envs(package, site_to_envs);
+                let mut envs_extra: Vec<MarkerEnv> = Vec::new();
+                for extra in ds.extras.iter() {
+                    if envs.is_empty() {
+                        envs_extra.push(MarkerEnv::from_pairs(&[("extra", extra)]));
+                    } else {
+                        envs_extra.extend(envs.iter().map(|env| env.with_extra(extra)));
+                    }
+                }
+                let sites = &self.package_to_sites[package];
+                let requires_dist =
+                    match PackageMetadata::from_package_sites(package, sites) {
+                        Some(md) => md.requires_dist,
+                        None => continue,
+                    };
+                for mut ds_extra in requires_dist
+                    .iter()
+                    .filter_map(|r| DepSpec::from_string(r).ok())
+                {
+                    // only requirements conditioned on an extra are added
+                    let activated = ds_extra.marker.as_ref().is_some_and(|m| {
+                        m.has_extra() && envs_extra.iter().any(|env| m.evaluate(env))
+                    });
+                    if activated {
+                        ds_extra.marker = None;
+                        dep_specs.push(ds_extra);
+                    }
+                }
+            }
+        }
+        dep_specs
+    }
+
+    /// Return the environment marker values of each scanned executable. If loaded from a snapshot, the recorded values are returned. If offline, values are inferred from the Python version of the sites of each executable.
+    fn get_exe_marker_envs(&self) -> Vec<(PathBuf, MarkerEnv)> {
+        if let Some(exe_envs) = &self.marker_envs {
+            return exe_envs.clone();
+        }
+        let mut exes: Vec<&PathBuf> = self.exe_to_sites.keys().collect();
+        exes.sort();
+        if self.offline {
+            exes.into_iter()
+                .filter_map(|exe| {
+                    self.exe_to_sites[exe]
+                        .iter()
+                        .find_map(|site| site_to_python_version(site.as_path()))
+                        .map(|v| (exe.clone(), MarkerEnv::from_python_version(&v)))
+                })
+                .collect()
+        } else {
+            exes.into_par_iter()
+                .filter_map(|exe| {
+                    MarkerEnv::from_exe(exe).ok().map(|env| (exe.clone(), env))
+                })
+                .collect()
+        }
+    }
+
+    /// Return, for each site, the environment marker values of the scanned executables that use that site.
+    fn get_marker_envs(&self) -> HashMap<PathShared, Vec<MarkerEnv>> {
+        let mut site_to_envs: HashMap<PathShared, Vec<MarkerEnv>> = HashMap::new();
+        for (exe, env) in self.get_exe_marker_envs() {
+            for site in self.exe_to_sites.get(&exe).into_iter().flatten() {
+                site_to_envs
+                    .entry(site.clone())
+                    .or_default()
+                    .push(env.clone());
+            }
+        }
+        site_to_envs
+    }
+
+    /// Return the metadata of all scanned executables. If loaded from a snapshot, the recorded metadata is returned. If offline, or if an executable cannot be run, metadata is read from `pyvenv.cfg` or inferred from sites.
+    fn get_interpreters(&self) -> Vec<Interpreter> {
+        if let Some(interpreters) = &self.interpreters {
+            return interpreters.clone();
+        }
+        let mut exes: Vec<&PathBuf> = self.exe_to_sites.keys().collect();
+        exes.sort();
+        let offline = self.offline;
+        exes.into_par_iter()
+            .map(|exe| {
+                let interpreter = if offline {
+                    None
+                } else {
+                    Interpreter::from_exe(exe).ok()
+                };
+                interpreter.unwrap_or_else(|| {
+                    Interpreter::from_static(exe, &self.exe_to_sites[exe])
+                })
+            })
+            .collect()
+    }
+
+    /// Return a report of the version, implementation, build, and prefix of each scanned executable.
+    pub(crate) fn to_interpreter_report(&self) -> InterpreterReport {
+        InterpreterReport::from_interpreters(self.get_interpreters())
+    }
+
+    /// Validate this scan against the provided DepManifest. If the DepManifest has environment markers, they are evaluated against the executables that use the site of each package.
+    pub fn to_validation_report(
+        &self,
+        dm: DepManifest,
+        vf: ValidationFlags,
+    ) -> ValidationReport {
+        // only call out to executables if necessary
+        let site_to_envs = if dm.has_markers() || dm.has_extras() || vf.report_orphans {
+            self.get_marker_envs()
+        } else {
+            HashMap::new()
+        };
+        self.to_validation_report_with_envs(dm, vf, &site_to_envs, &GitClientLive)
+    }
+
+    /// Validate this scan against the provided DepManifest. A found package is skipped if its DepSpec has a marker that does not apply to the environments of the executables using the package's sites; a missing package is skipped if its DepSpec does not apply to any environment. Requirements of requested extras are also validated. If `vf.reject_editable` is set, otherwise valid packages installed in editable mode are invalid. If `vf.resolve_revisions` is set, `git_client` is used to resolve requested VCS revisions of otherwise valid packages. If `vf.report_orphans` is set, packages that are not required by the DepManifest, directly or through the Requires-Dist requirements of required packages, are invalid. If `vf.reject_prerelease` is set, otherwise valid pre-releases are invalid unless their DepSpec specifies a pre-release. If `vf.exact` is set, neither a superset nor a subset is permitted, and found packages whose DepSpec does not apply to their environments are unrequired.
+    // For a Package whose requirement defines hashes but that has no recorded archive hash, as when installed from an index, return HashMismatch if its installed files do not match the digests of its RECORD or, if hashes are required, HashUnverifiable.
+    fn to_hash_record_explain(
+        &self,
+        package: &Package,
+        require_hashes: bool,
+    ) -> Option<(ValidationExplain, String)> {
+        let sites = self.package_to_sites.get(package)?;
+        if sites
+            .iter()
+            .any(|site| validate_record(package, site) == Some(false))
+        {
+            Some((
+                ValidationExplain::HashMismatch,
+                "installed files do not match RECORD".to_string(),
+            ))
+        } else if require_hashes {
+            Some((
+                ValidationExplain::HashUnverifiable,
+                "no archive hash recorded for installed package".to_string(),
+            ))
+        } else {
+            None
+        }
+    }
+
+    fn to_validation_report_with_envs<G: GitClient + Sync>(
         &self,
         dm: DepManifest,
         vf: ValidationFlags,
+        site_to_envs: &HashMap<PathShared, Vec<MarkerEnv>>,
+        git_client: &G,
     ) -> ValidationReport {
+        let _span = debug_span!("validation", requirements = dm.len()).entered();
+        // requirements of requested extras are validated with the DepManifest
+        let dm = if dm.has_extras() {
+            let dep_specs = self.get_extra_dep_specs(&dm, site_to_envs);
+            dm.extend(dep_specs).unwrap_or(dm)
+        } else {
+            dm
+        };
         let mut records: Vec<ValidationRecord> = Vec::new();
         let mut ds_keys_matched: HashSet<&String> = HashSet::new();
+        let mut packages_resolve: Vec<(Package, &DepSpec)> = Vec::new();
+
+        // packages not reachable from packages required by the DepManifest
+        let orphans: HashSet<Package> = if vf.report_orphans {
+            let dg =
+                DepGraph::from_package_to_sites(&self.package_to_sites, site_to_envs);
+            let roots: Vec<&Package> = dg
+                .get_packages()
+                .into_iter()
+                .filter(|p| dm.get_dep_spec(&p.key).is_some())
+                .collect();
+            let reachable = dg.get_reachable(&roots);
+            dg.get_packages()
+                .into_iter()
+                .filter(|p| !reachable.contains(p))
+                .cloned()
+                .collect()
+        } else {
+            HashSet::new()
+        };
+
+        let permit_superset = vf.permit_superset && !vf.exact;
+        let permit_subset = vf.permit_subset && !vf.exact;
 
         // iterate over found packages in order for better reporting
         for package in self.get_packages() {
-            let (valid, ds) = dm.validate(&package, vf.permit_superset);
+            PROGRESS.add_validated(1);
+            let (valid, ds) = if vf.ignore_local {
+                dm.validate(&package.to_public(), permit_superset)
+            } else {
+                dm.validate(&package, permit_superset)
+            };
             if let Some(ds) = ds {
                 ds_keys_matched.insert(&ds.key);
+                let envs = self.get_package_envs(&package, site_to_envs);
+                if !ds.is_applicable(&envs) {
+                    if vf.exact {
+                        let sites = self.package_to_sites.get(&package).cloned();
+                        records.push(ValidationRecord::from_explain_reason(
+                            Some(package),
+                            Some(ds.clone()),
+                            sites,
+                            ValidationExplain::Unrequired,
+                            "the marker of the requirement does not apply".to_string(),
+                        ));
+                    }
+                    continue;
+                }
             }
-            if !valid {
+            if orphans.contains(&package) {
+                let sites = self.package_to_sites.get(&package).cloned();
+                records.push(ValidationRecord::from_explain(
+                    Some(package),
+                    None,
+                    sites,
+                    ValidationExplain::Orphan,
+                ));
+            } else if !valid {
                 // package should always have defined sites
                 let sites = self.package_to_sites.get(&package).cloned();
                 // ds is an Option type, might be None
-                records.push(ValidationRecord::new(
-                    Some(package), // can take ownership of Package
+                let reason = ds.and_then(|ds| {
+                    ds.to_local_mismatch(&package)
+                        .or_else(|| ds.to_local_label_mismatch(&package))
+                });
+                let record = match reason {
+                    Some(reason) => ValidationRecord::from_explain_reason(
+                        Some(package),
+                        ds.cloned(),
+                        sites,
+                        ValidationExplain::Misdefined,
+                        reason,
+                    ),
+                    None => ValidationRecord::new(
+                        Some(package), // can take ownership of Package
+                        ds.cloned(),
+                        sites,
+                    ),
+                };
+                records.push(record);
+            } else if let Some(reason) = ds
+                .filter(|_| vf.reject_prerelease)
+                .and_then(|ds| ds.to_prerelease_mismatch(&package))
+            {
+                let sites = self.package_to_sites.get(&package).cloned();
+                records.push(ValidationRecord::from_explain_reason(
+                    Some(package),
+                    ds.cloned(),
+                    sites,
+                    ValidationExplain::Misdefined,
+                    reason,
+                ));
+            } else if let Some((explain, reason)) = ds
+                .filter(|ds| ds.is_hash_unverifiable(&package))
+                .and_then(|_| self.to_hash_record_explain(&package, vf.require_hashes))
+            {
+                let sites = self.package_to_sites.get(&package).cloned();
+                records.push(ValidationRecord::from_explain_reason(
+                    Some(package),
+                    ds.cloned(),
+                    sites,
+                    explain,
+                    reason,
+                ));
+            } else if vf.reject_editable && package.is_editable() {
+                let sites = self.package_to_sites.get(&package).cloned();
+                records.push(ValidationRecord::from_explain(
+                    Some(package),
                     ds.cloned(),
                     sites,
+                    ValidationExplain::Editable,
                 ));
+            } else if vf.resolve_revisions {
+                if let Some(ds) = ds {
+                    packages_resolve.push((package, ds));
+                }
             }
         }
-        if !vf.permit_subset {
+        // resolving revisions requires network access, so is done in parallel
+        let records_drifted: Vec<ValidationRecord> = packages_resolve
+            .into_par_iter()
+            .filter_map(|(package, ds)| {
+                let url = ds.url.as_ref()?;
+                package
+                    .direct_url
+                    .as_ref()?
+                    .to_revision_drift(url, git_client)?;
+                let sites = self.package_to_sites.get(&package).cloned();
+                Some(ValidationRecord::from_explain(
+                    Some(package),
+                    Some(ds.clone()),
+                    sites,
+                    ValidationExplain::Drifted,
+                ))
+            })
+            .collect();
+        records.extend(records_drifted);
+        if !permit_subset {
             // packages defined in DepSpec but not found
-            // NOTE: this is sorted, but not sorted with the other records
+            let envs: Vec<MarkerEnv> = site_to_envs.values().flatten().cloned().collect();
             for key in dm.get_dep_spec_difference(&ds_keys_matched) {
-                records.push(ValidationRecord::new(
-                    None,
-                    dm.get_dep_spec(key).cloned(),
-                    None,
-                ));
+                let ds = dm.get_dep_spec(key);
+                if ds.is_some_and(|ds| !ds.is_applicable(&envs)) {
+                    continue;
+                }
+                records.push(ValidationRecord::new(None, ds.cloned(), None));
             }
         }
-        ValidationReport { records }
+        let mut vr = ValidationReport { records };
+        vr.sort_records();
+        vr
     }
 
-    pub(crate) fn to_audit_report(
-        &self,
-        pattern: &str,
-        case_insensitive: bool,
-    ) -> AuditReport {
-        let packages = self.search_by_match(pattern, case_insensitive);
-        AuditReport::from_packages(&UreqClientLive, &packages)
+    /// Return the graph of installed packages and their Requires-Dist requirements, applying markers for the environments of the executables that use each package's sites.
+    pub(crate) fn to_dep_graph(&self) -> DepGraph {
+        let site_to_envs = self.get_marker_envs();
+        DepGraph::from_package_to_sites(&self.package_to_sites, &site_to_envs)
     }
 
-    /// The `count` Boolean determine if what type of UnpackReport is returned
-    pub(crate) fn to_unpack_report(
+    /// Return a report of the Requires-Dist requirements of installed packages that are not satisfied.
+    pub(crate) fn to_check_report(&self) -> CheckReport {
+        CheckReport::from_dep_graph(&self.to_dep_graph())
+    }
+
+    /// Return the top-level modules provided by packages that match the package pattern, limited to modules that match the module pattern. If a DepManifest is provided, the requirement of each package is included.
+    pub(crate) fn to_map_report(
         &self,
-        pattern: &str,
+        module: &str,
+        package: &str,
         case_insensitive: bool,
-        count: bool,
-    ) -> UnpackReport {
-        let mut packages = self.search_by_match(pattern, case_insensitive);
+        dm: Option<&DepManifest>,
+    ) -> MapReport {
+        let mut packages = self.search_by_name(package, case_insensitive);
         packages.sort();
-        let package_to_sites = packages
-            .iter()
-            .map(|p| (p.clone(), self.package_to_sites.get(p).unwrap().clone()))
+        let package_to_sites: Vec<(Package, Vec<PathShared>)> = packages
+            .into_iter()
+            .filter_map(|p| {
+                let sites = self.package_to_sites.get(&p)?.clone();
+                Some((p, sites))
+            })
             .collect();
-
-        UnpackReport::from_package_to_sites(count, &package_to_sites)
+        MapReport::from_package_to_sites(&package_to_sites, module, case_insensitive, dm)
     }
 
-    /// Given an `anchor`, produce a DepManifest based ont the packages observed in this scan.
-    pub(crate) fn to_dep_manifest(
+    /// Return the dependency tree of packages that match the pattern or, if no pattern is provided, of all packages not required by other packages.
+    pub(crate) fn to_tree_report(
         &self,
-        anchor: Anchor,
-    ) -> Result<DepManifest, Box<dyn std::error::Error>> {
-        let mut package_name_to_package: HashMap<String, Vec<Package>> = HashMap::new();
+        pattern: Option<&str>,
+        case_insensitive: bool,
+        depth: Option<usize>,
+    ) -> TreeReport {
+        let dg = self.to_dep_graph();
+        let roots = pattern.map(|p| self.search_by_name(p, case_insensitive));
+        TreeReport::from_dep_graph(&dg, roots.as_deref(), depth)
+    }
 
-        for package in self.package_to_sites.keys() {
-            package_name_to_package
-                .entry(package.name.clone())
-                .or_default()
-                .push(package.clone());
+    /// Return validation records for all packages whose license, as read from METADATA, is not permitted by the LicensePolicy.
+    pub(crate) fn to_license_records(&self, lp: &LicensePolicy) -> Vec<ValidationRecord> {
+        self.get_packages()
+            .into_par_iter()
+            .filter_map(|package| {
+                let sites = self.package_to_sites.get(&package)?;
+                let license = PackageMetadata::from_package_sites(&package, sites)
+                    .and_then(|md| md.to_policy_license());
+                if lp.is_permitted(license.as_deref()) {
+                    None
+                } else {
+                    let sites = Some(sites.clone());
+                    Some(ValidationRecord::from_explain(
+                        Some(package),
+                        None,
+                        sites,
+                        ValidationExplain::LicenseDenied,
+                    ))
+                }
+            })
+            .collect()
+    }
+
+    // Return a mapping of each site to the environment of the Python that owns it. Unless offline, executables are run to get their environments; otherwise, or if an executable cannot be run, an environment is inferred from the Python version of the site path.
+    fn get_site_to_env(&self) -> HashMap<PathShared, MarkerEnv> {
+        let exe_to_env: HashMap<&PathBuf, MarkerEnv> = if self.offline {
+            HashMap::new()
+        } else {
+            self.exe_to_sites
+                .par_iter()
+                .filter_map(|(exe, _)| {
+                    MarkerEnv::from_exe(exe).ok().map(|env| (exe, env))
+                })
+                .collect()
+        };
+        let mut site_to_env = HashMap::new();
+        for (exe, sites) in self.exe_to_sites.iter() {
+            for site in sites {
+                let env = exe_to_env.get(exe).cloned().or_else(|| {
+                    // a site without a version, such as Debian's `python3/dist-packages`, takes the version of another site of the executable
+                    site_to_python_version(site.as_path())
+                        .or_else(|| {
+                            sites
+                                .iter()
+                                .find_map(|s| site_to_python_version(s.as_path()))
+                        })
+                        .map(|v| MarkerEnv::from_python_version(&v))
+                });
+                if let Some(env) = env {
+                    site_to_env.insert(site.clone(), env);
+                }
+            }
         }
-        let names: Vec<String> = package_name_to_package.keys().cloned().collect();
+        site_to_env
+    }
+
+    /// Return validation records for all packages whose Requires-Python, as read from METADATA, is not satisfied by the Python that owns the site in which they are installed.
+    pub(crate) fn to_requires_python_records(&self) -> Vec<ValidationRecord> {
+        let site_to_python: HashMap<PathShared, VersionSpec> = self
+            .get_site_to_env()
+            .into_iter()
+            .filter_map(|(site, env)| {
+                let version = VersionSpec::new(env.get_python_version()?);
+                Some((site, version))
+            })
+            .collect();
+        self.get_packages()
+            .into_par_iter()
+            .filter_map(|package| {
+                let sites = self.package_to_sites.get(&package)?;
+                let mut invalid = Vec::new();
+                let mut reason = None;
+                for site in sites {
+                    let python = match site_to_python.get(site) {
+                        Some(python) => python,
+                        None => continue,
+                    };
+                    let md = match PackageMetadata::from_package_sites(
+                        &package,
+                        std::slice::from_ref(site),
+                    ) {
+                        Some(md) => md,
+                        None => continue,
+                    };
+                    if !md.is_python_supported(python) {
+                        reason = Some(format!(
+                            "Requires-Python {}; Python {}",
+                            md.requires_python.unwrap_or_default(),
+                            python
+                        ));
+                        invalid.push(site.clone());
+                    }
+                }
+                Some(ValidationRecord::from_explain_reason(
+                    Some(package),
+                    None,
+                    Some(invalid),
+                    ValidationExplain::PythonIncompatible,
+                    reason?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Return validation records for all packages whose wheel tags, as read from the WHEEL file of the dist-info directory, are not compatible with the Python that owns the site in which they are installed. Packages without a WHEEL file are not considered.
+    pub(crate) fn to_wheel_tag_records(&self) -> Vec<ValidationRecord> {
+        let site_to_env = self.get_site_to_env();
+        self.get_packages()
+            .into_par_iter()
+            .filter_map(|package| {
+                let sites = self.package_to_sites.get(&package)?;
+                let mut invalid = Vec::new();
+                let mut reason = None;
+                for site in sites {
+                    let env = match site_to_env.get(site) {
+                        Some(env) => env,
+                        None => continue,
+                    };
+                    let tags = match package.to_dist_info_dir(site) {
+                        Some(dist_info) => WheelTag::from_dist_info(&dist_info),
+                        None => continue,
+                    };
+                    if !tags.is_empty() && !tags.iter().any(|t| t.is_compatible(env)) {
+                        reason = Some(
+                            format!(
+                                "{}; {} {} {}",
+                                tags.iter()
+                                    .map(|t| t.to_string())
+                                    .collect::<Vec<_>>()
+                                    .join(","),
+                                env.get_python_version().unwrap_or_default(),
+                                env.get("sys_platform"),
+                                env.get("platform_machine"),
+                            )
+                            .trim_end()
+                            .to_string(),
+                        );
+                        invalid.push(site.clone());
+                    }
+                }
+                Some(ValidationRecord::from_explain_reason(
+                    Some(package),
+                    None,
+                    Some(invalid),
+                    ValidationExplain::IncompatibleBinary,
+                    reason?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Return validation records for all packages that are shadowed by a package of the same name in a site that precedes it on the `sys.path` of an executable; only the first package found for a name can be imported. If offline, sites are assumed to be in order of discovery.
+    pub(crate) fn to_shadow_records(&self) -> Vec<ValidationRecord> {
+        let mut site_to_packages: HashMap<&PathShared, Vec<&Package>> = HashMap::new();
+        for (package, sites) in self.package_to_sites.iter() {
+            for site in sites {
+                site_to_packages.entry(site).or_default().push(package);
+            }
+        }
+        let mut exes: Vec<&PathBuf> = self.exe_to_sites.keys().collect();
+        exes.sort();
+
+        let mut records: Vec<ValidationRecord> = Vec::new();
+        for exe in exes {
+            let sys_path = if self.offline {
+                Vec::new()
+            } else {
+                get_sys_path(exe).unwrap_or_default()
+            };
+            let sites = order_sites(&self.exe_to_sites[exe], &sys_path);
+            // the first Package, and its site, found for each key
+            let mut key_to_active: HashMap<&String, (&Package, &PathShared)> =
+                HashMap::new();
+            for site in sites.iter() {
+                let mut packages =
+                    site_to_packages.get(site).cloned().unwrap_or_default();
+                packages.sort();
+                for package in packages {
+                    match key_to_active.get(&package.key) {
+                        Some((active, active_site)) => {
+                            let record = ValidationRecord::from_explain_reason(
+                                Some(package.clone()),
+                                None,
+                                Some(vec![site.clone()]),
+                                ValidationExplain::Shadowed,
+                                format!("shadowed by {} in {}", active, active_site),
+                            );
+                            // exes that share sites report the same records
+                            if !records.contains(&record) {
+                                records.push(record);
+                            }
+                        }
+                        None => {
+                            key_to_active.insert(&package.key, (package, site));
+                        }
+                    }
+                }
+            }
+        }
+        records
+    }
+
+    /// Return validation records for all packages forbidden by the BanPolicy, with the reason of the ban.
+    pub(crate) fn to_ban_records(&self, bp: &BanPolicy) -> Vec<ValidationRecord> {
+        self.get_packages()
+            .into_iter()
+            .filter_map(|package| {
+                let reason = bp.get_reason(&package.key)?;
+                let sites = self.package_to_sites.get(&package).cloned();
+                Some(ValidationRecord::from_explain_reason(
+                    Some(package),
+                    None,
+                    sites,
+                    ValidationExplain::Banned,
+                    reason,
+                ))
+            })
+            .collect()
+    }
+
+    /// Return validation records for all packages not installed by one of the `installers`, as read from the INSTALLER file of the dist-info directory of each site; installers are compared case-insensitively by the first word. Packages without an INSTALLER file, such as those copied into a site, are invalid.
+    pub(crate) fn to_installer_records(
+        &self,
+        installers: &[String],
+    ) -> Vec<ValidationRecord> {
+        let required: Vec<String> =
+            installers.iter().map(|i| i.trim().to_lowercase()).collect();
+        self.get_packages()
+            .into_par_iter()
+            .filter_map(|package| {
+                let sites = self.package_to_sites.get(&package)?;
+                let mut invalid = Vec::new();
+                let mut reason = None;
+                for site in sites {
+                    let installer = package.to_installer(site);
+                    let name = installer
+                        .as_deref()
+                        .and_then(|i| i.split_whitespace().next())
+                        .map(|i| i.to_lowercase());
+                    if name.is_some_and(|n| required.contains(&n)) {
+                        continue;
+                    }
+                    reason = Some(format!(
+                        "{}; requires {}",
+                        match installer {
+                            Some(installer) => format!("installed by {}", installer),
+                            None => "no INSTALLER".to_string(),
+                        },
+                        required.join(", ")
+                    ));
+                    invalid.push(site.clone());
+                }
+                Some(ValidationRecord::from_explain_reason(
+                    Some(package),
+                    None,
+                    Some(invalid),
+                    ValidationExplain::InstallerDenied,
+                    reason?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Return a report of vulnerabilities of packages that match the pattern. If `yanked` is set, packages whose installed version has been yanked on PyPI are also reported; index responses are cached for the provided duration.
+    pub(crate) fn to_audit_report(
+        &self,
+        pattern: &str,
+        case_insensitive: bool,
+        yanked: bool,
+        cache_dur: Duration,
+    ) -> AuditReport {
+        let packages = self.search_by_match(pattern, case_insensitive);
+        let projects = if yanked {
+            let mut keys: Vec<String> = packages.iter().map(|p| p.key.clone()).collect();
+            keys.sort();
+            keys.dedup();
+            let cache_dir = path_pypi_cache(cache_dur);
+            Some(query_pypi_projects(
+                &UreqClientLive,
+                &keys,
+                cache_dir.as_deref(),
+                cache_dur,
+            ))
+        } else {
+            None
+        };
+        AuditReport::from_packages(&UreqClientLive, &packages, projects.as_ref())
+    }
+
+    /// Return a report of packages that match the pattern and are older than the latest release on PyPI. Index responses are cached in the cache directory for the provided duration; a duration of zero disables the cache.
+    pub(crate) fn to_outdated_report(
+        &self,
+        pattern: &str,
+        case_insensitive: bool,
+        cache_dur: Duration,
+    ) -> OutdatedReport {
+        let mut packages = self.search_by_match(pattern, case_insensitive);
+        packages.sort();
+        let cache_dir = path_pypi_cache(cache_dur);
+        OutdatedReport::from_packages(
+            &UreqClientLive,
+            &packages,
+            cache_dir.as_deref(),
+            cache_dur,
+        )
+    }
+
+    /// The `count` Boolean determine if what type of UnpackReport is returned
+    pub(crate) fn to_unpack_report(
+        &self,
+        pattern: &str,
+        case_insensitive: bool,
+        count: bool,
+    ) -> UnpackReport {
+        let mut packages = self.search_by_match(pattern, case_insensitive);
+        packages.sort();
+        let package_to_sites = packages
+            .iter()
+            .map(|p| (p.clone(), self.package_to_sites.get(p).unwrap().clone()))
+            .collect();
+
+        UnpackReport::from_package_to_sites(count, &package_to_sites)
+    }
+
+    /// Return a report of the disk usage of packages that match the pattern, per package and site or, if `by_site` is set, per site.
+    pub(crate) fn to_size_report(
+        &self,
+        pattern: &str,
+        case_insensitive: bool,
+        by_site: bool,
+    ) -> SizeReport {
+        let package_to_sites = self
+            .search_by_match(pattern, case_insensitive)
+            .into_iter()
+            .filter_map(|p| {
+                let sites = self.package_to_sites.get(&p)?.clone();
+                Some((p, sites))
+            })
+            .collect();
+        SizeReport::from_package_to_sites(&package_to_sites, by_site)
+    }
+
+    /// Return a report of files in sites not owned by any installed package, optionally restricted to `site`.
+    pub(crate) fn to_stray_report(&self, site: Option<&Path>) -> StrayReport {
+        let site = site.map(|s| path_normalize(s).unwrap_or_else(|_| s.to_path_buf()));
+        let mut site_to_packages: HashMap<PathShared, Vec<Package>> = self
+            .get_sites()
+            .into_iter()
+            .map(|s| (s, Vec::new()))
+            .collect();
+        for (package, sites) in self.package_to_sites.iter() {
+            for s in sites {
+                site_to_packages
+                    .entry(s.clone())
+                    .or_default()
+                    .push(package.clone());
+            }
+        }
+        if let Some(site) = site {
+            site_to_packages.retain(|s, _| s.as_path() == site);
+        }
+        StrayReport::from_site_to_packages(&site_to_packages)
+    }
+
+    /// Return a report of the integrity of the virtual environments of all scanned executables. As interpreters are run and symlinks are followed on this machine, offline scans, of a root or snapshot, are not supported.
+    pub(crate) fn to_doctor_report(&self) -> ResultDynError<DoctorReport> {
+        if self.offline {
+            return Err("doctor cannot examine environments of an offline scan".into());
+        }
+        let mut exes: Vec<PathBuf> = self.exe_to_sites.keys().cloned().collect();
+        exes.sort();
+        Ok(DoctorReport::from_exes(&exes, true))
+    }
+
+    /// Return a report of the files recorded in RECORD for packages that match the pattern, optionally restricted to those found in `site`.
+    pub(crate) fn to_files_report(
+        &self,
+        pattern: &str,
+        case_insensitive: bool,
+        site: Option<&Path>,
+    ) -> UnpackFilesReport {
+        let site = site.map(|s| path_normalize(s).unwrap_or_else(|_| s.to_path_buf()));
+        let packages = self.search_by_name(pattern, case_insensitive);
+        let package_to_sites = packages
+            .iter()
+            .filter_map(|p| {
+                let sites: Vec<PathShared> = self
+                    .package_to_sites
+                    .get(p)
+                    .unwrap()
+                    .iter()
+                    .filter(|s| match &site {
+                        Some(site) => s.as_path() == site,
+                        None => true,
+                    })
+                    .cloned()
+                    .collect();
+                if sites.is_empty() {
+                    None
+                } else {
+                    Some((p.clone(), sites))
+                }
+            })
+            .collect();
+        UnpackFilesReport::from_package_to_sites(&package_to_sites)
+    }
+
+    /// Return a report of installed files that are modified, missing, or extra relative to the RECORD files of packages that match the pattern.
+    pub(crate) fn to_verify_report(
+        &self,
+        pattern: &str,
+        case_insensitive: bool,
+    ) -> VerifyReport {
+        let packages = self.search_by_match(pattern, case_insensitive);
+        let package_to_sites = packages
+            .iter()
+            .map(|p| (p.clone(), self.package_to_sites.get(p).unwrap().clone()))
+            .collect();
+        VerifyReport::from_package_to_sites(&package_to_sites)
+    }
+
+    /// Return a report of the console and GUI scripts of packages that match the pattern that are missing or have a shebang that does not name an executable of their site. As shebangs name interpreters on this machine, offline scans, of a root or snapshot, are not supported.
+    pub(crate) fn to_scripts_report(
+        &self,
+        pattern: &str,
+        case_insensitive: bool,
+    ) -> ResultDynError<ScriptsReport> {
+        if self.offline {
+            return Err("scripts cannot be examined in an offline scan".into());
+        }
+        let packages = self.search_by_match(pattern, case_insensitive);
+        let package_to_sites = packages
+            .iter()
+            .map(|p| (p.clone(), self.package_to_sites.get(p).unwrap().clone()))
+            .collect();
+        let mut site_to_exes: HashMap<PathShared, Vec<PathBuf>> = HashMap::new();
+        for (exe, sites) in &self.exe_to_sites {
+            for site in sites {
+                site_to_exes
+                    .entry(site.clone())
+                    .or_default()
+                    .push(exe.clone());
+            }
+        }
+        Ok(ScriptsReport::from_package_to_sites(
+            &package_to_sites,
+            &site_to_exes,
+        ))
+    }
+
+    /// Given an `anchor`, produce a DepManifest based ont the packages observed in this scan. Packages installed from a VCS are pinned to their installed commit with a direct URL, independent of the anchor.
+    pub(crate) fn to_dep_manifest(
+        &self,
+        anchor: Anchor,
+    ) -> Result<DepManifest, Box<dyn std::error::Error>> {
+        self.to_dep_manifest_with_projects(anchor, None)
+    }
+
+    /// Produce a DepManifest that pins the exact version of each package with the hashes of its artifacts, as needed for `pip install --require-hashes`. Hashes are taken from the PyPI JSON API, with index responses cached for the provided duration, and from the archive hashes of packages installed from a URL.
+    pub(crate) fn to_dep_manifest_hashed(
+        &self,
+        cache_dur: Duration,
+    ) -> Result<DepManifest, Box<dyn std::error::Error>> {
+        let mut keys: Vec<String> = self
+            .package_to_sites
+            .keys()
+            .map(|p| p.key.clone())
+            .collect();
+        keys.sort();
+        keys.dedup();
+        let cache_dir = path_pypi_cache(cache_dur);
+        let projects =
+            query_pypi_projects(&UreqClientLive, &keys, cache_dir.as_deref(), cache_dur);
+        self.to_dep_manifest_with_projects(Anchor::Exact, Some(&projects))
+    }
+
+    fn to_dep_manifest_with_projects(
+        &self,
+        anchor: Anchor,
+        projects: Option<&HashMap<String, PyPIProject>>,
+    ) -> Result<DepManifest, Box<dyn std::error::Error>> {
+        let mut package_name_to_package: HashMap<String, Vec<Package>> = HashMap::new();
+
+        for package in self.package_to_sites.keys() {
+            package_name_to_package
+                .entry(package.name.clone())
+                .or_default()
+                .push(package.clone());
+        }
+        let names: Vec<String> = package_name_to_package.keys().cloned().collect();
         let mut dep_specs: Vec<DepSpec> = Vec::new();
         for name in names {
             let packages = match package_name_to_package.get_mut(&name) {
@@ -433,6 +1819,18 @@ impl ScanFS {
                 None => continue,
             };
 
+            let pkg_anchor = match anchor {
+                Anchor::Lower => pkg_min,
+                _ => pkg_max,
+            };
+            if let Some(url) = pkg_anchor
+                .direct_url
+                .as_ref()
+                .and_then(|durl| durl.to_vcs_url())
+            {
+                dep_specs.push(DepSpec::from_package_url(pkg_anchor, url));
+                continue;
+            }
             let ds = match anchor {
                 Anchor::Lower => {
                     DepSpec::from_package(pkg_min, DepOperator::GreaterThanOrEq)
@@ -440,21 +1838,116 @@ impl ScanFS {
                 Anchor::Upper => {
                     DepSpec::from_package(pkg_max, DepOperator::LessThanOrEq)
                 }
+                Anchor::Exact => DepSpec::from_package(pkg_max, DepOperator::Eq),
+                // a compatible release requires at least two release components
+                Anchor::Compatible if pkg_max.version.to_string().contains('.') => {
+                    DepSpec::from_package(pkg_max, DepOperator::Compatible)
+                }
+                Anchor::Compatible => {
+                    DepSpec::from_package(pkg_max, DepOperator::GreaterThanOrEq)
+                }
                 Anchor::Both => return Err("Not implemented".into()),
             };
-            if let Ok(dep_spec) = ds {
+            if let Ok(mut dep_spec) = ds {
+                if let Some(projects) = projects {
+                    let mut hashes: Vec<String> = pkg_anchor
+                        .direct_url
+                        .as_ref()
+                        .map(|durl| durl.get_archive_hashes())
+                        .unwrap_or_default();
+                    if let Some(project) = projects.get(&pkg_anchor.key) {
+                        hashes.extend(project.get_hashes(&pkg_anchor.version));
+                    }
+                    hashes.sort();
+                    hashes.dedup();
+                    dep_spec.hashes = hashes;
+                }
                 dep_specs.push(dep_spec);
             }
         }
         DepManifest::from_dep_specs(&dep_specs)
     }
 
+    /// Return this scan without user sites (e.g. `~/.local/lib/python3.12/site-packages`) and without packages only found in them.
+    pub(crate) fn without_user_sites(mut self) -> Self {
+        for sites in self.exe_to_sites.values_mut() {
+            sites.retain(|site| !site.is_user_site());
+        }
+        self.package_to_sites = self
+            .package_to_sites
+            .into_iter()
+            .filter_map(|(package, mut sites)| {
+                sites.retain(|site| !site.is_user_site());
+                if sites.is_empty() {
+                    None
+                } else {
+                    Some((package, sites))
+                }
+            })
+            .collect();
+        self
+    }
+
+    /// If more than one executable was scanned, return a mapping of each site to the sorted executables that use it, such that reports can show the executable of each row.
+    pub(crate) fn get_site_to_exes(&self) -> Option<HashMap<PathShared, Vec<PathBuf>>> {
+        if self.exe_to_sites.len() < 2 {
+            return None;
+        }
+        let mut site_to_exes: HashMap<PathShared, Vec<PathBuf>> = HashMap::new();
+        for (exe, sites) in &self.exe_to_sites {
+            for site in sites {
+                site_to_exes
+                    .entry(site.clone())
+                    .or_default()
+                    .push(exe.clone());
+            }
+        }
+        for exes in site_to_exes.values_mut() {
+            exes.sort();
+        }
+        Some(site_to_exes)
+    }
+
+    /// Return true if any executable or site could not be scanned.
+    pub(crate) fn has_warnings(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+
+    /// Return a report of the executables and sites that could not be scanned.
+    pub(crate) fn to_warning_report(&self) -> WarningReport {
+        WarningReport::from_warnings(&self.warnings)
+    }
+
     pub(crate) fn to_scan_report(&self) -> ScanReport {
-        ScanReport::from_package_to_sites(&self.package_to_sites)
+        ScanReport::from_package_to_sites(
+            &self.package_to_sites,
+            self.get_site_to_exes().as_ref(),
+        )
+    }
+
+    /// Return a report of packages added, removed, and changed in version from this scan to another scan.
+    pub(crate) fn to_diff_report(&self, other: &ScanFS) -> DiffReport {
+        DiffReport::from_packages(
+            self.package_to_sites.keys(),
+            other.package_to_sites.keys(),
+        )
+    }
+
+    pub(crate) fn to_count_report(&self, detail: bool) -> CountReport {
+        CountReport::from_scan_fs(self, detail)
     }
 
-    pub(crate) fn to_count_report(&self) -> CountReport {
-        CountReport::from_scan_fs(self)
+    pub(crate) fn to_sbom(&self, format: SbomFormat) -> Sbom {
+        let timestamp = time_to_iso8601(SystemTime::now());
+        match format {
+            SbomFormat::CycloneDX => Sbom::CycloneDX(
+                CycloneDXBom::from_package_to_sites(&self.package_to_sites, timestamp),
+            ),
+            SbomFormat::Spdx => Sbom::Spdx(SPDXDocument::from_package_to_sites(
+                &self.package_to_sites,
+                timestamp,
+            )),
+        }
     }
 
     pub(crate) fn to_search_report(
@@ -464,15 +1957,19 @@ impl ScanFS {
     ) -> ScanReport {
         let packages = self.search_by_match(pattern, case_insensitive);
         // println!("packages: {:?}", packages);
-        ScanReport::from_packages(&packages, &self.package_to_sites)
+        ScanReport::from_packages(
+            &packages,
+            &self.package_to_sites,
+            self.get_site_to_exes().as_ref(),
+        )
     }
 
-    pub(crate) fn to_purge_pattern(
+    /// Return an UnpackReport of the artifacts of packages that match the pattern, or of all packages if no pattern is provided.
+    pub(crate) fn to_purge_pattern_report(
         &self,
         pattern: &Option<String>,
         case_insensitive: bool,
-        log: bool,
-    ) -> io::Result<()> {
+    ) -> UnpackReport {
         let packages = match pattern {
             Some(p) => self.search_by_match(p, case_insensitive),
             None => self.package_to_sites.keys().cloned().collect(),
@@ -482,21 +1979,28 @@ impl ScanFS {
             .iter()
             .map(|p| (p.clone(), self.package_to_sites.get(p).unwrap().clone()))
             .collect();
-
-        let sr = UnpackReport::from_package_to_sites(false, &package_to_sites);
-        sr.remove(log)
+        UnpackReport::from_package_to_sites(false, &package_to_sites)
     }
 
-    pub(crate) fn to_purge_invalid(
+    /// Return an UnpackReport of the artifacts of packages that are unrequired or misdefined by the DepManifest.
+    pub(crate) fn to_purge_invalid_report(
         &self,
         dm: DepManifest,
         vf: ValidationFlags,
-        log: bool,
-    ) -> io::Result<()> {
+    ) -> UnpackReport {
         let vr = self.to_validation_report(dm, vf);
+        // only packages that are not required, or not at a required version, are purged; integrity and policy records are not fixed by uninstalling
         let packages: Vec<Package> = vr
             .records
             .iter()
+            .filter(|r| {
+                matches!(
+                    r.explain(),
+                    ValidationExplain::Misdefined
+                        | ValidationExplain::Unrequired
+                        | ValidationExplain::Orphan
+                )
+            })
             .filter_map(|r| r.package.as_ref().cloned())
             .collect();
         // packages.sort();
@@ -504,27 +2008,77 @@ impl ScanFS {
             .iter()
             .map(|p| (p.clone(), self.package_to_sites.get(p).unwrap().clone()))
             .collect();
-
-        let sr = UnpackReport::from_package_to_sites(false, &package_to_sites);
-        sr.remove(log)
+        UnpackReport::from_package_to_sites(false, &package_to_sites)
     }
 }
 
 //------------------------------------------------------------------------------
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison, clippy::len_zero, clippy::useless_vec)]
 mod tests {
     use super::*;
+    use crate::git_client::GitClientMock;
+    use crate::package_durl::DirectURL;
+    use crate::table::Tableable;
+    use crate::zip_index::write_zip_stored;
     use std::fs::File;
     use tempfile::tempdir;
 
     #[test]
     fn test_get_site_package_dirs_a() {
         let p1 = Path::new("python3");
-        let paths1 = get_site_package_dirs(p1, true);
+        let paths1 = get_site_package_dirs(p1, true).unwrap();
         assert_eq!(paths1.len() > 0, true);
-        let paths2 = get_site_package_dirs(p1, false);
+        let paths2 = get_site_package_dirs(p1, false).unwrap();
         assert!(paths1.len() >= paths2.len());
     }
+    #[test]
+    fn test_get_site_package_dirs_b() {
+        let dir = tempdir().unwrap();
+        let exe = dir.path().join("python");
+        let e = get_site_package_dirs(&exe, false).unwrap_err();
+        assert!(matches!(e, FetterError::ExeRun { .. }));
+        let (exe_to_sites, warnings) =
+            partition_exe_to_sites(vec![(exe.clone(), Err(e))]);
+        assert_eq!(exe_to_sites[&exe].len(), 0);
+        assert_eq!(warnings[0].path, exe);
+    }
+
+    #[test]
+    fn test_from_exe_to_sites_warnings_a() {
+        let dir = tempdir().unwrap();
+        let exe = dir.path().join("python");
+        let site_valid = dir.path().join("site-packages");
+        fs::create_dir_all(site_valid.join("six-1.16.0.dist-info")).unwrap();
+        // a file in place of a site cannot be read
+        let site_invalid = dir.path().join("dist-packages");
+        File::create(&site_invalid).unwrap();
+        let site_missing = dir.path().join("missing");
+
+        let mut exe_to_sites = HashMap::<PathBuf, Vec<PathShared>>::new();
+        exe_to_sites.insert(
+            exe,
+            vec![
+                PathShared::from_path_buf(site_valid),
+                PathShared::from_path_buf(site_invalid.clone()),
+                PathShared::from_path_buf(site_missing),
+            ],
+        );
+        let sfs = ScanFS::from_exe_to_sites(
+            exe_to_sites,
+            false,
+            "".to_string(),
+            false,
+            Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(sfs.package_to_sites.len(), 1);
+        assert!(sfs.has_warnings());
+        let wr = sfs.to_warning_report();
+        assert_eq!(wr.get_records().len(), 1);
+        assert_eq!(wr.get_records()[0].path, site_invalid);
+    }
+
     #[test]
     fn test_from_exe_to_sites_a() {
         let fp_dir = tempdir().unwrap();
@@ -545,7 +2099,14 @@ mod tests {
             fp_exe.clone(),
             vec![PathShared::from_path_buf(fp_sp.to_path_buf())],
         );
-        let sfs = ScanFS::from_exe_to_sites(exe_to_sites, false, "".to_string()).unwrap();
+        let sfs = ScanFS::from_exe_to_sites(
+            exe_to_sites,
+            false,
+            "".to_string(),
+            false,
+            Vec::new(),
+        )
+        .unwrap();
         assert_eq!(sfs.package_to_sites.len(), 2);
 
         let dm1 = DepManifest::from_iter(vec!["numpy >= 1.19", "foo==3"]).unwrap();
@@ -555,6 +2116,13 @@ mod tests {
             ValidationFlags {
                 permit_superset: false,
                 permit_subset: false,
+                resolve_revisions: false,
+                reject_editable: false,
+                report_orphans: false,
+                ignore_local: false,
+                reject_prerelease: false,
+                require_hashes: false,
+                exact: false,
             },
         );
         assert_eq!(invalid1.len(), 0);
@@ -565,10 +2133,123 @@ mod tests {
             ValidationFlags {
                 permit_superset: false,
                 permit_subset: false,
+                resolve_revisions: false,
+                reject_editable: false,
+                report_orphans: false,
+                ignore_local: false,
+                reject_prerelease: false,
+                require_hashes: false,
+                exact: false,
             },
         );
         assert_eq!(invalid2.len(), 1);
     }
+
+    #[test]
+    fn test_from_exe_to_sites_c() {
+        let fp_dir = tempdir().unwrap();
+        let mut sites = Vec::new();
+        for name in ["sp-c", "sp-a", "sp-b"] {
+            let fp_sp = fp_dir.path().join(name);
+            fs::create_dir(&fp_sp).unwrap();
+            fs::create_dir(fp_sp.join("numpy-1.19.1.dist-info")).unwrap();
+            sites.push(PathShared::from_path_buf(fp_sp));
+        }
+        // sites shared by exes are processed once and recorded in sorted order
+        let mut exe_to_sites = HashMap::<PathBuf, Vec<PathShared>>::new();
+        exe_to_sites.insert(fp_dir.path().join("python3"), sites.clone());
+        exe_to_sites.insert(fp_dir.path().join("python"), vec![sites[0].clone()]);
+        let sfs = ScanFS::from_exe_to_sites(
+            exe_to_sites,
+            false,
+            "".to_string(),
+            false,
+            Vec::new(),
+        )
+        .unwrap();
+
+        let p1 = Package::from_name_version_durl("numpy", "1.19.1", None).unwrap();
+        assert_eq!(
+            sfs.package_to_sites[&p1],
+            vec![sites[1].clone(), sites[2].clone(), sites[0].clone()]
+        );
+    }
+
+    #[test]
+    fn test_from_exe_to_sites_b() {
+        let fp_dir = tempdir().unwrap();
+        let fp_bin = fp_dir.path().join("bin");
+        fs::create_dir(&fp_bin).unwrap();
+        let fp_exe = fp_bin.join("python");
+        let _ = File::create(fp_exe.clone()).unwrap();
+
+        let fp_sp = fp_dir.path().join("lib/python3.11/site-packages");
+        fs::create_dir_all(&fp_sp).unwrap();
+        fs::create_dir(fp_sp.join("PyYAML-6.0.2.dist-info")).unwrap();
+        fs::create_dir(fp_sp.join("requests-2.32.3.dist-info")).unwrap();
+
+        let fp_cm = fp_dir.path().join("conda-meta");
+        fs::create_dir(&fp_cm).unwrap();
+        fs::write(
+            fp_cm.join("pyyaml-6.0.2-py311h2dc5d0c_1.json"),
+            r#"{"name": "pyyaml", "version": "6.0.2", "files": ["lib/python3.11/site-packages/PyYAML-6.0.2.dist-info/METADATA", "lib/python3.11/site-packages/yaml/__init__.py"]}"#,
+        )
+        .unwrap();
+        fs::write(
+            fp_cm.join("pycosat-0.6.6-py311h459d7ec_0.json"),
+            r#"{"name": "pycosat", "version": "0.6.6", "files": ["lib/python3.11/site-packages/pycosat.cpython-311-x86_64-linux-gnu.so", "lib/python3.11/site-packages/pycosat-0.6.6-py3.11.egg-info/PKG-INFO"]}"#,
+        )
+        .unwrap();
+        fs::write(
+            fp_cm.join("openssl-3.3.2-hb9d3cd8_0.json"),
+            r#"{"name": "openssl", "version": "3.3.2", "files": ["lib/libssl.so.3"]}"#,
+        )
+        .unwrap();
+
+        let site = PathShared::from_path_buf(fp_sp.to_path_buf());
+        let mut exe_to_sites = HashMap::<PathBuf, Vec<PathShared>>::new();
+        exe_to_sites.insert(fp_exe.clone(), vec![site.clone()]);
+        let sfs = ScanFS::from_exe_to_sites(
+            exe_to_sites,
+            false,
+            "".to_string(),
+            false,
+            Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            sfs.get_packages()
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>(),
+            vec!["pycosat-0.6.6", "PyYAML-6.0.2", "requests-2.32.3"]
+        );
+        let site_cm = PathShared::from_path_buf(fp_cm.clone());
+        let p1 = Package::from_name_version_durl("PyYAML", "6.0.2", None).unwrap();
+        let sites1 = &sfs.package_to_sites[&p1];
+        assert_eq!(sites1.len(), 2);
+        assert!(sites1.contains(&site) && sites1.contains(&site_cm));
+
+        let p2 = Package::from_name_version_durl("pycosat", "0.6.6", None).unwrap();
+        assert_eq!(sfs.package_to_sites[&p2], vec![site_cm]);
+
+        let dm = DepManifest::from_iter(vec!["pycosat==0.6.6"]).unwrap();
+        let vr = sfs.to_validation_report(
+            dm,
+            ValidationFlags {
+                permit_superset: true,
+                permit_subset: false,
+                resolve_revisions: false,
+                reject_editable: false,
+                report_orphans: false,
+                ignore_local: false,
+                reject_prerelease: false,
+                require_hashes: false,
+                exact: false,
+            },
+        );
+        assert_eq!(vr.len(), 0);
+    }
     //--------------------------------------------------------------------------
     #[test]
     fn from_exe_site_packages_a() {
@@ -590,15 +2271,97 @@ mod tests {
         assert_eq!(dm.len(), 3);
     }
 
-    //--------------------------------------------------------------------------
     #[test]
-    fn test_validation_a() {
+    fn test_to_dep_manifest_a() {
         let exe = PathBuf::from("/usr/bin/python3");
-        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let site = PathBuf::from("/usr/lib/python3.8/site-packages");
+        let durl = DirectURL::from_url_vcs_cid(
+            "https://github.com/pypa/packaging.git".to_string(),
+            Some("git".to_string()),
+            Some("0bbc7e4b4c0ee8b6b0b1f5d7c7e2b0b0c8a4c1a2".to_string()),
+        )
+        .unwrap();
         let packages = vec![
             Package::from_name_version_durl("numpy", "1.19.3", None).unwrap(),
-            Package::from_name_version_durl("requests", "0.7.6", None).unwrap(),
-            Package::from_name_version_durl("flask", "1.1.3", None).unwrap(),
+            Package::from_name_version_durl("numpy", "2.1.1", None).unwrap(),
+            Package::from_name_version_durl("pytz", "2024", None).unwrap(),
+            Package::from_name_version_durl("packaging", "24.2", Some(durl)).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let to_lines = |anchor: Anchor| -> Vec<String> {
+            let dm = sfs.to_dep_manifest(anchor).unwrap();
+            let mut lines: Vec<String> = ["numpy", "packaging", "pytz"]
+                .iter()
+                .map(|k| dm.get_dep_spec(k).unwrap().to_string())
+                .collect();
+            lines.sort();
+            lines
+        };
+        assert_eq!(
+            to_lines(Anchor::Exact),
+            vec![
+                "numpy==2.1.1",
+                "packaging @ git+https://github.com/pypa/packaging.git@0bbc7e4b4c0ee8b6b0b1f5d7c7e2b0b0c8a4c1a2",
+                "pytz==2024",
+            ]
+        );
+        assert_eq!(
+            to_lines(Anchor::Compatible),
+            vec![
+                "numpy~=2.1.1",
+                "packaging @ git+https://github.com/pypa/packaging.git@0bbc7e4b4c0ee8b6b0b1f5d7c7e2b0b0c8a4c1a2",
+                "pytz>=2024",
+            ]
+        );
+        assert_eq!(to_lines(Anchor::Lower)[0], "numpy>=1.19.3");
+    }
+
+    #[test]
+    fn test_to_dep_manifest_b() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3.8/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("certifi", "2024.8.30", None).unwrap(),
+            Package::from_name_version_durl("idna", "3.10", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let content = r#"{"info":{"version":"2024.8.30"},"releases":{"2024.8.30":[{"digests":{"sha256":"bec941d2aa8195e248a60b31ff9f0558284cf01a52591ceda73ea9afffd69fd9"}},{"digests":{"sha256":"922820b53db7a7257ffbda3f597266d435245903d80737e34f8a45ff3e3230d8"}}]}}"#;
+        let mut projects = HashMap::new();
+        projects.insert(
+            "certifi".to_string(),
+            PyPIProject::from_str(content).unwrap(),
+        );
+        let dm = sfs
+            .to_dep_manifest_with_projects(Anchor::Exact, Some(&projects))
+            .unwrap();
+        assert_eq!(
+            dm.get_dep_spec("certifi").unwrap().hashes,
+            vec![
+                "sha256:922820b53db7a7257ffbda3f597266d435245903d80737e34f8a45ff3e3230d8",
+                "sha256:bec941d2aa8195e248a60b31ff9f0558284cf01a52591ceda73ea9afffd69fd9",
+            ]
+        );
+        assert!(dm.get_dep_spec("idna").unwrap().hashes.is_empty());
+
+        let dmr = dm.to_dep_manifest_report();
+        let dir = tempdir().unwrap();
+        let fp = dir.path().join("requirements.txt");
+        dmr.to_file(&fp, ' ').unwrap();
+        // the output can be read as a requirements file
+        let dm2 = DepManifest::from_requirements_file(&fp).unwrap();
+        assert_eq!(dm2.get_dep_spec("certifi").unwrap().hashes.len(), 2);
+        assert_eq!(dm2.get_dep_spec("idna").unwrap().to_string(), "idna==3.10");
+    }
+
+    //--------------------------------------------------------------------------
+    #[test]
+    fn test_validation_a() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "1.19.3", None).unwrap(),
+            Package::from_name_version_durl("requests", "0.7.6", None).unwrap(),
+            Package::from_name_version_durl("flask", "1.1.3", None).unwrap(),
         ];
         let dm = DepManifest::from_iter(
             vec!["numpy>1.19", "requests==0.7.6", "flask> 1"].iter(),
@@ -611,6 +2374,13 @@ mod tests {
             ValidationFlags {
                 permit_superset: false,
                 permit_subset: false,
+                resolve_revisions: false,
+                reject_editable: false,
+                report_orphans: false,
+                ignore_local: false,
+                reject_prerelease: false,
+                require_hashes: false,
+                exact: false,
             },
         );
         assert_eq!(vr.len(), 0);
@@ -635,13 +2405,20 @@ mod tests {
             ValidationFlags {
                 permit_superset: false,
                 permit_subset: false,
+                resolve_revisions: false,
+                reject_editable: false,
+                report_orphans: false,
+                ignore_local: false,
+                reject_prerelease: false,
+                require_hashes: false,
+                exact: false,
             },
         );
 
         let json = serde_json::to_string(&vr.to_validation_digest()).unwrap();
         assert_eq!(
             json,
-            r#"[{"package":"flask-1.1.3","dependency":"flask>2","explain":"Misdefined","sites":["/usr/lib/python3/site-packages"]}]"#
+            r#"[{"package":"flask-1.1.3","dependency":"flask>2","explain":"Misdefined","code":"V003","category":"requirement","sites":["/usr/lib/python3/site-packages"]}]"#
         );
     }
     #[test]
@@ -664,13 +2441,20 @@ mod tests {
             ValidationFlags {
                 permit_superset: false,
                 permit_subset: false,
+                resolve_revisions: false,
+                reject_editable: false,
+                report_orphans: false,
+                ignore_local: false,
+                reject_prerelease: false,
+                require_hashes: false,
+                exact: false,
             },
         );
         assert_eq!(sfs.exe_to_sites.get(&exe).unwrap()[0].strong_count(), 7);
         let json = serde_json::to_string(&vr.to_validation_digest()).unwrap();
         assert_eq!(
             json,
-            r#"[{"package":"flask-1.1.3","dependency":"flask>2,<3","explain":"Misdefined","sites":["/usr/lib/python3/site-packages"]},{"package":"numpy-1.19.3","dependency":"numpy>2","explain":"Misdefined","sites":["/usr/lib/python3/site-packages"]},{"package":"requests-0.7.6","dependency":"requests==0.7.1","explain":"Misdefined","sites":["/usr/lib/python3/site-packages"]}]"#
+            r#"[{"package":"flask-1.1.3","dependency":"flask>2,<3","explain":"Misdefined","code":"V003","category":"requirement","sites":["/usr/lib/python3/site-packages"]},{"package":"numpy-1.19.3","dependency":"numpy>2","explain":"Misdefined","code":"V003","category":"requirement","sites":["/usr/lib/python3/site-packages"]},{"package":"requests-0.7.6","dependency":"requests==0.7.1","explain":"Misdefined","code":"V003","category":"requirement","sites":["/usr/lib/python3/site-packages"]}]"#
         );
     }
 
@@ -692,12 +2476,19 @@ mod tests {
             ValidationFlags {
                 permit_superset: true,
                 permit_subset: false,
+                resolve_revisions: false,
+                reject_editable: false,
+                report_orphans: false,
+                ignore_local: false,
+                reject_prerelease: false,
+                require_hashes: false,
+                exact: false,
             },
         );
         let json = serde_json::to_string(&vr.to_validation_digest()).unwrap();
         assert_eq!(
             json,
-            r#"[{"package":"flask-1.1.3","dependency":"flask>2,<3","explain":"Misdefined","sites":["/usr/lib/python3/site-packages"]},{"package":"numpy-1.19.3","dependency":"numpy>2","explain":"Misdefined","sites":["/usr/lib/python3/site-packages"]}]"#
+            r#"[{"package":"flask-1.1.3","dependency":"flask>2,<3","explain":"Misdefined","code":"V003","category":"requirement","sites":["/usr/lib/python3/site-packages"]},{"package":"numpy-1.19.3","dependency":"numpy>2","explain":"Misdefined","code":"V003","category":"requirement","sites":["/usr/lib/python3/site-packages"]}]"#
         );
     }
     #[test]
@@ -721,6 +2512,13 @@ mod tests {
             ValidationFlags {
                 permit_superset: false,
                 permit_subset: false,
+                resolve_revisions: false,
+                reject_editable: false,
+                report_orphans: false,
+                ignore_local: false,
+                reject_prerelease: false,
+                require_hashes: false,
+                exact: false,
             },
         );
         assert_eq!(vr.len(), 0);
@@ -745,13 +2543,20 @@ mod tests {
             ValidationFlags {
                 permit_superset: false,
                 permit_subset: false,
+                resolve_revisions: false,
+                reject_editable: false,
+                report_orphans: false,
+                ignore_local: false,
+                reject_prerelease: false,
+                require_hashes: false,
+                exact: false,
             },
         );
         assert_eq!(vr.len(), 1);
         let json = serde_json::to_string(&vr.to_validation_digest()).unwrap();
         assert_eq!(
             json,
-            r#"[{"package":null,"dependency":"flask>1,<2","explain":"Missing","sites":null}]"#
+            r#"[{"package":null,"dependency":"flask>1,<2","explain":"Missing","code":"V001","category":"requirement","sites":null}]"#
         );
     }
     #[test]
@@ -769,13 +2574,20 @@ mod tests {
             ValidationFlags {
                 permit_superset: false,
                 permit_subset: false,
+                resolve_revisions: false,
+                reject_editable: false,
+                report_orphans: false,
+                ignore_local: false,
+                reject_prerelease: false,
+                require_hashes: false,
+                exact: false,
             },
         );
         assert_eq!(vr1.len(), 1);
         let json = serde_json::to_string(&vr1.to_validation_digest()).unwrap();
         assert_eq!(
             json,
-            r#"[{"package":"static-frame-2.13.0","dependency":null,"explain":"Unrequired","sites":["/usr/lib/python3/site-packages"]}]"#
+            r#"[{"package":"static-frame-2.13.0","dependency":null,"explain":"Unrequired","code":"V002","category":"inventory","sites":["/usr/lib/python3/site-packages"]}]"#
         );
 
         let vr2 = sfs.to_validation_report(
@@ -783,6 +2595,13 @@ mod tests {
             ValidationFlags {
                 permit_superset: true,
                 permit_subset: false,
+                resolve_revisions: false,
+                reject_editable: false,
+                report_orphans: false,
+                ignore_local: false,
+                reject_prerelease: false,
+                require_hashes: false,
+                exact: false,
             },
         );
         assert_eq!(vr2.len(), 0);
@@ -807,12 +2626,19 @@ mod tests {
             ValidationFlags {
                 permit_superset: false,
                 permit_subset: false,
+                resolve_revisions: false,
+                reject_editable: false,
+                report_orphans: false,
+                ignore_local: false,
+                reject_prerelease: false,
+                require_hashes: false,
+                exact: false,
             },
         );
         let json = serde_json::to_string(&vr1.to_validation_digest()).unwrap();
         assert_eq!(
             json,
-            r#"[{"package":null,"dependency":"flask>1,<2","explain":"Missing","sites":null}]"#
+            r#"[{"package":null,"dependency":"flask>1,<2","explain":"Missing","code":"V001","category":"requirement","sites":null}]"#
         );
 
         let vr2 = sfs.to_validation_report(
@@ -820,11 +2646,412 @@ mod tests {
             ValidationFlags {
                 permit_superset: false,
                 permit_subset: true,
+                resolve_revisions: false,
+                reject_editable: false,
+                report_orphans: false,
+                ignore_local: false,
+                reject_prerelease: false,
+                require_hashes: false,
+                exact: false,
             },
         );
         assert_eq!(vr2.len(), 0);
     }
 
+    #[test]
+    fn test_validation_local_label_a() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages =
+            vec![Package::from_name_version_durl("torch", "2.1.0+cu118", None).unwrap()];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let dm = DepManifest::from_iter(vec!["torch==2.1.0"].iter()).unwrap();
+
+        let vr1 = sfs.to_validation_report(dm.clone(), ValidationFlags::default());
+        let json = serde_json::to_string(&vr1.to_validation_digest()).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"package":"torch-2.1.0+cu118","dependency":"torch==2.1.0","explain":"Misdefined","code":"V003","category":"requirement","sites":["/usr/lib/python3/site-packages"],"reason":"installed version has local label +cu118"}]"#
+        );
+
+        let vf = ValidationFlags {
+            ignore_local: true,
+            ..ValidationFlags::default()
+        };
+        let vr2 = sfs.to_validation_report(dm, vf);
+        assert_eq!(vr2.len(), 0);
+    }
+
+    #[test]
+    fn test_find_pypackages_site_a() {
+        let dir = tempdir().unwrap();
+        let project = dir.path().join("proj");
+        let site = project.join("__pypackages__/3.12/lib");
+        fs::create_dir_all(site.join("six-1.16.0.dist-info")).unwrap();
+        fs::create_dir_all(project.join("src/app")).unwrap();
+        fs::create_dir_all(dir.path().join("other")).unwrap();
+
+        assert_eq!(find_pypackages_site(&project, "3.12"), Some(site.clone()));
+        // the project root is found from a subdirectory
+        assert_eq!(
+            find_pypackages_site(&project.join("src/app"), "3.12"),
+            Some(site.clone())
+        );
+        assert_eq!(find_pypackages_site(&project, "3.11"), None);
+        assert_eq!(
+            find_pypackages_site(&dir.path().join("other"), "3.12"),
+            None
+        );
+        assert_eq!(site_to_python_version(&site), Some("3.12".to_string()));
+        let packages: Vec<String> = get_packages(&site)
+            .unwrap()
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        assert_eq!(packages, vec!["six-1.16.0"]);
+    }
+
+    #[test]
+    fn test_sys_path_to_sites_a() {
+        let dir = tempdir().unwrap();
+        let site = dir.path().join("lib/python3.12/site-packages");
+        let stdlib = dir.path().join("lib/python3.12");
+        let pth_dir = dir.path().join("opt/vendored");
+        let pythonpath_dir = dir.path().join("work/libs");
+        fs::create_dir_all(site.join("six-1.16.0.dist-info")).unwrap();
+        fs::create_dir_all(stdlib.join("json")).unwrap();
+        fs::create_dir_all(pth_dir.join("attrs-24.2.0.dist-info")).unwrap();
+        fs::create_dir_all(pythonpath_dir.join("pytz-2024.2.dist-info")).unwrap();
+        // the source directory of an editable install
+        let src_dir = dir.path().join("src/mypkg");
+        fs::create_dir_all(src_dir.join("mypkg.egg-info")).unwrap();
+        // a zipped egg in a site and a zip archive added by PYTHONPATH
+        let site_egg = site.join("requests-2.32.3-py3.12.egg");
+        write_zip_stored(
+            &site_egg,
+            b"",
+            &[("EGG-INFO/PKG-INFO", "Name: requests\nVersion: 2.32.3\n")],
+        )
+        .unwrap();
+        let vendor_zip = dir.path().join("work/vendor.zip");
+        write_zip_stored(
+            &vendor_zip,
+            b"",
+            &[(
+                "idna-3.10.dist-info/METADATA",
+                "Name: idna\nVersion: 3.10\n",
+            )],
+        )
+        .unwrap();
+        let sys_path = vec![
+            PathBuf::from(""),
+            pythonpath_dir.clone(),
+            vendor_zip.clone(),
+            dir.path().join("lib/python312.zip"),
+            stdlib,
+            site.clone(),
+            pth_dir.clone(),
+            pth_dir.clone(),
+            site_egg,
+            src_dir,
+            dir.path().join("missing"),
+        ];
+        let sites = vec![PathShared::from_path_buf(site)];
+        let extra = sys_path_to_sites(&sys_path, &sites);
+        assert_eq!(
+            extra.iter().map(|s| s.as_path()).collect::<Vec<_>>(),
+            vec![
+                pythonpath_dir.as_path(),
+                vendor_zip.as_path(),
+                pth_dir.as_path()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_exes_zipapp_a() {
+        let dir = tempdir().unwrap();
+        let fp = dir.path().join("app.pyz");
+        write_zip_stored(
+            &fp,
+            b"#!/usr/bin/env python3\n",
+            &[
+                ("__main__.py", "import six\n"),
+                (
+                    "six-1.16.0.dist-info/METADATA",
+                    "Name: six\nVersion: 1.16.0\n",
+                ),
+                (
+                    "attrs-24.2.0.dist-info/METADATA",
+                    "Name: attrs\nVersion: 24.2.0\n",
+                ),
+            ],
+        )
+        .unwrap();
+        let sfs =
+            ScanFS::from_exes(std::slice::from_ref(&fp), ScanFlags::default()).unwrap();
+        assert_eq!(
+            sfs.get_sites()
+                .iter()
+                .map(|s| s.as_path().to_path_buf())
+                .collect::<Vec<_>>(),
+            vec![fp.clone()]
+        );
+        let mut packages: Vec<String> =
+            sfs.get_packages().iter().map(|p| p.to_string()).collect();
+        packages.sort();
+        assert_eq!(packages, vec!["attrs-24.2.0", "six-1.16.0"]);
+        // the archive is not run to get environment marker values
+        assert!(sfs.get_marker_envs().is_empty());
+
+        let dm = DepManifest::from_iter(vec!["six==1.16.0", "attrs>=25"].iter()).unwrap();
+        let vr = sfs.to_validation_report(dm, ValidationFlags::default());
+        assert_eq!(vr.len(), 1);
+    }
+
+    #[test]
+    fn test_without_user_sites_a() {
+        let home = match crate::util::path_home() {
+            Some(home) => home,
+            None => return,
+        };
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "2.1.2", None).unwrap(),
+            Package::from_name_version_durl("six", "1.16.0", None).unwrap(),
+        ];
+        let mut sfs =
+            ScanFS::from_exe_site_packages(exe.clone(), site, packages).unwrap();
+        let usite =
+            PathShared::from_path_buf(home.join(".local/lib/python3.12/site-packages"));
+        assert!(usite.is_user_site());
+        sfs.exe_to_sites.get_mut(&exe).unwrap().push(usite.clone());
+        sfs.package_to_sites.insert(
+            Package::from_name_version_durl("requests", "2.32.3", None).unwrap(),
+            vec![usite.clone()],
+        );
+        sfs.package_to_sites
+            .get_mut(&Package::from_name_version_durl("six", "1.16.0", None).unwrap())
+            .unwrap()
+            .push(usite);
+
+        let sfs = sfs.without_user_sites();
+        assert_eq!(sfs.exe_to_sites.get(&exe).unwrap().len(), 1);
+        assert_eq!(
+            sfs.get_packages()
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>(),
+            vec!["numpy-2.1.2", "six-1.16.0"]
+        );
+    }
+
+    #[test]
+    fn test_site_to_exes_a() {
+        let exe = PathBuf::from("/opt/venvs/a/bin/python");
+        let site = PathBuf::from("/opt/venvs/a/lib/python3.12/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "2.1.2", None).unwrap(),
+            Package::from_name_version_durl("six", "1.16.0", None).unwrap(),
+        ];
+        let mut sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        assert!(sfs.get_site_to_exes().is_none());
+
+        let site_b = PathShared::from_path_buf(PathBuf::from(
+            "/opt/venvs/b/lib/python3.12/site-packages",
+        ));
+        sfs.exe_to_sites.insert(
+            PathBuf::from("/opt/venvs/b/bin/python"),
+            vec![site_b.clone()],
+        );
+        sfs.package_to_sites.insert(
+            Package::from_name_version_durl("numpy", "1.26.4", None).unwrap(),
+            vec![site_b],
+        );
+
+        let dir = tempdir().unwrap();
+        let fp = dir.path().join("scan.txt");
+        sfs.to_scan_report().to_file(&fp, '|').unwrap();
+        assert_eq!(
+            fs::read_to_string(&fp).unwrap().lines().collect::<Vec<_>>(),
+            vec![
+                "Package|Site|Executables",
+                "numpy-1.26.4|/opt/venvs/b/lib/python3.12/site-packages|/opt/venvs/b/bin/python",
+                "numpy-2.1.2|/opt/venvs/a/lib/python3.12/site-packages|/opt/venvs/a/bin/python",
+                "six-1.16.0|/opt/venvs/a/lib/python3.12/site-packages|/opt/venvs/a/bin/python",
+            ]
+        );
+
+        let dm = DepManifest::from_iter(vec!["numpy>=2", "six"].iter()).unwrap();
+        let mut vr = sfs.to_validation_report(dm, ValidationFlags::default());
+        vr.set_site_to_exes(&sfs.get_site_to_exes().unwrap());
+        let fp = dir.path().join("validate.txt");
+        vr.to_file(&fp, '|').unwrap();
+        assert_eq!(
+            fs::read_to_string(&fp).unwrap().lines().collect::<Vec<_>>(),
+            vec![
+                "Package|Dependency|Explain|Sites|Executables",
+                "numpy-1.26.4|numpy>=2|Misdefined|/opt/venvs/b/lib/python3.12/site-packages (exclusive: 1)|/opt/venvs/b/bin/python",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_purge_invalid_report_a() {
+        let dir = tempdir().unwrap();
+        let dir_site = dir.path().join("lib/python3.12/site-packages");
+        for name in ["six-1.16.0", "flask-1.2", "numpy-1.26.4", "requests-2.32.3"] {
+            let dir_dist_info = dir_site.join(format!("{}.dist-info", name));
+            fs::create_dir_all(&dir_dist_info).unwrap();
+            fs::write(
+                dir_dist_info.join("RECORD"),
+                format!("{}.dist-info/RECORD,,\n", name),
+            )
+            .unwrap();
+        }
+        let durl: DirectURL = serde_json::from_str(
+            r#"{"url": "https://example.com/six-1.16.0-py2.py3-none-any.whl", "archive_info": {"hash": "sha256=8abb2f1d"}}"#,
+        )
+        .unwrap();
+        let packages = vec![
+            Package::from_name_version_durl("six", "1.16.0", Some(durl)).unwrap(),
+            Package::from_name_version_durl("flask", "1.2", None).unwrap(),
+            Package::from_name_version_durl("numpy", "1.26.4", None).unwrap(),
+            Package::from_name_version_durl("requests", "2.32.3", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(
+            PathBuf::from("/usr/bin/python3"),
+            dir_site,
+            packages,
+        )
+        .unwrap();
+        let dm = DepManifest::from_iter(
+            vec![
+                "six==1.16.0 --hash=sha256:1e61c374",
+                "flask==1.2 --hash=sha256:1e61c374",
+                "numpy>=2",
+            ]
+            .iter(),
+        )
+        .unwrap();
+        let vf = ValidationFlags {
+            require_hashes: true,
+            ..ValidationFlags::default()
+        };
+        assert_eq!(sfs.to_validation_report(dm.clone(), vf).len(), 4);
+
+        // HashMismatch and HashUnverifiable packages are not purged
+        let ur = sfs.to_purge_invalid_report(dm, vf);
+        assert_eq!(ur.len(), 2);
+        let dir_out = tempdir().unwrap();
+        let fp = dir_out.path().join("purge.csv");
+        ur.to_output(&fp).unwrap();
+        let content = fs::read_to_string(&fp).unwrap();
+        assert!(content.contains("numpy-1.26.4"));
+        assert!(content.contains("requests-2.32.3"));
+        assert!(!content.contains("six-1.16.0"));
+        assert!(!content.contains("flask-1.2"));
+    }
+
+    #[test]
+    fn test_validation_hash_record_a() {
+        let dir = tempdir().unwrap();
+        let dir_site = dir.path().join("lib/python3.12/site-packages");
+        let dir_dist_info = dir_site.join("foo-1.0.dist-info");
+        fs::create_dir_all(&dir_dist_info).unwrap();
+        fs::create_dir_all(dir_site.join("foo")).unwrap();
+        let fp_init = dir_site.join("foo/__init__.py");
+        fs::write(&fp_init, "x = 1\n").unwrap();
+        fs::write(
+            dir_dist_info.join("RECORD"),
+            format!(
+                "foo-1.0.dist-info/RECORD,,\nfoo/__init__.py,{},6\n",
+                crate::util::hash_file_record(&fp_init).unwrap()
+            ),
+        )
+        .unwrap();
+        let packages = vec![Package::from_name_version_durl("foo", "1.0", None).unwrap()];
+        let sfs = ScanFS::from_exe_site_packages(
+            PathBuf::from("/usr/bin/python3"),
+            dir_site,
+            packages,
+        )
+        .unwrap();
+        let dm = DepManifest::from_iter(vec!["foo==1.0 --hash=sha256:8abb2f1d"].iter())
+            .unwrap();
+
+        // installed from an index, the installed files match RECORD
+        let vr1 = sfs.to_validation_report(dm.clone(), ValidationFlags::default());
+        assert_eq!(vr1.len(), 0);
+
+        let vf = ValidationFlags {
+            require_hashes: true,
+            ..ValidationFlags::default()
+        };
+        let vr2 = sfs.to_validation_report(dm.clone(), vf);
+        assert_eq!(vr2.len(), 1);
+        assert_eq!(vr2.to_validation_digest()[0].explain, "HashUnverifiable");
+
+        // a hot-patched file does not match RECORD
+        fs::write(&fp_init, "x = 2\n").unwrap();
+        let vr3 = sfs.to_validation_report(dm, ValidationFlags::default());
+        assert_eq!(vr3.len(), 1);
+        assert_eq!(vr3.to_validation_digest()[0].explain, "HashMismatch");
+    }
+
+    #[test]
+    fn test_validation_prerelease_a() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "2.0rc1", None).unwrap(),
+            Package::from_name_version_durl("scipy", "1.14.0rc2", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let dm = DepManifest::from_iter(vec!["numpy>=1.0", "scipy>=1.14.0rc1"].iter())
+            .unwrap();
+
+        let vr1 = sfs.to_validation_report(dm.clone(), ValidationFlags::default());
+        assert_eq!(vr1.len(), 0);
+
+        let vf = ValidationFlags {
+            reject_prerelease: true,
+            exact: false,
+            ..ValidationFlags::default()
+        };
+        let vr2 = sfs.to_validation_report(dm, vf);
+        let json = serde_json::to_string(&vr2.to_validation_digest()).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"package":"numpy-2.0rc1","dependency":"numpy>=1.0","explain":"Misdefined","code":"V003","category":"requirement","sites":["/usr/lib/python3/site-packages"],"reason":"installed version 2.0rc1 is a pre-release"}]"#
+        );
+    }
+
+    #[test]
+    fn test_validation_local_a() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let durl = DirectURL::from_editable_path(Path::new("/opt/build/mypkg"));
+        let packages =
+            vec![Package::from_name_version_durl("mypkg", "1.0", Some(durl)).unwrap()];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+
+        let dm = DepManifest::from_iter(vec!["mypkg @ file:///opt/build/mypkg/"].iter())
+            .unwrap();
+        let vr1 = sfs.to_validation_report(dm, ValidationFlags::default());
+        assert_eq!(vr1.len(), 0);
+
+        let dm =
+            DepManifest::from_iter(vec!["mypkg @ file:///opt/src/mypkg"].iter()).unwrap();
+        let vr2 = sfs.to_validation_report(dm, ValidationFlags::default());
+        let json = serde_json::to_string(&vr2.to_validation_digest()).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"package":"mypkg-1.0","dependency":"mypkg @ file:///opt/src/mypkg","explain":"Misdefined","code":"V003","category":"requirement","sites":["/usr/lib/python3/site-packages"],"reason":"installed editable from /opt/build/mypkg"}]"#
+        );
+    }
+
     //--------------------------------------------------------------------------
     #[test]
     fn test_search_a() {
@@ -905,4 +3132,706 @@ mod tests {
             "973122597250deea4e62e359208ab4335782561c12032746ce044a387a201d09"
         );
     }
+    //--------------------------------------------------------------------------
+    #[test]
+    fn test_validation_marker_a() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = "/usr/lib/python3/site-packages";
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "1.19.3", None).unwrap(),
+            Package::from_name_version_durl("tomli", "1.0.0", None).unwrap(),
+        ];
+        let dm = DepManifest::from_iter(
+            vec![
+                "numpy>1.19",
+                "tomli>=2; python_version < '3.11'",
+                "pywin32; sys_platform == 'win32'",
+            ]
+            .iter(),
+        )
+        .unwrap();
+        let sfs =
+            ScanFS::from_exe_site_packages(exe, PathBuf::from(site), packages).unwrap();
+        let vf = ValidationFlags {
+            permit_superset: false,
+            permit_subset: false,
+            resolve_revisions: false,
+            reject_editable: false,
+            report_orphans: false,
+            ignore_local: false,
+            reject_prerelease: false,
+            require_hashes: false,
+            exact: false,
+        };
+
+        // without environments, all markers apply
+        let vr1 = sfs.to_validation_report_with_envs(
+            dm.clone(),
+            vf,
+            &HashMap::new(),
+            &GitClientLive,
+        );
+        assert_eq!(vr1.len(), 2);
+
+        let env1 = MarkerEnv::from_pairs(&[
+            ("python_version", "3.12"),
+            ("sys_platform", "linux"),
+        ]);
+        let site_to_envs = HashMap::from([(PathShared::from_str(site), vec![env1])]);
+        let vr2 = sfs.to_validation_report_with_envs(
+            dm.clone(),
+            vf,
+            &site_to_envs,
+            &GitClientLive,
+        );
+        assert_eq!(vr2.len(), 0);
+
+        let env2 = MarkerEnv::from_pairs(&[
+            ("python_version", "3.10"),
+            ("sys_platform", "linux"),
+        ]);
+        let site_to_envs = HashMap::from([(PathShared::from_str(site), vec![env2])]);
+        let vr3 =
+            sfs.to_validation_report_with_envs(dm, vf, &site_to_envs, &GitClientLive);
+        assert_eq!(vr3.len(), 1);
+        let json = serde_json::to_string(&vr3.to_validation_digest()).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"package":"tomli-1.0.0","dependency":"tomli>=2; python_version < \"3.11\"","explain":"Misdefined","code":"V003","category":"requirement","sites":["/usr/lib/python3/site-packages"]}]"#
+        );
+    }
+
+    #[test]
+    fn test_validation_marker_b() {
+        let exe1 = PathBuf::from("/usr/bin/python3.10");
+        let exe2 = PathBuf::from("/usr/bin/python3.12");
+        let site1 = PathShared::from_str("/usr/lib/python3.10/site-packages");
+        let site2 = PathShared::from_str("/usr/lib/python3.12/site-packages");
+        let tomli = Package::from_name_version_durl("tomli", "1.0.0", None).unwrap();
+        let mut sfs = ScanFS::from_exe_site_packages(
+            exe2,
+            site2.as_path().to_path_buf(),
+            vec![tomli],
+        )
+        .unwrap();
+        sfs.exe_to_sites.insert(exe1, vec![site1.clone()]);
+        let dm = DepManifest::from_iter(vec!["tomli>=2; python_version < '3.11'"].iter())
+            .unwrap();
+        let vf = ValidationFlags::default();
+        let site_to_envs = HashMap::from([
+            (
+                site1,
+                vec![MarkerEnv::from_pairs(&[("python_version", "3.10")])],
+            ),
+            (
+                site2,
+                vec![MarkerEnv::from_pairs(&[("python_version", "3.12")])],
+            ),
+        ]);
+        // the marker applies to python3.10, but tomli is only in the site of python3.12
+        let vr =
+            sfs.to_validation_report_with_envs(dm, vf, &site_to_envs, &GitClientLive);
+        assert_eq!(vr.len(), 0);
+    }
+
+    #[test]
+    fn test_validation_exact_a() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "1.19.3", None).unwrap(),
+            Package::from_name_version_durl("six", "1.16.0", None).unwrap(),
+            Package::from_name_version_durl("tomli", "2.0.1", None).unwrap(),
+        ];
+        let dm = DepManifest::from_iter(
+            vec!["numpy>1.19", "tomli>=2; python_version < '3.11'", "zipp"].iter(),
+        )
+        .unwrap();
+        let sfs = ScanFS::from_exe_site_packages(exe, site.clone(), packages).unwrap();
+        let env = MarkerEnv::from_pairs(&[("python_version", "3.12")]);
+        let site_to_envs = HashMap::from([(PathShared::from_path_buf(site), vec![env])]);
+
+        let vf = ValidationFlags {
+            permit_superset: true,
+            permit_subset: true,
+            ..ValidationFlags::default()
+        };
+        let vr1 = sfs.to_validation_report_with_envs(
+            dm.clone(),
+            vf,
+            &site_to_envs,
+            &GitClientLive,
+        );
+        assert_eq!(vr1.len(), 0);
+
+        // exact overrides superset and subset, and packages installed for requirements that do not apply are unrequired
+        let vf = ValidationFlags { exact: true, ..vf };
+        let vr2 =
+            sfs.to_validation_report_with_envs(dm, vf, &site_to_envs, &GitClientLive);
+        let json = serde_json::to_string(&vr2.to_validation_digest()).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"package":"six-1.16.0","dependency":null,"explain":"Unrequired","code":"V002","category":"inventory","sites":["/usr/lib/python3/site-packages"]},{"package":"tomli-2.0.1","dependency":"tomli>=2; python_version < \"3.11\"","explain":"Unrequired","code":"V002","category":"inventory","sites":["/usr/lib/python3/site-packages"],"reason":"the marker of the requirement does not apply"},{"package":null,"dependency":"zipp","explain":"Missing","code":"V001","category":"requirement","sites":null}]"#
+        );
+    }
+
+    #[test]
+    fn test_validation_extras_a() {
+        let dir = tempdir().unwrap();
+        let fp_di = dir.path().join("requests-2.32.3.dist-info");
+        fs::create_dir(&fp_di).unwrap();
+        fs::write(
+            fp_di.join("METADATA"),
+            "Name: requests\nVersion: 2.32.3\nRequires-Dist: idna<4,>=2.5\nRequires-Dist: PySocks!=1.5.7,>=1.5.6; extra == \"socks\"\nRequires-Dist: chardet<6,>=3.0.2; extra == \"use-chardet-on-py3\"\n",
+        )
+        .unwrap();
+
+        let exe = PathBuf::from("/usr/bin/python3");
+        let packages = vec![
+            Package::from_name_version_durl("requests", "2.32.3", None).unwrap(),
+            Package::from_name_version_durl("PySocks", "1.5.7", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, dir.path().to_path_buf(), packages)
+            .unwrap();
+        let vf = ValidationFlags {
+            permit_superset: true,
+            permit_subset: false,
+            resolve_revisions: false,
+            reject_editable: false,
+            report_orphans: false,
+            ignore_local: false,
+            reject_prerelease: false,
+            require_hashes: false,
+            exact: false,
+        };
+        let site_to_envs = HashMap::from([(
+            PathShared::from_path_buf(dir.path().to_path_buf()),
+            vec![MarkerEnv::from_pairs(&[("python_version", "3.12")])],
+        )]);
+
+        // without extras, only requests is validated
+        let dm1 = DepManifest::from_iter(vec!["requests>=2.31"].iter()).unwrap();
+        let vr1 =
+            sfs.to_validation_report_with_envs(dm1, vf, &site_to_envs, &GitClientLive);
+        assert_eq!(vr1.len(), 0);
+
+        // the socks extra requires a PySocks version that is not installed
+        let dm2 = DepManifest::from_iter(vec!["requests[socks]>=2.31"].iter()).unwrap();
+        let vr2 =
+            sfs.to_validation_report_with_envs(dm2, vf, &site_to_envs, &GitClientLive);
+        let json = serde_json::to_string(&vr2.to_validation_digest()).unwrap();
+        assert_eq!(
+            json,
+            format!(
+                r#"[{{"package":"PySocks-1.5.7","dependency":"PySocks!=1.5.7,>=1.5.6","explain":"Misdefined","code":"V003","category":"requirement","sites":["{}"]}}]"#,
+                dir.path().display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_validation_resolve_revisions_a() {
+        let durl: DirectURL = serde_json::from_str(
+            r#"{"url": "https://github.com/uqfoundation/dill", "vcs_info": {"commit_id": "a0a8e86976708d0436eec5c8f7d25329da727cb5", "requested_revision": "main", "vcs": "git"}}"#,
+        )
+        .unwrap();
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages =
+            vec![Package::from_name_version_durl("dill", "0.3.8", Some(durl)).unwrap()];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let dm = DepManifest::from_iter(
+            vec!["dill @ git+https://github.com/uqfoundation/dill.git@main"].iter(),
+        )
+        .unwrap();
+        let client = GitClientMock {
+            mock_ls_remote: Some(
+                "15d7c6d6ccf4781c624ffbf54c90d23c6e94dc52\trefs/heads/main\n".to_string(),
+            ),
+        };
+        let mut vf = ValidationFlags {
+            permit_superset: false,
+            permit_subset: false,
+            resolve_revisions: false,
+            reject_editable: false,
+            report_orphans: false,
+            ignore_local: false,
+            reject_prerelease: false,
+            require_hashes: false,
+            exact: false,
+        };
+        let vr1 =
+            sfs.to_validation_report_with_envs(dm.clone(), vf, &HashMap::new(), &client);
+        assert_eq!(vr1.len(), 0);
+
+        vf.resolve_revisions = true;
+        let vr2 = sfs.to_validation_report_with_envs(dm, vf, &HashMap::new(), &client);
+        let json = serde_json::to_string(&vr2.to_validation_digest()).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"package":"dill-0.3.8","dependency":"dill @ git+https://github.com/uqfoundation/dill.git@main","explain":"Drifted","code":"V005","category":"integrity","sites":["/usr/lib/python3/site-packages"]}]"#
+        );
+    }
+
+    #[test]
+    fn test_validation_editable_a() {
+        let durl = DirectURL::from_editable_path(Path::new("/home/user/src/dill"));
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("dill", "0.3.8", Some(durl)).unwrap(),
+            Package::from_name_version_durl("numpy", "2.1.2", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let dm = DepManifest::from_iter(vec!["dill>=0.3", "numpy>2"].iter()).unwrap();
+        let mut vf = ValidationFlags::default();
+        let vr1 = sfs.to_validation_report(dm.clone(), vf);
+        assert_eq!(vr1.len(), 0);
+
+        vf.reject_editable = true;
+        let vr2 = sfs.to_validation_report(dm, vf);
+        let json = serde_json::to_string(&vr2.to_validation_digest()).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"package":"dill-0.3.8","dependency":"dill>=0.3","explain":"Editable","code":"V006","category":"provenance","sites":["/usr/lib/python3/site-packages"]}]"#
+        );
+    }
+
+    #[test]
+    fn test_validation_orphans_a() {
+        let dir = tempdir().unwrap();
+        let metadata = [
+            (
+                "requests-2.32.3.dist-info",
+                "Name: requests\nVersion: 2.32.3\nRequires-Dist: idna<4,>=2.5\n",
+            ),
+            ("idna-3.10.dist-info", "Name: idna\nVersion: 3.10\n"),
+            ("six-1.16.0.dist-info", "Name: six\nVersion: 1.16.0\n"),
+        ];
+        for (di, content) in metadata {
+            let fp_di = dir.path().join(di);
+            fs::create_dir(&fp_di).unwrap();
+            fs::write(fp_di.join("METADATA"), content).unwrap();
+        }
+        let exe = PathBuf::from("/usr/bin/python3");
+        let packages = vec![
+            Package::from_name_version_durl("requests", "2.32.3", None).unwrap(),
+            Package::from_name_version_durl("idna", "3.10", None).unwrap(),
+            Package::from_name_version_durl("six", "1.16.0", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, dir.path().to_path_buf(), packages)
+            .unwrap();
+        let dm = DepManifest::from_iter(vec!["requests>=2.31"].iter()).unwrap();
+        let mut vf = ValidationFlags::default();
+
+        // without orphans, transitive requirements are unrequired
+        let vr1 = sfs.to_validation_report_with_envs(
+            dm.clone(),
+            vf,
+            &HashMap::new(),
+            &GitClientLive,
+        );
+        let explains1: Vec<String> = vr1
+            .to_validation_digest()
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+        assert_eq!(explains1.len(), 2);
+        assert!(explains1[0].starts_with("idna-3.10  Unrequired"));
+        assert!(explains1[1].starts_with("six-1.16.0  Unrequired"));
+
+        vf.report_orphans = true;
+        let vr2 = sfs.to_validation_report_with_envs(
+            dm.clone(),
+            vf,
+            &HashMap::new(),
+            &GitClientLive,
+        );
+        let explains2: Vec<String> = vr2
+            .to_validation_digest()
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+        assert_eq!(explains2.len(), 2);
+        assert!(explains2[0].starts_with("idna-3.10  Unrequired"));
+        assert!(explains2[1].starts_with("six-1.16.0  Orphan"));
+
+        // with superset, only orphans are reported
+        vf.permit_superset = true;
+        let vr3 =
+            sfs.to_validation_report_with_envs(dm, vf, &HashMap::new(), &GitClientLive);
+        let explains3: Vec<String> = vr3
+            .to_validation_digest()
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+        assert_eq!(explains3.len(), 1);
+        assert!(explains3[0].starts_with("six-1.16.0  Orphan"));
+    }
+
+    #[test]
+    fn test_search_by_name_a() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("requests", "2.32.3", None).unwrap(),
+            Package::from_name_version_durl("requests-toolbelt", "1.0.0", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        assert_eq!(sfs.search_by_name("requests", true).len(), 1);
+        assert_eq!(sfs.search_by_name("Requests", true).len(), 1);
+        assert_eq!(sfs.search_by_name("requests-2.32.3", true).len(), 1);
+        assert_eq!(sfs.search_by_name("requests*", true).len(), 2);
+        assert_eq!(sfs.search_by_match("requests", true).len(), 0);
+    }
+
+    #[test]
+    fn test_retain_packages_a() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "2.1.2", None).unwrap(),
+            Package::from_name_version_durl("internal_tools", "1.0", None).unwrap(),
+            Package::from_name_version_durl("requests", "2.32.3", None).unwrap(),
+        ];
+        let mut sfs =
+            ScanFS::from_exe_site_packages(exe, site.clone(), packages).unwrap();
+        let filter = PackageFilter::new(&[], &["internal-*".to_string()]);
+        sfs.retain_packages(&filter, Some(&site)).unwrap();
+        let names: Vec<String> =
+            sfs.get_packages().iter().map(|p| p.to_string()).collect();
+        assert_eq!(names, vec!["numpy-2.1.2", "requests-2.32.3"]);
+
+        let filter = PackageFilter::new(&["num*".to_string()], &[]);
+        sfs.retain_packages(&filter, None).unwrap();
+        assert_eq!(sfs.get_packages().len(), 1);
+
+        assert!(sfs
+            .retain_packages(&filter, Some(Path::new("/opt/site-packages")))
+            .is_err());
+    }
+
+    #[test]
+    fn test_to_license_records_a() {
+        let dir = tempdir().unwrap();
+        let metadata = [
+            ("flask-3.0.3.dist-info", "Name: Flask\nVersion: 3.0.3\nLicense: BSD-3-Clause\n"),
+            ("gpl_pkg-1.0.dist-info", "Name: gpl-pkg\nVersion: 1.0\nClassifier: License :: OSI Approved :: GNU General Public License v3 (GPLv3)\n"),
+            ("unknown-2.0.dist-info", "Name: unknown\nVersion: 2.0\nLicense: Proprietary\n"),
+        ];
+        for (di, content) in metadata {
+            let fp_di = dir.path().join(di);
+            fs::create_dir(&fp_di).unwrap();
+            fs::write(fp_di.join("METADATA"), content).unwrap();
+        }
+        let exe = PathBuf::from("/usr/bin/python3");
+        let packages = vec![
+            Package::from_name_version_durl("Flask", "3.0.3", None).unwrap(),
+            Package::from_name_version_durl("gpl-pkg", "1.0", None).unwrap(),
+            Package::from_name_version_durl("unknown", "2.0", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, dir.path().to_path_buf(), packages)
+            .unwrap();
+
+        let lp1 = LicensePolicy::from_str("deny = [\"GPL-*\"]\n").unwrap();
+        let vr1 = ValidationReport {
+            records: sfs.to_license_records(&lp1),
+        };
+        let digest1 = vr1.to_validation_digest();
+        assert_eq!(digest1.len(), 1);
+        assert_eq!(
+            digest1[0].to_string(),
+            format!("gpl-pkg-1.0  LicenseDenied {}", dir.path().display())
+        );
+
+        let lp2 =
+            LicensePolicy::from_str("deny = [\"GPL-*\"]\ndeny-unknown = true\n").unwrap();
+        assert_eq!(sfs.to_license_records(&lp2).len(), 2);
+    }
+
+    #[test]
+    fn test_to_requires_python_records_a() {
+        let dir = tempdir().unwrap();
+        let site = dir
+            .path()
+            .join("lib")
+            .join("python3.9")
+            .join("site-packages");
+        fs::create_dir_all(&site).unwrap();
+        let metadata = [
+            (
+                "numpy-2.1.2.dist-info",
+                "Name: numpy\nVersion: 2.1.2\nRequires-Python: >=3.10\n",
+            ),
+            (
+                "six-1.16.0.dist-info",
+                "Name: six\nVersion: 1.16.0\nRequires-Python: >=2.7, !=3.0.*\n",
+            ),
+            ("pytz-2024.2.dist-info", "Name: pytz\nVersion: 2024.2\n"),
+        ];
+        for (di, content) in metadata {
+            let fp_di = site.join(di);
+            fs::create_dir(&fp_di).unwrap();
+            fs::write(fp_di.join("METADATA"), content).unwrap();
+        }
+        let exe = dir.path().join("bin").join("python3");
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "2.1.2", None).unwrap(),
+            Package::from_name_version_durl("six", "1.16.0", None).unwrap(),
+            Package::from_name_version_durl("pytz", "2024.2", None).unwrap(),
+        ];
+        // as the executable does not exist, the Python version is inferred from the site
+        let sfs = ScanFS::from_exe_site_packages(exe, site.clone(), packages).unwrap();
+        let vr = ValidationReport {
+            records: sfs.to_requires_python_records(),
+        };
+        let digest = vr.to_validation_digest();
+        assert_eq!(digest.len(), 1);
+        assert_eq!(digest[0].package.as_deref(), Some("numpy-2.1.2"));
+        assert_eq!(digest[0].explain, "PythonIncompatible");
+        assert_eq!(
+            digest[0].reason.as_deref(),
+            Some("Requires-Python >=3.10; Python 3.9")
+        );
+        assert_eq!(
+            digest[0].sites,
+            Some(vec![site.to_string_lossy().to_string()])
+        );
+    }
+
+    #[test]
+    fn test_to_wheel_tag_records_a() {
+        let dir = tempdir().unwrap();
+        let site = dir
+            .path()
+            .join("lib")
+            .join("python3.12")
+            .join("site-packages");
+        fs::create_dir_all(&site).unwrap();
+        let wheels = [
+            ("numpy-2.1.2.dist-info", "Tag: cp310-cp310-manylinux_2_17_x86_64\nTag: cp310-cp310-manylinux2014_x86_64\n"),
+            ("pyyaml-6.0.2.dist-info", "Tag: cp312-cp312-macosx_11_0_arm64\n"),
+            ("six-1.16.0.dist-info", "Tag: py2-none-any\nTag: py3-none-any\n"),
+            ("orjson-3.10.7.dist-info", "Tag: cp312-cp312-manylinux_2_17_x86_64\n"),
+        ];
+        for (di, content) in wheels {
+            let fp_di = site.join(di);
+            fs::create_dir(&fp_di).unwrap();
+            fs::write(fp_di.join("WHEEL"), content).unwrap();
+        }
+        let exe = dir.path().join("bin").join("python3");
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "2.1.2", None).unwrap(),
+            Package::from_name_version_durl("pyyaml", "6.0.2", None).unwrap(),
+            Package::from_name_version_durl("six", "1.16.0", None).unwrap(),
+            Package::from_name_version_durl("orjson", "3.10.7", None).unwrap(),
+            Package::from_name_version_durl("pytz", "2024.2", None).unwrap(),
+        ];
+        // as the executable does not exist, a CPython on Linux is inferred from the site
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let vr = ValidationReport {
+            records: sfs.to_wheel_tag_records(),
+        };
+        let digest = vr.to_validation_digest();
+        assert_eq!(digest.len(), 2);
+        assert_eq!(digest[0].package.as_deref(), Some("numpy-2.1.2"));
+        assert_eq!(digest[0].explain, "IncompatibleBinary");
+        assert_eq!(
+            digest[0].reason.as_deref(),
+            Some("cp310-cp310-manylinux_2_17_x86_64,cp310-cp310-manylinux2014_x86_64; 3.12 linux")
+        );
+        assert_eq!(digest[1].package.as_deref(), Some("pyyaml-6.0.2"));
+    }
+
+    #[test]
+    fn test_order_sites_a() {
+        let sites = vec![
+            PathShared::from_str("/usr/lib/python3/dist-packages"),
+            PathShared::from_str("/usr/local/lib/python3.12/dist-packages"),
+            PathShared::from_str("/home/user/.local/lib/python3.12/site-packages"),
+        ];
+        let sys_path = vec![
+            PathBuf::from("/usr/lib/python312.zip"),
+            PathBuf::from("/home/user/.local/lib/python3.12/site-packages"),
+            PathBuf::from("/usr/local/lib/python3.12/dist-packages"),
+        ];
+        let ordered: Vec<String> = order_sites(&sites, &sys_path)
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            ordered,
+            vec![
+                "/home/user/.local/lib/python3.12/site-packages",
+                "/usr/local/lib/python3.12/dist-packages",
+                "/usr/lib/python3/dist-packages",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_shadow_records_a() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site1 = PathBuf::from("/home/user/.local/lib/python3.12/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("requests", "2.32.3", None).unwrap(),
+            Package::from_name_version_durl("six", "1.16.0", None).unwrap(),
+        ];
+        let mut sfs =
+            ScanFS::from_exe_site_packages(exe.clone(), site1, packages).unwrap();
+        sfs.offline = true;
+
+        let site2 = PathShared::from_str("/usr/lib/python3/dist-packages");
+        sfs.exe_to_sites.get_mut(&exe).unwrap().push(site2.clone());
+        for (name, version) in
+            [("requests", "2.31.0"), ("six", "1.16.0"), ("idna", "3.10")]
+        {
+            let package = Package::from_name_version_durl(name, version, None).unwrap();
+            sfs.package_to_sites
+                .entry(package)
+                .or_default()
+                .push(site2.clone());
+        }
+        let vr = ValidationReport {
+            records: sfs.to_shadow_records(),
+        };
+        let digest = vr.to_validation_digest();
+        assert_eq!(digest.len(), 2);
+        assert_eq!(digest[0].package.as_deref(), Some("requests-2.31.0"));
+        assert_eq!(digest[0].explain, "Shadowed");
+        assert_eq!(
+            digest[0].reason.as_deref(),
+            Some("shadowed by requests-2.32.3 in /home/user/.local/lib/python3.12/site-packages")
+        );
+        assert_eq!(
+            digest[0].sites,
+            Some(vec!["/usr/lib/python3/dist-packages".to_string()])
+        );
+        assert_eq!(digest[1].package.as_deref(), Some("six-1.16.0"));
+    }
+
+    #[test]
+    fn test_to_ban_records_a() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("pycrypto", "2.6.1", None).unwrap(),
+            Package::from_name_version_durl("acme-legacy-auth", "0.3", None).unwrap(),
+            Package::from_name_version_durl("requests", "2.32.3", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let bp = BanPolicy::from_str(
+            "[[ban]]\npackage = \"pycrypto\"\nreason = \"Unmaintained\"\n\n[[ban]]\npackage = \"acme-legacy-*\"\n",
+        )
+        .unwrap();
+        let vr = ValidationReport {
+            records: sfs.to_ban_records(&bp),
+        };
+        let digest = vr.to_validation_digest();
+        assert_eq!(digest.len(), 2);
+        assert_eq!(digest[0].package.as_deref(), Some("acme-legacy-auth-0.3"));
+        assert_eq!(digest[0].reason.as_deref(), Some("banned by policy"));
+        assert_eq!(digest[1].explain, "Banned");
+        assert_eq!(digest[1].code, "V010");
+        assert_eq!(digest[1].reason.as_deref(), Some("Unmaintained"));
+    }
+
+    #[test]
+    fn test_to_installer_records_a() {
+        let dir = tempdir().unwrap();
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = dir.path().join("site-packages");
+        for (di, installer) in [
+            ("numpy-2.1.2.dist-info", "pip\n"),
+            ("six-1.16.0.dist-info", "Poetry 1.8.3\n"),
+            ("pyyaml-6.0.2.dist-info", "conda\n"),
+        ] {
+            fs::create_dir_all(site.join(di)).unwrap();
+            fs::write(site.join(di).join("INSTALLER"), installer).unwrap();
+        }
+        fs::create_dir_all(site.join("idna-3.10.dist-info")).unwrap();
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "2.1.2", None).unwrap(),
+            Package::from_name_version_durl("six", "1.16.0", None).unwrap(),
+            Package::from_name_version_durl("PyYAML", "6.0.2", None).unwrap(),
+            Package::from_name_version_durl("idna", "3.10", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let vr = ValidationReport {
+            records: sfs.to_installer_records(&["pip".to_string(), "POETRY".to_string()]),
+        };
+        let digest = vr.to_validation_digest();
+        assert_eq!(digest.len(), 2);
+        assert_eq!(digest[0].package.as_deref(), Some("idna-3.10"));
+        assert_eq!(digest[0].code, "V014");
+        assert_eq!(
+            digest[0].reason.as_deref(),
+            Some("no INSTALLER; requires pip, poetry")
+        );
+        assert_eq!(digest[1].package.as_deref(), Some("PyYAML-6.0.2"));
+        assert_eq!(digest[1].explain, "InstallerDenied");
+        assert_eq!(
+            digest[1].reason.as_deref(),
+            Some("installed by conda; requires pip, poetry")
+        );
+    }
+
+    #[test]
+    fn test_snapshot_a() {
+        let dir = tempdir().unwrap();
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let durl = DirectURL::from_url_vcs_cid(
+            "https://github.com/pypa/packaging.git".to_string(),
+            Some("git".to_string()),
+            Some("0bbc7e4b4c0ee8b6b0b1f5d7c7e2b0b0c8a4c1a2".to_string()),
+        )
+        .unwrap();
+        let packages = vec![
+            Package::from_name_version_durl("tomli", "1.0.0", None).unwrap(),
+            Package::from_name_version_durl("packaging", "24.2", Some(durl)).unwrap(),
+        ];
+        let mut sfs1 =
+            ScanFS::from_exe_site_packages(exe.clone(), site, packages).unwrap();
+        sfs1.marker_envs = Some(vec![(
+            exe,
+            MarkerEnv::from_pairs(&[("python_version", "3.10")]),
+        )]);
+        sfs1.interpreters = Some(vec![Interpreter::from_static(
+            Path::new("/usr/bin/python3"),
+            &[PathShared::from_str("/usr/lib/python3.10/site-packages")],
+        )]);
+        let fp = dir.path().join("env.json");
+        sfs1.to_snapshot_file(&fp).unwrap();
+
+        let sfs2 = ScanFS::from_snapshot_file(&fp).unwrap();
+        assert!(sfs2.offline);
+        assert_eq!(sfs2.exe_to_sites, sfs1.exe_to_sites);
+        assert_eq!(sfs2.get_packages(), sfs1.get_packages());
+        assert_eq!(sfs2.get_marker_envs(), sfs1.get_marker_envs());
+        assert_eq!(sfs2.get_interpreters(), sfs1.get_interpreters());
+        assert_eq!(sfs2.get_interpreters()[0].version, Some("3.10".to_string()));
+        assert!(sfs2.get_packages()[0].direct_url.is_some());
+
+        // recorded marker values are used for validation
+        let dm = DepManifest::from_iter(
+            vec!["tomli>=2; python_version < \"3.11\"", "packaging"].iter(),
+        )
+        .unwrap();
+        let vf = ValidationFlags {
+            permit_superset: false,
+            permit_subset: false,
+            resolve_revisions: false,
+            reject_editable: false,
+            report_orphans: false,
+            ignore_local: false,
+            reject_prerelease: false,
+            require_hashes: false,
+            exact: false,
+        };
+        assert_eq!(sfs2.to_validation_report(dm, vf).len(), 1);
+
+        fs::write(&fp, "{").unwrap();
+        assert!(ScanFS::from_snapshot_file(&fp).is_err());
+    }
 }
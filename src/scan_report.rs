@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 use crate::package::Package;
 use crate::path_shared::PathShared;
@@ -11,11 +12,50 @@ use crate::table::Tableable;
 pub(crate) struct ScanRecord {
     package: Package,
     sites: Vec<PathShared>,
+    installers: Vec<Option<String>>,
+    exes: Vec<String>,
+    user_sites: Vec<bool>,
+    show_source: bool,
+    show_installer: bool,
+    show_exe: bool,
+    show_user_site: bool,
 }
 
 impl ScanRecord {
-    pub(crate) fn new(package: Package, sites: Vec<PathShared>) -> Self {
-        ScanRecord { package, sites }
+    pub(crate) fn new(
+        package: Package,
+        sites: Vec<PathShared>,
+        site_to_exes: Option<&HashMap<PathShared, Vec<PathBuf>>>,
+    ) -> Self {
+        let mut sites = sites;
+        sites.sort_by(|a, b| a.as_path().cmp(b.as_path()));
+        let installers = sites.iter().map(|s| package.to_installer(s)).collect();
+        let exes = sites
+            .iter()
+            .map(|s| {
+                site_to_exes
+                    .and_then(|ste| ste.get(s))
+                    .map(|exes| {
+                        exes.iter()
+                            .map(|e| e.display().to_string())
+                            .collect::<Vec<_>>()
+                            .join(",")
+                    })
+                    .unwrap_or_default()
+            })
+            .collect();
+        let user_sites = sites.iter().map(|s| s.is_user_site()).collect();
+        ScanRecord {
+            package,
+            sites,
+            installers,
+            exes,
+            user_sites,
+            show_source: false,
+            show_installer: false,
+            show_exe: site_to_exes.is_some(),
+            show_user_site: false,
+        }
     }
 }
 
@@ -31,7 +71,25 @@ impl Rowable for ScanRecord {
             } else {
                 pkg_display.clone()
             };
-            rows.push(vec![p, path.to_string()]);
+            let mut row = vec![p, path.to_string()];
+            if self.show_source {
+                let source = match self.package.to_editable_src() {
+                    Some(src) if i == 0 || !is_tty => src.display().to_string(),
+                    _ => "".to_string(),
+                };
+                row.push(source);
+            }
+            if self.show_installer {
+                row.push(self.installers[i].clone().unwrap_or_default());
+            }
+            if self.show_user_site {
+                let user_site = if self.user_sites[i] { "yes" } else { "" };
+                row.push(user_site.to_string());
+            }
+            if self.show_exe {
+                row.push(self.exes[i].clone());
+            }
+            rows.push(row);
         }
         rows
     }
@@ -40,43 +98,107 @@ impl Rowable for ScanRecord {
 #[derive(Debug)]
 pub struct ScanReport {
     records: Vec<ScanRecord>,
+    show_source: bool,
+    show_installer: bool,
+    show_user_site: bool,
+    show_exe: bool,
 }
 
 impl ScanReport {
+    // Only show the source column if any package is installed in editable mode, only show the installer column if any installer is found, and only show the user site column if any package is installed in a user site. The executables column is shown if records are created with a mapping of sites to executables.
+    fn from_records(mut records: Vec<ScanRecord>, show_exe: bool) -> Self {
+        // packages are ordered case-insensitively by name, then by version; sites break ties
+        records.sort_by(|a, b| {
+            a.package.cmp(&b.package).then_with(|| {
+                let sites = |r: &ScanRecord| -> Vec<PathBuf> {
+                    r.sites.iter().map(|s| s.as_path().to_path_buf()).collect()
+                };
+                sites(a).cmp(&sites(b))
+            })
+        });
+        let show_source = records.iter().any(|r| r.package.is_editable());
+        let show_installer = records
+            .iter()
+            .any(|r| r.installers.iter().any(|i| i.is_some()));
+        let show_user_site = records.iter().any(|r| r.user_sites.contains(&true));
+        for record in records.iter_mut() {
+            record.show_source = show_source;
+            record.show_installer = show_installer;
+            record.show_user_site = show_user_site;
+        }
+        ScanReport {
+            records,
+            show_source,
+            show_installer,
+            show_user_site,
+            show_exe,
+        }
+    }
+
+    /// Create a report of all packages. If `site_to_exes` is provided, as when more than one executable is scanned, the executables that use each site are shown.
     pub(crate) fn from_package_to_sites(
         package_to_sites: &HashMap<Package, Vec<PathShared>>,
+        site_to_exes: Option<&HashMap<PathShared, Vec<PathBuf>>>,
     ) -> Self {
         let mut records = Vec::new();
         for (package, sites) in package_to_sites {
-            let record = ScanRecord::new(package.clone(), sites.clone());
+            let record = ScanRecord::new(package.clone(), sites.clone(), site_to_exes);
             records.push(record);
         }
-        records.sort_by_key(|item| item.package.clone());
-        ScanReport { records }
+        Self::from_records(records, site_to_exes.is_some())
     }
 
     // Alternative constructor when we want to report on a subset of all packages.
     pub(crate) fn from_packages(
         packages: &Vec<Package>,
         package_to_sites: &HashMap<Package, Vec<PathShared>>,
+        site_to_exes: Option<&HashMap<PathShared, Vec<PathBuf>>>,
     ) -> Self {
         let mut records = Vec::new();
         for package in packages {
             let sites = package_to_sites.get(package).unwrap();
-            let record = ScanRecord::new(package.clone(), sites.clone());
+            let record = ScanRecord::new(package.clone(), sites.clone(), site_to_exes);
             records.push(record);
         }
-        records.sort_by_key(|item| item.package.clone());
-        ScanReport { records }
+        Self::from_records(records, site_to_exes.is_some())
     }
 }
 
 impl Tableable<ScanRecord> for ScanReport {
     fn get_header(&self) -> Vec<ColumnFormat> {
-        vec![
+        let mut header = vec![
             ColumnFormat::new("Package".to_string(), false, "#666666".to_string()),
             ColumnFormat::new("Site".to_string(), true, "#666666".to_string()),
-        ]
+        ];
+        if self.show_source {
+            header.push(ColumnFormat::new(
+                "Source".to_string(),
+                true,
+                "#666666".to_string(),
+            ));
+        }
+        if self.show_installer {
+            header.push(ColumnFormat::new(
+                "Installer".to_string(),
+                false,
+                "#666666".to_string(),
+            ));
+        }
+        if self.show_user_site {
+            header.push(ColumnFormat::new(
+                "User Site".to_string(),
+                false,
+                "#666666".to_string(),
+            ));
+        }
+        if self.show_exe {
+            header.push(ColumnFormat::new(
+                "Executables".to_string(),
+                true,
+                "#666666".to_string(),
+            ));
+        }
+        header
     }
     fn get_records(&self) -> &Vec<ScanRecord> {
         &self.records
@@ -87,10 +209,13 @@ impl Tableable<ScanRecord> for ScanReport {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::package_durl::DirectURL;
     use crate::scan_fs::ScanFS;
+    use std::fs;
     use std::fs::File;
     use std::io;
     use std::io::BufRead;
+    use std::path::Path;
     use std::path::PathBuf;
     use tempfile::tempdir;
 
@@ -134,4 +259,138 @@ mod tests {
         );
         assert!(lines.next().is_none());
     }
+
+    #[test]
+    fn test_to_file_b() {
+        let dir = tempdir().unwrap();
+        let site =
+            PathShared::from_path_buf(dir.path().join("lib/python3/site-packages"));
+        let usite = PathShared::from_path_buf(
+            dir.path().join(".local/lib/python3.12/site-packages"),
+        );
+        let numpy = Package::from_name_version_durl("numpy", "2.1.2", None).unwrap();
+        let six = Package::from_name_version_durl("six", "1.16.0", None).unwrap();
+        // mark the user site directly, as user sites are otherwise found relative to the home directory
+        let mut record = ScanRecord::new(six, vec![site.clone(), usite.clone()], None);
+        record.user_sites = record.sites.iter().map(|s| *s == usite).collect();
+        let records = vec![record, ScanRecord::new(numpy, vec![site.clone()], None)];
+        let sr = ScanReport::from_records(records, false);
+
+        let fp = dir.path().join("scan.txt");
+        sr.to_file(&fp, '|').unwrap();
+        assert_eq!(
+            fs::read_to_string(&fp).unwrap().lines().collect::<Vec<_>>(),
+            vec![
+                "Package|Site|User Site".to_string(),
+                format!("numpy-2.1.2|{}|", site),
+                format!("six-1.16.0|{}|yes", usite),
+                format!("six-1.16.0|{}|", site),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_json_writer_a() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "1.19.3", None).unwrap(),
+            Package::from_name_version_durl("flask", "1.2", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let sr1 = sfs.to_scan_report();
+        let mut buffer = Vec::new();
+        sr1.to_json_writer(&mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap().trim(),
+            r#"[{"Package":"flask-1.2","Site":"/usr/lib/python3/site-packages"},{"Package":"numpy-1.19.3","Site":"/usr/lib/python3/site-packages"}]"#
+        );
+    }
+
+    #[test]
+    fn test_to_json_writer_b() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let durl = DirectURL::from_editable_path(Path::new("/home/user/src/dill"));
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "1.19.3", None).unwrap(),
+            Package::from_name_version_durl("dill", "0.3.9", Some(durl)).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let sr1 = sfs.to_scan_report();
+        let mut buffer = Vec::new();
+        sr1.to_json_writer(&mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap().trim(),
+            r#"[{"Package":"dill-0.3.9","Site":"/usr/lib/python3/site-packages","Source":"/home/user/src/dill"},{"Package":"numpy-1.19.3","Site":"/usr/lib/python3/site-packages","Source":""}]"#
+        );
+    }
+
+    #[test]
+    fn test_to_json_writer_c() {
+        let dir = tempdir().unwrap();
+        let site = dir.path().join("site-packages");
+        for (di, installer) in [
+            ("numpy-1.19.3.dist-info", "uv\n"),
+            ("flask-1.2.dist-info", ""),
+        ] {
+            fs::create_dir_all(site.join(di)).unwrap();
+            fs::write(site.join(di).join("INSTALLER"), installer).unwrap();
+        }
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "1.19.3", None).unwrap(),
+            Package::from_name_version_durl("flask", "1.2", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(
+            PathBuf::from("python3"),
+            site.clone(),
+            packages,
+        )
+        .unwrap();
+        let sr1 = sfs.to_scan_report();
+        let mut buffer = Vec::new();
+        sr1.to_json_writer(&mut buffer).unwrap();
+        let site = site.display();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap().trim(),
+            format!(
+                r#"[{{"Installer":"","Package":"flask-1.2","Site":"{}"}},{{"Installer":"uv","Package":"numpy-1.19.3","Site":"{}"}}]"#,
+                site, site
+            )
+        );
+    }
+
+    #[test]
+    fn test_from_package_to_sites_a() {
+        let site_a = PathShared::from_str("/usr/lib/python3/site-packages");
+        let site_b = PathShared::from_str("/opt/venv/lib/python3.12/site-packages");
+        let mut package_to_sites = HashMap::new();
+        for (name, version, sites) in [
+            ("six", "1.16.0", vec![site_a.clone(), site_b.clone()]),
+            ("PyYAML", "6.0.2", vec![site_a.clone()]),
+            ("numpy", "2.1.2", vec![site_b.clone()]),
+            ("numpy", "1.26.4", vec![site_a.clone()]),
+            ("Flask", "3.0.3", vec![site_b.clone()]),
+        ] {
+            let package = Package::from_name_version_durl(name, version, None).unwrap();
+            package_to_sites.insert(package, sites);
+        }
+        let sr = ScanReport::from_package_to_sites(&package_to_sites, None);
+        let rows: Vec<Vec<String>> = sr
+            .get_records()
+            .iter()
+            .flat_map(|r| r.to_rows(&RowableContext::Delimited))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["Flask-3.0.3", "/opt/venv/lib/python3.12/site-packages"],
+                vec!["numpy-1.26.4", "/usr/lib/python3/site-packages"],
+                vec!["numpy-2.1.2", "/opt/venv/lib/python3.12/site-packages"],
+                vec!["PyYAML-6.0.2", "/usr/lib/python3/site-packages"],
+                vec!["six-1.16.0", "/opt/venv/lib/python3.12/site-packages"],
+                vec!["six-1.16.0", "/usr/lib/python3/site-packages"],
+            ]
+        );
+    }
 }
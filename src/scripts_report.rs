@@ -0,0 +1,452 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use rayon::prelude::*;
+
+use crate::conda_meta::is_conda_meta;
+use crate::package::Package;
+use crate::path_shared::PathShared;
+use crate::table::ColumnFormat;
+use crate::table::Rowable;
+use crate::table::RowableContext;
+use crate::table::Tableable;
+use crate::util::path_is_same;
+
+// The maximum number of bytes read from the start of a script to find its interpreter.
+const SHEBANG_MAX: u64 = 8192;
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum ScriptStatus {
+    Missing,
+    InterpreterMissing,
+    InterpreterMismatch,
+}
+
+impl fmt::Display for ScriptStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ScriptStatus::Missing => "Missing",
+            ScriptStatus::InterpreterMissing => "InterpreterMissing",
+            ScriptStatus::InterpreterMismatch => "InterpreterMismatch",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+// Given the contents of an `entry_points.txt` file, return the sorted names of the console and GUI scripts it declares.
+fn get_script_names(content: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut is_script_section = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let section = section.trim();
+            is_script_section = section == "console_scripts" || section == "gui_scripts";
+        } else if is_script_section {
+            if let Some((name, _)) = line.split_once('=') {
+                let name = name.trim();
+                if !name.is_empty() {
+                    names.push(name.to_string());
+                }
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+// Remove `.` and `..` components without accessing the file system, as RECORD paths to scripts are relative to the site (e.g. `../../../bin/foo`).
+fn path_clean(path: &Path) -> PathBuf {
+    let mut clean = PathBuf::new();
+    for c in path.components() {
+        match c {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                clean.pop();
+            }
+            _ => clean.push(c),
+        }
+    }
+    clean
+}
+
+// Given the file name of an installed script, return the name of the script it provides: Windows installs scripts as `foo.exe`, and older installers as `foo-script.py`.
+fn file_name_to_script_name(file_name: &str) -> &str {
+    [".exe", "-script.pyw", "-script.py"]
+        .iter()
+        .find_map(|suffix| file_name.strip_suffix(suffix))
+        .unwrap_or(file_name)
+}
+
+// Given a RECORD file and the site its paths are relative to, return a mapping of script name to the paths of files installed outside of the site, such as in `bin` or `Scripts`.
+fn record_to_script_paths(content: &str, site: &Path) -> HashMap<String, PathBuf> {
+    let mut paths = HashMap::new();
+    for line in content.lines() {
+        let path = match line.split(',').next().filter(|p| !p.trim().is_empty()) {
+            Some(p) => path_clean(&site.join(p)),
+            None => continue,
+        };
+        if path.starts_with(site) {
+            continue;
+        }
+        if let Some(file_name) = path.file_name().and_then(|f| f.to_str()) {
+            let name = file_name_to_script_name(file_name).to_string();
+            paths.entry(name).or_insert(path);
+        }
+    }
+    paths
+}
+
+// Return the directory in which scripts are installed for an executable. Windows installs scripts in `Scripts`, which is in the same directory as the executable in a virtual environment, and below it otherwise.
+fn exe_to_scripts_dir(exe: &Path) -> Option<PathBuf> {
+    let dir = exe.parent()?;
+    let scripts = dir.join("Scripts");
+    if cfg!(windows)
+        && dir.file_name().is_some_and(|n| n != "Scripts")
+        && scripts.is_dir()
+    {
+        Some(scripts)
+    } else {
+        Some(dir.to_path_buf())
+    }
+}
+
+/// Given a script, return the interpreter named in its shebang. Scripts with long or space-containing interpreter paths are written by pip with a `/bin/sh` shebang followed by an `exec` of the interpreter. Returns None if there is no shebang, as for Windows launchers, or if the interpreter is found with `/usr/bin/env`, as then it is relocatable.
+fn read_shebang(script: &Path) -> Option<PathBuf> {
+    let mut bytes = Vec::new();
+    fs::File::open(script)
+        .ok()?
+        .take(SHEBANG_MAX)
+        .read_to_end(&mut bytes)
+        .ok()?;
+    let content = String::from_utf8_lossy(&bytes);
+    let mut lines = content.lines();
+    let first = lines.next()?.strip_prefix("#!")?.trim();
+    let command = if first == "/bin/sh" {
+        lines.find_map(|line| line.strip_prefix("'''exec' "))?
+    } else {
+        first
+    };
+    let interpreter = match command.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next()?,
+        None => command.split_whitespace().next()?,
+    };
+    let interpreter = PathBuf::from(interpreter);
+    if interpreter.file_name().is_some_and(|n| n == "env") {
+        None
+    } else {
+        Some(interpreter)
+    }
+}
+
+// Return true if a shebang interpreter is one of `exes`: it must be in the same directory as an executable, or, for executables not in a virtual environment, resolve to the same file. Virtual environment interpreters resolve to their base, so resolving them would accept scripts that bypass the environment.
+fn is_owning_interpreter(interpreter: &Path, exes: &[PathBuf]) -> bool {
+    exes.iter().any(|exe| {
+        let dir_exe = exe.parent();
+        let is_venv = dir_exe
+            .and_then(|d| d.parent())
+            .is_some_and(|p| p.join("pyvenv.cfg").exists());
+        match (dir_exe, interpreter.parent()) {
+            (Some(dir_exe), Some(dir_interp)) if path_is_same(dir_exe, dir_interp) => {
+                true
+            }
+            _ => !is_venv && path_is_same(exe, interpreter),
+        }
+    })
+}
+
+/// Given a package, its site, and the executables that use that site, return the status of each declared script that is missing or whose interpreter does not exist or is not one of the executables.
+fn package_to_status(
+    package: &Package,
+    site: &PathShared,
+    exes: &[PathBuf],
+) -> Vec<(String, ScriptStatus, PathBuf, Option<PathBuf>)> {
+    let dir_dist_info = match package.to_dist_info_dir(site) {
+        Some(dir) => dir,
+        None => return Vec::new(),
+    };
+    let names = match fs::read_to_string(dir_dist_info.join("entry_points.txt")) {
+        Ok(content) => get_script_names(&content),
+        Err(_) => return Vec::new(),
+    };
+    if names.is_empty() {
+        return Vec::new();
+    }
+    let recorded = fs::read_to_string(dir_dist_info.join("RECORD"))
+        .map(|content| record_to_script_paths(&content, site.as_path()))
+        .unwrap_or_default();
+    let dirs: Vec<PathBuf> = exes.iter().filter_map(|e| exe_to_scripts_dir(e)).collect();
+
+    let mut status = Vec::new();
+    for name in names {
+        let script = recorded.get(&name).cloned().or_else(|| {
+            let candidates: Vec<PathBuf> = dirs
+                .iter()
+                .flat_map(|d| [d.join(&name), d.join(format!("{}.exe", name))])
+                .collect();
+            candidates
+                .iter()
+                .find(|p| p.exists())
+                .or(candidates.first())
+                .cloned()
+        });
+        let script = match script {
+            Some(script) => script,
+            None => continue,
+        };
+        if !script.exists() {
+            status.push((name, ScriptStatus::Missing, script, None));
+            continue;
+        }
+        if let Some(interpreter) = read_shebang(&script) {
+            if !interpreter.exists() {
+                status.push((
+                    name,
+                    ScriptStatus::InterpreterMissing,
+                    script,
+                    Some(interpreter),
+                ));
+            } else if !is_owning_interpreter(&interpreter, exes) {
+                status.push((
+                    name,
+                    ScriptStatus::InterpreterMismatch,
+                    script,
+                    Some(interpreter),
+                ));
+            }
+        }
+    }
+    status
+}
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone)]
+pub(crate) struct ScriptsRecord {
+    package: Package,
+    site: PathShared,
+    status: Vec<(String, ScriptStatus, PathBuf, Option<PathBuf>)>,
+}
+
+impl Rowable for ScriptsRecord {
+    fn to_rows(&self, context: &RowableContext) -> Vec<Vec<String>> {
+        let is_tty = *context == RowableContext::Tty;
+        self.status
+            .iter()
+            .enumerate()
+            .map(|(i, (name, status, script, interpreter))| {
+                let (package_display, site_display) = if is_tty && i > 0 {
+                    ("".to_string(), "".to_string())
+                } else {
+                    (self.package.to_string(), self.site.to_string())
+                };
+                vec![
+                    package_display,
+                    site_display,
+                    name.clone(),
+                    status.to_string(),
+                    script.display().to_string(),
+                    interpreter
+                        .as_ref()
+                        .map(|p| p.display().to_string())
+                        .unwrap_or_default(),
+                ]
+            })
+            .collect()
+    }
+}
+
+//------------------------------------------------------------------------------
+/// A report of the console and GUI scripts declared in the `entry_points.txt` of each package that are missing from the scripts directory of the environment, or whose shebang names an interpreter that does not exist or is not an executable of the site, as found in relocated or partially installed environments. Scripts are located by their RECORD entries or, if not recorded, in the directories of the executables. Packages without a dist-info directory are not examined.
+pub(crate) struct ScriptsReport {
+    records: Vec<ScriptsRecord>,
+}
+
+impl ScriptsReport {
+    pub(crate) fn from_package_to_sites(
+        package_to_sites: &HashMap<Package, Vec<PathShared>>,
+        site_to_exes: &HashMap<PathShared, Vec<PathBuf>>,
+    ) -> Self {
+        let mut records: Vec<ScriptsRecord> = package_to_sites
+            .par_iter()
+            .flat_map(|(package, sites)| {
+                sites.par_iter().filter_map(move |site| {
+                    if is_conda_meta(site.as_path()) {
+                        return None;
+                    }
+                    let exes = site_to_exes.get(site)?;
+                    let status = package_to_status(package, site, exes);
+                    if status.is_empty() {
+                        None
+                    } else {
+                        Some(ScriptsRecord {
+                            package: package.clone(),
+                            site: site.clone(),
+                            status,
+                        })
+                    }
+                })
+            })
+            .collect();
+        records.sort_by(|a, b| {
+            (&a.package, a.site.as_path()).cmp(&(&b.package, b.site.as_path()))
+        });
+        ScriptsReport { records }
+    }
+
+    /// Return the number of scripts that are missing or have a broken interpreter.
+    pub(crate) fn len(&self) -> usize {
+        self.records.iter().map(|r| r.status.len()).sum()
+    }
+}
+
+impl Tableable<ScriptsRecord> for ScriptsReport {
+    fn get_header(&self) -> Vec<ColumnFormat> {
+        vec![
+            ColumnFormat::new("Package".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Site".to_string(), true, "#666666".to_string()),
+            ColumnFormat::new("Script".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Status".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Path".to_string(), true, "#666666".to_string()),
+            ColumnFormat::new("Interpreter".to_string(), true, "#666666".to_string()),
+        ]
+    }
+    fn get_records(&self) -> &Vec<ScriptsRecord> {
+        &self.records
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_get_script_names_a() {
+        let content = "[console_scripts]\nfoo = foo.cli:main\nfoo-admin=foo.admin:main\n\n[gui_scripts]\nfoo-gui = foo.gui:main\n\n[foo.plugins]\nbar = foo.bar\n";
+        assert_eq!(
+            get_script_names(content),
+            vec!["foo", "foo-admin", "foo-gui"]
+        );
+        assert_eq!(get_script_names("[foo.plugins]\nbar = foo.bar\n").len(), 0);
+    }
+
+    #[test]
+    fn test_file_name_to_script_name_a() {
+        assert_eq!(file_name_to_script_name("foo"), "foo");
+        assert_eq!(file_name_to_script_name("foo.exe"), "foo");
+        assert_eq!(file_name_to_script_name("foo-script.py"), "foo");
+    }
+
+    #[test]
+    fn test_record_to_script_paths_a() {
+        let site = Path::new("/venv/lib/python3.12/site-packages");
+        let content = "foo/__init__.py,sha256=abc,0\n../../../bin/foo,sha256=abc,0\nfoo-1.0.dist-info/RECORD,,\n";
+        let paths = record_to_script_paths(content, site);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths.get("foo").unwrap(), Path::new("/venv/bin/foo"));
+    }
+
+    #[test]
+    fn test_read_shebang_a() {
+        let dir = tempdir().unwrap();
+        let cases = [
+            ("#!/venv/bin/python\nimport sys\n", Some("/venv/bin/python")),
+            (
+                "#!/bin/sh\n'''exec' \"/my venv/bin/python\" \"$0\" \"$@\"\n' '''\n",
+                Some("/my venv/bin/python"),
+            ),
+            ("#!/usr/bin/env python3\n", None),
+            ("import sys\n", None),
+        ];
+        for (i, (content, expected)) in cases.iter().enumerate() {
+            let fp = dir.path().join(format!("script{}", i));
+            fs::write(&fp, content).unwrap();
+            assert_eq!(read_shebang(&fp), expected.map(PathBuf::from));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scripts_report_a() {
+        let dir = tempdir().unwrap();
+        let prefix = dir.path().join("venv");
+        let bin = prefix.join("bin");
+        let site = prefix.join("lib/python3.12/site-packages");
+        let base = dir.path().join("base/bin");
+        fs::create_dir_all(&bin).unwrap();
+        fs::create_dir_all(&base).unwrap();
+        fs::write(prefix.join("pyvenv.cfg"), "home = /usr/bin\n").unwrap();
+        let exe = bin.join("python");
+        fs::write(&exe, "").unwrap();
+        fs::write(base.join("python3"), "").unwrap();
+
+        let packages = [
+            (
+                "foo-1.0",
+                "[console_scripts]\nfoo = foo:main\nfoo-old = foo:old\n",
+            ),
+            ("bar-2.0", "[console_scripts]\nbar = bar:main\n"),
+            ("baz-3.0", "[console_scripts]\nbaz = baz:main\n"),
+        ];
+        let mut package_to_sites = HashMap::new();
+        let site_shared = PathShared::from_path_buf(site.clone());
+        for (name, entry_points) in packages.iter() {
+            let dir_dist_info = site.join(format!("{}.dist-info", name));
+            fs::create_dir_all(&dir_dist_info).unwrap();
+            fs::write(dir_dist_info.join("entry_points.txt"), entry_points).unwrap();
+            let script = name.split('-').next().unwrap();
+            fs::write(
+                dir_dist_info.join("RECORD"),
+                format!("../../../bin/{},,\n", script),
+            )
+            .unwrap();
+            let pkg = Package::from_dist_info(&format!("{}.dist-info", name), None, None)
+                .unwrap();
+            package_to_sites.insert(pkg, vec![site_shared.clone()]);
+        }
+        // valid
+        fs::write(
+            bin.join("foo"),
+            format!("#!{}\nimport foo\n", exe.display()),
+        )
+        .unwrap();
+        // relocated: the interpreter no longer exists
+        fs::write(bin.join("bar"), "#!/old/venv/bin/python\nimport bar\n").unwrap();
+        // installed with the base interpreter
+        fs::write(
+            bin.join("baz"),
+            format!("#!{}\nimport baz\n", base.join("python3").display()),
+        )
+        .unwrap();
+
+        let mut site_to_exes = HashMap::new();
+        site_to_exes.insert(site_shared, vec![exe]);
+        let sr = ScriptsReport::from_package_to_sites(&package_to_sites, &site_to_exes);
+        assert_eq!(sr.len(), 3);
+        let status: Vec<(String, ScriptStatus)> = sr
+            .records
+            .iter()
+            .flat_map(|r| r.status.iter().map(|(n, s, _, _)| (n.clone(), *s)))
+            .collect();
+        assert_eq!(
+            status,
+            vec![
+                ("bar".to_string(), ScriptStatus::InterpreterMissing),
+                ("baz".to_string(), ScriptStatus::InterpreterMismatch),
+                ("foo-old".to_string(), ScriptStatus::Missing),
+            ]
+        );
+        assert_eq!(sr.records[2].status[0].2, bin.join("foo-old"));
+    }
+}
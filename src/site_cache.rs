@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::package::Package;
+use crate::util::path_cache;
+use crate::util::ResultDynError;
+
+const SITES_JSON: &str = "sites.json";
+
+//------------------------------------------------------------------------------
+// The Packages found in a site, with the modification time of the site directory observed before it was read. Installing or removing a package adds or removes entries in the site directory, updating its modification time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SiteRecord {
+    mtime: Duration,
+    packages: Vec<Package>,
+}
+
+/// A cache of the Packages found in each site, stored independently of the scan cache such that sites whose directories have not been modified are not read again, even if the executables scanned differ.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct SiteCache {
+    sites: HashMap<PathBuf, SiteRecord>,
+}
+
+impl SiteCache {
+    pub(crate) fn from_file(file_path: &Path) -> ResultDynError<Self> {
+        let contents = fs::read_to_string(file_path)?;
+        let sc: SiteCache = serde_json::from_str(&contents)?;
+        Ok(sc)
+    }
+
+    /// Load the site cache from the cache directory. If it does not exist or cannot be read, an empty cache is returned.
+    pub(crate) fn from_cache_dir() -> Self {
+        path_cache(false)
+            .and_then(|dir| Self::from_file(&dir.join(SITES_JSON)).ok())
+            .unwrap_or_default()
+    }
+
+    /// Return the Packages of a site if it has been cached with the same modification time.
+    pub(crate) fn get_packages(
+        &self,
+        site: &Path,
+        mtime: Duration,
+    ) -> Option<Vec<Package>> {
+        match self.sites.get(site) {
+            Some(record) if record.mtime == mtime => Some(record.packages.clone()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn insert(&mut self, site: &Path, mtime: Duration, packages: &[Package]) {
+        self.sites.insert(
+            site.to_path_buf(),
+            SiteRecord {
+                mtime,
+                packages: packages.to_vec(),
+            },
+        );
+    }
+
+    pub(crate) fn to_file(&self, file_path: &Path) -> ResultDynError<()> {
+        let json = serde_json::to_string(self)?;
+        fs::write(file_path, json)?;
+        Ok(())
+    }
+
+    pub(crate) fn to_cache_dir(&self) -> ResultDynError<()> {
+        let dir = path_cache(true).ok_or("could not get cache directory")?;
+        self.to_file(&dir.join(SITES_JSON))
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::path_mtime;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_site_cache_a() {
+        let temp_dir = tempdir().unwrap();
+        let site = temp_dir.path().join("site-packages");
+        fs::create_dir(&site).unwrap();
+        let mtime = path_mtime(&site).unwrap();
+
+        let packages =
+            vec![Package::from_name_version_durl("numpy", "2.1.2", None).unwrap()];
+        let mut sc = SiteCache::default();
+        assert!(sc.get_packages(&site, mtime).is_none());
+        sc.insert(&site, mtime, &packages);
+        assert_eq!(sc.get_packages(&site, mtime), Some(packages));
+        // a modified site is not returned
+        assert!(sc
+            .get_packages(&site, mtime + Duration::from_secs(1))
+            .is_none());
+    }
+
+    #[test]
+    fn test_site_cache_b() {
+        let temp_dir = tempdir().unwrap();
+        let site = temp_dir.path().join("site-packages");
+        let mtime = Duration::from_secs(1_700_000_000);
+        let packages =
+            vec![Package::from_name_version_durl("requests", "2.32.3", None).unwrap()];
+        let mut sc1 = SiteCache::default();
+        sc1.insert(&site, mtime, &packages);
+
+        let fp = temp_dir.path().join(SITES_JSON);
+        sc1.to_file(&fp).unwrap();
+        let sc2 = SiteCache::from_file(&fp).unwrap();
+        assert_eq!(sc2.get_packages(&site, mtime), Some(packages));
+    }
+}
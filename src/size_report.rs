@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+
+use crate::conda_meta::is_conda_meta;
+use crate::package::Package;
+use crate::path_shared::PathShared;
+use crate::table::ColumnFormat;
+use crate::table::Rowable;
+use crate::table::RowableContext;
+use crate::table::Tableable;
+use crate::unpack_report::Artifacts;
+
+// Format a number of bytes with binary units, e.g. "1.5 MiB".
+fn bytes_display(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone)]
+pub(crate) struct SizeRecord {
+    package: Option<Package>,
+    packages: usize,
+    site: PathShared,
+    files: usize,
+    size: u64,
+}
+
+impl Rowable for SizeRecord {
+    fn to_rows(&self, _context: &RowableContext) -> Vec<Vec<String>> {
+        let package_display = match &self.package {
+            Some(package) => package.to_string(),
+            None => format!("{} packages", self.packages),
+        };
+        vec![vec![
+            package_display,
+            self.site.to_string(),
+            self.files.to_string(),
+            self.size.to_string(),
+            bytes_display(self.size),
+        ]]
+    }
+}
+
+//------------------------------------------------------------------------------
+/// A report of the disk usage of each package in each site, as the sum of the sizes of the files in its RECORD; files without a recorded size are read from disk. If `by_site` is set, sizes are summed for each site. Records are sorted by descending size.
+pub(crate) struct SizeReport {
+    records: Vec<SizeRecord>,
+}
+
+impl SizeReport {
+    pub(crate) fn from_package_to_sites(
+        package_to_sites: &HashMap<Package, Vec<PathShared>>,
+        by_site: bool,
+    ) -> Self {
+        let mut records: Vec<SizeRecord> = package_to_sites
+            .par_iter()
+            .flat_map(|(package, sites)| {
+                sites.par_iter().filter_map(move |site| {
+                    // conda-meta sites only attribute Packages; artifacts are found in site packages
+                    if is_conda_meta(site.as_path()) {
+                        return None;
+                    }
+                    let artifacts = Artifacts::from_package(package, site).ok()?;
+                    Some(SizeRecord {
+                        package: Some(package.clone()),
+                        packages: 1,
+                        site: site.clone(),
+                        files: artifacts.files.len(),
+                        size: artifacts.files.iter().filter_map(|af| af.size).sum(),
+                    })
+                })
+            })
+            .collect();
+        if by_site {
+            let mut site_to_record: HashMap<PathShared, SizeRecord> = HashMap::new();
+            for record in records {
+                let entry =
+                    site_to_record
+                        .entry(record.site.clone())
+                        .or_insert_with(|| SizeRecord {
+                            package: None,
+                            packages: 0,
+                            site: record.site.clone(),
+                            files: 0,
+                            size: 0,
+                        });
+                entry.packages += record.packages;
+                entry.files += record.files;
+                entry.size += record.size;
+            }
+            records = site_to_record.into_values().collect();
+        }
+        records.sort_by(|a, b| {
+            b.size
+                .cmp(&a.size)
+                .then_with(|| a.package.cmp(&b.package))
+                .then_with(|| a.site.as_path().cmp(b.site.as_path()))
+        });
+        SizeReport { records }
+    }
+}
+
+impl Tableable<SizeRecord> for SizeReport {
+    fn get_header(&self) -> Vec<ColumnFormat> {
+        vec![
+            ColumnFormat::new("Package".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Site".to_string(), true, "#666666".to_string()),
+            ColumnFormat::new("Files".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Bytes".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Size".to_string(), false, "#666666".to_string()),
+        ]
+    }
+    fn get_records(&self) -> &Vec<SizeRecord> {
+        &self.records
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_bytes_display_a() {
+        assert_eq!(bytes_display(0), "0 B");
+        assert_eq!(bytes_display(1023), "1023 B");
+        assert_eq!(bytes_display(1536), "1.5 KiB");
+        assert_eq!(bytes_display(3 * 1024 * 1024), "3.0 MiB");
+    }
+
+    #[test]
+    fn test_size_report_a() {
+        let dir = tempdir().unwrap();
+        let site = PathShared::from_path_buf(dir.path().to_path_buf());
+        let records = [
+            ("numpy-2.1.2.dist-info", "numpy/__init__.py,sha256=abc,4000\nnumpy/core.so,sha256=abc,60000\nnumpy-2.1.2.dist-info/RECORD,,\n"),
+            ("six-1.16.0.dist-info", "six.py,sha256=abc,34549\nsix-1.16.0.dist-info/RECORD,,\n"),
+        ];
+        for (di, content) in records {
+            let fp_di = dir.path().join(di);
+            fs::create_dir(&fp_di).unwrap();
+            fs::write(fp_di.join("RECORD"), content).unwrap();
+        }
+        let mut package_to_sites = HashMap::new();
+        for (name, version) in [("six", "1.16.0"), ("numpy", "2.1.2")] {
+            let package = Package::from_name_version_durl(name, version, None).unwrap();
+            package_to_sites.insert(package, vec![site.clone()]);
+        }
+
+        let sr = SizeReport::from_package_to_sites(&package_to_sites, false);
+        let fp = dir.path().join("report.txt");
+        sr.to_file(&fp, '|').unwrap();
+        let content = fs::read_to_string(&fp).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        // the size of RECORD files, written without a size, is read from disk
+        let record_size = fs::metadata(dir.path().join("six-1.16.0.dist-info/RECORD"))
+            .unwrap()
+            .len();
+        assert_eq!(lines[0], "Package|Site|Files|Bytes|Size");
+        assert!(lines[1].starts_with(&format!("numpy-2.1.2|{}|3|", site)));
+        assert_eq!(
+            lines[2],
+            format!(
+                "six-1.16.0|{}|2|{}|{}",
+                site,
+                34549 + record_size,
+                bytes_display(34549 + record_size)
+            )
+        );
+
+        let sr = SizeReport::from_package_to_sites(&package_to_sites, true);
+        assert_eq!(sr.records.len(), 1);
+        assert_eq!(sr.records[0].packages, 2);
+        assert_eq!(sr.records[0].files, 5);
+    }
+}
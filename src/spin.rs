@@ -1,18 +1,14 @@
 use crossterm::tty::IsTty;
-use crossterm::{
-    cursor,
-    terminal::{Clear, ClearType},
-    ExecutableCommand,
-};
-use std::io::{stdout, Write};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::io::stderr;
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
+    atomic::{AtomicBool, AtomicUsize, Ordering},
     Arc,
 };
 use std::thread;
 use std::time::Duration;
 
-use crate::table::write_color;
+use crate::table::use_color;
 
 // we duplicate each component so we can update frames faster while keeping the visual changes slow
 const FRAME_SPIN: [&str; 20] = [
@@ -26,30 +22,123 @@ const FRAME_SPIN: [&str; 20] = [
 // vec!["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█", "▇", "▆", "▅", "▄", "▃", "▂", "▁", " "];
 // vec!["○─•  ", "◉──• ", "◎───•", "◉──• ", "○─•  "];
 
+//------------------------------------------------------------------------------
+/// Counts of work done by long-running scans and validations, shown by `spin`.
+pub(crate) struct Progress {
+    exes: AtomicUsize,
+    sites: AtomicUsize,
+    packages: AtomicUsize,
+    validated: AtomicUsize,
+}
+
+/// The progress of the current operation; counts are reset when a spinner starts.
+pub(crate) static PROGRESS: Progress = Progress::new();
+
+impl Progress {
+    const fn new() -> Self {
+        Progress {
+            exes: AtomicUsize::new(0),
+            sites: AtomicUsize::new(0),
+            packages: AtomicUsize::new(0),
+            validated: AtomicUsize::new(0),
+        }
+    }
+
+    /// Count executables whose sites have been discovered.
+    pub(crate) fn add_exes(&self, count: usize) {
+        self.exes.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Count distinct sites to be read.
+    pub(crate) fn add_sites(&self, count: usize) {
+        self.sites.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Count packages read from dist-info or egg-info metadata.
+    pub(crate) fn add_packages(&self, count: usize) {
+        self.packages.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Count packages validated.
+    pub(crate) fn add_validated(&self, count: usize) {
+        self.validated.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn reset(&self) {
+        for count in [&self.exes, &self.sites, &self.packages, &self.validated] {
+            count.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Return a description of the non-zero counts, such as "12 executables, 30 sites, 4211 packages".
+    fn to_message(&self) -> String {
+        [
+            (&self.exes, "executables"),
+            (&self.sites, "sites"),
+            (&self.packages, "packages"),
+            (&self.validated, "validated"),
+        ]
+        .iter()
+        .map(|(count, label)| (count.load(Ordering::Relaxed), label))
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, label)| format!("{} {}", count, label))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
+
+//------------------------------------------------------------------------------
+/// Show a spinner with `message`, and the current progress, on stderr until `active` is false. Nothing is shown if stderr is not a terminal, or if finished within a second.
 pub(crate) fn spin(active: Arc<AtomicBool>, message: String) {
-    let mut stdout = stdout();
-    if !stdout.is_tty() {
+    if !stderr().is_tty() {
         return;
     }
-    let mut frame_idx = 0;
+    PROGRESS.reset();
 
     thread::spawn(move || {
         // wait 1 sec to avoid starting for fast searches
         let delay_init = Duration::from_secs(1);
         thread::sleep(delay_init);
         if active.load(Ordering::Relaxed) {
-            stdout.execute(Clear(ClearType::CurrentLine)).unwrap();
+            let template = if use_color(true) {
+                "{spinner:.241} {msg:.241}"
+            } else {
+                "{spinner} {msg}"
+            };
+            let style = ProgressStyle::with_template(template)
+                .unwrap()
+                .tick_strings(&FRAME_SPIN);
+            let pb = ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr())
+                .with_style(style);
             while active.load(Ordering::Relaxed) {
-                stdout.execute(cursor::MoveToColumn(0)).unwrap();
-                let fs = FRAME_SPIN[frame_idx % FRAME_SPIN.len()];
-                let msg = format!("{} {}... ", fs, message);
-                write_color(&mut stdout, "#666666", &msg);
-                stdout.flush().unwrap();
+                let progress = PROGRESS.to_message();
+                if progress.is_empty() {
+                    pb.set_message(format!("{}...", message));
+                } else {
+                    pb.set_message(format!("{}... {}", message, progress));
+                }
+                pb.tick();
                 thread::sleep(Duration::from_millis(80));
-                frame_idx += 1;
             }
-            stdout.execute(cursor::MoveToColumn(0)).unwrap();
-            stdout.execute(Clear(ClearType::CurrentLine)).unwrap();
+            pb.finish_and_clear();
         }
     });
 }
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_a() {
+        let progress = Progress::new();
+        assert_eq!(progress.to_message(), "");
+        progress.add_exes(2);
+        progress.add_packages(10);
+        progress.add_packages(5);
+        assert_eq!(progress.to_message(), "2 executables, 15 packages");
+        progress.reset();
+        assert_eq!(progress.to_message(), "");
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use rayon::prelude::*;
+use tracing::info;
+use tracing::warn;
+
+use crate::conda_meta::is_conda_meta;
+use crate::package::Package;
+use crate::path_shared::PathShared;
+use crate::table::ColumnFormat;
+use crate::table::Rowable;
+use crate::table::RowableContext;
+use crate::table::Tableable;
+use crate::unpack_report::Artifacts;
+
+// Files at the root of a site that are written by Python or virtualenv rather than by an installed package.
+const SITE_FILES: [&str; 3] = ["README.txt", "_virtualenv.pth", "_virtualenv.py"];
+
+// Recursively collect files and symlinks within `dir` not in `owned`. Symlinked directories are not followed; `__pycache__` directories, egg-info directories (which have no RECORD file), and directories in `skip` are not examined.
+fn collect_strays(
+    dir: &Path,
+    owned: &HashSet<PathBuf>,
+    skip: &HashSet<PathBuf>,
+    strays: &mut Vec<(PathBuf, u64)>,
+) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let ft = match entry.file_type() {
+            Ok(ft) => ft,
+            Err(_) => continue,
+        };
+        if ft.is_dir() {
+            let is_egg_info = path.extension().is_some_and(|ext| ext == "egg-info");
+            let is_examined = entry.file_name() != "__pycache__"
+                && !is_egg_info
+                && !skip.contains(&path);
+            if is_examined {
+                collect_strays(&path, owned, skip, strays);
+            }
+        } else if !owned.contains(&path) {
+            let size = fs::symlink_metadata(&path).map(|md| md.len()).unwrap_or(0);
+            strays.push((path, size));
+        }
+    }
+}
+
+/// Given a site and the packages installed in it, return all files within the site not listed in the RECORD file of any package. Packages without a RECORD file, such as those installed with `setup.py install`, cannot attribute their files; their dist-info and source directories are not examined.
+fn site_to_strays(site: &Path, packages: &[Package]) -> Vec<(PathBuf, u64)> {
+    let site_shared = PathShared::from_path_buf(site.to_path_buf());
+    let mut owned: HashSet<PathBuf> =
+        SITE_FILES.iter().map(|name| site.join(name)).collect();
+    let mut skip: HashSet<PathBuf> = HashSet::new();
+    for package in packages {
+        match Artifacts::from_package(package, &site_shared) {
+            Ok(artifacts) => {
+                owned.extend(artifacts.files.into_iter().map(|af| af.path));
+            }
+            Err(_) => {
+                skip.extend(package.to_dist_info_dir(&site_shared));
+                skip.extend(package.to_src_dir(&site_shared));
+            }
+        }
+    }
+    let mut strays = Vec::new();
+    collect_strays(site, &owned, &skip, &mut strays);
+    strays.sort();
+    strays
+}
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone)]
+pub(crate) struct StrayRecord {
+    site: PathShared,
+    path: PathBuf,
+    size: u64,
+}
+
+impl Rowable for StrayRecord {
+    fn to_rows(&self, _context: &RowableContext) -> Vec<Vec<String>> {
+        vec![vec![
+            self.site.to_string(),
+            self.path.display().to_string(),
+            self.size.to_string(),
+        ]]
+    }
+}
+
+//------------------------------------------------------------------------------
+/// A report of files found in sites that are not owned by any installed package, as determined by the union of the RECORD files of all packages in each site. Such files might be left over from failed uninstalls or manual edits.
+pub(crate) struct StrayReport {
+    records: Vec<StrayRecord>,
+}
+
+impl StrayReport {
+    pub(crate) fn from_site_to_packages(
+        site_to_packages: &HashMap<PathShared, Vec<Package>>,
+    ) -> Self {
+        let mut records: Vec<StrayRecord> = site_to_packages
+            .par_iter()
+            .filter(|(site, _)| !is_conda_meta(site.as_path()))
+            .flat_map(|(site, packages)| {
+                site_to_strays(site.as_path(), packages)
+                    .into_par_iter()
+                    .map(move |(path, size)| StrayRecord {
+                        site: site.clone(),
+                        path,
+                        size,
+                    })
+            })
+            .collect();
+        records.sort_by(|a, b| {
+            (a.site.as_path(), &a.path).cmp(&(b.site.as_path(), &b.path))
+        });
+        StrayReport { records }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub(crate) fn remove(&self, log: bool) -> io::Result<()> {
+        for record in &self.records {
+            let fp = &record.path;
+            if let Err(e) = fs::remove_file(fp) {
+                warn!("Failed to remove file {:?}: {}", fp, e);
+            } else if log {
+                info!("Removing file: {:?}", fp);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Tableable<StrayRecord> for StrayReport {
+    fn get_header(&self) -> Vec<ColumnFormat> {
+        vec![
+            ColumnFormat::new("Site".to_string(), true, "#666666".to_string()),
+            ColumnFormat::new("File".to_string(), true, "#666666".to_string()),
+            ColumnFormat::new("Bytes".to_string(), false, "#666666".to_string()),
+        ]
+    }
+    fn get_records(&self) -> &Vec<StrayRecord> {
+        &self.records
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_stray_report_a() {
+        let dir = tempdir().unwrap();
+        let dir_site = dir.path().join("lib/python3.12/site-packages");
+        let site = PathShared::from_path_buf(dir_site.clone());
+        for d in [
+            "six-1.16.0.dist-info",
+            "foo-1.0.dist-info",
+            "foo/__pycache__",
+            "bar-2.0.egg-info",
+            "bar",
+            "old_pkg",
+        ] {
+            fs::create_dir_all(dir_site.join(d)).unwrap();
+        }
+        fs::write(
+            dir_site.join("six-1.16.0.dist-info/RECORD"),
+            "six.py,sha256=abc,0\nsix-1.16.0.dist-info/RECORD,,\n",
+        )
+        .unwrap();
+        fs::write(
+            dir_site.join("foo-1.0.dist-info/RECORD"),
+            "foo/__init__.py,sha256=abc,0\nfoo-1.0.dist-info/RECORD,,\n",
+        )
+        .unwrap();
+        for f in [
+            "six.py",
+            "README.txt",
+            "foo/__init__.py",
+            "foo/__pycache__/__init__.cpython-312.pyc",
+            "foo/patch.py",
+            "bar-2.0.egg-info/PKG-INFO",
+            "bar/__init__.py",
+            "old_pkg/core.py",
+        ] {
+            fs::write(dir_site.join(f), "x = 1\n").unwrap();
+        }
+        let packages: Vec<Package> = [("six", "1.16.0"), ("foo", "1.0"), ("bar", "2.0")]
+            .iter()
+            .map(|(n, v)| Package::from_name_version_durl(n, v, None).unwrap())
+            .collect();
+        let mut site_to_packages = HashMap::new();
+        site_to_packages.insert(site.clone(), packages);
+
+        let sr = StrayReport::from_site_to_packages(&site_to_packages);
+        assert_eq!(sr.len(), 2);
+        let fp = dir.path().join("report.txt");
+        sr.to_file(&fp, '|').unwrap();
+        assert_eq!(
+            fs::read_to_string(&fp).unwrap().lines().collect::<Vec<_>>(),
+            vec![
+                "Site|File|Bytes".to_string(),
+                format!("{}|{}|6", site, dir_site.join("foo/patch.py").display()),
+                format!("{}|{}|6", site, dir_site.join("old_pkg/core.py").display()),
+            ]
+        );
+
+        sr.remove(false).unwrap();
+        assert!(!dir_site.join("foo/patch.py").exists());
+        assert!(dir_site.join("foo/__init__.py").exists());
+        assert_eq!(
+            StrayReport::from_site_to_packages(&site_to_packages).len(),
+            0
+        );
+    }
+}
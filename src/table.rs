@@ -4,11 +4,19 @@ use crossterm::{
     execute,
     style::{Attribute, Color, Print, SetAttribute, SetForegroundColor},
 };
+use std::env;
+use std::ffi::OsStr;
 use std::fs::File;
 use std::io;
 use std::io::{Error, Write};
 use std::os::fd::AsRawFd;
+use std::path::Path;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU8, Ordering};
+use unicode_width::UnicodeWidthChar;
+use unicode_width::UnicodeWidthStr;
+
+use crate::util::percent_encode;
 
 fn to_rgb(hex_color: &str) -> (u8, u8, u8) {
     if hex_color.len() == 7 && hex_color.starts_with('#') {
@@ -22,8 +30,80 @@ fn to_rgb(hex_color: &str) -> (u8, u8, u8) {
     panic!("Bad color code: {}", hex_color);
 }
 
-pub fn write_color<W: Write + IsTty>(writer: &mut W, hex_color: &str, message: &str) {
-    if writer.is_tty() {
+//------------------------------------------------------------------------------
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+// The process-wide color choice, stored as an index of ColorChoice, as all terminal writers share it.
+static COLOR_CHOICE: AtomicU8 = AtomicU8::new(0);
+
+pub(crate) fn set_color_choice(choice: ColorChoice) {
+    let value = match choice {
+        ColorChoice::Auto => 0,
+        ColorChoice::Always => 1,
+        ColorChoice::Never => 2,
+    };
+    COLOR_CHOICE.store(value, Ordering::Relaxed);
+}
+
+// Return true if an environment variable value is set to a value other than empty or, if `zero_unset`, "0".
+fn env_is_set(value: Option<&OsStr>, zero_unset: bool) -> bool {
+    value.is_some_and(|v| !v.is_empty() && (!zero_unset || v != "0"))
+}
+
+// Determine if color should be written; with Auto, color is never written if the NO_COLOR environment variable is set, always written if the CLICOLOR_FORCE environment variable is set (and not "0"), and otherwise written only to a TTY.
+pub(crate) fn use_color(is_tty: bool) -> bool {
+    match COLOR_CHOICE.load(Ordering::Relaxed) {
+        1 => true,
+        2 => false,
+        _ => {
+            if env_is_set(env::var_os("NO_COLOR").as_deref(), false) {
+                false
+            } else if env_is_set(env::var_os("CLICOLOR_FORCE").as_deref(), true) {
+                true
+            } else {
+                is_tty
+            }
+        }
+    }
+}
+
+// Determine if the terminal, as identified by its environment variables, is known to support OSC 8 hyperlinks.
+fn supports_hyperlinks() -> bool {
+    let var = |name: &str| env::var(name).unwrap_or_default();
+    env::var_os("DOMTERM").is_some()
+        || env::var_os("WT_SESSION").is_some()
+        || env::var_os("KONSOLE_VERSION").is_some()
+        || var("VTE_VERSION").parse::<u32>().is_ok_and(|v| v >= 5000)
+        || matches!(
+            var("TERM_PROGRAM").as_str(),
+            "Hyper" | "iTerm.app" | "terminology" | "WezTerm" | "vscode" | "ghostty"
+        )
+        || matches!(
+            var("TERM").as_str(),
+            "xterm-kitty" | "alacritty" | "xterm-ghostty"
+        )
+}
+
+// Determine if hyperlinks should be written; the FORCE_HYPERLINK environment variable enables them, or, if "0", disables them; otherwise, they are written to a TTY of a terminal known to support them.
+fn use_hyperlinks(is_tty: bool) -> bool {
+    match env::var_os("FORCE_HYPERLINK") {
+        Some(v) => v != "0",
+        None => is_tty && supports_hyperlinks(),
+    }
+}
+
+/// Write `message` in the color `hex_color` if color is enabled for `writer`, otherwise without color.
+pub fn write_color<W: Write + IsTty>(
+    writer: &mut W,
+    hex_color: &str,
+    message: &str,
+) -> io::Result<()> {
+    if use_color(writer.is_tty()) {
         let (r, g, b) = to_rgb(hex_color);
         execute!(
             writer,
@@ -32,9 +112,8 @@ pub fn write_color<W: Write + IsTty>(writer: &mut W, hex_color: &str, message: &
             Print(message),
             SetAttribute(Attribute::Reset)
         )
-        .unwrap();
     } else {
-        writeln!(writer, "{}", message).unwrap();
+        write!(writer, "{}", message)
     }
 }
 
@@ -109,18 +188,115 @@ fn optimize_widths(
     widths
 }
 
-fn prepare_field(value: &String, widths: &WidthFormat) -> String {
-    if value.len() <= widths.width_chars {
-        format!("{:<w$}", value, w = widths.width_pad)
-    } else if widths.width_chars > 3 && (value.len() - widths.width_chars) > 3 {
-        format!(
-            "{:<w$}",
-            format!("{}...", &value[..(widths.width_chars - 3)]),
-            w = widths.width_pad
+// Return the longest prefix of `value` that does not exceed `width` terminal columns; wide characters, such as CJK, occupy two columns, and combining characters none.
+fn truncate_width(value: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (i, c) in value.char_indices() {
+        used += c.width().unwrap_or(0);
+        if used > width {
+            return &value[..i];
+        }
+    }
+    value
+}
+
+// Pad `value` with spaces to `width` terminal columns.
+fn pad_width(value: &str, width: usize) -> String {
+    format!(
+        "{}{}",
+        value,
+        " ".repeat(width.saturating_sub(value.width()))
+    )
+}
+
+fn prepare_field(value: &str, widths: &WidthFormat) -> String {
+    let width = value.width();
+    if width <= widths.width_chars {
+        pad_width(value, widths.width_pad)
+    } else if widths.width_chars > 3 && (width - widths.width_chars) > 3 {
+        pad_width(
+            &format!("{}...", truncate_width(value, widths.width_chars - 3)),
+            widths.width_pad,
         )
     } else {
-        format!("{:<w$}", &value[..widths.width_chars], w = widths.width_pad)
+        pad_width(truncate_width(value, widths.width_chars), widths.width_pad)
+    }
+}
+
+/// Convert Rowables to a JSON array of objects, where each row is keyed by header labels.
+fn to_table_json_value<T: Rowable>(
+    column_formats: Vec<ColumnFormat>,
+    records: &Vec<T>,
+) -> serde_json::Value {
+    let mut rows_json = Vec::new();
+    for record in records {
+        for row in record.to_rows(&RowableContext::Delimited) {
+            let mut object = serde_json::Map::new();
+            for (cf, element) in column_formats.iter().zip(row) {
+                object.insert(cf.header.clone(), serde_json::Value::String(element));
+            }
+            rows_json.push(serde_json::Value::Object(object));
+        }
+    }
+    serde_json::Value::Array(rows_json)
+}
+
+// Quote a field of delimited text, as per RFC 4180, if it contains the delimiter, a double quote, or a line break; double quotes within are doubled.
+fn delimited_escape(value: &str, delimiter: &str) -> String {
+    if value.contains(delimiter) || value.contains(['"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Write one row of delimited text, ending with a line break. Fields of columns that are not quotable are written verbatim.
+fn write_delimited_row<W: Write>(
+    writer: &mut W,
+    row: &[String],
+    column_formats: &[ColumnFormat],
+    delimiter: &str,
+) -> Result<(), Error> {
+    let fields: Vec<String> = row
+        .iter()
+        .enumerate()
+        .map(|(i, value)| match column_formats.get(i) {
+            Some(cf) if !cf.is_quotable() => value.clone(),
+            _ => delimited_escape(value, delimiter),
+        })
+        .collect();
+    writeln!(writer, "{}", fields.join(delimiter))
+}
+
+// Escape text for a cell of a Markdown table: pipes are escaped, and line breaks are replaced with HTML breaks.
+fn markdown_escape(value: &str) -> String {
+    value
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace(['\n', '\r'], "<br>")
+}
+
+fn to_table_markdown<W: Write, T: Rowable>(
+    writer: &mut W,
+    column_formats: Vec<ColumnFormat>,
+    records: &Vec<T>,
+) -> Result<(), Error> {
+    if records.is_empty() || column_formats.is_empty() {
+        return Ok(());
+    }
+    let header_labels: Vec<String> = column_formats
+        .iter()
+        .map(|hf| markdown_escape(&hf.header))
+        .collect();
+    writeln!(writer, "| {} |", header_labels.join(" | "))?;
+    writeln!(writer, "|{}|", vec![" --- "; header_labels.len()].join("|"))?;
+    for record in records {
+        for row in record.to_rows(&RowableContext::Delimited) {
+            let fields: Vec<String> = row.iter().map(|v| markdown_escape(v)).collect();
+            writeln!(writer, "| {} |", fields.join(" | "))?;
+        }
     }
+    Ok(())
 }
 
 fn to_table_delimited<W: Write, T: Rowable>(
@@ -134,15 +310,83 @@ fn to_table_delimited<W: Write, T: Rowable>(
     }
     let header_labels: Vec<String> =
         column_formats.iter().map(|hf| hf.header.clone()).collect();
-    writeln!(writer, "{}", header_labels.join(delimiter))?;
+    write_delimited_row(writer, &header_labels, &column_formats, delimiter)?;
     for record in records {
         for row in record.to_rows(&RowableContext::Delimited) {
-            writeln!(writer, "{}", row.join(delimiter))?;
+            write_delimited_row(writer, &row, &column_formats, delimiter)?;
         }
     }
     Ok(())
 }
 
+// Escape text for inclusion in HTML or XML element content and attribute values.
+pub(crate) fn markup_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const HTML_STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222222}table{border-collapse:collapse}th,td{padding:4px 12px;text-align:left;border-bottom:1px solid #dddddd;font-size:14px}th{color:#666666;cursor:pointer;user-select:none}th:hover{color:#000000}th[data-order=asc]::after{content:' \\25B2'}th[data-order=desc]::after{content:' \\25BC'}tr.failure{background:#fbe9e7}td.failure{color:#cc0000;font-weight:bold}";
+
+// Sort table rows by the text of a column when its header is clicked, alternating ascending and descending order; numeric portions of text are compared as numbers.
+const HTML_SCRIPT: &str = "document.querySelectorAll('th').forEach(function(th,i){th.addEventListener('click',function(){var body=th.closest('table').tBodies[0];var asc=th.dataset.order!=='asc';th.parentNode.querySelectorAll('th').forEach(function(h){delete h.dataset.order});th.dataset.order=asc?'asc':'desc';var rows=Array.from(body.rows);rows.sort(function(a,b){var c=a.cells[i].textContent.localeCompare(b.cells[i].textContent,undefined,{numeric:true});return asc?c:-c});rows.forEach(function(r){body.appendChild(r)})})});";
+
+/// Write Rowables as a self-contained HTML document with a table that can be sorted by clicking column headers. Cells that report a failure, as determined by their ColumnFormat, and their rows are highlighted. Unlike other writers, a document with only a header is written if there are no records.
+fn to_table_html<W: Write, T: Rowable>(
+    writer: &mut W,
+    column_formats: Vec<ColumnFormat>,
+    records: &Vec<T>,
+) -> Result<(), Error> {
+    writeln!(writer, "<!DOCTYPE html>")?;
+    writeln!(
+        writer,
+        "<html><head><meta charset=\"utf-8\"><title>fetter</title><style>{}</style></head><body>",
+        HTML_STYLE
+    )?;
+    writeln!(writer, "<table><thead><tr>")?;
+    for cf in column_formats.iter() {
+        writeln!(writer, "<th>{}</th>", markup_escape(&cf.header))?;
+    }
+    writeln!(writer, "</tr></thead><tbody>")?;
+    for record in records {
+        for row in record.to_rows(&RowableContext::Delimited) {
+            let failures: Vec<bool> = column_formats
+                .iter()
+                .zip(row.iter())
+                .map(|(cf, element)| cf.is_failure(element))
+                .collect();
+            if failures.iter().any(|f| *f) {
+                write!(writer, "<tr class=\"failure\">")?;
+            } else {
+                write!(writer, "<tr>")?;
+            }
+            for (element, failure) in row.iter().zip(failures) {
+                if failure {
+                    write!(
+                        writer,
+                        "<td class=\"failure\">{}</td>",
+                        markup_escape(element)
+                    )?;
+                } else {
+                    write!(writer, "<td>{}</td>", markup_escape(element))?;
+                }
+            }
+            writeln!(writer, "</tr>")?;
+        }
+    }
+    writeln!(writer, "</tbody></table>")?;
+    writeln!(writer, "<script>{}</script></body></html>", HTML_SCRIPT)
+}
+
 /// Wite Rowables to a writer. If `delimiter` is None, we assume writing to stdout; if `delimiter` is not None, we assume writing a delimited text file.
 fn to_table_display<W: Write + AsRawFd, T: Rowable>(
     writer: &mut W,
@@ -159,33 +403,34 @@ fn to_table_display<W: Write + AsRawFd, T: Rowable>(
     // evaluate column_formats and all elements in every row to determine max colum widths; store extracted rows for reuse in writing body.
     let mut widths_max = vec![0; column_formats.len()];
     for (i, header) in header_labels.iter().enumerate() {
-        widths_max[i] = header.len();
+        widths_max[i] = header.width();
     }
     let mut rows = Vec::new();
     for record in records {
         for row in record.to_rows(&RowableContext::Tty) {
             for (i, element) in row.iter().enumerate() {
-                widths_max[i] = widths_max[i].max(element.len());
+                widths_max[i] = widths_max[i].max(element.width());
             }
             rows.push(row);
         }
     }
     let w_gutter = 2;
     let widths = optimize_widths(&widths_max, &ellipsisable, w_gutter);
+    let hyperlinks = use_hyperlinks(writer.is_tty());
     // header
     for (i, header) in header_labels.into_iter().enumerate() {
         write_color(
             writer,
             &column_formats[i].color,
             &prepare_field(&header, &widths[i]),
-        );
+        )?;
         // write!(writer, "{}", prepare_field(&header, &widths[i]),)?;
     }
     writeln!(writer)?;
     // body
     for row in rows {
         for (i, element) in row.into_iter().enumerate() {
-            let _ = column_formats[i].write_element(writer, &element, &widths[i]);
+            column_formats[i].write_element(writer, &element, &widths[i], hyperlinks)?;
         }
         writeln!(writer)?;
     }
@@ -193,6 +438,7 @@ fn to_table_display<W: Write + AsRawFd, T: Rowable>(
 }
 
 //------------------------------------------------------------------------------
+#[derive(Clone)]
 pub(crate) struct ColumnFormat {
     header: String,
     ellipsisable: bool,
@@ -208,25 +454,107 @@ impl ColumnFormat {
         }
     }
 
-    // All terminal content uses this method to write output. This permits per-column formatting based on the column type, discovered via the header string.
+    pub(crate) fn get_header(&self) -> &str {
+        &self.header
+    }
+
+    // Return true if this element reports a failure, discovered via the header string: explanation, status, and note columns, as well as vulnerability identifiers, are failures if not empty.
+    pub(crate) fn is_failure(&self, message: &str) -> bool {
+        matches!(
+            self.header.as_str(),
+            "Explain" | "Status" | "Note" | "Vulnerabilities"
+        ) && !message.is_empty()
+    }
+
+    // Return true if elements of this column are quoted in delimited output, discovered via the header string: a column with a comment header (e.g. "# via fetter") holds lines of a requirements file, which are written verbatim.
+    pub(crate) fn is_quotable(&self) -> bool {
+        !self.header.starts_with('#')
+    }
+
+    // Return the URL of a hyperlink for this element, discovered via the header string: packages link to their PyPI page, and sites to their directory.
+    fn to_hyperlink(&self, message: &str) -> Option<String> {
+        match self.header.as_str() {
+            "Package" => {
+                let is_name = |s: &str| {
+                    !s.is_empty()
+                        && s.chars().all(|c| {
+                            c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')
+                        })
+                };
+                match message.rsplit_once('-') {
+                    Some((name, version))
+                        if is_name(name)
+                            && version.starts_with(|c: char| c.is_ascii_digit()) =>
+                    {
+                        Some(format!(
+                            "https://pypi.org/project/{}/{}/",
+                            name,
+                            percent_encode(version)
+                        ))
+                    }
+                    _ if is_name(message) => {
+                        Some(format!("https://pypi.org/project/{}/", message))
+                    }
+                    _ => None,
+                }
+            }
+            "Site" | "Sites" => {
+                // sites can be followed by markers, such as "(user site)"
+                let path = match message.rsplit_once(" (") {
+                    Some((path, _)) if message.ends_with(')') => path,
+                    _ => message,
+                };
+                path.starts_with('/')
+                    .then(|| format!("file://{}", percent_encode(path)))
+            }
+            _ => None,
+        }
+    }
+
+    // All terminal content uses this method to write output. This permits per-column formatting based on the column type, discovered via the header string. If `hyperlinks`, elements that are not truncated are written as OSC 8 hyperlinks, if they have a URL.
     pub(crate) fn write_element<W: Write + IsTty>(
         &self,
         writer: &mut W,
-        message: &String,
+        message: &str,
         width_format: &WidthFormat,
+        hyperlinks: bool,
     ) -> Result<(), Error> {
         let field = prepare_field(message, width_format);
+        let url = if hyperlinks && field.starts_with(message) {
+            self.to_hyperlink(message.trim())
+        } else {
+            None
+        };
+        match url {
+            Some(url) => {
+                // leading indentation and trailing padding are not part of the link
+                let start = message.len() - message.trim_start().len();
+                let end = message.trim_end().len();
+                write!(writer, "{}", &field[..start])?;
+                write!(writer, "\x1b]8;;{}\x1b\\", url)?;
+                self.write_text(writer, &field[start..end])?;
+                write!(writer, "\x1b]8;;\x1b\\{}", &field[end..])
+            }
+            None => self.write_text(writer, &field),
+        }
+    }
+
+    fn write_text<W: Write + IsTty>(
+        &self,
+        writer: &mut W,
+        field: &str,
+    ) -> Result<(), Error> {
         if self.header == "Package" {
             // split on hyphen
             let parts: Vec<&str> = field.split('-').collect();
             for (i, part) in parts.iter().enumerate() {
                 if i > 0 {
-                    write_color(writer, "#ff9900", "-");
+                    write_color(writer, "#ff9900", "-")?;
                 }
                 write!(writer, "{}", part)?;
             }
         } else if self.header == "Site" {
-            write_color(writer, "#999999", &field);
+            write_color(writer, "#999999", field)?;
         // } else if message.starts_with("#") {
         //     write_color(writer, "#999999", &field);
         } else {
@@ -236,6 +564,44 @@ impl ColumnFormat {
     }
 }
 
+//------------------------------------------------------------------------------
+/// The format of a table written to a file.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum TableFormat {
+    Delimited(char),
+    Json,
+    Markdown,
+    Html,
+}
+
+impl TableFormat {
+    /// Infer a format from the extension of a file path: `.csv`, `.tsv`, `.json`, `.md`, or `.html`.
+    pub(crate) fn from_path(file_path: &Path) -> Option<Self> {
+        let ext = file_path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "csv" => Some(TableFormat::Delimited(',')),
+            "tsv" => Some(TableFormat::Delimited('\t')),
+            "json" => Some(TableFormat::Json),
+            "md" | "markdown" => Some(TableFormat::Markdown),
+            "html" | "htm" => Some(TableFormat::Html),
+            _ => None,
+        }
+    }
+}
+
+/// Return the format inferred from the extension of `file_path`, or an error naming the supported extensions.
+pub(crate) fn to_table_format(file_path: &Path) -> io::Result<TableFormat> {
+    TableFormat::from_path(file_path).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Cannot infer an output format from {:?}; use a .csv, .tsv, .json, .md, or .html extension",
+                file_path
+            ),
+        )
+    })
+}
+
 //------------------------------------------------------------------------------
 pub(crate) trait Tableable<T: Rowable> {
     fn get_header(&self) -> Vec<ColumnFormat>;
@@ -256,4 +622,263 @@ pub(crate) trait Tableable<T: Rowable> {
         let mut handle = stdout.lock();
         to_table_display(&mut handle, self.get_header(), self.get_records())
     }
+
+    /// Write this table to a file in the provided format.
+    fn to_file_format(&self, file_path: &PathBuf, format: TableFormat) -> io::Result<()> {
+        match format {
+            TableFormat::Delimited(delimiter) => self.to_file(file_path, delimiter),
+            TableFormat::Json => self.to_json_file(file_path),
+            TableFormat::Markdown => self.to_markdown_file(file_path),
+            TableFormat::Html => self.to_html_file(file_path),
+        }
+    }
+
+    /// Write this table to a file in the format inferred from its extension.
+    fn to_output(&self, file_path: &PathBuf) -> io::Result<()> {
+        self.to_file_format(file_path, to_table_format(file_path)?)
+    }
+
+    /// Write a JSON representation of this table, by default an array of objects keyed by header labels. Implementors can override this to provide a more specialized structure.
+    fn to_json_writer<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let value = to_table_json_value(self.get_header(), self.get_records());
+        serde_json::to_writer(&mut *writer, &value)?;
+        writeln!(writer)
+    }
+
+    fn to_json_file(&self, file_path: &PathBuf) -> io::Result<()> {
+        let mut file = File::create(file_path)?;
+        self.to_json_writer(&mut file)
+    }
+
+    fn to_json_stdout(&self) -> io::Result<()> {
+        let stdout = io::stdout();
+        let mut handle = stdout.lock();
+        self.to_json_writer(&mut handle)
+    }
+
+    fn to_markdown_file(&self, file_path: &PathBuf) -> io::Result<()> {
+        let mut file = File::create(file_path)?;
+        to_table_markdown(&mut file, self.get_header(), self.get_records())
+    }
+
+    fn to_html_file(&self, file_path: &PathBuf) -> io::Result<()> {
+        let mut file = File::create(file_path)?;
+        to_table_html(&mut file, self.get_header(), self.get_records())
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Record(Vec<String>);
+
+    impl Rowable for Record {
+        fn to_rows(&self, _context: &RowableContext) -> Vec<Vec<String>> {
+            vec![self.0.clone()]
+        }
+    }
+
+    #[test]
+    fn test_markup_escape_a() {
+        assert_eq!(
+            markup_escape("urllib3<2,>=1.21; extra == \"socks\" & 'a'"),
+            "urllib3&lt;2,&gt;=1.21; extra == &quot;socks&quot; &amp; &#39;a&#39;"
+        );
+    }
+
+    #[test]
+    fn test_to_table_html_a() {
+        let column_formats = vec![
+            ColumnFormat::new("Package".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Dependency".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Explain".to_string(), false, "#666666".to_string()),
+        ];
+        let records = vec![
+            Record(vec![
+                "requests-2.31.0".to_string(),
+                "requests>=2.32".to_string(),
+                "Misdefined".to_string(),
+            ]),
+            Record(vec![
+                "numpy-2.1.2".to_string(),
+                "".to_string(),
+                "".to_string(),
+            ]),
+        ];
+        let mut buffer = Vec::new();
+        to_table_html(&mut buffer, column_formats, &records).unwrap();
+        let html = String::from_utf8(buffer).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<th>Explain</th>"));
+        assert!(html.contains("<tr class=\"failure\"><td>requests-2.31.0</td><td>requests&gt;=2.32</td><td class=\"failure\">Misdefined</td></tr>"));
+        assert!(html.contains("<tr><td>numpy-2.1.2</td><td></td><td></td></tr>"));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+
+    #[test]
+    fn test_delimited_escape_a() {
+        assert_eq!(delimited_escape("numpy", ","), "numpy");
+        assert_eq!(delimited_escape("a,b", ","), "\"a,b\"");
+        assert_eq!(delimited_escape("a,b", "\t"), "a,b");
+        assert_eq!(delimited_escape("a\tb", "\t"), "\"a\tb\"");
+        assert_eq!(
+            delimited_escape("extra == \"socks\"", ","),
+            "\"extra == \"\"socks\"\"\""
+        );
+        assert_eq!(delimited_escape("a\nb", ","), "\"a\nb\"");
+    }
+
+    #[test]
+    fn test_to_table_delimited_a() {
+        let column_formats = vec![
+            ColumnFormat::new("Package".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Explain".to_string(), false, "#666666".to_string()),
+        ];
+        let records = vec![
+            Record(vec!["numpy-2.1.2".to_string(), "".to_string()]),
+            Record(vec![
+                "requests-2.31.0".to_string(),
+                "Misdefined, \"socks\" extra".to_string(),
+            ]),
+        ];
+        let mut buffer = Vec::new();
+        to_table_delimited(&mut buffer, column_formats, &records, ",").unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "Package,Explain\nnumpy-2.1.2,\nrequests-2.31.0,\"Misdefined, \"\"socks\"\" extra\"\n"
+        );
+    }
+
+    #[test]
+    fn test_to_table_delimited_b() {
+        let column_formats = vec![ColumnFormat::new(
+            "# via fetter".to_string(),
+            false,
+            "#666666".to_string(),
+        )];
+        let records = vec![Record(vec![
+            "requests[socks]>=2.31 ; python_version >= \"3.10\"".to_string(),
+        ])];
+        let mut buffer = Vec::new();
+        to_table_delimited(&mut buffer, column_formats, &records, " ").unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "# via fetter\nrequests[socks]>=2.31 ; python_version >= \"3.10\"\n"
+        );
+    }
+
+    #[test]
+    fn test_table_format_a() {
+        assert_eq!(
+            TableFormat::from_path(Path::new("report.csv")),
+            Some(TableFormat::Delimited(','))
+        );
+        assert_eq!(
+            TableFormat::from_path(Path::new("out/report.TSV")),
+            Some(TableFormat::Delimited('\t'))
+        );
+        assert_eq!(
+            TableFormat::from_path(Path::new("report.json")),
+            Some(TableFormat::Json)
+        );
+        assert_eq!(
+            TableFormat::from_path(Path::new("report.md")),
+            Some(TableFormat::Markdown)
+        );
+        assert_eq!(
+            TableFormat::from_path(Path::new("report.html")),
+            Some(TableFormat::Html)
+        );
+        assert_eq!(TableFormat::from_path(Path::new("report.txt")), None);
+        assert_eq!(TableFormat::from_path(Path::new("report")), None);
+        assert!(to_table_format(Path::new("report")).is_err());
+    }
+
+    #[test]
+    fn test_to_table_markdown_a() {
+        let column_formats = vec![
+            ColumnFormat::new("Package".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Explain".to_string(), false, "#666666".to_string()),
+        ];
+        let records = vec![
+            Record(vec!["numpy-2.1.2".to_string(), "".to_string()]),
+            Record(vec!["requests-2.31.0".to_string(), "a|b\nc".to_string()]),
+        ];
+        let mut buffer = Vec::new();
+        to_table_markdown(&mut buffer, column_formats, &records).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "| Package | Explain |\n| --- | --- |\n| numpy-2.1.2 |  |\n| requests-2.31.0 | a\\|b<br>c |\n"
+        );
+    }
+
+    #[test]
+    fn test_to_hyperlink_a() {
+        let cf = ColumnFormat::new("Package".to_string(), false, "#666666".to_string());
+        assert_eq!(
+            cf.to_hyperlink("typing-extensions-4.12.2"),
+            Some("https://pypi.org/project/typing-extensions/4.12.2/".to_string())
+        );
+        assert_eq!(
+            cf.to_hyperlink("torch-2.4.0+cu121"),
+            Some("https://pypi.org/project/torch/2.4.0%2Bcu121/".to_string())
+        );
+        assert_eq!(
+            cf.to_hyperlink("typing-extensions"),
+            Some("https://pypi.org/project/typing-extensions/".to_string())
+        );
+        assert_eq!(cf.to_hyperlink(""), None);
+        assert_eq!(cf.to_hyperlink("requests>=2"), None);
+
+        let cf = ColumnFormat::new("Sites".to_string(), true, "#666666".to_string());
+        assert_eq!(
+            cf.to_hyperlink("/home/a b/.local/lib/python3.12/site-packages (user site)"),
+            Some("file:///home/a%20b/.local/lib/python3.12/site-packages".to_string())
+        );
+        assert_eq!(
+            cf.to_hyperlink("/usr/lib/python3/dist-packages"),
+            Some("file:///usr/lib/python3/dist-packages".to_string())
+        );
+        assert_eq!(cf.to_hyperlink("~/site-packages"), None);
+
+        let cf = ColumnFormat::new("Explain".to_string(), false, "#666666".to_string());
+        assert_eq!(cf.to_hyperlink("Missing"), None);
+    }
+
+    #[test]
+    fn test_prepare_field_a() {
+        let widths = WidthFormat {
+            width_chars: 8,
+            width_pad: 10,
+        };
+        assert_eq!(prepare_field("numpy", &widths), "numpy     ");
+        assert_eq!(
+            prepare_field("/home/über/site-packages", &widths),
+            "/home...  "
+        );
+        // each CJK character occupies two columns
+        assert_eq!(prepare_field("日本語", &widths), "日本語    ");
+        assert_eq!(prepare_field("日本語のパッケージ", &widths), "日本...   ");
+        assert_eq!(prepare_field("éééééééééééé", &widths), "ééééé...  ");
+    }
+
+    #[test]
+    fn test_truncate_width_a() {
+        assert_eq!(truncate_width("abc", 5), "abc");
+        assert_eq!(truncate_width("日本語", 3), "日");
+        assert_eq!(truncate_width("日本語", 4), "日本");
+        // combining marks are retained with their base character
+        assert_eq!(truncate_width("e\u{301}e\u{301}e", 2), "e\u{301}e\u{301}");
+    }
+
+    #[test]
+    fn test_env_is_set_a() {
+        assert!(!env_is_set(None, false));
+        assert!(!env_is_set(Some(OsStr::new("")), false));
+        assert!(env_is_set(Some(OsStr::new("0")), false));
+        assert!(!env_is_set(Some(OsStr::new("0")), true));
+        assert!(env_is_set(Some(OsStr::new("1")), true));
+    }
 }
@@ -0,0 +1,286 @@
+use std::cmp::Ordering;
+
+use crate::table::ColumnFormat;
+use crate::table::Rowable;
+use crate::table::RowableContext;
+use crate::table::Tableable;
+use crate::util::ResultDynError;
+use crate::version_spec::VersionSpec;
+
+// Return the key used to select a column: the lower-case header label with spaces replaced by hyphens.
+fn column_key(header: &str) -> String {
+    header.to_lowercase().replace(' ', "-")
+}
+
+// Split a package display, as `name-version`, into its name and version; versions never contain hyphens.
+fn split_package(value: &str) -> (String, String) {
+    match value.rsplit_once('-') {
+        Some((name, version)) => (name.to_string(), version.to_string()),
+        None => (value.to_string(), "".to_string()),
+    }
+}
+
+// Compare values of a column; versions are compared as versions, and other values case-insensitively.
+fn cmp_values(key: &str, a: &str, b: &str) -> Ordering {
+    if key == "version" && !a.is_empty() && !b.is_empty() {
+        VersionSpec::new(a).cmp(&VersionSpec::new(b))
+    } else {
+        a.to_lowercase().cmp(&b.to_lowercase())
+    }
+}
+
+// Parse a sort as `column`, `column:asc`, or `column:desc`, returning the column key and if descending.
+fn parse_sort(sort: &str) -> ResultDynError<(String, bool)> {
+    let (key, direction) = sort.split_once(':').unwrap_or((sort, "asc"));
+    let descending = match direction.to_lowercase().as_str() {
+        "asc" => false,
+        "desc" => true,
+        _ => {
+            return Err(
+                format!("Invalid sort direction: {}; use asc or desc", direction).into(),
+            )
+        }
+    };
+    Ok((column_key(key.trim()), descending))
+}
+
+//------------------------------------------------------------------------------
+/// The rows of one record of a report, as displayed in the terminal and as written to delimited files.
+pub(crate) struct ViewRecord {
+    tty: Vec<Vec<String>>,
+    delimited: Vec<Vec<String>>,
+}
+
+impl ViewRecord {
+    fn select(&self, indices: &[usize]) -> Self {
+        let select = |rows: &Vec<Vec<String>>| {
+            rows.iter()
+                .map(|row| indices.iter().map(|i| row[*i].clone()).collect())
+                .collect()
+        };
+        ViewRecord {
+            tty: select(&self.tty),
+            delimited: select(&self.delimited),
+        }
+    }
+}
+
+impl Rowable for ViewRecord {
+    fn to_rows(&self, context: &RowableContext) -> Vec<Vec<String>> {
+        match context {
+            RowableContext::Tty => self.tty.clone(),
+            RowableContext::Delimited => self.delimited.clone(),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+/// A report with selected columns and sorted records. Columns are selected by the keys of their headers (e.g. `package`, `site`, or `user-site`); if a report has a Package column, the `name` and `version` of each package are also available.
+pub(crate) struct TableView {
+    header: Vec<ColumnFormat>,
+    records: Vec<ViewRecord>,
+}
+
+impl TableView {
+    pub(crate) fn from_report<R: Rowable, T: Tableable<R>>(
+        report: &T,
+        columns: Option<&[String]>,
+        sort: Option<&str>,
+    ) -> ResultDynError<Self> {
+        let mut header = report.get_header();
+        let mut records: Vec<ViewRecord> = report
+            .get_records()
+            .iter()
+            .map(|record| ViewRecord {
+                tty: record.to_rows(&RowableContext::Tty),
+                delimited: record.to_rows(&RowableContext::Delimited),
+            })
+            .collect();
+        let sort = sort.map(parse_sort).transpose()?;
+        let mut keys: Vec<String> = header
+            .iter()
+            .map(|cf| column_key(cf.get_header()))
+            .collect();
+
+        // name and version are derived from the Package column only if requested
+        let requested: Vec<String> = columns
+            .unwrap_or_default()
+            .iter()
+            .map(|c| column_key(c.trim()))
+            .chain(sort.iter().map(|(key, _)| key.clone()))
+            .collect();
+        if let Some(pos) = keys.iter().position(|k| k == "package") {
+            for (i, label) in ["Name", "Version"].iter().enumerate() {
+                let key = column_key(label);
+                if !requested.contains(&key) || keys.contains(&key) {
+                    continue;
+                }
+                for record in records.iter_mut() {
+                    for row in record.tty.iter_mut().chain(record.delimited.iter_mut()) {
+                        let (name, version) = split_package(&row[pos]);
+                        row.push(if i == 0 { name } else { version });
+                    }
+                }
+                header.push(ColumnFormat::new(
+                    label.to_string(),
+                    false,
+                    "#666666".to_string(),
+                ));
+                keys.push(key);
+            }
+        }
+        let position = |key: &String| {
+            keys.iter().position(|k| k == key).ok_or_else(|| {
+                format!("Unknown column: {}; use one of: {}", key, keys.join(", "))
+            })
+        };
+        if let Some((key, descending)) = &sort {
+            let i = position(key)?;
+            let value = |r: &ViewRecord| {
+                r.delimited
+                    .first()
+                    .map(|row| row[i].clone())
+                    .unwrap_or_default()
+            };
+            records.sort_by(|a, b| {
+                let ordering = cmp_values(key, &value(a), &value(b));
+                if *descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+        }
+        if let Some(columns) = columns {
+            let indices = columns
+                .iter()
+                .map(|c| position(&column_key(c.trim())))
+                .collect::<Result<Vec<usize>, String>>()?;
+            header = indices.iter().map(|i| header[*i].clone()).collect();
+            records = records.iter().map(|r| r.select(&indices)).collect();
+        }
+        Ok(TableView { header, records })
+    }
+}
+
+impl Tableable<ViewRecord> for TableView {
+    fn get_header(&self) -> Vec<ColumnFormat> {
+        self.header.clone()
+    }
+    fn get_records(&self) -> &Vec<ViewRecord> {
+        &self.records
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Record(Vec<String>);
+
+    impl Rowable for Record {
+        fn to_rows(&self, _context: &RowableContext) -> Vec<Vec<String>> {
+            vec![self.0.clone()]
+        }
+    }
+
+    struct Report(Vec<Record>);
+
+    impl Tableable<Record> for Report {
+        fn get_header(&self) -> Vec<ColumnFormat> {
+            vec![
+                ColumnFormat::new("Package".to_string(), false, "#666666".to_string()),
+                ColumnFormat::new("User Site".to_string(), false, "#666666".to_string()),
+            ]
+        }
+        fn get_records(&self) -> &Vec<Record> {
+            &self.0
+        }
+    }
+
+    fn to_report() -> Report {
+        Report(vec![
+            Record(vec!["numpy-2.1.2".to_string(), "".to_string()]),
+            Record(vec![
+                "typing-extensions-4.12.2".to_string(),
+                "yes".to_string(),
+            ]),
+            Record(vec!["Django-10.0".to_string(), "".to_string()]),
+        ])
+    }
+
+    fn to_rows(tv: &TableView) -> Vec<Vec<String>> {
+        tv.get_records()
+            .iter()
+            .flat_map(|r| r.to_rows(&RowableContext::Delimited))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_sort_a() {
+        assert_eq!(
+            parse_sort("version").unwrap(),
+            ("version".to_string(), false)
+        );
+        assert_eq!(
+            parse_sort("User Site:DESC").unwrap(),
+            ("user-site".to_string(), true)
+        );
+        assert!(parse_sort("version:up").is_err());
+    }
+
+    #[test]
+    fn test_table_view_a() {
+        let columns = vec!["version".to_string(), "name".to_string()];
+        let tv = TableView::from_report(
+            &to_report(),
+            Some(columns.as_slice()),
+            Some("version:desc"),
+        )
+        .unwrap();
+        let header: Vec<String> = tv
+            .get_header()
+            .iter()
+            .map(|cf| cf.get_header().to_string())
+            .collect();
+        assert_eq!(header, vec!["Version", "Name"]);
+        assert_eq!(
+            to_rows(&tv),
+            vec![
+                vec!["10.0", "Django"],
+                vec!["4.12.2", "typing-extensions"],
+                vec!["2.1.2", "numpy"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_table_view_b() {
+        let tv = TableView::from_report(&to_report(), None, Some("package")).unwrap();
+        assert_eq!(
+            to_rows(&tv),
+            vec![
+                vec!["Django-10.0", ""],
+                vec!["numpy-2.1.2", ""],
+                vec!["typing-extensions-4.12.2", "yes"],
+            ]
+        );
+        let columns = vec!["user-site".to_string()];
+        let tv =
+            TableView::from_report(&to_report(), Some(columns.as_slice()), None).unwrap();
+        assert_eq!(to_rows(&tv), vec![vec![""], vec!["yes"], vec![""]]);
+    }
+
+    #[test]
+    fn test_table_view_c() {
+        let columns = vec!["license".to_string()];
+        let e = TableView::from_report(&to_report(), Some(columns.as_slice()), None)
+            .err()
+            .unwrap();
+        assert_eq!(
+            e.to_string(),
+            "Unknown column: license; use one of: package, user-site"
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use std::fmt;
+
+use crate::dep_graph::DepGraph;
+use crate::dep_spec::DepSpec;
+use crate::package::Package;
+use crate::table::ColumnFormat;
+use crate::table::Rowable;
+use crate::table::RowableContext;
+use crate::table::Tableable;
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TreeNote {
+    Cycle,
+    Missing,
+}
+
+impl fmt::Display for TreeNote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            TreeNote::Cycle => "Cycle", // required by a package that it requires
+            TreeNote::Missing => "Missing", // required, not found
+        };
+        write!(f, "{}", value)
+    }
+}
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone)]
+pub(crate) struct TreeRecord {
+    depth: usize,
+    package: Option<Package>,
+    dep_spec: Option<DepSpec>,
+    note: Option<TreeNote>,
+}
+
+impl Rowable for TreeRecord {
+    fn to_rows(&self, context: &RowableContext) -> Vec<Vec<String>> {
+        let is_tty = *context == RowableContext::Tty;
+        let pkg_display = match &self.package {
+            Some(package) => package.to_string(),
+            None => "".to_string(),
+        };
+        let pkg_display = if is_tty {
+            format!("{}{}", "  ".repeat(self.depth), pkg_display)
+        } else {
+            pkg_display
+        };
+        vec![vec![
+            self.depth.to_string(),
+            pkg_display,
+            self.dep_spec
+                .as_ref()
+                .map(|ds| ds.to_string())
+                .unwrap_or_default(),
+            self.note.map(|n| n.to_string()).unwrap_or_default(),
+        ]]
+    }
+}
+
+//------------------------------------------------------------------------------
+/// A report of the installed dependency tree of one or more packages, in depth-first order. A package that requires a package already in its path is reported as a cycle and not expanded.
+pub(crate) struct TreeReport {
+    records: Vec<TreeRecord>,
+}
+
+impl TreeReport {
+    /// Given a DepGraph, return the tree of each root package. If no roots are provided, all packages that are not required by other packages are roots; packages only reachable through cycles are added as roots. If `depth` is provided, requirements are not expanded beyond that depth.
+    pub(crate) fn from_dep_graph(
+        dg: &DepGraph,
+        roots: Option<&[Package]>,
+        depth: Option<usize>,
+    ) -> Self {
+        let roots: Vec<&Package> = match roots {
+            Some(roots) => roots.iter().collect(),
+            None => {
+                let mut roots: Vec<&Package> = dg
+                    .get_packages()
+                    .into_iter()
+                    .filter(|p| dg.get_dependents(p).is_empty())
+                    .collect();
+                let reachable = dg.get_reachable(&roots);
+                let unreached: Vec<&Package> = dg
+                    .get_packages()
+                    .into_iter()
+                    .filter(|p| !reachable.contains(p))
+                    .collect();
+                // add the first unreached package of each cycle
+                let mut reached = reachable;
+                for package in unreached {
+                    if !reached.contains(package) {
+                        reached.extend(dg.get_reachable(&[package]));
+                        roots.push(package);
+                    }
+                }
+                roots.sort();
+                roots
+            }
+        };
+        let mut records = Vec::new();
+        for root in roots {
+            let mut path: Vec<&Package> = Vec::new();
+            Self::push_records(dg, root, None, 0, depth, &mut path, &mut records);
+        }
+        TreeReport { records }
+    }
+
+    fn push_records<'a>(
+        dg: &'a DepGraph,
+        package: &'a Package,
+        dep_spec: Option<&DepSpec>,
+        level: usize,
+        depth: Option<usize>,
+        path: &mut Vec<&'a Package>,
+        records: &mut Vec<TreeRecord>,
+    ) {
+        let is_cycle = path.contains(&package);
+        records.push(TreeRecord {
+            depth: level,
+            package: Some(package.clone()),
+            dep_spec: dep_spec.cloned(),
+            note: if is_cycle {
+                Some(TreeNote::Cycle)
+            } else {
+                None
+            },
+        });
+        if is_cycle || depth.is_some_and(|d| level >= d) {
+            return;
+        }
+        path.push(package);
+        for edge in dg.get_edges(package) {
+            if edge.packages.is_empty() {
+                records.push(TreeRecord {
+                    depth: level + 1,
+                    package: None,
+                    dep_spec: Some(edge.dep_spec.clone()),
+                    note: Some(TreeNote::Missing),
+                });
+            }
+            for required in edge.packages.iter() {
+                Self::push_records(
+                    dg,
+                    required,
+                    Some(&edge.dep_spec),
+                    level + 1,
+                    depth,
+                    path,
+                    records,
+                );
+            }
+        }
+        path.pop();
+    }
+}
+
+impl Tableable<TreeRecord> for TreeReport {
+    fn get_header(&self) -> Vec<ColumnFormat> {
+        vec![
+            ColumnFormat::new("Depth".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Package".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Requirement".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Note".to_string(), false, "#666666".to_string()),
+        ]
+    }
+    fn get_records(&self) -> &Vec<TreeRecord> {
+        &self.records
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path_shared::PathShared;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn to_dep_graph(site: &Path, packages: &[(&str, &[&str])]) -> DepGraph {
+        let mut package_to_sites = HashMap::new();
+        let site_shared = PathShared::from_path_buf(site.to_path_buf());
+        for (name, requires) in packages {
+            let fp_di = site.join(format!("{}-1.0.dist-info", name));
+            fs::create_dir(&fp_di).unwrap();
+            let mut content = format!("Name: {}\nVersion: 1.0\n", name);
+            for r in requires.iter() {
+                content.push_str(&format!("Requires-Dist: {}\n", r));
+            }
+            fs::write(fp_di.join("METADATA"), content).unwrap();
+            let package = Package::from_name_version_durl(name, "1.0", None).unwrap();
+            package_to_sites.insert(package, vec![site_shared.clone()]);
+        }
+        DepGraph::from_package_to_sites(&package_to_sites, &HashMap::new())
+    }
+
+    fn to_lines(tr: &TreeReport) -> Vec<String> {
+        tr.records
+            .iter()
+            .flat_map(|r| r.to_rows(&RowableContext::Delimited))
+            .map(|row| row.join("|"))
+            .collect()
+    }
+
+    #[test]
+    fn test_tree_report_a() {
+        let dir = tempdir().unwrap();
+        let dg = to_dep_graph(
+            dir.path(),
+            &[("a", &["b>=1", "c"]), ("b", &["d"]), ("d", &[]), ("e", &[])],
+        );
+        let tr = TreeReport::from_dep_graph(&dg, None, None);
+        assert_eq!(
+            to_lines(&tr),
+            vec![
+                "0|a-1.0||",
+                "1|b-1.0|b>=1|",
+                "2|d-1.0|d|",
+                "1||c|Missing",
+                "0|e-1.0||",
+            ]
+        );
+        // depth limits expansion
+        let tr = TreeReport::from_dep_graph(&dg, None, Some(1));
+        assert_eq!(
+            to_lines(&tr),
+            vec!["0|a-1.0||", "1|b-1.0|b>=1|", "1||c|Missing", "0|e-1.0||"]
+        );
+    }
+
+    #[test]
+    fn test_tree_report_b() {
+        let dir = tempdir().unwrap();
+        let dg = to_dep_graph(dir.path(), &[("a", &["b"]), ("b", &["a"])]);
+        // packages only in cycles are still reported
+        let tr = TreeReport::from_dep_graph(&dg, None, None);
+        assert_eq!(
+            to_lines(&tr),
+            vec!["0|a-1.0||", "1|b-1.0|b|", "2|a-1.0|a|Cycle"]
+        );
+        let roots = vec![Package::from_name_version_durl("b", "1.0", None).unwrap()];
+        let tr = TreeReport::from_dep_graph(&dg, Some(&roots), None);
+        assert_eq!(
+            to_lines(&tr),
+            vec!["0|b-1.0||", "1|a-1.0|a|", "2|b-1.0|b|Cycle"]
+        );
+    }
+}
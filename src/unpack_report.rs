@@ -3,28 +3,70 @@ use std::fs;
 use std::io;
 use std::io::BufRead;
 use std::marker::Send;
+use std::path::Path;
 use std::path::PathBuf;
 
 use rayon::prelude::*;
+use tracing::info;
+use tracing::warn;
 
+use crate::conda_meta::is_conda_meta;
 use crate::package::Package;
 use crate::path_shared::PathShared;
 use crate::table::ColumnFormat;
 use crate::table::Rowable;
 use crate::table::RowableContext;
+use crate::table::TableFormat;
 use crate::table::Tableable;
 use crate::util::ResultDynError;
 
 //------------------------------------------------------------------------------
+/// A file listed in a RECORD file, with the hash and size recorded for it, if any.
+#[derive(Debug, Clone)]
+pub(crate) struct ArtifactFile {
+    pub(crate) path: PathBuf,
+    pub(crate) exists: bool,
+    pub(crate) hash: Option<String>,
+    pub(crate) size: Option<u64>,
+}
+
+impl ArtifactFile {
+    /// Given a line of a RECORD file and the site its paths are relative to, return an ArtifactFile. If the RECORD does not give a size, the size of an existing file is read.
+    fn from_record_line(line: &str, dir_site: &Path) -> Option<Self> {
+        let mut parts = line.split(',');
+        let path = dir_site.join(parts.next()?);
+        let exists = path.exists();
+        let hash = parts
+            .next()
+            .map(|h| h.trim())
+            .filter(|h| !h.is_empty())
+            .map(|h| h.to_string());
+        let size = match parts.next().and_then(|s| s.trim().parse::<u64>().ok()) {
+            Some(size) => Some(size),
+            None if exists => fs::metadata(&path).ok().map(|md| md.len()),
+            None => None,
+        };
+        Some(ArtifactFile {
+            path,
+            exists,
+            hash,
+            size,
+        })
+    }
+}
+
 /// This contains the explicit files found in a RECORD file, as well as all discovered directories that contain one or more of those file.
 #[derive(Debug, Clone)]
-struct Artifacts {
-    files: Vec<(PathBuf, bool)>,
+pub(crate) struct Artifacts {
+    pub(crate) files: Vec<ArtifactFile>,
     dirs: Vec<PathBuf>,
 }
 
 impl Artifacts {
-    fn from_package(package: &Package, site: &PathShared) -> ResultDynError<Self> {
+    pub(crate) fn from_package(
+        package: &Package,
+        site: &PathShared,
+    ) -> ResultDynError<Self> {
         let dir_dist_info = package
             .to_dist_info_dir(site)
             .ok_or("Cannot find dist-info dir")?;
@@ -43,15 +85,8 @@ impl Artifacts {
             if line.trim().is_empty() {
                 continue;
             }
-            if let Some(fp_rel) = line.split(',').next() {
-                let fp = dir_site.join(fp_rel);
-                let exists = fp.exists();
-                files.push((fp.to_path_buf(), exists));
-                // if exists {
-                //     if let Some(dir) = fp.parent() {
-                //         dirs_observed.insert(dir.to_path_buf());
-                //     }
-                // }
+            if let Some(af) = ArtifactFile::from_record_line(&line, dir_site) {
+                files.push(af);
             }
         }
         let mut dirs = Vec::new();
@@ -64,20 +99,21 @@ impl Artifacts {
     }
 
     fn remove(&self, log: bool) -> io::Result<()> {
-        for (fp, exists) in &self.files {
-            if *exists {
+        for af in &self.files {
+            if af.exists {
+                let fp = &af.path;
                 if let Err(e) = fs::remove_file(fp) {
-                    eprintln!("Failed to remove file {:?}: {}", fp, e);
+                    warn!("Failed to remove file {:?}: {}", fp, e);
                 } else if log {
-                    eprintln!("Removing file: {:?}", fp);
+                    info!("Removing file: {:?}", fp);
                 }
             }
         }
         for dir in &self.dirs {
             if let Err(e) = fs::remove_dir_all(dir) {
-                eprintln!("Failed to remove directory {:?}: {}", dir, e);
+                warn!("Failed to remove directory {:?}: {}", dir, e);
             } else if log {
-                eprintln!("Removing directory: {:?}", dir);
+                info!("Removing directory: {:?}", dir);
             }
         }
         Ok(())
@@ -133,12 +169,12 @@ impl Rowable for UnpackFullRecord {
         };
 
         let mut rows: Vec<Vec<String>> = Vec::new();
-        for (fp, exists) in &self.artifacts.files {
+        for af in &self.artifacts.files {
             rows.push(vec![
                 package_display(),
                 site_display(),
-                exists.to_string(),
-                fp.display().to_string(),
+                af.exists.to_string(),
+                af.path.display().to_string(),
             ]);
         }
 
@@ -183,6 +219,49 @@ impl Rowable for UnpackCountRecord {
     }
 }
 
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone)]
+pub(crate) struct UnpackFilesRecord {
+    package: Package,
+    site: PathShared,
+    artifacts: Artifacts,
+}
+
+impl UnpackRecordTrait for UnpackFilesRecord {
+    fn new(package: Package, site: PathShared, artifacts: Artifacts) -> Self {
+        UnpackFilesRecord {
+            package,
+            site,
+            artifacts,
+        }
+    }
+}
+
+impl Rowable for UnpackFilesRecord {
+    fn to_rows(&self, context: &RowableContext) -> Vec<Vec<String>> {
+        let is_tty = *context == RowableContext::Tty;
+        self.artifacts
+            .files
+            .iter()
+            .enumerate()
+            .map(|(i, af)| {
+                let (package_display, site_display) = if is_tty && i > 0 {
+                    ("".to_string(), "".to_string())
+                } else {
+                    (self.package.to_string(), self.site.to_string())
+                };
+                vec![
+                    package_display,
+                    site_display,
+                    af.size.map(|s| s.to_string()).unwrap_or_default(),
+                    af.hash.clone().unwrap_or_default(),
+                    af.path.display().to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
 //------------------------------------------------------------------------------
 /// Generic function to covert a `HashMap` to a `Vec` of of UnpackRecords.
 fn package_to_sites_to_records<R>(
@@ -195,10 +274,14 @@ where
         .par_iter()
         .flat_map(|(package, sites)| {
             sites.par_iter().filter_map(move |site| {
+                // conda-meta sites only attribute Packages; artifacts are found in site packages
+                if is_conda_meta(site.as_path()) {
+                    return None;
+                }
                 if let Ok(artifacts) = Artifacts::from_package(package, site) {
                     Some(R::new(package.clone(), site.clone(), artifacts))
                 } else {
-                    eprintln!("Failed to read artifacts: {:?}", package);
+                    warn!("Failed to read artifacts: {:?}", package);
                     None
                 }
             })
@@ -225,6 +308,40 @@ impl Tableable<UnpackFullRecord> for UnpackFullReport {
     }
 }
 
+//------------------------------------------------------------------------------
+/// A report of every file recorded in the RECORD file of each package, with its size and hash.
+pub(crate) struct UnpackFilesReport {
+    records: Vec<UnpackFilesRecord>,
+}
+
+impl UnpackFilesReport {
+    pub(crate) fn from_package_to_sites(
+        package_to_sites: &HashMap<Package, Vec<PathShared>>,
+    ) -> Self {
+        let mut records: Vec<UnpackFilesRecord> =
+            package_to_sites_to_records(package_to_sites);
+        records.sort_by(|a, b| {
+            (&a.package, a.site.as_path()).cmp(&(&b.package, b.site.as_path()))
+        });
+        UnpackFilesReport { records }
+    }
+}
+
+impl Tableable<UnpackFilesRecord> for UnpackFilesReport {
+    fn get_header(&self) -> Vec<ColumnFormat> {
+        vec![
+            ColumnFormat::new("Package".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Site".to_string(), true, "#666666".to_string()),
+            ColumnFormat::new("Size".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Hash".to_string(), true, "#666666".to_string()),
+            ColumnFormat::new("File".to_string(), true, "#666666".to_string()),
+        ]
+    }
+    fn get_records(&self) -> &Vec<UnpackFilesRecord> {
+        &self.records
+    }
+}
+
 //------------------------------------------------------------------------------
 pub(crate) struct UnpackCountReport {
     records: Vec<UnpackCountRecord>,
@@ -263,6 +380,14 @@ impl UnpackReport {
         }
     }
 
+    /// Return the number of package and site records.
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            UnpackReport::Full(report) => report.records.len(),
+            UnpackReport::Count(report) => report.records.len(),
+        }
+    }
+
     pub(crate) fn to_stdout(&self) -> io::Result<()> {
         match self {
             UnpackReport::Full(report) => report.to_stdout(),
@@ -270,10 +395,28 @@ impl UnpackReport {
         }
     }
 
-    pub(crate) fn to_file(&self, file_path: &PathBuf, delimiter: char) -> io::Result<()> {
+    pub(crate) fn to_file_format(
+        &self,
+        file_path: &PathBuf,
+        format: TableFormat,
+    ) -> io::Result<()> {
         match self {
-            UnpackReport::Full(report) => report.to_file(file_path, delimiter),
-            UnpackReport::Count(report) => report.to_file(file_path, delimiter),
+            UnpackReport::Full(report) => report.to_file_format(file_path, format),
+            UnpackReport::Count(report) => report.to_file_format(file_path, format),
+        }
+    }
+
+    pub(crate) fn to_output(&self, file_path: &PathBuf) -> io::Result<()> {
+        match self {
+            UnpackReport::Full(report) => report.to_output(file_path),
+            UnpackReport::Count(report) => report.to_output(file_path),
+        }
+    }
+
+    pub(crate) fn to_json_stdout(&self) -> io::Result<()> {
+        match self {
+            UnpackReport::Full(report) => report.to_json_stdout(),
+            UnpackReport::Count(report) => report.to_json_stdout(),
         }
     }
 
@@ -296,6 +439,7 @@ impl UnpackReport {
 
 //------------------------------------------------------------------------------
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
     use std::fs;
@@ -380,4 +524,64 @@ xarray/util/print_versions.py,sha256=kSqlh0crnpEzanhYmV3F7RuGEys8nrOhM_Yf_i7D7bM
         assert_eq!(rc.files.len(), 59);
         assert_eq!(rc.dirs.len(), 1);
     }
+
+    #[test]
+    fn test_artifact_file_a() {
+        let dir_temp = tempdir().unwrap();
+        let af1 = ArtifactFile::from_record_line(
+            "xarray/convert.py,sha256=E2Rocp9OeVll4le8WtqQWnlGVAZ7hhmXqtnDnL1G1Vk,9643",
+            dir_temp.path(),
+        )
+        .unwrap();
+        assert_eq!(af1.path, dir_temp.path().join("xarray/convert.py"));
+        assert_eq!(af1.exists, false);
+        assert_eq!(
+            af1.hash,
+            Some("sha256=E2Rocp9OeVll4le8WtqQWnlGVAZ7hhmXqtnDnL1G1Vk".to_string())
+        );
+        assert_eq!(af1.size, Some(9643));
+
+        // sizes not recorded are read from existing files
+        let fp = dir_temp.path().join("foo.pyc");
+        fs::write(&fp, "12345").unwrap();
+        let af2 = ArtifactFile::from_record_line("foo.pyc,,", dir_temp.path()).unwrap();
+        assert_eq!(af2.exists, true);
+        assert_eq!(af2.hash, None);
+        assert_eq!(af2.size, Some(5));
+    }
+
+    #[test]
+    fn test_unpack_files_report_a() {
+        let pkg = Package::from_dist_info("foo-1.0.dist-info", None, None).unwrap();
+        let dir_temp = tempdir().unwrap();
+        let site = PathShared::from_path_buf(dir_temp.path().to_path_buf());
+        let dir_dist_info = dir_temp.path().join("foo-1.0.dist-info");
+        fs::create_dir(&dir_dist_info).unwrap();
+        fs::write(
+            dir_dist_info.join("RECORD"),
+            "foo-1.0.dist-info/METADATA,sha256=abc,120\nfoo-1.0.dist-info/RECORD,,\n",
+        )
+        .unwrap();
+        let mut package_to_sites = HashMap::new();
+        package_to_sites.insert(pkg, vec![site.clone()]);
+        let ufr = UnpackFilesReport::from_package_to_sites(&package_to_sites);
+
+        let fp = dir_temp.path().join("report.txt");
+        ufr.to_file(&fp, '|').unwrap();
+        let lines: Vec<String> = fs::read_to_string(&fp)
+            .unwrap()
+            .lines()
+            .map(|l| l.to_string())
+            .collect();
+        assert_eq!(lines[0], "Package|Site|Size|Hash|File");
+        assert_eq!(
+            lines[1],
+            format!(
+                "foo-1.0|{}|120|sha256=abc|{}",
+                site,
+                dir_dist_info.join("METADATA").display()
+            )
+        );
+        assert_eq!(lines.len(), 3);
+    }
 }
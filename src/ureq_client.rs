@@ -5,6 +5,15 @@ pub trait UreqClient {
     fn post(&self, url: &str, body: &str) -> Result<String, ureq::Error>;
     /// A get request
     fn get(&self, url: &str) -> Result<String, ureq::Error>;
+    /// A get request with the provided headers, as name and value pairs.
+    fn get_with_headers(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+    ) -> Result<String, ureq::Error> {
+        let _ = headers;
+        self.get(url)
+    }
 }
 
 pub struct UreqClientLive;
@@ -20,8 +29,20 @@ impl UreqClient for UreqClientLive {
         let response = ureq::get(url).call()?;
         Ok(response.into_string()?)
     }
+    fn get_with_headers(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+    ) -> Result<String, ureq::Error> {
+        let mut request = ureq::get(url);
+        for (name, value) in headers {
+            request = request.set(name, value);
+        }
+        Ok(request.call()?.into_string()?)
+    }
 }
 
+#[allow(dead_code)]
 pub struct UreqClientMock {
     pub mock_post: Option<String>,
     pub mock_get: Option<String>,
@@ -1,20 +1,47 @@
 use serde::{Deserialize, Serialize};
-// use std::cmp;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
 
+use crate::count_report::CountRecord;
+use crate::count_report::CountReport;
 use crate::dep_spec::DepSpec;
 use crate::package::Package;
 use crate::path_shared::PathShared;
+use crate::sarif::locate_requirement;
+use crate::sarif::read_bounds;
+use crate::sarif::SarifFinding;
+use crate::sarif::SarifLog;
+use crate::table::markup_escape;
 use crate::table::ColumnFormat;
 use crate::table::Rowable;
 use crate::table::RowableContext;
 use crate::table::Tableable;
+use crate::waiver::Waivers;
 
 //------------------------------------------------------------------------------
-enum ValidationExplain {
+/// The explanation of a validation record, with a stable, machine-readable code and a category.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValidationExplain {
     Missing,
     Unrequired,
     Misdefined,
+    HashMismatch,
+    HashUnverifiable,
+    Drifted,
+    Editable,
+    Orphan,
+    LicenseDenied,
+    Banned,
+    PythonIncompatible,
+    IncompatibleBinary,
+    Shadowed,
+    InstallerDenied,
     Undefined,
 }
 
@@ -24,17 +51,207 @@ impl fmt::Display for ValidationExplain {
             ValidationExplain::Missing => "Missing", // not found
             ValidationExplain::Unrequired => "Unrequired", // found, not specified
             ValidationExplain::Misdefined => "Misdefined", // found, not matched version
+            ValidationExplain::HashMismatch => "HashMismatch", // found, not matched hash
+            ValidationExplain::HashUnverifiable => "HashUnverifiable", // found, no recorded hash
+            ValidationExplain::Drifted => "Drifted", // found, requested revision has moved
+            ValidationExplain::Editable => "Editable", // found, installed in editable mode
+            ValidationExplain::Orphan => "Orphan", // found, not specified or required by a specified package
+            ValidationExplain::LicenseDenied => "LicenseDenied", // found, license not permitted
+            ValidationExplain::Banned => "Banned", // found, forbidden by policy
+            ValidationExplain::PythonIncompatible => "PythonIncompatible", // found, Requires-Python not satisfied
+            ValidationExplain::IncompatibleBinary => "IncompatibleBinary", // found, wheel tags not compatible
+            ValidationExplain::Shadowed => "Shadowed", // found, not importable due to a prior site
+            ValidationExplain::InstallerDenied => "InstallerDenied", // found, installed by a tool not required
             ValidationExplain::Undefined => "Undefined",
         };
         write!(f, "{}", value)
     }
 }
 
+impl ValidationExplain {
+    const ALL: [ValidationExplain; 15] = [
+        ValidationExplain::Missing,
+        ValidationExplain::Unrequired,
+        ValidationExplain::Misdefined,
+        ValidationExplain::HashMismatch,
+        ValidationExplain::HashUnverifiable,
+        ValidationExplain::Drifted,
+        ValidationExplain::Editable,
+        ValidationExplain::Orphan,
+        ValidationExplain::LicenseDenied,
+        ValidationExplain::Banned,
+        ValidationExplain::PythonIncompatible,
+        ValidationExplain::IncompatibleBinary,
+        ValidationExplain::Shadowed,
+        ValidationExplain::InstallerDenied,
+        ValidationExplain::Undefined,
+    ];
+
+    /// Return the stable code of this explanation; codes are never reused.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValidationExplain::Undefined => "V000",
+            ValidationExplain::Missing => "V001",
+            ValidationExplain::Unrequired => "V002",
+            ValidationExplain::Misdefined => "V003",
+            ValidationExplain::HashMismatch => "V004",
+            ValidationExplain::Drifted => "V005",
+            ValidationExplain::Editable => "V006",
+            ValidationExplain::Orphan => "V007",
+            ValidationExplain::LicenseDenied => "V008",
+            ValidationExplain::HashUnverifiable => "V009",
+            ValidationExplain::Banned => "V010",
+            ValidationExplain::PythonIncompatible => "V011",
+            ValidationExplain::IncompatibleBinary => "V012",
+            ValidationExplain::Shadowed => "V013",
+            ValidationExplain::InstallerDenied => "V014",
+        }
+    }
+
+    /// Return the category of this explanation: `requirement` for packages that do not satisfy the bound requirements, `inventory` for packages that are not required, `integrity` for packages that do not match their requested artifact or revision, `provenance` for how, and by what tool, packages were installed, and `license` for license policies, and `policy` for packages forbidden by a ban policy, and `compatibility` for packages that do not support the Python or platform that installed them.
+    pub fn category(&self) -> &'static str {
+        match self {
+            ValidationExplain::Missing | ValidationExplain::Misdefined => "requirement",
+            ValidationExplain::Unrequired
+            | ValidationExplain::Orphan
+            | ValidationExplain::Shadowed => "inventory",
+            ValidationExplain::HashMismatch
+            | ValidationExplain::HashUnverifiable
+            | ValidationExplain::Drifted => "integrity",
+            ValidationExplain::Editable | ValidationExplain::InstallerDenied => {
+                "provenance"
+            }
+            ValidationExplain::LicenseDenied => "license",
+            ValidationExplain::Banned => "policy",
+            ValidationExplain::PythonIncompatible
+            | ValidationExplain::IncompatibleBinary => "compatibility",
+            ValidationExplain::Undefined => "undefined",
+        }
+    }
+
+    /// Return the explanations selected by a code, a name, or a category, compared case-insensitively.
+    pub fn from_selector(selector: &str) -> Vec<Self> {
+        let selector = selector.trim().to_lowercase();
+        Self::ALL
+            .into_iter()
+            .filter(|e| {
+                e.code().to_lowercase() == selector
+                    || e.to_string().to_lowercase() == selector
+                    || e.category() == selector
+            })
+            .collect()
+    }
+
+    // Return a sentence describing this explanation, as used for SARIF rules.
+    fn to_description(self) -> &'static str {
+        match self {
+            ValidationExplain::Missing => "A required package is not installed.",
+            ValidationExplain::Unrequired => "An installed package is not required.",
+            ValidationExplain::Misdefined => {
+                "An installed package does not satisfy its requirement."
+            }
+            ValidationExplain::HashMismatch => {
+                "An installed package does not match the hashes of its requirement."
+            }
+            ValidationExplain::HashUnverifiable => {
+                "An installed package has no recorded hash to verify against its requirement."
+            }
+            ValidationExplain::Drifted => {
+                "An installed package is not at the commit of its requested revision."
+            }
+            ValidationExplain::Editable => "An installed package is installed in editable mode.",
+            ValidationExplain::Orphan => {
+                "An installed package is neither required nor required by a required package."
+            }
+            ValidationExplain::LicenseDenied => {
+                "An installed package has a license not permitted by the license policy."
+            }
+            ValidationExplain::Banned => "An installed package is forbidden by the ban policy.",
+            ValidationExplain::PythonIncompatible => {
+                "An installed package does not support the version of the Python that owns its site."
+            }
+            ValidationExplain::IncompatibleBinary => {
+                "An installed package was built for a different Python, ABI, or platform than that of the Python that owns its site."
+            }
+            ValidationExplain::Shadowed => {
+                "An installed package is shadowed by a package of the same name in a site that precedes it on sys.path."
+            }
+            ValidationExplain::InstallerDenied => {
+                "An installed package was installed by a tool other than those required."
+            }
+            ValidationExplain::Undefined => "A validation record is undefined.",
+        }
+    }
+}
+
 //------------------------------------------------------------------------------
-#[derive(Debug)]
-pub(crate) struct ValidationFlags {
-    pub(crate) permit_superset: bool,
-    pub(crate) permit_subset: bool,
+/// Options of validation. As options might be added, create with `ValidationFlags::default()` and set each with the corresponding `with_` method.
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct ValidationFlags {
+    pub permit_superset: bool,
+    pub permit_subset: bool,
+    /// If set, requested VCS branch or tag revisions are resolved with the remote repository.
+    pub resolve_revisions: bool,
+    /// If set, packages installed in editable mode are invalid.
+    pub reject_editable: bool,
+    /// If set, packages not required by the DepManifest, directly or transitively, are invalid.
+    pub report_orphans: bool,
+    /// If set, local version labels of installed packages (e.g. `+cu118`) are ignored when matching requirements.
+    pub ignore_local: bool,
+    /// If set, installed pre-releases only satisfy requirements that specify a pre-release, as with pip's default resolution.
+    pub reject_prerelease: bool,
+    /// If set, packages of requirements that define hashes are invalid if no archive hash of the installed package was recorded to compare them to.
+    pub require_hashes: bool,
+    /// If set, installed packages must match the applicable requirements one-to-one: neither a superset nor a subset is permitted, regardless of `permit_superset` and `permit_subset`, and installed packages whose requirement has a marker that does not apply are not required.
+    pub exact: bool,
+}
+
+impl ValidationFlags {
+    pub fn with_permit_superset(mut self, value: bool) -> Self {
+        self.permit_superset = value;
+        self
+    }
+
+    pub fn with_permit_subset(mut self, value: bool) -> Self {
+        self.permit_subset = value;
+        self
+    }
+
+    pub fn with_resolve_revisions(mut self, value: bool) -> Self {
+        self.resolve_revisions = value;
+        self
+    }
+
+    pub fn with_reject_editable(mut self, value: bool) -> Self {
+        self.reject_editable = value;
+        self
+    }
+
+    pub fn with_report_orphans(mut self, value: bool) -> Self {
+        self.report_orphans = value;
+        self
+    }
+
+    pub fn with_ignore_local(mut self, value: bool) -> Self {
+        self.ignore_local = value;
+        self
+    }
+
+    pub fn with_reject_prerelease(mut self, value: bool) -> Self {
+        self.reject_prerelease = value;
+        self
+    }
+
+    pub fn with_require_hashes(mut self, value: bool) -> Self {
+        self.require_hashes = value;
+        self
+    }
+
+    pub fn with_exact(mut self, value: bool) -> Self {
+        self.exact = value;
+        self
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -42,6 +259,13 @@ pub(crate) struct ValidationRecord {
     pub(crate) package: Option<Package>,
     dep_spec: Option<DepSpec>,
     sites: Option<Vec<PathShared>>,
+    explain: Option<ValidationExplain>,
+    reason: Option<String>,
+    waiver: Option<String>,
+    // if defined, as when more than one executable is scanned, the executables that use the sites
+    exes: Option<Vec<PathBuf>>,
+    // if defined, the number of executables that use each site, in the order of `sites`
+    site_exe_counts: Option<Vec<usize>>,
 }
 
 impl ValidationRecord {
@@ -54,12 +278,106 @@ impl ValidationRecord {
             package,
             dep_spec,
             sites,
+            explain: None,
+            reason: None,
+            waiver: None,
+            exes: None,
+            site_exe_counts: None,
         }
     }
 
-    fn explain(&self) -> ValidationExplain {
+    /// Create a record with an explanation that cannot be derived from the Package and DepSpec alone.
+    pub(crate) fn from_explain(
+        package: Option<Package>,
+        dep_spec: Option<DepSpec>,
+        sites: Option<Vec<PathShared>>,
+        explain: ValidationExplain,
+    ) -> Self {
+        ValidationRecord {
+            package,
+            dep_spec,
+            sites,
+            explain: Some(explain),
+            reason: None,
+            waiver: None,
+            exes: None,
+            site_exe_counts: None,
+        }
+    }
+
+    /// Create a record with an explanation and the reason for it, such as the reason a package is banned.
+    pub(crate) fn from_explain_reason(
+        package: Option<Package>,
+        dep_spec: Option<DepSpec>,
+        sites: Option<Vec<PathShared>>,
+        explain: ValidationExplain,
+        reason: String,
+    ) -> Self {
+        ValidationRecord {
+            package,
+            dep_spec,
+            sites,
+            explain: Some(explain),
+            reason: Some(reason),
+            waiver: None,
+            exes: None,
+            site_exe_counts: None,
+        }
+    }
+
+    fn to_digest_record(&self) -> ValidationDigestRecord {
+        ValidationDigestRecord {
+            package: self.package.as_ref().map(|package| package.to_string()),
+            dependency: self.dep_spec.as_ref().map(|dep_spec| dep_spec.to_string()),
+            explain: self.explain().to_string(),
+            code: self.explain().code().to_string(),
+            category: self.explain().category().to_string(),
+            sites: self
+                .sites
+                .as_ref()
+                .map(|sites| sites.iter().map(|s| s.to_string()).collect()),
+            reason: self.reason.clone(),
+            waiver: self.waiver.clone(),
+            exes: self
+                .exes
+                .as_ref()
+                .map(|exes| exes.iter().map(|e| e.display().to_string()).collect()),
+        }
+    }
+
+    // Return the key of the DepSpec, or if not defined, the Package.
+    fn get_key(&self) -> Option<&String> {
+        match (&self.dep_spec, &self.package) {
+            (Some(dep_spec), _) => Some(&dep_spec.key),
+            (None, Some(package)) => Some(&package.key),
+            (None, None) => None,
+        }
+    }
+
+    // Return the normalized name of the Package or, if not installed, of the DepSpec.
+    fn get_sort_key(&self) -> &str {
+        match (&self.package, &self.dep_spec) {
+            (Some(package), _) => &package.key,
+            (None, Some(dep_spec)) => &dep_spec.key,
+            (None, None) => "",
+        }
+    }
+
+    pub(crate) fn explain(&self) -> ValidationExplain {
+        if let Some(explain) = self.explain {
+            return explain;
+        }
         match (&self.package, &self.dep_spec) {
-            (Some(_), Some(_)) => ValidationExplain::Misdefined,
+            (Some(package), Some(dep_spec)) => {
+                // a hash is only a cause if the version and URL match
+                if !dep_spec.validate_version(&package.version)
+                    || !dep_spec.validate_url(package)
+                {
+                    ValidationExplain::Misdefined
+                } else {
+                    ValidationExplain::HashMismatch
+                }
+            }
             (None, Some(_)) => ValidationExplain::Missing,
             (Some(_), None) => ValidationExplain::Unrequired,
             (None, None) => ValidationExplain::Undefined,
@@ -67,11 +385,30 @@ impl ValidationRecord {
     }
 }
 
+// Return the display of a site, marked if it is a user site and, if the number of executables that use it is known, as shared by more than one executable or exclusive to one.
+fn site_display(site: &PathShared, exe_count: Option<usize>) -> String {
+    let mut markers = Vec::new();
+    if site.is_user_site() {
+        markers.push("user site".to_string());
+    }
+    match exe_count {
+        Some(count) if count > 1 => markers.push(format!("shared: {}", count)),
+        Some(count) => markers.push(format!("exclusive: {}", count)),
+        None => {}
+    }
+    if markers.is_empty() {
+        site.to_string()
+    } else {
+        format!("{} ({})", site, markers.join(", "))
+    }
+}
+
 impl Rowable for ValidationRecord {
-    fn to_rows(&self, _context: &RowableContext) -> Vec<Vec<String>> {
+    fn to_rows(&self, context: &RowableContext) -> Vec<Vec<String>> {
         // these could be different or configurable
         let dep_missing = "";
         let pkg_missing = "";
+        let is_tty = *context == RowableContext::Tty;
 
         let pkg_display = match &self.package {
             Some(package) => package.to_string(),
@@ -81,102 +418,488 @@ impl Rowable for ValidationRecord {
             Some(dep_spec) => dep_spec.to_string(),
             None => dep_missing.to_string(),
         };
-        // we reduce this to a string for concise representation
-        let sites_display = match &self.sites {
-            Some(sites) => sites
-                .iter()
-                .map(|s| s.to_string())
-                .collect::<Vec<_>>()
-                .join(","),
-            None => "".to_string(),
+        // user sites are marked, as packages found there are not isolated to an environment; sites are marked as shared or exclusive if the executables that use them are known
+        let sites_display: Vec<String> = self
+            .sites
+            .iter()
+            .flatten()
+            .enumerate()
+            .map(|(i, s)| {
+                let count = self
+                    .site_exe_counts
+                    .as_ref()
+                    .and_then(|c| c.get(i).copied());
+                site_display(s, count)
+            })
+            .collect();
+        let mut explain_display = self.explain().to_string();
+        if let Some(reason) = &self.reason {
+            explain_display.push_str(&format!(": {}", reason));
+        }
+        if self.waiver.is_some() {
+            explain_display.push_str(" (waived)");
+        }
+        // for a TTY, each site is listed on its own row; otherwise, sites are reduced to a string for concise representation
+        let (site_first, sites_rest) = if is_tty && sites_display.len() > 1 {
+            (sites_display[0].clone(), &sites_display[1..])
+        } else {
+            (sites_display.join(","), &[][..])
         };
-        vec![vec![
-            pkg_display,
-            dep_display,
-            self.explain().to_string(),
-            sites_display,
-        ]]
+        let mut row = vec![pkg_display, dep_display, explain_display, site_first];
+        if let Some(exes) = &self.exes {
+            row.push(
+                exes.iter()
+                    .map(|e| e.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+        }
+        let width = row.len();
+        let mut rows = vec![row];
+        for site in sites_rest {
+            let mut row = vec![String::new(); width];
+            row[3] = site.clone();
+            rows.push(row);
+        }
+        rows
     }
 }
 
 //------------------------------------------------------------------------------
-// A summary of validation results suitable for JSON serialization to naive readers that need lablled fields.
-#[derive(Serialize, Deserialize)]
-pub(crate) struct ValidationDigestRecord {
-    package: Option<String>,
-    dependency: Option<String>,
-    explain: String,
-    sites: Option<Vec<String>>,
+/// A summary of validation results suitable for JSON serialization to naive readers that need lablled fields.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ValidationDigestRecord {
+    pub package: Option<String>,
+    pub dependency: Option<String>,
+    pub explain: String,
+    #[serde(default)]
+    pub code: String,
+    #[serde(default)]
+    pub category: String,
+    pub sites: Option<Vec<String>>,
+    /// The reason for the explanation, if any, such as the reason a package is banned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// The note of the waiver applied to this record, if any; waived records do not fail validation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waiver: Option<String>,
+    /// The executables that use the sites of this record, if more than one executable was scanned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exes: Option<Vec<String>>,
+}
+
+impl fmt::Display for ValidationDigestRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.package.as_deref().unwrap_or(""),
+            self.dependency.as_deref().unwrap_or(""),
+            self.explain,
+            self.sites.as_ref().map(|s| s.join(",")).unwrap_or_default(),
+        )
+    }
+}
+
+pub type ValidationDigest = Vec<ValidationDigestRecord>;
+
+/// Given a prior and a current ValidationDigest, return lines for records that are new, prefixed with "+", and for records that have been resolved, prefixed with "-".
+pub(crate) fn validation_digest_diff(
+    prior: &ValidationDigest,
+    current: &ValidationDigest,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    for record in current.iter().filter(|r| !prior.contains(r)) {
+        lines.push(format!("+ {}", record));
+    }
+    for record in prior.iter().filter(|r| !current.contains(r)) {
+        lines.push(format!("- {}", record));
+    }
+    lines
+}
+
+// Escape the message of a GitHub Actions workflow command.
+fn github_escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
 }
 
-pub(crate) type ValidationDigest = Vec<ValidationDigestRecord>;
+// Escape a property value of a GitHub Actions workflow command.
+fn github_escape_property(value: &str) -> String {
+    github_escape_data(value)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+// Order records case-insensitively by the name of the Package, or of the DepSpec if not installed, then by version, then by site; the explanation code, the name, and the reason break remaining ties.
+fn cmp_records(a: &ValidationRecord, b: &ValidationRecord) -> Ordering {
+    let sites = |r: &ValidationRecord| -> Vec<PathBuf> {
+        r.sites
+            .iter()
+            .flatten()
+            .map(|s| s.as_path().to_path_buf())
+            .collect()
+    };
+    a.get_sort_key()
+        .cmp(b.get_sort_key())
+        .then_with(|| {
+            let version_a = a.package.as_ref().map(|p| &p.version);
+            version_a.cmp(&b.package.as_ref().map(|p| &p.version))
+        })
+        .then_with(|| sites(a).cmp(&sites(b)))
+        .then_with(|| a.explain().code().cmp(b.explain().code()))
+        .then_with(|| a.package.cmp(&b.package))
+        .then_with(|| a.reason.cmp(&b.reason))
+}
 
 //------------------------------------------------------------------------------
-// Complete report of a validation process.
+/// Complete report of a validation process.
 pub struct ValidationReport {
     pub(crate) records: Vec<ValidationRecord>,
 }
 
 impl ValidationReport {
-    pub(crate) fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         self.records.len()
     }
 
-    pub(crate) fn to_validation_digest(&self) -> ValidationDigest {
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Sort records case-insensitively by package, then by version, then by site, such that output does not depend on the order of file-system iteration or of parallel validation. Records added after validation, such as those of policies, are sorted with the others.
+    pub(crate) fn sort_records(&mut self) {
+        self.records.sort_by(cmp_records);
+    }
+
+    /// Retain only records whose explanation is in `select`, if provided, and is not in `ignore`.
+    pub fn filter_explains(
+        &mut self,
+        select: Option<&[ValidationExplain]>,
+        ignore: &[ValidationExplain],
+    ) {
+        self.records.retain(|r| {
+            let explain = r.explain();
+            let selected = match select {
+                Some(select) => select.contains(&explain),
+                None => true,
+            };
+            selected && !ignore.contains(&explain)
+        });
+    }
+
+    /// Mark records that match an active waiver as waived; waived records remain in the report but are not counted as failures.
+    pub(crate) fn apply_waivers(&mut self, waivers: &Waivers, today: i64) {
+        for record in self.records.iter_mut() {
+            let explain = record.explain();
+            record.waiver = record
+                .get_key()
+                .and_then(|key| waivers.get_note(key, explain, today));
+        }
+    }
+
+    /// Given a mapping of sites to the executables that use them, as when more than one executable is scanned, set the executables of each record, such that they are shown in an additional column, and mark each site as shared by more than one executable or exclusive to one.
+    pub(crate) fn set_site_to_exes(
+        &mut self,
+        site_to_exes: &HashMap<PathShared, Vec<PathBuf>>,
+    ) {
+        for record in self.records.iter_mut() {
+            let mut exes: Vec<PathBuf> = record
+                .sites
+                .iter()
+                .flatten()
+                .filter_map(|site| site_to_exes.get(site))
+                .flatten()
+                .cloned()
+                .collect();
+            exes.sort();
+            exes.dedup();
+            record.exes = Some(exes);
+            record.site_exe_counts = record.sites.as_ref().map(|sites| {
+                sites
+                    .iter()
+                    .map(|site| site_to_exes.get(site).map_or(0, |e| e.len()))
+                    .collect()
+            });
+        }
+    }
+
+    /// Return the number of records that fail validation, excluding waived records.
+    pub fn failure_count(&self) -> usize {
+        self.records.iter().filter(|r| r.waiver.is_none()).count()
+    }
+
+    /// Return a summary of this report: the number of records of each explanation, in the order of their codes, the total number of records and of failures, and the number of records of each site.
+    pub(crate) fn to_summary_report(&self) -> CountReport {
+        let mut records = Vec::new();
+        for explain in ValidationExplain::ALL {
+            let count = self
+                .records
+                .iter()
+                .filter(|r| r.explain() == explain)
+                .count();
+            if count > 0 {
+                records.push(CountRecord::new(explain.to_string(), count));
+            }
+        }
+        records.push(CountRecord::new("Total".to_string(), self.records.len()));
+        records.push(CountRecord::new(
+            "Failures".to_string(),
+            self.failure_count(),
+        ));
+        let mut site_to_count: HashMap<&PathShared, usize> = HashMap::new();
+        for site in self
+            .records
+            .iter()
+            .filter_map(|r| r.sites.as_ref())
+            .flatten()
+        {
+            *site_to_count.entry(site).or_default() += 1;
+        }
+        let mut sites: Vec<(&PathShared, usize)> = site_to_count.into_iter().collect();
+        sites.sort_by(|a, b| a.0.as_path().cmp(b.0.as_path()));
+        for (site, count) in sites {
+            records.push(CountRecord::new(site.to_string(), count));
+        }
+        CountReport::from_records(records)
+    }
+
+    pub fn to_validation_digest(&self) -> ValidationDigest {
         let mut records: Vec<&ValidationRecord> = self.records.iter().collect();
-        records.sort_by_key(|item| &item.package);
+        records.sort_by(|a, b| cmp_records(a, b));
+        records.iter().map(|r| r.to_digest_record()).collect()
+    }
 
-        let mut digests: ValidationDigest = Vec::new();
-        for record in &records {
-            let pkg_display = record
-                .package
-                .as_ref()
-                .map(|package| format!("{}", package));
-            let dep_display = record
+    /// Write this report as JUnit XML. Each provided DepSpec is a test case that fails with the explanations of the records that refer to it; records that do not refer to a provided DepSpec, such as unrequired packages, are additional failed test cases.
+    pub(crate) fn to_junit_writer<W: Write>(
+        &self,
+        writer: &mut W,
+        dep_specs: &[&DepSpec],
+    ) -> io::Result<()> {
+        let mut cases: Vec<(String, Vec<&ValidationRecord>)> = dep_specs
+            .iter()
+            .map(|ds| {
+                let records = self
+                    .records
+                    .iter()
+                    .filter(|r| r.dep_spec.as_ref().is_some_and(|d| d.key == ds.key))
+                    .collect();
+                (ds.to_string(), records)
+            })
+            .collect();
+        for record in self.records.iter() {
+            let listed = record
                 .dep_spec
                 .as_ref()
-                .map(|dep_spec| format!("{}", dep_spec));
-            let sites = record
-                .sites
-                .as_ref()
-                .map(|sites| sites.iter().map(|s| s.to_string()).collect::<Vec<_>>());
-            digests.push(ValidationDigestRecord {
-                package: pkg_display,
-                dependency: dep_display,
-                explain: record.explain().to_string(),
-                sites,
-            });
+                .is_some_and(|d| dep_specs.iter().any(|ds| ds.key == d.key));
+            if !listed {
+                let name = match (&record.package, &record.dep_spec) {
+                    (Some(package), _) => package.to_string(),
+                    (None, Some(dep_spec)) => dep_spec.to_string(),
+                    (None, None) => "".to_string(),
+                };
+                cases.push((name, vec![record]));
+            }
+        }
+        // cases with only waived records are skipped
+        let is_skipped =
+            |records: &[&ValidationRecord]| records.iter().all(|r| r.waiver.is_some());
+        let failures = cases
+            .iter()
+            .filter(|(_, r)| !r.is_empty() && !is_skipped(r))
+            .count();
+        let skipped = cases
+            .iter()
+            .filter(|(_, r)| !r.is_empty() && is_skipped(r))
+            .count();
+
+        writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(
+            writer,
+            "<testsuites name=\"fetter\" tests=\"{}\" failures=\"{}\" skipped=\"{}\">",
+            cases.len(),
+            failures,
+            skipped
+        )?;
+        writeln!(
+            writer,
+            "<testsuite name=\"validate\" tests=\"{}\" failures=\"{}\" skipped=\"{}\">",
+            cases.len(),
+            failures,
+            skipped
+        )?;
+        for (name, records) in cases {
+            if records.is_empty() {
+                writeln!(
+                    writer,
+                    "<testcase classname=\"fetter.validate\" name=\"{}\"/>",
+                    markup_escape(&name)
+                )?;
+                continue;
+            }
+            if is_skipped(&records) {
+                let waivers: Vec<&str> =
+                    records.iter().filter_map(|r| r.waiver.as_deref()).collect();
+                writeln!(
+                    writer,
+                    "<testcase classname=\"fetter.validate\" name=\"{}\"><skipped message=\"{}\"/></testcase>",
+                    markup_escape(&name),
+                    markup_escape(&waivers.join(", "))
+                )?;
+                continue;
+            }
+            let mut explains: Vec<String> =
+                records.iter().map(|r| r.explain().to_string()).collect();
+            explains.dedup();
+            let explain = markup_escape(&explains.join(", "));
+            let details: Vec<String> = records
+                .iter()
+                .map(|r| markup_escape(&r.to_digest_record().to_string()))
+                .collect();
+            writeln!(
+                writer,
+                "<testcase classname=\"fetter.validate\" name=\"{}\"><failure message=\"{}\" type=\"{}\">{}</failure></testcase>",
+                markup_escape(&name),
+                explain,
+                explain,
+                details.join("\n")
+            )?;
         }
-        digests
+        writeln!(writer, "</testsuite>")?;
+        writeln!(writer, "</testsuites>")
+    }
+
+    // Return a SARIF finding for each record, with the key of its DepSpec or Package used to locate it in the bound requirements.
+    fn to_sarif_findings(&self) -> Vec<SarifFinding> {
+        self.records
+            .iter()
+            .map(|record| {
+                let explain = record.explain();
+                let digest = record.to_digest_record();
+                let message = [digest.package, digest.dependency]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let message = match &record.reason {
+                    Some(reason) => format!("{} ({})", message, reason),
+                    None => message,
+                };
+                // waived records are retained as notes
+                let (level, message) = match &record.waiver {
+                    Some(waiver) => (
+                        "note",
+                        format!("{}: {} (waived: {})", explain, message, waiver),
+                    ),
+                    None => ("error", format!("{}: {}", explain, message)),
+                };
+                SarifFinding {
+                    rule_id: explain.to_string(),
+                    rule_description: explain.to_description().to_string(),
+                    help_uri: None,
+                    level,
+                    message,
+                    key: record.get_key().cloned(),
+                }
+            })
+            .collect()
+    }
+
+    /// Return a SARIF log of this report, locating each record in the first bound requirements file that declares the key of its DepSpec or Package.
+    pub(crate) fn to_sarif(&self, bounds: &[PathBuf]) -> SarifLog {
+        SarifLog::from_findings(self.to_sarif_findings(), bounds)
+    }
+
+    /// Return a GitHub Actions workflow command for each record, annotating an error, or a notice if waived, at the line of the first bound requirements file that declares its package, or at the first bound file if not declared.
+    pub(crate) fn to_github_annotations(&self, bounds: &[PathBuf]) -> Vec<String> {
+        let contents = read_bounds(bounds);
+        self.to_sarif_findings()
+            .into_iter()
+            .map(|finding| {
+                let (bound, line) = locate_requirement(&contents, finding.key.as_deref())
+                    .unwrap_or((Path::new(""), None));
+                let file =
+                    github_escape_property(&bound.to_string_lossy().replace('\\', "/"));
+                let line = line
+                    .map(|line| format!(",line={}", line))
+                    .unwrap_or_default();
+                let command = match finding.level {
+                    "note" => "notice",
+                    level => level,
+                };
+                format!(
+                    "::{} file={}{},title={}::{}",
+                    command,
+                    file,
+                    line,
+                    github_escape_property(&finding.rule_id),
+                    github_escape_data(&finding.message)
+                )
+            })
+            .collect()
+    }
+
+    pub(crate) fn to_junit_file(
+        &self,
+        file_path: &PathBuf,
+        dep_specs: &[&DepSpec],
+    ) -> io::Result<()> {
+        let mut file = File::create(file_path)?;
+        self.to_junit_writer(&mut file, dep_specs)
     }
 }
 
 impl Tableable<ValidationRecord> for ValidationReport {
     fn get_header(&self) -> Vec<ColumnFormat> {
-        vec![
+        let mut header = vec![
             ColumnFormat::new("Package".to_string(), false, "#666666".to_string()),
             ColumnFormat::new("Dependency".to_string(), false, "#666666".to_string()),
             ColumnFormat::new("Explain".to_string(), false, "#666666".to_string()),
             ColumnFormat::new("Sites".to_string(), true, "#666666".to_string()),
-        ]
+        ];
+        if self.records.iter().any(|r| r.exes.is_some()) {
+            header.push(ColumnFormat::new(
+                "Executables".to_string(),
+                true,
+                "#666666".to_string(),
+            ));
+        }
+        header
     }
     fn get_records(&self) -> &Vec<ValidationRecord> {
         &self.records
     }
+    fn to_json_writer<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        serde_json::to_writer(&mut *writer, &self.to_validation_digest())?;
+        writeln!(writer)
+    }
 }
 
 //------------------------------------------------------------------------------
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod tests {
     use super::*;
     use crate::dep_manifest::DepManifest;
+    use crate::package_durl::DirectURL;
     use crate::scan_fs::ScanFS;
-    use std::fs::File;
-    use std::io;
+    use std::fs;
     use std::io::BufRead;
-    use std::path::PathBuf;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_validation_flags_a() {
+        let vf = ValidationFlags::default()
+            .with_permit_superset(true)
+            .with_exact(true);
+        assert!(vf.permit_superset);
+        assert!(vf.exact);
+        assert!(!vf.permit_subset);
+        assert!(!vf.require_hashes);
+    }
+
     #[test]
     fn test_to_file_a() {
         let exe = PathBuf::from("/usr/bin/python3");
@@ -199,6 +922,13 @@ mod tests {
             ValidationFlags {
                 permit_superset: false,
                 permit_subset: false,
+                resolve_revisions: false,
+                reject_editable: false,
+                report_orphans: false,
+                ignore_local: false,
+                reject_prerelease: false,
+                require_hashes: false,
+                exact: false,
             },
         );
 
@@ -223,4 +953,486 @@ mod tests {
         assert_eq!(lines.next().unwrap().unwrap(), "static-frame-2.13.0|static_frame==2.1.0|Misdefined|/usr/lib/python3/site-packages");
         assert!(lines.next().is_none());
     }
+
+    #[test]
+    fn test_to_json_file_a() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "1.19.3", None).unwrap(),
+            Package::from_name_version_durl("flask", "1.2", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let dm =
+            DepManifest::from_iter(vec!["numpy==2.1.0", "flask>1,<2"].iter()).unwrap();
+        let vr1 = sfs.to_validation_report(
+            dm,
+            ValidationFlags {
+                permit_superset: false,
+                permit_subset: false,
+                resolve_revisions: false,
+                reject_editable: false,
+                report_orphans: false,
+                ignore_local: false,
+                reject_prerelease: false,
+                require_hashes: false,
+                exact: false,
+            },
+        );
+        let dir = tempdir().unwrap();
+        let fp = dir.path().join("valid.json");
+        let _ = vr1.to_json_file(&fp);
+
+        let file = File::open(&fp).unwrap();
+        let mut lines = io::BufReader::new(file).lines();
+        assert_eq!(
+            lines.next().unwrap().unwrap(),
+            r#"[{"package":"numpy-1.19.3","dependency":"numpy==2.1.0","explain":"Misdefined","code":"V003","category":"requirement","sites":["/usr/lib/python3/site-packages"]}]"#
+        );
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    fn test_to_junit_writer_a() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "1.19.3", None).unwrap(),
+            Package::from_name_version_durl("flask", "1.2", None).unwrap(),
+            Package::from_name_version_durl("packaging", "24.1", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let dm = DepManifest::from_iter(vec!["numpy==2.1.0", "flask>1,<2", "six"].iter())
+            .unwrap();
+        let vr = sfs.to_validation_report(dm.clone(), ValidationFlags::default());
+
+        let mut buffer = Vec::new();
+        vr.to_junit_writer(&mut buffer, &dm.get_dep_specs())
+            .unwrap();
+        let xml = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = xml.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<testsuites name="fetter" tests="4" failures="3" skipped="0">"#,
+                r#"<testsuite name="validate" tests="4" failures="3" skipped="0">"#,
+                r#"<testcase classname="fetter.validate" name="flask&gt;1,&lt;2"/>"#,
+                r#"<testcase classname="fetter.validate" name="numpy==2.1.0"><failure message="Misdefined" type="Misdefined">numpy-1.19.3 numpy==2.1.0 Misdefined /usr/lib/python3/site-packages</failure></testcase>"#,
+                r#"<testcase classname="fetter.validate" name="six"><failure message="Missing" type="Missing"> six Missing </failure></testcase>"#,
+                r#"<testcase classname="fetter.validate" name="packaging-24.1"><failure message="Unrequired" type="Unrequired">packaging-24.1  Unrequired /usr/lib/python3/site-packages</failure></testcase>"#,
+                "</testsuite>",
+                "</testsuites>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_sarif_a() {
+        let dir = tempdir().unwrap();
+        let bound = dir.path().join("requirements.txt");
+        fs::write(&bound, "flask>1,<2\nnumpy==2.1.0\n").unwrap();
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "1.19.3", None).unwrap(),
+            Package::from_name_version_durl("flask", "1.2", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let dm = DepManifest::from_requirements_file(&bound).unwrap();
+        let vr = sfs.to_validation_report(dm, ValidationFlags::default());
+
+        let value =
+            serde_json::to_value(vr.to_sarif(std::slice::from_ref(&bound))).unwrap();
+        let result = &value["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], "Misdefined");
+        assert_eq!(
+            result["message"]["text"],
+            "Misdefined: numpy-1.19.3, numpy==2.1.0"
+        );
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["region"]["startLine"],
+            2
+        );
+    }
+
+    #[test]
+    fn test_to_github_annotations_a() {
+        let dir = tempdir().unwrap();
+        let bound = dir.path().join("requirements.lock");
+        fs::write(&bound, "flask>1,<2\nnumpy==2.1.0\n").unwrap();
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "1.19.3", None).unwrap(),
+            Package::from_name_version_durl("flask", "1.2", None).unwrap(),
+            Package::from_name_version_durl("six", "1.16.0", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let dm = DepManifest::from_requirements_file(&bound).unwrap();
+        let vr = sfs.to_validation_report(dm, ValidationFlags::default());
+
+        let file = github_escape_property(&bound.to_string_lossy());
+        assert_eq!(
+            vr.to_github_annotations(std::slice::from_ref(&bound)),
+            vec![
+                format!("::error file={},line=2,title=Misdefined::Misdefined: numpy-1.19.3, numpy==2.1.0", file),
+                format!("::error file={},title=Unrequired::Unrequired: six-1.16.0", file),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_explains_a() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "1.19.3", None).unwrap(),
+            Package::from_name_version_durl("six", "1.16.0", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let dm = DepManifest::from_iter(vec!["numpy==2.1.0", "flask"].iter()).unwrap();
+        let vr = sfs.to_validation_report(dm.clone(), ValidationFlags::default());
+        assert_eq!(vr.len(), 3);
+
+        let mut vr1 = sfs.to_validation_report(dm.clone(), ValidationFlags::default());
+        vr1.filter_explains(None, &ValidationExplain::from_selector("inventory"));
+        assert_eq!(vr1.len(), 2);
+
+        let mut vr2 = sfs.to_validation_report(dm, ValidationFlags::default());
+        let select = ValidationExplain::from_selector("requirement");
+        vr2.filter_explains(Some(&select), &ValidationExplain::from_selector("Missing"));
+        let digest = vr2.to_validation_digest();
+        assert_eq!(digest.len(), 1);
+        assert_eq!(digest[0].code, "V003");
+        assert_eq!(digest[0].category, "requirement");
+    }
+
+    #[test]
+    fn test_apply_waivers_a() {
+        let dir = tempdir().unwrap();
+        let bound = dir.path().join("requirements.txt");
+        fs::write(&bound, "numpy==2.1.0\nflask\n").unwrap();
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let packages = vec![
+            Package::from_name_version_durl("numpy", "1.19.3", None).unwrap(),
+            Package::from_name_version_durl("six", "1.16.0", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let dm = DepManifest::from_requirements_file(&bound).unwrap();
+        let mut vr = sfs.to_validation_report(dm.clone(), ValidationFlags::default());
+        assert_eq!(vr.failure_count(), 3);
+
+        let waivers = Waivers::from_str(
+            "[[ignore]]\npackage = \"numpy\"\ncode = \"V003\"\nreason = \"Pending upgrade\"\n\n[[ignore]]\npackage = \"six\"\nexpires = \"2024-01-01\"\n",
+        )
+        .unwrap();
+        let today = crate::util::iso8601_to_days("2024-06-30").unwrap();
+        vr.apply_waivers(&waivers, today);
+        // waived records are retained, but do not fail
+        assert_eq!(vr.len(), 3);
+        assert_eq!(vr.failure_count(), 2);
+
+        let digest = vr.to_validation_digest();
+        let numpy = digest
+            .iter()
+            .find(|r| r.package.as_deref() == Some("numpy-1.19.3"))
+            .unwrap();
+        assert_eq!(numpy.waiver.as_deref(), Some("Pending upgrade"));
+        assert!(serde_json::to_string(numpy)
+            .unwrap()
+            .ends_with(r#""waiver":"Pending upgrade"}"#));
+
+        let file = github_escape_property(&bound.to_string_lossy());
+        assert!(vr.to_github_annotations(std::slice::from_ref(&bound)).contains(&format!(
+            "::notice file={},line=1,title=Misdefined::Misdefined: numpy-1.19.3, numpy==2.1.0 (waived: Pending upgrade)",
+            file
+        )));
+
+        let mut buffer = Vec::new();
+        vr.to_junit_writer(&mut buffer, &dm.get_dep_specs())
+            .unwrap();
+        let xml = String::from_utf8(buffer).unwrap();
+        assert!(xml.contains(r#"failures="2" skipped="1""#));
+        assert!(xml.contains(r#"<testcase classname="fetter.validate" name="numpy==2.1.0"><skipped message="Pending upgrade"/></testcase>"#));
+    }
+
+    #[test]
+    fn test_github_escape_a() {
+        assert_eq!(github_escape_data("a%b\nc"), "a%25b%0Ac");
+        assert_eq!(github_escape_property("a:b,c"), "a%3Ab%2Cc");
+    }
+
+    #[test]
+    fn test_to_json_file_b() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let durl: DirectURL = serde_json::from_str(
+            r#"{"url": "https://files.pythonhosted.org/six-1.16.0-py2.py3-none-any.whl", "archive_info": {"hashes": {"sha256": "8abb2f1d86890a2dfb989f9a77cfcfd3e47c2a354b01111771326f8aa26e0254"}}}"#,
+        )
+        .unwrap();
+        let packages = vec![
+            Package::from_name_version_durl("six", "1.16.0", Some(durl)).unwrap(),
+            Package::from_name_version_durl("flask", "1.2", None).unwrap(),
+        ];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let dm = DepManifest::from_iter(
+            vec![
+                "six==1.16.0 --hash=sha256:1e61c37477a1626458e36f7b1d82aa5c9b094fa4802892072e49de9c60c4c926",
+                "flask==1.2 --hash=sha256:1e61c37477a1626458e36f7b1d82aa5c9b094fa4802892072e49de9c60c4c926",
+            ]
+            .iter(),
+        )
+        .unwrap();
+        let vr1 = sfs.to_validation_report(
+            dm.clone(),
+            ValidationFlags {
+                permit_superset: false,
+                permit_subset: false,
+                resolve_revisions: false,
+                reject_editable: false,
+                report_orphans: false,
+                ignore_local: false,
+                reject_prerelease: false,
+                require_hashes: false,
+                exact: false,
+            },
+        );
+        // flask has no recorded archive hash and no RECORD to verify, and is not invalid
+        assert_eq!(vr1.len(), 1);
+        assert_eq!(vr1.to_validation_digest()[0].explain, "HashMismatch");
+
+        let vf = ValidationFlags {
+            require_hashes: true,
+            ..ValidationFlags::default()
+        };
+        let vr2 = sfs.to_validation_report(dm, vf);
+        assert_eq!(vr2.len(), 2);
+        assert_eq!(
+            serde_json::to_string(&vr2.to_validation_digest()).unwrap(),
+            r#"[{"package":"flask-1.2","dependency":"flask==1.2","explain":"HashUnverifiable","code":"V009","category":"integrity","sites":["/usr/lib/python3/site-packages"],"reason":"no archive hash recorded for installed package"},{"package":"six-1.16.0","dependency":"six==1.16.0","explain":"HashMismatch","code":"V004","category":"integrity","sites":["/usr/lib/python3/site-packages"]}]"#
+        );
+    }
+
+    #[test]
+    fn test_to_json_file_c() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let url = "https://files.example.com/acme-1.0-py3-none-any.whl";
+        let durl: DirectURL = serde_json::from_str(&format!(
+            r#"{{"url": "{}", "archive_info": {{"hash": "sha256=8abb2f1d"}}}}"#,
+            url
+        ))
+        .unwrap();
+        let packages =
+            vec![Package::from_name_version_durl("acme", "1.0", Some(durl)).unwrap()];
+        let sfs = ScanFS::from_exe_site_packages(exe, site, packages).unwrap();
+        let vf = ValidationFlags::default();
+
+        let dm = DepManifest::from_iter(
+            vec![format!("acme @ {}#sha256=8abb2f1d", url)].iter(),
+        )
+        .unwrap();
+        assert_eq!(sfs.to_validation_report(dm, vf).len(), 0);
+
+        // the hash in the URL fragment does not match the installed archive
+        let dm = DepManifest::from_iter(
+            vec![format!("acme @ {}#sha256=1e61c374", url)].iter(),
+        )
+        .unwrap();
+        let vr = sfs.to_validation_report(dm, vf);
+        assert_eq!(vr.len(), 1);
+        assert_eq!(vr.to_validation_digest()[0].explain, "HashMismatch");
+
+        // the URL does not match the installed archive
+        let dm = DepManifest::from_iter(
+            vec!["acme @ https://files.example.com/acme-1.0-py3-none-any.whl.bak"].iter(),
+        )
+        .unwrap();
+        let vr = sfs.to_validation_report(dm, vf);
+        assert_eq!(vr.to_validation_digest()[0].explain, "Misdefined");
+    }
+
+    #[test]
+    fn test_validation_digest_diff_a() {
+        let exe = PathBuf::from("/usr/bin/python3");
+        let site = PathBuf::from("/usr/lib/python3/site-packages");
+        let vf = ValidationFlags::default();
+        let packages1 = vec![
+            Package::from_name_version_durl("numpy", "1.19.3", None).unwrap(),
+            Package::from_name_version_durl("flask", "1.2", None).unwrap(),
+        ];
+        let sfs1 =
+            ScanFS::from_exe_site_packages(exe.clone(), site.clone(), packages1).unwrap();
+        let packages2 = vec![
+            Package::from_name_version_durl("numpy", "2.1.0", None).unwrap(),
+            Package::from_name_version_durl("flask", "1.2", None).unwrap(),
+            Package::from_name_version_durl("requests", "2.32.3", None).unwrap(),
+        ];
+        let sfs2 = ScanFS::from_exe_site_packages(exe, site, packages2).unwrap();
+
+        let dm =
+            DepManifest::from_iter(vec!["numpy==2.1.0", "flask>1,<2"].iter()).unwrap();
+        let vd1 = sfs1
+            .to_validation_report(dm.clone(), vf)
+            .to_validation_digest();
+        let vd2 = sfs2.to_validation_report(dm, vf).to_validation_digest();
+        assert_eq!(
+            validation_digest_diff(&vd1, &vd2),
+            vec![
+                "+ requests-2.32.3  Unrequired /usr/lib/python3/site-packages",
+                "- numpy-1.19.3 numpy==2.1.0 Misdefined /usr/lib/python3/site-packages",
+            ]
+        );
+        assert!(validation_digest_diff(&vd2, &vd2).is_empty());
+    }
+
+    #[test]
+    fn test_set_site_to_exes_a() {
+        let site_a = PathShared::from_str("/usr/lib/python3.12/site-packages");
+        let site_b = PathShared::from_str("/opt/venv/lib/python3.12/site-packages");
+        let exe_a = PathBuf::from("/usr/bin/python3");
+        let exe_b = PathBuf::from("/opt/venv/bin/python");
+        let mut site_to_exes = HashMap::new();
+        site_to_exes.insert(site_a.clone(), vec![exe_b.clone(), exe_a.clone()]);
+        site_to_exes.insert(site_b.clone(), vec![exe_b.clone()]);
+
+        let mut vr = ValidationReport {
+            records: vec![ValidationRecord::new(
+                Some(Package::from_name_version_durl("six", "1.16.0", None).unwrap()),
+                None,
+                Some(vec![site_b, site_a]),
+            )],
+        };
+        let rows = vr.records[0].to_rows(&RowableContext::Tty);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0][3], "/opt/venv/lib/python3.12/site-packages");
+
+        vr.set_site_to_exes(&site_to_exes);
+        let rows = vr.records[0].to_rows(&RowableContext::Tty);
+        assert_eq!(
+            rows,
+            vec![
+                vec![
+                    "six-1.16.0",
+                    "",
+                    "Unrequired",
+                    "/opt/venv/lib/python3.12/site-packages (exclusive: 1)",
+                    "/opt/venv/bin/python,/usr/bin/python3",
+                ],
+                vec![
+                    "",
+                    "",
+                    "",
+                    "/usr/lib/python3.12/site-packages (shared: 2)",
+                    ""
+                ],
+            ]
+        );
+        let rows = vr.records[0].to_rows(&RowableContext::Delimited);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(
+            rows[0][3],
+            "/opt/venv/lib/python3.12/site-packages (exclusive: 1),/usr/lib/python3.12/site-packages (shared: 2)"
+        );
+    }
+
+    #[test]
+    fn test_to_summary_report_a() {
+        let site_a = PathShared::from_str("/usr/lib/python3.12/site-packages");
+        let site_b = PathShared::from_str("/opt/venv/lib/python3.12/site-packages");
+        let vr = ValidationReport {
+            records: vec![
+                ValidationRecord::new(
+                    Some(Package::from_name_version_durl("six", "1.16.0", None).unwrap()),
+                    None,
+                    Some(vec![site_b.clone(), site_a.clone()]),
+                ),
+                ValidationRecord::new(
+                    Some(Package::from_name_version_durl("pip", "24.2", None).unwrap()),
+                    None,
+                    Some(vec![site_a]),
+                ),
+                ValidationRecord::new(
+                    None,
+                    Some(DepSpec::from_string("numpy>=2").unwrap()),
+                    None,
+                ),
+            ],
+        };
+        let dir = tempdir().unwrap();
+        let fp = dir.path().join("summary.txt");
+        vr.to_summary_report().to_file(&fp, ',').unwrap();
+        assert_eq!(
+            fs::read_to_string(&fp).unwrap(),
+            ",Count\nMissing,1\nUnrequired,2\nTotal,3\nFailures,3\n/opt/venv/lib/python3.12/site-packages,1\n/usr/lib/python3.12/site-packages,2\n"
+        );
+    }
+
+    #[test]
+    fn test_sort_records_a() {
+        let site_a = PathShared::from_str("/usr/lib/python3/site-packages");
+        let site_b = PathShared::from_str("/opt/venv/lib/python3.12/site-packages");
+        let package =
+            |name, version| Package::from_name_version_durl(name, version, None);
+        let mut vr = ValidationReport {
+            records: vec![
+                ValidationRecord::new(
+                    package("six", "1.16.0"),
+                    None,
+                    Some(vec![site_a.clone()]),
+                ),
+                ValidationRecord::new(
+                    None,
+                    Some(DepSpec::from_string("zipp").unwrap()),
+                    None,
+                ),
+                ValidationRecord::new(
+                    package("Six", "1.16.0"),
+                    None,
+                    Some(vec![site_b.clone()]),
+                ),
+                ValidationRecord::new(
+                    package("PyYAML", "6.0.2"),
+                    None,
+                    Some(vec![site_a.clone()]),
+                ),
+                ValidationRecord::new(
+                    None,
+                    Some(DepSpec::from_string("attrs").unwrap()),
+                    None,
+                ),
+                ValidationRecord::new(
+                    package("pyyaml", "5.4.1"),
+                    None,
+                    Some(vec![site_b]),
+                ),
+            ],
+        };
+        vr.sort_records();
+        let keys: Vec<String> = vr
+            .records
+            .iter()
+            .map(|r| match (&r.package, &r.dep_spec) {
+                (Some(p), _) => p.to_string(),
+                (None, Some(ds)) => ds.to_string(),
+                (None, None) => "".to_string(),
+            })
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                "attrs",
+                "pyyaml-5.4.1",
+                "PyYAML-6.0.2",
+                "Six-1.16.0",
+                "six-1.16.0",
+                "zipp"
+            ]
+        );
+        let digest = vr.to_validation_digest();
+        assert_eq!(digest[1].package.as_deref(), Some("pyyaml-5.4.1"));
+        assert_eq!(digest[5].dependency.as_deref(), Some("zipp"));
+    }
 }
@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use rayon::prelude::*;
+use tracing::warn;
+
+use crate::conda_meta::is_conda_meta;
+use crate::package::Package;
+use crate::path_shared::PathShared;
+use crate::table::ColumnFormat;
+use crate::table::Rowable;
+use crate::table::RowableContext;
+use crate::table::Tableable;
+use crate::unpack_report::Artifacts;
+use crate::util::hash_file_record;
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum VerifyStatus {
+    Modified,
+    Missing,
+    Extra,
+}
+
+impl fmt::Display for VerifyStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            VerifyStatus::Modified => "Modified",
+            VerifyStatus::Missing => "Missing",
+            VerifyStatus::Extra => "Extra",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+// Return true if a RECORD path leaves the site, such as for scripts installed in `bin`.
+fn is_outside_site(path: &Path, site: &Path) -> bool {
+    match path.strip_prefix(site) {
+        Ok(rel) => rel.components().any(|c| c == Component::ParentDir),
+        Err(_) => true,
+    }
+}
+
+/// Given the Artifacts of a package, return the status of each file that does not match its RECORD. Files listed in RECORD with a sha256 digest are hashed and compared; files without a digest, such as RECORD itself, are only checked for existence. Extra files are those not listed in RECORD found in directories within the site that contain listed files; `__pycache__` directories are not examined, as byte-code might be written after installation.
+fn artifacts_to_status(
+    artifacts: &Artifacts,
+    site: &Path,
+) -> Vec<(VerifyStatus, PathBuf)> {
+    let mut status = Vec::new();
+    let mut recorded: HashSet<&Path> = HashSet::new();
+    let mut dirs: HashSet<&Path> = HashSet::new();
+
+    for af in &artifacts.files {
+        recorded.insert(af.path.as_path());
+        if !af.exists {
+            status.push((VerifyStatus::Missing, af.path.clone()));
+            continue;
+        }
+        if let Some(hash) = af.hash.as_ref().filter(|h| h.starts_with("sha256=")) {
+            match hash_file_record(&af.path) {
+                Ok(h) if h == *hash => {}
+                _ => status.push((VerifyStatus::Modified, af.path.clone())),
+            }
+        }
+        if let Some(dir) = af.path.parent() {
+            if dir != site
+                && !is_outside_site(&af.path, site)
+                && dir.file_name().is_some_and(|n| n != "__pycache__")
+            {
+                dirs.insert(dir);
+            }
+        }
+    }
+    for dir in dirs {
+        if let Ok(entries) = fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if entry.file_type().is_ok_and(|ft| ft.is_file())
+                    && !recorded.contains(path.as_path())
+                {
+                    status.push((VerifyStatus::Extra, path));
+                }
+            }
+        }
+    }
+    status.sort();
+    status
+}
+
+/// Return true if the files installed for a Package in a site exist and match the digests of its RECORD; extra files are not considered. Returns None if the RECORD cannot be read, as for conda-meta sites.
+pub(crate) fn validate_record(package: &Package, site: &PathShared) -> Option<bool> {
+    if is_conda_meta(site.as_path()) {
+        return None;
+    }
+    let artifacts = Artifacts::from_package(package, site).ok()?;
+    Some(
+        artifacts_to_status(&artifacts, site.as_path())
+            .iter()
+            .all(|(status, _)| *status == VerifyStatus::Extra),
+    )
+}
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone)]
+pub(crate) struct VerifyRecord {
+    package: Package,
+    site: PathShared,
+    status: Vec<(VerifyStatus, PathBuf)>,
+}
+
+impl Rowable for VerifyRecord {
+    fn to_rows(&self, context: &RowableContext) -> Vec<Vec<String>> {
+        let is_tty = *context == RowableContext::Tty;
+        self.status
+            .iter()
+            .enumerate()
+            .map(|(i, (status, fp))| {
+                let (package_display, site_display) = if is_tty && i > 0 {
+                    ("".to_string(), "".to_string())
+                } else {
+                    (self.package.to_string(), self.site.to_string())
+                };
+                vec![
+                    package_display,
+                    site_display,
+                    status.to_string(),
+                    fp.display().to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+//------------------------------------------------------------------------------
+/// A report of installed files that do not match the RECORD file of their package: files modified after installation, files missing, and extra files not recorded.
+pub(crate) struct VerifyReport {
+    records: Vec<VerifyRecord>,
+}
+
+impl VerifyReport {
+    pub(crate) fn from_package_to_sites(
+        package_to_sites: &HashMap<Package, Vec<PathShared>>,
+    ) -> Self {
+        let mut records: Vec<VerifyRecord> = package_to_sites
+            .par_iter()
+            .flat_map(|(package, sites)| {
+                sites.par_iter().filter_map(move |site| {
+                    // conda-meta sites only attribute Packages; artifacts are found in site packages
+                    if is_conda_meta(site.as_path()) {
+                        return None;
+                    }
+                    match Artifacts::from_package(package, site) {
+                        Ok(artifacts) => {
+                            let status = artifacts_to_status(&artifacts, site.as_path());
+                            if status.is_empty() {
+                                None
+                            } else {
+                                Some(VerifyRecord {
+                                    package: package.clone(),
+                                    site: site.clone(),
+                                    status,
+                                })
+                            }
+                        }
+                        Err(_) => {
+                            warn!("Failed to read artifacts: {:?}", package);
+                            None
+                        }
+                    }
+                })
+            })
+            .collect();
+        records.sort_by(|a, b| {
+            (&a.package, a.site.as_path()).cmp(&(&b.package, b.site.as_path()))
+        });
+        VerifyReport { records }
+    }
+
+    /// Return the number of files that do not match their RECORD.
+    pub(crate) fn len(&self) -> usize {
+        self.records.iter().map(|r| r.status.len()).sum()
+    }
+}
+
+impl Tableable<VerifyRecord> for VerifyReport {
+    fn get_header(&self) -> Vec<ColumnFormat> {
+        vec![
+            ColumnFormat::new("Package".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("Site".to_string(), true, "#666666".to_string()),
+            ColumnFormat::new("Status".to_string(), false, "#666666".to_string()),
+            ColumnFormat::new("File".to_string(), true, "#666666".to_string()),
+        ]
+    }
+    fn get_records(&self) -> &Vec<VerifyRecord> {
+        &self.records
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_verify_report_a() {
+        let dir_temp = tempdir().unwrap();
+        let dir_site = dir_temp.path().join("lib/python3.12/site-packages");
+        let dir_bin = dir_temp.path().join("bin");
+        let dir_dist_info = dir_site.join("foo-1.0.dist-info");
+        let dir_src = dir_site.join("foo");
+        fs::create_dir_all(&dir_dist_info).unwrap();
+        fs::create_dir_all(&dir_src).unwrap();
+        fs::create_dir_all(&dir_bin).unwrap();
+        let site = PathShared::from_path_buf(dir_site.clone());
+        let pkg = Package::from_dist_info("foo-1.0.dist-info", None, None).unwrap();
+
+        fs::write(dir_dist_info.join("INSTALLER"), "pip\n").unwrap();
+        fs::write(dir_src.join("__init__.py"), "x = 1\n").unwrap();
+        fs::write(dir_src.join("extra.py"), "").unwrap();
+        fs::create_dir(dir_src.join("__pycache__")).unwrap();
+        fs::write(dir_src.join("__pycache__/__init__.cpython-312.pyc"), "").unwrap();
+        // files of other packages in the site or in bin are not extra
+        fs::write(dir_site.join("six.py"), "").unwrap();
+        fs::write(dir_bin.join("foo"), "").unwrap();
+        fs::write(dir_bin.join("bar"), "").unwrap();
+        let hash_init = hash_file_record(&dir_src.join("__init__.py")).unwrap();
+        fs::write(
+            dir_dist_info.join("RECORD"),
+            format!(
+                "foo-1.0.dist-info/INSTALLER,sha256=zuuue4knoyJ-UwPPXg8fezS7VCrXJQrAP7zeNuwvFQg,4\n\
+                 foo-1.0.dist-info/RECORD,,\n\
+                 foo/__init__.py,{},6\n\
+                 foo/core.py,sha256=47DEQpj8HBSa-_TImW-5JCeuQeRkm5NMpJWZG3hSuFU,0\n\
+                 ../../../bin/foo,sha256=47DEQpj8HBSa-_TImW-5JCeuQeRkm5NMpJWZG3hSuFU,0\n",
+                hash_init
+            ),
+        )
+        .unwrap();
+
+        let mut package_to_sites = HashMap::new();
+        package_to_sites.insert(pkg, vec![site]);
+        let vr1 = VerifyReport::from_package_to_sites(&package_to_sites);
+        assert_eq!(vr1.len(), 2);
+        assert_eq!(
+            vr1.records[0].status,
+            vec![
+                (VerifyStatus::Missing, dir_src.join("core.py")),
+                (VerifyStatus::Extra, dir_src.join("extra.py")),
+            ]
+        );
+
+        // a hot-patched file is modified
+        fs::write(dir_src.join("__init__.py"), "x = 2\n").unwrap();
+        let vr2 = VerifyReport::from_package_to_sites(&package_to_sites);
+        assert_eq!(vr2.len(), 3);
+        assert_eq!(
+            vr2.records[0].status[0],
+            (VerifyStatus::Modified, dir_src.join("__init__.py"))
+        );
+    }
+
+    #[test]
+    fn test_is_outside_site_a() {
+        let site = Path::new("/usr/lib/python3.12/site-packages");
+        assert_eq!(is_outside_site(&site.join("foo/bar.py"), site), false);
+        assert_eq!(is_outside_site(&site.join("../../../bin/foo"), site), true);
+        assert_eq!(is_outside_site(Path::new("/usr/bin/foo"), site), true);
+    }
+}
@@ -5,6 +5,109 @@ use std::hash::Hasher;
 
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+// Labels of pre-releases, with the kind to which each is normalized.
+const PRE_LABELS: [(&str, PreRelease); 8] = [
+    ("a", PreRelease::Alpha),
+    ("alpha", PreRelease::Alpha),
+    ("b", PreRelease::Beta),
+    ("beta", PreRelease::Beta),
+    ("c", PreRelease::Candidate),
+    ("rc", PreRelease::Candidate),
+    ("pre", PreRelease::Candidate),
+    ("preview", PreRelease::Candidate),
+];
+
+const POST_LABELS: [&str; 3] = ["post", "rev", "r"];
+
+// Given a string, parse a number from its start, returning the number and the remainder.
+fn take_number(s: &str) -> Option<(u32, &str)> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    if end == 0 {
+        return None;
+    }
+    Some((s[..end].parse().ok()?, &s[end..]))
+}
+
+// Remove a leading "-", "_", or "." separator, if found.
+fn trim_separator(s: &str) -> &str {
+    s.strip_prefix(['-', '_', '.']).unwrap_or(s)
+}
+
+// Given the remainder of a version, parse a segment (e.g. "rc1", ".post2", or "-dev") whose label is one of `labels`, returning the index of the longest matching label, the number of the segment (zero if not given), and the remainder.
+fn take_segment<'a>(s: &'a str, labels: &[&str]) -> Option<(usize, u32, &'a str)> {
+    let s = trim_separator(s);
+    let (index, rest) = labels
+        .iter()
+        .enumerate()
+        .filter_map(|(i, label)| s.strip_prefix(label).map(|rest| (i, rest)))
+        .max_by_key(|(i, _)| labels[*i].len())?;
+    match take_number(trim_separator(rest)) {
+        Some((number, rest)) => Some((index, number, rest)),
+        None => Some((index, 0, rest)),
+    }
+}
+
+// Compare release segments with zero padding; a wildcard "*" is equal to any segment, and a trailing wildcard is equal to all remaining segments.
+fn cmp_release(a: &[VersionPart], b: &[VersionPart]) -> Ordering {
+    let is_trailing_wildcard = |parts: &[VersionPart], i: usize| {
+        i + 1 == parts.len() && matches!(&parts[i], VersionPart::Text(t) if t == "*")
+    };
+    let max_len = a.len().max(b.len());
+    for i in 0..max_len {
+        if is_trailing_wildcard(a, i) || is_trailing_wildcard(b, i) {
+            return Ordering::Equal;
+        }
+        // extend to max with zero padding
+        let a_part = a.get(i).unwrap_or(&VersionPart::Number(0));
+        let b_part = b.get(i).unwrap_or(&VersionPart::Number(0));
+
+        let ordering = match (a_part, b_part) {
+            (VersionPart::Number(a), VersionPart::Number(b)) => a.cmp(b),
+            (VersionPart::Text(a), VersionPart::Text(b)) => {
+                if a == "*" || b == "*" {
+                    Ordering::Equal
+                } else {
+                    a.cmp(b)
+                }
+            }
+            (VersionPart::Number(_), VersionPart::Text(b)) => {
+                if b == "*" {
+                    Ordering::Equal
+                } else {
+                    Ordering::Greater // numbers are always greater than text
+                }
+            }
+            (VersionPart::Text(a), VersionPart::Number(_)) => {
+                if a == "*" {
+                    Ordering::Equal
+                } else {
+                    Ordering::Less
+                }
+            }
+        };
+        if ordering != Ordering::Equal {
+            return ordering; // else, continue iteration
+        }
+    }
+    Ordering::Equal
+}
+
+// Compare local version labels: a version without a local label is less than one with a label; numeric segments are greater than text segments.
+fn cmp_local(a: &[VersionPart], b: &[VersionPart]) -> Ordering {
+    for (a_part, b_part) in a.iter().zip(b) {
+        let ordering = match (a_part, b_part) {
+            (VersionPart::Number(a), VersionPart::Number(b)) => a.cmp(b),
+            (VersionPart::Text(a), VersionPart::Text(b)) => a.cmp(b),
+            (VersionPart::Number(_), VersionPart::Text(_)) => Ordering::Greater,
+            (VersionPart::Text(_), VersionPart::Number(_)) => Ordering::Less,
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
 //------------------------------------------------------------------------------
 #[derive(Debug, Eq, Ord, PartialEq, PartialOrd, Clone, Hash, Serialize, Deserialize)]
 enum VersionPart {
@@ -12,9 +115,36 @@ enum VersionPart {
     Text(String),
 }
 
+impl VersionPart {
+    fn from_part(part: &str) -> Self {
+        if let Ok(number) = part.parse::<u32>() {
+            VersionPart::Number(number)
+        } else {
+            VersionPart::Text(part.to_string())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, Ord, PartialEq, PartialOrd, Hash)]
+enum PreRelease {
+    Alpha,
+    Beta,
+    Candidate,
+}
+
 //------------------------------------------------------------------------------
+/// A version, as specified in PEP 440, with an epoch (`1!2.0`), release segments, and optional pre-release (`2.0rc1`), post-release (`2.0.post1`), development release (`2.0.dev3`), and local (`2.0+ubuntu1`) segments. Release segments can be a wildcard ("*"), as used in requirements. Versions that cannot be parsed as PEP 440 are compared by their "."-delimited parts.
 #[derive(Debug, Clone)]
-pub(crate) struct VersionSpec(Vec<VersionPart>);
+pub struct VersionSpec {
+    epoch: u32,
+    release: Vec<VersionPart>,
+    pre: Option<(PreRelease, u32)>,
+    post: Option<u32>,
+    dev: Option<u32>,
+    local: Vec<VersionPart>,
+    // the version as provided, used for display and arbitrary equality
+    text: String,
+}
 
 impl Serialize for VersionSpec {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -36,99 +166,291 @@ impl<'de> Deserialize<'de> for VersionSpec {
 }
 
 impl VersionSpec {
-    pub(crate) fn new(version_str: &str) -> Self {
-        let parts = version_str
-            .split('.')
-            .map(|part| {
-                if let Ok(number) = part.parse::<u32>() {
-                    VersionPart::Number(number)
+    pub fn new(version_str: &str) -> Self {
+        Self::from_pep440(version_str).unwrap_or_else(|| VersionSpec {
+            epoch: 0,
+            release: version_str.split('.').map(VersionPart::from_part).collect(),
+            pre: None,
+            post: None,
+            dev: None,
+            local: Vec::new(),
+            text: version_str.to_string(),
+        })
+    }
+
+    // Parse a version as specified in PEP 440, permitting the alternative spellings of its normalization rules (e.g. `v1.0`, `1.0-alpha.1`, `1.0-1`). Returns None if the version is not valid.
+    fn from_pep440(version_str: &str) -> Option<Self> {
+        let lower = version_str.trim().to_lowercase();
+        let version = lower.strip_prefix('v').unwrap_or(&lower);
+        let (public, local) = match version.split_once('+') {
+            Some((public, local)) => (public, Some(local)),
+            None => (version, None),
+        };
+        let (epoch, mut rest) = match public.split_once('!') {
+            Some((epoch, rest)) => (epoch.parse().ok()?, rest),
+            None => (0, public),
+        };
+        let mut release = Vec::new();
+        loop {
+            if let Some(r) = rest.strip_prefix('*') {
+                release.push(VersionPart::Text("*".to_string()));
+                rest = r;
+            } else {
+                let (number, r) = take_number(rest)?;
+                release.push(VersionPart::Number(number));
+                rest = r;
+            }
+            match rest.strip_prefix('.') {
+                Some(r) if r.starts_with(|c: char| c.is_ascii_digit() || c == '*') => {
+                    rest = r
+                }
+                _ => break,
+            }
+        }
+        let labels: Vec<&str> = PRE_LABELS.iter().map(|(label, _)| *label).collect();
+        let mut pre = None;
+        if let Some((i, number, r)) = take_segment(rest, &labels) {
+            pre = Some((PRE_LABELS[i].1, number));
+            rest = r;
+        }
+        let mut post = None;
+        if let Some((_, number, r)) = take_segment(rest, &POST_LABELS) {
+            post = Some(number);
+            rest = r;
+        } else if let Some((number, r)) = rest.strip_prefix('-').and_then(take_number) {
+            // an implicit post-release, e.g. "1.0-1"
+            post = Some(number);
+            rest = r;
+        }
+        let mut dev = None;
+        if let Some((_, number, r)) = take_segment(rest, &["dev"]) {
+            dev = Some(number);
+            rest = r;
+        }
+        if !rest.is_empty() {
+            return None;
+        }
+        let local = match local {
+            Some("") => return None,
+            Some(local) => local
+                .split(['.', '-', '_'])
+                .map(VersionPart::from_part)
+                .collect(),
+            None => Vec::new(),
+        };
+        Some(VersionSpec {
+            epoch,
+            release,
+            pre,
+            post,
+            dev,
+            local,
+            text: version_str.to_string(),
+        })
+    }
+
+    /// Return the local label of this version (e.g. `cu118` of `2.1.0+cu118`), if defined.
+    pub(crate) fn get_local(&self) -> Option<&str> {
+        if self.local.is_empty() {
+            return None;
+        }
+        self.text.split_once('+').map(|(_, local)| local)
+    }
+
+    /// Return a copy of this version without its local label.
+    pub(crate) fn to_public(&self) -> Self {
+        VersionSpec {
+            local: Vec::new(),
+            text: self
+                .text
+                .split('+')
+                .next()
+                .unwrap_or(&self.text)
+                .to_string(),
+            ..self.clone()
+        }
+    }
+
+    /// Return true if this is a pre-release or development release.
+    pub(crate) fn is_prerelease(&self) -> bool {
+        self.pre.is_some() || self.dev.is_some()
+    }
+
+    /// Return true if this is a post-release.
+    pub(crate) fn is_postrelease(&self) -> bool {
+        self.post.is_some()
+    }
+
+    fn has_wildcard(&self) -> bool {
+        self.release
+            .iter()
+            .any(|part| matches!(part, VersionPart::Text(t) if t == "*"))
+    }
+
+    // Return true if the epoch and release segments are equal, ignoring pre-, post-, and development release segments.
+    fn is_same_release(&self, other: &Self) -> bool {
+        self.epoch == other.epoch
+            && cmp_release(&self.release, &other.release) == Ordering::Equal
+    }
+
+    // Return a key for ordering pre-, post-, and development release segments: a development release of a final release sorts before its pre-releases, pre-releases sort before the final release, and post-releases after.
+    fn to_suffix_key(&self) -> ((u8, u32), Option<u32>, (bool, u32)) {
+        let pre = match (self.pre, self.post, self.dev) {
+            (Some((kind, number)), _, _) => (kind as u8 + 1, number),
+            (None, None, Some(_)) => (0, 0),
+            (None, _, _) => (u8::MAX, 0),
+        };
+        let dev = match self.dev {
+            Some(number) => (false, number),
+            None => (true, 0),
+        };
+        (pre, self.post, dev)
+    }
+
+    // Compare without local version labels. If either release has a wildcard, only epochs and releases are compared.
+    fn cmp_public(&self, other: &Self) -> Ordering {
+        self.epoch
+            .cmp(&other.epoch)
+            .then_with(|| cmp_release(&self.release, &other.release))
+            .then_with(|| {
+                if self.has_wildcard() || other.has_wildcard() {
+                    Ordering::Equal
                 } else {
-                    VersionPart::Text(part.to_string())
+                    self.to_suffix_key().cmp(&other.to_suffix_key())
                 }
             })
-            .collect();
-        VersionSpec(parts)
     }
-    pub(crate) fn is_compatible(&self, other: &Self) -> bool {
+
+    /// Return true if this version matches `spec` with the `==` operator. A local label (e.g. `+cu118`) must match; see `to_public()` to ignore local labels.
+    pub(crate) fn is_equal(&self, spec: &Self) -> bool {
+        self.cmp(spec) == Ordering::Equal
+    }
+
+    /// Return true if this version matches `spec` with the `<=` operator.
+    pub(crate) fn is_less_equal(&self, spec: &Self) -> bool {
+        self.cmp(spec) != Ordering::Greater
+    }
+
+    /// Return true if this version matches `spec` with the `>=` operator.
+    pub(crate) fn is_greater_equal(&self, spec: &Self) -> bool {
+        self.cmp(spec) != Ordering::Less
+    }
+
+    /// Return true if this version matches `spec` with the exclusive `<` operator: pre-releases of the release of `spec` are excluded unless `spec` is itself a pre-release.
+    pub(crate) fn is_less(&self, spec: &Self) -> bool {
+        self.cmp_public(spec) == Ordering::Less
+            && !(self.is_prerelease()
+                && !spec.is_prerelease()
+                && self.is_same_release(spec))
+    }
+
+    /// Return true if this version matches `spec` with the exclusive `>` operator: post-releases and local versions of the release of `spec` are excluded unless `spec` is itself a post-release.
+    pub(crate) fn is_greater(&self, spec: &Self) -> bool {
+        self.cmp_public(spec) == Ordering::Greater
+            && !(self.is_postrelease()
+                && !spec.is_postrelease()
+                && self.is_same_release(spec))
+    }
+
+    /// Return true if this version matches `spec` with the compatible release operator `~=`: the version must be greater than or equal to `spec` and match the release of `spec` with its last segment replaced by a wildcard, such that `~=1.4.2` is equivalent to `>=1.4.2, ==1.4.*`.
+    pub(crate) fn is_compatible(&self, spec: &Self) -> bool {
         // https://packaging.python.org/en/latest/specifications/version-specifiers/#compatible-release
-        if let (
-            Some(VersionPart::Number(self_major)),
-            Some(VersionPart::Number(other_major)),
-        ) = (self.0.first(), other.0.first())
-        {
-            return self_major == other_major;
-        }
-        false
+        let len = spec.release.len().saturating_sub(1).max(1);
+        let mut release: Vec<VersionPart> =
+            spec.release.iter().take(len).cloned().collect();
+        release.push(VersionPart::Text("*".to_string()));
+        let prefix = VersionSpec {
+            epoch: spec.epoch,
+            release,
+            pre: None,
+            post: None,
+            dev: None,
+            local: Vec::new(),
+            text: String::new(),
+        };
+        self.is_greater_equal(spec) && self.is_equal(&prefix)
     }
-    pub(crate) fn is_arbitrary_equal(&self, other: &Self) -> bool {
+
+    /// Return true if this version matches `spec` with the arbitrary equality operator `===`: the versions, as provided, must be equal as case-insensitive strings, without normalization.
+    pub(crate) fn is_arbitrary_equal(&self, spec: &Self) -> bool {
         // https://packaging.python.org/en/latest/specifications/version-specifiers/#arbitrary-equality
-        self.to_string() == other.to_string()
+        self.text.trim().to_lowercase() == spec.text.trim().to_lowercase()
+    }
+
+    //--------------------------------------------------------------------------
+
+    // Return a version of the first `len` release segments, with the last segment incremented if `increment` is set, such as `1.5` for `1.4.2`, a `len` of two, and `increment`. All segments must be numbers.
+    fn to_release_prefix(&self, len: usize, increment: bool) -> Option<Self> {
+        if len == 0 || len > self.release.len() {
+            return None;
+        }
+        let mut parts = Vec::with_capacity(len);
+        for (i, part) in self.release.iter().take(len).enumerate() {
+            match part {
+                VersionPart::Number(n) if increment && i + 1 == len => {
+                    parts.push((n + 1).to_string())
+                }
+                VersionPart::Number(n) => parts.push(n.to_string()),
+                VersionPart::Text(_) => return None,
+            }
+        }
+        let release = parts.join(".");
+        if self.epoch > 0 {
+            Some(VersionSpec::new(&format!("{}!{}", self.epoch, release)))
+        } else {
+            Some(VersionSpec::new(&release))
+        }
+    }
+
+    /// For a version with a trailing wildcard (e.g. `1.4.*`), return the inclusive lower and exclusive upper bounds of the versions it matches (e.g. `1.4` and `1.5`).
+    pub(crate) fn to_wildcard_bounds(&self) -> Option<(Self, Self)> {
+        let len = self.release.len().checked_sub(1)?;
+        if !matches!(self.release.last(), Some(VersionPart::Text(t)) if t == "*") {
+            return None;
+        }
+        Some((
+            self.to_release_prefix(len, false)?,
+            self.to_release_prefix(len, true)?,
+        ))
+    }
+
+    /// For a compatible release (e.g. `1.4.2` of `~=1.4.2`), return the exclusive upper bound of the versions it matches (e.g. `1.5`).
+    pub(crate) fn to_compatible_upper(&self) -> Option<Self> {
+        let len = self.release.len().saturating_sub(1).max(1);
+        self.to_release_prefix(len, true)
     }
 }
 impl fmt::Display for VersionSpec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let version_string = self
-            .0
-            .iter()
-            .map(|part| match part {
-                VersionPart::Number(num) => num.to_string(),
-                VersionPart::Text(text) => text.clone(),
-            })
-            .collect::<Vec<_>>()
-            .join(".");
-        write!(f, "{}", version_string)
+        write!(f, "{}", self.text)
     }
 }
 
-// This hash implementation does not treate wildcards "*" special, which may be an issue as PartialEq does
+// This hash implementation is consistent with equality, ignoring trailing zeros of the release, but does not treat wildcards "*" special, which may be an issue as PartialEq does
 impl Hash for VersionSpec {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        for part in &self.0 {
-            part.hash(state);
+        self.epoch.hash(state);
+        let mut release = self.release.as_slice();
+        while let Some((VersionPart::Number(0), rest)) = release.split_last() {
+            release = rest;
         }
+        release.hash(state);
+        self.to_suffix_key().hash(state);
+        self.local.hash(state);
     }
 }
 
-// This ordering implemenation is handling wild cards and zero-padding, but may not yet be handling "post" release correctly
-// https://packaging.python.org/en/latest/specifications/version-specifiers/#post-releases
+// This ordering implementation follows PEP 440, handling epochs, zero-padding, pre-, post-, and development releases, and local versions; wildcards are equal to any release segment
+// https://packaging.python.org/en/latest/specifications/version-specifiers/#summary-of-permitted-suffixes-and-relative-ordering
 impl Ord for VersionSpec {
     fn cmp(&self, other: &Self) -> Ordering {
-        let max_len = self.0.len().max(other.0.len());
-        for i in 0..max_len {
-            // extend to max with zero padding
-            let self_part = self.0.get(i).unwrap_or(&VersionPart::Number(0));
-            let other_part = other.0.get(i).unwrap_or(&VersionPart::Number(0));
-
-            let ordering = match (self_part, other_part) {
-                (VersionPart::Number(a), VersionPart::Number(b)) => a.cmp(b),
-                (VersionPart::Text(a), VersionPart::Text(b)) => {
-                    if a == "*" || b == "*" {
-                        Ordering::Equal
-                    } else {
-                        a.cmp(b)
-                    }
-                }
-                (VersionPart::Number(_), VersionPart::Text(b)) => {
-                    if b == "*" {
-                        Ordering::Equal
-                    } else {
-                        Ordering::Greater // numbers are always greater than text
-                    }
-                }
-                (VersionPart::Text(a), VersionPart::Number(_)) => {
-                    if a == "*" {
-                        Ordering::Equal
-                    } else {
-                        Ordering::Less
-                    }
-                }
-            };
-            if ordering != Ordering::Equal {
-                return ordering; // else, continue iteration
+        self.cmp_public(other).then_with(|| {
+            if self.has_wildcard() || other.has_wildcard() {
+                Ordering::Equal
+            } else {
+                cmp_local(&self.local, &other.local)
             }
-        }
-        // self.0.len().cmp(&other.0.len())
-        Ordering::Equal
+        })
     }
 }
 impl PartialOrd for VersionSpec {
@@ -138,31 +460,7 @@ impl PartialOrd for VersionSpec {
 }
 impl PartialEq for VersionSpec {
     fn eq(&self, other: &Self) -> bool {
-        let max_len = self.0.len().max(other.0.len());
-        for i in 0..max_len {
-            // extend to max with zero padding
-            let self_part = self.0.get(i).unwrap_or(&VersionPart::Number(0));
-            let other_part = other.0.get(i).unwrap_or(&VersionPart::Number(0));
-
-            match (self_part, other_part) {
-                // if wildcard "*" both equal
-                (VersionPart::Text(a), VersionPart::Text(b)) if a == "*" || b == "*" => {
-                    continue
-                }
-                (VersionPart::Text(a), VersionPart::Number(_)) if a == "*" => continue,
-                (VersionPart::Number(_), VersionPart::Text(b)) if b == "*" => continue,
-                // parts must match exactly
-                (VersionPart::Number(a), VersionPart::Number(b)) if a != b => {
-                    return false
-                }
-                (VersionPart::Text(a), VersionPart::Text(b)) if a != b => return false,
-                // not equal
-                (VersionPart::Number(_), VersionPart::Text(_)) => return false,
-                (VersionPart::Text(_), VersionPart::Number(_)) => return false,
-                _ => {} // continue
-            }
-        }
-        true
+        self.cmp(other) == Ordering::Equal
     }
 }
 
@@ -170,6 +468,7 @@ impl Eq for VersionSpec {}
 
 //------------------------------------------------------------------------------
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison, clippy::single_component_path_imports)]
 mod tests {
 
     use super::*;
@@ -204,14 +503,95 @@ mod tests {
         assert_eq!(VersionSpec::new("1.7.1") < VersionSpec::new("1.8"), true);
         assert_eq!(
             VersionSpec::new("1.7.0.post1") > VersionSpec::new("1.7"),
-            false
+            true
         );
         assert_eq!(
             VersionSpec::new("1.7.1") > VersionSpec::new("1.7.post1"),
             true
         );
-        // this is supposed to be true: >1.7.post2 will allow 1.7.1 and 1.7.0.post3 but not 1.7.0.
-        // assert_eq!(VersionSpec::new("1.7.0") > VersionSpec::new("1.7.post1"), false);
+        // >1.7.post2 will allow 1.7.1 and 1.7.0.post3 but not 1.7.0.
+        assert_eq!(
+            VersionSpec::new("1.7.0") > VersionSpec::new("1.7.post1"),
+            false
+        );
+        assert_eq!(
+            VersionSpec::new("1.7.0.post3") > VersionSpec::new("1.7.post2"),
+            true
+        );
+    }
+    #[test]
+    fn test_version_spec_f() {
+        // ordering from PEP 440
+        let versions = [
+            "1.0.dev456",
+            "1.0a1",
+            "1.0a2.dev456",
+            "1.0a12.dev456",
+            "1.0a12",
+            "1.0b1.dev456",
+            "1.0b2",
+            "1.0b2.post345.dev456",
+            "1.0b2.post345",
+            "1.0rc1.dev456",
+            "1.0rc1",
+            "1.0",
+            "1.0+abc.5",
+            "1.0+abc.7",
+            "1.0+5",
+            "1.0.post456.dev34",
+            "1.0.post456",
+            "1.0.15",
+            "1.1.dev1",
+            "1!0.5",
+        ];
+        for pair in versions.windows(2) {
+            assert!(
+                VersionSpec::new(pair[0]) < VersionSpec::new(pair[1]),
+                "{} < {}",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+    #[test]
+    fn test_version_spec_g() {
+        // alternative spellings are normalized
+        assert_eq!(VersionSpec::new("1.0-ALPHA.1"), VersionSpec::new("1.0a1"));
+        assert_eq!(VersionSpec::new("1.0c1"), VersionSpec::new("1.0rc1"));
+        assert_eq!(VersionSpec::new("1.0-1"), VersionSpec::new("1.0.post1"));
+        assert_eq!(VersionSpec::new("1.0.rev"), VersionSpec::new("1.0.post0"));
+        assert_eq!(VersionSpec::new("v1.0_dev2"), VersionSpec::new("1.0.dev2"));
+        assert_eq!(VersionSpec::new("0!1.0"), VersionSpec::new("1.0"));
+        assert_ne!(VersionSpec::new("1!1.0"), VersionSpec::new("1.0"));
+        // display is not normalized
+        assert_eq!(VersionSpec::new("1.0-ALPHA.1").to_string(), "1.0-ALPHA.1");
+    }
+    #[test]
+    fn test_version_spec_local_a() {
+        let v1 = VersionSpec::new("2.1.0+cu118");
+        assert_eq!(v1.get_local(), Some("cu118"));
+        assert_eq!(v1.to_public().to_string(), "2.1.0");
+        assert_eq!(v1.to_public().get_local(), None);
+        assert_eq!(VersionSpec::new("2.1.0").get_local(), None);
+        assert!(v1 > VersionSpec::new("2.1.0"));
+    }
+    #[test]
+    fn test_version_spec_h() {
+        let v = |s: &str| VersionSpec::new(s);
+        assert!(v("2.0rc1").is_greater_equal(&v("2.0.0rc1")));
+        assert!(!v("2.0b3").is_greater_equal(&v("2.0.0rc1")));
+        assert!(!v("1.0+local").is_equal(&v("1.0")));
+        assert!(v("1.0+local").to_public().is_equal(&v("1.0")));
+        assert!(!v("1.0").is_equal(&v("1.0+local")));
+        assert!(v("1.0+local").is_greater_equal(&v("1.0")));
+        assert!(!v("1.0+local").is_less_equal(&v("1.0")));
+        assert!(!v("2.0rc1").is_less(&v("2.0")));
+        assert!(v("2.0rc1").is_less(&v("2.0rc2")));
+        assert!(!v("1.7.0.post1").is_greater(&v("1.7")));
+        assert!(!v("1.7+local").is_greater(&v("1.7")));
+        assert!(v("1.7.0.post3").is_greater(&v("1.7.post2")));
+        assert!(v("2.0rc1").is_prerelease());
+        assert!(!v("2.0.post1").is_prerelease());
     }
     #[test]
     fn test_version_is_major_compatible_a() {
@@ -225,10 +605,31 @@ mod tests {
         );
         assert_eq!(
             VersionSpec::new("2.2").is_compatible(&VersionSpec::new("2.2.3.9")),
+            false
+        );
+        assert_eq!(
+            VersionSpec::new("2.9").is_compatible(&VersionSpec::new("2.2")),
             true
         );
     }
     #[test]
+    fn test_version_is_compatible_b() {
+        let is_compatible = |v: &str, spec: &str| {
+            VersionSpec::new(v).is_compatible(&VersionSpec::new(spec))
+        };
+        assert!(is_compatible("1.4.2", "1.4.2"));
+        assert!(is_compatible("1.4.9.1", "1.4.2"));
+        assert!(is_compatible("1.4.3.post1", "1.4.2"));
+        assert!(!is_compatible("1.4.1", "1.4.2"));
+        assert!(!is_compatible("1.5.0", "1.4.2"));
+        assert!(is_compatible("2.3", "2.2.post3"));
+        assert!(!is_compatible("2.2", "2.2.post3"));
+        assert!(is_compatible("1.4.5", "1.4.5a4"));
+        assert!(!is_compatible("1.4.5a3", "1.4.5a4"));
+        assert!(!is_compatible("1!1.4.5", "1.4.2"));
+        assert!(is_compatible("1.4.2+local", "1.4.2"));
+    }
+    #[test]
     fn test_version_is_major_compatible_b() {
         assert_eq!(
             VersionSpec::new("2.2-2").is_arbitrary_equal(&VersionSpec::new("2.2-2")),
@@ -247,6 +648,41 @@ mod tests {
                 .is_arbitrary_equal(&VersionSpec::new("1.0+downstream1")),
             false
         );
+        assert_eq!(
+            VersionSpec::new("1.0+Local")
+                .is_arbitrary_equal(&VersionSpec::new("1.0+local")),
+            true
+        );
+        assert_eq!(
+            VersionSpec::new("1.0.0").is_arbitrary_equal(&VersionSpec::new("1.0")),
+            false
+        );
+    }
+    #[test]
+    fn test_version_bounds_a() {
+        let (lower, upper) = VersionSpec::new("1.4.*").to_wildcard_bounds().unwrap();
+        assert_eq!(
+            (lower.to_string(), upper.to_string()),
+            ("1.4".into(), "1.5".into())
+        );
+        let (lower, upper) = VersionSpec::new("1!2.*").to_wildcard_bounds().unwrap();
+        assert_eq!(
+            (lower.to_string(), upper.to_string()),
+            ("1!2".into(), "1!3".into())
+        );
+        assert!(VersionSpec::new("1.4").to_wildcard_bounds().is_none());
+        assert!(VersionSpec::new("*").to_wildcard_bounds().is_none());
+
+        let upper = |v: &str| {
+            VersionSpec::new(v)
+                .to_compatible_upper()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(upper("1.4.2"), "1.5");
+        assert_eq!(upper("2.2"), "3");
+        assert_eq!(upper("2.2.post3"), "3");
+        assert_eq!(upper("1"), "2");
     }
     //--------------------------------------------------------------------------
     #[test]
@@ -0,0 +1,233 @@
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+use serde::Deserialize;
+
+use crate::package_match::match_str;
+use crate::util::iso8601_to_days;
+use crate::util::name_to_key;
+use crate::util::time_to_iso8601;
+use crate::util::ResultDynError;
+use crate::validation_report::ValidationExplain;
+
+// The name of the file, in the current directory, from which waivers are read if not otherwise provided.
+const WAIVER_FILE: &str = ".fetter-ignore";
+
+//------------------------------------------------------------------------------
+/// A waiver of validation failures for a package. The `package` can use glob-like wildcards; if `code` is not provided, all explanations are waived, otherwise `code` is a code, name, or category as used by `--select`. If `expires` (a YYYY-MM-DD date) is provided, the waiver does not apply after that date.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Waiver {
+    package: String,
+    code: Option<String>,
+    expires: Option<String>,
+    reason: Option<String>,
+}
+
+impl Waiver {
+    fn is_active(&self, today: i64) -> bool {
+        match self.expires.as_deref().and_then(iso8601_to_days) {
+            Some(expires) => today <= expires,
+            None => true,
+        }
+    }
+
+    fn is_match(&self, key: &str, explain: ValidationExplain) -> bool {
+        let selected = match &self.code {
+            Some(code) => ValidationExplain::from_selector(code).contains(&explain),
+            None => true,
+        };
+        selected && match_str(&name_to_key(&self.package), key, true)
+    }
+
+    /// Return a description of this waiver, as shown in reports.
+    fn to_note(&self) -> String {
+        let mut note = self.reason.clone().unwrap_or_else(|| "waived".to_string());
+        if let Some(expires) = &self.expires {
+            note.push_str(&format!(" (expires {})", expires));
+        }
+        note
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct WaiverFile {
+    #[serde(default)]
+    ignore: Vec<Waiver>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PyProjectTool {
+    #[serde(default)]
+    fetter: WaiverFile,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PyProjectFile {
+    #[serde(default)]
+    tool: PyProjectTool,
+}
+
+//------------------------------------------------------------------------------
+/// A collection of waivers, read from the `[[ignore]]` tables of a `.fetter-ignore` TOML file or the `[[tool.fetter.ignore]]` tables of a pyproject.toml.
+#[derive(Debug, Default)]
+pub(crate) struct Waivers {
+    waivers: Vec<Waiver>,
+}
+
+impl Waivers {
+    fn validate(waivers: Vec<Waiver>) -> ResultDynError<Self> {
+        for waiver in waivers.iter() {
+            if let Some(code) = &waiver.code {
+                if ValidationExplain::from_selector(code).is_empty() {
+                    return Err(format!("Unknown validation code: {}", code).into());
+                }
+            }
+            if let Some(expires) = &waiver.expires {
+                if expires.len() != 10 || iso8601_to_days(expires).is_none() {
+                    return Err(format!("Invalid expiry date: {}", expires).into());
+                }
+            }
+        }
+        Ok(Waivers { waivers })
+    }
+
+    pub(crate) fn from_str(content: &str) -> ResultDynError<Self> {
+        let wf: WaiverFile = toml::from_str(content)?;
+        Self::validate(wf.ignore)
+    }
+
+    pub(crate) fn from_pyproject(content: &str) -> ResultDynError<Self> {
+        let pf: PyProjectFile = toml::from_str(content)?;
+        Self::validate(pf.tool.fetter.ignore)
+    }
+
+    /// Read waivers from a file; files named pyproject.toml are read from `[[tool.fetter.ignore]]`.
+    pub(crate) fn from_file(file_path: &Path) -> ResultDynError<Self> {
+        let content = fs::read_to_string(file_path)?;
+        if file_path.file_name().is_some_and(|n| n == "pyproject.toml") {
+            Self::from_pyproject(&content)
+        } else {
+            Self::from_str(&content)
+        }
+    }
+
+    /// Read waivers from a `.fetter-ignore` file in `dir`, or, if not found, from a pyproject.toml in `dir`. If neither file is found, no waivers are returned.
+    pub(crate) fn from_dir(dir: &Path) -> ResultDynError<Self> {
+        let fp = dir.join(WAIVER_FILE);
+        if fp.is_file() {
+            return Self::from_file(&fp);
+        }
+        let fp = dir.join("pyproject.toml");
+        if fp.is_file() {
+            return Self::from_file(&fp);
+        }
+        Ok(Waivers::default())
+    }
+
+    /// Return the note of the first waiver, active on the `today` day since the Unix epoch, that matches the package key and explanation.
+    pub(crate) fn get_note(
+        &self,
+        key: &str,
+        explain: ValidationExplain,
+        today: i64,
+    ) -> Option<String> {
+        self.waivers
+            .iter()
+            .find(|w| w.is_active(today) && w.is_match(key, explain))
+            .map(|w| w.to_note())
+    }
+}
+
+/// Return the current day as days since the Unix epoch.
+pub(crate) fn days_today() -> i64 {
+    iso8601_to_days(&time_to_iso8601(SystemTime::now())).unwrap_or_default()
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const WAIVERS: &str = r#"
+[[ignore]]
+package = "numpy"
+code = "V003"
+expires = "2024-06-30"
+reason = "Pinned until the next release"
+
+[[ignore]]
+package = "zope-*"
+code = "inventory"
+
+[[ignore]]
+package = "Static_Frame"
+"#;
+
+    #[test]
+    fn test_waivers_a() {
+        let ws = Waivers::from_str(WAIVERS).unwrap();
+        assert_eq!(ws.waivers.len(), 3);
+        let today = iso8601_to_days("2024-06-30").unwrap();
+        assert_eq!(
+            ws.get_note("numpy", ValidationExplain::Misdefined, today),
+            Some("Pinned until the next release (expires 2024-06-30)".to_string())
+        );
+        assert_eq!(
+            ws.get_note("numpy", ValidationExplain::Missing, today),
+            None
+        );
+        // expired waivers do not apply
+        assert_eq!(
+            ws.get_note("numpy", ValidationExplain::Misdefined, today + 1),
+            None
+        );
+        assert_eq!(
+            ws.get_note("zope_interface", ValidationExplain::Unrequired, today),
+            Some("waived".to_string())
+        );
+        assert_eq!(
+            ws.get_note("zope_interface", ValidationExplain::Missing, today),
+            None
+        );
+        assert!(ws
+            .get_note("static_frame", ValidationExplain::Missing, today)
+            .is_some());
+    }
+
+    #[test]
+    fn test_waivers_b() {
+        assert!(
+            Waivers::from_str("[[ignore]]\npackage = \"numpy\"\ncode = \"V999\"\n")
+                .is_err()
+        );
+        assert!(Waivers::from_str(
+            "[[ignore]]\npackage = \"numpy\"\nexpires = \"2024-6-30\"\n"
+        )
+        .is_err());
+        assert!(Waivers::from_str("[[ignore]]\ncode = \"V001\"\n").is_err());
+    }
+
+    #[test]
+    fn test_waivers_from_dir_a() {
+        let dir = tempdir().unwrap();
+        assert_eq!(Waivers::from_dir(dir.path()).unwrap().waivers.len(), 0);
+
+        fs::write(
+            dir.path().join("pyproject.toml"),
+            "[project]\nname = \"foo\"\n\n[[tool.fetter.ignore]]\npackage = \"numpy\"\ncode = \"Misdefined\"\n",
+        )
+        .unwrap();
+        let ws = Waivers::from_dir(dir.path()).unwrap();
+        assert_eq!(ws.waivers.len(), 1);
+        assert!(ws
+            .get_note("numpy", ValidationExplain::Misdefined, 0)
+            .is_some());
+
+        // a .fetter-ignore file takes precedence
+        fs::write(dir.path().join(WAIVER_FILE), WAIVERS).unwrap();
+        assert_eq!(Waivers::from_dir(dir.path()).unwrap().waivers.len(), 3);
+    }
+}
@@ -0,0 +1,81 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::table::ColumnFormat;
+use crate::table::Rowable;
+use crate::table::RowableContext;
+use crate::table::Tableable;
+
+//------------------------------------------------------------------------------
+/// An executable or site that could not be scanned; the scan continued without it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct ScanWarning {
+    pub(crate) path: PathBuf,
+    pub(crate) message: String,
+}
+
+impl ScanWarning {
+    pub(crate) fn new(path: PathBuf, message: String) -> Self {
+        ScanWarning { path, message }
+    }
+}
+
+impl fmt::Display for ScanWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.message)
+    }
+}
+
+impl Rowable for ScanWarning {
+    fn to_rows(&self, _context: &RowableContext) -> Vec<Vec<String>> {
+        vec![vec![self.path.display().to_string(), self.message.clone()]]
+    }
+}
+
+//------------------------------------------------------------------------------
+#[derive(Debug)]
+pub(crate) struct WarningReport {
+    records: Vec<ScanWarning>,
+}
+
+impl WarningReport {
+    pub(crate) fn from_warnings(warnings: &[ScanWarning]) -> Self {
+        let mut records = warnings.to_vec();
+        records.sort();
+        WarningReport { records }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+impl Tableable<ScanWarning> for WarningReport {
+    fn get_header(&self) -> Vec<ColumnFormat> {
+        vec![
+            ColumnFormat::new("Path".to_string(), true, "#666666".to_string()),
+            ColumnFormat::new("Warning".to_string(), false, "#666666".to_string()),
+        ]
+    }
+    fn get_records(&self) -> &Vec<ScanWarning> {
+        &self.records
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_warning_report_a() {
+        let warnings = vec![
+            ScanWarning::new(PathBuf::from("/b"), "permission denied".to_string()),
+            ScanWarning::new(PathBuf::from("/a"), "not found".to_string()),
+        ];
+        let wr = WarningReport::from_warnings(&warnings);
+        assert!(!wr.is_empty());
+        assert_eq!(wr.get_records()[0].path, PathBuf::from("/a"));
+        assert_eq!(wr.get_records()[1].to_string(), "/b: permission denied");
+    }
+}
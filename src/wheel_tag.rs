@@ -0,0 +1,231 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::dep_marker::MarkerEnv;
+
+// Given a Python tag suffix such as "310" or "3", return the major and optional minor version.
+fn tag_to_version(digits: &str) -> Option<(u32, Option<u32>)> {
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let major = digits[..1].parse().ok()?;
+    let minor = if digits.len() > 1 {
+        Some(digits[1..].parse().ok()?)
+    } else {
+        None
+    };
+    Some((major, minor))
+}
+
+// A minor version of a tag is compatible if not defined or not greater than the running minor version.
+fn is_minor_within(minor: Option<u32>, running: u32) -> bool {
+    match minor {
+        Some(minor) => minor <= running,
+        None => true,
+    }
+}
+
+// Given a `python_version` environment marker value such as "3.12", return the major and minor version.
+fn env_to_version(value: &str) -> Option<(u32, u32)> {
+    let mut parts = value.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+//------------------------------------------------------------------------------
+/// A wheel compatibility tag, as recorded with `Tag` fields in the WHEEL file of a dist-info directory.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct WheelTag {
+    python: String,
+    abi: String,
+    platform: String,
+}
+
+impl fmt::Display for WheelTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}-{}", self.python, self.abi, self.platform)
+    }
+}
+
+impl WheelTag {
+    /// Parse a tag, expanding compressed tag sets (e.g. "py2.py3-none-any") into all tags.
+    pub(crate) fn from_str(tag: &str) -> Vec<Self> {
+        let parts: Vec<&str> = tag.trim().split('-').collect();
+        if parts.len() != 3 {
+            return Vec::new();
+        }
+        let mut tags = Vec::new();
+        for python in parts[0].split('.') {
+            for abi in parts[1].split('.') {
+                for platform in parts[2].split('.') {
+                    tags.push(WheelTag {
+                        python: python.to_string(),
+                        abi: abi.to_string(),
+                        platform: platform.to_string(),
+                    });
+                }
+            }
+        }
+        tags
+    }
+
+    /// Return all tags defined in the content of a WHEEL file.
+    pub(crate) fn from_wheel_str(content: &str) -> Vec<Self> {
+        content
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .filter(|(key, _)| key.trim().eq_ignore_ascii_case("tag"))
+            .flat_map(|(_, value)| Self::from_str(value))
+            .collect()
+    }
+
+    /// Return all tags defined in the WHEEL file of a dist-info directory; if the file is not found, no tags are returned.
+    pub(crate) fn from_dist_info(dist_info: &Path) -> Vec<Self> {
+        fs::read_to_string(dist_info.join("WHEEL"))
+            .map(|content| Self::from_wheel_str(&content))
+            .unwrap_or_default()
+    }
+
+    //--------------------------------------------------------------------------
+
+    fn is_python_compatible(&self, env: &MarkerEnv) -> bool {
+        let running = match env_to_version(env.get("python_version")) {
+            Some(running) => running,
+            None => return true,
+        };
+        let implementation = env.get("implementation_name");
+        let (prefix, digits) = self.python.split_at(self.python.len().min(2));
+        let (major, minor) = match tag_to_version(digits) {
+            Some(version) => version,
+            None => return true, // an unknown tag is not rejected
+        };
+        if major != running.0 {
+            return false;
+        }
+        match prefix {
+            // generic tags are compatible with all later minor versions
+            "py" => is_minor_within(minor, running.1),
+            "cp" => {
+                if !implementation.is_empty() && implementation != "cpython" {
+                    return false;
+                }
+                if self.abi == "abi3" || self.abi == "none" {
+                    is_minor_within(minor, running.1)
+                } else {
+                    minor == Some(running.1)
+                }
+            }
+            "pp" => {
+                (implementation.is_empty() || implementation == "pypy")
+                    && is_minor_within(minor, running.1)
+            }
+            _ => true,
+        }
+    }
+
+    fn is_platform_compatible(&self, env: &MarkerEnv) -> bool {
+        let platform = self.platform.to_lowercase();
+        let sys_platform = env.get("sys_platform");
+        let machine = env.get("platform_machine").to_lowercase();
+        if platform == "any" || sys_platform.is_empty() {
+            return true;
+        }
+        let is_arch = |arch: &str| machine.is_empty() || machine == arch;
+        let is_suffix =
+            machine.is_empty() || platform.ends_with(&format!("_{}", machine));
+        if platform.starts_with("manylinux")
+            || platform.starts_with("musllinux")
+            || platform.starts_with("linux")
+        {
+            sys_platform.starts_with("linux") && is_suffix
+        } else if platform.starts_with("macosx") {
+            sys_platform == "darwin"
+                && (platform.ends_with("_universal")
+                    || (platform.ends_with("_universal2")
+                        && (is_arch("x86_64") || is_arch("arm64")))
+                    || (platform.ends_with("_intel") && is_arch("x86_64"))
+                    || is_suffix)
+        } else if platform.starts_with("win") {
+            sys_platform == "win32"
+                && match platform.as_str() {
+                    "win_amd64" => is_arch("amd64"),
+                    "win_arm64" => is_arch("arm64"),
+                    _ => true,
+                }
+        } else {
+            // other platforms, such as emscripten or ios, are not rejected
+            true
+        }
+    }
+
+    /// Return true if this tag can be installed on the environment. Values not defined by the environment, such as the machine when inferred from a site path, are not considered.
+    pub(crate) fn is_compatible(&self, env: &MarkerEnv) -> bool {
+        self.is_python_compatible(env) && self.is_platform_compatible(env)
+    }
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_env(version: &str, platform: &str, machine: &str) -> MarkerEnv {
+        MarkerEnv::from_pairs(&[
+            ("python_version", version),
+            ("implementation_name", "cpython"),
+            ("sys_platform", platform),
+            ("platform_machine", machine),
+        ])
+    }
+
+    #[test]
+    fn test_from_str_a() {
+        let tags = WheelTag::from_str("py2.py3-none-any");
+        assert_eq!(
+            tags.iter().map(|t| t.to_string()).collect::<Vec<_>>(),
+            vec!["py2-none-any", "py3-none-any"]
+        );
+        assert!(WheelTag::from_str("cp312").is_empty());
+    }
+
+    #[test]
+    fn test_from_wheel_str_a() {
+        let content = "Wheel-Version: 1.0\nGenerator: bdist_wheel (0.43.0)\nRoot-Is-Purelib: false\nTag: cp312-cp312-manylinux_2_17_x86_64\nTag: cp312-cp312-manylinux2014_x86_64\n";
+        let tags = WheelTag::from_wheel_str(content);
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[1].to_string(), "cp312-cp312-manylinux2014_x86_64");
+    }
+
+    #[test]
+    fn test_is_compatible_a() {
+        let env = to_env("3.12", "linux", "x86_64");
+        let is_compatible = |tag: &str| WheelTag::from_str(tag)[0].is_compatible(&env);
+        assert!(is_compatible("py3-none-any"));
+        assert!(is_compatible("cp312-cp312-manylinux_2_17_x86_64"));
+        assert!(is_compatible("cp38-abi3-musllinux_1_1_x86_64"));
+        assert!(!is_compatible("py2-none-any"));
+        assert!(!is_compatible("cp310-cp310-manylinux_2_17_x86_64"));
+        assert!(!is_compatible("cp313-abi3-manylinux_2_17_x86_64"));
+        assert!(!is_compatible("cp312-cp312-manylinux_2_17_aarch64"));
+        assert!(!is_compatible("cp312-cp312-macosx_11_0_arm64"));
+        assert!(!is_compatible("cp312-cp312-win_amd64"));
+        assert!(!is_compatible("pp310-pypy310_pp73-manylinux_2_17_x86_64"));
+    }
+
+    #[test]
+    fn test_is_compatible_b() {
+        let env = to_env("3.11", "darwin", "arm64");
+        let is_compatible = |tag: &str| WheelTag::from_str(tag)[0].is_compatible(&env);
+        assert!(is_compatible("cp311-cp311-macosx_11_0_arm64"));
+        assert!(is_compatible("cp311-cp311-macosx_10_9_universal2"));
+        assert!(!is_compatible("cp311-cp311-macosx_10_9_x86_64"));
+        assert!(!is_compatible("cp311-cp311-manylinux_2_17_aarch64"));
+
+        // without a machine, only the platform is considered
+        let env = to_env("3.11", "linux", "");
+        assert!(WheelTag::from_str("cp311-cp311-manylinux_2_17_aarch64")[0]
+            .is_compatible(&env));
+    }
+}
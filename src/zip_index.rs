@@ -0,0 +1,481 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+const SIG_EOCD: u32 = 0x06054b50;
+const SIG_CENTRAL: u32 = 0x02014b50;
+const SIG_LOCAL: u32 = 0x04034b50;
+// the end of central directory record is 22 bytes, followed by a comment of up to 65535 bytes
+const EOCD_SEARCH: usize = 22 + 65535;
+
+// Extensions of zip archives that can be sites: zipapps, zipped eggs, and zip files added to `sys.path`.
+const ZIP_EXTENSIONS: [&str; 3] = ["pyz", "egg", "zip"];
+
+fn to_u16(data: &[u8], pos: usize) -> io::Result<u16> {
+    data.get(pos..pos + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| invalid("truncated zip archive"))
+}
+
+fn to_u32(data: &[u8], pos: usize) -> io::Result<u32> {
+    data.get(pos..pos + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| invalid("truncated zip archive"))
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Return true if the path is a file with the extension of a zip archive that can contain packages, such as a zipapp (`.pyz`) or a zipped egg.
+pub(crate) fn is_zip_archive(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ZIP_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        && path.is_file()
+}
+
+//------------------------------------------------------------------------------
+// Huffman decoding for deflate, after the canonical decoding of zlib's "puff".
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99,
+    115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025,
+    1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12,
+    12, 13, 13,
+];
+// the order in which code length code lengths are given in a dynamic block
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, n: u32) -> io::Result<u32> {
+        while self.count < n {
+            let byte = *self
+                .data
+                .get(self.pos)
+                .ok_or_else(|| invalid("truncated deflate stream"))?;
+            self.buffer |= (byte as u32) << self.count;
+            self.pos += 1;
+            self.count += 8;
+        }
+        let value = self.buffer & ((1u32 << n) - 1);
+        self.buffer = self.buffer.checked_shr(n).unwrap_or(0);
+        self.count -= n;
+        Ok(value)
+    }
+
+    // Discard remaining bits of the current byte, as done before a stored block.
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+    }
+}
+
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn from_lengths(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0u16; 16];
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0u16; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, br: &mut BitReader) -> io::Result<u16> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for len in 1..16 {
+            code |= br.bits(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - count < first {
+                return self
+                    .symbols
+                    .get((index + code - first) as usize)
+                    .copied()
+                    .ok_or_else(|| invalid("invalid deflate code"));
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err(invalid("invalid deflate code"))
+    }
+}
+
+fn inflate_codes(
+    br: &mut BitReader,
+    out: &mut Vec<u8>,
+    lit: &Huffman,
+    dist: &Huffman,
+) -> io::Result<()> {
+    loop {
+        let symbol = lit.decode(br)? as usize;
+        if symbol < 256 {
+            out.push(symbol as u8);
+        } else if symbol == 256 {
+            return Ok(());
+        } else {
+            let i = symbol - 257;
+            if i >= LENGTH_BASE.len() {
+                return Err(invalid("invalid deflate length"));
+            }
+            let len = LENGTH_BASE[i] as usize + br.bits(LENGTH_EXTRA[i] as u32)? as usize;
+            let d = dist.decode(br)? as usize;
+            if d >= DIST_BASE.len() {
+                return Err(invalid("invalid deflate distance"));
+            }
+            let distance =
+                DIST_BASE[d] as usize + br.bits(DIST_EXTRA[d] as u32)? as usize;
+            if distance > out.len() {
+                return Err(invalid("invalid deflate distance"));
+            }
+            let start = out.len() - distance;
+            for i in 0..len {
+                out.push(out[start + i]);
+            }
+        }
+    }
+}
+
+/// Decompress a raw deflate stream, as used by zip entries compressed with method 8.
+fn inflate(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut br = BitReader {
+        data,
+        pos: 0,
+        buffer: 0,
+        count: 0,
+    };
+    let mut out = Vec::new();
+    loop {
+        let is_last = br.bits(1)? == 1;
+        match br.bits(2)? {
+            0 => {
+                br.align();
+                let len = to_u16(data, br.pos)? as usize;
+                let start = br.pos + 4;
+                let block = data
+                    .get(start..start + len)
+                    .ok_or_else(|| invalid("truncated deflate stream"))?;
+                out.extend_from_slice(block);
+                br.pos = start + len;
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                for (i, len) in lengths.iter_mut().enumerate() {
+                    *len = match i {
+                        0..=143 => 8,
+                        144..=255 => 9,
+                        256..=279 => 7,
+                        _ => 8,
+                    };
+                }
+                let lit = Huffman::from_lengths(&lengths);
+                let dist = Huffman::from_lengths(&[5u8; 30]);
+                inflate_codes(&mut br, &mut out, &lit, &dist)?;
+            }
+            2 => {
+                let n_lit = br.bits(5)? as usize + 257;
+                let n_dist = br.bits(5)? as usize + 1;
+                let n_code = br.bits(4)? as usize + 4;
+                let mut code_lengths = [0u8; 19];
+                for &i in CODE_LENGTH_ORDER.iter().take(n_code) {
+                    code_lengths[i] = br.bits(3)? as u8;
+                }
+                let code = Huffman::from_lengths(&code_lengths);
+                let mut lengths: Vec<u8> = Vec::with_capacity(n_lit + n_dist);
+                while lengths.len() < n_lit + n_dist {
+                    let symbol = code.decode(&mut br)?;
+                    let (value, repeat) = match symbol {
+                        0..=15 => (symbol as u8, 1),
+                        16 => {
+                            let prior = *lengths
+                                .last()
+                                .ok_or_else(|| invalid("invalid deflate lengths"))?;
+                            (prior, 3 + br.bits(2)? as usize)
+                        }
+                        17 => (0, 3 + br.bits(3)? as usize),
+                        _ => (0, 11 + br.bits(7)? as usize),
+                    };
+                    lengths.resize(lengths.len() + repeat, value);
+                }
+                if lengths.len() > n_lit + n_dist {
+                    return Err(invalid("invalid deflate lengths"));
+                }
+                let lit = Huffman::from_lengths(&lengths[..n_lit]);
+                let dist = Huffman::from_lengths(&lengths[n_lit..]);
+                inflate_codes(&mut br, &mut out, &lit, &dist)?;
+            }
+            _ => return Err(invalid("invalid deflate block")),
+        }
+        if is_last {
+            return Ok(out);
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+#[derive(Debug, Clone)]
+struct ZipEntry {
+    name: String,
+    method: u16,
+    size_compressed: usize,
+    offset: usize,
+}
+
+/// The index of a zip archive, as read from its central directory, such that the names of entries can be listed and small entries, such as package metadata, can be read. Archives with a prefix, such as the shebang line of a zipapp, are supported; ZIP64 archives are not.
+pub(crate) struct ZipIndex {
+    path: PathBuf,
+    entries: Vec<ZipEntry>,
+}
+
+impl ZipIndex {
+    pub(crate) fn from_path(path: &Path) -> io::Result<Self> {
+        let data = fs::read(path)?;
+        Self::from_bytes(path, &data)
+    }
+
+    fn from_bytes(path: &Path, data: &[u8]) -> io::Result<Self> {
+        let search_start = data.len().saturating_sub(EOCD_SEARCH);
+        let eocd = (search_start..data.len().saturating_sub(21))
+            .rev()
+            .find(|&pos| to_u32(data, pos).is_ok_and(|sig| sig == SIG_EOCD))
+            .ok_or_else(|| invalid("not a zip archive"))?;
+        let count = to_u16(data, eocd + 10)? as usize;
+        let cd_size = to_u32(data, eocd + 12)? as usize;
+        let cd_offset = to_u32(data, eocd + 16)? as usize;
+        if cd_offset == u32::MAX as usize || count == u16::MAX as usize {
+            return Err(invalid("ZIP64 archives are not supported"));
+        }
+        // offsets are relative to the start of the archive, which might follow a prefix
+        let cd_start = eocd
+            .checked_sub(cd_size)
+            .ok_or_else(|| invalid("invalid central directory"))?;
+        let prefix = cd_start
+            .checked_sub(cd_offset)
+            .ok_or_else(|| invalid("invalid central directory"))?;
+
+        let mut entries = Vec::with_capacity(count);
+        let mut pos = cd_start;
+        for _ in 0..count {
+            if to_u32(data, pos)? != SIG_CENTRAL {
+                return Err(invalid("invalid central directory"));
+            }
+            let method = to_u16(data, pos + 10)?;
+            let size_compressed = to_u32(data, pos + 20)? as usize;
+            let len_name = to_u16(data, pos + 28)? as usize;
+            let len_extra = to_u16(data, pos + 30)? as usize;
+            let len_comment = to_u16(data, pos + 32)? as usize;
+            let offset = to_u32(data, pos + 42)? as usize + prefix;
+            let name = data
+                .get(pos + 46..pos + 46 + len_name)
+                .ok_or_else(|| invalid("truncated zip archive"))?;
+            entries.push(ZipEntry {
+                name: String::from_utf8_lossy(name).into_owned(),
+                method,
+                size_compressed,
+                offset,
+            });
+            pos += 46 + len_name + len_extra + len_comment;
+        }
+        Ok(ZipIndex {
+            path: path.to_path_buf(),
+            entries,
+        })
+    }
+
+    /// Return the names of all entries, as given in the archive; directories end with "/".
+    pub(crate) fn get_names(&self) -> Vec<&str> {
+        self.entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    /// Read an entry as a string. Only stored and deflated entries can be read.
+    pub(crate) fn read_to_string(&self, name: &str) -> io::Result<String> {
+        let entry = self
+            .entries
+            .iter()
+            .find(|e| e.name == name)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, name.to_string()))?;
+        let data = fs::read(&self.path)?;
+        if to_u32(&data, entry.offset)? != SIG_LOCAL {
+            return Err(invalid("invalid local header"));
+        }
+        let len_name = to_u16(&data, entry.offset + 26)? as usize;
+        let len_extra = to_u16(&data, entry.offset + 28)? as usize;
+        let start = entry.offset + 30 + len_name + len_extra;
+        let content = data
+            .get(start..start + entry.size_compressed)
+            .ok_or_else(|| invalid("truncated zip archive"))?;
+        let bytes = match entry.method {
+            0 => content.to_vec(),
+            8 => inflate(content)?,
+            _ => return Err(invalid("unsupported compression method")),
+        };
+        String::from_utf8(bytes).map_err(|_| invalid("entry is not UTF-8"))
+    }
+}
+
+/// Write a zip archive of uncompressed entries after a prefix, only for testing. CRC-32 values are not written, as they are not checked when reading.
+#[allow(dead_code)]
+pub(crate) fn write_zip_stored(
+    path: &Path,
+    prefix: &[u8],
+    entries: &[(&str, &str)],
+) -> io::Result<()> {
+    let mut data = prefix.to_vec();
+    let mut central = Vec::new();
+    for (name, content) in entries {
+        let offset = (data.len() - prefix.len()) as u32;
+        let size = content.len() as u32;
+        data.extend(SIG_LOCAL.to_le_bytes());
+        data.extend([20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        data.extend(size.to_le_bytes());
+        data.extend(size.to_le_bytes());
+        data.extend((name.len() as u16).to_le_bytes());
+        data.extend([0, 0]);
+        data.extend(name.as_bytes());
+        data.extend(content.as_bytes());
+        central.extend(SIG_CENTRAL.to_le_bytes());
+        central.extend([20, 0, 20, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        central.extend(size.to_le_bytes());
+        central.extend(size.to_le_bytes());
+        central.extend((name.len() as u16).to_le_bytes());
+        central.extend([0; 12]);
+        central.extend(offset.to_le_bytes());
+        central.extend(name.as_bytes());
+    }
+    let cd_offset = (data.len() - prefix.len()) as u32;
+    let cd_size = central.len() as u32;
+    data.extend(central);
+    data.extend(SIG_EOCD.to_le_bytes());
+    data.extend([0, 0, 0, 0]);
+    data.extend((entries.len() as u16).to_le_bytes());
+    data.extend((entries.len() as u16).to_le_bytes());
+    data.extend(cd_size.to_le_bytes());
+    data.extend(cd_offset.to_le_bytes());
+    data.extend([0, 0]);
+    fs::write(path, data)
+}
+
+//------------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    // A zip archive, as written by Python's `zipfile` with ZIP_DEFLATED, of `six-1.16.0.dist-info/METADATA` and `six.py`.
+    const ZIP_DEFLATED: &[u8] = &[
+        80, 75, 3, 4, 20, 0, 0, 0, 8, 0, 0, 0, 33, 88, 95, 122, 80, 12, 88, 0, 0, 0, 96,
+        0, 0, 0, 29, 0, 0, 0, 115, 105, 120, 45, 49, 46, 49, 54, 46, 48, 46, 100, 105,
+        115, 116, 45, 105, 110, 102, 111, 47, 77, 69, 84, 65, 68, 65, 84, 65, 243, 77,
+        45, 73, 76, 73, 44, 73, 212, 13, 75, 45, 42, 206, 204, 207, 179, 82, 48, 210, 51,
+        228, 242, 75, 204, 77, 181, 82, 40, 206, 172, 224, 130, 11, 27, 234, 25, 154,
+        233, 25, 112, 5, 151, 230, 230, 38, 22, 85, 90, 41, 4, 84, 150, 100, 228, 231,
+        41, 24, 41, 36, 230, 165, 40, 24, 43, 36, 231, 231, 22, 36, 150, 100, 38, 101,
+        230, 100, 150, 84, 42, 148, 150, 128, 232, 204, 212, 98, 46, 0, 80, 75, 3, 4, 20,
+        0, 0, 0, 8, 0, 0, 0, 33, 88, 228, 196, 56, 94, 18, 0, 0, 0, 184, 1, 0, 0, 6, 0,
+        0, 0, 115, 105, 120, 46, 112, 121, 203, 204, 45, 200, 47, 42, 81, 40, 174, 44,
+        230, 202, 28, 101, 14, 29, 38, 0, 80, 75, 1, 2, 20, 3, 20, 0, 0, 0, 8, 0, 0, 0,
+        33, 88, 95, 122, 80, 12, 88, 0, 0, 0, 96, 0, 0, 0, 29, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 128, 1, 0, 0, 0, 0, 115, 105, 120, 45, 49, 46, 49, 54, 46, 48, 46, 100,
+        105, 115, 116, 45, 105, 110, 102, 111, 47, 77, 69, 84, 65, 68, 65, 84, 65, 80,
+        75, 1, 2, 20, 3, 20, 0, 0, 0, 8, 0, 0, 0, 33, 88, 228, 196, 56, 94, 18, 0, 0, 0,
+        184, 1, 0, 0, 6, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 128, 1, 147, 0, 0, 0, 115, 105,
+        120, 46, 112, 121, 80, 75, 5, 6, 0, 0, 0, 0, 2, 0, 2, 0, 127, 0, 0, 0, 201, 0, 0,
+        0, 0, 0,
+    ];
+
+    #[test]
+    fn test_zip_index_a() {
+        let dir = tempdir().unwrap();
+        let fp = dir.path().join("app.pyz");
+        write_zip_stored(
+            &fp,
+            b"#!/usr/bin/env python3\n",
+            &[
+                ("__main__.py", "import six\n"),
+                (
+                    "six-1.16.0.dist-info/METADATA",
+                    "Metadata-Version: 2.1\nName: six\nVersion: 1.16.0\n",
+                ),
+            ],
+        )
+        .unwrap();
+        assert!(is_zip_archive(&fp));
+        let zi = ZipIndex::from_path(&fp).unwrap();
+        assert_eq!(
+            zi.get_names(),
+            vec!["__main__.py", "six-1.16.0.dist-info/METADATA"]
+        );
+        assert_eq!(
+            zi.read_to_string("six-1.16.0.dist-info/METADATA").unwrap(),
+            "Metadata-Version: 2.1\nName: six\nVersion: 1.16.0\n"
+        );
+        assert!(zi.read_to_string("missing.txt").is_err());
+    }
+
+    #[test]
+    fn test_zip_index_b() {
+        let dir = tempdir().unwrap();
+        let fp = dir.path().join("six.zip");
+        fs::write(&fp, ZIP_DEFLATED).unwrap();
+        let zi = ZipIndex::from_path(&fp).unwrap();
+        assert_eq!(
+            zi.get_names(),
+            vec!["six-1.16.0.dist-info/METADATA", "six.py"]
+        );
+        assert_eq!(
+            zi.read_to_string("six-1.16.0.dist-info/METADATA").unwrap(),
+            "Metadata-Version: 2.1\nName: six\nVersion: 1.16.0\nSummary: Python 2 and 3 compatibility utilities\n"
+        );
+        assert_eq!(
+            zi.read_to_string("six.py").unwrap(),
+            "import sys\n".repeat(40)
+        );
+    }
+
+    #[test]
+    fn test_zip_index_c() {
+        let dir = tempdir().unwrap();
+        let fp = dir.path().join("foo.pyz");
+        fs::write(&fp, "not a zip").unwrap();
+        assert!(ZipIndex::from_path(&fp).is_err());
+        assert!(!is_zip_archive(&dir.path().join("missing.pyz")));
+    }
+}